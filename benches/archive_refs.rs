@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use once_cell::sync::Lazy;
+use rscache::Cache;
+
+const CACHE_PATH: &str = "./data/osrs_cache";
+
+static CACHE: Lazy<Cache> = Lazy::new(|| Cache::new(CACHE_PATH).unwrap());
+static HASHED: Lazy<runefs::Indices> = Lazy::new(|| runefs::Indices::new(CACHE_PATH).unwrap());
+
+// The map index (5) is sparse, the others are dense.
+const INDICES: [u8; 3] = [2, 5, 7];
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("archive_ref_lookup");
+
+    for index_id in INDICES {
+        let count = HASHED.get(&index_id).unwrap().archive_refs.len() as u32;
+
+        group.bench_function(format!("hash_map_idx{}", index_id), |b| {
+            b.iter(|| {
+                for archive_id in 0..count {
+                    black_box(
+                        HASHED
+                            .get(&black_box(index_id))
+                            .and_then(|index| index.archive_refs.get(&black_box(archive_id)))
+                            .filter(|archive| archive.length != 0),
+                    );
+                }
+            })
+        });
+        group.bench_function(format!("slice_idx{}", index_id), |b| {
+            b.iter(|| {
+                for archive_id in 0..count {
+                    let _ = black_box(CACHE.archive_ref(black_box(index_id), black_box(archive_id)));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
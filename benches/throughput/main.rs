@@ -0,0 +1,123 @@
+//! Throughput benchmarks against the generated test fixture, no real cache needed.
+//!
+//! Performance changes should cite these numbers. Record a baseline on the
+//! target branch, then compare the change against it on the same machine:
+//!
+//! ```text
+//! git checkout master && cargo bench --bench throughput -- --save-baseline master
+//! git checkout my-change && cargo bench --bench throughput -- --baseline master
+//! ```
+//!
+//! | Benchmark                  | Measures                                                  |
+//! |----------------------------|-----------------------------------------------------------|
+//! | `cache/open`               | `Cache::new`, parsing every idx file and reference table  |
+//! | `read/sequential`          | reading and decoding every archive of index 0 in id order |
+//! | `read/random`              | the same archives in a fixed random order                 |
+//! | `read/raw`                 | reading the raw containers of index 0, without decoding   |
+//! | `read/small_raw`           | raw reads of the single sector archives of index 0        |
+//! | `read/small_ref`           | the same archives through `Cache::read_ref`, borrowed     |
+//! | `checksum`                 | `Cache::checksum` over every index                        |
+//! | `checksum/persisted`       | `Checksum::load_or_create` with an up to date file        |
+//! | `loaders/configs`          | constructing the item, npc and object loaders             |
+//! | `loaders/configs_serial`   | parsing the same definitions without the loaders          |
+//! | `loaders/region`           | loading a region's map and XTEA encrypted locations       |
+//!
+//! With the `rayon` feature the loaders parse in parallel, `loaders/configs`
+//! against `loaders/configs_serial` of a `--features rayon` run shows the gain.
+//!
+//! The `safe-io` store is compared against the memory map the same way:
+//!
+//! ```text
+//! cargo bench --bench throughput -- --save-baseline mmap
+//! cargo bench --bench throughput --no-default-features --features safe-io -- --baseline mmap
+//! ```
+//!
+//! The workloads live in `workloads.rs`, `tests/bench_smoke.rs` runs each of
+//! them once as part of `cargo test`.
+
+#[allow(dead_code)]
+#[path = "../../tests/test_util/fixture.rs"]
+mod fixture;
+mod workloads;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use once_cell::sync::Lazy;
+use rscache::Cache;
+
+use fixture::Fixture;
+use workloads::READ_INDEX;
+
+static FIXTURE: Lazy<Fixture> = Lazy::new(workloads::fixture);
+static CACHE: Lazy<Cache> = Lazy::new(|| FIXTURE.cache());
+
+fn cache(c: &mut Criterion) {
+    c.bench_function("cache/open", |b| {
+        b.iter(|| workloads::open(black_box(FIXTURE.path())).unwrap())
+    });
+}
+
+fn read(c: &mut Criterion) {
+    let sequential = workloads::archive_ids(&CACHE, READ_INDEX).unwrap();
+    let random = workloads::shuffled_archive_ids(&CACHE, READ_INDEX).unwrap();
+    let len = workloads::read_archives(&CACHE, READ_INDEX, &sequential).unwrap();
+
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Bytes(len as u64));
+    group.bench_function("sequential", |b| {
+        b.iter(|| workloads::read_archives(&CACHE, READ_INDEX, black_box(&sequential)).unwrap())
+    });
+    group.bench_function("random", |b| {
+        b.iter(|| workloads::read_archives(&CACHE, READ_INDEX, black_box(&random)).unwrap())
+    });
+    group.finish();
+
+    let raw_len = workloads::read_raw_archives(&CACHE, READ_INDEX, &sequential).unwrap();
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Bytes(raw_len as u64));
+    group.bench_function("raw", |b| {
+        b.iter(|| workloads::read_raw_archives(&CACHE, READ_INDEX, black_box(&sequential)).unwrap())
+    });
+    group.finish();
+
+    let small = workloads::single_sector_ids(&CACHE, READ_INDEX, &sequential).unwrap();
+    let (small_len, _) = workloads::read_ref_archives(&CACHE, READ_INDEX, &small).unwrap();
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Bytes(small_len as u64));
+    group.bench_function("small_raw", |b| {
+        b.iter(|| workloads::read_raw_archives(&CACHE, READ_INDEX, black_box(&small)).unwrap())
+    });
+    group.bench_function("small_ref", |b| {
+        b.iter(|| workloads::read_ref_archives(&CACHE, READ_INDEX, black_box(&small)).unwrap())
+    });
+    group.finish();
+}
+
+fn checksum(c: &mut Criterion) {
+    c.bench_function("checksum", |b| {
+        b.iter(|| workloads::checksum(black_box(&CACHE)).unwrap())
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("checksum.bin");
+    workloads::persisted_checksum(&CACHE, &path).unwrap();
+    c.bench_function("checksum/persisted", |b| {
+        b.iter(|| workloads::persisted_checksum(black_box(&CACHE), &path).unwrap())
+    });
+}
+
+fn loaders(c: &mut Criterion) {
+    let mut group = c.benchmark_group("loaders");
+    group.bench_function("configs", |b| {
+        b.iter(|| workloads::configs(black_box(&CACHE)).unwrap())
+    });
+    group.bench_function("configs_serial", |b| {
+        b.iter(|| workloads::configs_serial(black_box(&CACHE)).unwrap())
+    });
+    group.bench_function("region", |b| {
+        b.iter(|| workloads::region(black_box(&CACHE)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, cache, read, checksum, loaders);
+criterion_main!(benches);
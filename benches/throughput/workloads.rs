@@ -0,0 +1,151 @@
+//! Workloads measured by the throughput benchmarks.
+//!
+//! Shared with `tests/bench_smoke.rs`, which runs every workload once against
+//! the same fixture so the benchmarks keep compiling and keep doing real work.
+
+use std::{borrow::Cow, path::Path};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use rscache::{
+    checksum::Checksum,
+    definition::{
+        config_archives::{CONFIG_INDEX, ITEMS, NPCS, OBJECTS},
+        osrs::{FetchDefinition, ItemDefinition, NpcDefinition, ObjectDefinition},
+    },
+    loader::osrs::{ItemLoader, LocationLoader, MapLoader, NpcLoader, ObjectLoader},
+    reference_table::ReferenceTable,
+    Cache,
+};
+use runefs::codec::Compression;
+
+use crate::fixture::{self, ArchiveSpec, Fixture, LOCATION_KEYS, REGION_ID};
+
+/// Index filled with extra archives for the read benchmarks.
+pub const READ_INDEX: u8 = 0;
+/// Number of extra archives added to [`READ_INDEX`].
+pub const READ_ARCHIVES: u32 = 256;
+
+/// The default fixture with [`READ_ARCHIVES`] extra archives in [`READ_INDEX`].
+///
+/// Archives span one to eight sectors and cycle through every compression, so
+/// reads cover short and long sector chains as well as both decompressors.
+pub fn fixture() -> Fixture {
+    let compressions = [Compression::None, Compression::Bzip2, Compression::Gzip];
+
+    (0..READ_ARCHIVES)
+        .fold(fixture::defaults(), |builder, archive| {
+            let id = archive + 3;
+            let len = 512 * (archive as usize % 8) + 100;
+            builder.archive(
+                READ_INDEX,
+                ArchiveSpec::new(id, vec![fixture::pattern(len)])
+                    .compression(compressions[archive as usize % compressions.len()]),
+            )
+        })
+        .build()
+}
+
+/// Opens the cache, parsing every idx file and reference table.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Cache, rscache::Error> {
+    Cache::new(path)
+}
+
+/// Ids of every archive listed in the index's reference table.
+pub fn archive_ids(cache: &Cache, index_id: u8) -> Result<Vec<u32>, rscache::Error> {
+    let table = ReferenceTable::parse(&cache.read_decoded(255, index_id.into())?)?;
+
+    Ok(table.archives.iter().map(|archive| archive.id).collect())
+}
+
+/// [`archive_ids`] in a fixed random order.
+pub fn shuffled_archive_ids(cache: &Cache, index_id: u8) -> Result<Vec<u32>, rscache::Error> {
+    let mut ids = archive_ids(cache, index_id)?;
+    ids.shuffle(&mut StdRng::seed_from_u64(0x5EED));
+
+    Ok(ids)
+}
+
+/// Reads and decodes the given archives in order, returning the decoded length.
+pub fn read_archives(cache: &Cache, index_id: u8, ids: &[u32]) -> Result<usize, rscache::Error> {
+    let mut len = 0;
+    for &archive_id in ids {
+        len += cache.read_decoded(index_id, archive_id)?.len();
+    }
+
+    Ok(len)
+}
+
+/// Reads the raw containers of the given archives in order, returning their length.
+pub fn read_raw_archives(cache: &Cache, index_id: u8, ids: &[u32]) -> Result<usize, rscache::Error> {
+    let mut len = 0;
+    for &archive_id in ids {
+        len += cache.read(index_id, archive_id)?.len();
+    }
+
+    Ok(len)
+}
+
+/// The archives of `ids` stored in a single sector.
+pub fn single_sector_ids(cache: &Cache, index_id: u8, ids: &[u32]) -> Result<Vec<u32>, rscache::Error> {
+    let mut single = Vec::new();
+    for &archive_id in ids {
+        if cache.archive_placement(index_id, archive_id)?.sectors.len() == 1 {
+            single.push(archive_id);
+        }
+    }
+
+    Ok(single)
+}
+
+/// Reads the given archives through [`Cache::read_ref`] in order, returning
+/// their length and how many were borrowed.
+pub fn read_ref_archives(cache: &Cache, index_id: u8, ids: &[u32]) -> Result<(usize, usize), rscache::Error> {
+    let mut len = 0;
+    let mut borrowed = 0;
+    for &archive_id in ids {
+        let buffer = cache.read_ref(index_id, archive_id)?;
+        len += buffer.len();
+        borrowed += matches!(buffer, Cow::Borrowed(_)) as usize;
+    }
+
+    Ok((len, borrowed))
+}
+
+/// Builds the update protocol checksum of every index.
+pub fn checksum(cache: &Cache) -> Result<Checksum, rscache::Error> {
+    cache.checksum()
+}
+
+/// Loads the checksum persisted at `path`, creating it on the first call.
+pub fn persisted_checksum(cache: &Cache, path: &Path) -> Result<Checksum, rscache::Error> {
+    Checksum::load_or_create(cache, path)
+}
+
+/// Constructs the item, npc and object loaders.
+pub fn configs(cache: &Cache) -> Result<(ItemLoader, NpcLoader, ObjectLoader), rscache::Error> {
+    Ok((
+        ItemLoader::new(cache)?,
+        NpcLoader::new(cache)?,
+        ObjectLoader::new(cache)?,
+    ))
+}
+
+/// Parses the item, npc and object definitions serially, the way the loaders
+/// do without the `rayon` feature.
+pub fn configs_serial(cache: &Cache) -> Result<usize, rscache::Error> {
+    let items = ItemDefinition::fetch_from_archive::<ItemDefinition>(cache, CONFIG_INDEX, ITEMS)?;
+    let npcs = NpcDefinition::fetch_from_archive::<NpcDefinition>(cache, CONFIG_INDEX, NPCS)?;
+    let objects =
+        ObjectDefinition::fetch_from_archive::<ObjectDefinition>(cache, CONFIG_INDEX, OBJECTS)?;
+
+    Ok(items.len() + npcs.len() + objects.len())
+}
+
+/// Loads the fixture region's map and locations with fresh loaders, returning
+/// the number of locations.
+pub fn region(cache: &Cache) -> Result<usize, rscache::Error> {
+    MapLoader::new(cache).load(REGION_ID)?;
+    let locations = LocationLoader::new(cache).load(REGION_ID, &LOCATION_KEYS)?.data.len();
+
+    Ok(locations)
+}
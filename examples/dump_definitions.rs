@@ -0,0 +1,39 @@
+use std::io::{self, Write};
+
+use rscache::{
+    definition::{osrs, DefinitionKind},
+    util, Cache,
+};
+
+// This example dumps every definition of a config type as JSON, one per line.
+// Every kind goes through the same code, the definitions are only handled
+// as `dyn Definition`.
+//
+// cargo run --example dump_definitions --features serde -- npc
+fn main() -> Result<(), rscache::Error> {
+    let cache = Cache::new("./data/osrs_cache")?;
+
+    let name = std::env::args().nth(1).unwrap_or_else(|| "item".to_owned());
+    let kind = DefinitionKind::ALL
+        .into_iter()
+        .find(|kind| kind.config_archive().name() == name)
+        .unwrap_or_else(|| panic!("unknown definition kind {}", name));
+    let archive_id = kind.config_archive().archive_id();
+
+    let file_ids = cache.archive_metadata(2, archive_id)?.file_ids;
+    let buffer = cache.read_decoded(2, archive_id)?;
+    let files = util::split_group(&buffer, file_ids.len())?;
+
+    let mut stdout = io::stdout().lock();
+    for (&id, data) in file_ids.iter().zip(&files) {
+        let definition = osrs::parse_any(kind, id, data)?;
+        // skip the unnamed filler definitions.
+        if definition.name().is_none() {
+            continue;
+        }
+
+        writeln!(stdout, "{}", definition.to_json().map_err(io::Error::from)?)?;
+    }
+
+    Ok(())
+}
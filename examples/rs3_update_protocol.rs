@@ -3,8 +3,8 @@ use rscache::{
     Cache,
 };
 
-pub const EXPONENT: &'static [u8] = b"5206580307236375668350588432916871591810765290737810323990754121164270399789630501436083337726278206128394461017374810549461689174118305784406140446740993";
-pub const MODULUS: &'static [u8] = b"6950273013450460376345707589939362735767433035117300645755821424559380572176824658371246045200577956729474374073582306250298535718024104420271215590565201";
+pub const EXPONENT: &[u8] = b"5206580307236375668350588432916871591810765290737810323990754121164270399789630501436083337726278206128394461017374810549461689174118305784406140446740993";
+pub const MODULUS: &[u8] = b"6950273013450460376345707589939362735767433035117300645755821424559380572176824658371246045200577956729474374073582306250298535718024104420271215590565201";
 
 struct IncomingUpdatePacket {
     pub index_id: u8,
@@ -33,7 +33,7 @@ fn main() -> Result<(), rscache::Error> {
             index_id,
             archive_id,
             ..
-        } => cache.read(index_id, archive_id as u32).map(|mut buffer| {
+        } => cache.read(index_id, archive_id).map(|mut buffer| {
             if index_id != 255 {
                 let len = buffer.len();
                 buffer.truncate(len - 2);
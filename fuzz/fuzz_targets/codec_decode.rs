@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rscache::{codec, util};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(buffer) = codec::decode(data) {
+        let file_count = data.first().map_or(0, |&count| count as usize);
+        let _ = util::split_group(&buffer, file_count);
+    }
+});
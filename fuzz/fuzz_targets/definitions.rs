@@ -0,0 +1,52 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rscache::{
+    definition::{
+        osrs::{
+            AreaDefinition, ComponentDefinition, DbRowDefinition, DbTableDefinition, Definition,
+            EnumDefinition, HealthBarDefinition, HitSplatDefinition, IdentityKitDefinition,
+            InventoryDefinition, ItemDefinition, LocationDefinition, MapDefinition,
+            ModelDefinition, NpcDefinition, ObjectDefinition, OverlayDefinition, ParamDefinition,
+            SequenceDefinition, SpotAnimDefinition, SpriteDefinition, StructDefinition,
+            UnderlayDefinition, VarbitDefinition, WorldMapDefinition,
+        },
+        rs3,
+    },
+    util::Huffman,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ItemDefinition::new(0, data);
+    let _ = NpcDefinition::new(0, data);
+    let _ = ObjectDefinition::new(0, data);
+    let _ = MapDefinition::new(12850, data);
+    let _ = LocationDefinition::new(12850, data);
+    let _ = ComponentDefinition::new(0, data);
+    let _ = EnumDefinition::new(0, data);
+    let _ = VarbitDefinition::new(0, data);
+    let _ = StructDefinition::new(0, data);
+    let _ = ParamDefinition::new(0, data);
+    let _ = UnderlayDefinition::new(0, data);
+    let _ = OverlayDefinition::new(0, data);
+    let _ = AreaDefinition::new(0, data);
+    let _ = SequenceDefinition::new(0, data);
+    let _ = SpotAnimDefinition::new(0, data);
+    let _ = InventoryDefinition::new(0, data);
+    let _ = IdentityKitDefinition::new(0, data);
+    let _ = HitSplatDefinition::new(0, data);
+    let _ = HealthBarDefinition::new(0, data);
+    let _ = DbTableDefinition::new(0, data);
+    let _ = DbRowDefinition::new(0, data);
+    let _ = WorldMapDefinition::new(0, data);
+    let _ = SpriteDefinition::decode(0, data);
+    let _ = ModelDefinition::new(0, data);
+    let _ = <rs3::ItemDefinition as rs3::Definition>::new(0, data);
+
+    if let Some((&len, data)) = data.split_first() {
+        let (sizes, compressed) = data.split_at(data.len() / 2);
+        if let Ok(huffman) = Huffman::new(sizes) {
+            let _ = huffman.decompress(compressed, len as usize);
+        }
+    }
+});
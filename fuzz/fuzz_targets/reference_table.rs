@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rscache::reference_table::ReferenceTable;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ReferenceTable::parse(data);
+});
@@ -0,0 +1,357 @@
+//! Standalone bundles holding a single index.
+//!
+//! A bundle carries the index's reference table and the raw containers of its
+//! archives, exactly as stored in `main_file_cache.dat2`, so an index (e.g.
+//! just the maps) can be shared without the rest of the cache.
+//!
+//! # Format
+//!
+//! All integers are big endian.
+//!
+//! | Field             | Size        | Description                                |
+//! |-------------------|-------------|--------------------------------------------|
+//! | magic             | 4           | `RSIB`                                     |
+//! | version           | 1           | [`BUNDLE_VERSION`]                         |
+//! | index id          | 1           |                                            |
+//! | table length      | 4           |                                            |
+//! | reference table   | table length | container of the index's reference table  |
+//! | archive count     | 4           |                                            |
+//!
+//! Followed by every archive, in ascending id order:
+//!
+//! | Field             | Size        | Description                                |
+//! |-------------------|-------------|--------------------------------------------|
+//! | archive id        | 4           |                                            |
+//! | length            | 4           |                                            |
+//! | container         | length      | raw archive container                      |
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::bundle::IndexBundle;
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! let mut buffer = Vec::new();
+//! cache.export_index(10, &mut buffer)?;
+//!
+//! let bundle = IndexBundle::read_from(buffer.as_slice())?;
+//! assert_eq!(bundle.index_id, 10);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use nom::{
+    bytes::complete::{tag, take},
+    multi::count,
+    number::complete::{be_u32, be_u8},
+    IResult,
+};
+use runefs::{ARCHIVE_REF_LEN, IDX_PREFIX, MAIN_DATA, REFERENCE_TABLE_ID};
+
+use crate::{codec, error::BundleError, reference_table::ReferenceTable};
+
+/// Format version written by [`IndexBundle::write_to`].
+pub const BUNDLE_VERSION: u8 = 1;
+
+const MAGIC: &[u8; 4] = b"RSIB";
+const SECTOR_SIZE: usize = 520;
+
+/// A single index with its reference table, see the [module documentation](self).
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct IndexBundle {
+    pub index_id: u8,
+    /// Container of the reference table, as stored in index 255.
+    pub reference_table: Vec<u8>,
+    /// Archives in ascending id order.
+    pub archives: Vec<BundledArchive>,
+}
+
+/// Raw container of a single archive in an [`IndexBundle`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct BundledArchive {
+    pub id: u32,
+    pub container: Vec<u8>,
+}
+
+impl IndexBundle {
+    /// Reads and validates a bundle.
+    ///
+    /// Every archive must be listed in the bundled reference table with a
+    /// matching crc.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BundleError`] when the header is invalid or an archive fails
+    /// validation, and a parser error when the bundle is truncated.
+    pub fn read_from<R: Read>(mut reader: R) -> crate::Result<Self> {
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+
+        let (buffer, _) = tag::<_, _, ()>(&MAGIC[..])(buffer.as_slice())
+            .map_err(|_| BundleError::InvalidMagic)?;
+        let (buffer, version) = be_u8(buffer)?;
+        if version != BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion(version).into());
+        }
+        let (buffer, index_id) = be_u8(buffer)?;
+        let (buffer, reference_table) = sized(buffer)?;
+        let (buffer, archive_count) = be_u32(buffer)?;
+        let (_, archives) = count(archive, archive_count as usize)(buffer)?;
+
+        let bundle = Self {
+            index_id,
+            reference_table: reference_table.to_vec(),
+            archives,
+        };
+        bundle.validate()?;
+
+        Ok(bundle)
+    }
+
+    /// Writes the bundle in the current [format version](BUNDLE_VERSION).
+    ///
+    /// # Errors
+    ///
+    /// Only fails when writing to `W` fails.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> crate::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[BUNDLE_VERSION, self.index_id])?;
+        writer.write_all(&(self.reference_table.len() as u32).to_be_bytes())?;
+        writer.write_all(&self.reference_table)?;
+        writer.write_all(&(self.archives.len() as u32).to_be_bytes())?;
+        for archive in &self.archives {
+            writer.write_all(&archive.id.to_be_bytes())?;
+            writer.write_all(&(archive.container.len() as u32).to_be_bytes())?;
+            writer.write_all(&archive.container)?;
+        }
+
+        Ok(())
+    }
+
+    /// Parsed reference table of the bundled index.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the reference table container can't be decoded or parsed.
+    pub fn parse_reference_table(&self) -> crate::Result<ReferenceTable> {
+        ReferenceTable::parse(&codec::decode(&self.reference_table)?)
+    }
+
+    fn validate(&self) -> crate::Result<()> {
+        let table = self.parse_reference_table()?;
+
+        for archive in &self.archives {
+            let entry = table
+                .archive(archive.id)
+                .ok_or(BundleError::UnknownArchive(archive.id))?;
+            // the version trailer isn't part of the crc.
+            let (body, _) = codec::split_trailer(&archive.container);
+            let crc = crc32fast::hash(body);
+
+            if crc != entry.crc {
+                return Err(BundleError::InvalidCrc {
+                    arc: archive.id,
+                    expected: entry.crc,
+                    actual: crc,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Installs the index into the cache directory at `path`, overwriting
+    /// the existing index and its reference table.
+    ///
+    /// Archives are appended to `main_file_cache.dat2`, sectors of the old
+    /// index are left in place but are no longer referenced. An already open
+    /// [`Cache`](crate::Cache) has to be [reloaded](crate::Cache::reload) to
+    /// see the new index.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the cache files can't be read or written.
+    pub fn install<P: AsRef<Path>>(&self, path: P) -> crate::Result<()> {
+        let path = path.as_ref();
+        let mut dat2 = OpenOptions::new().write(true).open(path.join(MAIN_DATA))?;
+        let len = dat2.seek(SeekFrom::End(0))? as usize;
+        // new chains start on a sector boundary, the first sector is never used.
+        let start = len.div_ceil(SECTOR_SIZE).max(1);
+        let mut sector = start;
+
+        let mut data = Vec::new();
+        let mut idx = Vec::new();
+        for archive in &self.archives {
+            let entry = sectors(
+                &mut data,
+                &mut sector,
+                self.index_id,
+                archive.id,
+                &archive.container,
+            );
+            put_entry(&mut idx, archive.id, entry);
+        }
+        let table_entry = sectors(
+            &mut data,
+            &mut sector,
+            REFERENCE_TABLE_ID,
+            self.index_id as u32,
+            &self.reference_table,
+        );
+
+        dat2.set_len((start * SECTOR_SIZE) as u64)?;
+        dat2.seek(SeekFrom::Start((start * SECTOR_SIZE) as u64))?;
+        dat2.write_all(&data)?;
+
+        let reference_path = path.join(format!("{}{}", IDX_PREFIX, REFERENCE_TABLE_ID));
+        let mut reference_idx = fs::read(&reference_path)?;
+        put_entry(&mut reference_idx, self.index_id as u32, table_entry);
+
+        fs::write(path.join(format!("{}{}", IDX_PREFIX, self.index_id)), idx)?;
+        fs::write(reference_path, reference_idx)?;
+
+        Ok(())
+    }
+}
+
+/// Reads and validates a bundle, then [installs](IndexBundle::install) it into
+/// the cache directory at `path`.
+///
+/// # Errors
+///
+/// See [`IndexBundle::read_from`] and [`IndexBundle::install`].
+pub fn import_index<P: AsRef<Path>, R: Read>(path: P, reader: R) -> crate::Result<()> {
+    IndexBundle::read_from(reader)?.install(path)
+}
+
+fn sized(buffer: &[u8]) -> IResult<&[u8], &[u8], ()> {
+    let (buffer, len) = be_u32(buffer)?;
+
+    take(len)(buffer)
+}
+
+fn archive(buffer: &[u8]) -> IResult<&[u8], BundledArchive, ()> {
+    let (buffer, id) = be_u32(buffer)?;
+    let (buffer, container) = sized(buffer)?;
+
+    Ok((
+        buffer,
+        BundledArchive {
+            id,
+            container: container.to_vec(),
+        },
+    ))
+}
+
+/// Lays out the data as a chain of sectors starting at `sector`, returning its idx entry.
+pub(crate) fn sectors(
+    dat2: &mut Vec<u8>,
+    sector: &mut usize,
+    index_id: u8,
+    archive_id: u32,
+    data: &[u8],
+) -> [u8; ARCHIVE_REF_LEN] {
+    let first = *sector;
+
+    let chunks = data.chunks(data_len(archive_id));
+    let last = chunks.len().saturating_sub(1);
+    for (chunk, block) in chunks.enumerate() {
+        let next = if chunk == last { 0 } else { *sector + 1 };
+        put_sector(dat2, index_id, archive_id, chunk, next, block);
+        *sector += 1;
+    }
+
+    archive_entry(data.len(), first)
+}
+
+/// Writes the data as a chain of sectors into an open data file, returning its idx entry.
+///
+/// The chain takes over the `reuse` sectors in order when it fits into them,
+/// otherwise it is appended to the file.
+pub(crate) fn write_chain(
+    dat2: &mut File,
+    reuse: &[usize],
+    index_id: u8,
+    archive_id: u32,
+    data: &[u8],
+) -> crate::Result<[u8; ARCHIVE_REF_LEN]> {
+    let mut blocks: Vec<_> = data.chunks(data_len(archive_id)).collect();
+    if blocks.is_empty() {
+        blocks.push(&[]);
+    }
+
+    let chain: Vec<usize> = if blocks.len() <= reuse.len() {
+        reuse[..blocks.len()].to_vec()
+    } else {
+        let len = dat2.seek(SeekFrom::End(0))? as usize;
+        // new chains start on a sector boundary, the first sector is never used.
+        let start = len.div_ceil(SECTOR_SIZE).max(1);
+        (start..start + blocks.len()).collect()
+    };
+
+    let mut buffer = Vec::with_capacity(SECTOR_SIZE);
+    for (chunk, (&sector, block)) in chain.iter().zip(blocks).enumerate() {
+        let next = chain.get(chunk + 1).copied().unwrap_or_default();
+        buffer.clear();
+        put_sector(&mut buffer, index_id, archive_id, chunk, next, block);
+        dat2.seek(SeekFrom::Start((sector * SECTOR_SIZE) as u64))?;
+        dat2.write_all(&buffer)?;
+    }
+
+    Ok(archive_entry(data.len(), chain[0]))
+}
+
+/// Data bytes per sector, archives above `u16::MAX` have an expanded header.
+fn data_len(archive_id: u32) -> usize {
+    if archive_id > u16::MAX as u32 {
+        510
+    } else {
+        512
+    }
+}
+
+/// Appends a single sector of a chain, its block padded with zeros.
+fn put_sector(
+    dat2: &mut Vec<u8>,
+    index_id: u8,
+    archive_id: u32,
+    chunk: usize,
+    next: usize,
+    block: &[u8],
+) {
+    let start = dat2.len();
+    if archive_id > u16::MAX as u32 {
+        dat2.extend(archive_id.to_be_bytes());
+    } else {
+        dat2.extend((archive_id as u16).to_be_bytes());
+    }
+    dat2.extend((chunk as u16).to_be_bytes());
+    dat2.extend(&(next as u32).to_be_bytes()[1..]);
+    dat2.push(index_id);
+    dat2.extend(block);
+    dat2.resize(start + SECTOR_SIZE, 0);
+}
+
+fn archive_entry(len: usize, sector: usize) -> [u8; ARCHIVE_REF_LEN] {
+    let mut entry = [0; ARCHIVE_REF_LEN];
+    entry[..3].copy_from_slice(&(len as u32).to_be_bytes()[1..]);
+    entry[3..].copy_from_slice(&(sector as u32).to_be_bytes()[1..]);
+    entry
+}
+
+pub(crate) fn put_entry(idx: &mut Vec<u8>, archive_id: u32, entry: [u8; ARCHIVE_REF_LEN]) {
+    let offset = archive_id as usize * ARCHIVE_REF_LEN;
+    if idx.len() < offset + ARCHIVE_REF_LEN {
+        idx.resize(offset + ARCHIVE_REF_LEN, 0);
+    }
+    idx[offset..offset + ARCHIVE_REF_LEN].copy_from_slice(&entry);
+}
@@ -0,0 +1,322 @@
+//! Content-addressed backups of cache revisions.
+//!
+//! Consecutive revisions share most of their archives. [`export`] stores every
+//! raw container once under its sha-256 in an object store, and returns a
+//! [`Manifest`] listing which object every archive of the revision uses.
+//! Exporting many revisions into the same store only adds the containers that
+//! changed. [`restore`] turns a manifest back into a cache whose checksum
+//! matches the exported one.
+//!
+//! Objects live at `objects/<first two hex digits>/<hex digest>` in the store.
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::cas;
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! # let (store, out) = (tempfile::tempdir()?, tempfile::tempdir()?);
+//! let manifest = cas::export(&cache, store.path())?;
+//! cas::restore(&manifest, store.path(), out.path())?;
+//!
+//! assert_eq!(Cache::new(out.path())?.checksum()?, cache.checksum()?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use runefs::{IDX_PREFIX, MAIN_DATA, REFERENCE_TABLE_ID, SECTOR_SIZE};
+use sha2::{Digest, Sha256};
+
+use crate::{bundle, Cache};
+
+/// Format version written by [`Manifest::write_to`].
+pub const MANIFEST_VERSION: u8 = 1;
+
+const MANIFEST_HEADER: &str = "rs-cache manifest";
+
+/// Sha-256 of a raw archive container.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct ContentHash(pub [u8; 32]);
+
+impl ContentHash {
+    pub fn of(data: &[u8]) -> Self {
+        Self(Sha256::digest(data).into())
+    }
+
+    /// Location of the object in a store, relative to the store directory.
+    pub fn object_path(&self) -> PathBuf {
+        let hex = self.to_string();
+
+        Path::new("objects").join(&hex[..2]).join(hex)
+    }
+}
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ContentHash({})", self)
+    }
+}
+
+impl FromStr for ContentHash {
+    type Err = crate::Error;
+
+    /// Parses 64 hex digits.
+    fn from_str(hex: &str) -> crate::Result<Self> {
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(invalid_data(format!("invalid content hash {:?}", hex)));
+        }
+
+        let mut hash = [0; 32];
+        for (index, byte) in hash.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16)
+                .map_err(|_| invalid_data(format!("invalid content hash {:?}", hex)))?;
+        }
+
+        Ok(Self(hash))
+    }
+}
+
+/// The archives of a single revision, see the [module docs](self).
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Manifest {
+    /// Every index with an `.idx` file, reference table index excluded, in ascending order.
+    pub indices: Vec<u8>,
+    /// Every stored archive in ascending index and archive order, reference tables included.
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// A single archive of a [`Manifest`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ManifestEntry {
+    pub index_id: u8,
+    pub archive_id: u32,
+    /// Version in the index's reference table, for reference tables the version of the table.
+    pub version: u32,
+    pub hash: ContentHash,
+}
+
+impl Manifest {
+    /// Writes the manifest as text in the current [format version](MANIFEST_VERSION).
+    ///
+    /// The first line holds the version, the second the index ids, every
+    /// following line one entry as `index archive version hash`.
+    ///
+    /// # Errors
+    ///
+    /// Only fails when writing to `W` fails.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> crate::Result<()> {
+        writeln!(writer, "{} {}", MANIFEST_HEADER, MANIFEST_VERSION)?;
+        let indices: Vec<_> = self.indices.iter().map(u8::to_string).collect();
+        writeln!(writer, "{}", indices.join(" "))?;
+        for entry in &self.entries {
+            writeln!(
+                writer,
+                "{} {} {} {}",
+                entry.index_id, entry.archive_id, entry.version, entry.hash
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a manifest written by [`write_to`](Manifest::write_to).
+    ///
+    /// # Errors
+    ///
+    /// Fails with an [`InvalidData`](io::ErrorKind::InvalidData) error when the
+    /// manifest is malformed or of another version.
+    pub fn read_from<R: Read>(reader: R) -> crate::Result<Self> {
+        let mut lines = BufReader::new(reader).lines();
+        let mut next_line = || -> crate::Result<Option<String>> { Ok(lines.next().transpose()?) };
+
+        let header = next_line()?.unwrap_or_default();
+        if header != format!("{} {}", MANIFEST_HEADER, MANIFEST_VERSION) {
+            return Err(invalid_data(format!("unsupported manifest {:?}", header)));
+        }
+
+        let indices = next_line()?
+            .ok_or_else(|| invalid_data("manifest without indices".to_owned()))?
+            .split_whitespace()
+            .map(|index_id| parse(index_id, "index id"))
+            .collect::<crate::Result<_>>()?;
+
+        let mut entries = Vec::new();
+        while let Some(line) = next_line()? {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            match fields[..] {
+                [index_id, archive_id, version, hash] => entries.push(ManifestEntry {
+                    index_id: parse(index_id, "index id")?,
+                    archive_id: parse(archive_id, "archive id")?,
+                    version: parse(version, "version")?,
+                    hash: hash.parse()?,
+                }),
+                [] => continue,
+                _ => return Err(invalid_data(format!("invalid manifest entry {:?}", line))),
+            }
+        }
+
+        Ok(Self { indices, entries })
+    }
+}
+
+/// Stores every archive of the cache in the object store at `store_dir` and
+/// returns the manifest of the revision.
+///
+/// Objects already in the store aren't written again. New objects are written
+/// to a temporary file first, so an interrupted export never leaves a
+/// truncated object behind.
+///
+/// # Errors
+///
+/// Fails when an archive can't be read or the store can't be written.
+pub fn export<P: AsRef<Path>>(cache: &Cache, store_dir: P) -> crate::Result<Manifest> {
+    let store_dir = store_dir.as_ref();
+    let mut indices = Vec::new();
+    let mut entries = Vec::new();
+
+    let index_ids: Vec<_> = cache.indices.ids().collect();
+    for index_id in index_ids {
+        if index_id != REFERENCE_TABLE_ID {
+            indices.push(index_id);
+        }
+
+        for archive in cache.archives(index_id)? {
+            let container = cache.read_raw(index_id, archive.id)?;
+            let hash = ContentHash::of(&container);
+            let version = if index_id == REFERENCE_TABLE_ID {
+                u8::try_from(archive.id)
+                    .ok()
+                    .and_then(|table_id| cache.indices.get(&table_id))
+                    .map_or(0, |index| index.metadata.version())
+            } else {
+                cache
+                    .indices
+                    .get(&index_id)
+                    .and_then(|index| index.metadata.archive(archive.id))
+                    .map_or(0, |entry| entry.version)
+            };
+
+            store(store_dir, hash, &container)?;
+            entries.push(ManifestEntry {
+                index_id,
+                archive_id: archive.id,
+                version,
+                hash,
+            });
+        }
+    }
+
+    Ok(Manifest { indices, entries })
+}
+
+/// Rebuilds the cache of a manifest from the object store at `store_dir` into `out_dir`.
+///
+/// Containers are restored byte for byte, so the restored cache has the same
+/// checksum as the exported one. Sectors are laid out anew.
+///
+/// # Errors
+///
+/// Fails when `out_dir` already holds a cache, when an object is missing or
+/// doesn't match its hash, or when the cache files can't be written.
+pub fn restore<P: AsRef<Path>, Q: AsRef<Path>>(
+    manifest: &Manifest,
+    store_dir: P,
+    out_dir: Q,
+) -> crate::Result<()> {
+    let (store_dir, out_dir) = (store_dir.as_ref(), out_dir.as_ref());
+    if out_dir.join(MAIN_DATA).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already holds a cache", out_dir.display()),
+        )
+        .into());
+    }
+    fs::create_dir_all(out_dir)?;
+
+    let mut idx: BTreeMap<u8, Vec<u8>> = manifest
+        .indices
+        .iter()
+        .chain([&REFERENCE_TABLE_ID])
+        .map(|&index_id| (index_id, Vec::new()))
+        .collect();
+    // the first sector is never used.
+    let mut dat2 = vec![0; SECTOR_SIZE];
+    let mut sector = 1;
+
+    for entry in &manifest.entries {
+        let container = fs::read(store_dir.join(entry.hash.object_path()))?;
+        if ContentHash::of(&container) != entry.hash {
+            return Err(invalid_data(format!(
+                "object {} doesn't match its hash",
+                entry.hash
+            )));
+        }
+
+        let archive_ref = bundle::sectors(
+            &mut dat2,
+            &mut sector,
+            entry.index_id,
+            entry.archive_id,
+            &container,
+        );
+        bundle::put_entry(
+            idx.entry(entry.index_id).or_default(),
+            entry.archive_id,
+            archive_ref,
+        );
+    }
+
+    fs::write(out_dir.join(MAIN_DATA), dat2)?;
+    for (index_id, idx) in idx {
+        fs::write(out_dir.join(format!("{}{}", IDX_PREFIX, index_id)), idx)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the object unless the store has it already.
+fn store(store_dir: &Path, hash: ContentHash, container: &[u8]) -> crate::Result<()> {
+    let path = store_dir.join(hash.object_path());
+    if path.exists() {
+        return Ok(());
+    }
+
+    let dir = path.parent().expect("objects are stored in a directory");
+    fs::create_dir_all(dir)?;
+    let temporary = dir.join(format!("{}.tmp", hash));
+    File::create(&temporary)?.write_all(container)?;
+    fs::rename(temporary, path)?;
+
+    Ok(())
+}
+
+fn parse<T: FromStr>(field: &str, name: &str) -> crate::Result<T> {
+    field
+        .parse()
+        .map_err(|_| invalid_data(format!("invalid {} {:?} in manifest", name, field)))
+}
+
+fn invalid_data(message: String) -> crate::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
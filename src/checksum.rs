@@ -15,7 +15,7 @@
 //! let checksum = cache.checksum()?;
 //! let checksum = Checksum::new(&cache)?;
 //!
-//! checksum.validate(&client_crcs)?;
+//! checksum.validate_detailed(&client_crcs)?;
 //!
 //! // Encode the checksum with the OSRS protocol.
 //! let buffer = checksum.encode()?;
@@ -23,34 +23,96 @@
 //! # }
 //! ```
 
+use std::fs;
+use std::io::{self, Read};
 use std::iter::IntoIterator;
+use std::path::Path;
 use std::slice::Iter;
 
-use crate::{error::ValidateError, Cache};
-use nom::{combinator::cond, number::complete::be_u32};
+use crate::{codec, error::{ChecksumMismatch, CrcMismatch, ValidateError}, extension::ReadExt, reference_table::ReferenceTable, Cache};
 use runefs::{
     codec::{Buffer, Encoded},
-    REFERENCE_TABLE_ID,
+    error::{Error as RuneFsError, ReadError},
+    IDX_PREFIX, MAIN_DATA, REFERENCE_TABLE_ID,
 };
 
+#[cfg(feature = "rs3")]
+use crate::error::ChecksumError;
 #[cfg(feature = "rs3")]
 use num_bigint::{BigInt, Sign};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "rs3")]
+#[cfg(feature = "whirlpool")]
 use whirlpool::{Digest, Whirlpool};
 
+/// Indices where [`Checksum::validate_detailed`] doesn't count an empty crc on
+/// either side as a mismatch.
+///
+/// RS3 caches never list index 47 and OSRS clients send an empty crc for the
+/// removed world map index 16, while older caches still have it.
+pub const UNCHECKED_INDICES: [u8; 2] = [16, 47];
+
 /// Each entry in the checksum is mapped to an [`Index`](runefs::Index).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(not(feature = "rs3"), derive(Default))]
+#[cfg_attr(not(feature = "whirlpool"), derive(Default))]
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct Entry {
     pub(crate) crc: u32,
     pub(crate) version: u32,
-    #[cfg(feature = "rs3")]
+    #[cfg(feature = "whirlpool")]
     pub(crate) hash: Vec<u8>,
 }
 
+impl Entry {
+    #[allow(missing_docs)]
+    #[inline]
+    pub const fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    #[allow(missing_docs)]
+    #[inline]
+    pub const fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Whirlpool digest of the index's reference table, 64 zero bytes for
+    /// indices without one.
+    #[cfg(feature = "whirlpool")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "whirlpool")))]
+    #[inline]
+    pub fn hash(&self) -> &[u8] {
+        &self.hash
+    }
+
+    /// Appends the entry as OSRS clients expect it, its crc followed by its
+    /// version.
+    ///
+    /// RS3 entries also carry a whirlpool hash and are framed differently, see
+    /// [`Checksum::encode_for`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let checksum = cache.checksum()?;
+    /// let mut buffer = Vec::new();
+    /// for entry in &checksum {
+    ///     entry.encode_into(&mut buffer);
+    /// }
+    ///
+    /// assert_eq!(buffer.len(), checksum.iter().count() * 8);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encode_into(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(u32::to_be_bytes(self.crc));
+        buffer.extend(u32::to_be_bytes(self.version));
+    }
+}
+
 /// Validator for the `Cache`.
 ///
 /// Used to validate cache index files. It contains a list of entries, one entry for each index file.
@@ -72,55 +134,177 @@ impl Checksum {
     /// Decoding of a index buffer fails, this is considered a bug.
     pub fn new(cache: &Cache) -> crate::Result<Self> {
         Ok(Self {
-            index_count: cache.indices.count(),
+            // the reference table index is counted as well.
+            index_count: cache.index_count() + 1,
             entries: Self::entries(cache)?,
         })
     }
 
+    /// An entry for every index id below [`Cache::index_count`], clients look
+    /// entries up by position. Indices without a readable reference table,
+    /// gaps included, take up an empty entry.
     fn entries(cache: &Cache) -> crate::Result<Vec<Entry>> {
-        let entries: Vec<Entry> = (0..cache.indices.count())
-            .filter_map(|idx_id| cache.read(REFERENCE_TABLE_ID, idx_id as u32).ok())
-            .enumerate()
-            .map(|(idx_id, buffer)| -> crate::Result<Entry> {
-                if buffer.is_empty() || idx_id == 47 {
-                    Ok(Entry::default())
-                } else {
-                    // let (buffer, size) = if with_rsa {
-                    //     be_u8(buffer.as_slice())?
-                    // } else {
-                    //     (buffer.as_slice(), (buffer.len() / 8) as u8)
-                    // };
-
-                    #[cfg(feature = "rs3")]
-                    let hash = {
-                        let mut hasher = Whirlpool::new();
-                        hasher.update(&buffer);
-                        hasher.finalize().as_slice().to_vec()
-                    };
-
-                    let checksum = crc32fast::hash(&buffer);
-
-                    let data = buffer.decode()?;
-                    let (_, version) = cond(data[0] >= 6, be_u32)(&data[1..5])?;
-                    let version = version.unwrap_or(0);
-
-                    Ok(Entry {
-                        crc: checksum,
-                        version,
-                        #[cfg(feature = "rs3")]
-                        hash,
-                    })
-                }
-            })
-            .filter_map(crate::Result::ok)
+        let entries: Vec<Entry> = (0..cache.index_count())
+            .map(|idx_id| Self::entry(cache, idx_id as u8).unwrap_or_default())
             .collect();
 
         Ok(entries)
     }
 
-    /// Consumes the `Checksum` and encodes it into a byte buffer.
+    /// Whether the index takes up an entry.
+    fn listed(cache: &Cache, idx_id: u8) -> bool {
+        (idx_id as usize) < cache.index_count()
+    }
+
+    fn entry(cache: &Cache, idx_id: u8) -> crate::Result<Entry> {
+        let buffer = match cache.read(REFERENCE_TABLE_ID, idx_id as u32) {
+            Ok(buffer) => buffer,
+            Err(_) if cache.contains_index(idx_id) => Buffer::default(),
+            Err(err) => return Err(err),
+        };
+
+        if buffer.is_empty() || idx_id == UNCHECKED_INDICES[1] {
+            return Ok(Entry::default());
+        }
+
+        // let (buffer, size) = if with_rsa {
+        //     be_u8(buffer.as_slice())?
+        // } else {
+        //     (buffer.as_slice(), (buffer.len() / 8) as u8)
+        // };
+
+        #[cfg(feature = "whirlpool")]
+        let hash = {
+            let mut hasher = Whirlpool::new();
+            hasher.update(&buffer);
+            hasher.finalize().as_slice().to_vec()
+        };
+
+        let checksum = crc32fast::hash(&buffer);
+
+        // indices only listed in idx255 weren't parsed when the cache was opened.
+        let version = if cache.contains_index(idx_id) {
+            cache.metadata_of(idx_id)?.version()
+        } else {
+            ReferenceTable::parse(&codec::decode(&buffer)?)?.version()
+        };
+
+        Ok(Entry {
+            crc: checksum,
+            version,
+            #[cfg(feature = "whirlpool")]
+            hash,
+        })
+    }
+
+    /// Recomputes the entry of a single index in place, leaving the others untouched.
+    ///
+    /// Meant for after an index's reference table changed, e.g. by
+    /// [installing](crate::bundle::IndexBundle::install) a bundle and
+    /// [reloading](Cache::reload) the cache. The result is the same as a new
+    /// [`Checksum`] as long as no other index changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IndexNotFound` when the index had no entry in this checksum.
+    /// Like in [`new`](Checksum::new) an index whose reference table can't be
+    /// read gets an empty entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::{Cache, checksum::Checksum};
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let mut checksum = cache.checksum()?;
+    /// checksum.update_from(&cache, 2)?;
+    ///
+    /// assert_eq!(checksum, cache.checksum()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_from(&mut self, cache: &Cache, index_id: u8) -> crate::Result<()> {
+        self.update_entry(index_id, Self::listed_entry(cache, index_id)?)
+    }
+
+    /// Replaces the entry of a single index, e.g. one computed with
+    /// [`Cache::checksum_entry`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `IndexNotFound` when the index has no entry in this checksum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let mut checksum = cache.checksum()?;
+    /// checksum.update_entry(2, cache.checksum_entry(2)?)?;
+    ///
+    /// assert_eq!(checksum, cache.checksum()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_entry(&mut self, index_id: u8, entry: Entry) -> crate::Result<()> {
+        *self
+            .entries
+            .get_mut(index_id as usize)
+            .ok_or(RuneFsError::Read(ReadError::IndexNotFound(index_id)))? = entry;
+
+        Ok(())
+    }
+
+    /// The entry of a single index, computed like [`new`](Checksum::new) does.
+    pub(crate) fn listed_entry(cache: &Cache, index_id: u8) -> crate::Result<Entry> {
+        if !Self::listed(cache, index_id) {
+            return Err(RuneFsError::Read(ReadError::IndexNotFound(index_id)).into());
+        }
+
+        Ok(Self::entry(cache, index_id).unwrap_or_default())
+    }
+
+    /// Keeps the entries of the first `count` indices only, checksums with
+    /// fewer entries are returned as is.
+    ///
+    /// Clients expect a table with an entry for every index of their revision,
+    /// serving a newer cache with more indices to them fails the handshake.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let checksum = cache.checksum()?.truncated(16);
+    ///
+    /// assert_eq!(checksum.iter().count(), 16);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn truncated(self, count: usize) -> Self {
+        if self.entries.len() > count {
+            self.resized(count)
+        } else {
+            self
+        }
+    }
+
+    /// Truncates the checksum to `count` entries or pads it with empty
+    /// entries, like those of indices without a reference table, up to `count`.
+    ///
+    /// See [`Cache::checksum_with_len`].
+    pub fn resized(mut self, count: usize) -> Self {
+        self.entries.resize_with(count, Entry::default);
+        // the reference table index is counted as well.
+        self.index_count = count + 1;
+        self
+    }
+
+    /// Encodes the `Checksum` into a byte buffer, keeping it around to
+    /// [validate](Checksum::validate) the crcs the client sends back.
     ///
-    /// 
     /// Note: It defaults to OSRS. RS3 clients use RSA to encrypt
     /// network traffic, which includes the checksum. When encoding for RS3 clients
     /// use [`RsaChecksum`](RsaChecksum) instead.
@@ -130,40 +314,152 @@ impl Checksum {
     /// # Errors
     ///
     /// Encoding of the formatted buffer fails, this is considered a bug.
-    pub fn encode(self) -> crate::Result<Buffer<Encoded>> {
-        let mut buffer = Vec::with_capacity(self.entries.len() * 8);
+    pub fn encode(&self) -> crate::Result<Buffer<Encoded>> {
+        Ok(Buffer::from(self.encode_for(Protocol::Osrs)?))
+    }
+
+    /// Encodes the `Checksum` for OSRS clients in the given table layout.
+    ///
+    /// [`ChecksumFormat::Legacy`] is the same as [`encode`](Checksum::encode),
+    /// [`ChecksumFormat::Versioned`] leads the entries with the
+    /// [format byte](ChecksumFormat::VERSIONED) and the number of entries.
+    ///
+    /// # Errors
+    ///
+    /// Versioned tables fail to encode with more than 255 entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// use rscache::checksum::ChecksumFormat;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let checksum = cache.checksum()?;
+    /// let legacy = checksum.encode()?;
+    /// let versioned = checksum.encode_versioned(ChecksumFormat::Versioned)?;
+    ///
+    /// // an uncompressed container, then the format byte and the entry count.
+    /// assert_eq!(versioned[5..7], [ChecksumFormat::VERSIONED, checksum.iter().count() as u8]);
+    /// assert_eq!(versioned[7..], legacy[5..]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encode_versioned(&self, format: ChecksumFormat) -> crate::Result<Buffer<Encoded>> {
+        match format {
+            ChecksumFormat::Legacy => self.encode(),
+            ChecksumFormat::Versioned => {
+                let count = u8::try_from(self.entries.len()).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("{} entries don't fit a versioned table", self.entries.len()),
+                    )
+                })?;
+                let mut buffer = Vec::with_capacity(2 + self.entries.len() * 8);
+                buffer.extend([ChecksumFormat::VERSIONED, count]);
+                for entry in &self.entries {
+                    entry.encode_into(&mut buffer);
+                }
 
-        for entry in self.entries {
-            buffer.extend(u32::to_be_bytes(entry.crc));
-            buffer.extend(u32::to_be_bytes(entry.version));
+                Ok(Buffer::from(buffer).encode()?)
+            }
         }
+    }
+
+    /// Encodes the checksum for the given client protocol.
+    ///
+    /// Servers supporting both games can pick the protocol at runtime, the output
+    /// is the same as [`encode`](Checksum::encode) or `RsaChecksum::encode`.
+    ///
+    /// # Errors
+    ///
+    /// Encoding of the formatted buffer fails, this is considered a bug. RS3
+    /// encoding fails with [`InvalidRsaKey`](crate::error::ChecksumError::InvalidRsaKey)
+    /// on invalid keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// use rscache::checksum::Protocol;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let checksum = cache.checksum()?;
+    /// let buffer = checksum.encode_for(Protocol::Osrs)?;
+    ///
+    /// assert_eq!(buffer, checksum.encode()?.finalize());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn encode_for(&self, protocol: Protocol<'_>) -> crate::Result<Vec<u8>> {
+        match protocol {
+            Protocol::Osrs => {
+                let mut buffer = Vec::with_capacity(self.entries.len() * 8);
+
+                for entry in &self.entries {
+                    entry.encode_into(&mut buffer);
+                }
 
-        // let mut buffer = codec::encode(Compression::None, &buffer, None)?;
+                // let mut buffer = codec::encode(Compression::None, &buffer, None)?;
 
-        // #[cfg(feature = "whirlpool")]
-        // {
-        //     let mut hasher = Whirlpool::new();
-        //     hasher.update(&buffer);
-        //     let mut hash = hasher.finalize().as_slice().to_vec();
-        //     hash.insert(0, 0);
+                // #[cfg(feature = "whirlpool")]
+                // {
+                //     let mut hasher = Whirlpool::new();
+                //     hasher.update(&buffer);
+                //     let mut hash = hasher.finalize().as_slice().to_vec();
+                //     hash.insert(0, 0);
 
-        //     let rsa_keys = self.rsa_keys.as_ref().unwrap();
-        //     let exp = BigInt::parse_bytes(rsa_keys.exponent, 10).unwrap_or_default();
-        //     let mud = BigInt::parse_bytes(rsa_keys.modulus, 10).unwrap_or_default();
-        //     let rsa = BigInt::from_bytes_be(Sign::Plus, &hash)
-        //         .modpow(&exp, &mud)
-        //         .to_bytes_be()
-        //         .1;
+                //     let rsa_keys = self.rsa_keys.as_ref().unwrap();
+                //     let exp = BigInt::parse_bytes(rsa_keys.exponent, 10).unwrap_or_default();
+                //     let mud = BigInt::parse_bytes(rsa_keys.modulus, 10).unwrap_or_default();
+                //     let rsa = BigInt::from_bytes_be(Sign::Plus, &hash)
+                //         .modpow(&exp, &mud)
+                //         .to_bytes_be()
+                //         .1;
 
-        //     buffer.extend(rsa);
-        // }
+                //     buffer.extend(rsa);
+                // }
 
-        // Ok(buffer)
-        // Ok(codec::encode(Compression::None, &buffer, None)?)
-        Ok(Buffer::from(buffer).encode()?)
+                // Ok(buffer)
+                // Ok(codec::encode(Compression::None, &buffer, None)?)
+                Ok(Buffer::from(buffer).encode()?.finalize())
+            }
+            #[cfg(feature = "rs3")]
+            Protocol::Rs3(keys) => {
+                let index_count = self.index_count - 1;
+                let mut buffer = vec![0; 81 * index_count];
+
+                buffer[0] = index_count as u8;
+                for (index, entry) in self.entries.iter().enumerate() {
+                    let offset = index * 80;
+                    buffer[offset + 1..=offset + 4].copy_from_slice(&u32::to_be_bytes(entry.crc));
+                    buffer[offset + 5..=offset + 8]
+                        .copy_from_slice(&u32::to_be_bytes(entry.version));
+                    buffer[offset + 9..=offset + 12].copy_from_slice(&u32::to_be_bytes(0));
+                    buffer[offset + 13..=offset + 16].copy_from_slice(&u32::to_be_bytes(0));
+                    buffer[offset + 17..=offset + 80].copy_from_slice(&entry.hash);
+                }
+
+                let mut hasher = Whirlpool::new();
+                hasher.update(&buffer);
+                let mut hash = hasher.finalize().as_slice().to_vec();
+                hash.insert(0, 0);
+
+                buffer.extend(keys.encrypt(&hash)?);
+
+                Ok(buffer)
+            }
+            #[cfg(not(feature = "rs3"))]
+            Protocol::__Keys(_, never) => match never {},
+        }
     }
 
     /// Validates the given crcs from the client with the internal crcs of this cache.
+    ///
+    /// Stops at the first difference, prefer [`validate_detailed`](Checksum::validate_detailed)
+    /// which reports all of them.
     /// 
     /// # Errors
     /// 
@@ -201,6 +497,73 @@ impl Checksum {
         Ok(())
     }
 
+    /// Validates the given crcs from the client with the internal crcs of this
+    /// cache, collecting every difference.
+    ///
+    /// Crcs are compared for every index both sides have, even when the client
+    /// sent too many or too few. An empty crc on either side of one of the
+    /// [`UNCHECKED_INDICES`] is skipped instead of compared.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ChecksumMismatch`] listing the differing crcs when a crc
+    /// mismatches or the number of crcs is wrong.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let checksum = cache.checksum()?;
+    /// let mut crcs: Vec<u32> = checksum.iter().map(|entry| entry.crc()).collect();
+    /// checksum.validate_detailed(&crcs)?;
+    ///
+    /// crcs[2] ^= 1;
+    /// crcs.pop();
+    /// let mismatch = checksum.validate_detailed(&crcs).unwrap_err();
+    /// assert!(mismatch.is_length_mismatch());
+    /// assert_eq!(mismatch.mismatches[0].index_id, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_detailed<'b, I>(&self, crcs: I) -> Result<(), ChecksumMismatch>
+    where
+        I: IntoIterator<Item = &'b u32>,
+    {
+        let mut report = ChecksumMismatch {
+            expected_len: self.entries.len(),
+            ..ChecksumMismatch::default()
+        };
+
+        for (index, external) in crcs.into_iter().enumerate() {
+            report.actual_len += 1;
+            let Some(entry) = self.entries.get(index) else {
+                continue;
+            };
+
+            let index_id = index as u8;
+            if entry.crc == *external {
+                continue;
+            }
+            if UNCHECKED_INDICES.contains(&index_id) && (entry.crc == 0 || *external == 0) {
+                report.skipped.push(index_id);
+                continue;
+            }
+            report.mismatches.push(CrcMismatch {
+                index_id,
+                expected: entry.crc,
+                actual: *external,
+            });
+        }
+
+        if report.is_length_mismatch() || !report.mismatches.is_empty() {
+            Err(report)
+        } else {
+            Ok(())
+        }
+    }
+
     #[allow(missing_docs)]
     #[inline]
     pub const fn index_count(&self) -> usize {
@@ -212,35 +575,518 @@ impl Checksum {
     pub fn iter(&self) -> Iter<'_, Entry> {
         self.entries.iter()
     }
+
+    /// Compares every entry with a known-good list, e.g. crcs published by
+    /// OpenRS2 or recorded from an earlier build.
+    ///
+    /// Entries are matched by index id, see [`MatchReport`] for the verdict.
+    pub fn matches_reference(&self, reference: &[ReferenceEntry]) -> MatchReport {
+        let mut indices: Vec<IndexReport> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index_id, entry)| {
+                let index_id = index_id as u8;
+                let actual = ReferenceEntry {
+                    index_id,
+                    crc: entry.crc,
+                    version: entry.version,
+                };
+                let expected = reference
+                    .iter()
+                    .find(|expected| expected.index_id == index_id);
+                let verdict = match expected {
+                    None => IndexVerdict::Unlisted,
+                    Some(expected) if *expected == actual => IndexVerdict::Match,
+                    Some(&expected) => IndexVerdict::Mismatch { expected, actual },
+                };
+
+                IndexReport { index_id, verdict }
+            })
+            .collect();
+
+        for expected in reference {
+            let listed = indices
+                .iter()
+                .any(|report| report.index_id == expected.index_id);
+            if !listed {
+                indices.push(IndexReport {
+                    index_id: expected.index_id,
+                    verdict: IndexVerdict::Missing,
+                });
+            }
+        }
+        indices.sort_by_key(|report| report.index_id);
+
+        MatchReport { indices }
+    }
+}
+
+/// Magic of the files written by [`Checksum::load_or_create`].
+const PERSISTED_MAGIC: &[u8; 4] = b"RSCK";
+/// Format version of the files written by [`Checksum::load_or_create`].
+const PERSISTED_VERSION: u8 = 1;
+/// Length of every sample taken from a reference table container.
+const SAMPLE_LEN: usize = 16;
+/// Samples taken from a reference table container, evenly spaced and including both ends.
+const SAMPLES: usize = 4;
+
+impl Checksum {
+    /// Loads the checksum persisted at `cache_file`, creating and persisting it
+    /// when the file is missing, unreadable or was written for a different cache.
+    ///
+    /// The persisted checksum is only used when the cache still matches the
+    /// fingerprint it was stored with, which is made up of:
+    /// - the length of `main_file_cache.dat2` and of every idx file.
+    /// - a crc over `main_file_cache.idx255` and samples of every reference
+    ///   table container: its length, header and evenly spaced windows,
+    ///   including the last bytes.
+    ///
+    /// Nothing but the reference tables is read from `main_file_cache.dat2`.
+    /// The checksum is computed from those tables, so archives changing without
+    /// their reference table don't matter. Gzip and bzip2 containers end in a
+    /// crc of their decompressed data, so sampling their last bytes catches any
+    /// change to such a reference table. Uncompressed and lzma containers are
+    /// hashed in full.
+    ///
+    /// The residual risk is a reference table recompressed to the same length
+    /// from the same contents, whose stored bytes (and so the checksum crc)
+    /// differ only between the samples, and crc32 collisions.
+    ///
+    /// Creating a checksum is already cheap once the cache is open, the
+    /// `checksum/persisted` benchmark compares both paths. The file mostly
+    /// spares recomputing the checksum for other processes serving it.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the fingerprint can't be computed, the checksum can't be
+    /// created or `cache_file` can't be written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::{Cache, checksum::Checksum};
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// # let dir = tempfile::tempdir()?;
+    /// let path = dir.path().join("checksum.bin");
+    /// let checksum = Checksum::load_or_create(&cache, &path)?;
+    ///
+    /// assert_eq!(Checksum::load_persisted(&cache, &path)?, Some(checksum));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_or_create<P: AsRef<Path>>(cache: &Cache, cache_file: P) -> crate::Result<Self> {
+        let cache_file = cache_file.as_ref();
+        let fingerprint = Self::fingerprint(cache)?;
+
+        if let Ok(Some(checksum)) = Self::read_persisted(cache_file, &fingerprint) {
+            return Ok(checksum);
+        }
+
+        let checksum = Self::new(cache)?;
+        let tmp = cache_file.with_extension("tmp");
+        fs::write(&tmp, checksum.persisted(&fingerprint))?;
+        fs::rename(&tmp, cache_file)?;
+
+        Ok(checksum)
+    }
+
+    /// Loads the checksum persisted by [`load_or_create`](Checksum::load_or_create),
+    /// `None` when the file is missing or was written for a different cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` I/O error when the file is corrupted, and fails
+    /// when the file or the cache's fingerprint can't be read.
+    pub fn load_persisted<P: AsRef<Path>>(
+        cache: &Cache,
+        cache_file: P,
+    ) -> crate::Result<Option<Self>> {
+        Self::read_persisted(cache_file.as_ref(), &Self::fingerprint(cache)?)
+    }
+
+    fn read_persisted(cache_file: &Path, fingerprint: &[u8]) -> crate::Result<Option<Self>> {
+        let buffer = match fs::read(cache_file) {
+            Ok(buffer) => buffer,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let corrupted = || io::Error::new(io::ErrorKind::InvalidData, "corrupted checksum file");
+
+        let (body, crc) = buffer
+            .len()
+            .checked_sub(4)
+            .map(|len| buffer.split_at(len))
+            .ok_or_else(corrupted)?;
+        if crc32fast::hash(body).to_be_bytes() != crc || !body.starts_with(PERSISTED_MAGIC) {
+            return Err(corrupted().into());
+        }
+
+        let mut reader = &body[PERSISTED_MAGIC.len()..];
+        let version = reader.read_u8()?;
+        let hashed = reader.read_u8()? == 1;
+        let len = reader.read_u32()? as usize;
+        if version != PERSISTED_VERSION
+            || hashed != cfg!(feature = "whirlpool")
+            || reader.get(..len) != Some(fingerprint)
+        {
+            return Ok(None);
+        }
+        reader = &reader[len..];
+
+        let index_count = reader.read_u32()? as usize;
+        let entry_count = reader.read_u32()? as usize;
+        let mut entries = Vec::with_capacity(entry_count.min(u8::MAX as usize + 1));
+        for _ in 0..entry_count {
+            let crc = reader.read_u32()?;
+            let version = reader.read_u32()?;
+            #[cfg(feature = "whirlpool")]
+            let hash = {
+                let mut hash = vec![0; 64];
+                reader.read_exact(&mut hash)?;
+                hash
+            };
+
+            entries.push(Entry {
+                crc,
+                version,
+                #[cfg(feature = "whirlpool")]
+                hash,
+            });
+        }
+        if !reader.is_empty() {
+            return Err(corrupted().into());
+        }
+
+        Ok(Some(Self {
+            index_count,
+            entries,
+        }))
+    }
+
+    fn persisted(&self, fingerprint: &[u8]) -> Vec<u8> {
+        let mut buffer = PERSISTED_MAGIC.to_vec();
+        buffer.push(PERSISTED_VERSION);
+        buffer.push(cfg!(feature = "whirlpool") as u8);
+        buffer.extend((fingerprint.len() as u32).to_be_bytes());
+        buffer.extend(fingerprint);
+        buffer.extend((self.index_count as u32).to_be_bytes());
+        buffer.extend((self.entries.len() as u32).to_be_bytes());
+        for entry in &self.entries {
+            buffer.extend(entry.crc.to_be_bytes());
+            buffer.extend(entry.version.to_be_bytes());
+            #[cfg(feature = "whirlpool")]
+            buffer.extend(&entry.hash);
+        }
+        buffer.extend(crc32fast::hash(&buffer).to_be_bytes());
+
+        buffer
+    }
+
+    /// Cheap fingerprint of everything a checksum is computed from, see
+    /// [`load_or_create`](Checksum::load_or_create).
+    fn fingerprint(cache: &Cache) -> crate::Result<Vec<u8>> {
+        let idx_path = |index_id: u8| cache.path.join(format!("{}{}", IDX_PREFIX, index_id));
+
+        let dat2_len = fs::metadata(cache.path.join(MAIN_DATA))?.len();
+        let mut fingerprint = dat2_len.to_be_bytes().to_vec();
+        for index_id in cache.indices.ids() {
+            fingerprint.push(index_id);
+            fingerprint.extend(fs::metadata(idx_path(index_id))?.len().to_be_bytes());
+        }
+
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&fs::read(idx_path(REFERENCE_TABLE_ID))?);
+        for index_id in 0..REFERENCE_TABLE_ID {
+            if let Ok(container) = cache.read_raw(REFERENCE_TABLE_ID, index_id as u32) {
+                hasher.update(&[index_id]);
+                sample(&mut hasher, &container);
+            }
+        }
+        fingerprint.extend(hasher.finalize().to_be_bytes());
+
+        Ok(fingerprint)
+    }
+}
+
+/// Hashes the samples of a reference table container, see [`Checksum::load_or_create`].
+fn sample(hasher: &mut crc32fast::Hasher, container: &[u8]) {
+    hasher.update(&(container.len() as u64).to_be_bytes());
+
+    // bzip2 and gzip streams end in a crc of the decompressed data.
+    let compressed = matches!(container.first(), Some(1 | 2));
+    if !compressed || container.len() <= SAMPLES * SAMPLE_LEN {
+        hasher.update(container);
+        return;
+    }
+
+    let last = container.len() - SAMPLE_LEN;
+    for sample in 0..SAMPLES {
+        let start = last * sample / (SAMPLES - 1);
+        hasher.update(&container[start..start + SAMPLE_LEN]);
+    }
+}
+
+/// Known-good checksum entry of a single index, see [`Checksum::matches_reference`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ReferenceEntry {
+    pub index_id: u8,
+    pub crc: u32,
+    pub version: u32,
+}
+
+impl ReferenceEntry {
+    /// Reads a reference list from csv with `index_id,crc,version` rows.
+    ///
+    /// A header row, empty lines and lines starting with `#` are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` I/O error on malformed rows.
+    pub fn read_csv<R: Read>(mut reader: R) -> crate::Result<Vec<Self>> {
+        let mut csv = String::new();
+        reader.read_to_string(&mut csv)?;
+
+        let mut entries = Vec::new();
+        for (row, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let entry = match fields.as_slice() {
+                [index_id, crc, version] => index_id
+                    .parse()
+                    .ok()
+                    .zip(crc.parse().ok())
+                    .zip(version.parse().ok())
+                    .map(|((index_id, crc), version)| Self {
+                        index_id,
+                        crc,
+                        version,
+                    }),
+                _ => None,
+            };
+
+            match entry {
+                Some(entry) => entries.push(entry),
+                // the header row.
+                None if row == 0 && entries.is_empty() => continue,
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("malformed reference entry on line {}: {}", row + 1, line),
+                    )
+                    .into())
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Reads a reference list from a json array of entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` I/O error when the json doesn't describe a list of entries.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    pub fn read_json<R: Read>(reader: R) -> crate::Result<Vec<Self>> {
+        serde_json::from_reader(reader).map_err(|err| io::Error::from(err).into())
+    }
+}
+
+/// Agreement of a single index with the reference list.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum IndexVerdict {
+    /// Crc and version are equal.
+    Match,
+    /// The crc or version differs.
+    Mismatch {
+        expected: ReferenceEntry,
+        actual: ReferenceEntry,
+    },
+    /// The reference lists the index but the cache doesn't have it.
+    Missing,
+    /// The cache has the index but the reference doesn't list it.
+    Unlisted,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct IndexReport {
+    pub index_id: u8,
+    pub verdict: IndexVerdict,
+}
+
+/// Result of [`Checksum::matches_reference`], one report for every index in
+/// either the cache or the reference list, in ascending index order.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct MatchReport {
+    pub indices: Vec<IndexReport>,
+}
+
+impl MatchReport {
+    /// Whether the cache is exactly the referenced build, every index has to match.
+    pub fn is_match(&self) -> bool {
+        self.indices
+            .iter()
+            .all(|report| report.verdict == IndexVerdict::Match)
+    }
+
+    /// Reports of the indices that don't match.
+    pub fn mismatches(&self) -> impl Iterator<Item = &IndexReport> {
+        self.indices
+            .iter()
+            .filter(|report| report.verdict != IndexVerdict::Match)
+    }
+}
+
+/// Layout of the checksum table sent to OSRS clients, see [`Checksum::encode_versioned`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum ChecksumFormat {
+    /// The crc and version of every index, what [`Checksum::encode`] writes.
+    #[default]
+    Legacy,
+    /// The [format byte](ChecksumFormat::VERSIONED) and the number of
+    /// entries, followed by the crc and version of every index.
+    Versioned,
+}
+
+impl ChecksumFormat {
+    /// Format byte leading [`Versioned`](ChecksumFormat::Versioned) tables.
+    pub const VERSIONED: u8 = 1;
+}
+
+/// Client protocol to [encode](Checksum::encode_for) a checksum for.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Protocol<'a> {
+    /// A plain list of crcs and versions.
+    Osrs,
+    /// Crcs, versions and whirlpool hashes, signed with the given RSA keys.
+    #[cfg(feature = "rs3")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rs3")))]
+    Rs3(RsaKeys<'a>),
+    #[cfg(not(feature = "rs3"))]
+    #[doc(hidden)]
+    __Keys(std::marker::PhantomData<&'a [u8]>, std::convert::Infallible),
+}
+
+/// How the keys of [`RsaKeys`] are written.
+#[cfg(feature = "rs3")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rs3")))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum RsaKeyFormat {
+    /// ASCII decimal digits, like most server configs store them.
+    #[default]
+    Decimal,
+    /// ASCII hexadecimal digits, in either case.
+    Hex,
+    /// A raw big-endian unsigned integer.
+    Bytes,
 }
 
 /// A struct that holds both keys for RSA encryption.
+///
+/// The keys are parsed when encrypting, keys that aren't numbers in their
+/// [format](RsaKeyFormat) or a zero modulus fail with
+/// [`ChecksumError::InvalidRsaKey`].
+///
+/// # Examples
+///
+/// ```
+/// use rscache::checksum::RsaKeys;
+///
+/// let decimal = RsaKeys::new(b"65537", b"3233");
+/// let hex = RsaKeys::from_hex(b"10001", b"ca1");
+/// let bytes = RsaKeys::from_be_bytes(&[1, 0, 1], &[0x0C, 0xA1]);
+///
+/// assert_eq!(decimal.encrypt(&[42]).unwrap(), hex.encrypt(&[42]).unwrap());
+/// assert_eq!(decimal.encrypt(&[42]).unwrap(), bytes.encrypt(&[42]).unwrap());
+/// assert!(RsaKeys::new(b"65537", b"0xca1").encrypt(&[42]).is_err());
+/// ```
 #[cfg(feature = "rs3")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rs3")))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
 pub struct RsaKeys<'a> {
     pub(crate) exponent: &'a [u8],
     pub(crate) modulus: &'a [u8],
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) format: RsaKeyFormat,
 }
 
 #[cfg(feature = "rs3")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rs3")))]
 impl<'a> RsaKeys<'a> {
-    /// Generate a RSA key set with the given keys.
+    /// Generate a RSA key set with the given decimal keys.
     pub const fn new(exponent: &'a [u8], modulus: &'a [u8]) -> Self {
-        Self { exponent, modulus }
+        Self::with_format(exponent, modulus, RsaKeyFormat::Decimal)
+    }
+
+    /// Generate a RSA key set with the given hexadecimal keys.
+    pub const fn from_hex(exponent: &'a [u8], modulus: &'a [u8]) -> Self {
+        Self::with_format(exponent, modulus, RsaKeyFormat::Hex)
+    }
+
+    /// Generate a RSA key set with the given big-endian keys.
+    pub const fn from_be_bytes(exponent: &'a [u8], modulus: &'a [u8]) -> Self {
+        Self::with_format(exponent, modulus, RsaKeyFormat::Bytes)
+    }
+
+    /// Generate a RSA key set with keys written in `format`.
+    pub const fn with_format(exponent: &'a [u8], modulus: &'a [u8], format: RsaKeyFormat) -> Self {
+        Self {
+            exponent,
+            modulus,
+            format,
+        }
+    }
+
+    #[allow(missing_docs)]
+    #[inline]
+    pub const fn format(&self) -> RsaKeyFormat {
+        self.format
     }
 
     /// Encrypts the given hash.
-    // TODO: maybe make this panic if the exponent or modulus not line up
-    pub fn encrypt(&self, hash: &[u8]) -> Vec<u8> {
-        let exp = BigInt::parse_bytes(self.exponent, 10).unwrap_or_default();
-        let mud = BigInt::parse_bytes(self.modulus, 10).unwrap_or_default();
-        BigInt::from_bytes_be(Sign::Plus, hash)
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ChecksumError::InvalidRsaKey`] when a key isn't a number
+    /// in its format or the modulus is zero.
+    pub fn encrypt(&self, hash: &[u8]) -> Result<Vec<u8>, ChecksumError> {
+        let (exp, mud) = self.parse()?;
+
+        Ok(BigInt::from_bytes_be(Sign::Plus, hash)
             .modpow(&exp, &mud)
             .to_bytes_be()
-            .1
+            .1)
+    }
+
+    /// The exponent and modulus, checked like [`encrypt`](RsaKeys::encrypt) does.
+    fn parse(&self) -> Result<(BigInt, BigInt), ChecksumError> {
+        let parse = |key: &[u8], name| {
+            let key = match self.format {
+                RsaKeyFormat::Decimal => BigInt::parse_bytes(key, 10),
+                RsaKeyFormat::Hex => BigInt::parse_bytes(key, 16),
+                RsaKeyFormat::Bytes => Some(BigInt::from_bytes_be(Sign::Plus, key)),
+            };
+            key.filter(|key| key.sign() != Sign::Minus)
+                .ok_or(ChecksumError::InvalidRsaKey(name))
+        };
+        let exponent = parse(self.exponent, "exponent")?;
+        let modulus = parse(self.modulus, "modulus")?;
+        if modulus.sign() == Sign::NoSign {
+            return Err(ChecksumError::InvalidRsaKey("modulus"));
+        }
+
+        Ok((exponent, modulus))
     }
 }
 
@@ -278,7 +1124,14 @@ pub struct RsaChecksum<'a> {
 #[cfg(feature = "rs3")]
 impl<'a> RsaChecksum<'a> {
     /// Generate a checksum with RSA encryption support.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`ChecksumError::InvalidRsaKey`] on keys that can't encrypt,
+    /// see [`RsaKeys`], or when generating the checksum fails.
     pub fn with_keys(cache: &Cache, rsa_keys: RsaKeys<'a>) -> crate::Result<Self> {
+        rsa_keys.parse()?;
+
         Ok(Self {
             checksum: Checksum::new(cache)?,
             rsa_keys,
@@ -286,28 +1139,10 @@ impl<'a> RsaChecksum<'a> {
     }
 
     /// Same as [`Checksum::encode`](Checksum::encode) but for RS3.
-    pub fn encode(self) -> crate::Result<Buffer<Encoded>> {
-        let index_count = self.checksum.index_count - 1;
-        let mut buffer = vec![0; 81 * index_count];
-
-        buffer[0] = index_count as u8;
-        for (index, entry) in self.checksum.entries.iter().enumerate() {
-            let offset = index * 80;
-            buffer[offset + 1..=offset + 4].copy_from_slice(&u32::to_be_bytes(entry.crc));
-            buffer[offset + 5..=offset + 8].copy_from_slice(&u32::to_be_bytes(entry.version));
-            buffer[offset + 9..=offset + 12].copy_from_slice(&u32::to_be_bytes(0));
-            buffer[offset + 13..=offset + 16].copy_from_slice(&u32::to_be_bytes(0));
-            buffer[offset + 17..=offset + 80].copy_from_slice(&entry.hash);
-        }
-
-        let mut hasher = Whirlpool::new();
-        hasher.update(&buffer);
-        let mut hash = hasher.finalize().as_slice().to_vec();
-        hash.insert(0, 0);
-
-        buffer.extend(self.rsa_keys.encrypt(&hash));
-
-        Ok(Buffer::from(buffer))
+    pub fn encode(&self) -> crate::Result<Buffer<Encoded>> {
+        Ok(Buffer::from(
+            self.checksum.encode_for(Protocol::Rs3(self.rsa_keys))?,
+        ))
     }
 }
 
@@ -360,7 +1195,7 @@ impl<'a> IntoIterator for &'a RsaChecksum<'a> {
     }
 }
 
-#[cfg(feature = "rs3")]
+#[cfg(feature = "whirlpool")]
 impl Default for Entry {
     #[inline]
     fn default() -> Self {
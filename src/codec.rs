@@ -0,0 +1,685 @@
+//! Panic free decoding of archive containers.
+//!
+//! [`Buffer::decode`] trusts the container header, a corrupted header can make
+//! it panic or allocate gigabytes up front. The decoders in this module check
+//! the header first and are used for every read made through the [`Cache`](crate::Cache).
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::codec;
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! let buffer = cache.read(2, 10)?;
+//! let decoded = codec::decode(&buffer)?;
+//!
+//! assert!(codec::decode(&buffer[..buffer.len() / 2]).is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use bzip2::{Decompress, Status};
+use flate2::write::GzDecoder;
+
+use nom::number::complete::{be_u32, be_u8};
+#[doc(no_inline)]
+pub use runefs::codec::Compression;
+use runefs::{
+    codec::{Buffer, Decoded, Encoded},
+    error::Error as RuneFsError,
+    xtea, REFERENCE_TABLE_ID,
+};
+
+use crate::error::ReadError;
+
+/// Largest decompressed length a container may declare, the largest archives
+/// in the cache are a few MB.
+pub const MAX_DECOMPRESSED_LEN: usize = 64 * 1024 * 1024;
+
+const NONE: u8 = 0;
+const BZIP2: u8 = 1;
+const GZIP: u8 = 2;
+#[cfg(feature = "rs3")]
+const LZMA: u8 = 3;
+
+/// Decodes a raw container.
+///
+/// # Errors
+///
+/// Returns an error when the header is malformed, the declared decompressed
+/// length exceeds [`MAX_DECOMPRESSED_LEN`] or decompression fails.
+pub fn decode(buffer: &[u8]) -> crate::Result<Buffer<Decoded>> {
+    decode_owned(buffer.to_vec())
+}
+
+/// A decoded container along with what [`decode`] drops, see [`decode_full`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct DecodedArchive {
+    /// Compression the container was stored with.
+    pub compression: Compression,
+    pub data: Vec<u8>,
+    /// The version trailer, see [`split_trailer`].
+    pub version: Option<u16>,
+}
+
+/// [`decode`]s a raw container, keeping its compression and version trailer.
+///
+/// The trailer is the 2 bytes following the body, which is the header (5 bytes,
+/// 9 for compressed containers) and the declared compressed length. Containers
+/// of any other length have no trailer.
+///
+/// # Errors
+///
+/// See [`decode`].
+///
+/// # Examples
+///
+/// ```
+/// # use rscache::Cache;
+/// use rscache::codec::{self, Compression};
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// # let cache = Cache::new("./data/osrs_cache")?;
+/// let archive = codec::decode_full(&cache.read(2, 10)?)?;
+///
+/// assert_eq!(archive.compression, Compression::Bzip2);
+/// assert_eq!(archive.version, Some(cache.archive_metadata(2, 10)?.trailer_version()));
+/// # Ok(())
+/// # }
+/// ```
+pub fn decode_full(buffer: &[u8]) -> crate::Result<DecodedArchive> {
+    let data = decode(buffer)?.finalize();
+    let compression = Compression::try_from(buffer[0]).map_err(RuneFsError::from)?;
+
+    Ok(DecodedArchive {
+        compression,
+        data,
+        version: split_trailer(buffer).1,
+    })
+}
+
+/// Deciphers a raw container with the given XTEA keys, then [`decode`]s it.
+///
+/// Everything after the 5-byte header is enciphered, so wrong keys garble the
+/// decompressed length and the compressed stream. Uncompressed containers
+/// have neither and decode into garbage instead.
+///
+/// # Errors
+///
+/// Returns [`ReadError::InvalidKeys`] when the deciphered container doesn't
+/// decode, so callers can try other keys, see [`decode`] for malformed headers.
+pub fn decode_with_keys(buffer: &[u8], keys: &[u32; 4]) -> crate::Result<Buffer<Decoded>> {
+    let mut buffer = buffer.to_vec();
+    match buffer.get_mut(5..) {
+        Some(data) => xtea::decipher(data, keys),
+        None => return decode_owned(buffer),
+    }
+
+    decode_owned(buffer).map_err(|_| ReadError::InvalidKeys.into())
+}
+
+/// Streams the decompressed contents of a raw container into `writer`,
+/// returning the number of bytes written.
+///
+/// The header is checked like [`decode`] does and the container has to
+/// decompress to exactly its declared length. Bzip2 and gzip streams are
+/// decompressed as they're written, LZMA is decompressed in memory first.
+///
+/// # Errors
+///
+/// See [`decode`], also fails when writing to `W` fails. Whatever was written
+/// before the error stays written.
+///
+/// # Examples
+///
+/// ```
+/// # use rscache::Cache;
+/// use rscache::codec;
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// # let cache = Cache::new("./data/osrs_cache")?;
+/// let buffer = cache.read(2, 10)?;
+/// let mut decoded = Vec::new();
+/// let len = codec::decode_into(&buffer, &mut decoded)?;
+///
+/// assert_eq!(len, decoded.len());
+/// assert_eq!(decoded, codec::decode(&buffer)?.finalize());
+/// # Ok(())
+/// # }
+/// ```
+pub fn decode_into<W: Write>(buffer: &[u8], writer: &mut W) -> crate::Result<usize> {
+    let mut container = ContainerWriter::new(writer);
+    container.write_all(buffer)?;
+
+    container.finish()
+}
+
+/// Splits the 2-byte version trailer off a raw container, when it has one.
+///
+/// The trailer holds the low 16 bits of the archive's
+/// [`version`](crate::reference_table::ArchiveEntry::version) and isn't
+/// covered by the reference table crc.
+///
+/// # Examples
+///
+/// ```
+/// # use rscache::Cache;
+/// use rscache::codec;
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// # let cache = Cache::new("./data/osrs_cache")?;
+/// let container = cache.read(2, 10)?;
+/// let (body, version) = codec::split_trailer(&container);
+///
+/// let entry = cache.archive_metadata(2, 10)?;
+/// assert_eq!(version, Some(entry.trailer_version()));
+/// assert_eq!(crc32fast::hash(body), entry.crc);
+/// # Ok(())
+/// # }
+/// ```
+pub fn split_trailer(container: &[u8]) -> (&[u8], Option<u16>) {
+    let header_len = match container.first() {
+        Some(&NONE) => 5_usize,
+        Some(_) => 9,
+        None => return (container, None),
+    };
+    let compressed_len = match container.get(1..5) {
+        Some(len) => u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize,
+        None => return (container, None),
+    };
+
+    match header_len
+        .checked_add(compressed_len)
+        .filter(|&body_len| body_len.checked_add(2) == Some(container.len()))
+    {
+        Some(body_len) => {
+            let (body, trailer) = container.split_at(body_len);
+            (body, Some(u16::from_be_bytes([trailer[0], trailer[1]])))
+        }
+        None => (container, None),
+    }
+}
+
+/// Encodes data into a raw container with the given compression, without a version trailer.
+///
+/// `Compression::Lzma`, used by RS3, needs the `rs3` feature like decoding it does.
+///
+/// # Errors
+///
+/// Fails when compression fails.
+pub fn encode(data: &[u8], compression: Compression) -> crate::Result<Vec<u8>> {
+    let buffer: Buffer<Decoded> = Buffer::from(data);
+
+    Ok(buffer.with_compression(compression).encode()?.finalize())
+}
+
+/// [`encode`]s data into a raw container followed by the 2-byte version trailer,
+/// the low 16 bits of the archive's [`version`](crate::reference_table::ArchiveEntry::version).
+///
+/// The trailer isn't covered by the reference table crc, see [`split_trailer`].
+///
+/// # Errors
+///
+/// Fails when compression fails.
+///
+/// # Examples
+///
+/// ```
+/// use rscache::codec::{self, Compression};
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// let container = codec::encode_with_version(b"huffman", Compression::Gzip, 7)?;
+///
+/// assert_eq!(codec::split_trailer(&container).1, Some(7));
+/// assert_eq!(codec::decode(&container)?.as_slice(), b"huffman");
+/// # Ok(())
+/// # }
+/// ```
+pub fn encode_with_version(
+    data: &[u8],
+    compression: Compression,
+    version: u16,
+) -> crate::Result<Vec<u8>> {
+    let mut container = encode(data, compression)?;
+    container.extend(version.to_be_bytes());
+
+    Ok(container)
+}
+
+/// [`encode`]s data into `writer`, returning the length of the container.
+///
+/// The header declares the compressed length up front, so the compressed
+/// stream is still built in memory before it's written.
+///
+/// # Errors
+///
+/// Fails when compression or writing to `W` fails.
+pub fn encode_into<W: Write>(
+    data: &[u8],
+    compression: Compression,
+    writer: &mut W,
+) -> crate::Result<usize> {
+    let container = encode(data, compression)?;
+    writer.write_all(&container)?;
+
+    Ok(container.len())
+}
+
+/// Picks the compression of re-encoded archives, per index with per-archive overrides.
+///
+/// # Examples
+///
+/// ```
+/// use rscache::codec::{self, Compression, CompressionPolicy};
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// let policy = CompressionPolicy::osrs().with_archive(2, 10, Compression::Bzip2);
+/// assert_eq!(policy.compression_for(2, 9), Compression::Gzip);
+/// assert_eq!(policy.compression_for(2, 10), Compression::Bzip2);
+///
+/// let container = policy.encode(2, 10, b"item definitions")?;
+/// assert_eq!(codec::decode(&container)?.as_slice(), b"item definitions");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CompressionPolicy {
+    default: Compression,
+    indices: BTreeMap<u8, Compression>,
+    archives: BTreeMap<(u8, u32), Compression>,
+}
+
+impl CompressionPolicy {
+    /// Uses the same compression for every archive until overridden.
+    pub const fn new(default: Compression) -> Self {
+        Self {
+            default,
+            indices: BTreeMap::new(),
+            archives: BTreeMap::new(),
+        }
+    }
+
+    /// The compression most archives of each index use in current osrs caches.
+    ///
+    /// Everything is gzip, including the reference tables in index 255, except
+    /// for the bzip2 indices 9, 16 and 18 and the uncompressed indices 17 and 20.
+    pub fn osrs() -> Self {
+        Self::new(Compression::Gzip)
+            .with_index(9, Compression::Bzip2)
+            .with_index(16, Compression::Bzip2)
+            .with_index(17, Compression::None)
+            .with_index(18, Compression::Bzip2)
+            .with_index(20, Compression::None)
+            .with_index(REFERENCE_TABLE_ID, Compression::Gzip)
+    }
+
+    /// Overrides the compression of every archive in the index.
+    pub fn with_index(mut self, index_id: u8, compression: Compression) -> Self {
+        self.indices.insert(index_id, compression);
+        self
+    }
+
+    /// Overrides the compression of a single archive, taking precedence over its index.
+    pub fn with_archive(mut self, index_id: u8, archive_id: u32, compression: Compression) -> Self {
+        self.archives.insert((index_id, archive_id), compression);
+        self
+    }
+
+    /// Compression chosen for the archive.
+    pub fn compression_for(&self, index_id: u8, archive_id: u32) -> Compression {
+        self.archives
+            .get(&(index_id, archive_id))
+            .or_else(|| self.indices.get(&index_id))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// [`encode`]s the archive with the compression chosen for it.
+    ///
+    /// # Errors
+    ///
+    /// See [`encode`].
+    pub fn encode(&self, index_id: u8, archive_id: u32, data: &[u8]) -> crate::Result<Vec<u8>> {
+        encode(data, self.compression_for(index_id, archive_id))
+    }
+}
+
+impl Default for CompressionPolicy {
+    /// Same as [`CompressionPolicy::osrs`].
+    fn default() -> Self {
+        Self::osrs()
+    }
+}
+
+/// Whether two raw containers are byte-identical, trailer included.
+///
+/// Containers that decode to the same data are not interchangeable, clients
+/// check the crc of the exact bytes they receive against the reference table.
+/// A re-encode with the same compression can still produce different bytes.
+///
+/// # Examples
+///
+/// ```
+/// # use rscache::Cache;
+/// use rscache::codec::{self, Compression};
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// # let cache = Cache::new("./data/osrs_cache")?;
+/// let container = cache.read_raw(2, 10)?;
+/// let (body, _) = codec::split_trailer(&container);
+/// let reencoded = codec::encode(&codec::decode(body)?, Compression::Bzip2)?;
+///
+/// assert!(codec::is_identical_container(&container, &cache.read_raw(2, 10)?));
+/// assert!(!codec::is_identical_container(&container, &reencoded));
+/// # Ok(())
+/// # }
+/// ```
+#[inline]
+pub fn is_identical_container(a: &[u8], b: &[u8]) -> bool {
+    a == b
+}
+
+fn decode_owned(buffer: Vec<u8>) -> crate::Result<Buffer<Decoded>> {
+    let (data, compression) = be_u8(buffer.as_slice())?;
+    let (data, compressed_len) = be_u32(data)?;
+    let compressed_len = compressed_len as usize;
+
+    if compression != NONE {
+        let (data, decompressed_len) = be_u32(data)?;
+        let decompressed_len = decompressed_len as usize;
+
+        if decompressed_len > MAX_DECOMPRESSED_LEN {
+            return Err(invalid_data(format!(
+                "container declares {} decompressed bytes",
+                decompressed_len
+            )));
+        }
+        if compressed_len > data.len() || (compression == BZIP2 && compressed_len < 4) {
+            return Err(invalid_data(format!(
+                "container declares {} compressed bytes but holds {}",
+                compressed_len,
+                data.len()
+            )));
+        }
+
+        #[cfg(feature = "rs3")]
+        if compression == LZMA {
+            return decompress_lzma(&data[..compressed_len], decompressed_len);
+        }
+    }
+
+    let encoded: Buffer<Encoded> = Buffer::from(buffer);
+
+    Ok(encoded.decode()?)
+}
+
+/// Decodes a raw container written to it in pieces, like the sectors of an
+/// archive, see [`decode_into`].
+///
+/// Anything past the declared compressed length, like the version trailer, is ignored.
+pub(crate) struct ContainerWriter<W: Write> {
+    header: Vec<u8>,
+    /// `None` once the header turned out malformed.
+    sink: Option<Sink<W>>,
+    compressed_len: usize,
+    /// Compressed bytes still expected.
+    remaining: usize,
+}
+
+enum Sink<W: Write> {
+    /// Waiting for the rest of the header.
+    Header(W),
+    None(Limited<W>),
+    Bzip2(Bzip2Writer<W>),
+    Gzip(GzDecoder<Limited<W>>),
+    #[cfg(feature = "rs3")]
+    Lzma(Vec<u8>, Limited<W>),
+}
+
+impl<W: Write> ContainerWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        Self {
+            header: Vec::with_capacity(9),
+            sink: Some(Sink::Header(writer)),
+            compressed_len: 0,
+            remaining: 0,
+        }
+    }
+
+    /// Checks that the whole container was written and decompressed to its
+    /// declared length, returning that length.
+    pub(crate) fn finish(self) -> crate::Result<usize> {
+        if self.remaining > 0 {
+            return Err(invalid_data(format!(
+                "container declares {} compressed bytes but holds {}",
+                self.compressed_len,
+                self.compressed_len - self.remaining
+            )));
+        }
+
+        let (written, limit) = match self.sink {
+            Some(Sink::Header(_)) => {
+                return Err(invalid_data("container ends within its header".to_owned()))
+            }
+            None => return Err(malformed_header().into()),
+            Some(Sink::None(mut limited)) => {
+                limited.flush()?;
+                (limited.written, limited.limit)
+            }
+            Some(Sink::Bzip2(mut decoder)) => {
+                decoder.write_all(&[])?;
+                decoder.inner.flush()?;
+                (decoder.inner.written, decoder.inner.limit)
+            }
+            Some(Sink::Gzip(decoder)) => {
+                let mut limited = decoder.finish()?;
+                limited.flush()?;
+                (limited.written, limited.limit)
+            }
+            #[cfg(feature = "rs3")]
+            Some(Sink::Lzma(data, mut limited)) => {
+                limited.write_all(decompress_lzma(&data, limited.limit)?.as_slice())?;
+                limited.flush()?;
+                (limited.written, limited.limit)
+            }
+        };
+
+        if written != limit {
+            return Err(invalid_data(format!(
+                "container declares {} decompressed bytes but holds {}",
+                limit, written
+            )));
+        }
+
+        Ok(written)
+    }
+
+    /// Length of the header, as far as it's known.
+    fn header_len(&self) -> usize {
+        match self.header.first() {
+            None => 1,
+            Some(&NONE) => 5,
+            Some(_) => 9,
+        }
+    }
+
+    /// Sets up decompression once the header is complete.
+    fn start_body(&mut self) -> io::Result<()> {
+        let Some(Sink::Header(writer)) = self.sink.take() else {
+            return Err(malformed_header());
+        };
+        let header = &self.header;
+        let compressed_len =
+            u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        let decompressed_len = match header.get(5..9) {
+            Some(len) => u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize,
+            None => compressed_len,
+        };
+        if header[0] != NONE && decompressed_len > MAX_DECOMPRESSED_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("container declares {} decompressed bytes", decompressed_len),
+            ));
+        }
+
+        let limited = Limited {
+            inner: writer,
+            written: 0,
+            limit: decompressed_len,
+        };
+        self.sink = Some(match header[0] {
+            NONE => Sink::None(limited),
+            BZIP2 => {
+                let mut decoder = Bzip2Writer {
+                    decompress: Decompress::new(false),
+                    buffer: Vec::with_capacity(32 * 1024),
+                    done: false,
+                    inner: limited,
+                };
+                // containers leave out the stream header, the block size doesn't matter to decompression.
+                decoder.write_all(b"BZh1")?;
+                Sink::Bzip2(decoder)
+            }
+            GZIP => Sink::Gzip(GzDecoder::new(limited)),
+            #[cfg(feature = "rs3")]
+            LZMA => Sink::Lzma(Vec::new(), limited),
+            compression => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsupported container compression {}", compression),
+                ))
+            }
+        });
+        self.compressed_len = compressed_len;
+        self.remaining = compressed_len;
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for ContainerWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut data = buf;
+        while self.header.len() < self.header_len() {
+            let Some((&byte, rest)) = data.split_first() else {
+                return Ok(buf.len());
+            };
+            self.header.push(byte);
+            data = rest;
+            if self.header.len() == self.header_len() && self.header.len() > 1 {
+                self.start_body()?;
+            }
+        }
+
+        let data = &data[..data.len().min(self.remaining)];
+        match &mut self.sink {
+            Some(Sink::None(limited)) => limited.write_all(data)?,
+            Some(Sink::Bzip2(decoder)) => decoder.write_all(data)?,
+            Some(Sink::Gzip(decoder)) => decoder.write_all(data)?,
+            #[cfg(feature = "rs3")]
+            Some(Sink::Lzma(compressed, _)) => compressed.extend_from_slice(data),
+            Some(Sink::Header(_)) | None => return Err(malformed_header()),
+        }
+        self.remaining -= data.len();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Decompresses bzip2 written to it into `W`.
+///
+/// `bzip2::write::BzDecoder` loops forever finishing a truncated stream, even when dropped.
+struct Bzip2Writer<W: Write> {
+    decompress: Decompress,
+    buffer: Vec<u8>,
+    /// Whether the stream ended, anything written after it is ignored.
+    done: bool,
+    inner: Limited<W>,
+}
+
+impl<W: Write> Bzip2Writer<W> {
+    /// Decompresses as much as `data` allows, an empty `data` drains what's left.
+    fn write_all(&mut self, mut data: &[u8]) -> io::Result<()> {
+        while !self.done {
+            let (total_in, total_out) = (self.decompress.total_in(), self.decompress.total_out());
+            let status = self
+                .decompress
+                .decompress_vec(data, &mut self.buffer)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            data = &data[(self.decompress.total_in() - total_in) as usize..];
+            self.inner.write_all(&self.buffer)?;
+            self.buffer.clear();
+
+            self.done = status == Status::StreamEnd;
+            if self.decompress.total_in() == total_in && self.decompress.total_out() == total_out {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Fails writes past the declared decompressed length.
+struct Limited<W: Write> {
+    inner: W,
+    written: usize,
+    limit: usize,
+}
+
+impl<W: Write> Write for Limited<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.limit - self.written {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "container decompresses past its declared {} bytes",
+                    self.limit
+                ),
+            ));
+        }
+        let written = self.inner.write(buf)?;
+        self.written += written;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn malformed_header() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed container header")
+}
+
+#[cfg(feature = "rs3")]
+fn decompress_lzma(data: &[u8], decompressed_len: usize) -> crate::Result<Buffer<Decoded>> {
+    use lzma_rs::decompress::{Options, UnpackedSize};
+    use runefs::codec::Compression;
+
+    let mut decompressed = Vec::with_capacity(decompressed_len);
+    let options = Options {
+        unpacked_size: UnpackedSize::UseProvided(Some(decompressed_len as u64)),
+        ..Options::default()
+    };
+    lzma_rs::lzma_decompress_with_options(&mut io::BufReader::new(data), &mut decompressed, &options)
+        .map_err(|err| invalid_data(err.to_string()))?;
+
+    Ok(Buffer::from(decompressed).with_compression(Compression::Lzma))
+}
+
+fn invalid_data(message: String) -> crate::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
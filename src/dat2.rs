@@ -0,0 +1,333 @@
+//! Bounds checked reads from `main_file_cache.dat2`.
+
+use std::{borrow::Cow, fs::File, io::Write, path::Path};
+
+#[cfg(all(feature = "mmap", not(feature = "safe-io")))]
+use memmap2::Mmap;
+
+use crate::error::ReadError;
+use file_store::FileStore;
+use runefs::{
+    codec::{Buffer, Encoded},
+    error::{Error as RuneFsError, ParseError},
+    ArchiveRef, Sector, SectorHeaderSize, SECTOR_DATA_SIZE, SECTOR_EXPANDED_DATA_SIZE,
+    SECTOR_EXPANDED_HEADER_SIZE, SECTOR_HEADER_SIZE, SECTOR_SIZE,
+};
+
+mod file_store;
+
+/// Largest up front reservation for a single read, longer archives grow the
+/// buffer as their sectors are read.
+const MAX_RESERVED_LEN: usize = 4 * 1024 * 1024;
+
+/// Sector reads over the data file, memory mapped or through positioned
+/// reads with the `safe-io` feature and for caches that are written to.
+///
+/// Unlike [`runefs::Dat2`] every sector is checked against the bounds of the
+/// file, so corrupted idx entries or sector chains fail with an error.
+#[derive(Debug)]
+pub(crate) struct Dat2(Store);
+
+impl Dat2 {
+    pub fn new<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        Ok(Self(Store::open(File::open(path.as_ref())?)?))
+    }
+
+    /// Opens the data file for positioned reads even when it would be memory
+    /// mapped, so it can be written to while open.
+    pub fn unmapped<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let store = FileStore::open(File::open(path.as_ref())?)?;
+        #[cfg(all(feature = "mmap", not(feature = "safe-io")))]
+        let store = Store::File(store);
+
+        Ok(Self(store))
+    }
+
+    /// Whether reads can borrow from a memory map.
+    #[cfg(test)]
+    pub fn is_mapped(&self) -> bool {
+        self.0.borrow(0, 0).is_some()
+    }
+
+    pub fn read(&self, archive: &ArchiveRef) -> crate::Result<Buffer<Encoded>> {
+        let mut buffer = Vec::new();
+        self.read_into_vec(archive, &mut buffer)?;
+
+        Ok(Buffer::from(buffer))
+    }
+
+    /// Appends the archive to `buffer`, copying every sector straight into the
+    /// reserved capacity instead of going through [`Write`].
+    pub fn read_into_vec(&self, archive: &ArchiveRef, buffer: &mut Vec<u8>) -> crate::Result<()> {
+        buffer.reserve(self.reserved_len(archive));
+        self.walk(archive, |_, data| {
+            buffer.extend_from_slice(data);
+            Ok(())
+        })
+    }
+
+    /// The archive as a slice of the data file when it fits in a single sector
+    /// and the store is memory mapped, otherwise read into an owned buffer.
+    pub fn read_ref(&self, archive: &ArchiveRef) -> crate::Result<Cow<'_, [u8]>> {
+        let header_size = SectorHeaderSize::from(archive);
+        let (header_len, data_len) = layout(&header_size);
+        if archive.length == 0 || archive.length > data_len {
+            return Ok(Cow::Owned(self.read(archive)?.finalize()));
+        }
+
+        let block = archive
+            .sector
+            .checked_mul(SECTOR_SIZE)
+            .and_then(|offset| self.0.borrow(offset, header_len + archive.length));
+        let Some(block) = block else {
+            return Ok(Cow::Owned(self.read(archive)?.finalize()));
+        };
+        let sector = Sector::new(block, &header_size)
+            .map_err(|_| RuneFsError::Parse(ParseError::Sector(archive.sector)))?;
+        sector
+            .header
+            .validate(archive.id, 0, archive.index_id)
+            .map_err(RuneFsError::Read)?;
+
+        Ok(Cow::Borrowed(sector.data_block))
+    }
+
+    pub fn read_into_writer<W: Write>(&self, archive: &ArchiveRef, writer: &mut W) -> crate::Result<()> {
+        self.walk(archive, |_, data| writer.write_all(data))
+    }
+
+    /// Whether the sector holds nothing but zeros, like the space of an
+    /// archive that was deleted without rewriting the idx files.
+    ///
+    /// Sectors past the end of the file are not zeroed, reading them fails.
+    pub fn is_zeroed(&self, sector: usize) -> bool {
+        let mut scratch = [0; SECTOR_SIZE];
+        let block = sector.checked_mul(SECTOR_SIZE).and_then(|offset| {
+            let len = self.0.len().checked_sub(offset)?.min(SECTOR_SIZE);
+            self.0.get(offset, len, &mut scratch).ok().flatten()
+        });
+
+        block.is_some_and(|block| !block.is_empty() && block.iter().all(|&byte| byte == 0))
+    }
+
+    /// Every sector of the archive's chain in order, validated like a read.
+    pub fn sectors(&self, archive: &ArchiveRef) -> crate::Result<Vec<usize>> {
+        let (_, data_len) = layout(&SectorHeaderSize::from(archive));
+        let file_sectors = self.0.len().div_ceil(SECTOR_SIZE);
+        let mut sectors = Vec::with_capacity(archive.length.div_ceil(data_len).min(file_sectors));
+        self.walk(archive, |sector, _| {
+            sectors.push(sector);
+            Ok(())
+        })?;
+
+        Ok(sectors)
+    }
+
+    /// Follows the archive's sector chain, handing every sector and its data block to `f`.
+    fn walk<F>(&self, archive: &ArchiveRef, f: F) -> crate::Result<()>
+    where
+        F: FnMut(usize, &[u8]) -> std::io::Result<()>,
+    {
+        match self.follow(archive, f)? {
+            Ok(()) => Ok(()),
+            Err(ChainBreak::Short { read, .. }) => Err(ReadError::LengthMismatch {
+                idx: archive.index_id,
+                arc: archive.id,
+                expected: archive.length,
+                actual: read,
+            }
+            .into()),
+            Err(ChainBreak::Header { error: None, .. }) => {
+                Err(RuneFsError::Parse(ParseError::Sector(archive.sector)).into())
+            }
+            Err(ChainBreak::Header {
+                error: Some(error), ..
+            }) => Err(RuneFsError::Read(error).into()),
+        }
+    }
+
+    /// Follows the archive's sector chain like [`walk`](Dat2::walk), telling
+    /// where it breaks instead of failing with a read error.
+    ///
+    /// Only errors of the store or of `f` are returned as errors.
+    pub fn follow<F>(
+        &self,
+        archive: &ArchiveRef,
+        mut f: F,
+    ) -> std::io::Result<Result<(), ChainBreak>>
+    where
+        F: FnMut(usize, &[u8]) -> std::io::Result<()>,
+    {
+        let header_size = SectorHeaderSize::from(archive);
+        let (header_len, data_len) = layout(&header_size);
+
+        let mut current = archive.sector;
+        let mut remaining = archive.length;
+        let mut chunk = 0;
+        let mut scratch = [0; SECTOR_SIZE];
+        while remaining > 0 {
+            let short = ChainBreak::Short {
+                sector: current,
+                read: archive.length - remaining,
+            };
+            // the chain ended or runs past the end of the file before the archive is complete.
+            if chunk > 0 && current == 0 {
+                return Ok(Err(short));
+            }
+            let len = remaining.min(data_len);
+            let Some(offset) = current.checked_mul(SECTOR_SIZE) else {
+                return Ok(Err(short));
+            };
+            let Some(block) = self.0.get(offset, header_len + len, &mut scratch)? else {
+                return Ok(Err(short));
+            };
+            let Ok(sector) = Sector::new(block, &header_size) else {
+                return Ok(Err(ChainBreak::Header {
+                    sector: current,
+                    chunk,
+                    error: None,
+                }));
+            };
+
+            if let Err(error) = sector.header.validate(archive.id, chunk, archive.index_id) {
+                return Ok(Err(ChainBreak::Header {
+                    sector: current,
+                    chunk,
+                    error: Some(error),
+                }));
+            }
+            f(current, sector.data_block)?;
+
+            remaining -= len;
+            current = sector.header.next;
+            chunk += 1;
+        }
+
+        Ok(Ok(()))
+    }
+
+    /// The idx length, bounded by what the sectors from the first one to the end of the file can hold.
+    fn reserved_len(&self, archive: &ArchiveRef) -> usize {
+        let (_, data_len) = layout(&SectorHeaderSize::from(archive));
+        let sectors = self.0.len().div_ceil(SECTOR_SIZE).saturating_sub(archive.sector);
+
+        archive
+            .length
+            .min(sectors.saturating_mul(data_len))
+            .min(MAX_RESERVED_LEN)
+    }
+}
+
+/// Where a sector chain breaks, see [`Dat2::follow`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub(crate) enum ChainBreak {
+    /// The chain ends at `sector`, or it is past the end of the file, after
+    /// `read` bytes of the archive.
+    Short { sector: usize, read: usize },
+    /// The sector holding `chunk` can't be parsed, or its header belongs to
+    /// another archive, chunk or index.
+    Header {
+        sector: usize,
+        chunk: usize,
+        error: Option<runefs::error::ReadError>,
+    },
+}
+
+/// Header and data length of a single sector.
+const fn layout(header_size: &SectorHeaderSize) -> (usize, usize) {
+    match header_size {
+        SectorHeaderSize::Normal => (SECTOR_HEADER_SIZE, SECTOR_DATA_SIZE),
+        SectorHeaderSize::Expanded => (SECTOR_EXPANDED_HEADER_SIZE, SECTOR_EXPANDED_DATA_SIZE),
+    }
+}
+
+/// Storage behind [`Dat2`], picked at compile time.
+#[cfg(any(feature = "safe-io", not(feature = "mmap")))]
+type Store = FileStore;
+
+/// The data file mapped into memory, the default, or read through a
+/// [`FileStore`] when the cache is written to.
+#[cfg(all(feature = "mmap", not(feature = "safe-io")))]
+#[derive(Debug)]
+enum Store {
+    Mapped(MmapStore),
+    File(FileStore),
+}
+
+#[cfg(all(feature = "mmap", not(feature = "safe-io")))]
+impl Store {
+    fn open(file: File) -> std::io::Result<Self> {
+        MmapStore::open(file).map(Self::Mapped)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Self::Mapped(store) => store.len(),
+            Self::File(store) => store.len(),
+        }
+    }
+
+    #[inline]
+    fn get<'a>(
+        &'a self,
+        offset: usize,
+        len: usize,
+        scratch: &'a mut [u8; SECTOR_SIZE],
+    ) -> std::io::Result<Option<&'a [u8]>> {
+        match self {
+            Self::Mapped(store) => store.get(offset, len, scratch),
+            Self::File(store) => store.get(offset, len, scratch),
+        }
+    }
+
+    #[inline]
+    fn borrow(&self, offset: usize, len: usize) -> Option<&[u8]> {
+        match self {
+            Self::Mapped(store) => store.borrow(offset, len),
+            Self::File(store) => store.borrow(offset, len),
+        }
+    }
+}
+
+/// The data file mapped into memory, the default.
+#[cfg(all(feature = "mmap", not(feature = "safe-io")))]
+#[derive(Debug)]
+struct MmapStore(Mmap);
+
+#[cfg(all(feature = "mmap", not(feature = "safe-io")))]
+impl MmapStore {
+    fn open(file: File) -> std::io::Result<Self> {
+        // SAFETY: mapping is only undefined behaviour when the file is changed
+        // while mapped. The crate only writes to data files of caches opened
+        // with `Cache::open_rw`, which are never mapped (see `Dat2::unmapped`),
+        // and caches aren't meant to be updated by others while a server reads
+        // them, see the crate level safety docs.
+        Ok(Self(unsafe { Mmap::map(&file)? }))
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The `len` bytes at `offset`, `None` when they run past the end of the file.
+    #[inline]
+    fn get<'a>(
+        &'a self,
+        offset: usize,
+        len: usize,
+        _scratch: &'a mut [u8; SECTOR_SIZE],
+    ) -> std::io::Result<Option<&'a [u8]>> {
+        Ok(self.borrow(offset, len))
+    }
+
+    /// The `len` bytes at `offset` straight from the map, `None` when they run
+    /// past the end of the file.
+    #[inline]
+    fn borrow(&self, offset: usize, len: usize) -> Option<&[u8]> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.0.get(offset..end))
+    }
+}
@@ -0,0 +1,188 @@
+//! Data file reads without a memory map, see [`FileStore`].
+
+#[cfg(test)]
+use std::path::Path;
+use std::{
+    fs::File,
+    io,
+    sync::{Mutex, PoisonError},
+};
+
+use runefs::SECTOR_SIZE;
+
+/// Sectors per block of the [`FileStore`] cache.
+const BLOCK_SECTORS: usize = 64;
+const BLOCK_LEN: usize = BLOCK_SECTORS * SECTOR_SIZE;
+/// Blocks kept around by the [`FileStore`], about 2 MiB.
+const CACHED_BLOCKS: usize = 64;
+
+/// Positioned reads from the data file through a small LRU cache of
+/// sector-aligned blocks, without `unsafe`.
+///
+/// Archives are mostly stored in consecutive sectors, so sequential reads hit
+/// the cache for all but the first sector of a block.
+#[derive(Debug)]
+pub(super) struct FileStore {
+    file: File,
+    len: usize,
+    blocks: Mutex<BlockCache>,
+}
+
+#[derive(Debug, Default)]
+struct BlockCache {
+    blocks: Vec<CachedBlock>,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct CachedBlock {
+    index: usize,
+    data: Box<[u8]>,
+    last_used: u64,
+}
+
+impl FileStore {
+    pub fn open(file: File) -> io::Result<Self> {
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "data file too large"))?;
+
+        Ok(Self {
+            file,
+            len,
+            blocks: Mutex::new(BlockCache::default()),
+        })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The `len` bytes at `offset`, copied into `scratch`. `None` when they
+    /// run past the end of the file.
+    ///
+    /// Reads never span sectors, so `offset` is sector-aligned and `len` at most
+    /// a sector long.
+    pub fn get<'a>(
+        &self,
+        offset: usize,
+        len: usize,
+        scratch: &'a mut [u8; SECTOR_SIZE],
+    ) -> io::Result<Option<&'a [u8]>> {
+        debug_assert!(offset.is_multiple_of(SECTOR_SIZE) && len <= SECTOR_SIZE);
+        if offset.checked_add(len).is_none_or(|end| end > self.len) {
+            return Ok(None);
+        }
+
+        let index = offset / BLOCK_LEN;
+        let start = offset % BLOCK_LEN;
+        // a poisoned lock only means another read failed half way, the cached blocks are intact.
+        let mut cache = self.blocks.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.tick += 1;
+        let tick = cache.tick;
+
+        let position = match cache.blocks.iter().position(|block| block.index == index) {
+            Some(position) => position,
+            None => {
+                let block_offset = index * BLOCK_LEN;
+                let mut data = vec![0; BLOCK_LEN.min(self.len - block_offset)].into_boxed_slice();
+                read_at(&self.file, &mut data, block_offset as u64)?;
+
+                let block = CachedBlock {
+                    index,
+                    data,
+                    last_used: tick,
+                };
+                if cache.blocks.len() < CACHED_BLOCKS {
+                    cache.blocks.push(block);
+                    cache.blocks.len() - 1
+                } else {
+                    let (oldest, _) = cache
+                        .blocks
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, block)| block.last_used)
+                        .expect("the cache holds blocks");
+                    cache.blocks[oldest] = block;
+                    oldest
+                }
+            }
+        };
+
+        let block = &mut cache.blocks[position];
+        block.last_used = tick;
+        scratch[..len].copy_from_slice(&block.data[start..start + len]);
+
+        Ok(Some(&scratch[..len]))
+    }
+
+    /// Always `None`, nothing is mapped so every read copies through [`get`](FileStore::get).
+    #[inline]
+    pub fn borrow(&self, _offset: usize, _len: usize) -> Option<&[u8]> {
+        None
+    }
+}
+
+/// Fills `buffer` from `offset` without moving the file cursor.
+#[cfg(unix)]
+fn read_at(file: &File, buffer: &mut [u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buffer, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, mut buffer: &mut [u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+
+    while !buffer.is_empty() {
+        match file.seek_read(buffer, offset) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => {
+                buffer = &mut buffer[read..];
+                offset += read as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Other platforms seek, which is fine as reads are serialized by the block cache lock.
+#[cfg(not(any(unix, windows)))]
+fn read_at(mut file: &File, buffer: &mut [u8], offset: u64) -> io::Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(buffer)
+}
+
+#[test]
+fn file_store_matches_file() -> crate::Result<()> {
+    let path = Path::new("./data/osrs_cache").join(runefs::MAIN_DATA);
+    let data = std::fs::read(&path)?;
+    let store = FileStore::open(File::open(&path)?)?;
+    assert_eq!(store.len(), data.len());
+
+    // strided over more blocks than are cached, twice to read evicted blocks again.
+    let sectors = data.len() / SECTOR_SIZE;
+    let mut scratch = [0; SECTOR_SIZE];
+    for sector in (0..sectors).step_by(97).chain((0..sectors).step_by(89)) {
+        let offset = sector * SECTOR_SIZE;
+        let block = store.get(offset, SECTOR_SIZE, &mut scratch)?;
+        assert_eq!(block, Some(&data[offset..offset + SECTOR_SIZE]));
+    }
+    assert_eq!(store.blocks.lock().unwrap().blocks.len(), CACHED_BLOCKS);
+    assert!(store.borrow(0, SECTOR_SIZE).is_none());
+
+    let last = (data.len() - 1) / SECTOR_SIZE * SECTOR_SIZE;
+    assert!(store.get(last, data.len() - last, &mut scratch)?.is_some());
+    assert!(store
+        .get(last, data.len() - last + 1, &mut scratch)?
+        .is_none());
+    assert!(store
+        .get(usize::MAX / SECTOR_SIZE * SECTOR_SIZE, 1, &mut scratch)?
+        .is_none());
+
+    Ok(())
+}
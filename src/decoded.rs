@@ -0,0 +1,104 @@
+//! Least recently used decoded archives, see [`CacheBuilder::decoded_cache_size`](crate::CacheBuilder::decoded_cache_size).
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// Index and archive id.
+type Key = (u8, u32);
+
+/// Decoded archives by index and archive id, bounded by their total length.
+#[derive(Debug, Default)]
+pub(crate) struct DecodedArchives {
+    capacity: usize,
+    len: usize,
+    tick: u64,
+    /// Archives with the tick of their last use.
+    archives: HashMap<Key, (Arc<Vec<u8>>, u64)>,
+    /// Keys by the tick of their last use, oldest first.
+    recency: BTreeMap<u64, Key>,
+}
+
+impl DecodedArchives {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn get(&mut self, key: Key) -> Option<Arc<Vec<u8>>> {
+        self.tick += 1;
+        let (buffer, last_used) = self.archives.get_mut(&key)?;
+        self.recency.remove(last_used);
+        self.recency.insert(self.tick, key);
+        *last_used = self.tick;
+
+        Some(Arc::clone(buffer))
+    }
+
+    /// Keeps the archive, dropping the least recently used ones until it fits.
+    ///
+    /// Archives larger than the capacity aren't kept.
+    pub(crate) fn insert(&mut self, key: Key, buffer: Arc<Vec<u8>>) {
+        if buffer.len() > self.capacity {
+            return;
+        }
+        self.remove(key);
+        while self.len + buffer.len() > self.capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some((evicted, _)) = self.archives.remove(&oldest) {
+                self.len -= evicted.len();
+            }
+        }
+
+        self.tick += 1;
+        self.len += buffer.len();
+        self.recency.insert(self.tick, key);
+        self.archives.insert(key, (buffer, self.tick));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.len = 0;
+        self.archives.clear();
+        self.recency.clear();
+    }
+
+    /// Total decoded length of the kept archives.
+    #[inline]
+    pub(crate) const fn len(&self) -> usize {
+        self.len
+    }
+
+    fn remove(&mut self, key: Key) {
+        if let Some((buffer, last_used)) = self.archives.remove(&key) {
+            self.len -= buffer.len();
+            self.recency.remove(&last_used);
+        }
+    }
+}
+
+#[test]
+fn evicts_least_recently_used_bytes() {
+    let mut decoded = DecodedArchives::new(10);
+    decoded.insert((2, 1), Arc::new(vec![0; 4]));
+    decoded.insert((2, 2), Arc::new(vec![0; 4]));
+    assert!(decoded.get((2, 1)).is_some());
+
+    // evicts (2, 2) rather than the more recently used (2, 1).
+    decoded.insert((5, 1), Arc::new(vec![0; 6]));
+    assert_eq!(decoded.len(), 10);
+    assert!(decoded.get((2, 2)).is_none());
+    assert!(decoded.get((2, 1)).is_some());
+
+    decoded.insert((5, 2), Arc::new(vec![0; 11]));
+    assert!(decoded.get((5, 2)).is_none());
+    assert_eq!(decoded.len(), 10);
+
+    decoded.clear();
+    assert_eq!(decoded.len(), 0);
+    assert!(decoded.get((5, 1)).is_none());
+}
@@ -0,0 +1,138 @@
+//! Read-time decryption of archives.
+//!
+//! Location archives of index 5 are enciphered with XTEA, every region with
+//! keys of its own, and some private caches encrypt archives of other indices
+//! as well. A [`DecryptionRegistry`] attached to the cache through
+//! [`CacheBuilder::decryption`](crate::CacheBuilder::decryption) tells
+//! [`Cache::read_decoded`](crate::Cache::read_decoded) and the loaders how to
+//! decipher these, so the keys don't have to be passed to every call.
+//!
+//! # Example
+//!
+//! ```
+//! use rscache::{decryption::DecryptionRegistry, loader::osrs::LocationLoader, Cache};
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! let mut registry = DecryptionRegistry::new();
+//! registry.insert_region(12850, [3030157619, 2364842415, 3297319647, 1973582566]);
+//!
+//! let cache = Cache::builder("./data/osrs_cache").decryption(registry).open()?;
+//! let mut location_loader = LocationLoader::new(&cache);
+//! let lumbridge = location_loader.load_registered(12850)?;
+//!
+//! assert_eq!(lumbridge.data.len(), 4730);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+
+use runefs::codec::{Buffer, Decoded};
+
+use crate::{codec, util::djd2};
+
+/// Index holding the map and location archives.
+const MAP_INDEX: u8 = 5;
+
+/// How an archive container is deciphered before it is decoded.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+#[non_exhaustive]
+pub enum Decryption {
+    /// XTEA over everything after the 5-byte container header.
+    Xtea([u32; 4]),
+}
+
+impl Decryption {
+    /// Deciphers and decodes a raw container.
+    ///
+    /// # Errors
+    ///
+    /// See [`codec::decode_with_keys`].
+    pub fn decode(&self, buffer: &[u8]) -> crate::Result<Buffer<Decoded>> {
+        match self {
+            Self::Xtea(keys) => codec::decode_with_keys(buffer, keys),
+        }
+    }
+}
+
+/// The archives of an index a [`Decryption`] applies to.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+enum Archives {
+    Id(u32),
+    /// Archive names are only stored as [hashes](djd2::hash), so only exact
+    /// names can be matched.
+    Name(i32),
+    All,
+}
+
+/// Decryptions keyed by index and archive, see the [module docs](self).
+///
+/// A registration for an archive id takes precedence over one for its name,
+/// which takes precedence over one for the whole index.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct DecryptionRegistry(BTreeMap<(u8, Archives), Decryption>);
+
+impl DecryptionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the decryption of a single archive, returning the one it replaces.
+    pub fn insert(
+        &mut self,
+        index_id: u8,
+        archive_id: u32,
+        decryption: Decryption,
+    ) -> Option<Decryption> {
+        self.0
+            .insert((index_id, Archives::Id(archive_id)), decryption)
+    }
+
+    /// Registers the decryption of the archive with the given name, returning the one it replaces.
+    pub fn insert_named<T: AsRef<str>>(
+        &mut self,
+        index_id: u8,
+        name: T,
+        decryption: Decryption,
+    ) -> Option<Decryption> {
+        self.0
+            .insert((index_id, Archives::Name(djd2::hash(name))), decryption)
+    }
+
+    /// Registers the decryption of every archive in the index, returning the one it replaces.
+    pub fn insert_index(&mut self, index_id: u8, decryption: Decryption) -> Option<Decryption> {
+        self.0.insert((index_id, Archives::All), decryption)
+    }
+
+    /// Registers the XTEA keys of a region's location archive, returning the
+    /// decryption they replace.
+    pub fn insert_region(&mut self, region_id: u16, keys: [u32; 4]) -> Option<Decryption> {
+        let name = format!("l{}_{}", region_id >> 8, region_id & 0xFF);
+
+        self.insert_named(MAP_INDEX, name, Decryption::Xtea(keys))
+    }
+
+    /// The decryption of the archive, `name_hash` is the archive's name hash
+    /// for indices that have named archives.
+    pub fn get(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+        name_hash: Option<i32>,
+    ) -> Option<&Decryption> {
+        self.0
+            .get(&(index_id, Archives::Id(archive_id)))
+            .or_else(|| self.0.get(&(index_id, Archives::Name(name_hash?))))
+            .or_else(|| self.0.get(&(index_id, Archives::All)))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
@@ -0,0 +1,228 @@
+//! Layout of the config index.
+//!
+//! OSRS keeps every config type in its own archive of the [config index](CONFIG_INDEX),
+//! every file of such an archive is a single definition. The archive ids have
+//! been stable since the index was introduced.
+//!
+//! RS3 moved the large config types into indices of their own, see
+//! [`ConfigIndex`] with the `rs3` feature.
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::definition::config_archives::ConfigArchive;
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! let items = cache.read_config(ConfigArchive::Item)?;
+//!
+//! assert_eq!(ConfigArchive::Item.archive_id(), 10);
+//! assert_eq!(ConfigArchive::from_archive_id(10), Some(ConfigArchive::Item));
+//! # Ok(())
+//! # }
+//! ```
+
+/// Index holding the config archives.
+pub const CONFIG_INDEX: u8 = 2;
+
+// Archive ids of the config types, see [`ConfigArchive`].
+pub const UNDERLAYS: u32 = 1;
+pub const IDENTITY_KITS: u32 = 3;
+pub const OVERLAYS: u32 = 4;
+pub const INVENTORIES: u32 = 5;
+pub const OBJECTS: u32 = 6;
+pub const ENUMS: u32 = 8;
+pub const NPCS: u32 = 9;
+pub const ITEMS: u32 = 10;
+pub const PARAMS: u32 = 11;
+pub const SEQUENCES: u32 = 12;
+pub const SPOT_ANIMATIONS: u32 = 13;
+pub const VAR_BITS: u32 = 14;
+pub const VAR_CLIENT_STRINGS: u32 = 15;
+pub const VAR_PLAYERS: u32 = 16;
+pub const VAR_CLIENTS: u32 = 19;
+pub const HIT_SPLATS: u32 = 32;
+pub const HEALTH_BARS: u32 = 33;
+pub const STRUCTS: u32 = 34;
+pub const AREAS: u32 = 35;
+pub const DB_ROWS: u32 = 38;
+pub const DB_TABLES: u32 = 39;
+
+/// A well known archive of the [config index](CONFIG_INDEX).
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ConfigArchive {
+    Underlay,
+    IdentityKit,
+    Overlay,
+    Inventory,
+    /// Objects (locs) placed in the world, see [`ObjectDefinition`](super::osrs::ObjectDefinition).
+    Object,
+    Enum,
+    Npc,
+    Item,
+    Param,
+    Sequence,
+    SpotAnimation,
+    VarBit,
+    VarClientString,
+    VarPlayer,
+    VarClient,
+    HitSplat,
+    HealthBar,
+    Struct,
+    Area,
+    /// Only in revisions from 2022 onwards.
+    DbRow,
+    /// Only in revisions from 2022 onwards.
+    DbTable,
+}
+
+impl ConfigArchive {
+    /// Every archive in ascending id order.
+    pub const ALL: [Self; 21] = [
+        Self::Underlay,
+        Self::IdentityKit,
+        Self::Overlay,
+        Self::Inventory,
+        Self::Object,
+        Self::Enum,
+        Self::Npc,
+        Self::Item,
+        Self::Param,
+        Self::Sequence,
+        Self::SpotAnimation,
+        Self::VarBit,
+        Self::VarClientString,
+        Self::VarPlayer,
+        Self::VarClient,
+        Self::HitSplat,
+        Self::HealthBar,
+        Self::Struct,
+        Self::Area,
+        Self::DbRow,
+        Self::DbTable,
+    ];
+
+    /// Id of the archive in the [config index](CONFIG_INDEX).
+    pub const fn archive_id(self) -> u32 {
+        match self {
+            Self::Underlay => UNDERLAYS,
+            Self::IdentityKit => IDENTITY_KITS,
+            Self::Overlay => OVERLAYS,
+            Self::Inventory => INVENTORIES,
+            Self::Object => OBJECTS,
+            Self::Enum => ENUMS,
+            Self::Npc => NPCS,
+            Self::Item => ITEMS,
+            Self::Param => PARAMS,
+            Self::Sequence => SEQUENCES,
+            Self::SpotAnimation => SPOT_ANIMATIONS,
+            Self::VarBit => VAR_BITS,
+            Self::VarClientString => VAR_CLIENT_STRINGS,
+            Self::VarPlayer => VAR_PLAYERS,
+            Self::VarClient => VAR_CLIENTS,
+            Self::HitSplat => HIT_SPLATS,
+            Self::HealthBar => HEALTH_BARS,
+            Self::Struct => STRUCTS,
+            Self::Area => AREAS,
+            Self::DbRow => DB_ROWS,
+            Self::DbTable => DB_TABLES,
+        }
+    }
+
+    /// The archive with the given id, `None` for archives that aren't well known.
+    pub fn from_archive_id(archive_id: u32) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|archive| archive.archive_id() == archive_id)
+    }
+
+    /// Snake case name of the config type, e.g. `"spot_animation"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Underlay => "underlay",
+            Self::IdentityKit => "identity_kit",
+            Self::Overlay => "overlay",
+            Self::Inventory => "inventory",
+            Self::Object => "object",
+            Self::Enum => "enum",
+            Self::Npc => "npc",
+            Self::Item => "item",
+            Self::Param => "param",
+            Self::Sequence => "sequence",
+            Self::SpotAnimation => "spot_animation",
+            Self::VarBit => "var_bit",
+            Self::VarClientString => "var_client_string",
+            Self::VarPlayer => "var_player",
+            Self::VarClient => "var_client",
+            Self::HitSplat => "hit_splat",
+            Self::HealthBar => "health_bar",
+            Self::Struct => "struct",
+            Self::Area => "area",
+            Self::DbRow => "db_row",
+            Self::DbTable => "db_table",
+        }
+    }
+}
+
+/// A config type stored in an index of its own in RS3 caches.
+///
+/// Definitions are grouped 256 to an archive, the definition id is
+/// `archive_id << 8 | file_id`.
+#[cfg(feature = "rs3")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rs3")))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ConfigIndex {
+    Object,
+    Enum,
+    Npc,
+    Item,
+    Sequence,
+    SpotAnimation,
+    VarBit,
+}
+
+#[cfg(feature = "rs3")]
+impl ConfigIndex {
+    /// Every config index in ascending id order.
+    pub const ALL: [Self; 7] = [
+        Self::Object,
+        Self::Enum,
+        Self::Npc,
+        Self::Item,
+        Self::Sequence,
+        Self::SpotAnimation,
+        Self::VarBit,
+    ];
+
+    pub const fn index_id(self) -> u8 {
+        match self {
+            Self::Object => 16,
+            Self::Enum => 17,
+            Self::Npc => 18,
+            Self::Item => 19,
+            Self::Sequence => 20,
+            Self::SpotAnimation => 21,
+            Self::VarBit => 22,
+        }
+    }
+
+    /// The config type with the given index id.
+    pub fn from_index_id(index_id: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|index| index.index_id() == index_id)
+    }
+
+    /// Snake case name of the config type, the same as its [`ConfigArchive`] counterpart.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Object => ConfigArchive::Object.name(),
+            Self::Enum => ConfigArchive::Enum.name(),
+            Self::Npc => ConfigArchive::Npc.name(),
+            Self::Item => ConfigArchive::Item.name(),
+            Self::Sequence => ConfigArchive::Sequence.name(),
+            Self::SpotAnimation => ConfigArchive::SpotAnimation.name(),
+            Self::VarBit => ConfigArchive::VarBit.name(),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Strings with a variant per client language.
+//!
+//! Definitions store a single string per field, translated clients read their
+//! strings from a cache of their own language. A [`LocalizedString`] collects
+//! the variants of one field across these caches, see
+//! [`ItemLoader::add_translations`](crate::loader::rs3::ItemLoader::add_translations).
+//! Without translations it behaves like the English string it was parsed from.
+//!
+//! # Example
+//!
+//! ```
+//! use rscache::definition::{Language, LocalizedString};
+//!
+//! let mut name = LocalizedString::from("Bronze sword");
+//! name.insert(Language::German, "Bronzeschwert");
+//! assert_eq!(name, "Bronze sword");
+//!
+//! name.select(Language::German);
+//! assert_eq!(name, "Bronzeschwert");
+//! assert_eq!(name.english(), "Bronze sword");
+//!
+//! // missing variants fall back to English.
+//! name.select(Language::French);
+//! assert_eq!(name, "Bronze sword");
+//! ```
+
+use std::{collections::BTreeMap, fmt, ops::Deref};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Client languages, numbered like the client does.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    French,
+    Portuguese,
+    Dutch,
+    Spanish,
+    SpanishMexico,
+}
+
+impl Language {
+    /// Every language in ascending id order.
+    pub const ALL: [Self; 7] = [
+        Self::English,
+        Self::German,
+        Self::French,
+        Self::Portuguese,
+        Self::Dutch,
+        Self::Spanish,
+        Self::SpanishMexico,
+    ];
+
+    pub const fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::English),
+            1 => Some(Self::German),
+            2 => Some(Self::French),
+            3 => Some(Self::Portuguese),
+            4 => Some(Self::Dutch),
+            5 => Some(Self::Spanish),
+            6 => Some(Self::SpanishMexico),
+            _ => None,
+        }
+    }
+}
+
+/// A string with a variant per [`Language`], see the [module docs](self).
+///
+/// Derefs to the variant of the [selected](LocalizedString::select) language,
+/// or the English one when there is no such variant.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct LocalizedString {
+    variants: BTreeMap<Language, String>,
+    selected: Language,
+}
+
+impl LocalizedString {
+    /// A string holding only the English variant.
+    pub fn new<T: Into<String>>(english: T) -> Self {
+        Self::from(english.into())
+    }
+
+    /// The variant of the selected language, English when it has none.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.get(self.selected).unwrap_or_else(|| self.english())
+    }
+
+    /// The English variant, empty when there is none.
+    #[inline]
+    pub fn english(&self) -> &str {
+        self.get(Language::English).unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn get(&self, language: Language) -> Option<&str> {
+        self.variants.get(&language).map(String::as_str)
+    }
+
+    /// Adds the variant of a language, returning the one it replaces.
+    pub fn insert<T: Into<String>>(&mut self, language: Language, text: T) -> Option<String> {
+        self.variants.insert(language, text.into())
+    }
+
+    /// Language of the variant [`as_str`](LocalizedString::as_str) returns.
+    #[inline]
+    pub const fn selected(&self) -> Language {
+        self.selected
+    }
+
+    #[inline]
+    pub fn select(&mut self, language: Language) {
+        self.selected = language;
+    }
+
+    /// Every variant in ascending language order.
+    pub fn variants(&self) -> impl Iterator<Item = (Language, &str)> {
+        self.variants
+            .iter()
+            .map(|(&language, text)| (language, text.as_str()))
+    }
+}
+
+impl From<String> for LocalizedString {
+    fn from(english: String) -> Self {
+        Self {
+            variants: BTreeMap::from([(Language::English, english)]),
+            selected: Language::English,
+        }
+    }
+}
+
+impl From<&str> for LocalizedString {
+    fn from(english: &str) -> Self {
+        Self::from(english.to_owned())
+    }
+}
+
+impl Deref for LocalizedString {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for LocalizedString {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for LocalizedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for LocalizedString {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for LocalizedString {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
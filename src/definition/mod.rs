@@ -1,8 +1,19 @@
 //! Defines RuneScape data structures.
 
+pub mod config_archives;
+pub mod localized;
+pub mod opcodes;
 /// OSRS definitions.
 pub mod osrs;
+pub mod param;
 /// RS3 definitions.
 #[cfg(feature = "rs3")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rs3")))]
 pub mod rs3;
+
+#[doc(inline)]
+pub use localized::{Language, LocalizedString};
+#[doc(inline)]
+pub use opcodes::{annotate, AnnotatedField, DefinitionKind};
+#[doc(inline)]
+pub use param::Value;
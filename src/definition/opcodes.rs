@@ -0,0 +1,636 @@
+//! Opcode tables for the opcode-driven definition formats.
+//!
+//! Every config definition is stored as a list of `opcode, payload` pairs terminated by
+//! opcode `0`. The tables in this module describe, per [`DefinitionKind`], which field
+//! every opcode sets and how its payload is encoded. The parsers [`read`] every payload
+//! through these same tables and only convert the [`FieldValue`] into their field, so
+//! whatever a table says is exactly what the parser will consume. A parser expecting
+//! another type than its table fails with an error instead of misreading the definition.
+//!
+//! The tables are also useful on their own, when a new revision breaks a loader
+//! [`annotate`] splits the raw bytes of a definition into labeled fields up to the first
+//! opcode this crate doesn't know about.
+//!
+//! # Example
+//!
+//! ```
+//! use rscache::definition::opcodes::{self, DefinitionKind, FieldType};
+//!
+//! // opcode 2 (name) "Coins", opcode 11 (stackable), terminator.
+//! let buffer = &[2, 67, 111, 105, 110, 115, 0, 11, 0];
+//! let fields = opcodes::annotate(DefinitionKind::Item, buffer);
+//!
+//! assert_eq!(fields[0].name(), "name");
+//! assert_eq!(fields[0].ty(), Some(FieldType::String));
+//! assert_eq!(fields[1].name(), "stackable");
+//! assert_eq!(fields.len(), 2);
+//! ```
+
+use std::{
+    any,
+    collections::BTreeMap,
+    fmt,
+    io::{self, Read},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{config_archives::ConfigArchive, Value};
+use crate::extension::ReadExt;
+
+/// The definition formats that are described by an opcode table.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum DefinitionKind {
+    Item,
+    Npc,
+    Object,
+}
+
+impl DefinitionKind {
+    /// Every kind.
+    pub const ALL: [Self; 3] = [Self::Item, Self::Npc, Self::Object];
+
+    /// The config archive holding the definitions of this kind.
+    #[inline]
+    pub const fn config_archive(self) -> ConfigArchive {
+        match self {
+            Self::Item => ConfigArchive::Item,
+            Self::Npc => ConfigArchive::Npc,
+            Self::Object => ConfigArchive::Object,
+        }
+    }
+
+    /// The opcode table belonging to this kind.
+    #[inline]
+    pub const fn opcodes(self) -> &'static [Opcode] {
+        match self {
+            Self::Item => ITEM,
+            Self::Npc => NPC,
+            Self::Object => OBJECT,
+        }
+    }
+}
+
+/// Encoding of the payload that follows an opcode.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum FieldType {
+    /// No payload, the presence of the opcode is the value.
+    Flag,
+    U8,
+    I8,
+    U16,
+    I16,
+    I32,
+    /// 0-terminated string.
+    String,
+    /// Two `u16` values.
+    U16Pair,
+    /// A `u16` followed by a `u8`.
+    U16U8,
+    /// Four `u16` values.
+    U16Quad,
+    /// `u8` length followed by that many `u16` values.
+    U16List,
+    /// `u8` length followed by that many `(u16, u8)` pairs.
+    U16U8List,
+    /// `u8` length followed by that many `(u16, u16)` pairs.
+    U16PairList,
+    /// Varbit id, varp id, `u8` length and length + 1 `u16` morph ids.
+    Morphs,
+    /// Same as `Morphs` but with an extra `u16` default morph before the length.
+    MorphsWithDefault,
+    /// Two `u16` values, a `u8` distance, `u8` length and that many `u16` sound ids.
+    AmbientSounds,
+    /// `u8` length followed by that many `(is_string: u8, key: u24, value)` entries.
+    Params,
+}
+
+impl FieldType {
+    /// Reads the payload of this type.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the reader runs out of bytes before the payload is complete.
+    pub fn read<R: Read>(self, reader: &mut R) -> io::Result<FieldValue> {
+        let value = match self {
+            Self::Flag => FieldValue::Flag,
+            Self::U8 => FieldValue::U8(reader.read_u8()?),
+            Self::I8 => FieldValue::I8(reader.read_i8()?),
+            Self::U16 => FieldValue::U16(reader.read_u16()?),
+            Self::I16 => FieldValue::I16(reader.read_i16()?),
+            Self::I32 => FieldValue::I32(reader.read_i32()?),
+            Self::String => FieldValue::String(reader.read_string()?),
+            Self::U16Pair => FieldValue::U16Pair((reader.read_u16()?, reader.read_u16()?)),
+            Self::U16U8 => FieldValue::U16U8((reader.read_u16()?, reader.read_u8()?)),
+            Self::U16Quad => FieldValue::U16Quad([
+                reader.read_u16()?,
+                reader.read_u16()?,
+                reader.read_u16()?,
+                reader.read_u16()?,
+            ]),
+            Self::U16List => {
+                let len = reader.read_u8()?;
+                let mut values = Vec::with_capacity(len.into());
+                for _ in 0..len {
+                    values.push(reader.read_u16()?);
+                }
+                FieldValue::U16List(values)
+            }
+            Self::U16U8List => {
+                let len = reader.read_u8()?;
+                let mut values = Vec::with_capacity(len.into());
+                for _ in 0..len {
+                    values.push((reader.read_u16()?, reader.read_u8()?));
+                }
+                FieldValue::U16U8List(values)
+            }
+            Self::U16PairList => {
+                let len = reader.read_u8()?;
+                let mut values = Vec::with_capacity(len.into());
+                for _ in 0..len {
+                    values.push((reader.read_u16()?, reader.read_u16()?));
+                }
+                FieldValue::U16PairList(values)
+            }
+            Self::Morphs | Self::MorphsWithDefault => {
+                let varbit = reader.read_u16()?;
+                let varp = reader.read_u16()?;
+                let default = if self == Self::MorphsWithDefault {
+                    Some(reader.read_u16()?)
+                } else {
+                    None
+                };
+                let len = reader.read_u8()?;
+                let mut ids = Vec::with_capacity(usize::from(len) + 1);
+                for _ in 0..=len {
+                    ids.push(reader.read_u16()?);
+                }
+                FieldValue::Morphs((varbit, varp, default, ids))
+            }
+            Self::AmbientSounds => {
+                let first = reader.read_u16()?;
+                let second = reader.read_u16()?;
+                let distance = reader.read_u8()?;
+                let len = reader.read_u8()?;
+                let mut ids = Vec::with_capacity(len.into());
+                for _ in 0..len {
+                    ids.push(reader.read_u16()?);
+                }
+                FieldValue::AmbientSounds((first, second, distance, ids))
+            }
+            Self::Params => {
+                let len = reader.read_u8()?;
+                let mut params = BTreeMap::new();
+                for _ in 0..len {
+                    let is_string = reader.read_u8()? == 1;
+                    let key = reader.read_u24()?;
+                    let value = if is_string {
+                        Value::String(reader.read_string()?)
+                    } else {
+                        Value::Int(reader.read_i32()?)
+                    };
+                    params.insert(key, value);
+                }
+                FieldValue::Params(params)
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Reads past the payload of this type.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the reader runs out of bytes before the payload is complete.
+    pub fn skip<R: Read>(self, reader: &mut R) -> io::Result<()> {
+        self.read(reader).map(drop)
+    }
+}
+
+/// A payload read according to its [`FieldType`], converts into the matching
+/// Rust type with `TryFrom`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum FieldValue {
+    Flag,
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    I32(i32),
+    String(String),
+    U16Pair((u16, u16)),
+    U16U8((u16, u8)),
+    U16Quad([u16; 4]),
+    U16List(Vec<u16>),
+    U16U8List(Vec<(u16, u8)>),
+    U16PairList(Vec<(u16, u16)>),
+    /// Varbit id, varp id, the default morph of `MorphsWithDefault` and the
+    /// morph ids, `u16::MAX` for none.
+    Morphs((u16, u16, Option<u16>, Vec<u16>)),
+    AmbientSounds((u16, u16, u8, Vec<u16>)),
+    Params(BTreeMap<u32, Value>),
+}
+
+macro_rules! impl_try_from_field_value {
+    ($($variant:ident => $ty:ty),+ $(,)?) => {
+        $(
+            impl TryFrom<FieldValue> for $ty {
+                type Error = FieldValue;
+
+                #[inline]
+                fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+                    match value {
+                        FieldValue::$variant(value) => Ok(value),
+                        value => Err(value),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_try_from_field_value! {
+    U8 => u8,
+    I8 => i8,
+    U16 => u16,
+    I16 => i16,
+    I32 => i32,
+    String => String,
+    U16Pair => (u16, u16),
+    U16U8 => (u16, u8),
+    U16Quad => [u16; 4],
+    U16List => Vec<u16>,
+    U16U8List => Vec<(u16, u8)>,
+    U16PairList => Vec<(u16, u16)>,
+    Morphs => (u16, u16, Option<u16>, Vec<u16>),
+    AmbientSounds => (u16, u16, u8, Vec<u16>),
+    Params => BTreeMap<u32, Value>,
+}
+
+/// A flag is always set, `true`.
+impl TryFrom<FieldValue> for bool {
+    type Error = FieldValue;
+
+    #[inline]
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        match value {
+            FieldValue::Flag => Ok(true),
+            value => Err(value),
+        }
+    }
+}
+
+/// An opcode and its payload, read through the opcode table by [`read`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Field {
+    pub kind: DefinitionKind,
+    pub opcode: u8,
+    pub entry: &'static Opcode,
+    pub value: FieldValue,
+}
+
+impl Field {
+    /// Converts the payload into the type the parser stores.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error when the table entry has another type.
+    pub fn get<T: TryFrom<FieldValue>>(self) -> io::Result<T> {
+        let Self {
+            kind,
+            opcode,
+            entry,
+            value,
+        } = self;
+
+        T::try_from(value).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{:?} opcode {} ({}) is {:?} in its table but parsed as {}",
+                    kind,
+                    opcode,
+                    entry.field,
+                    entry.ty,
+                    any::type_name::<T>()
+                ),
+            )
+        })
+    }
+}
+
+/// A single entry of an opcode table.
+///
+/// Grouped opcodes like the five action slots share one entry that covers the whole range.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Opcode {
+    pub first: u8,
+    pub last: u8,
+    pub field: &'static str,
+    pub ty: FieldType,
+}
+
+impl Opcode {
+    const fn new(opcode: u8, field: &'static str, ty: FieldType) -> Self {
+        Self::range(opcode, opcode, field, ty)
+    }
+
+    const fn range(first: u8, last: u8, field: &'static str, ty: FieldType) -> Self {
+        Self {
+            first,
+            last,
+            field,
+            ty,
+        }
+    }
+
+    #[inline]
+    pub const fn contains(&self, opcode: u8) -> bool {
+        opcode >= self.first && opcode <= self.last
+    }
+}
+
+/// Finds the table entry for `opcode`.
+pub fn lookup(table: &'static [Opcode], opcode: u8) -> Option<&'static Opcode> {
+    table.iter().find(|entry| entry.contains(opcode))
+}
+
+/// Reads the payload of an opcode as its table entry describes it.
+///
+/// # Errors
+///
+/// Returns an `InvalidData` error if the opcode is not in the table, or any error the
+/// reader produces while reading the payload.
+pub fn read<R: Read>(kind: DefinitionKind, opcode: u8, reader: &mut R) -> io::Result<Field> {
+    let entry = lookup(kind.opcodes(), opcode).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unknown {:?} opcode {}", kind, opcode),
+        )
+    })?;
+
+    Ok(Field {
+        kind,
+        opcode,
+        entry,
+        value: entry.ty.read(reader)?,
+    })
+}
+
+/// Skips the payload of an opcode, see [`read`].
+///
+/// # Errors
+///
+/// Returns an `InvalidData` error if the opcode is not in the table, or any error the
+/// reader produces while skipping the payload.
+pub fn skip<R: Read>(kind: DefinitionKind, opcode: u8, reader: &mut R) -> io::Result<()> {
+    read(kind, opcode, reader).map(drop)
+}
+
+/// An opcode missing from the table, met by a lenient parser.
+///
+/// The payload length of an unknown opcode can't be known, lenient parsers
+/// stop at it and keep every field read before.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct UnknownOpcode {
+    pub kind: DefinitionKind,
+    pub opcode: u8,
+    /// Offset of the opcode byte in the definition buffer.
+    pub offset: usize,
+}
+
+impl fmt::Display for UnknownOpcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown {:?} opcode {} at offset {}, the rest of the definition was skipped",
+            self.kind, self.opcode, self.offset
+        )
+    }
+}
+
+/// Item definitions, config archive 10.
+pub const ITEM: &[Opcode] = &[
+    Opcode::new(1, "inventory_model", FieldType::U16),
+    Opcode::new(2, "name", FieldType::String),
+    Opcode::new(4, "zoom2d", FieldType::U16),
+    Opcode::new(5, "x_an2d", FieldType::U16),
+    Opcode::new(6, "y_an2d", FieldType::U16),
+    Opcode::new(7, "x_offset2d", FieldType::U16),
+    Opcode::new(8, "y_offset2d", FieldType::U16),
+    Opcode::new(9, "unknown_string", FieldType::String),
+    Opcode::new(11, "stackable", FieldType::Flag),
+    Opcode::new(12, "cost", FieldType::I32),
+    Opcode::new(13, "wear_pos1", FieldType::U8),
+    Opcode::new(14, "wear_pos2", FieldType::U8),
+    Opcode::new(16, "members_only", FieldType::Flag),
+    Opcode::new(23, "male_model10", FieldType::U16U8),
+    Opcode::new(24, "male_model1", FieldType::U16),
+    Opcode::new(25, "female_model10", FieldType::U16U8),
+    Opcode::new(26, "female_model1", FieldType::U16),
+    Opcode::new(27, "wear_pos3", FieldType::U8),
+    Opcode::range(30, 34, "options", FieldType::String),
+    Opcode::range(35, 39, "interface_options", FieldType::String),
+    Opcode::new(40, "color_replace", FieldType::U16PairList),
+    Opcode::new(41, "texture_replace", FieldType::U16PairList),
+    Opcode::new(42, "shift_click_drop_index", FieldType::U8),
+    Opcode::new(65, "tradable", FieldType::Flag),
+    Opcode::new(75, "weight", FieldType::I16),
+    Opcode::new(78, "male_model12", FieldType::U16),
+    Opcode::new(79, "female_model12", FieldType::U16),
+    Opcode::new(90, "male_head_model1", FieldType::U16),
+    Opcode::new(91, "female_head_model1", FieldType::U16),
+    Opcode::new(92, "male_head_model2", FieldType::U16),
+    Opcode::new(93, "female_head_model2", FieldType::U16),
+    Opcode::new(94, "category", FieldType::U16),
+    Opcode::new(95, "z_an2d", FieldType::U16),
+    Opcode::new(97, "noted_id", FieldType::U16),
+    Opcode::new(98, "noted_template", FieldType::U16),
+    Opcode::range(100, 109, "stack_variant", FieldType::U16Pair),
+    Opcode::new(110, "resize_x", FieldType::U16),
+    Opcode::new(111, "resize_y", FieldType::U16),
+    Opcode::new(112, "resize_z", FieldType::U16),
+    Opcode::new(113, "ambient", FieldType::I8),
+    Opcode::new(114, "contrast", FieldType::I8),
+    Opcode::new(115, "team", FieldType::U8),
+    Opcode::new(139, "bought_link", FieldType::U16),
+    Opcode::new(140, "bought_template", FieldType::U16),
+    Opcode::new(148, "placeholder_id", FieldType::U16),
+    Opcode::new(149, "placeholder_template", FieldType::U16),
+    Opcode::new(249, "params", FieldType::Params),
+];
+
+/// Npc definitions, config archive 9.
+pub const NPC: &[Opcode] = &[
+    Opcode::new(1, "models", FieldType::U16List),
+    Opcode::new(2, "name", FieldType::String),
+    Opcode::new(12, "size", FieldType::U8),
+    Opcode::new(13, "standing_animation", FieldType::U16),
+    Opcode::new(14, "walking_animation", FieldType::U16),
+    Opcode::new(15, "rotate_left_animation", FieldType::U16),
+    Opcode::new(16, "rotate_right_animation", FieldType::U16),
+    Opcode::new(17, "rotation_animations", FieldType::U16Quad),
+    Opcode::new(18, "category", FieldType::U16),
+    Opcode::range(30, 34, "actions", FieldType::String),
+    Opcode::new(40, "recolor", FieldType::U16PairList),
+    Opcode::new(41, "retexture", FieldType::U16PairList),
+    Opcode::new(60, "chat_head_models", FieldType::U16List),
+    Opcode::range(74, 79, "stats", FieldType::U16),
+    Opcode::new(93, "visible_on_minimap", FieldType::Flag),
+    Opcode::new(95, "combat_level", FieldType::U16),
+    Opcode::new(97, "width_scale", FieldType::U16),
+    Opcode::new(98, "height_scale", FieldType::U16),
+    Opcode::new(99, "render_priority", FieldType::Flag),
+    Opcode::new(100, "ambient", FieldType::U8),
+    Opcode::new(101, "contrast", FieldType::U8),
+    Opcode::new(102, "head_icon", FieldType::U16),
+    Opcode::new(103, "rotate_speed", FieldType::U16),
+    Opcode::new(106, "morphs", FieldType::Morphs),
+    Opcode::new(107, "interactable", FieldType::Flag),
+    Opcode::new(109, "rotate_flag", FieldType::Flag),
+    Opcode::new(111, "pet", FieldType::Flag),
+    Opcode::new(114, "running_animation", FieldType::U16),
+    Opcode::new(115, "running_animations", FieldType::U16Quad),
+    Opcode::new(116, "crawling_animation", FieldType::U16),
+    Opcode::new(117, "crawling_animations", FieldType::U16Quad),
+    Opcode::new(118, "morphs", FieldType::MorphsWithDefault),
+    Opcode::new(122, "follower", FieldType::Flag),
+    Opcode::new(123, "low_priority_follower_ops", FieldType::Flag),
+    Opcode::new(124, "height", FieldType::U16),
+    Opcode::new(249, "params", FieldType::Params),
+];
+
+/// Object definitions, config archive 6.
+pub const OBJECT: &[Opcode] = &[
+    Opcode::new(1, "models", FieldType::U16U8List),
+    Opcode::new(2, "name", FieldType::String),
+    Opcode::new(5, "models", FieldType::U16List),
+    Opcode::new(14, "size_x", FieldType::U8),
+    Opcode::new(15, "size_y", FieldType::U8),
+    Opcode::new(17, "not_solid", FieldType::Flag),
+    Opcode::new(18, "not_blocks_projectile", FieldType::Flag),
+    Opcode::new(19, "wall_or_door", FieldType::U8),
+    Opcode::new(21, "contoured_ground", FieldType::Flag),
+    Opcode::new(22, "merge_normals", FieldType::Flag),
+    Opcode::new(23, "occludes", FieldType::Flag),
+    Opcode::new(24, "animation_id", FieldType::U16),
+    Opcode::new(27, "interact_type", FieldType::Flag),
+    Opcode::new(28, "decor_displacement", FieldType::U8),
+    Opcode::new(29, "ambient", FieldType::U8),
+    Opcode::range(30, 34, "actions", FieldType::String),
+    Opcode::new(39, "contrast", FieldType::U8),
+    Opcode::new(40, "recolor", FieldType::U16PairList),
+    Opcode::new(41, "retexture", FieldType::U16PairList),
+    Opcode::new(61, "category", FieldType::U16),
+    Opcode::new(62, "rotated", FieldType::Flag),
+    Opcode::new(64, "shadow", FieldType::Flag),
+    Opcode::new(65, "model_size_x", FieldType::U16),
+    Opcode::new(66, "model_size_z", FieldType::U16),
+    Opcode::new(67, "model_size_y", FieldType::U16),
+    Opcode::new(68, "map_scene_id", FieldType::U16),
+    Opcode::new(69, "blocking_mask", FieldType::U8),
+    Opcode::new(70, "offset_x", FieldType::U16),
+    Opcode::new(71, "offset_z", FieldType::U16),
+    Opcode::new(72, "offset_y", FieldType::U16),
+    Opcode::new(73, "obstruct_ground", FieldType::Flag),
+    Opcode::new(74, "hollow", FieldType::Flag),
+    Opcode::new(75, "supports_items", FieldType::U8),
+    Opcode::new(77, "morphs", FieldType::Morphs),
+    Opcode::new(78, "ambient_sound", FieldType::U16U8),
+    Opcode::new(79, "ambient_sounds", FieldType::AmbientSounds),
+    Opcode::new(81, "contoured_ground", FieldType::U8),
+    Opcode::new(82, "map_area_id", FieldType::U16),
+    Opcode::new(89, "randomize_animation", FieldType::Flag),
+    Opcode::new(92, "morphs", FieldType::MorphsWithDefault),
+    Opcode::new(249, "params", FieldType::Params),
+];
+
+/// A labeled slice of a raw definition buffer, produced by [`annotate`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct AnnotatedField<'a> {
+    /// Offset of the opcode byte in the buffer.
+    pub offset: usize,
+    pub opcode: u8,
+    /// The table entry of the opcode, `None` if this crate doesn't know it.
+    pub entry: Option<&'static Opcode>,
+    /// The payload bytes, everything that remains for unknown or truncated fields.
+    pub payload: &'a [u8],
+    /// Whether the buffer ended before the payload was complete.
+    pub truncated: bool,
+}
+
+impl<'a> AnnotatedField<'a> {
+    /// The field name from the opcode table, `"unknown"` for unknown opcodes.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.entry.map_or("unknown", |entry| entry.field)
+    }
+
+    #[inline]
+    pub fn ty(&self) -> Option<FieldType> {
+        self.entry.map(|entry| entry.ty)
+    }
+}
+
+impl<'a> fmt::Display for AnnotatedField<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>6} [{:>3}] {}", self.offset, self.opcode, self.name())?;
+        if let Some(ty) = self.ty() {
+            write!(f, ": {:?}", ty)?;
+        }
+        if self.truncated {
+            write!(f, " (truncated)")?;
+        }
+        write!(f, " {:02x?}", self.payload)
+    }
+}
+
+/// Splits a raw definition buffer into its labeled fields.
+///
+/// Annotation stops at the terminating `0` opcode, which is not included. When an opcode
+/// isn't in the table, or its payload is truncated, the last field carries the remainder
+/// of the buffer since nothing after it can be trusted.
+pub fn annotate(kind: DefinitionKind, buffer: &[u8]) -> Vec<AnnotatedField<'_>> {
+    let mut fields = Vec::new();
+    let mut offset = 0;
+
+    while let Some(&opcode) = buffer.get(offset) {
+        if opcode == 0 {
+            break;
+        }
+
+        let start = offset + 1;
+        let mut remaining = &buffer[start..];
+        let entry = lookup(kind.opcodes(), opcode);
+        let complete = match entry {
+            Some(entry) => entry.ty.skip(&mut remaining).is_ok(),
+            None => false,
+        };
+        let end = if complete {
+            buffer.len() - remaining.len()
+        } else {
+            buffer.len()
+        };
+
+        fields.push(AnnotatedField {
+            offset,
+            opcode,
+            entry,
+            payload: &buffer[start..end],
+            truncated: entry.is_some() && !complete,
+        });
+
+        if !complete {
+            break;
+        }
+        offset = end;
+    }
+
+    fields
+}
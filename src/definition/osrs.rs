@@ -1,25 +1,125 @@
+mod area_def;
+mod db_row_def;
+mod db_table_def;
+mod enum_def;
+mod health_bar_def;
+mod hit_splat_def;
+mod identity_kit_def;
+#[allow(clippy::too_many_lines)]
+mod interface_def;
+mod inventory_def;
 #[allow(clippy::too_many_lines)]
 mod item_def;
 mod loc_def;
 mod map_def;
+mod model_def;
 mod npc_def;
 #[allow(clippy::too_many_lines)]
 mod obj_def;
+mod overlay_def;
+mod param_def;
+mod sequence_def;
+mod spot_anim_def;
+mod sprite_def;
+mod struct_def;
+mod underlay_def;
+mod varbit_def;
+mod world_map_def;
 
+pub use area_def::*;
+pub use db_row_def::*;
+pub use db_table_def::*;
+pub use enum_def::*;
+pub use health_bar_def::*;
+pub use hit_splat_def::*;
+pub use identity_kit_def::*;
+pub use interface_def::*;
+pub use inventory_def::*;
 pub use item_def::*;
 pub use loc_def::*;
 pub use map_def::*;
+pub use model_def::*;
 pub use npc_def::*;
 pub use obj_def::*;
+pub use overlay_def::*;
+pub use param_def::*;
+pub use sequence_def::*;
+pub use spot_anim_def::*;
+pub use sprite_def::*;
+pub use struct_def::*;
+pub use underlay_def::*;
+pub use varbit_def::*;
+pub use world_map_def::*;
+
+use std::{
+    collections::BTreeMap,
+    io::{self, BufReader},
+    ops::ControlFlow,
+};
 
-use std::collections::HashMap;
+use super::DefinitionKind;
+use crate::{extension::ReadExt, util, Cache};
+use runefs::error::{Error as RuneFsError, ReadError};
 
-use crate::Cache;
-use runefs::{ArchiveFileGroup, IndexMetadata, REFERENCE_TABLE_ID};
+/// Common interface of every definition.
+///
+/// Besides parsing, the trait is object safe, tools handling every kind of
+/// definition alike can use `dyn Definition`, see [`parse_any`].
+pub trait Definition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self>
+    where
+        Self: Sized;
+
+    /// Id the definition was parsed with.
+    fn id(&self) -> u32;
+
+    /// Display name, `None` for definitions without a name or named `"null"`.
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        None
+    }
 
-/// Marker trait for definitions.
-pub trait Definition: Sized {
-    fn new(id: u16, buffer: &[u8]) -> crate::Result<Self>;
+    /// Serializes the definition, definitions of other crates fail unless they override this.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        Err(<serde_json::Error as serde::ser::Error>::custom(
+            "definition is not serializable",
+        ))
+    }
+}
+
+/// Parses a definition of the given kind, for tools that don't know the kind up front.
+///
+/// # Errors
+///
+/// Fails like the `new` of the kind's definition.
+///
+/// # Examples
+///
+/// ```
+/// use rscache::definition::{osrs, DefinitionKind};
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// // opcode 2 (name) "Coins", terminator.
+/// let buffer = &[2, 67, 111, 105, 110, 115, 0, 0];
+/// let definition = osrs::parse_any(DefinitionKind::Item, 995, buffer)?;
+///
+/// assert_eq!(definition.id(), 995);
+/// assert_eq!(definition.name(), Some("Coins"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_any(
+    kind: DefinitionKind,
+    id: u32,
+    buffer: &[u8],
+) -> crate::Result<Box<dyn Definition>> {
+    Ok(match kind {
+        DefinitionKind::Item => Box::new(ItemDefinition::new(id, buffer)?),
+        DefinitionKind::Npc => Box::new(NpcDefinition::new(id, buffer)?),
+        DefinitionKind::Object => Box::new(ObjectDefinition::new(id, buffer)?),
+    })
 }
 
 /// Adds definition fetching from the cache to every struct that implements `Definition`.
@@ -27,7 +127,7 @@ pub trait Definition: Sized {
 /// The main difference between `fetch_from_index` and `fetch_from_archive`:
 /// - `fetch_from_index` will get only 1 definition from each archive making it a 1:1 relation.
 /// - `fetch_from_archive` will get multiple definitions from each archive making it a N:1 relation
-/// where N is atleast 1.
+///   where N is atleast 1.
 pub trait FetchDefinition: Definition {
     // TODO: finish documentation with example.
     /// Fetches multiple definitions from every archive in the index.
@@ -37,18 +137,15 @@ pub trait FetchDefinition: Definition {
     /// # Errors
     ///
     /// Can return multiple errors: if reading, decoding or parsing definition buffers fail.
-    fn fetch_from_index<D>(cache: &Cache, index_id: u8) -> crate::Result<HashMap<u16, D>>
+    fn fetch_from_index<D>(cache: &Cache, index_id: u8) -> crate::Result<BTreeMap<u32, D>>
     where
         D: Definition,
     {
-        let buffer = cache.read(REFERENCE_TABLE_ID, index_id as u32)?.decode()?;
-        let archives = IndexMetadata::from_buffer(buffer)?;
-        let mut definitions = HashMap::new();
-        for archive in &archives {
-            let buffer = cache.read(index_id, archive.id)?.decode()?;
-
-            definitions.insert(archive.id as u16, D::new(archive.id as u16, &buffer)?);
-        }
+        let mut definitions = BTreeMap::new();
+        Self::for_each_in_index(cache, index_id, |id, definition: D| {
+            definitions.insert(id, definition);
+            ControlFlow::Continue(())
+        })?;
 
         Ok(definitions)
     }
@@ -64,7 +161,7 @@ pub trait FetchDefinition: Definition {
     /// # Examples
     ///
     /// ```
-    /// # use std::collections::HashMap;
+    /// # use std::collections::BTreeMap;
     /// # use rscache::Cache;
     /// use rscache::definition::osrs::{
     ///     FetchDefinition,
@@ -76,7 +173,7 @@ pub trait FetchDefinition: Definition {
     /// let index_id = 2; // Config index.
     /// let archive_id = 10; // Archive containing item definitions.
     ///
-    /// let item_defs: HashMap<u16, ItemDefinition>
+    /// let item_defs: BTreeMap<u32, ItemDefinition>
     ///     = ItemDefinition::fetch_from_archive(&cache, index_id, archive_id)?;
     /// # Ok(())
     /// # }
@@ -85,27 +182,254 @@ pub trait FetchDefinition: Definition {
         cache: &Cache,
         index_id: u8,
         archive_id: u32,
-    ) -> crate::Result<HashMap<u16, D>>
+    ) -> crate::Result<BTreeMap<u32, D>>
     where
         D: Definition,
     {
-        let buffer = cache.read(REFERENCE_TABLE_ID, index_id as u32)?.decode()?;
-        let archives = IndexMetadata::from_buffer(buffer)?;
-        let entry_count = archives[archive_id as usize - 1].entry_count;
-        let buffer = cache.read(index_id, archive_id)?.decode()?;
-
-        let archive_group = ArchiveFileGroup::from_buffer(&buffer, entry_count);
-
-        let mut definitions = HashMap::new();
-        for archive_file in archive_group {
-            definitions.insert(
-                archive_file.id as u16,
-                D::new(archive_file.id as u16, &archive_file.data)?,
-            );
-        }
+        let mut definitions = BTreeMap::new();
+        Self::for_each_in_archive(cache, index_id, archive_id, |id, definition: D| {
+            definitions.insert(id, definition);
+            ControlFlow::Continue(())
+        })?;
 
         Ok(definitions)
     }
+
+    /// Parses the definitions of every archive in the index one by one, without retaining them.
+    ///
+    /// Archives after the one where `f` breaks are never read.
+    ///
+    /// # Errors
+    ///
+    /// See [`fetch_from_index`](FetchDefinition::fetch_from_index).
+    fn for_each_in_index<D, F>(cache: &Cache, index_id: u8, mut f: F) -> crate::Result<()>
+    where
+        D: Definition,
+        F: FnMut(u32, D) -> ControlFlow<()>,
+    {
+        let archives = cache.index_metadata(index_id)?;
+        for archive in &archives.archives {
+            let buffer = cache.read_decoded_shared(index_id, archive.id)?;
+
+            if f(archive.id, D::new(archive.id, &buffer)?).is_break() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses the definitions of a single archive one by one, without retaining them.
+    ///
+    /// Parsing stops as soon as `f` breaks.
+    ///
+    /// # Errors
+    ///
+    /// See [`fetch_from_archive`](FetchDefinition::fetch_from_archive).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::ops::ControlFlow;
+    /// # use rscache::Cache;
+    /// use rscache::definition::osrs::{FetchDefinition, ItemDefinition};
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let mut first_members_item = None;
+    /// ItemDefinition::for_each_in_archive(&cache, 2, 10, |id, item: ItemDefinition| {
+    ///     if item.members_only {
+    ///         first_members_item = Some(id);
+    ///         return ControlFlow::Break(());
+    ///     }
+    ///     ControlFlow::Continue(())
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn for_each_in_archive<D, F>(
+        cache: &Cache,
+        index_id: u8,
+        archive_id: u32,
+        mut f: F,
+    ) -> crate::Result<()>
+    where
+        D: Definition,
+        F: FnMut(u32, D) -> ControlFlow<()>,
+    {
+        let archive = cache
+            .index_metadata(index_id)?
+            .archive(archive_id)
+            .ok_or(RuneFsError::Read(ReadError::ArchiveNotFound {
+                idx: index_id,
+                arc: archive_id,
+            }))?;
+        let buffer = cache.read_decoded_shared(index_id, archive_id)?;
+
+        let files = util::split_group(&buffer, archive.file_ids.len())?;
+
+        // file ids can be sparse, the reference table holds the actual id of every file.
+        for (&id, data) in archive.file_ids.iter().zip(&files) {
+            if f(id, D::new(id, data)?).is_break() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<D: Definition> FetchDefinition for D {}
+
+/// [`FetchDefinition::fetch_from_archive`] for the eager loaders, the files
+/// are parsed in parallel with the `rayon` feature.
+pub(crate) fn fetch_archive<D>(
+    cache: &Cache,
+    index_id: u8,
+    archive_id: u32,
+) -> crate::Result<BTreeMap<u32, D>>
+where
+    D: Definition + Send,
+{
+    #[cfg(not(feature = "rayon"))]
+    return D::fetch_from_archive(cache, index_id, archive_id);
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        let archive = cache
+            .index_metadata(index_id)?
+            .archive(archive_id)
+            .ok_or(RuneFsError::Read(ReadError::ArchiveNotFound {
+                idx: index_id,
+                arc: archive_id,
+            }))?;
+        let buffer = cache.read_decoded_shared(index_id, archive_id)?;
+
+        let files = util::split_group(&buffer, archive.file_ids.len())?;
+
+        archive
+            .file_ids
+            .par_iter()
+            .zip(&files)
+            .map(|(&id, data)| Ok((id, D::new(id, data)?)))
+            .collect()
+    }
+}
+
+/// Error for an opcode a config definition without an [opcode table](super::opcodes)
+/// doesn't know, its payload length can't be known.
+fn unknown_opcode(kind: &str, opcode: u8) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unknown {} opcode {}", kind, opcode),
+    )
+}
+
+/// Morph varbit, varp and ids.
+type Morphs = (Option<u16>, Option<u16>, Vec<Option<u16>>);
+
+/// Reads the morph varbit, varp and ids of the npc and object morph opcodes,
+/// the later form `with_default` carries a default morph.
+///
+/// The ids always get an extra last entry, the default or hidden without one.
+fn read_morphs(
+    reader: &mut BufReader<&[u8]>,
+    with_default: bool,
+) -> io::Result<Morphs> {
+    let varbit = read_morph_id(reader)?;
+    let varp = read_morph_id(reader)?;
+    let default = if with_default {
+        read_morph_id(reader)?
+    } else {
+        None
+    };
+
+    let len = reader.read_u8()? as usize;
+    let mut morphisms = Vec::with_capacity(len + 2);
+    for _ in 0..=len {
+        morphisms.push(read_morph_id(reader)?);
+    }
+    morphisms.push(default);
+
+    Ok((varbit, varp, morphisms))
+}
+
+/// Morph varbit, varp and ids from the payload of a morph opcode, see
+/// [`FieldValue::Morphs`](super::opcodes::FieldValue::Morphs) and [`read_morphs`].
+fn morphs((varbit, varp, default, ids): (u16, u16, Option<u16>, Vec<u16>)) -> Morphs {
+    let mut morphisms: Vec<Option<u16>> = ids.into_iter().map(nullable_id).collect();
+    morphisms.push(default.and_then(nullable_id));
+
+    (nullable_id(varbit), nullable_id(varp), morphisms)
+}
+
+/// A `u16` id where `u16::MAX` (-1) means none.
+fn read_morph_id(reader: &mut BufReader<&[u8]>) -> io::Result<Option<u16>> {
+    Ok(nullable_id(reader.read_u16()?))
+}
+
+#[inline]
+fn nullable_id(id: u16) -> Option<u16> {
+    (id != u16::MAX).then_some(id)
+}
+
+/// The morph for a var value, the last entry for values past the end.
+fn resolve_morph(morphisms: &[Option<u16>], value: usize) -> Option<u16> {
+    let (default, morphisms) = morphisms.split_last()?;
+
+    morphisms.get(value).copied().unwrap_or(*default)
+}
+
+/// Ids stored as big smarts where 32767 stands for none, like sprite and font ids.
+fn read_nullable_smart(reader: &mut BufReader<&[u8]>) -> io::Result<Option<u32>> {
+    let id = reader.read_smart()?;
+
+    Ok((id != 32767).then_some(id))
+}
+
+/// Recolor or retexture `(find, replace)` pairs, prefixed by their count.
+fn read_replacements(reader: &mut BufReader<&[u8]>) -> io::Result<Vec<(u16, u16)>> {
+    let len = reader.read_u8()?;
+    let mut replacements = Vec::with_capacity(len.into());
+    for _ in 0..len {
+        replacements.push((reader.read_u16()?, reader.read_u16()?));
+    }
+
+    Ok(replacements)
+}
+
+/// Hue as a fraction of a turn, saturation and lightness of a 24-bit RGB color,
+/// with the channels scaled by 256 like the client does.
+fn rgb_to_hsl(rgb: u32) -> (f64, f64, f64) {
+    let [_, red, green, blue] = rgb.to_be_bytes();
+    let (red, green, blue) = (
+        f64::from(red) / 256.0,
+        f64::from(green) / 256.0,
+        f64::from(blue) / 256.0,
+    );
+    let min = red.min(green).min(blue);
+    let max = red.max(green).max(blue);
+    let lightness = (min + max) / 2.0;
+
+    if min == max {
+        return (0.0, 0.0, lightness);
+    }
+
+    let delta = max - min;
+    let saturation = if lightness < 0.5 {
+        delta / (max + min)
+    } else {
+        delta / (2.0 - max - min)
+    };
+    let hue = if red == max {
+        (green - blue) / delta
+    } else if green == max {
+        2.0 + (blue - red) / delta
+    } else {
+        4.0 + (red - green) / delta
+    };
+
+    (hue / 6.0, saturation, lightness)
+}
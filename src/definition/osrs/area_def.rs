@@ -0,0 +1,158 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// A world map element, an icon and or label, fetched from the cache through
+/// the [AreaLoader](../../loader/osrs/struct.AreaLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct AreaDefinition {
+    pub id: u32,
+    /// Sprite group of the map icon.
+    pub sprite_id: Option<u32>,
+    /// Sprite group of the map icon while hovered.
+    pub hover_sprite_id: Option<u32>,
+    /// Label drawn on the map, empty for icons without one.
+    pub name: String,
+    /// 24-bit RGB color of the label.
+    pub text_color: u32,
+    /// Font of the label, 0 small, 1 medium and 2 large.
+    pub text_size: u8,
+    /// Raw flags of opcode 7.
+    pub flags: u8,
+    /// Right click options of the element.
+    pub actions: [Option<String>; 5],
+    /// Name shown after the `actions` in the right click menu.
+    pub menu_target: Option<String>,
+    /// Corners of the polygon shaded on the map, relative to the element.
+    pub polygon: Vec<(i16, i16)>,
+    /// Category shared by elements of the same kind, e.g. all bank icons.
+    pub category: Option<u16>,
+    pub horizontal_alignment: Option<u8>,
+    pub vertical_alignment: Option<u8>,
+}
+
+impl AreaDefinition {
+    /// Returns `true` if one of the actions matches `action`, ignoring ASCII case.
+    #[inline]
+    pub fn has_action(&self, action: &str) -> bool {
+        self.actions
+            .iter()
+            .flatten()
+            .any(|candidate| candidate.eq_ignore_ascii_case(action))
+    }
+}
+
+impl Definition for AreaDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let area_def = decode_buffer(id, &mut reader)?;
+
+        Ok(area_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        Some(self.name.as_str()).filter(|name| !name.is_empty() && *name != "null")
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<AreaDefinition> {
+    let mut area_def = AreaDefinition {
+        id,
+        ..AreaDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                area_def.sprite_id = super::read_nullable_smart(reader)?;
+            }
+            2 => {
+                area_def.hover_sprite_id = super::read_nullable_smart(reader)?;
+            }
+            3 => {
+                area_def.name = reader.read_string()?;
+            }
+            4 => {
+                area_def.text_color = reader.read_u24()?;
+            }
+            5 => {
+                reader.read_u24()?;
+            }
+            6 => {
+                area_def.text_size = reader.read_u8()?;
+            }
+            7 => {
+                area_def.flags = reader.read_u8()?;
+            }
+            8 | 28 => {
+                reader.read_u8()?;
+            }
+            10..=14 => {
+                area_def.actions[opcode as usize - 10] = Some(reader.read_string()?);
+            }
+            15 => {
+                let len = reader.read_u8()?;
+                for _ in 0..len {
+                    area_def
+                        .polygon
+                        .push((reader.read_i16()?, reader.read_i16()?));
+                }
+                reader.read_i32()?;
+                let colors = reader.read_u8()?;
+                for _ in 0..colors {
+                    reader.read_i32()?;
+                }
+                for _ in 0..len {
+                    reader.read_u8()?;
+                }
+            }
+            16 => {}
+            17 => {
+                area_def.menu_target = Some(reader.read_string()?);
+            }
+            18 | 25 => {
+                super::read_nullable_smart(reader)?;
+            }
+            19 => {
+                area_def.category = Some(reader.read_u16()?);
+            }
+            21 | 22 => {
+                reader.read_i32()?;
+            }
+            23 => {
+                reader.read_u24()?;
+            }
+            24 => {
+                reader.read_i32()?;
+            }
+            29 => {
+                area_def.horizontal_alignment = Some(reader.read_u8()?);
+            }
+            30 => {
+                area_def.vertical_alignment = Some(reader.read_u8()?);
+            }
+            _ => return Err(super::unknown_opcode("area", opcode)),
+        }
+    }
+
+    Ok(area_def)
+}
@@ -0,0 +1,100 @@
+use std::{collections::BTreeMap, io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{db_table_def, DbColumn, DbTableDefinition, Definition};
+use crate::extension::ReadExt;
+
+/// A row of a database table, fetched from the cache through the
+/// [DbRowLoader](../../loader/osrs/struct.DbRowLoader.html).
+///
+/// Only in revisions from 2022 onwards.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct DbRowDefinition {
+    pub id: u32,
+    /// The [table](DbTableDefinition) of the row.
+    pub table_id: u32,
+    /// Columns by id, columns left out take the table's defaults.
+    pub columns: BTreeMap<u8, DbColumn>,
+}
+
+impl DbRowDefinition {
+    /// The column of the row, or the table's default when the row leaves it out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// use rscache::definition::osrs::{DbColumn, DbRowDefinition, DbTableDefinition, DbValue};
+    ///
+    /// let default = DbColumn {
+    ///     types: vec![0],
+    ///     values: vec![DbValue::Int(7)],
+    /// };
+    /// let table = DbTableDefinition {
+    ///     id: 1,
+    ///     columns: BTreeMap::from([(3, default)]),
+    /// };
+    /// let row = DbRowDefinition {
+    ///     table_id: 1,
+    ///     ..DbRowDefinition::default()
+    /// };
+    ///
+    /// let column = row.column_or_default(&table, 3).unwrap();
+    /// assert_eq!(column.values, [DbValue::Int(7)]);
+    /// ```
+    pub fn column_or_default<'a>(
+        &'a self,
+        table: &'a DbTableDefinition,
+        column_id: u8,
+    ) -> Option<&'a DbColumn> {
+        self.columns
+            .get(&column_id)
+            .or_else(|| table.columns.get(&column_id))
+    }
+}
+
+impl Definition for DbRowDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let db_row_def = decode_buffer(id, &mut reader)?;
+
+        Ok(db_row_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<DbRowDefinition> {
+    let mut db_row_def = DbRowDefinition {
+        id,
+        ..DbRowDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            3 => {
+                db_row_def.columns = db_table_def::read_columns(reader, false)?;
+            }
+            4 => {
+                db_row_def.table_id = reader.read_var_u32()?;
+            }
+            _ => return Err(super::unknown_opcode("db row", opcode)),
+        }
+    }
+
+    Ok(db_row_def)
+}
@@ -0,0 +1,200 @@
+use std::{collections::BTreeMap, io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Definition, Position};
+use crate::extension::ReadExt;
+
+/// Script type id of string columns.
+pub const DB_STRING_TYPE: u16 = 36;
+/// Script type id of coordinate columns.
+pub const DB_COORDINATE_TYPE: u16 = 22;
+
+/// A value of a database column.
+///
+/// Every type besides strings and coordinates is stored as an integer, like
+/// booleans, ids of other configs or enums.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum DbValue {
+    Int(i32),
+    String(String),
+    Coordinate(Position),
+}
+
+impl DbValue {
+    /// The integer, `None` for strings and coordinates.
+    #[inline]
+    pub const fn as_int(&self) -> Option<i32> {
+        match self {
+            Self::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The string, `None` for integers and coordinates.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The coordinate, `None` for integers and strings.
+    #[inline]
+    pub const fn as_coordinate(&self) -> Option<Position> {
+        match self {
+            Self::Coordinate(position) => Some(*position),
+            _ => None,
+        }
+    }
+}
+
+/// A column of a database table or row.
+///
+/// Columns are tuples of one or more types, and hold any number of tuples.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct DbColumn {
+    /// Script type ids of the tuple.
+    pub types: Vec<u16>,
+    /// The tuples one after the other.
+    pub values: Vec<DbValue>,
+}
+
+impl DbColumn {
+    /// The tuples of the column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::{DbColumn, DbValue};
+    ///
+    /// let column = DbColumn {
+    ///     types: vec![0, 36],
+    ///     values: vec![
+    ///         DbValue::Int(1),
+    ///         DbValue::String("Harmony".to_owned()),
+    ///         DbValue::Int(2),
+    ///         DbValue::String("Autumn Voyage".to_owned()),
+    ///     ],
+    /// };
+    ///
+    /// let tuples: Vec<_> = column.tuples().collect();
+    /// assert_eq!(tuples.len(), 2);
+    /// assert_eq!(tuples[1][1].as_str(), Some("Autumn Voyage"));
+    /// ```
+    pub fn tuples(&self) -> impl Iterator<Item = &[DbValue]> {
+        self.values.chunks(self.types.len().max(1))
+    }
+}
+
+/// The schema of a database table, fetched from the cache through the
+/// [DbTableLoader](../../loader/osrs/struct.DbTableLoader.html).
+///
+/// Only in revisions from 2022 onwards.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct DbTableDefinition {
+    pub id: u32,
+    /// Columns by id, the values are the defaults of rows that leave the column out.
+    pub columns: BTreeMap<u8, DbColumn>,
+}
+
+impl Definition for DbTableDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let db_table_def = decode_buffer(id, &mut reader)?;
+
+        Ok(db_table_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<DbTableDefinition> {
+    let mut db_table_def = DbTableDefinition {
+        id,
+        ..DbTableDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                db_table_def.columns = read_columns(reader, true)?;
+            }
+            _ => return Err(super::unknown_opcode("db table", opcode)),
+        }
+    }
+
+    Ok(db_table_def)
+}
+
+/// Reads the columns of a table or a row, ended by 255.
+///
+/// Table columns flag in their id whether they carry default values, row
+/// columns always carry values.
+pub(super) fn read_columns(
+    reader: &mut BufReader<&[u8]>,
+    table: bool,
+) -> io::Result<BTreeMap<u8, DbColumn>> {
+    let mut columns = BTreeMap::new();
+
+    // Number of columns, they are sparse so it's of no use.
+    reader.read_u8()?;
+    loop {
+        let setting = reader.read_u8()?;
+        if setting == 255 {
+            break;
+        }
+
+        let (column_id, has_values) = if table {
+            (setting & 0x7F, setting & 0x80 != 0)
+        } else {
+            (setting, true)
+        };
+
+        let len = reader.read_u8()?;
+        let mut column = DbColumn {
+            types: Vec::with_capacity(len.into()),
+            values: Vec::new(),
+        };
+        for _ in 0..len {
+            column.types.push(reader.read_short_smart()?);
+        }
+
+        if has_values {
+            let tuples = reader.read_short_smart()?;
+            for _ in 0..tuples {
+                for &value_type in &column.types {
+                    column.values.push(read_value(reader, value_type)?);
+                }
+            }
+        }
+
+        columns.insert(column_id, column);
+    }
+
+    Ok(columns)
+}
+
+fn read_value(reader: &mut BufReader<&[u8]>, value_type: u16) -> io::Result<DbValue> {
+    Ok(match value_type {
+        DB_STRING_TYPE => DbValue::String(reader.read_string()?),
+        DB_COORDINATE_TYPE => DbValue::Coordinate(Position::from_packed(reader.read_u32()?)),
+        _ => DbValue::Int(reader.read_i32()?),
+    })
+}
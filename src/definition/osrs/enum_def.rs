@@ -0,0 +1,140 @@
+use std::{collections::BTreeMap, io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::{definition::Value, extension::ReadExt};
+
+/// A cs2 enum mapping integer keys to integer or string values, fetched from
+/// the cache through the [EnumLoader](../../loader/osrs/struct.EnumLoader.html).
+///
+/// Key and value types are cs2 type chars, e.g. `'i'` for integers, `'s'` for
+/// strings, `'o'` for items and `'n'` for npcs.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct EnumDefinition {
+    pub id: u32,
+    pub keytype: char,
+    pub valtype: char,
+    /// Value of missing integer keys.
+    pub default_int: i32,
+    /// Value of missing string keys.
+    pub default_string: String,
+    pub values: BTreeMap<i32, Value>,
+}
+
+impl Default for EnumDefinition {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            keytype: '\0',
+            valtype: '\0',
+            default_int: 0,
+            default_string: "null".to_owned(),
+            values: BTreeMap::new(),
+        }
+    }
+}
+
+impl EnumDefinition {
+    /// The value of `key`, `None` when the enum doesn't have it.
+    #[inline]
+    pub fn get(&self, key: i32) -> Option<&Value> {
+        self.values.get(&key)
+    }
+
+    /// The integer value of `key`, `default_int` when it's missing or a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::{osrs::EnumDefinition, Value};
+    ///
+    /// let mut enum_def = EnumDefinition {
+    ///     default_int: -1,
+    ///     ..EnumDefinition::default()
+    /// };
+    /// enum_def.values.insert(3, Value::Int(4151));
+    ///
+    /// assert_eq!(enum_def.int(3), 4151);
+    /// assert_eq!(enum_def.int(4), -1);
+    /// ```
+    pub fn int(&self, key: i32) -> i32 {
+        self.get(key)
+            .and_then(Value::as_int)
+            .unwrap_or(self.default_int)
+    }
+
+    /// The string value of `key`, `default_string` when it's missing or an integer.
+    pub fn string(&self, key: i32) -> &str {
+        self.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or(&self.default_string)
+    }
+}
+
+impl Definition for EnumDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let enum_def = decode_buffer(id, &mut reader)?;
+
+        Ok(enum_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<EnumDefinition> {
+    let mut enum_def = EnumDefinition {
+        id,
+        ..EnumDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                enum_def.keytype = char::from(reader.read_u8()?);
+            }
+            2 => {
+                enum_def.valtype = char::from(reader.read_u8()?);
+            }
+            3 => {
+                enum_def.default_string = reader.read_string()?;
+            }
+            4 => {
+                enum_def.default_int = reader.read_i32()?;
+            }
+            5 => {
+                let len = reader.read_u16()?;
+                for _ in 0..len {
+                    let key = reader.read_i32()?;
+                    enum_def
+                        .values
+                        .insert(key, Value::String(reader.read_string()?));
+                }
+            }
+            6 => {
+                let len = reader.read_u16()?;
+                for _ in 0..len {
+                    let key = reader.read_i32()?;
+                    enum_def.values.insert(key, Value::Int(reader.read_i32()?));
+                }
+            }
+            _ => return Err(super::unknown_opcode("enum", opcode)),
+        }
+    }
+
+    Ok(enum_def)
+}
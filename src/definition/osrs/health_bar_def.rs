@@ -0,0 +1,106 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// The bar drawn over the head of a damaged entity, fetched from the cache
+/// through the [HealthBarLoader](../../loader/osrs/struct.HealthBarLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct HealthBarDefinition {
+    pub id: u32,
+    /// Client cycles of 20ms the bar stays visible after an update.
+    pub display_cycles: u16,
+    /// Client cycle after an update the bar starts fading out, `None` when it
+    /// disappears at once.
+    pub fade_start_cycle: Option<u16>,
+    /// Sprite group of the remaining health.
+    pub front_sprite_id: Option<u32>,
+    /// Sprite group of the missing health.
+    pub back_sprite_id: Option<u32>,
+    /// Width in pixels of bars drawn without sprites.
+    pub width: u8,
+    /// Pixels of the sprites left out on both ends.
+    pub padding: u8,
+}
+
+impl Default for HealthBarDefinition {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            display_cycles: 70,
+            fade_start_cycle: None,
+            front_sprite_id: None,
+            back_sprite_id: None,
+            width: 30,
+            padding: 0,
+        }
+    }
+}
+
+impl Definition for HealthBarDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let health_bar_def = decode_buffer(id, &mut reader)?;
+
+        Ok(health_bar_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<HealthBarDefinition> {
+    let mut health_bar_def = HealthBarDefinition {
+        id,
+        ..HealthBarDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                reader.read_u16()?;
+            }
+            2 | 3 | 6 => {
+                reader.read_u8()?;
+            }
+            4 => {
+                health_bar_def.fade_start_cycle = Some(0);
+            }
+            5 => {
+                health_bar_def.display_cycles = reader.read_u16()?;
+            }
+            7 => {
+                health_bar_def.front_sprite_id = super::read_nullable_smart(reader)?;
+            }
+            8 => {
+                health_bar_def.back_sprite_id = super::read_nullable_smart(reader)?;
+            }
+            11 => {
+                health_bar_def.fade_start_cycle = Some(reader.read_u16()?);
+            }
+            14 => {
+                health_bar_def.width = reader.read_u8()?;
+            }
+            15 => {
+                health_bar_def.padding = reader.read_u8()?;
+            }
+            _ => return Err(super::unknown_opcode("health bar", opcode)),
+        }
+    }
+
+    Ok(health_bar_def)
+}
@@ -0,0 +1,186 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// A damage or heal marker drawn over an entity, fetched from the cache through
+/// the [HitSplatLoader](../../loader/osrs/struct.HitSplatLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct HitSplatDefinition {
+    pub id: u32,
+    /// Font group of the text.
+    pub font_id: Option<u32>,
+    /// 24-bit RGB color of the text.
+    pub text_color: u32,
+    /// Sprite group drawn left of the text, like the poison drop.
+    pub icon_sprite_id: Option<u32>,
+    /// Sprite group of the left end of the background.
+    pub left_sprite_id: Option<u32>,
+    /// Sprite group repeated behind the text.
+    pub middle_sprite_id: Option<u32>,
+    /// Sprite group of the right end of the background.
+    pub right_sprite_id: Option<u32>,
+    /// Pixels the splat moves horizontally over its display cycles.
+    pub scroll_x: i16,
+    /// Pixels the splat moves vertically over its display cycles.
+    pub scroll_y: i16,
+    /// Text of the splat, `%1` is replaced by the amount, see [`text`](Self::text).
+    pub format: String,
+    /// Client cycles of 20ms the splat stays visible.
+    pub display_cycles: u16,
+    /// Client cycle the splat starts fading out, `None` when it disappears at once.
+    pub fade_start_cycle: Option<u16>,
+    pub damage_mode: Option<u8>,
+    pub text_offset_y: i16,
+    /// Varbit selecting the [morph](HitSplatDefinition::morph), takes precedence over `morph_varp`.
+    pub morph_varbit: Option<u16>,
+    /// Varp selecting the [morph](HitSplatDefinition::morph).
+    pub morph_varp: Option<u16>,
+    /// Hitsplat ids indexed by the var value, `None` hides the splat. The last
+    /// entry is the default for values past the end.
+    pub morphisms: Vec<Option<u16>>,
+}
+
+impl Default for HitSplatDefinition {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            font_id: None,
+            text_color: 0xFF_FF_FF,
+            icon_sprite_id: None,
+            left_sprite_id: None,
+            middle_sprite_id: None,
+            right_sprite_id: None,
+            scroll_x: 0,
+            scroll_y: 0,
+            format: String::new(),
+            display_cycles: 70,
+            fade_start_cycle: None,
+            damage_mode: None,
+            text_offset_y: 0,
+            morph_varbit: None,
+            morph_varp: None,
+            morphisms: Vec::new(),
+        }
+    }
+}
+
+impl HitSplatDefinition {
+    /// The hitsplat id shown for the given value of the morph varbit or varp,
+    /// the default morph for values past the end.
+    ///
+    /// `None` when the hitsplat doesn't morph or is hidden for that value.
+    pub fn morph(&self, value: usize) -> Option<u16> {
+        super::resolve_morph(&self.morphisms, value)
+    }
+
+    /// The text drawn for a hit of `amount`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::HitSplatDefinition;
+    ///
+    /// let hit_splat = HitSplatDefinition {
+    ///     format: "%1".to_owned(),
+    ///     ..HitSplatDefinition::default()
+    /// };
+    ///
+    /// assert_eq!(hit_splat.text(12), "12");
+    /// ```
+    pub fn text(&self, amount: i32) -> String {
+        self.format.replace("%1", &amount.to_string())
+    }
+}
+
+impl Definition for HitSplatDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let hit_splat_def = decode_buffer(id, &mut reader)?;
+
+        Ok(hit_splat_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<HitSplatDefinition> {
+    let mut hit_splat_def = HitSplatDefinition {
+        id,
+        ..HitSplatDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                hit_splat_def.font_id = super::read_nullable_smart(reader)?;
+            }
+            2 => {
+                hit_splat_def.text_color = reader.read_u24()?;
+            }
+            3 => {
+                hit_splat_def.icon_sprite_id = super::read_nullable_smart(reader)?;
+            }
+            4 => {
+                hit_splat_def.left_sprite_id = super::read_nullable_smart(reader)?;
+            }
+            5 => {
+                hit_splat_def.middle_sprite_id = super::read_nullable_smart(reader)?;
+            }
+            6 => {
+                hit_splat_def.right_sprite_id = super::read_nullable_smart(reader)?;
+            }
+            7 => {
+                hit_splat_def.scroll_x = reader.read_i16()?;
+            }
+            8 => {
+                // The string is circumfixed, a 0 comes before it too.
+                reader.read_u8()?;
+                hit_splat_def.format = reader.read_string()?;
+            }
+            9 => {
+                hit_splat_def.display_cycles = reader.read_u16()?;
+            }
+            10 => {
+                hit_splat_def.scroll_y = reader.read_i16()?;
+            }
+            11 => {
+                hit_splat_def.fade_start_cycle = Some(0);
+            }
+            12 => {
+                hit_splat_def.damage_mode = Some(reader.read_u8()?);
+            }
+            13 => {
+                hit_splat_def.text_offset_y = reader.read_i16()?;
+            }
+            14 => {
+                hit_splat_def.fade_start_cycle = Some(reader.read_u16()?);
+            }
+            17 | 18 => {
+                (
+                    hit_splat_def.morph_varbit,
+                    hit_splat_def.morph_varp,
+                    hit_splat_def.morphisms,
+                ) = super::read_morphs(reader, opcode == 18)?;
+            }
+            _ => return Err(super::unknown_opcode("hit splat", opcode)),
+        }
+    }
+
+    Ok(hit_splat_def)
+}
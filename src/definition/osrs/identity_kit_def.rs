@@ -0,0 +1,86 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// A body part players can pick for their appearance, fetched from the cache
+/// through the [IdentityKitLoader](../../loader/osrs/struct.IdentityKitLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct IdentityKitDefinition {
+    pub id: u32,
+    /// Head, jaw, torso, arms, hands, legs and feet from 0 to 6 for male kits,
+    /// from 7 to 13 for female kits.
+    pub body_part: Option<u8>,
+    /// Models of the body part on the player.
+    pub models: Vec<u16>,
+    /// Kits that can't be picked in the character designer.
+    pub non_selectable: bool,
+    /// Colors of the models replaced by another, as `(find, replace)` pairs.
+    pub recolors: Vec<(u16, u16)>,
+    /// Textures of the models replaced by another, as `(find, replace)` pairs.
+    pub retextures: Vec<(u16, u16)>,
+    /// Models of the body part on the chathead.
+    pub head_models: [Option<u16>; 10],
+}
+
+impl Definition for IdentityKitDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let identity_kit_def = decode_buffer(id, &mut reader)?;
+
+        Ok(identity_kit_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<IdentityKitDefinition> {
+    let mut identity_kit_def = IdentityKitDefinition {
+        id,
+        ..IdentityKitDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                identity_kit_def.body_part = Some(reader.read_u8()?);
+            }
+            2 => {
+                let len = reader.read_u8()?;
+                for _ in 0..len {
+                    identity_kit_def.models.push(reader.read_u16()?);
+                }
+            }
+            3 => {
+                identity_kit_def.non_selectable = true;
+            }
+            40 => {
+                identity_kit_def.recolors = super::read_replacements(reader)?;
+            }
+            41 => {
+                identity_kit_def.retextures = super::read_replacements(reader)?;
+            }
+            60..=69 => {
+                identity_kit_def.head_models[opcode as usize - 60] = super::read_morph_id(reader)?;
+            }
+            _ => return Err(super::unknown_opcode("identity kit", opcode)),
+        }
+    }
+
+    Ok(identity_kit_def)
+}
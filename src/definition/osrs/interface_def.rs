@@ -0,0 +1,515 @@
+use std::io::{self, BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::extension::ReadExt;
+
+/// Contains all the information about a certain interface component fetched from the cache through
+/// the [InterfaceLoader](../../loader/osrs/struct.InterfaceLoader.html).
+///
+/// The `id` is packed as `interface_id << 16 | component_id`, the same way the client refers
+/// to components.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ComponentDefinition {
+    pub id: u32,
+    pub if3: bool,
+    pub component_type: u8,
+    pub content_type: u16,
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub parent_id: Option<u32>,
+    pub hidden: bool,
+    pub sprite_id: Option<i32>,
+    pub model_id: Option<u16>,
+    pub font_id: Option<u16>,
+    pub text: String,
+    pub text_color: i32,
+    pub name: String,
+    pub actions: Vec<String>,
+    pub target_verb: String,
+    pub click_mask: u32,
+    pub hooks: Vec<Hook>,
+    pub var_transmit_triggers: Vec<i32>,
+    pub inv_transmit_triggers: Vec<i32>,
+    pub stat_transmit_triggers: Vec<i32>,
+}
+
+/// A client script bound to one of the listener slots of an if3 component.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Hook {
+    pub trigger: HookTrigger,
+    pub script_id: u32,
+    pub args: Vec<HookArg>,
+}
+
+/// Listener slots of an if3 component, in the order they are encoded.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum HookTrigger {
+    Load,
+    MouseOver,
+    MouseLeave,
+    TargetLeave,
+    TargetEnter,
+    VarTransmit,
+    InvTransmit,
+    StatTransmit,
+    Timer,
+    Op,
+    MouseRepeat,
+    Click,
+    ClickRepeat,
+    Release,
+    Hold,
+    Drag,
+    DragComplete,
+    ScrollWheel,
+}
+
+impl HookTrigger {
+    /// Every trigger in encoding order.
+    pub const ALL: [Self; 18] = [
+        Self::Load,
+        Self::MouseOver,
+        Self::MouseLeave,
+        Self::TargetLeave,
+        Self::TargetEnter,
+        Self::VarTransmit,
+        Self::InvTransmit,
+        Self::StatTransmit,
+        Self::Timer,
+        Self::Op,
+        Self::MouseRepeat,
+        Self::Click,
+        Self::ClickRepeat,
+        Self::Release,
+        Self::Hold,
+        Self::Drag,
+        Self::DragComplete,
+        Self::ScrollWheel,
+    ];
+}
+
+/// An argument passed to a hook's script.
+///
+/// Besides plain integers and strings the client substitutes a handful of magic
+/// values with event data when the hook fires, these get their own variant.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum HookArg {
+    Int(i32),
+    String(String),
+    MouseX,
+    MouseY,
+    ComponentId,
+    Op,
+    ComponentIndex,
+    DragTargetId,
+    DragTargetIndex,
+    KeyCode,
+    KeyChar,
+    OpBase,
+}
+
+impl HookArg {
+    fn from_int(value: i32) -> Self {
+        match value {
+            -2147483647 => Self::MouseX,
+            -2147483646 => Self::MouseY,
+            -2147483645 => Self::ComponentId,
+            -2147483644 => Self::Op,
+            -2147483643 => Self::ComponentIndex,
+            -2147483642 => Self::DragTargetId,
+            -2147483641 => Self::DragTargetIndex,
+            -2147483640 => Self::KeyCode,
+            -2147483639 => Self::KeyChar,
+            _ => Self::Int(value),
+        }
+    }
+
+    fn from_string(value: String) -> Self {
+        if value == "event_opbase" {
+            Self::OpBase
+        } else {
+            Self::String(value)
+        }
+    }
+}
+
+impl ComponentDefinition {
+    /// Decodes a component, `id` being the packed interface and component id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer is truncated or a hook is malformed.
+    pub fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let component = if buffer.first() == Some(&0xFF) {
+            reader.read_u8()?;
+            decode_if3(id, &mut reader)?
+        } else {
+            decode_if1(id, &mut reader)?
+        };
+
+        Ok(component)
+    }
+
+    /// Returns the first hook bound to the given trigger.
+    pub fn hook(&self, trigger: HookTrigger) -> Option<&Hook> {
+        self.hooks.iter().find(|hook| hook.trigger == trigger)
+    }
+}
+
+fn decode_if3(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<ComponentDefinition> {
+    let mut component = ComponentDefinition {
+        id,
+        if3: true,
+        ..ComponentDefinition::default()
+    };
+
+    component.component_type = reader.read_u8()?;
+    component.content_type = reader.read_u16()?;
+    component.x = reader.read_i16()?;
+    component.y = reader.read_i16()?;
+    component.width = reader.read_u16()?;
+    component.height = if component.component_type == 9 {
+        reader.read_i16()? as u16
+    } else {
+        reader.read_u16()?
+    };
+    let width_mode = reader.read_u8()?;
+    // height, x and y position modes.
+    reader.read_u8()?;
+    reader.read_u8()?;
+    reader.read_u8()?;
+    component.parent_id = read_parent(id, reader)?;
+    component.hidden = reader.read_u8()? == 1;
+
+    match component.component_type {
+        0 => {
+            // scroll width, scroll height and no click through.
+            reader.read_u16()?;
+            reader.read_u16()?;
+            reader.read_u8()?;
+        }
+        3 => {
+            component.text_color = reader.read_i32()?;
+            // filled and transparency.
+            reader.read_u8()?;
+            reader.read_u8()?;
+        }
+        4 => {
+            component.font_id = read_optional_u16(reader)?;
+            component.text = reader.read_string()?;
+            // line height, x and y alignment and shadowed.
+            for _ in 0..4 {
+                reader.read_u8()?;
+            }
+            component.text_color = reader.read_i32()?;
+        }
+        5 => {
+            component.sprite_id = Some(reader.read_i32()?).filter(|&sprite| sprite != -1);
+            // texture, tiling, transparency, border.
+            reader.read_u16()?;
+            reader.read_u8()?;
+            reader.read_u8()?;
+            reader.read_u8()?;
+            // shadow color, vertical and horizontal flip.
+            reader.read_i32()?;
+            reader.read_u8()?;
+            reader.read_u8()?;
+        }
+        6 => {
+            component.model_id = read_optional_u16(reader)?;
+            // offsets, rotations, zoom, animation, orthogonal and model height.
+            for _ in 0..7 {
+                reader.read_u16()?;
+            }
+            reader.read_u8()?;
+            reader.read_u16()?;
+            // the client checks the width mode for both of these, not the height mode.
+            if width_mode != 0 {
+                reader.read_u16()?;
+                reader.read_u16()?;
+            }
+        }
+        9 => {
+            // line width, color and direction.
+            reader.read_u8()?;
+            component.text_color = reader.read_i32()?;
+            reader.read_u8()?;
+        }
+        _ => {}
+    }
+
+    component.click_mask = reader.read_u24()?;
+    component.name = reader.read_string()?;
+
+    let len = reader.read_u8()?;
+    component.actions = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        component.actions.push(reader.read_string()?);
+    }
+
+    // drag dead zone, drag dead time and drag render behaviour.
+    reader.read_u8()?;
+    reader.read_u8()?;
+    reader.read_u8()?;
+    component.target_verb = reader.read_string()?;
+
+    for trigger in HookTrigger::ALL {
+        if let Some(hook) = read_hook(trigger, reader)? {
+            component.hooks.push(hook);
+        }
+    }
+
+    component.var_transmit_triggers = read_transmit_triggers(reader)?;
+    component.inv_transmit_triggers = read_transmit_triggers(reader)?;
+    component.stat_transmit_triggers = read_transmit_triggers(reader)?;
+
+    Ok(component)
+}
+
+fn decode_if1(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<ComponentDefinition> {
+    let mut component = ComponentDefinition {
+        id,
+        ..ComponentDefinition::default()
+    };
+
+    component.component_type = reader.read_u8()?;
+    let menu_type = reader.read_u8()?;
+    component.content_type = reader.read_u16()?;
+    component.x = reader.read_i16()?;
+    component.y = reader.read_i16()?;
+    component.width = reader.read_u16()?;
+    component.height = reader.read_u16()?;
+    // transparency.
+    reader.read_u8()?;
+    component.parent_id = read_parent(id, reader)?;
+    // hovered sibling.
+    reader.read_u16()?;
+
+    // legacy client script comparisons and instructions.
+    let len = reader.read_u8()?;
+    for _ in 0..len {
+        reader.read_u8()?;
+        reader.read_u16()?;
+    }
+    let len = reader.read_u8()?;
+    for _ in 0..len {
+        let instructions = reader.read_u16()?;
+        for _ in 0..instructions {
+            reader.read_u16()?;
+        }
+    }
+
+    match component.component_type {
+        0 => {
+            // scroll height.
+            reader.read_u16()?;
+            component.hidden = reader.read_u8()? == 1;
+        }
+        1 => {
+            reader.read_u16()?;
+            reader.read_u8()?;
+        }
+        2 => {
+            for bit in [28, 30, 31, 29] {
+                if reader.read_u8()? == 1 {
+                    component.click_mask |= 1 << bit;
+                }
+            }
+            // x and y pitch.
+            reader.read_u8()?;
+            reader.read_u8()?;
+            // sprite slots.
+            for _ in 0..20 {
+                if reader.read_u8()? == 1 {
+                    reader.read_i16()?;
+                    reader.read_i16()?;
+                    reader.read_i32()?;
+                }
+            }
+            read_config_actions(&mut component, reader)?;
+        }
+        3 => {
+            // filled.
+            reader.read_u8()?;
+        }
+        _ => {}
+    }
+
+    if component.component_type == 4 || component.component_type == 1 {
+        // x and y alignment and line height.
+        reader.read_u8()?;
+        reader.read_u8()?;
+        reader.read_u8()?;
+        component.font_id = read_optional_u16(reader)?;
+        // shadowed.
+        reader.read_u8()?;
+    }
+
+    if component.component_type == 4 {
+        component.text = reader.read_string()?;
+        // alternate text.
+        reader.read_string()?;
+    }
+
+    if matches!(component.component_type, 1 | 3 | 4) {
+        component.text_color = reader.read_i32()?;
+    }
+
+    if matches!(component.component_type, 3 | 4) {
+        // alternate, hovered and alternate hovered colors.
+        reader.read_i32()?;
+        reader.read_i32()?;
+        reader.read_i32()?;
+    }
+
+    match component.component_type {
+        5 => {
+            component.sprite_id = Some(reader.read_i32()?).filter(|&sprite| sprite != -1);
+            // alternate sprite.
+            reader.read_i32()?;
+        }
+        6 => {
+            component.model_id = read_optional_u16(reader)?;
+            // alternate model, animations, zoom and rotations.
+            for _ in 0..6 {
+                reader.read_u16()?;
+            }
+        }
+        7 => {
+            // x alignment.
+            reader.read_u8()?;
+            component.font_id = read_optional_u16(reader)?;
+            // shadowed.
+            reader.read_u8()?;
+            component.text_color = reader.read_i32()?;
+            // x and y pitch.
+            reader.read_i16()?;
+            reader.read_i16()?;
+            if reader.read_u8()? == 1 {
+                component.click_mask |= 1 << 30;
+            }
+            read_config_actions(&mut component, reader)?;
+        }
+        8 => {
+            component.text = reader.read_string()?;
+        }
+        _ => {}
+    }
+
+    if menu_type == 2 || component.component_type == 2 {
+        component.target_verb = reader.read_string()?;
+        // spell name.
+        reader.read_string()?;
+        component.click_mask |= (reader.read_u16()? as u32 & 63) << 11;
+    }
+
+    if matches!(menu_type, 1 | 4 | 5 | 6) {
+        let tooltip = reader.read_string()?;
+        let tooltip = if tooltip.is_empty() {
+            match menu_type {
+                1 => "Ok".to_owned(),
+                6 => "Continue".to_owned(),
+                _ => "Select".to_owned(),
+            }
+        } else {
+            tooltip
+        };
+        component.actions.push(tooltip);
+    }
+
+    match menu_type {
+        1 | 4 | 5 => component.click_mask |= 1 << 22,
+        6 => component.click_mask |= 1,
+        _ => {}
+    }
+
+    Ok(component)
+}
+
+fn read_parent(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<Option<u32>> {
+    let parent = reader.read_u16()?;
+
+    Ok(if parent == u16::MAX {
+        None
+    } else {
+        Some(id & !0xFFFF | parent as u32)
+    })
+}
+
+fn read_optional_u16(reader: &mut BufReader<&[u8]>) -> io::Result<Option<u16>> {
+    let value = reader.read_u16()?;
+
+    Ok(if value == u16::MAX { None } else { Some(value) })
+}
+
+fn read_config_actions(
+    component: &mut ComponentDefinition,
+    reader: &mut BufReader<&[u8]>,
+) -> io::Result<()> {
+    for slot in 0..5 {
+        let action = reader.read_string()?;
+        if !action.is_empty() {
+            component.click_mask |= 1 << (slot + 23);
+            component.actions.push(action);
+        }
+    }
+
+    Ok(())
+}
+
+fn read_hook(trigger: HookTrigger, reader: &mut BufReader<&[u8]>) -> io::Result<Option<Hook>> {
+    let len = reader.read_u8()?;
+    if len == 0 {
+        return Ok(None);
+    }
+
+    let mut args = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        let arg = match reader.read_u8()? {
+            0 => HookArg::from_int(reader.read_i32()?),
+            1 => HookArg::from_string(reader.read_string()?),
+            kind => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown {:?} hook argument type {}", trigger, kind),
+                ))
+            }
+        };
+        args.push(arg);
+    }
+
+    let script_id = match args.remove(0) {
+        HookArg::Int(script_id) if script_id >= 0 => script_id as u32,
+        arg => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{:?} hook has no script id, found {:?}", trigger, arg),
+            ))
+        }
+    };
+
+    Ok(Some(Hook {
+        trigger,
+        script_id,
+        args,
+    }))
+}
+
+fn read_transmit_triggers(reader: &mut BufReader<&[u8]>) -> io::Result<Vec<i32>> {
+    let len = reader.read_u8()?;
+    let mut triggers = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        triggers.push(reader.read_i32()?);
+    }
+
+    Ok(triggers)
+}
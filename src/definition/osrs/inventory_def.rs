@@ -0,0 +1,57 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// The capacity of a container like the bank or a shop, fetched from the cache
+/// through the [InventoryLoader](../../loader/osrs/struct.InventoryLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct InventoryDefinition {
+    pub id: u32,
+    /// Number of slots, 0 when the definition doesn't declare it.
+    pub capacity: u16,
+}
+
+impl Definition for InventoryDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let inventory_def = decode_buffer(id, &mut reader)?;
+
+        Ok(inventory_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<InventoryDefinition> {
+    let mut inventory_def = InventoryDefinition {
+        id,
+        ..InventoryDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            2 => {
+                inventory_def.capacity = reader.read_u16()?;
+            }
+            _ => return Err(super::unknown_opcode("inventory", opcode)),
+        }
+    }
+
+    Ok(inventory_def)
+}
@@ -1,21 +1,29 @@
-use std::{collections::HashMap, io, io::BufReader};
+use std::{collections::BTreeMap, io, io::BufReader};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use super::Definition;
-use crate::{extension::ReadExt, util};
+use crate::{
+    definition::{
+        opcodes::{self, DefinitionKind},
+        Value,
+    },
+    extension::ReadExt,
+};
 
 /// Contains all the information about a certain item fetched from the cache through
 /// the [ItemLoader](../../loader/osrs/struct.ItemLoader.html).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
 pub struct ItemDefinition {
-    pub id: u16,
+    pub id: u32,
     pub name: String,
     pub stackable: bool,
     pub cost: i32,
     pub members_only: bool,
+    /// Actions shown when the item lies on the ground, see
+    /// [`ground_actions`](Self::ground_actions).
     pub options: [String; 5],
     pub interface_options: [String; 5],
     pub tradable: bool,
@@ -27,10 +35,22 @@ pub struct ItemDefinition {
     pub bought_link: Option<u16>,
     pub bought_tempalte: Option<u16>,
     pub shift_click_drop_index: Option<u8>,
-    pub params: HashMap<u32, String>,
+    /// Equipment slot the item is worn in.
+    pub wear_pos1: Option<u8>,
+    /// Slot hidden by the item when worn, e.g. the arms for a platebody.
+    pub wear_pos2: Option<u8>,
+    /// Second slot hidden by the item when worn, e.g. the jaw for a full helm.
+    pub wear_pos3: Option<u8>,
+    /// The bank placeholder of this item.
+    pub placeholder_id: Option<u16>,
+    /// Set on placeholders, the template they are drawn with.
+    pub placeholder_template: Option<u16>,
+    /// Integer and string params keyed by their param id.
+    pub params: BTreeMap<u32, Value>,
     pub inventory_model_data: InventoryModelData,
     pub character_model_data: CharacterModelData,
-    pub weight: u16,
+    /// Weight in grams, negative for weight reducing equipment.
+    pub weight: i16,
     pub category: u16,
 }
 
@@ -72,16 +92,74 @@ pub struct CharacterModelData {
     pub female_head_model2: Option<u16>,
 }
 
+impl ItemDefinition {
+    /// The ground [`options`](Self::options), `None` for empty or hidden slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::definition::osrs::ItemDefinition;
+    /// let mut item = ItemDefinition::default();
+    /// item.options[2] = "Take".to_owned();
+    /// item.options[3] = "Hidden".to_owned();
+    ///
+    /// assert_eq!(item.ground_actions(), [None, None, Some("Take"), None, None]);
+    /// ```
+    pub fn ground_actions(&self) -> [Option<&str>; 5] {
+        self.options.each_ref().map(|option| {
+            if option.is_empty() || option.eq_ignore_ascii_case("hidden") {
+                None
+            } else {
+                Some(option.as_str())
+            }
+        })
+    }
+
+    /// Returns `true` if one of the ground actions matches `action`, ignoring ASCII case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::definition::osrs::ItemDefinition;
+    /// let mut item = ItemDefinition::default();
+    /// item.options[2] = "Take".to_owned();
+    ///
+    /// assert!(item.has_ground_action("Take"));
+    /// assert!(!item.has_ground_action("Light"));
+    /// ```
+    pub fn has_ground_action(&self, action: &str) -> bool {
+        self.ground_actions()
+            .into_iter()
+            .flatten()
+            .any(|ground_action| ground_action.eq_ignore_ascii_case(action))
+    }
+}
+
 impl Definition for ItemDefinition {
-    fn new(id: u16, buffer: &[u8]) -> crate::Result<Self> {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
         let mut reader = BufReader::new(buffer);
         let item_def = decode_buffer(id, &mut reader)?;
 
         Ok(item_def)
     }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        Some(self.name.as_str()).filter(|name| !name.is_empty() && *name != "null")
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
 }
 
-fn decode_buffer(id: u16, reader: &mut BufReader<&[u8]>) -> io::Result<ItemDefinition> {
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<ItemDefinition> {
     let mut item_def = ItemDefinition {
         id,
         inventory_model_data: InventoryModelData {
@@ -110,183 +188,87 @@ fn decode_buffer(id: u16, reader: &mut BufReader<&[u8]>) -> io::Result<ItemDefin
 
     loop {
         let opcode = reader.read_u8()?;
+        if opcode == 0 {
+            break;
+        }
 
+        let field = opcodes::read(DefinitionKind::Item, opcode, reader)?;
         match opcode {
-            0 => break,
-            1 => {
-                item_def.inventory_model_data.inventory_model = reader.read_u16()?;
-            }
-            2 => {
-                item_def.name = reader.read_string()?;
-            }
-            4 => {
-                item_def.inventory_model_data.zoom2d = reader.read_u16()?;
-            }
-            5 => {
-                item_def.inventory_model_data.x_an2d = reader.read_u16()?;
-            }
-            6 => {
-                item_def.inventory_model_data.y_an2d = reader.read_u16()?;
-            }
-            7 => {
-                item_def.inventory_model_data.x_offset2d = reader.read_u16()?;
-            }
-            8 => {
-                item_def.inventory_model_data.y_offset2d = reader.read_u16()?;
-            }
-            9 => {
-                let _ = reader.read_string()?;
-            }
-            11 => {
-                item_def.stackable = true;
-            }
-            12 => {
-                item_def.cost = reader.read_i32()?;
-            }
-            13..=14 => {
-                let _ = reader.read_u8()?;
-            }
-            16 => item_def.members_only = true,
+            1 => item_def.inventory_model_data.inventory_model = field.get()?,
+            2 => item_def.name = field.get()?,
+            4 => item_def.inventory_model_data.zoom2d = field.get()?,
+            5 => item_def.inventory_model_data.x_an2d = field.get()?,
+            6 => item_def.inventory_model_data.y_an2d = field.get()?,
+            7 => item_def.inventory_model_data.x_offset2d = field.get()?,
+            8 => item_def.inventory_model_data.y_offset2d = field.get()?,
+            11 => item_def.stackable = field.get()?,
+            12 => item_def.cost = field.get()?,
+            13 => item_def.wear_pos1 = Some(field.get()?),
+            14 => item_def.wear_pos2 = Some(field.get()?),
+            16 => item_def.members_only = field.get()?,
             23 => {
-                item_def.character_model_data.male_model10 = Some(reader.read_u16()?);
-                item_def.character_model_data.male_model_offset = reader.read_u8()?;
-            }
-            24 => {
-                item_def.character_model_data.male_model1 = Some(reader.read_u16()?);
+                let (model, offset) = field.get()?;
+                item_def.character_model_data.male_model10 = Some(model);
+                item_def.character_model_data.male_model_offset = offset;
             }
+            24 => item_def.character_model_data.male_model1 = Some(field.get()?),
             25 => {
-                item_def.character_model_data.female_model10 = Some(reader.read_u16()?);
-                item_def.character_model_data.female_model_offset = reader.read_u8()?;
-            }
-            26 => {
-                item_def.character_model_data.female_model1 = Some(reader.read_u16()?);
-            }
-            27 => {
-                let _ = reader.read_u8()?;
-            }
-            30..=34 => {
-                item_def.options[opcode as usize - 30] = reader.read_string()?;
-            }
-            35..=39 => {
-                item_def.interface_options[opcode as usize - 35] = reader.read_string()?;
-            }
+                let (model, offset) = field.get()?;
+                item_def.character_model_data.female_model10 = Some(model);
+                item_def.character_model_data.female_model_offset = offset;
+            }
+            26 => item_def.character_model_data.female_model1 = Some(field.get()?),
+            27 => item_def.wear_pos3 = Some(field.get()?),
+            30..=34 => item_def.options[opcode as usize - 30] = field.get()?,
+            35..=39 => item_def.interface_options[opcode as usize - 35] = field.get()?,
             40 => {
-                let len = reader.read_u8()? as usize;
-                item_def.inventory_model_data.color_find = Vec::with_capacity(len);
-                item_def.inventory_model_data.color_replace = Vec::with_capacity(len);
-                for _ in 0..len {
-                    item_def
-                        .inventory_model_data
-                        .color_find
-                        .push(reader.read_u16()?);
-                    item_def
-                        .inventory_model_data
-                        .color_replace
-                        .push(reader.read_u16()?);
-                }
+                let colors: Vec<(u16, u16)> = field.get()?;
+                (
+                    item_def.inventory_model_data.color_find,
+                    item_def.inventory_model_data.color_replace,
+                ) = colors.into_iter().unzip();
             }
             41 => {
-                let len = reader.read_u8()? as usize;
-                item_def.inventory_model_data.texture_find = Vec::with_capacity(len);
-                item_def.inventory_model_data.texture_replace = Vec::with_capacity(len);
-                for _ in 0..len {
-                    item_def
-                        .inventory_model_data
-                        .texture_find
-                        .push(reader.read_u16()?);
-                    item_def
-                        .inventory_model_data
-                        .texture_replace
-                        .push(reader.read_u16()?);
-                }
-            }
-            42 => {
-                item_def.shift_click_drop_index = Some(reader.read_u8()?);
-            }
-            65 => {
-                item_def.tradable = true;
-            }
-            75 => {
-                item_def.weight = reader.read_u16()?;
-            }
-            78 => {
-                item_def.character_model_data.male_model12 = Some(reader.read_u16()?);
-            }
-            79 => {
-                item_def.character_model_data.female_model12 = Some(reader.read_u16()?);
-            }
-            90 => {
-                item_def.character_model_data.male_head_model1 = Some(reader.read_u16()?);
-            }
-            91 => {
-                item_def.character_model_data.female_head_model1 = Some(reader.read_u16()?);
-            }
-            92 => {
-                item_def.character_model_data.male_head_model2 = Some(reader.read_u16()?);
-            }
-            93 => {
-                item_def.character_model_data.female_head_model2 = Some(reader.read_u16()?);
-            }
-            94 => {
-                item_def.category = reader.read_u16()?;
-            }
-            95 => {
-                item_def.inventory_model_data.z_an2d = reader.read_u16()?;
-            }
-            97 => {
-                item_def.noted_id = Some(reader.read_u16()?);
-            }
+                let textures: Vec<(u16, u16)> = field.get()?;
+                (
+                    item_def.inventory_model_data.texture_find,
+                    item_def.inventory_model_data.texture_replace,
+                ) = textures.into_iter().unzip();
+            }
+            42 => item_def.shift_click_drop_index = Some(field.get()?),
+            65 => item_def.tradable = field.get()?,
+            75 => item_def.weight = field.get()?,
+            78 => item_def.character_model_data.male_model12 = Some(field.get()?),
+            79 => item_def.character_model_data.female_model12 = Some(field.get()?),
+            90 => item_def.character_model_data.male_head_model1 = Some(field.get()?),
+            91 => item_def.character_model_data.female_head_model1 = Some(field.get()?),
+            92 => item_def.character_model_data.male_head_model2 = Some(field.get()?),
+            93 => item_def.character_model_data.female_head_model2 = Some(field.get()?),
+            94 => item_def.category = field.get()?,
+            95 => item_def.inventory_model_data.z_an2d = field.get()?,
+            97 => item_def.noted_id = Some(field.get()?),
             98 => {
-                item_def.noted_template = Some(reader.read_u16()?);
+                item_def.noted_template = Some(field.get()?);
                 item_def.stackable = true;
             }
             100..=109 => {
-                item_def.stack_ids = Some([0; 10]);
-                item_def.stack_count = Some([0; 10]);
-                match item_def.stack_ids {
-                    Some(mut stack_ids) => {
-                        stack_ids[opcode as usize - 100] = reader.read_u16()?;
-                    }
-                    _ => unreachable!(),
-                }
-                match item_def.stack_count {
-                    Some(mut stack_count) => {
-                        stack_count[opcode as usize - 100] = reader.read_u16()?;
-                    }
-                    _ => unreachable!(),
-                }
-            }
-            110 => {
-                item_def.inventory_model_data.resize_x = reader.read_u16()?;
-            }
-            111 => {
-                item_def.inventory_model_data.resize_y = reader.read_u16()?;
-            }
-            112 => {
-                item_def.inventory_model_data.resize_z = reader.read_u16()?;
-            }
-            113 => {
-                item_def.inventory_model_data.ambient = reader.read_i8()?;
-            }
-            114 => {
-                item_def.inventory_model_data.contrast = reader.read_i8()?;
-            }
-            115 => {
-                item_def.team = reader.read_u8()?;
-            }
-            139 => {
-                item_def.bought_link = Some(reader.read_u16()?);
-            }
-            140 => {
-                item_def.bought_tempalte = Some(reader.read_u16()?);
-            }
-            148 | 149 => {
-                reader.read_u16()?;
-            }
-            249 => {
-                item_def.params = util::read_parameters(reader)?;
-            }
-            _ => unreachable!(),
+                let (stack_id, stack_count) = field.get()?;
+                item_def.stack_ids.get_or_insert([0; 10])[opcode as usize - 100] = stack_id;
+                item_def.stack_count.get_or_insert([0; 10])[opcode as usize - 100] = stack_count;
+            }
+            110 => item_def.inventory_model_data.resize_x = field.get()?,
+            111 => item_def.inventory_model_data.resize_y = field.get()?,
+            112 => item_def.inventory_model_data.resize_z = field.get()?,
+            113 => item_def.inventory_model_data.ambient = field.get()?,
+            114 => item_def.inventory_model_data.contrast = field.get()?,
+            115 => item_def.team = field.get()?,
+            139 => item_def.bought_link = Some(field.get()?),
+            140 => item_def.bought_tempalte = Some(field.get()?),
+            148 => item_def.placeholder_id = Some(field.get()?),
+            149 => item_def.placeholder_template = Some(field.get()?),
+            249 => item_def.params = field.get()?,
+            // known opcodes of fields that aren't stored.
+            _ => {}
         }
     }
 
@@ -1,9 +1,11 @@
-use nom::number::complete::be_u8;
+use nom::{
+    number::complete::{be_u16, be_u8},
+    IResult,
+};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use super::Definition;
-use runefs::parse::{be_u16_smart, be_u32_smart_compat};
+use super::{Definition, MapDefinition};
 
 /// Contains all the information about a certain location fetched from the cache through
 /// the [LocationLoader](../../loader/osrs/struct.LocationLoader.html).
@@ -21,6 +23,113 @@ impl LocationDefinition {
     pub const fn region_base_coords(&self) -> (u16, u16) {
         (self.region_x << 6, self.region_y << 6)
     }
+
+    /// Absolute tile of a location of this region.
+    ///
+    /// [`Location::pos`] is stored relative to the region id rather than its base tile.
+    #[inline]
+    pub const fn position_of(&self, location: &Location) -> Position {
+        let (base_x, base_y) = self.region_base_coords();
+        let (x, y, plane) = location.pos;
+
+        Position::new(
+            base_x.wrapping_add(x.wrapping_sub(self.region_x)),
+            base_y.wrapping_add(y.wrapping_sub(self.region_y)),
+            plane as u8,
+        )
+    }
+
+    /// Every location of the region, placed at its absolute tile.
+    ///
+    /// Planes are the ones stored in the cache, see [`spawns_on`](Self::spawns_on)
+    /// to place them like the client does.
+    pub fn spawns(&self) -> impl Iterator<Item = LocationSpawn> + '_ {
+        self.data.iter().map(|location| LocationSpawn {
+            id: location.id,
+            loc_type: location.loc_type,
+            orientation: location.orientation,
+            position: self.position_of(location),
+            bridged: false,
+        })
+    }
+
+    /// Every location of the region placed at its absolute tile, one plane
+    /// lower on the bridge tiles of `map` like the client renders them.
+    ///
+    /// Locations on the ground plane below a bridge stay on it.
+    pub fn spawns_on<'a>(
+        &'a self,
+        map: &'a MapDefinition,
+    ) -> impl Iterator<Item = LocationSpawn> + 'a {
+        self.spawns().map(move |mut spawn| {
+            if map.is_bridge(spawn.position.x, spawn.position.y) {
+                spawn.position.plane = spawn.position.plane.saturating_sub(1);
+                spawn.bridged = true;
+            }
+            spawn
+        })
+    }
+}
+
+/// A location placed in the world, see [`LocationDefinition::spawns`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct LocationSpawn {
+    /// Id of the [object](super::ObjectDefinition).
+    pub id: u32,
+    pub loc_type: u8,
+    pub orientation: u8,
+    /// Absolute south west tile.
+    pub position: Position,
+    /// Whether the location stands on a bridge tile, its plane is one lower
+    /// than the stored one then.
+    pub bridged: bool,
+}
+
+impl LocationSpawn {
+    /// The semantic kind of the raw [`loc_type`](LocationSpawn::loc_type), `None` above 22.
+    #[inline]
+    pub const fn kind(&self) -> Option<LocationKind> {
+        LocationKind::from_type(self.loc_type)
+    }
+}
+
+/// An absolute tile in the world.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Position {
+    pub x: u16,
+    pub y: u16,
+    pub plane: u8,
+}
+
+impl Position {
+    #[inline]
+    pub const fn new(x: u16, y: u16, plane: u8) -> Self {
+        Self { x, y, plane }
+    }
+
+    /// Id of the 64×64 region holding the tile, as used by the map and location loaders.
+    #[inline]
+    pub const fn region_id(self) -> u16 {
+        (self.x >> 6) << 8 | self.y >> 6
+    }
+
+    /// The client's `plane << 28 | x << 14 | y` packing.
+    #[inline]
+    pub const fn packed(self) -> u32 {
+        (self.plane as u32 & 0x3) << 28 | (self.x as u32 & 0x3FFF) << 14 | self.y as u32 & 0x3FFF
+    }
+
+    /// Unpacks the client's `plane << 28 | x << 14 | y` packing.
+    #[inline]
+    pub const fn from_packed(packed: u32) -> Self {
+        Self {
+            x: (packed >> 14 & 0x3FFF) as u16,
+            y: (packed & 0x3FFF) as u16,
+            plane: (packed >> 28 & 0x3) as u8,
+        }
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -32,12 +141,247 @@ pub struct Location {
     pub pos: (u16, u16, u16),
 }
 
+impl Location {
+    /// The semantic kind of the raw [`loc_type`](Location::loc_type), `None` above 22.
+    #[inline]
+    pub const fn kind(&self) -> Option<LocationKind> {
+        LocationKind::from_type(self.loc_type)
+    }
+
+    /// The tile edges and corners this location blocks when it is a wall.
+    ///
+    /// Follows the client's orientation table:
+    /// - type 0, straight walls block a single edge.
+    /// - type 1 and 3, wall corners block a single corner.
+    /// - type 2, L-shaped corner walls block two adjacent edges.
+    /// - type 9, diagonal walls run from corner to corner across the tile.
+    ///
+    /// Returns `None` for every other type.
+    pub const fn wall_edges(&self) -> Option<WallEdges> {
+        const EDGES: [WallEdges; 4] = [
+            WallEdges::WEST,
+            WallEdges::NORTH,
+            WallEdges::EAST,
+            WallEdges::SOUTH,
+        ];
+        const CORNERS: [WallEdges; 4] = [
+            WallEdges::NORTH_WEST,
+            WallEdges::NORTH_EAST,
+            WallEdges::SOUTH_EAST,
+            WallEdges::SOUTH_WEST,
+        ];
+
+        let rotation = (self.orientation & 3) as usize;
+        let edges = match self.kind() {
+            Some(LocationKind::WallStraight) => EDGES[rotation],
+            Some(LocationKind::WallDiagonalCorner | LocationKind::WallSquareCorner) => {
+                CORNERS[rotation]
+            }
+            Some(LocationKind::WallL) => EDGES[rotation].union(EDGES[(rotation + 1) & 3]),
+            Some(LocationKind::WallDiagonal) if rotation & 1 == 0 => {
+                WallEdges::SOUTH_WEST_TO_NORTH_EAST
+            }
+            Some(LocationKind::WallDiagonal) => WallEdges::NORTH_WEST_TO_SOUTH_EAST,
+            _ => return None,
+        };
+
+        Some(edges)
+    }
+}
+
+/// What a location [type](Location::loc_type) is, following the client's numbering.
+///
+/// The client groups the kinds into four [categories](LocationCategory), which
+/// decide the scene layer a location is drawn on and how it collides.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[repr(u8)]
+pub enum LocationKind {
+    /// A wall along one edge of the tile.
+    WallStraight = 0,
+    /// A short diagonal wall cutting off one corner of the tile.
+    WallDiagonalCorner = 1,
+    /// Two walls meeting in a corner, along two adjacent edges.
+    WallL = 2,
+    /// A pillar filling one corner of the tile.
+    WallSquareCorner = 3,
+    /// Decoration on a straight wall, flush with it.
+    WallDecorStraight = 4,
+    /// Decoration on a straight wall, offset away from it.
+    WallDecorStraightOffset = 5,
+    /// Decoration on the outside of a diagonal wall, offset away from it.
+    WallDecorDiagonalOffset = 6,
+    /// Decoration on the inside of a diagonal wall.
+    WallDecorDiagonal = 7,
+    /// Decoration on both sides of a diagonal wall.
+    WallDecorDiagonalBoth = 8,
+    /// A wall running diagonally across the whole tile.
+    WallDiagonal = 9,
+    /// A regular object, like trees, tables and most interactive objects.
+    Centerpiece = 10,
+    /// A regular object rotated by 45 degrees.
+    CenterpieceDiagonal = 11,
+    /// A sloped roof section.
+    RoofStraight = 12,
+    /// A diagonal roof section attached to an edge.
+    RoofDiagonalWithEdge = 13,
+    /// A diagonal roof section.
+    RoofDiagonal = 14,
+    /// An inward facing roof corner.
+    RoofLConcave = 15,
+    /// An outward facing roof corner.
+    RoofLConvex = 16,
+    /// A flat roof section.
+    RoofFlat = 17,
+    /// A straight roof edge.
+    RoofEdgeStraight = 18,
+    /// A diagonal roof edge corner.
+    RoofEdgeDiagonalCorner = 19,
+    /// An L-shaped roof edge.
+    RoofEdgeL = 20,
+    /// A square roof edge corner.
+    RoofEdgeSquareCorner = 21,
+    /// Decoration lying on the ground, like rugs and flowers.
+    GroundDecor = 22,
+}
+
+impl LocationKind {
+    /// Converts a raw location type, returns `None` above 22.
+    pub const fn from_type(loc_type: u8) -> Option<Self> {
+        let kind = match loc_type {
+            0 => Self::WallStraight,
+            1 => Self::WallDiagonalCorner,
+            2 => Self::WallL,
+            3 => Self::WallSquareCorner,
+            4 => Self::WallDecorStraight,
+            5 => Self::WallDecorStraightOffset,
+            6 => Self::WallDecorDiagonalOffset,
+            7 => Self::WallDecorDiagonal,
+            8 => Self::WallDecorDiagonalBoth,
+            9 => Self::WallDiagonal,
+            10 => Self::Centerpiece,
+            11 => Self::CenterpieceDiagonal,
+            12 => Self::RoofStraight,
+            13 => Self::RoofDiagonalWithEdge,
+            14 => Self::RoofDiagonal,
+            15 => Self::RoofLConcave,
+            16 => Self::RoofLConvex,
+            17 => Self::RoofFlat,
+            18 => Self::RoofEdgeStraight,
+            19 => Self::RoofEdgeDiagonalCorner,
+            20 => Self::RoofEdgeL,
+            21 => Self::RoofEdgeSquareCorner,
+            22 => Self::GroundDecor,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    /// The raw location type.
+    #[inline]
+    pub const fn loc_type(self) -> u8 {
+        self as u8
+    }
+
+    /// The client's grouping of this kind.
+    ///
+    /// Diagonal walls and roofs are placed like centerpieces, even though
+    /// diagonal walls still block [wall edges](Location::wall_edges).
+    pub const fn category(self) -> LocationCategory {
+        match self as u8 {
+            0..=3 => LocationCategory::Wall,
+            4..=8 => LocationCategory::WallDecoration,
+            9..=21 => LocationCategory::Centerpiece,
+            _ => LocationCategory::GroundDecoration,
+        }
+    }
+}
+
+/// Scene layer of a [`LocationKind`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum LocationCategory {
+    /// Types 0 to 3.
+    Wall,
+    /// Types 4 to 8.
+    WallDecoration,
+    /// Types 9 to 21.
+    Centerpiece,
+    /// Type 22.
+    GroundDecoration,
+}
+
+/// Set of tile edges, corners and diagonals blocked by a wall.
+///
+/// See [`Location::wall_edges`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct WallEdges(u16);
+
+impl WallEdges {
+    pub const NORTH: Self = Self(1);
+    pub const EAST: Self = Self(1 << 1);
+    pub const SOUTH: Self = Self(1 << 2);
+    pub const WEST: Self = Self(1 << 3);
+    pub const NORTH_WEST: Self = Self(1 << 4);
+    pub const NORTH_EAST: Self = Self(1 << 5);
+    pub const SOUTH_EAST: Self = Self(1 << 6);
+    pub const SOUTH_WEST: Self = Self(1 << 7);
+    pub const SOUTH_WEST_TO_NORTH_EAST: Self = Self(1 << 8);
+    pub const NORTH_WEST_TO_SOUTH_EAST: Self = Self(1 << 9);
+
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    #[inline]
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl std::ops::BitOr for WallEdges {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
 impl Definition for LocationDefinition {
-    fn new(id: u16, buffer: &[u8]) -> crate::Result<Self> {
-        let loc_def = decode_buffer(id, buffer)?;
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let loc_def = decode_buffer(id as u16, buffer)?;
 
         Ok(loc_def)
     }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        u32::from(self.id)
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
 }
 
 fn decode_buffer(id: u16, mut buffer: &[u8]) -> crate::Result<LocationDefinition> {
@@ -48,30 +392,29 @@ fn decode_buffer(id: u16, mut buffer: &[u8]) -> crate::Result<LocationDefinition
         ..LocationDefinition::default()
     };
 
-    let mut id = -1;
+    let mut id = -1_i32;
 
     loop {
-        let (buf, id_offset) = be_u32_smart_compat(buffer)?;
+        let (buf, id_offset) = smart_u32_compat(buffer)?;
         buffer = buf;
 
         if id_offset == 0 || buffer.is_empty() {
             break;
         }
 
-        id += id_offset as i32;
+        id = id.wrapping_add(id_offset as i32);
 
-        let mut pos = 0;
+        let mut pos = 0_u16;
 
         loop {
-            let (buf, pos_offset) = be_u16_smart(buffer)?;
-            let pos_offset = pos_offset;
+            let (buf, pos_offset) = smart_u16(buffer)?;
             buffer = buf;
 
             if pos_offset == 0 {
                 break;
             }
 
-            pos += pos_offset - 1;
+            pos = pos.wrapping_add(pos_offset - 1);
 
             let local_x = pos >> 6 & 0x3F;
             let local_y = pos & 0x3F;
@@ -99,3 +442,33 @@ fn decode_buffer(id: u16, mut buffer: &[u8]) -> crate::Result<LocationDefinition
 
     Ok(loc_def)
 }
+
+/// Reads 1 byte if the first byte < 128, reads 2 bytes otherwise.
+fn smart_u16(buffer: &[u8]) -> IResult<&[u8], u16, ()> {
+    match buffer.first() {
+        Some(&byte) if byte < 128 => {
+            let (buffer, value) = be_u8(buffer)?;
+            Ok((buffer, u16::from(value)))
+        }
+        Some(_) => {
+            let (buffer, value) = be_u16(buffer)?;
+            Ok((buffer, value.wrapping_sub(0x8000)))
+        }
+        None => Err(nom::Err::Error(())),
+    }
+}
+
+/// Sum of consecutive smarts, continuing as long as one is `i16::MAX`.
+fn smart_u32_compat(mut buffer: &[u8]) -> IResult<&[u8], u32, ()> {
+    let mut value = 0_u32;
+
+    loop {
+        let (buf, part) = smart_u16(buffer)?;
+        buffer = buf;
+        value = value.wrapping_add(u32::from(part));
+
+        if part != i16::MAX as u16 {
+            return Ok((buffer, value));
+        }
+    }
+}
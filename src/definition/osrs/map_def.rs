@@ -4,12 +4,15 @@ use std::{io, io::BufReader};
 use serde::{Deserialize, Serialize};
 
 use super::Definition;
-use crate::extension::ReadExt;
+use crate::{error::ReadError, extension::ReadExt};
 
 const X: usize = 64;
 const Y: usize = 64;
 const Z: usize = 4;
 
+/// Tile setting on plane 1 of bridges.
+const BRIDGE_TILE: u8 = 0x2;
+
 /// Contains all the information about a certain map fetched from the cache through
 /// the [MapLoader](../../loader/osrs/struct.MapLoader.html).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -33,15 +36,25 @@ pub struct MapData {
 }
 
 impl Definition for MapDefinition {
-    fn new(id: u16, buffer: &[u8]) -> crate::Result<Self> {
-        let x = id >> 8;
-        let y = id & 0xFF;
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let x = (id >> 8 & 0xFF) as u16;
+        let y = (id & 0xFF) as u16;
 
         let mut reader = BufReader::new(buffer);
         let map_def = decode_buffer(x, y, &mut reader)?;
 
         Ok(map_def)
     }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        u32::from(self.region_x) << 8 | u32::from(self.region_y)
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
 }
 
 impl MapDefinition {
@@ -54,6 +67,39 @@ impl MapDefinition {
         (self.region_x << 6, self.region_y << 6)
     }
 
+    /// Data of the tile at world coordinates `x` and `y`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReadError::TileOutOfBounds`] when the tile isn't part of this region
+    /// or the plane is above 3.
+    pub fn tile_at(&self, x: u16, y: u16, plane: u8) -> crate::Result<&MapData> {
+        let (base_x, base_y) = self.region_base_coords();
+        let local_x = x.wrapping_sub(base_x) as usize;
+        let local_y = y.wrapping_sub(base_y) as usize;
+
+        if local_x >= X || local_y >= Y || plane as usize >= Z {
+            return Err(ReadError::TileOutOfBounds {
+                region: self.id() as u16,
+                x,
+                y,
+                plane,
+            }
+            .into());
+        }
+
+        Ok(&self.data[plane as usize][local_x][local_y])
+    }
+
+    /// Whether the tile at world coordinates `x` and `y` is part of a bridge,
+    /// the client moves everything above it one plane down.
+    ///
+    /// Tiles outside of the region are never bridges.
+    pub fn is_bridge(&self, x: u16, y: u16) -> bool {
+        self.tile_at(x, y, 1)
+            .is_ok_and(|tile| tile.settings & BRIDGE_TILE == BRIDGE_TILE)
+    }
+
     pub fn blocked_tiles(&self) -> Vec<(u16, u16, u16)> {
         let region_base_x = self.region_x << 6;
         let region_base_y = self.region_y << 6;
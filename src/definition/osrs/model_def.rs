@@ -0,0 +1,499 @@
+use std::io;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// A model vertex, `y` points down.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Vertex {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// A model fetched from the cache through the
+/// [ModelLoader](../../loader/osrs/struct.ModelLoader.html).
+///
+/// The per-face vectors are either empty, when the model doesn't store that
+/// property, or hold one entry per face.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct ModelDefinition {
+    pub id: u32,
+    pub vertices: Vec<Vertex>,
+    /// Triangles as indices into `vertices`.
+    pub faces: Vec<[u16; 3]>,
+    /// HSL colors of the faces.
+    pub face_colors: Vec<u16>,
+    pub face_render_types: Vec<u8>,
+    /// Render priority of every face, empty when they all share `priority`.
+    pub face_priorities: Vec<u8>,
+    pub priority: u8,
+    pub face_alphas: Vec<u8>,
+    /// Texture of every face, `None` for faces that are only colored.
+    pub face_textures: Vec<Option<u16>>,
+    /// Index into `texture_triangles` of every textured face, `None` when the
+    /// face maps the texture onto its own vertices.
+    pub texture_coordinates: Vec<Option<u8>>,
+    /// Vertices of the triangles textures are mapped onto, only decoded for
+    /// planar mappings and zeroed for the others, like the client does.
+    pub texture_triangles: Vec<[u16; 3]>,
+    /// How textures are mapped onto `texture_triangles`, empty when they're all
+    /// planar (0).
+    pub texture_render_types: Vec<u8>,
+    /// Animation skin group of every vertex.
+    pub vertex_skins: Vec<u8>,
+    /// Skin group of every face, used by alpha animations.
+    pub face_skins: Vec<u8>,
+}
+
+impl ModelDefinition {
+    /// The smallest and largest coordinates of the vertices, `None` for a model
+    /// without vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::{ModelDefinition, Vertex};
+    ///
+    /// let model = ModelDefinition {
+    ///     vertices: vec![
+    ///         Vertex { x: -4, y: 0, z: 2 },
+    ///         Vertex { x: 3, y: -8, z: 1 },
+    ///     ],
+    ///     ..ModelDefinition::default()
+    /// };
+    ///
+    /// let (min, max) = model.bounds().unwrap();
+    /// assert_eq!(min, Vertex { x: -4, y: -8, z: 1 });
+    /// assert_eq!(max, Vertex { x: 3, y: 0, z: 2 });
+    /// ```
+    pub fn bounds(&self) -> Option<(Vertex, Vertex)> {
+        let first = *self.vertices.first()?;
+
+        Some(
+            self.vertices
+                .iter()
+                .fold((first, first), |(min, max), vertex| {
+                    (
+                        Vertex {
+                            x: min.x.min(vertex.x),
+                            y: min.y.min(vertex.y),
+                            z: min.z.min(vertex.z),
+                        },
+                        Vertex {
+                            x: max.x.max(vertex.x),
+                            y: max.y.max(vertex.y),
+                            z: max.z.max(vertex.z),
+                        },
+                    )
+                }),
+        )
+    }
+
+    /// The render priority of a face, `priority` for models without
+    /// per-face priorities.
+    #[inline]
+    pub fn face_priority(&self, face: usize) -> u8 {
+        self.face_priorities
+            .get(face)
+            .copied()
+            .unwrap_or(self.priority)
+    }
+}
+
+impl Definition for ModelDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let model_def = decode_buffer(id, buffer)?;
+
+        Ok(model_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed model")
+}
+
+/// Consecutive sections of the model body.
+struct Sections<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Sections<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.buffer.len())
+            .ok_or_else(malformed)?;
+        let section = &self.buffer[self.offset..end];
+        self.offset = end;
+
+        Ok(section)
+    }
+
+    fn take_if(&mut self, present: bool, len: usize) -> io::Result<&'a [u8]> {
+        self.take(if present { len } else { 0 })
+    }
+}
+
+/// Counts and flags stored in the footer.
+#[derive(Default)]
+struct Header {
+    vertex_count: usize,
+    face_count: usize,
+    texture_count: usize,
+    render_types: bool,
+    priority: u8,
+    alphas: bool,
+    face_skins: bool,
+    textures: bool,
+    vertex_skins: bool,
+    x_len: usize,
+    y_len: usize,
+    z_len: usize,
+    index_len: usize,
+    texture_coordinate_len: usize,
+    skin_len: usize,
+}
+
+fn decode_buffer(id: u32, buffer: &[u8]) -> io::Result<ModelDefinition> {
+    let len = buffer.len();
+    if len < 2 {
+        return Err(malformed());
+    }
+
+    // the newer formats end with 0xFF followed by their version.
+    match (buffer[len - 2], buffer[len - 1]) {
+        (0xFF, 0xFD) => decode_versioned(id, buffer, true),
+        (0xFF, 0xFE) => decode_legacy(id, buffer, true),
+        (0xFF, 0xFF) => decode_versioned(id, buffer, false),
+        _ => decode_legacy(id, buffer, false),
+    }
+}
+
+fn split_footer(buffer: &[u8], len: usize) -> io::Result<(&[u8], &[u8])> {
+    let offset = buffer.len().checked_sub(len).ok_or_else(malformed)?;
+
+    Ok(buffer.split_at(offset))
+}
+
+/// Decodes the original format and its successor with animaya skins, these
+/// keep texture info in the render type of faces.
+fn decode_legacy(id: u32, buffer: &[u8], animaya: bool) -> io::Result<ModelDefinition> {
+    let (body, mut footer) = split_footer(buffer, if animaya { 23 } else { 18 })?;
+
+    let mut header = Header {
+        vertex_count: usize::from(footer.read_u16()?),
+        face_count: usize::from(footer.read_u16()?),
+        texture_count: usize::from(footer.read_u8()?),
+        render_types: footer.read_u8()? == 1,
+        priority: footer.read_u8()?,
+        alphas: footer.read_u8()? == 1,
+        face_skins: footer.read_u8()? == 1,
+        vertex_skins: footer.read_u8()? == 1,
+        ..Header::default()
+    };
+    if animaya {
+        footer.read_u8()?;
+    }
+    header.x_len = usize::from(footer.read_u16()?);
+    header.y_len = usize::from(footer.read_u16()?);
+    header.z_len = usize::from(footer.read_u16()?);
+    header.index_len = usize::from(footer.read_u16()?);
+    header.skin_len = if animaya {
+        usize::from(footer.read_u16()?)
+    } else if header.vertex_skins {
+        header.vertex_count
+    } else {
+        0
+    };
+
+    let (vertex_count, face_count) = (header.vertex_count, header.face_count);
+    let mut sections = Sections {
+        buffer: body,
+        offset: 0,
+    };
+    let vertex_flags = sections.take(vertex_count)?;
+    let compression_types = sections.take(face_count)?;
+    let priorities = sections.take_if(header.priority == 255, face_count)?;
+    let face_skins = sections.take_if(header.face_skins, face_count)?;
+    let render_info = sections.take_if(header.render_types, face_count)?;
+    let skins = sections.take(header.skin_len)?;
+    let alphas = sections.take_if(header.alphas, face_count)?;
+    let indices = sections.take(header.index_len)?;
+    let mut colors = sections.take(face_count * 2)?;
+    let mut texture_triangles = sections.take(header.texture_count * 6)?;
+    let xs = sections.take(header.x_len)?;
+    let ys = sections.take(header.y_len)?;
+    let zs = sections.take(header.z_len)?;
+
+    let mut model_def = ModelDefinition {
+        id,
+        vertices: read_vertices(vertex_flags, xs, ys, zs, vertex_count)?,
+        faces: read_faces(compression_types, indices, face_count, vertex_count)?,
+        face_priorities: priorities.to_vec(),
+        priority: if header.priority == 255 { 0 } else { header.priority },
+        face_alphas: alphas.to_vec(),
+        face_skins: face_skins.to_vec(),
+        vertex_skins: read_vertex_skins(skins, &header)?,
+        ..ModelDefinition::default()
+    };
+    for _ in 0..face_count {
+        model_def.face_colors.push(colors.read_u16()?);
+    }
+    for _ in 0..header.texture_count {
+        model_def
+            .texture_triangles
+            .push(read_triangle(&mut texture_triangles)?);
+    }
+
+    if header.render_types {
+        let mut face_textures = Vec::with_capacity(face_count);
+        let mut texture_coordinates = Vec::with_capacity(face_count);
+        for (face, &info) in render_info.iter().enumerate() {
+            model_def.face_render_types.push(info & 1);
+
+            if info & 2 == 0 {
+                face_textures.push(None);
+                texture_coordinates.push(None);
+                continue;
+            }
+
+            // textured faces store their texture in place of the color.
+            face_textures.push(Some(model_def.face_colors[face]));
+            model_def.face_colors[face] = 127;
+
+            let coordinate = info >> 2;
+            let own_vertices = model_def
+                .texture_triangles
+                .get(usize::from(coordinate))
+                .is_some_and(|triangle| *triangle == model_def.faces[face]);
+            texture_coordinates.push(if own_vertices { None } else { Some(coordinate) });
+        }
+
+        if face_textures.iter().any(Option::is_some) {
+            model_def.face_textures = face_textures;
+        }
+        if texture_coordinates.iter().any(Option::is_some) {
+            model_def.texture_coordinates = texture_coordinates;
+        }
+    }
+
+    Ok(model_def)
+}
+
+/// Decodes the versioned format and its successor with animaya skins, these
+/// store textures separately and support non-planar texture mapping.
+fn decode_versioned(id: u32, buffer: &[u8], animaya: bool) -> io::Result<ModelDefinition> {
+    let (body, mut footer) = split_footer(buffer, if animaya { 26 } else { 23 })?;
+
+    let mut header = Header {
+        vertex_count: usize::from(footer.read_u16()?),
+        face_count: usize::from(footer.read_u16()?),
+        texture_count: usize::from(footer.read_u8()?),
+        render_types: footer.read_u8()? & 1 == 1,
+        priority: footer.read_u8()?,
+        alphas: footer.read_u8()? == 1,
+        face_skins: footer.read_u8()? == 1,
+        textures: footer.read_u8()? == 1,
+        vertex_skins: footer.read_u8()? == 1,
+        ..Header::default()
+    };
+    if animaya {
+        footer.read_u8()?;
+    }
+    header.x_len = usize::from(footer.read_u16()?);
+    header.y_len = usize::from(footer.read_u16()?);
+    header.z_len = usize::from(footer.read_u16()?);
+    header.index_len = usize::from(footer.read_u16()?);
+    header.texture_coordinate_len = usize::from(footer.read_u16()?);
+    header.skin_len = if animaya {
+        usize::from(footer.read_u16()?)
+    } else if header.vertex_skins {
+        header.vertex_count
+    } else {
+        0
+    };
+
+    let (vertex_count, face_count) = (header.vertex_count, header.face_count);
+    let mut sections = Sections {
+        buffer: body,
+        offset: 0,
+    };
+    let texture_render_types = sections.take(header.texture_count)?;
+    let vertex_flags = sections.take(vertex_count)?;
+    let render_types = sections.take_if(header.render_types, face_count)?;
+    let compression_types = sections.take(face_count)?;
+    let priorities = sections.take_if(header.priority == 255, face_count)?;
+    let face_skins = sections.take_if(header.face_skins, face_count)?;
+    let skins = sections.take(header.skin_len)?;
+    let alphas = sections.take_if(header.alphas, face_count)?;
+    let indices = sections.take(header.index_len)?;
+    let mut textures = sections.take_if(header.textures, face_count * 2)?;
+    let mut coordinates = sections.take(header.texture_coordinate_len)?;
+    let mut colors = sections.take(face_count * 2)?;
+    let xs = sections.take(header.x_len)?;
+    let ys = sections.take(header.y_len)?;
+    let zs = sections.take(header.z_len)?;
+
+    let planar_count = texture_render_types.iter().filter(|&&kind| kind == 0).count();
+    let mut planar = sections.take(planar_count * 6)?;
+
+    let mut model_def = ModelDefinition {
+        id,
+        vertices: read_vertices(vertex_flags, xs, ys, zs, vertex_count)?,
+        faces: read_faces(compression_types, indices, face_count, vertex_count)?,
+        face_render_types: render_types.to_vec(),
+        face_priorities: priorities.to_vec(),
+        priority: if header.priority == 255 { 0 } else { header.priority },
+        face_alphas: alphas.to_vec(),
+        face_skins: face_skins.to_vec(),
+        vertex_skins: read_vertex_skins(skins, &header)?,
+        ..ModelDefinition::default()
+    };
+    for _ in 0..face_count {
+        model_def.face_colors.push(colors.read_u16()?);
+    }
+
+    if header.textures {
+        for _ in 0..face_count {
+            let texture = textures.read_u16()?.checked_sub(1);
+            model_def.face_textures.push(texture);
+
+            // coordinates are only stored alongside texture triangles.
+            let coordinate = match texture {
+                Some(_) if header.texture_count > 0 => coordinates.read_u8()?.checked_sub(1),
+                _ => None,
+            };
+            model_def.texture_coordinates.push(coordinate);
+        }
+    }
+
+    for &kind in texture_render_types {
+        let triangle = if kind == 0 {
+            read_triangle(&mut planar)?
+        } else {
+            [0; 3]
+        };
+        model_def.texture_triangles.push(triangle);
+    }
+    if texture_render_types.iter().any(|&kind| kind != 0) {
+        model_def.texture_render_types = texture_render_types.to_vec();
+    }
+
+    Ok(model_def)
+}
+
+/// Vertices are stored as deltas to the previous vertex, split by axis. The
+/// flags of a vertex tell which axes changed.
+fn read_vertices(
+    mut flags: &[u8],
+    mut xs: &[u8],
+    mut ys: &[u8],
+    mut zs: &[u8],
+    count: usize,
+) -> io::Result<Vec<Vertex>> {
+    let mut vertices = Vec::with_capacity(count);
+    let mut vertex = Vertex::default();
+
+    for _ in 0..count {
+        let flag = flags.read_u8()?;
+        if flag & 1 != 0 {
+            vertex.x += i32::from(xs.read_smart_i16()?);
+        }
+        if flag & 2 != 0 {
+            vertex.y += i32::from(ys.read_smart_i16()?);
+        }
+        if flag & 4 != 0 {
+            vertex.z += i32::from(zs.read_smart_i16()?);
+        }
+
+        vertices.push(vertex);
+    }
+
+    Ok(vertices)
+}
+
+/// Faces are stored as deltas to the last index read, the compression type of
+/// a face tells which indices it shares with the previous face:
+///
+/// 1. none, all three indices follow.
+/// 2. keeps the first and last index, the last one moves to the middle.
+/// 3. keeps the middle and last index, the last one moves to the front.
+/// 4. keeps the first two indices swapped.
+fn read_faces(
+    mut types: &[u8],
+    mut indices: &[u8],
+    count: usize,
+    vertex_count: usize,
+) -> io::Result<Vec<[u16; 3]>> {
+    let mut faces = Vec::with_capacity(count);
+    let (mut a, mut b, mut c, mut last) = (0_i32, 0_i32, 0_i32, 0_i32);
+
+    for _ in 0..count {
+        match types.read_u8()? {
+            1 => {
+                a = last + i32::from(indices.read_smart_i16()?);
+                b = a + i32::from(indices.read_smart_i16()?);
+                c = b + i32::from(indices.read_smart_i16()?);
+            }
+            2 => {
+                b = c;
+                c = last + i32::from(indices.read_smart_i16()?);
+            }
+            3 => {
+                a = c;
+                c = last + i32::from(indices.read_smart_i16()?);
+            }
+            4 => {
+                std::mem::swap(&mut a, &mut b);
+                c = last + i32::from(indices.read_smart_i16()?);
+            }
+            _ => return Err(malformed()),
+        }
+        last = c;
+
+        let mut face = [0; 3];
+        for (index, vertex) in face.iter_mut().zip([a, b, c]) {
+            *index = u16::try_from(vertex)
+                .ok()
+                .filter(|&vertex| usize::from(vertex) < vertex_count)
+                .ok_or_else(malformed)?;
+        }
+        faces.push(face);
+    }
+
+    Ok(faces)
+}
+
+fn read_triangle(reader: &mut &[u8]) -> io::Result<[u16; 3]> {
+    Ok([reader.read_u16()?, reader.read_u16()?, reader.read_u16()?])
+}
+
+/// Vertex skins lead the skin section, animaya groups follow them.
+fn read_vertex_skins(skins: &[u8], header: &Header) -> io::Result<Vec<u8>> {
+    if !header.vertex_skins {
+        return Ok(Vec::new());
+    }
+
+    skins
+        .get(..header.vertex_count)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(malformed)
+}
@@ -1,32 +1,60 @@
-use std::{collections::HashMap, io, io::BufReader};
+use std::{collections::BTreeMap, io, io::BufReader};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use super::Definition;
-use crate::{extension::ReadExt, util};
+use crate::{
+    definition::{
+        opcodes::{self, DefinitionKind, UnknownOpcode},
+        Value,
+    },
+    extension::ReadExt,
+};
 
 /// Contains all the information about a certain npc fetched from the cache through
 /// the [NpcLoader](../../loader/osrs/struct.NpcLoader.html).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
 pub struct NpcDefinition {
-    pub id: u16,
+    pub id: u32,
     pub name: String,
     pub size: usize,
     pub actions: [String; 5],
     pub visible_on_minimap: bool,
     pub combat_level: Option<u16>,
-    pub configs: Vec<u16>,
-    pub varbit_id: Option<u16>,
-    pub varp_index: Option<u16>,
+    /// Only set by revisions from 2023 onwards.
+    pub category: Option<u16>,
+    /// Only set by revisions from 2023 onwards.
+    pub height: Option<u16>,
+    pub stats: NpcStats,
+    /// Varbit selecting the [morph](NpcDefinition::morph), takes precedence over `morph_varp`.
+    pub morph_varbit: Option<u16>,
+    /// Varp selecting the [morph](NpcDefinition::morph).
+    pub morph_varp: Option<u16>,
+    /// Npc ids indexed by the var value, `None` hides the npc. The last entry
+    /// is the default for values past the end.
+    pub morphisms: Vec<Option<u16>>,
     pub interactable: bool,
     pub pet: bool,
-    pub params: HashMap<u32, String>,
+    pub low_priority_follower_ops: bool,
+    pub params: BTreeMap<u32, Value>,
     pub model_data: NpcModelData,
     pub animation_data: NpcAnimationData,
 }
 
+/// Combat stats, only set by revisions from 2023 onwards.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct NpcStats {
+    pub attack: Option<u16>,
+    pub defence: Option<u16>,
+    pub strength: Option<u16>,
+    pub hitpoints: Option<u16>,
+    pub ranged: Option<u16>,
+    pub magic: Option<u16>,
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
 pub struct NpcModelData {
@@ -56,19 +84,103 @@ pub struct NpcAnimationData {
     pub rotate_180: Option<u16>,
     pub rotate_90_left: Option<u16>,
     pub rotate_90_right: Option<u16>,
+    pub running: Option<u16>,
+    pub running_rotate_180: Option<u16>,
+    pub running_rotate_left: Option<u16>,
+    pub running_rotate_right: Option<u16>,
+    pub crawling: Option<u16>,
+    pub crawling_rotate_180: Option<u16>,
+    pub crawling_rotate_left: Option<u16>,
+    pub crawling_rotate_right: Option<u16>,
 }
 
 impl Definition for NpcDefinition {
-    fn new(id: u16, buffer: &[u8]) -> crate::Result<Self> {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
         let mut reader = BufReader::new(buffer);
-        let npc_def = decode_buffer(id, &mut reader)?;
+        let (npc_def, _) = decode_buffer(id, &mut reader, false)?;
 
         Ok(npc_def)
     }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        Some(self.name.as_str()).filter(|name| !name.is_empty() && *name != "null")
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+impl NpcDefinition {
+    /// The npc id shown for the given value of the morph varbit or varp, the
+    /// default morph for values past the end.
+    ///
+    /// `None` when the npc doesn't morph or is hidden for that value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::NpcDefinition;
+    ///
+    /// let npc = NpcDefinition {
+    ///     morph_varbit: Some(12),
+    ///     morphisms: vec![None, Some(7), Some(8)],
+    ///     ..NpcDefinition::default()
+    /// };
+    ///
+    /// assert_eq!(npc.morph(0), None);
+    /// assert_eq!(npc.morph(1), Some(7));
+    /// assert_eq!(npc.morph(5), Some(8));
+    /// ```
+    pub fn morph(&self, value: usize) -> Option<u16> {
+        super::resolve_morph(&self.morphisms, value)
+    }
+
+    /// Parses the definition like [`Definition::new`], but stops at the first
+    /// opcode this crate doesn't know instead of failing.
+    ///
+    /// Both the pre-2023 layout and the newer one (category, stats, height,
+    /// running and crawling animations, follower flags) are understood.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the buffer ends before the definition does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::NpcDefinition;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// // name "Man", then opcode 200 which no revision uses.
+    /// let (npc, unknown) = NpcDefinition::new_lenient(1, &[2, 77, 97, 110, 0, 200, 1, 0])?;
+    ///
+    /// assert_eq!(npc.name, "Man");
+    /// assert_eq!(unknown.map(|unknown| unknown.opcode), Some(200));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_lenient(id: u32, buffer: &[u8]) -> crate::Result<(Self, Option<UnknownOpcode>)> {
+        let mut reader = BufReader::new(buffer);
+
+        Ok(decode_buffer(id, &mut reader, true)?)
+    }
 }
 
 #[allow(clippy::too_many_lines)]
-fn decode_buffer(id: u16, reader: &mut BufReader<&[u8]>) -> io::Result<NpcDefinition> {
+fn decode_buffer(
+    id: u32,
+    reader: &mut BufReader<&[u8]>,
+    lenient: bool,
+) -> io::Result<(NpcDefinition, Option<UnknownOpcode>)> {
+    let len = reader.get_ref().len();
     let mut npc_def = NpcDefinition {
         id,
         interactable: true,
@@ -85,142 +197,109 @@ fn decode_buffer(id: u16, reader: &mut BufReader<&[u8]>) -> io::Result<NpcDefini
 
     loop {
         let opcode = reader.read_u8()?;
+        if opcode == 0 {
+            break;
+        }
+        if lenient && opcodes::lookup(DefinitionKind::Npc.opcodes(), opcode).is_none() {
+            let read = len - reader.get_ref().len() - reader.buffer().len();
+            let unknown = UnknownOpcode {
+                kind: DefinitionKind::Npc,
+                opcode,
+                offset: read - 1,
+            };
+
+            return Ok((npc_def, Some(unknown)));
+        }
 
+        let field = opcodes::read(DefinitionKind::Npc, opcode, reader)?;
         match opcode {
-            0 => break,
-            1 => {
-                let len = reader.read_u8()?;
-                for _ in 0..len {
-                    npc_def.model_data.models.push(reader.read_u16()?);
-                }
-            }
-            2 => {
-                npc_def.name = reader.read_string()?;
-            }
-            12 => {
-                npc_def.size = reader.read_u8()? as usize;
-            }
-            13 => {
-                npc_def.animation_data.standing = Some(reader.read_u16()?);
-            }
-            14 => {
-                npc_def.animation_data.walking = Some(reader.read_u16()?);
-            }
-            15 => {
-                npc_def.animation_data.rotate_left = Some(reader.read_u16()?);
-            }
-            16 => {
-                npc_def.animation_data.rotate_right = Some(reader.read_u16()?);
-            }
+            1 => npc_def.model_data.models.extend(field.get::<Vec<u16>>()?),
+            2 => npc_def.name = field.get()?,
+            12 => npc_def.size = field.get::<u8>()?.into(),
+            13 => npc_def.animation_data.standing = Some(field.get()?),
+            14 => npc_def.animation_data.walking = Some(field.get()?),
+            15 => npc_def.animation_data.rotate_left = Some(field.get()?),
+            16 => npc_def.animation_data.rotate_right = Some(field.get()?),
             17 => {
-                npc_def.animation_data.walking = Some(reader.read_u16()?);
-                npc_def.animation_data.rotate_180 = Some(reader.read_u16()?);
-                npc_def.animation_data.rotate_90_right = Some(reader.read_u16()?);
-                npc_def.animation_data.rotate_90_left = Some(reader.read_u16()?);
-            }
-            30..=34 => {
-                npc_def.actions[opcode as usize - 30] = reader.read_string()?;
+                let [walking, rotate_180, rotate_90_right, rotate_90_left] = field.get()?;
+                let animations = &mut npc_def.animation_data;
+                animations.walking = Some(walking);
+                animations.rotate_180 = Some(rotate_180);
+                animations.rotate_90_right = Some(rotate_90_right);
+                animations.rotate_90_left = Some(rotate_90_left);
             }
+            18 => npc_def.category = Some(field.get()?),
+            30..=34 => npc_def.actions[opcode as usize - 30] = field.get()?,
             40 => {
-                let len = reader.read_u8()?;
-                for _ in 0..len {
-                    npc_def.model_data.recolor_find.push(reader.read_u16()?);
-                    npc_def.model_data.recolor_replace.push(reader.read_u16()?);
+                for (find, replace) in field.get::<Vec<(u16, u16)>>()? {
+                    npc_def.model_data.recolor_find.push(find);
+                    npc_def.model_data.recolor_replace.push(replace);
                 }
             }
             41 => {
-                let len = reader.read_u8()?;
-                for _ in 0..len {
-                    npc_def.model_data.retexture_find.push(reader.read_u16()?);
-                    npc_def
-                        .model_data
-                        .retexture_replace
-                        .push(reader.read_u16()?);
+                for (find, replace) in field.get::<Vec<(u16, u16)>>()? {
+                    npc_def.model_data.retexture_find.push(find);
+                    npc_def.model_data.retexture_replace.push(replace);
                 }
             }
-            60 => {
-                let len = reader.read_u8()?;
-                for _ in 0..len {
-                    npc_def.model_data.chat_head_models.push(reader.read_u16()?);
+            60 => npc_def
+                .model_data
+                .chat_head_models
+                .extend(field.get::<Vec<u16>>()?),
+            74..=79 => {
+                let stat = Some(field.get()?);
+                let stats = &mut npc_def.stats;
+                match opcode {
+                    74 => stats.attack = stat,
+                    75 => stats.defence = stat,
+                    76 => stats.strength = stat,
+                    77 => stats.hitpoints = stat,
+                    78 => stats.ranged = stat,
+                    _ => stats.magic = stat,
                 }
             }
-            93 => npc_def.visible_on_minimap = true,
-            95 => {
-                npc_def.combat_level = Some(reader.read_u16()?);
-            }
-            97 => {
-                npc_def.model_data.width_scale = reader.read_u16()?;
+            93 => npc_def.visible_on_minimap = field.get()?,
+            95 => npc_def.combat_level = Some(field.get()?),
+            97 => npc_def.model_data.width_scale = field.get()?,
+            98 => npc_def.model_data.height_scale = field.get()?,
+            99 => npc_def.model_data.render_priority = field.get()?,
+            100 => npc_def.model_data.ambient = field.get()?,
+            101 => npc_def.model_data.contrast = field.get()?,
+            102 => npc_def.model_data.head_icon = Some(field.get()?),
+            103 => npc_def.model_data.rotate_speed = field.get()?,
+            106 | 118 => {
+                (npc_def.morph_varbit, npc_def.morph_varp, npc_def.morphisms) =
+                    super::morphs(field.get()?);
             }
-            98 => {
-                npc_def.model_data.height_scale = reader.read_u16()?;
+            107 => npc_def.interactable = !field.get::<bool>()?,
+            109 => npc_def.model_data.rotate_flag = !field.get::<bool>()?,
+            111 | 122 => npc_def.pet = field.get()?,
+            114 => npc_def.animation_data.running = Some(field.get()?),
+            115 => {
+                let [running, rotate_180, rotate_left, rotate_right] = field.get()?;
+                let animations = &mut npc_def.animation_data;
+                animations.running = Some(running);
+                animations.running_rotate_180 = Some(rotate_180);
+                animations.running_rotate_left = Some(rotate_left);
+                animations.running_rotate_right = Some(rotate_right);
             }
-            99 => npc_def.model_data.render_priority = true,
-            100 => {
-                npc_def.model_data.ambient = reader.read_u8()?;
+            116 => npc_def.animation_data.crawling = Some(field.get()?),
+            117 => {
+                let [crawling, rotate_180, rotate_left, rotate_right] = field.get()?;
+                let animations = &mut npc_def.animation_data;
+                animations.crawling = Some(crawling);
+                animations.crawling_rotate_180 = Some(rotate_180);
+                animations.crawling_rotate_left = Some(rotate_left);
+                animations.crawling_rotate_right = Some(rotate_right);
             }
-            101 => {
-                npc_def.model_data.contrast = reader.read_u8()?;
-            }
-            102 => {
-                npc_def.model_data.head_icon = Some(reader.read_u16()?);
-            }
-            103 => {
-                npc_def.model_data.rotate_speed = reader.read_u16()?;
-            }
-            106 => {
-                let varbit_id = reader.read_u16()?;
-                npc_def.varbit_id = if varbit_id == std::u16::MAX {
-                    None
-                } else {
-                    Some(varbit_id)
-                };
-
-                let varp_index = reader.read_u16()?;
-                npc_def.varp_index = if varp_index == std::u16::MAX {
-                    None
-                } else {
-                    Some(varp_index)
-                };
-
-                npc_def.configs = Vec::new();
-                let len = reader.read_u8()?;
-                for _ in 0..=len {
-                    npc_def.configs.push(reader.read_u16()?);
-                }
-            }
-            107 => npc_def.interactable = false,
-            109 => npc_def.model_data.rotate_flag = false,
-            111 => npc_def.pet = true,
-            118 => {
-                let varbit_id = reader.read_u16()?;
-                npc_def.varbit_id = if varbit_id == std::u16::MAX {
-                    None
-                } else {
-                    Some(varbit_id)
-                };
-
-                let varp_index = reader.read_u16()?;
-                npc_def.varp_index = if varp_index == std::u16::MAX {
-                    None
-                } else {
-                    Some(varp_index)
-                };
-
-                // should append var at end
-                let _var = reader.read_u16()?;
-
-                npc_def.configs = Vec::new();
-                let len = reader.read_u8()?;
-                for _ in 0..=len {
-                    npc_def.configs.push(reader.read_u16()?);
-                }
-            }
-            249 => {
-                npc_def.params = util::read_parameters(reader)?;
-            }
-            _ => unreachable!(),
+            123 => npc_def.low_priority_follower_ops = field.get()?,
+            124 => npc_def.height = Some(field.get()?),
+            249 => npc_def.params = field.get()?,
+            // known opcodes of fields that aren't stored.
+            _ => {}
         }
     }
 
-    Ok(npc_def)
+    Ok((npc_def, None))
 }
+
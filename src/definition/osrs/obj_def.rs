@@ -1,35 +1,53 @@
-use std::{collections::HashMap, io, io::BufReader};
+use std::{collections::BTreeMap, io, io::BufReader};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use super::Definition;
-use crate::{extension::ReadExt, util};
+use crate::{
+    definition::{
+        opcodes::{self, DefinitionKind},
+        Value,
+    },
+    extension::ReadExt,
+};
 
 /// Contains all the information about a certain object fetched from the cache through
 /// the [ObjectLoader](../../loader/osrs/struct.ObjectLoader.html).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
 pub struct ObjectDefinition {
-    pub id: u16,
+    pub id: u32,
     pub name: String,
-    pub config_id: Option<u16>,
     pub map_area_id: Option<u16>,
     pub map_scene_id: u16,
-    pub animation_id: u16,
+    pub animation_id: Option<u16>,
+    /// Whether the object clips the tiles it stands on, `false` when `interact_type` is 0.
     pub solid: bool,
+    /// Walls of hollow objects don't block line of sight.
+    pub hollow: bool,
+    pub occludes: bool,
     pub shadow: bool,
     pub obstruct_ground: bool,
     pub supports_items: Option<u8>,
+    /// Menu actions, empty for unused slots.
     pub actions: [String; 5],
+    /// 0 for objects that can be walked through, 1 for objects only reachable
+    /// from their sides and 2 for everything else.
     pub interact_type: u8,
     pub rotated: bool,
     pub ambient_sound_id: u16,
     pub blocks_projectile: bool,
     pub wall_or_door: Option<u8>,
     pub contoured_ground: Option<u8>,
-    pub config_change_dest: Vec<u16>,
-    pub params: HashMap<u32, String>,
+    /// Varbit selecting the [morph](ObjectDefinition::morph), takes precedence over `morph_varp`.
+    pub morph_varbit: Option<u16>,
+    /// Varp selecting the [morph](ObjectDefinition::morph).
+    pub morph_varp: Option<u16>,
+    /// Object ids indexed by the var value, `None` hides the object. The last
+    /// entry is the default for values past the end.
+    pub morphisms: Vec<Option<u16>>,
+    pub params: BTreeMap<u32, Value>,
     pub model_data: ObjectModelData,
     pub category: u16,
 }
@@ -51,7 +69,6 @@ pub struct ObjectModelData {
     pub model_size_x: u16,
     pub model_size_y: u16,
     pub model_size_z: u16,
-    pub varp_id: Option<u16>,
     pub ambient: u8,
     pub contrast: u8,
     pub decord_displacement: u8,
@@ -59,17 +76,62 @@ pub struct ObjectModelData {
     pub blocking_mask: u8,
 }
 
+impl ObjectDefinition {
+    /// Returns `true` if one of the actions matches `action`, ignoring ASCII case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::definition::osrs::ObjectDefinition;
+    /// let door = ObjectDefinition {
+    ///     actions: ["Open".to_owned(), String::new(), String::new(), String::new(), String::new()],
+    ///     ..ObjectDefinition::default()
+    /// };
+    ///
+    /// assert!(door.has_action("open"));
+    /// assert!(!door.has_action("Close"));
+    /// ```
+    pub fn has_action(&self, action: &str) -> bool {
+        self.actions
+            .iter()
+            .any(|object_action| !object_action.is_empty() && object_action.eq_ignore_ascii_case(action))
+    }
+
+    /// The object id shown for the given value of the morph varbit or varp,
+    /// the default morph for values past the end.
+    ///
+    /// `None` when the object doesn't morph or is hidden for that value.
+    pub fn morph(&self, value: usize) -> Option<u16> {
+        super::resolve_morph(&self.morphisms, value)
+    }
+}
+
 impl Definition for ObjectDefinition {
-    fn new(id: u16, buffer: &[u8]) -> crate::Result<Self> {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
         let mut reader = BufReader::new(buffer);
         let mut obj_def = decode_buffer(id, &mut reader)?;
         post(&mut obj_def);
 
         Ok(obj_def)
     }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        Some(self.name.as_str()).filter(|name| !name.is_empty() && *name != "null")
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
 }
 
-fn decode_buffer(id: u16, reader: &mut BufReader<&[u8]>) -> io::Result<ObjectDefinition> {
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<ObjectDefinition> {
     let mut obj_def = ObjectDefinition {
         id,
         interact_type: 2,
@@ -89,192 +151,90 @@ fn decode_buffer(id: u16, reader: &mut BufReader<&[u8]>) -> io::Result<ObjectDef
 
     loop {
         let opcode = reader.read_u8()?;
+        if opcode == 0 {
+            break;
+        }
 
+        let field = opcodes::read(DefinitionKind::Object, opcode, reader)?;
         match opcode {
-            0 => break,
             1 => {
-                let len = reader.read_u8()?;
-                for _ in 0..len {
-                    obj_def.model_data.models.push(reader.read_u16()?);
-                    obj_def.model_data.types.push(reader.read_u8()?);
+                for (model, ty) in field.get::<Vec<(u16, u8)>>()? {
+                    obj_def.model_data.models.push(model);
+                    obj_def.model_data.types.push(ty);
                 }
             }
-            2 => {
-                obj_def.name = reader.read_string()?;
-            }
+            2 => obj_def.name = field.get()?,
             5 => {
-                let len = reader.read_u8()?;
                 obj_def.model_data.types.clear();
-                for _ in 0..len {
-                    obj_def.model_data.models.push(reader.read_u16()?);
-                }
-            }
-            14 => {
-                obj_def.model_data.size_x = reader.read_u8()?;
-            }
-            15 => {
-                obj_def.model_data.size_y = reader.read_u8()?;
+                obj_def.model_data.models.extend(field.get::<Vec<u16>>()?);
             }
+            14 => obj_def.model_data.size_x = field.get()?,
+            15 => obj_def.model_data.size_y = field.get()?,
             17 => {
+                field.get::<bool>()?;
                 obj_def.interact_type = 0;
+                obj_def.solid = false;
                 obj_def.blocks_projectile = false;
             }
-            18 => {
-                obj_def.blocks_projectile = false;
-            }
-            19 => {
-                obj_def.wall_or_door = Some(reader.read_u8()?);
-            }
+            18 => obj_def.blocks_projectile = !field.get::<bool>()?,
+            19 => obj_def.wall_or_door = Some(field.get()?),
             21 => {
+                field.get::<bool>()?;
                 obj_def.contoured_ground = Some(0);
             }
-            22 => {
-                obj_def.model_data.merge_normals = true;
-            }
-            23 => { /* skip */ }
+            22 => obj_def.model_data.merge_normals = field.get()?,
+            23 => obj_def.occludes = field.get()?,
             24 => {
-                obj_def.animation_id = reader.read_u16()?;
+                let animation_id = field.get()?;
+                obj_def.animation_id = (animation_id != u16::MAX).then_some(animation_id);
             }
             27 => {
+                field.get::<bool>()?;
                 obj_def.interact_type = 1;
             }
-            28 => {
-                obj_def.model_data.decord_displacement = reader.read_u8()?;
-            }
-            29 => {
-                obj_def.model_data.ambient = reader.read_u8()?;
-            }
-            30..=34 => {
-                obj_def.actions[opcode as usize - 30] = reader.read_string()?;
-            }
-            39 => {
-                obj_def.model_data.contrast = reader.read_u8()?;
-            }
+            28 => obj_def.model_data.decord_displacement = field.get()?,
+            29 => obj_def.model_data.ambient = field.get()?,
+            30..=34 => obj_def.actions[opcode as usize - 30] = field.get()?,
+            39 => obj_def.model_data.contrast = field.get()?,
             40 => {
-                let len = reader.read_u8()?;
-                for _ in 0..len {
-                    obj_def.model_data.recolor_find.push(reader.read_u16()?);
-                    obj_def.model_data.recolor_replace.push(reader.read_u16()?);
+                for (find, replace) in field.get::<Vec<(u16, u16)>>()? {
+                    obj_def.model_data.recolor_find.push(find);
+                    obj_def.model_data.recolor_replace.push(replace);
                 }
             }
             41 => {
-                let len = reader.read_u8()?;
-                for _ in 0..len {
-                    obj_def.model_data.retexture_find.push(reader.read_u16()?);
-                    obj_def
-                        .model_data
-                        .retexture_replace
-                        .push(reader.read_u16()?);
+                for (find, replace) in field.get::<Vec<(u16, u16)>>()? {
+                    obj_def.model_data.retexture_find.push(find);
+                    obj_def.model_data.retexture_replace.push(replace);
                 }
             }
-            61 => {
-                obj_def.category = reader.read_u16()?;
-            }
-            62 => {
-                obj_def.rotated = true;
-            }
-            64 => {
-                obj_def.shadow = true;
-            }
-            65 => {
-                obj_def.model_data.model_size_x = reader.read_u16()?;
-            }
-            66 => {
-                obj_def.model_data.model_size_z = reader.read_u16()?;
-            }
-            67 => {
-                obj_def.model_data.model_size_y = reader.read_u16()?;
-            }
-            68 => {
-                obj_def.map_scene_id = reader.read_u16()?;
-            }
-            69 => {
-                obj_def.model_data.blocking_mask = reader.read_u8()?;
-            }
-            70 => {
-                obj_def.model_data.offset_x = reader.read_u16()?;
-            }
-            71 => {
-                obj_def.model_data.offset_z = reader.read_u16()?;
-            }
-            72 => {
-                obj_def.model_data.offset_y = reader.read_u16()?;
-            }
-            73 => {
-                obj_def.obstruct_ground = true;
-            }
-            74 => {
-                obj_def.solid = false;
-            }
-            75 => {
-                obj_def.supports_items = Some(reader.read_u8()?);
-            }
-            77 => {
-                let varp_id = reader.read_u16()?;
-                obj_def.model_data.varp_id = if varp_id == std::u16::MAX {
-                    None
-                } else {
-                    Some(varp_id)
-                };
-                let config_id = reader.read_u16()?;
-                obj_def.config_id = if config_id == std::u16::MAX {
-                    None
-                } else {
-                    Some(config_id)
-                };
-                let len = reader.read_u8()?;
-                obj_def.config_change_dest = Vec::new();
-                for _ in 0..=len {
-                    obj_def.config_change_dest.push(reader.read_u16()?);
-                }
+            61 => obj_def.category = field.get()?,
+            62 => obj_def.rotated = field.get()?,
+            64 => obj_def.shadow = field.get()?,
+            65 => obj_def.model_data.model_size_x = field.get()?,
+            66 => obj_def.model_data.model_size_z = field.get()?,
+            67 => obj_def.model_data.model_size_y = field.get()?,
+            68 => obj_def.map_scene_id = field.get()?,
+            69 => obj_def.model_data.blocking_mask = field.get()?,
+            70 => obj_def.model_data.offset_x = field.get()?,
+            71 => obj_def.model_data.offset_z = field.get()?,
+            72 => obj_def.model_data.offset_y = field.get()?,
+            73 => obj_def.obstruct_ground = field.get()?,
+            74 => obj_def.hollow = field.get()?,
+            75 => obj_def.supports_items = Some(field.get()?),
+            77 | 92 => {
+                (obj_def.morph_varbit, obj_def.morph_varp, obj_def.morphisms) =
+                    super::morphs(field.get()?);
             }
             78 => {
-                obj_def.ambient_sound_id = reader.read_u16()?;
-                reader.read_u8()?;
-            }
-            79 => {
-                reader.read_u16()?;
-                reader.read_u16()?;
-                reader.read_u8()?;
-                let len = reader.read_u8()?;
-                for _ in 0..len {
-                    reader.read_u16()?;
-                }
-            }
-            81 => {
-                obj_def.contoured_ground = Some(reader.read_u8()?);
-            }
-            82 => {
-                obj_def.map_area_id = Some(reader.read_u16()?);
-            }
-            89 => { /* skip */ }
-            92 => {
-                let varp_id = reader.read_u16()?;
-                obj_def.model_data.varp_id = if varp_id == std::u16::MAX {
-                    None
-                } else {
-                    Some(varp_id)
-                };
-
-                let config_id = reader.read_u16()?;
-                obj_def.config_id = if config_id == std::u16::MAX {
-                    None
-                } else {
-                    Some(config_id)
-                };
-                // should append var at end
-                let _var = reader.read_u16()?;
-
-                let len = reader.read_u8()?;
-                obj_def.config_change_dest = Vec::new();
-                for _ in 0..=len {
-                    obj_def.config_change_dest.push(reader.read_u16()?);
-                }
-            }
-            249 => {
-                obj_def.params = util::read_parameters(reader)?;
-            }
-            _ => unreachable!(),
+                let (sound_id, _distance): (u16, u8) = field.get()?;
+                obj_def.ambient_sound_id = sound_id;
+            }
+            81 => obj_def.contoured_ground = Some(field.get()?),
+            82 => obj_def.map_area_id = Some(field.get()?),
+            249 => obj_def.params = field.get()?,
+            // known opcodes of fields that aren't stored.
+            _ => {}
         }
     }
 
@@ -0,0 +1,122 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Definition, Hsl};
+use crate::extension::ReadExt;
+
+/// A floor color or texture painted over the underlay of tiles, fetched from
+/// the cache through the [OverlayLoader](../../loader/osrs/struct.OverlayLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct OverlayDefinition {
+    pub id: u32,
+    /// 24-bit RGB color.
+    pub color: u32,
+    pub texture: Option<u8>,
+    /// Whether the overlay covers the underlay of its tiles entirely.
+    pub hide_underlay: bool,
+    /// 24-bit RGB color used on the minimap instead of `color`.
+    pub secondary_color: Option<u32>,
+}
+
+impl Default for OverlayDefinition {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            color: 0,
+            texture: None,
+            hide_underlay: true,
+            secondary_color: None,
+        }
+    }
+}
+
+impl OverlayDefinition {
+    /// The color in the client's HSL space, the hue is in `0..256` but can be
+    /// slightly negative for reds leaning towards blue, like in the client.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::{Hsl, OverlayDefinition};
+    ///
+    /// let water = OverlayDefinition {
+    ///     color: 0x00_00_FF,
+    ///     ..OverlayDefinition::default()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     water.hsl(),
+    ///     Hsl { hue: 170, saturation: 255, lightness: 127 }
+    /// );
+    /// ```
+    pub fn hsl(&self) -> Hsl {
+        overlay_hsl(self.color)
+    }
+
+    /// The HSL of the `secondary_color`, if any.
+    pub fn secondary_hsl(&self) -> Option<Hsl> {
+        self.secondary_color.map(overlay_hsl)
+    }
+}
+
+fn overlay_hsl(color: u32) -> Hsl {
+    let (hue, saturation, lightness) = super::rgb_to_hsl(color);
+
+    Hsl {
+        hue: (hue * 256.0) as i32,
+        saturation: ((saturation * 256.0) as i32).clamp(0, 255),
+        lightness: ((lightness * 256.0) as i32).clamp(0, 255),
+    }
+}
+
+impl Definition for OverlayDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let overlay_def = decode_buffer(id, &mut reader)?;
+
+        Ok(overlay_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<OverlayDefinition> {
+    let mut overlay_def = OverlayDefinition {
+        id,
+        ..OverlayDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                overlay_def.color = reader.read_u24()?;
+            }
+            2 => {
+                overlay_def.texture = Some(reader.read_u8()?);
+            }
+            5 => {
+                overlay_def.hide_underlay = false;
+            }
+            7 => {
+                overlay_def.secondary_color = Some(reader.read_u24()?);
+            }
+            _ => return Err(super::unknown_opcode("overlay", opcode)),
+        }
+    }
+
+    Ok(overlay_def)
+}
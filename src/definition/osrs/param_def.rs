@@ -0,0 +1,113 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::{definition::Value, extension::ReadExt};
+
+/// Declares the type and default of a param, fetched from the cache through
+/// the [ParamLoader](../../loader/osrs/struct.ParamLoader.html).
+///
+/// Definitions only list the params that differ from the default.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ParamDefinition {
+    pub id: u32,
+    /// The cs2 type char of the value, `'s'` for strings.
+    pub stack_type: char,
+    pub default_int: i32,
+    pub default_string: Option<String>,
+    /// Whether the param is disabled on free to play worlds.
+    pub auto_disable: bool,
+}
+
+impl Default for ParamDefinition {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            stack_type: '\0',
+            default_int: 0,
+            default_string: None,
+            auto_disable: true,
+        }
+    }
+}
+
+impl ParamDefinition {
+    /// Whether values of this param are strings.
+    #[inline]
+    pub const fn is_string(&self) -> bool {
+        self.stack_type == 's'
+    }
+
+    /// The value of definitions without this param.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::{osrs::ParamDefinition, Value};
+    ///
+    /// let param = ParamDefinition {
+    ///     stack_type: 'i',
+    ///     default_int: 4,
+    ///     ..ParamDefinition::default()
+    /// };
+    ///
+    /// assert_eq!(param.default_value(), Value::Int(4));
+    /// ```
+    pub fn default_value(&self) -> Value {
+        if self.is_string() {
+            Value::String(self.default_string.clone().unwrap_or_default())
+        } else {
+            Value::Int(self.default_int)
+        }
+    }
+}
+
+impl Definition for ParamDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let param_def = decode_buffer(id, &mut reader)?;
+
+        Ok(param_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<ParamDefinition> {
+    let mut param_def = ParamDefinition {
+        id,
+        ..ParamDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                param_def.stack_type = char::from(reader.read_u8()?);
+            }
+            2 => {
+                param_def.default_int = reader.read_i32()?;
+            }
+            4 => param_def.auto_disable = false,
+            5 => {
+                param_def.default_string = Some(reader.read_string()?);
+            }
+            _ => return Err(super::unknown_opcode("param", opcode)),
+        }
+    }
+
+    Ok(param_def)
+}
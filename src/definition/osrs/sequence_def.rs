@@ -0,0 +1,241 @@
+use std::{collections::BTreeMap, io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// A reference to an animation frame, a file of a frame group in index 0.
+///
+/// Sequences store these packed as `group << 16 | file`.
+///
+/// # Examples
+///
+/// ```
+/// use rscache::definition::osrs::FrameId;
+///
+/// let frame = FrameId::from_packed(0x0401_0002);
+/// assert_eq!(frame, FrameId { group: 0x401, file: 2 });
+/// assert_eq!(frame.packed(), 0x0401_0002);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct FrameId {
+    /// Archive of the frame group in index 0.
+    pub group: u16,
+    /// File of the frame in the group.
+    pub file: u16,
+}
+
+impl FrameId {
+    #[inline]
+    pub const fn from_packed(packed: u32) -> Self {
+        Self {
+            group: (packed >> 16) as u16,
+            file: packed as u16,
+        }
+    }
+
+    #[inline]
+    pub const fn packed(self) -> u32 {
+        (self.group as u32) << 16 | self.file as u32
+    }
+}
+
+/// An animation, fetched from the cache through the
+/// [SequenceLoader](../../loader/osrs/struct.SequenceLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SequenceDefinition {
+    pub id: u32,
+    pub frame_ids: Vec<FrameId>,
+    /// Duration of every frame, in client ticks of 20ms.
+    pub frame_lengths: Vec<u16>,
+    /// Frames played on the chat head, if they differ from `frame_ids`.
+    pub chat_frame_ids: Vec<FrameId>,
+    /// Number of frames from the end the animation goes back to when it loops,
+    /// `None` to restart from the first frame.
+    pub loop_offset: Option<u16>,
+    /// Skeleton labels of the body parts this animation moves, when it can be
+    /// interleaved with another one.
+    pub interleave_order: Vec<u8>,
+    pub stretches: bool,
+    pub forced_priority: u8,
+    /// Replaces the shield while animating, encoded like player appearances:
+    /// item ids are offset by 512 and `Some(0)` hides it.
+    pub left_hand_item: Option<u16>,
+    /// Replaces the weapon while animating, encoded like `left_hand_item`.
+    pub right_hand_item: Option<u16>,
+    pub max_loops: u8,
+    pub precedence_animating: Option<u8>,
+    pub priority: Option<u8>,
+    /// What happens when the animation is played again while it's running.
+    pub replay_mode: u8,
+    /// Packed sound effect of every frame.
+    pub frame_sounds: Vec<u32>,
+    /// Animaya animation played instead of frames.
+    pub animaya_id: Option<i32>,
+    /// Packed sound effects of animaya animations by frame.
+    pub animaya_sounds: BTreeMap<u16, u32>,
+    /// First and last frame played of the animaya animation.
+    pub animaya_range: Option<(u16, u16)>,
+    /// Skeleton labels the animaya animation moves.
+    pub animaya_masks: Vec<u8>,
+}
+
+impl Default for SequenceDefinition {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            frame_ids: Vec::new(),
+            frame_lengths: Vec::new(),
+            chat_frame_ids: Vec::new(),
+            loop_offset: None,
+            interleave_order: Vec::new(),
+            stretches: false,
+            forced_priority: 5,
+            left_hand_item: None,
+            right_hand_item: None,
+            max_loops: 99,
+            precedence_animating: None,
+            priority: None,
+            replay_mode: 2,
+            frame_sounds: Vec::new(),
+            animaya_id: None,
+            animaya_sounds: BTreeMap::new(),
+            animaya_range: None,
+            animaya_masks: Vec::new(),
+        }
+    }
+}
+
+impl SequenceDefinition {
+    /// Total duration of a single play of the frames, in client ticks of 20ms.
+    #[inline]
+    pub fn duration(&self) -> u32 {
+        self.frame_lengths.iter().map(|&length| u32::from(length)).sum()
+    }
+}
+
+impl Definition for SequenceDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let sequence_def = decode_buffer(id, &mut reader)?;
+
+        Ok(sequence_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<SequenceDefinition> {
+    let mut sequence_def = SequenceDefinition {
+        id,
+        ..SequenceDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                let len = reader.read_u16()?;
+                for _ in 0..len {
+                    sequence_def.frame_lengths.push(reader.read_u16()?);
+                }
+                sequence_def.frame_ids = read_frame_ids(reader, len.into())?;
+            }
+            2 => {
+                sequence_def.loop_offset = Some(reader.read_u16()?);
+            }
+            3 => {
+                let len = reader.read_u8()?;
+                for _ in 0..len {
+                    sequence_def.interleave_order.push(reader.read_u8()?);
+                }
+            }
+            4 => {
+                sequence_def.stretches = true;
+            }
+            5 => {
+                sequence_def.forced_priority = reader.read_u8()?;
+            }
+            6 => {
+                sequence_def.left_hand_item = Some(reader.read_u16()?);
+            }
+            7 => {
+                sequence_def.right_hand_item = Some(reader.read_u16()?);
+            }
+            8 => {
+                sequence_def.max_loops = reader.read_u8()?;
+            }
+            9 => {
+                sequence_def.precedence_animating = Some(reader.read_u8()?);
+            }
+            10 => {
+                sequence_def.priority = Some(reader.read_u8()?);
+            }
+            11 => {
+                sequence_def.replay_mode = reader.read_u8()?;
+            }
+            12 => {
+                let len = reader.read_u8()?;
+                sequence_def.chat_frame_ids = read_frame_ids(reader, len.into())?;
+            }
+            13 => {
+                let len = reader.read_u8()?;
+                for _ in 0..len {
+                    sequence_def.frame_sounds.push(reader.read_u24()?);
+                }
+            }
+            14 => {
+                sequence_def.animaya_id = Some(reader.read_i32()?);
+            }
+            15 => {
+                let len = reader.read_u16()?;
+                for _ in 0..len {
+                    let frame = reader.read_u16()?;
+                    sequence_def.animaya_sounds.insert(frame, reader.read_u24()?);
+                }
+            }
+            16 => {
+                sequence_def.animaya_range = Some((reader.read_u16()?, reader.read_u16()?));
+            }
+            17 => {
+                let len = reader.read_u8()?;
+                for _ in 0..len {
+                    sequence_def.animaya_masks.push(reader.read_u8()?);
+                }
+            }
+            _ => return Err(super::unknown_opcode("sequence", opcode)),
+        }
+    }
+
+    Ok(sequence_def)
+}
+
+/// Frame ids are stored as a table of files followed by a table of groups.
+fn read_frame_ids(reader: &mut BufReader<&[u8]>, len: usize) -> io::Result<Vec<FrameId>> {
+    let mut frame_ids = Vec::with_capacity(len);
+    for _ in 0..len {
+        frame_ids.push(FrameId {
+            group: 0,
+            file: reader.read_u16()?,
+        });
+    }
+    for frame_id in &mut frame_ids {
+        frame_id.group = reader.read_u16()?;
+    }
+
+    Ok(frame_ids)
+}
@@ -0,0 +1,112 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// A graphic like a projectile or a spell effect, fetched from the cache through
+/// the [SpotAnimLoader](../../loader/osrs/struct.SpotAnimLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct SpotAnimDefinition {
+    pub id: u32,
+    pub model_id: u16,
+    /// [Sequence](super::SequenceDefinition) the model plays.
+    pub animation_id: Option<u16>,
+    /// Horizontal scale, 128 is the model's size.
+    pub resize_x: u16,
+    /// Vertical scale, 128 is the model's size.
+    pub resize_y: u16,
+    /// Rotation around the vertical axis, in steps of 90 degrees.
+    pub rotation: u16,
+    pub ambient: u8,
+    pub contrast: u8,
+    /// Colors of the model replaced by another, as `(find, replace)` pairs.
+    pub recolors: Vec<(u16, u16)>,
+    /// Textures of the model replaced by another, as `(find, replace)` pairs.
+    pub retextures: Vec<(u16, u16)>,
+}
+
+impl Default for SpotAnimDefinition {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            model_id: 0,
+            animation_id: None,
+            resize_x: 128,
+            resize_y: 128,
+            rotation: 0,
+            ambient: 0,
+            contrast: 0,
+            recolors: Vec::new(),
+            retextures: Vec::new(),
+        }
+    }
+}
+
+impl Definition for SpotAnimDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let spot_anim_def = decode_buffer(id, &mut reader)?;
+
+        Ok(spot_anim_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<SpotAnimDefinition> {
+    let mut spot_anim_def = SpotAnimDefinition {
+        id,
+        ..SpotAnimDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                spot_anim_def.model_id = reader.read_u16()?;
+            }
+            2 => {
+                let animation_id = reader.read_u16()?;
+                spot_anim_def.animation_id = (animation_id != u16::MAX).then_some(animation_id);
+            }
+            4 => {
+                spot_anim_def.resize_x = reader.read_u16()?;
+            }
+            5 => {
+                spot_anim_def.resize_y = reader.read_u16()?;
+            }
+            6 => {
+                spot_anim_def.rotation = reader.read_u16()?;
+            }
+            7 => {
+                spot_anim_def.ambient = reader.read_u8()?;
+            }
+            8 => {
+                spot_anim_def.contrast = reader.read_u8()?;
+            }
+            40 => {
+                spot_anim_def.recolors = super::read_replacements(reader)?;
+            }
+            41 => {
+                spot_anim_def.retextures = super::read_replacements(reader)?;
+            }
+            _ => return Err(super::unknown_opcode("spot anim", opcode)),
+        }
+    }
+
+    Ok(spot_anim_def)
+}
@@ -0,0 +1,190 @@
+use std::io;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::extension::ReadExt;
+
+/// Pixel palette indices are stored column by column instead of row by row.
+const FLAG_VERTICAL: u8 = 0b01;
+/// An alpha channel follows the palette indices, in the same order.
+const FLAG_ALPHA: u8 = 0b10;
+
+/// A single frame of a sprite group fetched from the cache through the
+/// [SpriteLoader](../../loader/osrs/struct.SpriteLoader.html).
+///
+/// Most groups in the sprite index hold a single frame, sheets like the map
+/// icons hold several that share the same `max_width` and `max_height`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct SpriteDefinition {
+    /// Archive id of the sprite group.
+    pub id: u32,
+    /// Position of the frame in its group.
+    pub frame: usize,
+    pub width: u16,
+    pub height: u16,
+    /// Offset of the frame within the `max_width` × `max_height` canvas.
+    pub offset_x: u16,
+    pub offset_y: u16,
+    pub max_width: u16,
+    pub max_height: u16,
+    /// `width * height` ARGB pixels in row-major order, palette index 0 is
+    /// fully transparent.
+    pub pixels: Vec<u32>,
+}
+
+impl SpriteDefinition {
+    /// Decodes every frame of a sprite group.
+    ///
+    /// The group ends with a footer describing the frames: their sizes and
+    /// offsets, the shared palette and the frame count. Every frame's pixels
+    /// are palette indices, optionally column-major and followed by an alpha
+    /// channel.
+    ///
+    /// # Errors
+    ///
+    /// Fails with an `InvalidData` error when the footer or the pixel data
+    /// doesn't fit the buffer.
+    pub fn decode(id: u32, buffer: &[u8]) -> crate::Result<Vec<Self>> {
+        Ok(decode_buffer(id, buffer)?)
+    }
+
+    /// The pixels as RGBA bytes, for image libraries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::SpriteDefinition;
+    ///
+    /// let sprite = SpriteDefinition {
+    ///     width: 2,
+    ///     height: 1,
+    ///     pixels: vec![0xFF_11_22_33, 0],
+    ///     ..SpriteDefinition::default()
+    /// };
+    ///
+    /// assert_eq!(sprite.to_rgba(), [0x11, 0x22, 0x33, 0xFF, 0, 0, 0, 0]);
+    /// ```
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|&argb| {
+                let [alpha, red, green, blue] = argb.to_be_bytes();
+                [red, green, blue, alpha]
+            })
+            .collect()
+    }
+
+    /// The ARGB pixel at `(x, y)` of the frame, `None` outside of it.
+    #[inline]
+    pub fn pixel(&self, x: u16, y: u16) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        self.pixels
+            .get(usize::from(y) * usize::from(self.width) + usize::from(x))
+            .copied()
+    }
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed sprite group")
+}
+
+fn decode_buffer(id: u32, buffer: &[u8]) -> io::Result<Vec<SpriteDefinition>> {
+    let count_offset = buffer.len().checked_sub(2).ok_or_else(malformed)?;
+    let count = usize::from((&buffer[count_offset..]).read_u16()?);
+
+    // max width, max height and the palette length, then four u16 per frame.
+    let footer_offset = count
+        .checked_mul(8)
+        .and_then(|len| count_offset.checked_sub(len + 5))
+        .ok_or_else(malformed)?;
+    let mut footer = &buffer[footer_offset..count_offset];
+    let max_width = footer.read_u16()?;
+    let max_height = footer.read_u16()?;
+    let palette_len = usize::from(footer.read_u8()?) + 1;
+
+    let mut frames: Vec<SpriteDefinition> = (0..count)
+        .map(|frame| SpriteDefinition {
+            id,
+            frame,
+            max_width,
+            max_height,
+            ..SpriteDefinition::default()
+        })
+        .collect();
+    for frame in &mut frames {
+        frame.offset_x = footer.read_u16()?;
+    }
+    for frame in &mut frames {
+        frame.offset_y = footer.read_u16()?;
+    }
+    for frame in &mut frames {
+        frame.width = footer.read_u16()?;
+    }
+    for frame in &mut frames {
+        frame.height = footer.read_u16()?;
+    }
+
+    let palette_offset = footer_offset
+        .checked_sub((palette_len - 1) * 3)
+        .ok_or_else(malformed)?;
+    let mut palette_data = &buffer[palette_offset..footer_offset];
+    let mut palette = vec![0; palette_len];
+    for color in palette.iter_mut().skip(1) {
+        // black is stored as 1, 0 is reserved for transparency.
+        *color = palette_data.read_u24()?.max(1);
+    }
+
+    let mut data = &buffer[..palette_offset];
+    for frame in &mut frames {
+        let width = usize::from(frame.width);
+        let height = usize::from(frame.height);
+        let len = width * height;
+
+        let flags = data.read_u8()?;
+        let indices = read_channel(&mut data, width, height, flags)?;
+        let alphas = if flags & FLAG_ALPHA == 0 {
+            indices
+                .iter()
+                .map(|&index| if index == 0 { 0 } else { 0xFF })
+                .collect()
+        } else {
+            read_channel(&mut data, width, height, flags)?
+        };
+
+        frame.pixels = Vec::with_capacity(len);
+        for (&index, &alpha) in indices.iter().zip(&alphas) {
+            let color = palette.get(usize::from(index)).ok_or_else(malformed)?;
+            frame.pixels.push(u32::from(alpha) << 24 | color);
+        }
+    }
+
+    Ok(frames)
+}
+
+/// Reads one byte per pixel, reordered to row-major for vertical frames.
+fn read_channel(data: &mut &[u8], width: usize, height: usize, flags: u8) -> io::Result<Vec<u8>> {
+    let len = width * height;
+    if data.len() < len {
+        return Err(malformed());
+    }
+    let (channel, rest) = data.split_at(len);
+    *data = rest;
+
+    if flags & FLAG_VERTICAL == 0 {
+        return Ok(channel.to_vec());
+    }
+
+    let mut row_major = vec![0; len];
+    for (x, column) in channel.chunks_exact(height.max(1)).enumerate() {
+        for (y, &value) in column.iter().enumerate() {
+            row_major[y * width + x] = value;
+        }
+    }
+
+    Ok(row_major)
+}
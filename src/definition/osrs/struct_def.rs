@@ -0,0 +1,57 @@
+use std::{collections::BTreeMap, io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::{definition::Value, extension::ReadExt, util};
+
+/// A struct config, a bare params map keyed into by enums and other configs,
+/// fetched from the cache through the [StructLoader](../../loader/osrs/struct.StructLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct StructDefinition {
+    pub id: u32,
+    /// Integer and string params keyed by their param id.
+    pub params: BTreeMap<u32, Value>,
+}
+
+impl Definition for StructDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let struct_def = decode_buffer(id, &mut reader)?;
+
+        Ok(struct_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<StructDefinition> {
+    let mut struct_def = StructDefinition {
+        id,
+        ..StructDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            249 => {
+                struct_def.params = util::read_parameters(reader)?;
+            }
+            _ => return Err(super::unknown_opcode("struct", opcode)),
+        }
+    }
+
+    Ok(struct_def)
+}
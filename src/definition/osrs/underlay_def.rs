@@ -0,0 +1,114 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// A floor color in the client's HSL space, saturation and lightness are in `0..256`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Hsl {
+    pub hue: i32,
+    pub saturation: i32,
+    pub lightness: i32,
+}
+
+/// The base floor color of tiles, fetched from the cache through the
+/// [UnderlayLoader](../../loader/osrs/struct.UnderlayLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct UnderlayDefinition {
+    pub id: u32,
+    /// 24-bit RGB color.
+    pub color: u32,
+}
+
+impl UnderlayDefinition {
+    /// The color as the client blends it with the underlays of the surrounding
+    /// tiles, the hue is already weighted by the [`hue_multiplier`](Self::hue_multiplier).
+    ///
+    /// The client sums the weighted hues and the multipliers over the tiles
+    /// around a tile and divides them to get the tile's hue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::{Hsl, UnderlayDefinition};
+    ///
+    /// let grass = UnderlayDefinition {
+    ///     color: 0x50_70_20,
+    ///     ..UnderlayDefinition::default()
+    /// };
+    ///
+    /// assert_eq!(grass.hue_multiplier(), 80);
+    /// assert_eq!(
+    ///     grass.hsl(),
+    ///     Hsl { hue: 18, saturation: 142, lightness: 72 }
+    /// );
+    /// ```
+    pub fn hsl(&self) -> Hsl {
+        let (hue, saturation, lightness) = super::rgb_to_hsl(self.color);
+
+        Hsl {
+            hue: (f64::from(self.hue_multiplier()) * hue) as i32,
+            saturation: ((saturation * 256.0) as i32).clamp(0, 255),
+            lightness: ((lightness * 256.0) as i32).clamp(0, 255),
+        }
+    }
+
+    /// Weight of the color in the blend, larger for saturated colors of
+    /// medium lightness and at least 1.
+    pub fn hue_multiplier(&self) -> i32 {
+        let (_, saturation, lightness) = super::rgb_to_hsl(self.color);
+
+        let multiplier = if lightness > 0.5 {
+            saturation * (1.0 - lightness) * 512.0
+        } else {
+            saturation * lightness * 512.0
+        };
+
+        (multiplier as i32).max(1)
+    }
+}
+
+impl Definition for UnderlayDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let underlay_def = decode_buffer(id, &mut reader)?;
+
+        Ok(underlay_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<UnderlayDefinition> {
+    let mut underlay_def = UnderlayDefinition {
+        id,
+        ..UnderlayDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                underlay_def.color = reader.read_u24()?;
+            }
+            _ => return Err(super::unknown_opcode("underlay", opcode)),
+        }
+    }
+
+    Ok(underlay_def)
+}
@@ -0,0 +1,91 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::Definition;
+use crate::extension::ReadExt;
+
+/// A varbit, the bits `lsb..=msb` of a varp, fetched from the cache through
+/// the [VarbitLoader](../../loader/osrs/struct.VarbitLoader.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct VarbitDefinition {
+    pub id: u32,
+    pub varp_id: u16,
+    /// Least significant bit of the varbit in the varp.
+    pub lsb: u8,
+    /// Most significant bit of the varbit in the varp, inclusive.
+    pub msb: u8,
+}
+
+impl VarbitDefinition {
+    /// The varbit's value in the value of its varp.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::VarbitDefinition;
+    ///
+    /// let varbit = VarbitDefinition {
+    ///     lsb: 4,
+    ///     msb: 7,
+    ///     ..VarbitDefinition::default()
+    /// };
+    ///
+    /// assert_eq!(varbit.extract(0x1A5), 0xA);
+    /// ```
+    pub const fn extract(&self, varp_value: i32) -> i32 {
+        let lsb = self.lsb as u32 % 32;
+        let width = (self.msb as u32).saturating_sub(lsb) + 1;
+        let mask = if width >= 32 {
+            u32::MAX
+        } else {
+            (1 << width) - 1
+        };
+
+        ((varp_value as u32 >> lsb) & mask) as i32
+    }
+}
+
+impl Definition for VarbitDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let varbit_def = decode_buffer(id, &mut reader)?;
+
+        Ok(varbit_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<VarbitDefinition> {
+    let mut varbit_def = VarbitDefinition {
+        id,
+        ..VarbitDefinition::default()
+    };
+
+    loop {
+        let opcode = reader.read_u8()?;
+
+        match opcode {
+            0 => break,
+            1 => {
+                varbit_def.varp_id = reader.read_u16()?;
+                varbit_def.lsb = reader.read_u8()?;
+                varbit_def.msb = reader.read_u8()?;
+            }
+            _ => return Err(super::unknown_opcode("varbit", opcode)),
+        }
+    }
+
+    Ok(varbit_def)
+}
@@ -0,0 +1,385 @@
+use std::{io, io::BufReader};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Definition, Position};
+use crate::extension::ReadExt;
+
+/// An inclusive rectangle of tiles.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct TileRect {
+    pub min_x: u16,
+    pub min_y: u16,
+    pub max_x: u16,
+    pub max_y: u16,
+}
+
+impl TileRect {
+    /// The tiles of the regions from `(min_x, min_y)` to `(max_x, max_y)`, in region coordinates.
+    #[inline]
+    pub const fn from_regions(min_x: u16, min_y: u16, max_x: u16, max_y: u16) -> Self {
+        Self {
+            min_x: min_x << 6,
+            min_y: min_y << 6,
+            max_x: max_x << 6 | 63,
+            max_y: max_y << 6 | 63,
+        }
+    }
+
+    #[inline]
+    pub const fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+
+    /// Ids of the 64×64 regions the rectangle overlaps, as used by the map loader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::TileRect;
+    ///
+    /// let rect = TileRect::from_regions(50, 50, 51, 50);
+    /// let region_ids: Vec<u16> = rect.region_ids().collect();
+    ///
+    /// assert_eq!(region_ids, [12850, 13106]);
+    /// ```
+    pub fn region_ids(&self) -> impl Iterator<Item = u16> {
+        let (min_y, max_y) = (self.min_y >> 6, self.max_y >> 6);
+
+        (self.min_x >> 6..=self.max_x >> 6)
+            .flat_map(move |x| (min_y..=max_y).map(move |y| x << 8 | y))
+    }
+
+    fn from_chunks(region_x: u16, chunks_x: (u8, u8), region_y: u16, chunks_y: (u8, u8)) -> Self {
+        Self {
+            min_x: region_x << 6 | u16::from(chunks_x.0) << 3,
+            min_y: region_y << 6 | u16::from(chunks_y.0) << 3,
+            max_x: region_x << 6 | u16::from(chunks_x.1) << 3 | 7,
+            max_y: region_y << 6 | u16::from(chunks_y.1) << 3 | 7,
+        }
+    }
+}
+
+/// How a [`WorldMapSection`] is stored, in whole regions or in chunks of 8×8 tiles.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum WorldMapSectionKind {
+    Regions,
+    Region,
+    Chunks,
+    Chunk,
+}
+
+/// A part of the world shown on a world map and the place it's drawn at on the map.
+///
+/// Sections let maps like dungeons move parts of the world next to each other.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct WorldMapSection {
+    pub kind: WorldMapSectionKind,
+    /// Lowest plane of the world shown.
+    pub plane: u8,
+    /// Number of planes shown from `plane` up.
+    pub planes: u8,
+    /// Tiles of the world shown.
+    pub source: TileRect,
+    /// Tiles of the map the source is drawn at.
+    pub display: TileRect,
+}
+
+impl WorldMapSection {
+    /// Whether the section shows the tile.
+    #[inline]
+    pub const fn contains(&self, position: Position) -> bool {
+        position.plane >= self.plane
+            && position.plane - self.plane < self.planes
+            && self.source.contains(position.x, position.y)
+    }
+
+    /// Where the tile is drawn on the map, `None` if the section doesn't show it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::definition::osrs::{Position, TileRect, WorldMapSection, WorldMapSectionKind};
+    ///
+    /// let section = WorldMapSection {
+    ///     kind: WorldMapSectionKind::Region,
+    ///     plane: 0,
+    ///     planes: 1,
+    ///     source: TileRect::from_regions(27, 83, 27, 83),
+    ///     display: TileRect::from_regions(28, 84, 28, 84),
+    /// };
+    ///
+    /// let position = Position::new(1730, 5315, 0);
+    /// assert_eq!(section.display_position(position), Some(Position::new(1794, 5379, 0)));
+    /// ```
+    pub const fn display_position(&self, position: Position) -> Option<Position> {
+        if !self.contains(position) {
+            return None;
+        }
+
+        Some(Position {
+            x: self.display.min_x + (position.x - self.source.min_x),
+            y: self.display.min_y + (position.y - self.source.min_y),
+            plane: position.plane,
+        })
+    }
+}
+
+/// A region or a chunk of the composite map, and the geography drawn for it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct WorldMapSquare {
+    /// Lowest plane of the world shown.
+    pub plane: u8,
+    /// Number of planes shown from `plane` up.
+    pub planes: u8,
+    /// Tiles of the world shown.
+    pub source: TileRect,
+    /// Tiles of the map the source is drawn at.
+    pub display: TileRect,
+    /// Archive of the square's geography in index 18.
+    pub geography_group: Option<u32>,
+    /// File of the square's geography in the archive.
+    pub geography_file: Option<u32>,
+}
+
+/// An element placed on a world map.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct WorldMapIcon {
+    /// The [area](super::AreaDefinition) drawn.
+    pub area_id: Option<u32>,
+    pub position: Position,
+    /// Whether the icon is only shown on members worlds.
+    pub members: bool,
+}
+
+/// A world map, like the surface or a dungeon, fetched from the cache through
+/// the [WorldMapLoader](../../loader/osrs/struct.WorldMapLoader.html).
+///
+/// The definition is parsed from the map's file in the details group, the
+/// squares and icons come from its file in the composite map group, see
+/// [`read_composite`](Self::read_composite).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct WorldMapDefinition {
+    pub id: u32,
+    /// Name the client looks the map up by, `"main"` for the surface.
+    pub internal_name: String,
+    pub name: String,
+    /// Tile the map is centered on when opened.
+    pub origin: Option<Position>,
+    pub background_color: u32,
+    /// Whether this is the surface map.
+    pub main: bool,
+    /// Zoom the map opens with, in percent.
+    pub default_zoom: u8,
+    pub sections: Vec<WorldMapSection>,
+    pub squares: Vec<WorldMapSquare>,
+    pub icons: Vec<WorldMapIcon>,
+}
+
+impl WorldMapDefinition {
+    /// Parses the map's file of the composite map group into `squares` and `icons`.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the buffer ends early or holds an unknown square type.
+    pub fn read_composite(&mut self, buffer: &[u8]) -> crate::Result<()> {
+        let mut reader = BufReader::new(buffer);
+
+        let len = reader.read_u16()?;
+        let mut squares = Vec::with_capacity(len.into());
+        for _ in 0..len {
+            squares.push(read_square(&mut reader, 0)?);
+        }
+        let len = reader.read_u16()?;
+        squares.reserve(len.into());
+        for _ in 0..len {
+            squares.push(read_square(&mut reader, 1)?);
+        }
+
+        let len = reader.read_u16()?;
+        let mut icons = Vec::with_capacity(len.into());
+        for _ in 0..len {
+            icons.push(WorldMapIcon {
+                area_id: super::read_nullable_smart(&mut reader)?,
+                position: Position::from_packed(reader.read_u32()?),
+                members: reader.read_u8()? == 1,
+            });
+        }
+
+        self.squares = squares;
+        self.icons = icons;
+
+        Ok(())
+    }
+
+    /// The section showing the tile, if any.
+    pub fn section_at(&self, position: Position) -> Option<&WorldMapSection> {
+        self.sections
+            .iter()
+            .find(|section| section.contains(position))
+    }
+
+    /// Smallest rectangle of the map holding every section.
+    pub fn bounds(&self) -> Option<TileRect> {
+        self.sections
+            .iter()
+            .map(|section| section.display)
+            .reduce(|bounds, display| TileRect {
+                min_x: bounds.min_x.min(display.min_x),
+                min_y: bounds.min_y.min(display.min_y),
+                max_x: bounds.max_x.max(display.max_x),
+                max_y: bounds.max_y.max(display.max_y),
+            })
+    }
+}
+
+impl Definition for WorldMapDefinition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self> {
+        let mut reader = BufReader::new(buffer);
+        let world_map_def = decode_buffer(id, &mut reader)?;
+
+        Ok(world_map_def)
+    }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        Some(self.name.as_str()).filter(|name| !name.is_empty() && *name != "null")
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<WorldMapDefinition> {
+    let internal_name = reader.read_string()?;
+    let name = reader.read_string()?;
+    let origin = reader.read_u32()?;
+    let background_color = reader.read_u32()?;
+    reader.read_u8()?;
+    let main = reader.read_u8()? == 1;
+    let default_zoom = reader.read_u8()?;
+
+    let len = reader.read_u8()?;
+    let mut sections = Vec::with_capacity(len.into());
+    for _ in 0..len {
+        sections.push(read_section(reader)?);
+    }
+
+    Ok(WorldMapDefinition {
+        id,
+        internal_name,
+        name,
+        origin: (origin != u32::MAX).then(|| Position::from_packed(origin)),
+        background_color,
+        main,
+        default_zoom,
+        sections,
+        ..WorldMapDefinition::default()
+    })
+}
+
+fn read_section(reader: &mut BufReader<&[u8]>) -> io::Result<WorldMapSection> {
+    let kind = match reader.read_u8()? {
+        0 => WorldMapSectionKind::Regions,
+        1 => WorldMapSectionKind::Region,
+        2 => WorldMapSectionKind::Chunks,
+        3 => WorldMapSectionKind::Chunk,
+        kind => return Err(unknown_kind("section", kind)),
+    };
+    let plane = reader.read_u8()?;
+    let planes = reader.read_u8()?;
+
+    let mut read_rect = || -> io::Result<TileRect> {
+        Ok(match kind {
+            WorldMapSectionKind::Regions => TileRect::from_regions(
+                reader.read_u16()?,
+                reader.read_u16()?,
+                reader.read_u16()?,
+                reader.read_u16()?,
+            ),
+            WorldMapSectionKind::Region => {
+                let (x, y) = (reader.read_u16()?, reader.read_u16()?);
+                TileRect::from_regions(x, y, x, y)
+            }
+            WorldMapSectionKind::Chunks => {
+                let x = reader.read_u16()?;
+                let chunks_x = (reader.read_u8()?, reader.read_u8()?);
+                let y = reader.read_u16()?;
+                let chunks_y = (reader.read_u8()?, reader.read_u8()?);
+                TileRect::from_chunks(x, chunks_x, y, chunks_y)
+            }
+            WorldMapSectionKind::Chunk => {
+                let (x, chunk_x) = (reader.read_u16()?, reader.read_u8()?);
+                let (y, chunk_y) = (reader.read_u16()?, reader.read_u8()?);
+                TileRect::from_chunks(x, (chunk_x, chunk_x), y, (chunk_y, chunk_y))
+            }
+        })
+    };
+    let source = read_rect()?;
+    let display = read_rect()?;
+
+    Ok(WorldMapSection {
+        kind,
+        plane,
+        planes,
+        source,
+        display,
+    })
+}
+
+/// Squares of type 0 are whole regions, squares of type 1 are chunks.
+fn read_square(reader: &mut BufReader<&[u8]>, expected_kind: u8) -> io::Result<WorldMapSquare> {
+    let kind = reader.read_u8()?;
+    if kind != expected_kind {
+        return Err(unknown_kind("square", kind));
+    }
+    let plane = reader.read_u8()?;
+    let planes = reader.read_u8()?;
+
+    let mut read_rect = || -> io::Result<TileRect> {
+        let (x, y) = (reader.read_u16()?, reader.read_u16()?);
+        if kind == 0 {
+            return Ok(TileRect::from_regions(x, y, x, y));
+        }
+        let (chunk_x, chunk_y) = (reader.read_u8()?, reader.read_u8()?);
+
+        Ok(TileRect::from_chunks(
+            x,
+            (chunk_x, chunk_x),
+            y,
+            (chunk_y, chunk_y),
+        ))
+    };
+    let source = read_rect()?;
+    let display = read_rect()?;
+
+    Ok(WorldMapSquare {
+        plane,
+        planes,
+        source,
+        display,
+        geography_group: super::read_nullable_smart(reader)?,
+        geography_file: super::read_nullable_smart(reader)?,
+    })
+}
+
+fn unknown_kind(what: &str, kind: u8) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unknown world map {} type {}", what, kind),
+    )
+}
@@ -0,0 +1,82 @@
+//! Values of the `params` maps carried by definitions.
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A single param value, definitions store either integers or strings under
+/// a param id.
+///
+/// Many properties of newer content, like the attack speed or equipment slot
+/// of an item, are only available as params.
+///
+/// # Examples
+///
+/// ```
+/// use rscache::definition::Value;
+///
+/// let speed = Value::Int(4);
+/// assert_eq!(speed.as_int(), Some(4));
+/// assert_eq!(speed.as_str(), None);
+/// assert_eq!(speed.to_string(), "4");
+///
+/// assert_eq!(Value::from("Wield").as_str(), Some("Wield"));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Value {
+    Int(i32),
+    String(String),
+}
+
+impl Value {
+    /// The integer, `None` for strings.
+    #[inline]
+    pub const fn as_int(&self) -> Option<i32> {
+        match self {
+            Self::Int(value) => Some(*value),
+            Self::String(_) => None,
+        }
+    }
+
+    /// The string, `None` for integers.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Int(_) => None,
+            Self::String(value) => Some(value),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(value) => value.fmt(f),
+            Self::String(value) => value.fmt(f),
+        }
+    }
+}
+
+impl From<i32> for Value {
+    #[inline]
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<String> for Value {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
@@ -3,15 +3,34 @@ mod item_def;
 
 pub use item_def::*;
 
-use crate::Cache;
-use runefs::{IndexMetadata, ArchiveFileGroup, REFERENCE_TABLE_ID};
-use std::collections::HashMap;
+use crate::{util, Cache};
+use std::{collections::BTreeMap, io, ops::ControlFlow};
 
-pub(crate) const ID_BLOCK_SIZE: usize = 256;
+pub(crate) const ID_BLOCK_SIZE: u32 = 256;
 
-/// Marker trait for definitions.
-pub trait Definition: Sized {
-    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self>;
+/// Common interface of every definition, see [`osrs::Definition`](super::osrs::Definition).
+pub trait Definition {
+    fn new(id: u32, buffer: &[u8]) -> crate::Result<Self>
+    where
+        Self: Sized;
+
+    /// Id the definition was parsed with.
+    fn id(&self) -> u32;
+
+    /// Display name, `None` for definitions without a name or named `"null"`.
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Serializes the definition, definitions of other crates fail unless they override this.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        Err(<serde_json::Error as serde::ser::Error>::custom(
+            "definition is not serializable",
+        ))
+    }
 }
 
 /// Adds definition fetching from the cache to every struct that implements `Definition`.
@@ -24,31 +43,57 @@ pub trait FetchDefinition: Definition {
     /// # Errors
     ///
     /// Can return multiple errors: if reading, decoding or parsing definition buffers fail.
-    fn fetch_from_index<D>(cache: &Cache, index_id: u8) -> crate::Result<HashMap<u32, D>>
+    fn fetch_from_index<D>(cache: &Cache, index_id: u8) -> crate::Result<BTreeMap<u32, D>>
     where
         D: Definition,
     {
-        let buffer = cache.read(REFERENCE_TABLE_ID, index_id as u32)?.decode()?;
-        let archives = IndexMetadata::from_buffer(buffer)?;
+        let mut definitions = BTreeMap::new();
+        Self::for_each_in_index(cache, index_id, |id, definition: D| {
+            definitions.insert(id, definition);
+            ControlFlow::Continue(())
+        })?;
 
-        let mut definitions = std::collections::HashMap::new();
-        let mut base_id = 0;
+        Ok(definitions)
+    }
+
+    /// Parses the definitions of every archive in the index one by one, without retaining them.
+    ///
+    /// Archives after the one where `f` breaks are never read.
+    ///
+    /// # Errors
+    ///
+    /// See [`fetch_from_index`](FetchDefinition::fetch_from_index).
+    fn for_each_in_index<D, F>(cache: &Cache, index_id: u8, mut f: F) -> crate::Result<()>
+    where
+        D: Definition,
+        F: FnMut(u32, D) -> ControlFlow<()>,
+    {
+        let archives = cache.index_metadata(index_id)?;
 
-        for archive in &archives {
-            let buffer = cache.read(index_id, archive.id as u32)?.decode()?;
+        for archive in &archives.archives {
+            let buffer = cache.read_decoded_shared(index_id, archive.id)?;
 
-            let archive_group = ArchiveFileGroup::from_buffer(&buffer, archive.entry_count);
+            let files = util::split_group(&buffer, archive.file_ids.len())?;
 
-            for archive_file in archive_group {
-                let id = base_id + archive.valid_ids[archive_file.id as usize] as usize;
-                definitions.insert(id as u32, D::new(id as u32, &archive_file.data)?);
+            // archives hold blocks of `ID_BLOCK_SIZE` definitions, keyed by `id >> 8`.
+            let base_id = archive.id.wrapping_mul(ID_BLOCK_SIZE);
+            for (&file_id, data) in archive.file_ids.iter().zip(&files) {
+                let id = base_id.wrapping_add(file_id);
+                if f(id, D::new(id, data)?).is_break() {
+                    return Ok(());
+                }
             }
-
-            base_id += ID_BLOCK_SIZE;
         }
 
-        Ok(definitions)
+        Ok(())
     }
 }
 
 impl<D: Definition> FetchDefinition for D {}
+
+fn unknown_opcode(kind: &str, opcode: u8) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unknown {} opcode {}", kind, opcode),
+    )
+}
@@ -4,7 +4,7 @@ use std::{io, io::BufReader};
 use serde::{Deserialize, Serialize};
 
 use super::Definition;
-use crate::{extension::ReadExt, util};
+use crate::{definition::LocalizedString, extension::ReadExt, util};
 
 /// Contains all the information about a certain item fetched from the cache through
 /// the [ItemLoader](../../loader/rs3/struct.ItemLoader.html).
@@ -13,7 +13,9 @@ use crate::{extension::ReadExt, util};
 pub struct ItemDefinition {
     pub id: u32,
     pub model_data: ModelData,
-    pub name: String,
+    /// The name in the cache's language, translations are added by the
+    /// [loader](crate::loader::rs3::ItemLoader::add_translations).
+    pub name: LocalizedString,
     pub stackable: bool,
     pub cost: i32,
     pub members_only: bool,
@@ -64,6 +66,21 @@ impl Definition for ItemDefinition {
 
         Ok(item_def)
     }
+
+    #[inline]
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        Some(self.name.as_str()).filter(|name| !name.is_empty() && *name != "null")
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
 }
 
 fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<ItemDefinition> {
@@ -95,7 +112,7 @@ fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<ItemDefin
                 item_def.model_data.id = reader.read_smart()?;
             }
             2 => {
-                item_def.name = reader.read_string()?;
+                item_def.name = reader.read_string()?.into();
             }
             4 => {
                 item_def.model_data.zoom = reader.read_u16()?;
@@ -191,20 +208,11 @@ fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<ItemDefin
                 item_def.stackable = true;
             }
             100..=109 => {
-                item_def.stack_ids = Some([0; 10]);
-                item_def.stack_count = Some([0; 10]);
-                match item_def.stack_ids {
-                    Some(mut stack_ids) => {
-                        stack_ids[opcode as usize - 100] = reader.read_u16()?;
-                    }
-                    _ => unreachable!(),
-                }
-                match item_def.stack_count {
-                    Some(mut stack_count) => {
-                        stack_count[opcode as usize - 100] = reader.read_u16()?;
-                    }
-                    _ => unreachable!(),
-                }
+                let stack_ids = item_def.stack_ids.get_or_insert([0; 10]);
+                stack_ids[opcode as usize - 100] = reader.read_u16()?;
+
+                let stack_count = item_def.stack_count.get_or_insert([0; 10]);
+                stack_count[opcode as usize - 100] = reader.read_u16()?;
             }
             115 => {
                 item_def.team = reader.read_u8()?;
@@ -262,10 +270,7 @@ fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<ItemDefin
             90..=93 | 242..=248 => {
                 reader.read_smart()?;
             }
-            _ => {
-                println!("{} {}", id, opcode);
-                unreachable!()
-            }
+            _ => return Err(super::unknown_opcode("item", opcode)),
         }
     }
 
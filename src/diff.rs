@@ -0,0 +1,187 @@
+//! Differences between two caches, e.g. before and after a game update.
+//!
+//! [`Cache::diff`] compares the reference tables of both caches, archive
+//! contents are never read or decoded. An archive is modified when its crc or
+//! version differs.
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! # fn main() -> Result<(), rscache::Error> {
+//! let old = Cache::new("./data/osrs_cache")?;
+//! let new = Cache::new("./data/osrs_cache")?;
+//! let diff = old.diff(&new)?;
+//!
+//! for index in &diff.indices {
+//!     println!(
+//!         "index {}: {} added, {} removed, {} modified",
+//!         index.index_id,
+//!         index.added.len(),
+//!         index.removed.len(),
+//!         index.modified.len()
+//!     );
+//! }
+//! assert!(diff.is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use runefs::REFERENCE_TABLE_ID;
+
+use crate::{
+    reference_table::{ArchiveEntry, IndexMetadata},
+    Cache,
+};
+
+/// Every index that differs between two caches, see [`Cache::diff`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct CacheDiff {
+    /// Indices with changes, in ascending order.
+    pub indices: Vec<IndexDiff>,
+}
+
+impl CacheDiff {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Changes of the given index, `None` when it didn't change.
+    pub fn index(&self, index_id: u8) -> Option<&IndexDiff> {
+        self.indices
+            .binary_search_by_key(&index_id, |index| index.index_id)
+            .ok()
+            .map(|position| &self.indices[position])
+    }
+}
+
+/// Changes of a single index, archive ids in ascending order.
+///
+/// Indices missing from one of the caches, or without a reference table, diff
+/// like an empty index, every archive of the other is added or removed.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct IndexDiff {
+    pub index_id: u8,
+    /// Revision of the reference table in the cache diffed from, `None` when
+    /// the index is missing or its table stores none.
+    pub old_revision: Option<u32>,
+    /// Revision of the reference table in the other cache.
+    pub new_revision: Option<u32>,
+    /// Archives only the other cache lists.
+    pub added: Vec<u32>,
+    /// Archives only the cache diffed from lists.
+    pub removed: Vec<u32>,
+    pub modified: Vec<ArchiveChange>,
+}
+
+impl IndexDiff {
+    /// Whether any archive was added, removed or modified.
+    #[inline]
+    pub fn has_archive_changes(&self) -> bool {
+        !(self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty())
+    }
+}
+
+/// An archive listed in both caches with a different crc or version.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ArchiveChange {
+    pub archive_id: u32,
+    pub old_crc: u32,
+    pub new_crc: u32,
+    pub old_version: u32,
+    pub new_version: u32,
+}
+
+pub(crate) fn diff(old: &Cache, new: &Cache) -> crate::Result<CacheDiff> {
+    let mut index_ids: Vec<u8> = old.indices.ids().chain(new.indices.ids()).collect();
+    index_ids.sort_unstable();
+    index_ids.dedup();
+
+    let mut indices = Vec::new();
+    for index_id in index_ids {
+        if index_id == REFERENCE_TABLE_ID {
+            continue;
+        }
+        let old_table = table(old, index_id)?;
+        let new_table = table(new, index_id)?;
+        let index = diff_index(index_id, old_table.as_ref(), new_table.as_ref());
+
+        if index.has_archive_changes() || index.old_revision != index.new_revision {
+            indices.push(index);
+        }
+    }
+
+    Ok(CacheDiff { indices })
+}
+
+/// Reference table of the index, `None` for missing indices and indices
+/// without a table.
+fn table(cache: &Cache, index_id: u8) -> crate::Result<Option<IndexMetadata<'_>>> {
+    let index = cache.indices.get(&index_id);
+    if !index.is_some_and(|index| index.has_reference_table) {
+        return Ok(None);
+    }
+
+    cache.metadata_of(index_id).map(Some)
+}
+
+fn diff_index(
+    index_id: u8,
+    old: Option<&IndexMetadata<'_>>,
+    new: Option<&IndexMetadata<'_>>,
+) -> IndexDiff {
+    let (old_revision, old_archives) = split(old);
+    let (new_revision, new_archives) = split(new);
+    let mut index = IndexDiff {
+        index_id,
+        old_revision,
+        new_revision,
+        ..IndexDiff::default()
+    };
+
+    // both tables list their archives by ascending id.
+    let (mut old, mut new) = (
+        old_archives.iter().peekable(),
+        new_archives.iter().peekable(),
+    );
+    loop {
+        let order = match (old.peek(), new.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(old), Some(new)) => old.id.cmp(&new.id),
+        };
+
+        match order {
+            Ordering::Less => index.removed.extend(old.next().map(|archive| archive.id)),
+            Ordering::Greater => index.added.extend(new.next().map(|archive| archive.id)),
+            Ordering::Equal => {
+                let (old, new) = (old.next().unwrap(), new.next().unwrap());
+                if old.crc != new.crc || old.version != new.version {
+                    index.modified.push(ArchiveChange {
+                        archive_id: old.id,
+                        old_crc: old.crc,
+                        new_crc: new.crc,
+                        old_version: old.version,
+                        new_version: new.version,
+                    });
+                }
+            }
+        }
+    }
+
+    index
+}
+
+fn split<'a>(table: Option<&'a IndexMetadata<'_>>) -> (Option<u32>, &'a [ArchiveEntry]) {
+    table.map_or((None, &[]), |table| (table.revision(), table.archives()))
+}
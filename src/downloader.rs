@@ -0,0 +1,372 @@
+//! Downloads OSRS caches from an update server (js5).
+//!
+//! [`Downloader`] handshakes with the server's revision, requests the checksum
+//! table (index 255, archive 255), then every index's reference table and every
+//! archive listed in them. The result is written as `main_file_cache.dat2` and
+//! the `.idx` files, so [`Cache::new`] can open it.
+//!
+//! Downloading into a directory that already holds a cache resumes it, archives
+//! whose crc matches the server's are kept as they are.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use rscache::{downloader::Downloader, Cache};
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! let mut downloader = Downloader::connect("oldschool1.runescape.com:43594", 221)?;
+//! let report = downloader.download("./cache", |progress| {
+//!     println!("index {}: {}/{}", progress.index_id, progress.done, progress.total);
+//! })?;
+//! println!("{} downloaded, {} up to date", report.downloaded, report.skipped);
+//!
+//! let cache = Cache::new("./cache")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::Path,
+};
+
+use runefs::{IDX_PREFIX, MAIN_DATA, REFERENCE_TABLE_ID};
+
+use crate::{
+    bundle, codec,
+    error::DownloadError,
+    protocol,
+    reference_table::{ArchiveEntry, ReferenceTable},
+    Cache,
+};
+
+/// Opcode of the handshake, followed by the client revision.
+pub const HANDSHAKE: u8 = 15;
+
+/// Status the server answers a handshake with when it accepts the revision.
+pub const HANDSHAKE_OK: u8 = 0;
+
+/// Opcode of a priority archive request.
+pub const PRIORITY_REQUEST: u8 = 1;
+
+/// Opcode telling the server the client is logged out.
+pub const LOGGED_OUT: u8 = 3;
+
+/// Requests sent ahead of their responses.
+pub const MAX_PENDING: usize = 20;
+
+/// Reported once for every archive listed in a reference table, see
+/// [`Downloader::download`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Progress {
+    pub index_id: u8,
+    pub archive_id: u32,
+    /// Archives of the index handled so far, this one included.
+    pub done: usize,
+    /// Archives listed in the index's reference table.
+    pub total: usize,
+    /// Compressed bytes of the archives handled so far, `None` when the
+    /// reference table doesn't carry archive sizes.
+    pub done_bytes: Option<u64>,
+    /// Compressed bytes of all archives listed in the reference table.
+    pub total_bytes: Option<u64>,
+    /// Whether the archive was fetched, `false` when it was already up to date.
+    pub downloaded: bool,
+}
+
+/// Archive counts of a finished [`Downloader::download`], reference tables
+/// included.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct DownloadReport {
+    pub downloaded: usize,
+    pub skipped: usize,
+}
+
+/// A js5 client.
+#[derive(Debug)]
+pub struct Downloader<S> {
+    stream: S,
+}
+
+impl Downloader<TcpStream> {
+    /// Connects to an update server and handshakes, see [`handshake`](Downloader::handshake).
+    ///
+    /// # Errors
+    ///
+    /// Fails when the server can't be reached or rejects the revision.
+    pub fn connect<A: ToSocketAddrs>(addr: A, revision: u32) -> crate::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+
+        Self::handshake(stream, revision)
+    }
+}
+
+impl<S: Read + Write> Downloader<S> {
+    /// Handshakes over an already connected stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DownloadError::Rejected`] when the server doesn't accept
+    /// `revision`, usually because the cache was updated since.
+    pub fn handshake(mut stream: S, revision: u32) -> crate::Result<Self> {
+        let mut handshake = vec![HANDSHAKE];
+        handshake.extend(revision.to_be_bytes());
+        stream.write_all(&handshake)?;
+        stream.flush()?;
+
+        let mut status = [0];
+        stream.read_exact(&mut status)?;
+        if status[0] != HANDSHAKE_OK {
+            return Err(DownloadError::Rejected(status[0]).into());
+        }
+
+        stream.write_all(&[LOGGED_OUT, 0, 0, 0])?;
+
+        Ok(Self { stream })
+    }
+
+    /// Requests a single container, without the version trailer.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the stream fails or the server answers with another archive.
+    pub fn request(&mut self, index_id: u8, archive_id: u32) -> crate::Result<Vec<u8>> {
+        let mut container = Vec::new();
+        self.fetch(&[(index_id, archive_id)], |_, _, response| {
+            container = response;
+            Ok(())
+        })?;
+
+        Ok(container)
+    }
+
+    /// Requests every `(index, archive)` pair, calling `handle` with each
+    /// container as it arrives.
+    ///
+    /// Up to [`MAX_PENDING`] requests are sent ahead, so containers may arrive
+    /// out of order.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the stream fails, the server answers with an archive that
+    /// wasn't requested, an archive id doesn't fit the protocol's 16 bits or
+    /// `handle` fails.
+    pub fn fetch<F>(&mut self, requests: &[(u8, u32)], mut handle: F) -> crate::Result<()>
+    where
+        F: FnMut(u8, u32, Vec<u8>) -> crate::Result<()>,
+    {
+        let mut pending = HashSet::new();
+        let mut requests = requests.iter();
+
+        loop {
+            while pending.len() < MAX_PENDING {
+                let Some(&(index_id, archive_id)) = requests.next() else {
+                    break;
+                };
+                let archive_id = u16::try_from(archive_id).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("archive {} can't be requested over js5", archive_id),
+                    )
+                })?;
+
+                let [archive_hi, archive_lo] = archive_id.to_be_bytes();
+                self.stream
+                    .write_all(&[PRIORITY_REQUEST, index_id, archive_hi, archive_lo])?;
+                pending.insert((index_id, archive_id));
+            }
+            if pending.is_empty() {
+                return Ok(());
+            }
+            self.stream.flush()?;
+
+            let response = protocol::read_js5_response(&mut self.stream)?;
+            let (index_id, archive_id) = (response.index_id, response.archive_id);
+            if !pending.remove(&(index_id, archive_id)) {
+                return Err(DownloadError::UnexpectedResponse {
+                    idx: index_id,
+                    arc: archive_id as u32,
+                }
+                .into());
+            }
+
+            handle(index_id, archive_id as u32, response.container)?;
+        }
+    }
+
+    /// Downloads every index into the cache directory at `path`, creating it
+    /// when missing.
+    ///
+    /// Reference tables and archives already in the directory are kept when
+    /// their crc matches the server's. `progress` is called for every archive
+    /// listed in a reference table, whether it was downloaded or not.
+    ///
+    /// The `.idx` file of an index is written after all its archives, followed
+    /// by its reference table, so an interrupted download resumes from the
+    /// first unfinished index.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the download fails, see [`fetch`](Downloader::fetch), when a
+    /// container doesn't match its crc, or when the cache files can't be
+    /// written.
+    pub fn download<P, F>(&mut self, path: P, mut progress: F) -> crate::Result<DownloadReport>
+    where
+        P: AsRef<Path>,
+        F: FnMut(Progress),
+    {
+        let path = path.as_ref();
+        fs::create_dir_all(path)?;
+        let idx_path = |index_id: u8| path.join(format!("{}{}", IDX_PREFIX, index_id));
+        let existing = if path.join(MAIN_DATA).exists() {
+            Cache::new(path).ok()
+        } else {
+            None
+        };
+        let stored = |index_id: u8, archive_id: u32, crc: u32| {
+            existing
+                .as_ref()
+                .and_then(|cache| cache.read_raw(index_id, archive_id).ok())
+                .filter(|container| {
+                    let (body, _) = codec::split_trailer(container);
+                    crc32fast::hash(body) == crc
+                })
+        };
+
+        let mut dat2 = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path.join(MAIN_DATA))?;
+        let mut report = DownloadReport::default();
+
+        let checksum =
+            codec::decode(&self.request(REFERENCE_TABLE_ID, REFERENCE_TABLE_ID as u32)?)?;
+        let crcs: Vec<u32> = checksum
+            .chunks_exact(8)
+            .map(|entry| u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]))
+            .collect();
+
+        let mut table_idx = read_or_empty(&idx_path(REFERENCE_TABLE_ID))?;
+        for (index_id, &table_crc) in (0..=u8::MAX).zip(&crcs) {
+            let mut idx = read_or_empty(&idx_path(index_id))?;
+            // indices without a reference table still take up their slot.
+            if table_crc == 0 {
+                fs::write(idx_path(index_id), idx)?;
+                continue;
+            }
+
+            let stored_table = stored(REFERENCE_TABLE_ID, index_id as u32, table_crc);
+            let table_container = match &stored_table {
+                Some(container) => {
+                    report.skipped += 1;
+                    container.clone()
+                }
+                None => {
+                    report.downloaded += 1;
+                    let container = self.request(REFERENCE_TABLE_ID, index_id as u32)?;
+                    check_crc(REFERENCE_TABLE_ID, index_id as u32, table_crc, &container)?;
+                    container
+                }
+            };
+            let table = ReferenceTable::parse(&codec::decode(&table_container)?)?;
+
+            let total = table.archives.len();
+            let mut done = 0;
+            let compressed =
+                |archive: &ArchiveEntry| archive.sizes.map(|sizes| u64::from(sizes.compressed));
+            let total_bytes = table.archives.iter().map(compressed).sum::<Option<u64>>();
+            let mut done_bytes = total_bytes.map(|_| 0);
+            let mut missing = Vec::new();
+            for archive in &table.archives {
+                if stored(index_id, archive.id, archive.crc).is_some() {
+                    done += 1;
+                    done_bytes = done_bytes.zip(compressed(archive)).map(|(a, b)| a + b);
+                    report.skipped += 1;
+                    progress(Progress {
+                        index_id,
+                        archive_id: archive.id,
+                        done,
+                        total,
+                        done_bytes,
+                        total_bytes,
+                        downloaded: false,
+                    });
+                } else {
+                    missing.push((index_id, archive.id));
+                }
+            }
+
+            self.fetch(&missing, |index_id, archive_id, mut container| {
+                let position = table
+                    .archives
+                    .binary_search_by_key(&archive_id, |archive| archive.id)
+                    .expect("only listed archives are requested");
+                let archive = &table.archives[position];
+                check_crc(index_id, archive_id, archive.crc, &container)?;
+
+                container.extend(archive.trailer_version().to_be_bytes());
+                let entry = bundle::write_chain(&mut dat2, &[], index_id, archive_id, &container)?;
+                bundle::put_entry(&mut idx, archive_id, entry);
+
+                done += 1;
+                done_bytes = done_bytes.zip(compressed(archive)).map(|(a, b)| a + b);
+                report.downloaded += 1;
+                progress(Progress {
+                    index_id,
+                    archive_id,
+                    done,
+                    total,
+                    done_bytes,
+                    total_bytes,
+                    downloaded: true,
+                });
+                Ok(())
+            })?;
+            fs::write(idx_path(index_id), idx)?;
+            if stored_table.is_some() {
+                continue;
+            }
+
+            let entry = bundle::write_chain(
+                &mut dat2,
+                &[],
+                REFERENCE_TABLE_ID,
+                index_id as u32,
+                &table_container,
+            )?;
+            bundle::put_entry(&mut table_idx, index_id as u32, entry);
+            fs::write(idx_path(REFERENCE_TABLE_ID), &table_idx)?;
+        }
+        fs::write(idx_path(REFERENCE_TABLE_ID), &table_idx)?;
+
+        Ok(report)
+    }
+}
+
+fn check_crc(index_id: u8, archive_id: u32, expected: u32, container: &[u8]) -> crate::Result<()> {
+    let actual = crc32fast::hash(container);
+    if actual != expected {
+        return Err(DownloadError::InvalidCrc {
+            idx: index_id,
+            arc: archive_id,
+            expected,
+            actual,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+fn read_or_empty(path: &Path) -> io::Result<Vec<u8>> {
+    match fs::read(path) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        result => result,
+    }
+}
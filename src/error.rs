@@ -20,6 +20,18 @@ pub enum Error {
     Validate(#[from] ValidateError),
     #[error(transparent)]
     RuneFs(#[from] RuneFsError),
+    #[error(transparent)]
+    Bundle(#[from] BundleError),
+    #[error(transparent)]
+    Read(#[from] ReadError),
+    #[error(transparent)]
+    Flavor(#[from] FlavorError),
+    #[error(transparent)]
+    Checksum(#[from] ChecksumMismatch),
+    #[error(transparent)]
+    ChecksumEncode(#[from] ChecksumError),
+    #[error(transparent)]
+    Download(#[from] DownloadError),
 }
 
 #[derive(Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -43,4 +55,156 @@ pub enum ValidateError {
         internal: u32,
         external: u32,
     },
+    #[error("archive {arc} in index {idx} decoded to {actual} bytes but the reference table expects {expected}")]
+    InvalidSize {
+        idx: u8,
+        arc: u32,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("file {file} not found in archive {arc} of index {idx}")]
+    FileNotFound {
+        idx: u8,
+        arc: u32,
+        file: u32,
+    },
+}
+
+/// A checksum can't be encoded.
+#[derive(Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ChecksumError {
+    /// The named RSA key isn't a number in its
+    /// [format](crate::checksum::RsaKeyFormat), or the modulus is zero.
+    #[error("invalid rsa {0}")]
+    InvalidRsaKey(&'static str),
+}
+
+/// Every difference between a checksum and the crcs sent by a client, see
+/// [`Checksum::validate_detailed`](crate::checksum::Checksum::validate_detailed).
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ChecksumMismatch {
+    /// Number of entries in the checksum.
+    pub expected_len: usize,
+    /// Number of crcs sent by the client.
+    pub actual_len: usize,
+    /// Differing crcs of the indices both sides have, in ascending index order.
+    pub mismatches: Vec<CrcMismatch>,
+    /// [Unchecked](crate::checksum::UNCHECKED_INDICES) indices where one side was empty.
+    pub skipped: Vec<u8>,
+}
+
+impl ChecksumMismatch {
+    /// Whether the client sent too many or too few crcs.
+    #[inline]
+    pub const fn is_length_mismatch(&self) -> bool {
+        self.expected_len != self.actual_len
+    }
+}
+
+impl std::fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("checksum mismatch")?;
+        if self.is_length_mismatch() {
+            write!(
+                f,
+                ", expected {} crcs but got {}",
+                self.expected_len, self.actual_len
+            )?;
+        }
+        for mismatch in &self.mismatches {
+            write!(f, ", {}", mismatch)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+/// Crc of a single index that differs from the client's, see [`ChecksumMismatch`].
+#[derive(Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("index {index_id} expected crc {expected} but was {actual}")]
+pub struct CrcMismatch {
+    pub index_id: u8,
+    pub expected: u32,
+    pub actual: u32,
+}
+
+#[derive(Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum BundleError {
+    #[error("not an index bundle")]
+    InvalidMagic,
+    #[error("unsupported index bundle version {0}")]
+    UnsupportedVersion(u8),
+    #[error("bundled archive {0} is missing from the bundled reference table")]
+    UnknownArchive(u32),
+    #[error("mismatch crc for bundled archive {arc}, expected {expected} but was {actual}")]
+    InvalidCrc {
+        arc: u32,
+        expected: u32,
+        actual: u32,
+    },
+}
+
+#[derive(Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ReadError {
+    #[error("archive {arc} in index {idx} should be {expected} bytes but its sectors only hold {actual}")]
+    LengthMismatch {
+        idx: u8,
+        arc: u32,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("file {file} not found in archive {arc} of index {idx}")]
+    FileNotFound {
+        idx: u8,
+        arc: u32,
+        file: u32,
+    },
+    #[error("no file with name hash {hash} in archive {arc} of index {idx}")]
+    FileNameNotFound {
+        idx: u8,
+        arc: u32,
+        hash: i32,
+    },
+    #[error("tile ({x}, {y}) on plane {plane} is outside of region {region}")]
+    TileOutOfBounds {
+        region: u16,
+        x: u16,
+        y: u16,
+        plane: u8,
+    },
+    /// The container doesn't decode after deciphering, the XTEA keys are most likely wrong.
+    #[error("container could not be decoded with the given xtea keys")]
+    InvalidKeys,
+}
+
+/// The update server misbehaved while downloading a cache.
+#[derive(Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum DownloadError {
+    #[error("the update server rejected the handshake with status {0}")]
+    Rejected(u8),
+    #[error("received archive {arc} of index {idx} which wasn't requested")]
+    UnexpectedResponse { idx: u8, arc: u32 },
+    #[error("mismatch crc for downloaded archive {arc} in index {idx}, expected {expected} but was {actual}")]
+    InvalidCrc {
+        idx: u8,
+        arc: u32,
+        expected: u32,
+        actual: u32,
+    },
+}
+
+/// The cache is of a flavor that can't be opened with the enabled features, see
+/// [`CacheBuilder::check_flavor`](crate::CacheBuilder::check_flavor).
+#[derive(Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum FlavorError {
+    #[error("this looks like an RS3 cache, enable the rs3 feature to open it")]
+    Rs3,
+    #[error("this looks like an OSRS cache, disable the rs3 feature to open it")]
+    Osrs,
+    #[error("this looks like an RS3 jcache cache, only dat2 caches are supported")]
+    Jcache,
+    #[error("this looks like a cache from before the dat2 format, which isn't supported")]
+    Legacy,
 }
@@ -11,6 +11,8 @@ pub trait ReadExt: Read {
     fn read_u16(&mut self) -> io::Result<u16>;
     fn read_i16(&mut self) -> io::Result<i16>;
     fn read_smart_u16(&mut self) -> io::Result<u16>;
+    fn read_smart_i16(&mut self) -> io::Result<i16>;
+    fn read_short_smart(&mut self) -> io::Result<u16>;
     fn read_u24(&mut self) -> io::Result<u32>;
     fn read_i24(&mut self) -> io::Result<i32>;
     fn read_u32(&mut self) -> io::Result<u32>;
@@ -20,6 +22,7 @@ pub trait ReadExt: Read {
     fn read_u128(&mut self) -> io::Result<u128>;
     fn read_i128(&mut self) -> io::Result<i128>;
     fn read_smart(&mut self) -> io::Result<u32>;
+    fn read_var_u32(&mut self) -> io::Result<u32>;
     fn read_string(&mut self) -> io::Result<String>;
 }
 
@@ -58,7 +61,28 @@ impl<T: Read> ReadExt for T {
             arr[1] = value;
 
             let value = u16::from_be_bytes(arr);
-            Ok(value - 0xC000)
+            Ok(value.wrapping_sub(0xC000))
+        }
+    }
+
+    fn read_smart_i16(&mut self) -> io::Result<i16> {
+        let byte = self.read_u8()?;
+
+        if byte < 128 {
+            Ok(i16::from(byte) - 64)
+        } else {
+            let value = u16::from_be_bytes([byte, self.read_u8()?]);
+            Ok((i32::from(value) - 0xC000) as i16)
+        }
+    }
+
+    fn read_short_smart(&mut self) -> io::Result<u16> {
+        let byte = self.read_u8()?;
+
+        if byte < 128 {
+            Ok(byte.into())
+        } else {
+            Ok(u16::from_be_bytes([byte, self.read_u8()?]) - 0x8000)
         }
     }
     fn read_u24(&mut self) -> io::Result<u32> {
@@ -131,6 +155,20 @@ impl<T: Read> ReadExt for T {
         Ok(u32::from_be_bytes(arr) & 0x7fffffff)
     }
 
+    /// 7 bits per byte, least significant first, the high bit marks a following byte.
+    fn read_var_u32(&mut self) -> io::Result<u32> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            value |= u32::from(byte & 0x7F).checked_shl(shift).unwrap_or(0);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
     fn read_string(&mut self) -> io::Result<String> {
         let mut bytes = Vec::new();
         loop {
@@ -0,0 +1,208 @@
+//! Tells OSRS, RS3 and pre-dat2 caches apart before opening them.
+//!
+//! The flavors share file names and most of their formats, so opening a cache
+//! with the wrong protocol tends to fail much later with unrelated errors.
+//! [`detect`] looks at the files in a directory and the headers of its
+//! reference tables and names the flavor they point to.
+//!
+//! # Example
+//!
+//! ```
+//! use rscache::CacheFlavor;
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! let flavor = rscache::detect("./data/osrs_cache")?;
+//! assert!(matches!(flavor, CacheFlavor::Osrs { .. }));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::BTreeSet, fs, path::Path};
+
+use runefs::{ArchiveRef, ARCHIVE_REF_LEN, IDX_PREFIX, MAIN_DATA, REFERENCE_TABLE_ID};
+
+use crate::{
+    codec,
+    dat2::Dat2,
+    error::FlavorError,
+    reference_table::{ReferenceTable, ReferenceTableFlags},
+};
+
+/// Data file of caches from before the dat2 format.
+pub const LEGACY_DATA: &str = "main_file_cache.dat";
+
+/// Extension of the sqlite archive stores of current RS3 clients.
+pub const JCACHE_EXTENSION: &str = "jcache";
+
+/// OSRS caches have never come close to this many indices, RS3 caches have well over it.
+pub const OSRS_MAX_INDICES: usize = 32;
+
+/// The kind of cache found in a directory, see [`detect`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum CacheFlavor {
+    /// A dat2 cache without any RS3 traits.
+    Osrs {
+        /// The highest [revision](ReferenceTable::revision) of the reference
+        /// tables, which grows with every update. `None` when every table
+        /// predates protocol 6.
+        revision_estimate: Option<u32>,
+    },
+    /// A dat2 cache with whirlpool digests or more indices than OSRS has, or a
+    /// directory of jcache files.
+    Rs3 { index_count: usize, jcache: bool },
+    /// A cache with a `main_file_cache.dat` but no dat2 file.
+    Legacy { index_count: usize },
+    /// Nothing conclusive was found.
+    Unknown { evidence: Evidence },
+}
+
+impl CacheFlavor {
+    /// Whether a [`Cache`](crate::Cache) can open caches of this flavor with the
+    /// enabled features, or why not.
+    ///
+    /// Unknown caches aren't rejected, opening them reports the actual problem.
+    pub const fn check(&self) -> Result<(), FlavorError> {
+        match self {
+            Self::Osrs { .. } if cfg!(feature = "rs3") => Err(FlavorError::Osrs),
+            Self::Rs3 { jcache: true, .. } => Err(FlavorError::Jcache),
+            Self::Rs3 { .. } if !cfg!(feature = "rs3") => Err(FlavorError::Rs3),
+            Self::Legacy { .. } => Err(FlavorError::Legacy),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// What [`detect`] found in a cache directory.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Evidence {
+    /// Whether there is a `main_file_cache.dat2`.
+    pub main_data: bool,
+    /// Whether there is a [`main_file_cache.dat`](LEGACY_DATA).
+    pub legacy_data: bool,
+    pub jcache_files: usize,
+    /// Ids of the `.idx` files in ascending order, the reference table index excluded.
+    pub index_ids: Vec<u8>,
+    /// Whether there is a `main_file_cache.idx255`.
+    pub reference_index: bool,
+    /// Number of reference tables that were read.
+    pub reference_tables: usize,
+    /// Ids of the indices whose reference table couldn't be read or parsed.
+    pub unreadable_tables: Vec<u8>,
+    /// Every protocol used by the reference tables.
+    pub protocols: BTreeSet<u8>,
+    /// Union of the flags of every reference table.
+    pub flags: ReferenceTableFlags,
+    pub max_revision: Option<u32>,
+}
+
+impl Evidence {
+    /// Collects the evidence in the directory.
+    ///
+    /// Broken reference tables are recorded instead of failing.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the directory or one of the index files can't be read.
+    pub fn collect<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let mut evidence = Self::default();
+
+        for entry in fs::read_dir(path)? {
+            let name = entry?.file_name();
+            let name = name.to_string_lossy();
+            if name == MAIN_DATA {
+                evidence.main_data = true;
+            } else if name == LEGACY_DATA {
+                evidence.legacy_data = true;
+            } else if name.ends_with(&format!(".{}", JCACHE_EXTENSION)) {
+                evidence.jcache_files += 1;
+            } else if let Some(index_id) = name
+                .strip_prefix(IDX_PREFIX)
+                .and_then(|index_id| index_id.parse::<u8>().ok())
+            {
+                if index_id == REFERENCE_TABLE_ID {
+                    evidence.reference_index = true;
+                } else {
+                    evidence.index_ids.push(index_id);
+                }
+            }
+        }
+        evidence.index_ids.sort_unstable();
+
+        if evidence.main_data && evidence.reference_index {
+            evidence.read_tables(path)?;
+        }
+
+        Ok(evidence)
+    }
+
+    /// The flavor the evidence points to.
+    pub fn flavor(&self) -> CacheFlavor {
+        let index_count = self.index_ids.len();
+        let rs3_flags = self.flags.contains(ReferenceTableFlags::WHIRLPOOL)
+            || self.flags.contains(ReferenceTableFlags::HASHES);
+
+        if self.main_data && self.reference_tables > 0 {
+            if rs3_flags || index_count > OSRS_MAX_INDICES {
+                CacheFlavor::Rs3 {
+                    index_count,
+                    jcache: false,
+                }
+            } else {
+                CacheFlavor::Osrs {
+                    revision_estimate: self.max_revision,
+                }
+            }
+        } else if !self.main_data && self.jcache_files > 0 {
+            CacheFlavor::Rs3 {
+                index_count: self.jcache_files,
+                jcache: true,
+            }
+        } else if !self.main_data && self.legacy_data {
+            CacheFlavor::Legacy { index_count }
+        } else {
+            CacheFlavor::Unknown {
+                evidence: self.clone(),
+            }
+        }
+    }
+
+    fn read_tables(&mut self, path: &Path) -> crate::Result<()> {
+        let data = Dat2::new(path.join(MAIN_DATA))?;
+        let reference_index = fs::read(path.join(format!("{}{}", IDX_PREFIX, REFERENCE_TABLE_ID)))?;
+
+        for (index_id, entry) in reference_index.chunks_exact(ARCHIVE_REF_LEN).enumerate() {
+            let index_id = index_id as u8;
+            let table = ArchiveRef::from_buffer(index_id as u32, REFERENCE_TABLE_ID, entry)
+                .map_err(crate::Error::from)
+                .and_then(|archive| {
+                    if archive.length == 0 || data.is_zeroed(archive.sector) {
+                        return Ok(None);
+                    }
+                    ReferenceTable::parse(&codec::decode(&data.read(&archive)?)?).map(Some)
+                });
+
+            match table {
+                Ok(Some(table)) => {
+                    self.reference_tables += 1;
+                    self.protocols.insert(table.protocol);
+                    self.flags = self.flags | table.flags;
+                    self.max_revision = self.max_revision.max(table.revision);
+                }
+                Ok(None) => {}
+                Err(_) => self.unreadable_tables.push(index_id),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Classifies the cache in the directory, see the [module docs](self).
+///
+/// # Errors
+///
+/// See [`Evidence::collect`].
+pub fn detect<P: AsRef<Path>>(path: P) -> crate::Result<CacheFlavor> {
+    Ok(Evidence::collect(path)?.flavor())
+}
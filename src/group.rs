@@ -0,0 +1,20 @@
+//! Names of well known groups, for use with [`Cache::named_group`](crate::Cache::named_group).
+//!
+//! The client looks these up by name while logging in or drawing the title screen.
+
+/// Huffman code lengths for chat messages, in index 10.
+pub const HUFFMAN: &str = "huffman";
+/// Title screen background, a plain jpeg, in index 10.
+pub const TITLE_IMAGE: &str = "title.jpg";
+/// Login screen music track, in index 6.
+pub const TITLE_MUSIC: &str = "scape main";
+/// Title screen logo sprite, in index 8.
+pub const LOGO: &str = "logo";
+/// Login box sprite, in index 8.
+pub const TITLE_BOX: &str = "titlebox";
+/// Login button sprite, in index 8.
+pub const TITLE_BUTTON: &str = "titlebutton";
+/// World maps, a file per map, in index 19.
+pub const WORLD_MAP_DETAILS: &str = "details";
+/// Squares and icons of the world maps, a file per map, in index 19.
+pub const WORLD_MAP_COMPOSITE: &str = "compositemap";
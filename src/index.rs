@@ -0,0 +1,218 @@
+//! Archive locations of every index, parsed from the `.idx` files.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use runefs::{ArchiveRef, ARCHIVE_REF_LEN, IDX_PREFIX, REFERENCE_TABLE_ID};
+
+use crate::{codec, dat2::Dat2, reference_table::ReferenceTable, OpenWarning};
+
+/// Every index file found in the cache directory, including the reference table index.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Indices {
+    indices: BTreeMap<u8, Index>,
+    warnings: Vec<OpenWarning>,
+}
+
+impl Indices {
+    pub fn new<P: AsRef<Path>>(path: P, data: &Dat2) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let reference_index = Index::from_buffer(
+            REFERENCE_TABLE_ID,
+            &fs::read(path.join(format!("{}{}", IDX_PREFIX, REFERENCE_TABLE_ID)))?,
+        )?;
+        let mut indices = BTreeMap::new();
+        let mut warnings = Vec::new();
+
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            let index_id = path
+                .extension()
+                .and_then(std::ffi::OsStr::to_str)
+                .and_then(|extension| extension.strip_prefix("idx"))
+                .and_then(|index_id| index_id.parse::<u8>().ok());
+
+            match index_id {
+                Some(index_id) if index_id != REFERENCE_TABLE_ID => {
+                    let mut index = Index::from_buffer(index_id, &fs::read(&path)?)?;
+                    // zero-length groups leave the index without a reference table.
+                    if let Some(table) = reference_index.archive_refs.get(index_id as u32) {
+                        if data.is_zeroed(table.sector) {
+                            warnings.push(OpenWarning::ZeroedReferenceTable {
+                                index_id,
+                                sector: table.sector,
+                            });
+                        } else {
+                            index.metadata =
+                                ReferenceTable::parse(&codec::decode(&data.read(table)?)?)?;
+                            index.has_reference_table = true;
+                        }
+                    }
+                    indices.insert(index_id, index);
+                }
+                _ => continue,
+            }
+        }
+
+        indices.insert(REFERENCE_TABLE_ID, reference_index);
+        warnings.sort_unstable();
+
+        Ok(Self { indices, warnings })
+    }
+
+    #[inline]
+    pub fn get(&self, index_id: &u8) -> Option<&Index> {
+        self.indices.get(index_id)
+    }
+
+    /// Ids of every index in ascending order, including the reference table index.
+    #[inline]
+    pub fn ids(&self) -> impl Iterator<Item = u8> + '_ {
+        self.indices.keys().copied()
+    }
+
+    /// Problems found while opening, in ascending index order.
+    #[inline]
+    pub fn warnings(&self) -> &[OpenWarning] {
+        &self.warnings
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Index {
+    pub archive_refs: ArchiveRefs,
+    pub metadata: ReferenceTable,
+    /// Whether `metadata` was parsed from a stored reference table, always
+    /// false for the reference table index itself.
+    pub has_reference_table: bool,
+}
+
+impl Index {
+    fn from_buffer(index_id: u8, buffer: &[u8]) -> crate::Result<Self> {
+        let mut archive_refs = Vec::with_capacity(buffer.len() / ARCHIVE_REF_LEN);
+        for (archive_id, entry) in buffer.chunks_exact(ARCHIVE_REF_LEN).enumerate() {
+            archive_refs.push(ArchiveRef::from_buffer(archive_id as u32, index_id, entry)?);
+        }
+
+        Ok(Self {
+            archive_refs: ArchiveRefs::new(archive_refs),
+            metadata: ReferenceTable::default(),
+            has_reference_table: false,
+        })
+    }
+}
+
+/// Archive locations of a single index.
+///
+/// Idx files store an entry for every id up to the highest one, missing archives
+/// have a length of 0. Most indices are dense and are looked up by position,
+/// sparse ones (like maps) only keep their present archives and are searched.
+#[derive(Clone, Debug)]
+pub(crate) enum ArchiveRefs {
+    Dense { refs: Box<[ArchiveRef]>, len: usize },
+    Sparse(Box<[ArchiveRef]>),
+}
+
+impl Default for ArchiveRefs {
+    fn default() -> Self {
+        Self::Sparse(Box::default())
+    }
+}
+
+impl ArchiveRefs {
+    /// Indices where less than a quarter of the ids is present are stored sparse.
+    const SPARSE_RATIO: usize = 4;
+
+    fn new(refs: Vec<ArchiveRef>) -> Self {
+        let len = refs.iter().filter(|archive| archive.length != 0).count();
+
+        if len * Self::SPARSE_RATIO < refs.len() {
+            let present: Vec<ArchiveRef> = refs
+                .into_iter()
+                .filter(|archive| archive.length != 0)
+                .collect();
+            Self::Sparse(present.into_boxed_slice())
+        } else {
+            Self::Dense {
+                refs: refs.into_boxed_slice(),
+                len,
+            }
+        }
+    }
+
+    /// Location of a present archive.
+    #[inline]
+    pub fn get(&self, archive_id: u32) -> Option<&ArchiveRef> {
+        match self {
+            Self::Dense { refs, .. } => refs
+                .get(archive_id as usize)
+                .filter(|archive| archive.length != 0),
+            Self::Sparse(refs) => refs
+                .binary_search_by_key(&archive_id, |archive| archive.id)
+                .ok()
+                .map(|index| &refs[index]),
+        }
+    }
+
+    /// Number of present archives.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Dense { len, .. } => *len,
+            Self::Sparse(refs) => refs.len(),
+        }
+    }
+
+    /// Locations of the present archives, in ascending id order.
+    pub fn iter(&self) -> impl Iterator<Item = &ArchiveRef> {
+        let refs = match self {
+            Self::Dense { refs, .. } | Self::Sparse(refs) => refs,
+        };
+
+        refs.iter().filter(|archive| archive.length != 0)
+    }
+
+    /// Heap memory used by the archive locations.
+    #[cfg(test)]
+    fn heap_size(&self) -> usize {
+        match self {
+            Self::Dense { refs, .. } | Self::Sparse(refs) => {
+                refs.len() * std::mem::size_of::<ArchiveRef>()
+            }
+        }
+    }
+}
+
+#[test]
+fn smaller_than_hash_maps() -> crate::Result<()> {
+    let path = "./data/osrs_cache";
+    let data = Dat2::new(Path::new(path).join(runefs::MAIN_DATA))?;
+    let indices = Indices::new(path, &data)?;
+    let hashed = runefs::Indices::new(path)?;
+
+    let mut slices = 0;
+    let mut maps = 0;
+    for (index_id, index) in &hashed {
+        let archive_refs = &indices.get(index_id).unwrap().archive_refs;
+        slices += archive_refs.heap_size();
+        // hashbrown stores one control byte per bucket next to every (key, value) pair.
+        maps += index.archive_refs.capacity() * (std::mem::size_of::<(u32, ArchiveRef)>() + 1);
+
+        let present = index
+            .archive_refs
+            .values()
+            .filter(|archive| archive.length != 0);
+        assert_eq!(archive_refs.len(), present.clone().count());
+        for archive in present {
+            assert_eq!(archive_refs.get(archive.id), Some(archive));
+        }
+    }
+
+    assert!(
+        slices < maps,
+        "{} bytes stored, {} bytes hashed",
+        slices,
+        maps
+    );
+
+    Ok(())
+}
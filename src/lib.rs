@@ -1,4 +1,4 @@
-//! A read-only, high-level, virtual file API for the RuneScape cache.
+//! A high-level, virtual file API for the RuneScape cache.
 //!
 //! This crate provides high performant data reads into the [Oldschool
 //! RuneScape] and [RuneScape 3] cache file systems. It can read the necessary
@@ -29,12 +29,23 @@
 //! In order to read bytes in a high performant way the cache uses [memmap2].
 //! This can be unsafe because of its potential for _Undefined Behaviour_ when
 //! the underlying file is subsequently modified, in or out of process. Using
-//! `Mmap` here is safe because the RuneScape cache is a read-only binary file
-//! system. The map will remain valid even after the `File` is dropped, it's
+//! `Mmap` here is safe because a cache opened with [`Cache::new`] is only ever
+//! read. The map will remain valid even after the `File` is dropped, it's
 //! completely independent of the `File` used to create it. Therefore, the use
 //! of unsafe is not propagated outwards. When the `Cache` is dropped memory
 //! will be subsequently unmapped.
 //!
+//! The map is the only `unsafe` in the crate. A cache opened with
+//! [`Cache::open_rw`] is written to, so it never maps the data file and reads
+//! it like the `safe-io` feature does. Don't update a cache in place, with
+//! [`Cache::write`] or otherwise, while another `Cache` has it open, write
+//! the new files next to it and [reload](Cache::reload) instead.
+//!
+//! Where that guarantee can't be made, or `unsafe` isn't allowed at all, the
+//! `safe-io` feature replaces the map with positioned file reads through a
+//! small block cache. The crate is then compiled with `forbid(unsafe_code)`.
+//! Reads cost more, see the [`throughput` benchmarks] for numbers.
+//!
 //! # Features
 //!
 //! The cache's protocol defaults to OSRS. In order to use the RS3 protocol you
@@ -42,6 +53,13 @@
 //! `Serialize` and `Deserialize`. The `serde-derive` feature flag can be used
 //! to enable (de)serialization on any compatible types.
 //!
+//! The default `mmap` feature memory maps the data file. Enabling `safe-io`,
+//! or disabling default features, reads it without `unsafe` instead, see
+//! [Safety](#safety).
+//!
+//! The `cas` feature adds the [`cas`] module, which backs up cache revisions
+//! into a deduplicating, content-addressed object store.
+//!
 //! # Quick Start
 //!
 //! For an instance that stays local to this thread you can simply use:
@@ -113,9 +131,11 @@
 //! [opening an issue]: https://github.com/jimvdl/rs-cache/issues/new
 //! [serde]: https://crates.io/crates/serde
 //! [memmap2]: https://crates.io/crates/memmap2
+//! [`throughput` benchmarks]: https://github.com/jimvdl/rs-cache/tree/master/benches/throughput
 //! [`Huffman`]: crate::util::Huffman
 //! [`IsaacRand`]: crate::util::IsaacRand
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(any(feature = "safe-io", not(feature = "mmap")), forbid(unsafe_code))]
 #![deny(
     clippy::all,
     clippy::correctness,
@@ -127,29 +147,344 @@
 
 #[macro_use]
 pub mod util;
+pub mod bundle;
+#[cfg(feature = "cas")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cas")))]
+pub mod cas;
 pub mod checksum;
+pub mod codec;
+mod dat2;
+mod decoded;
+pub mod decryption;
 pub mod definition;
+pub mod diff;
+#[cfg(feature = "net")]
+#[cfg_attr(docsrs, doc(cfg(feature = "net")))]
+pub mod downloader;
 pub mod error;
 pub mod extension;
+pub mod flavor;
+pub mod group;
+mod index;
 pub mod loader;
+pub mod protocol;
+pub mod prune;
+pub mod reference_table;
+#[cfg(feature = "image")]
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+pub mod render;
+pub mod timeline;
+pub mod verify;
 
 #[doc(inline)]
 pub use error::Error;
+pub use flavor::{detect, CacheFlavor};
 use error::Result;
 
 use checksum::Checksum;
+use decryption::{Decryption, DecryptionRegistry};
+use definition::config_archives::{ConfigArchive, CONFIG_INDEX};
 #[cfg(feature = "rs3")]
 use checksum::{RsaChecksum, RsaKeys};
+use error::ValidateError;
+use reference_table::{ArchiveEntry, IndexMetadata, ReferenceTable, ReferenceTableFlags};
 use runefs::codec::{Buffer, Decoded, Encoded};
 use runefs::error::{Error as RuneFsError, ReadError};
-use runefs::{ArchiveRef, Dat2, Indices, MAIN_DATA};
-use std::{io::Write, path::Path};
+use index::Indices;
+use dat2::Dat2;
+use decoded::DecodedArchives;
+use runefs::{ArchiveRef, MAIN_DATA, REFERENCE_TABLE_ID};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
 
 /// A complete virtual representation of the RuneScape cache file system.
 #[derive(Debug)]
 pub struct Cache {
     pub(crate) data: Dat2,
     pub(crate) indices: Indices,
+    pub(crate) path: PathBuf,
+    check_sizes: bool,
+    writable: bool,
+    read_transforms: Transforms,
+    write_transforms: Transforms,
+    decryption: DecryptionRegistry,
+    decoded: Option<Mutex<DecodedArchives>>,
+}
+
+/// Transforms a raw archive container of the given index and archive, see
+/// [`CacheBuilder::with_read_transform`].
+pub type ReadTransform =
+    dyn Fn(u8, u32, Vec<u8>) -> std::result::Result<Vec<u8>, Error> + Send + Sync;
+
+/// Transforms an archive container before it is stored, see
+/// [`CacheBuilder::with_write_transform`].
+pub type WriteTransform = ReadTransform;
+
+#[derive(Clone, Default)]
+struct Transforms(Vec<Arc<ReadTransform>>);
+
+impl Transforms {
+    fn apply(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+        buffer: Buffer<Encoded>,
+    ) -> crate::Result<Buffer<Encoded>> {
+        if self.0.is_empty() || index_id == REFERENCE_TABLE_ID {
+            return Ok(buffer);
+        }
+
+        Ok(Buffer::from(self.apply_to(
+            index_id,
+            archive_id,
+            buffer.finalize(),
+        )?))
+    }
+
+    fn apply_to(&self, index_id: u8, archive_id: u32, mut data: Vec<u8>) -> crate::Result<Vec<u8>> {
+        for transform in &self.0 {
+            data = transform(index_id, archive_id, data)?;
+        }
+
+        Ok(data)
+    }
+}
+
+impl std::fmt::Debug for Transforms {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Transforms")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// An archive stored in an index, see [`Cache::archives`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ArchiveInfo {
+    pub id: u32,
+    /// Hash of the archive's name, `None` when the reference table isn't
+    /// [named](reference_table::ReferenceTableFlags::NAMED) or doesn't list the archive.
+    pub name_hash: Option<i32>,
+    /// The first sector, as stored in the idx file.
+    pub sector: u32,
+    /// Length of the archive container in bytes.
+    pub length: usize,
+}
+
+/// Where an archive is stored in `main_file_cache.dat2`, see [`Cache::archive_placement`].
+///
+/// Sector `n` starts at byte `n * 520` of the data file, with a header of
+/// [`header_len`](ArchivePlacement::header_len) bytes followed by up to
+/// [`data_len`](ArchivePlacement::data_len) bytes of the archive.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ArchivePlacement {
+    pub index_id: u8,
+    pub archive_id: u32,
+    /// The first sector, as stored in the idx file.
+    pub sector: u32,
+    /// Length of the archive container in bytes.
+    pub length: usize,
+    /// Every sector of the chain in order, starting with [`sector`](ArchivePlacement::sector).
+    pub sectors: Vec<u32>,
+}
+
+impl ArchivePlacement {
+    /// Length of every sector header, archives with ids above `u16::MAX` have larger headers.
+    pub const fn header_len(&self) -> usize {
+        if self.archive_id > u16::MAX as u32 {
+            runefs::SECTOR_EXPANDED_HEADER_SIZE
+        } else {
+            runefs::SECTOR_HEADER_SIZE
+        }
+    }
+
+    /// Archive bytes held by every sector but the last.
+    pub const fn data_len(&self) -> usize {
+        runefs::SECTOR_SIZE - self.header_len()
+    }
+}
+
+/// Whether an index is present in the cache, see [`Cache::index_state`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum IndexState {
+    /// There is no `.idx` file for the index.
+    Missing,
+    /// The `.idx` file exists but holds no archives, or there is no usable
+    /// reference table for it.
+    Empty,
+    /// The index holds this many archives.
+    Populated { archives: usize },
+}
+
+/// A problem that didn't stop the cache from opening, see [`Cache::open_warnings`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[non_exhaustive]
+pub enum OpenWarning {
+    /// The idx255 entry of the index points to a sector holding only zeros,
+    /// the index is treated as [`Empty`](IndexState::Empty).
+    ZeroedReferenceTable { index_id: u8, sector: usize },
+}
+
+/// Opens a [`Cache`] with non-default options.
+///
+/// # Examples
+///
+/// ```
+/// use rscache::Cache;
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// let cache = Cache::builder("./data/osrs_cache")
+///     .check_sizes(true)
+///     .open()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CacheBuilder {
+    path: PathBuf,
+    check_sizes: bool,
+    check_flavor: bool,
+    writable: bool,
+    read_transforms: Transforms,
+    write_transforms: Transforms,
+    decryption: DecryptionRegistry,
+    decoded_cache_size: usize,
+}
+
+impl CacheBuilder {
+    /// Check decoded archive lengths against the reference table in
+    /// [`read_decoded`](Cache::read_decoded), disabled by default.
+    ///
+    /// Only indices with the [`SIZES`](ReferenceTableFlags::SIZES) flag are checked.
+    pub const fn check_sizes(mut self, check_sizes: bool) -> Self {
+        self.check_sizes = check_sizes;
+        self
+    }
+
+    /// [Detect](detect) the flavor of the cache before opening it, disabled by default.
+    ///
+    /// Caches that need other features, or can't be opened at all, then fail
+    /// early with a [`FlavorError`](error::FlavorError) naming the problem
+    /// instead of an error from deep inside the parser.
+    pub const fn check_flavor(mut self, check_flavor: bool) -> Self {
+        self.check_flavor = check_flavor;
+        self
+    }
+
+    /// Allow [writing](Cache::write) archives, disabled by default, see [`Cache::open_rw`].
+    ///
+    /// Writable caches never memory map the data file.
+    pub const fn writable(mut self, writable: bool) -> Self {
+        self.writable = writable;
+        self
+    }
+
+    /// Registers a transform applied to every raw archive container right after
+    /// it is read from disk and before it is decoded, e.g. to undo custom
+    /// obfuscation. Transforms run in registration order.
+    ///
+    /// Reference tables (index 255) are parsed when the cache is opened and are
+    /// never transformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::Cache;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// let cache = Cache::builder("./data/osrs_cache")
+    ///     .with_read_transform(|_index_id, _archive_id, data| Ok(data))
+    ///     .open()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_read_transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(u8, u32, Vec<u8>) -> std::result::Result<Vec<u8>, Error> + Send + Sync + 'static,
+    {
+        self.read_transforms.0.push(Arc::new(transform));
+        self
+    }
+
+    /// Registers a transform applied to every archive container
+    /// [written](Cache::write), version trailer included, right before it is
+    /// stored, e.g. to redo the obfuscation a [read
+    /// transform](CacheBuilder::with_read_transform) undoes. Transforms run in
+    /// registration order.
+    ///
+    /// Reference tables (index 255) are never transformed, the crc they list
+    /// is computed over the stored container.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::Cache;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// let cache = Cache::builder("./data/osrs_cache")
+    ///     .writable(true)
+    ///     .with_write_transform(|_index_id, _archive_id, data| Ok(data))
+    ///     .open()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_write_transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(u8, u32, Vec<u8>) -> std::result::Result<Vec<u8>, Error> + Send + Sync + 'static,
+    {
+        self.write_transforms.0.push(Arc::new(transform));
+        self
+    }
+
+    /// Deciphers the registered archives in [`read_decoded`](Cache::read_decoded)
+    /// and the loaders, see [`decryption`].
+    ///
+    /// Decryption happens after the [read transforms](CacheBuilder::with_read_transform).
+    pub fn decryption(mut self, registry: DecryptionRegistry) -> Self {
+        self.decryption = registry;
+        self
+    }
+
+    /// Keep up to `bytes` of decoded archives around for
+    /// [`read_decoded_shared`](Cache::read_decoded_shared), dropping the least
+    /// recently used ones first. Disabled with 0, the default.
+    ///
+    /// The limit counts decoded bytes rather than archives, a single map
+    /// archive can be as large as hundreds of config archives.
+    pub const fn decoded_cache_size(mut self, bytes: usize) -> Self {
+        self.decoded_cache_size = bytes;
+        self
+    }
+
+    /// Opens the cache with the configured options.
+    ///
+    /// # Errors
+    ///
+    /// See [`Cache::new`].
+    pub fn open(self) -> crate::Result<Cache> {
+        if self.check_flavor {
+            detect(&self.path)?.check()?;
+        }
+        let data = open_data(&self.path, self.writable)?;
+
+        Ok(Cache {
+            indices: Indices::new(&self.path, &data)?,
+            data,
+            check_sizes: self.check_sizes,
+            writable: self.writable,
+            read_transforms: self.read_transforms,
+            write_transforms: self.write_transforms,
+            decryption: self.decryption,
+            decoded: (self.decoded_cache_size > 0)
+                .then(|| Mutex::new(DecodedArchives::new(self.decoded_cache_size))),
+            path: self.path,
+        })
+    }
 }
 
 impl Cache {
@@ -165,10 +500,286 @@ impl Cache {
     /// Other errors might include protocol changes in newer caches. Any error
     /// unrelated to I/O at this stage should be considered a bug.
     pub fn new<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
-        Ok(Self {
-            data: Dat2::new(path.as_ref().join(MAIN_DATA))?,
-            indices: Indices::new(path)?,
-        })
+        Self::builder(path).open()
+    }
+
+    /// Creates a [`CacheBuilder`] to open the cache with non-default options.
+    pub fn builder<P: AsRef<Path>>(path: P) -> CacheBuilder {
+        CacheBuilder {
+            path: path.as_ref().to_path_buf(),
+            check_sizes: false,
+            check_flavor: false,
+            writable: false,
+            read_transforms: Transforms::default(),
+            write_transforms: Transforms::default(),
+            decryption: DecryptionRegistry::default(),
+            decoded_cache_size: 0,
+        }
+    }
+
+    /// Opens the cache like [`Cache::new`] and allows [writing](Cache::write) to it.
+    ///
+    /// The data file is read through positioned reads, like with the `safe-io`
+    /// feature, instead of being memory mapped, so it can be written to safely.
+    ///
+    /// # Errors
+    ///
+    /// See [`Cache::new`].
+    pub fn open_rw<P: AsRef<Path>>(path: P) -> crate::Result<Self> {
+        Self::builder(path).writable(true).open()
+    }
+
+    /// Re-opens the cache from disk, picking up any changes made to its files.
+    ///
+    /// Reference tables are parsed again.
+    ///
+    /// # Errors
+    ///
+    /// See [`Cache::new`], the cache is left untouched when reopening fails.
+    pub fn reload(&mut self) -> crate::Result<()> {
+        let data = open_data(&self.path, self.writable)?;
+        self.indices = Indices::new(&self.path, &data)?;
+        self.data = data;
+        if let Some(decoded) = &mut self.decoded {
+            decoded
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clear();
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the data into an archive, replacing the archive or adding it
+    /// to the index, and updates the index's reference table.
+    ///
+    /// The container keeps the compression of the archive it replaces, new
+    /// archives get the one of [`CompressionPolicy::osrs`](codec::CompressionPolicy::osrs)
+    /// and a single file, see [`write_with_policy`](Cache::write_with_policy)
+    /// to choose it. It takes over the sectors of the old container when it
+    /// fits into them and is appended to the data file otherwise. The archive's
+    /// version is bumped, its crc, sizes and whirlpool digest are updated, and
+    /// the reference table is written back the same way with a bumped revision.
+    /// The cache is [reloaded](Cache::reload) afterwards.
+    ///
+    /// Containers are written without XTEA encryption, the
+    /// [write transforms](CacheBuilder::with_write_transform) run on them
+    /// right before they are stored. Other instances opened on the same files
+    /// have to be reloaded.
+    ///
+    /// # Errors
+    ///
+    /// Fails with a `PermissionDenied` I/O error unless the cache was opened
+    /// with [`open_rw`](Cache::open_rw), with `IndexNotFound` when the index
+    /// doesn't exist, with an `Unsupported` I/O error for the reference table
+    /// index, indices without a reference table and tables with
+    /// [`HASHES`](ReferenceTableFlags::HASHES) (or whirlpool digests without
+    /// the `whirlpool` feature), and when the files can't be written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let dir = tempfile::tempdir()?;
+    /// # for entry in std::fs::read_dir("./data/osrs_cache")? {
+    /// #     let entry = entry?;
+    /// #     std::fs::copy(entry.path(), dir.path().join(entry.file_name()))?;
+    /// # }
+    /// let mut cache = Cache::open_rw(dir.path())?;
+    /// let version = cache.archive_metadata(10, 1)?.version;
+    ///
+    /// cache.write(10, 1, b"huffman")?;
+    /// assert_eq!(cache.read_decoded(10, 1)?.as_slice(), b"huffman");
+    /// assert_eq!(cache.archive_metadata(10, 1)?.version, version + 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write(&mut self, index_id: u8, archive_id: u32, data: &[u8]) -> crate::Result<()> {
+        let mut policy = codec::CompressionPolicy::osrs();
+        for (index_id, archive_id) in [
+            (index_id, archive_id),
+            (REFERENCE_TABLE_ID, index_id as u32),
+        ] {
+            if let Some(compression) = self.stored_compression(index_id, archive_id) {
+                policy = policy.with_archive(index_id, archive_id, compression);
+            }
+        }
+
+        self.write_with_policy(index_id, archive_id, data, &policy)
+    }
+
+    /// [`write`](Cache::write) with the compression of the archive and of the
+    /// index's reference table chosen by `policy`, instead of keeping the
+    /// stored ones.
+    ///
+    /// # Errors
+    ///
+    /// See [`write`](Cache::write).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// use rscache::codec::{self, Compression, CompressionPolicy};
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let dir = tempfile::tempdir()?;
+    /// # for entry in std::fs::read_dir("./data/osrs_cache")? {
+    /// #     let entry = entry?;
+    /// #     std::fs::copy(entry.path(), dir.path().join(entry.file_name()))?;
+    /// # }
+    /// let mut cache = Cache::open_rw(dir.path())?;
+    /// let policy = CompressionPolicy::osrs().with_index(10, Compression::None);
+    ///
+    /// cache.write_with_policy(10, 1, b"huffman", &policy)?;
+    /// let archive = codec::decode_full(&cache.read(10, 1)?)?;
+    /// assert_eq!(archive.compression, Compression::None);
+    /// assert_eq!(archive.data, b"huffman");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_with_policy(
+        &mut self,
+        index_id: u8,
+        archive_id: u32,
+        data: &[u8],
+        policy: &codec::CompressionPolicy,
+    ) -> crate::Result<()> {
+        let unsupported = |message: &str| -> Error {
+            std::io::Error::new(std::io::ErrorKind::Unsupported, message.to_owned()).into()
+        };
+        if !self.writable {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "the cache was opened read-only, see Cache::open_rw",
+            )
+            .into());
+        }
+        let index = self
+            .indices
+            .get(&index_id)
+            .ok_or(RuneFsError::Read(ReadError::IndexNotFound(index_id)))?;
+        if index_id == REFERENCE_TABLE_ID {
+            return Err(unsupported(
+                "reference tables are written along with their index",
+            ));
+        }
+        if !index.has_reference_table {
+            return Err(unsupported("the index has no reference table"));
+        }
+
+        let mut table = self.reference_table(index_id)?.clone();
+        let flags = table.flags;
+        if flags.contains(ReferenceTableFlags::HASHES)
+            || (flags.contains(ReferenceTableFlags::WHIRLPOOL) && !cfg!(feature = "whirlpool"))
+        {
+            return Err(unsupported(
+                "the reference table has digests that can't be updated",
+            ));
+        }
+
+        let old_ref = index.archive_refs.get(archive_id).copied();
+        let table_ref = self
+            .indices
+            .get(&REFERENCE_TABLE_ID)
+            .and_then(|index| index.archive_refs.get(index_id as u32))
+            .copied();
+        let body = codec::encode(data, policy.compression_for(index_id, archive_id))?;
+        let named = flags.contains(ReferenceTableFlags::NAMED);
+        let mut archive = table
+            .archive(archive_id)
+            .cloned()
+            .unwrap_or_else(|| ArchiveEntry {
+                id: archive_id,
+                name_hash: named.then_some(0),
+                file_ids: vec![0],
+                file_name_hashes: named.then(|| vec![0]),
+                ..ArchiveEntry::default()
+            });
+        archive.version = archive.version.wrapping_add(1);
+        if flags.contains(ReferenceTableFlags::SIZES) {
+            archive.sizes = Some(reference_table::ArchiveSizes {
+                compressed: body.len() as u32,
+                decompressed: data.len() as u32,
+            });
+        }
+        let mut container = body;
+        container.extend(archive.trailer_version().to_be_bytes());
+        let container = self
+            .write_transforms
+            .apply_to(index_id, archive_id, container)?;
+        let (stored, _) = codec::split_trailer(&container);
+        archive.crc = crc32fast::hash(stored);
+        #[cfg(feature = "whirlpool")]
+        if flags.contains(ReferenceTableFlags::WHIRLPOOL) {
+            use whirlpool::{Digest, Whirlpool};
+            archive.whirlpool = Some(Whirlpool::digest(stored).to_vec());
+        }
+
+        match table
+            .archives
+            .binary_search_by_key(&archive_id, |archive| archive.id)
+        {
+            Ok(position) => table.archives[position] = archive,
+            Err(position) => table.archives.insert(position, archive),
+        }
+        table.revision = table.revision.map(|revision| revision.wrapping_add(1));
+        let table_container = codec::encode(
+            &table.encode(),
+            policy.compression_for(REFERENCE_TABLE_ID, index_id as u32),
+        )?;
+
+        let old_sectors = old_ref
+            .map(|archive| self.data.sectors(&archive))
+            .transpose()?;
+        let table_sectors = table_ref
+            .map(|table| self.data.sectors(&table))
+            .transpose()?;
+        let mut dat2 = std::fs::OpenOptions::new()
+            .write(true)
+            .open(self.path.join(MAIN_DATA))?;
+        let entry = bundle::write_chain(
+            &mut dat2,
+            old_sectors.as_deref().unwrap_or_default(),
+            index_id,
+            archive_id,
+            &container,
+        )?;
+        let table_entry = bundle::write_chain(
+            &mut dat2,
+            table_sectors.as_deref().unwrap_or_default(),
+            REFERENCE_TABLE_ID,
+            index_id as u32,
+            &table_container,
+        )?;
+
+        for (index_id, archive_id, entry) in [
+            (index_id, archive_id, entry),
+            (REFERENCE_TABLE_ID, index_id as u32, table_entry),
+        ] {
+            let path = self
+                .path
+                .join(format!("{}{}", runefs::IDX_PREFIX, index_id));
+            let mut idx = std::fs::read(&path)?;
+            bundle::put_entry(&mut idx, archive_id, entry);
+            std::fs::write(path, idx)?;
+        }
+
+        self.reload()
+    }
+
+    /// Compression of the stored container, `None` when the archive doesn't
+    /// exist or can't be read.
+    fn stored_compression(&self, index_id: u8, archive_id: u32) -> Option<codec::Compression> {
+        let archive = *self.indices.get(&index_id)?.archive_refs.get(archive_id)?;
+        let container = self.data.read(&archive).ok()?;
+        let container = self
+            .read_transforms
+            .apply(archive.index_id, archive.id, container)
+            .ok()?;
+
+        codec::Compression::try_from(*container.first()?).ok()
     }
 
     /// Generate a checksum based on the current cache.
@@ -180,6 +791,95 @@ impl Cache {
         Checksum::new(self)
     }
 
+    /// Computes the checksum entry of a single index, without reading the
+    /// reference tables of the others.
+    ///
+    /// The entry is the same as in a full [`checksum`](Cache::checksum), an
+    /// index without a readable reference table gets an empty one. Use
+    /// [`Checksum::update_entry`] to patch it into an existing checksum.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IndexNotFound` when the index is past the last one the
+    /// checksum lists, see [`index_count`](Cache::index_count).
+    pub fn checksum_entry(&self, index_id: u8) -> crate::Result<checksum::Entry> {
+        Checksum::listed_entry(self, index_id)
+    }
+
+    /// Compares the reference tables of this cache with `other`, listing the
+    /// archives `other` added, removed or modified per index.
+    ///
+    /// Only metadata is compared, see [`diff`](crate::diff).
+    ///
+    /// # Errors
+    ///
+    /// Fails when a reference table of either cache can't be read or parsed.
+    pub fn diff(&self, other: &Cache) -> crate::Result<diff::CacheDiff> {
+        diff::diff(self, other)
+    }
+
+    /// Checks the sector chain of every stored archive, reference tables
+    /// included, and that every archive a reference table lists is stored.
+    ///
+    /// Problems are collected per archive instead of failing on the first
+    /// one, see [`verify`](crate::verify) for the details.
+    ///
+    /// # Errors
+    ///
+    /// Only fails when `main_file_cache.dat2` can't be read.
+    pub fn verify(&self) -> crate::Result<verify::VerifyReport> {
+        self.verify_with(verify::VerifyOptions::new())
+    }
+
+    /// Same as [`verify`](Cache::verify) with extra checks, like the crc of
+    /// every container.
+    ///
+    /// # Errors
+    ///
+    /// Only fails when `main_file_cache.dat2` can't be read.
+    pub fn verify_with(&self, options: verify::VerifyOptions) -> crate::Result<verify::VerifyReport> {
+        verify::verify(self, options)
+    }
+
+    /// Generate a checksum with exactly `count` entries, for clients that expect
+    /// a table of that length.
+    ///
+    /// Entries of indices beyond `count` are dropped, missing ones are padded
+    /// with empty entries, see [`Checksum::resized`].
+    pub fn checksum_with_len(&self, count: usize) -> crate::Result<Checksum> {
+        Ok(self.checksum()?.resized(count))
+    }
+
+    /// Compares the checksum of the cache with a known-good reference list stored at `path`.
+    ///
+    /// Files ending in `.json` are read with
+    /// [`ReferenceEntry::read_json`](checksum::ReferenceEntry::read_json) when
+    /// the `serde` feature is enabled, all others as
+    /// [csv](checksum::ReferenceEntry::read_csv). This never touches the network.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the reference list can't be read or parsed, or the checksum
+    /// can't be created.
+    pub fn verify_against_reference<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> crate::Result<checksum::MatchReport> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+
+        #[cfg(feature = "serde")]
+        let reference = if path.extension().is_some_and(|ext| ext == "json") {
+            checksum::ReferenceEntry::read_json(file)?
+        } else {
+            checksum::ReferenceEntry::read_csv(file)?
+        };
+        #[cfg(not(feature = "serde"))]
+        let reference = checksum::ReferenceEntry::read_csv(file)?;
+
+        Ok(self.checksum()?.matches_reference(&reference))
+    }
+
     /// Generate a checksum based on the current cache with RSA encryption.
     ///
     /// `RsaChecksum` wraps a regular `Checksum` with the added benefit of
@@ -193,6 +893,9 @@ impl Cache {
     /// Retrieves and constructs data corresponding to the given index and
     /// archive.
     ///
+    /// [Read transforms](CacheBuilder::with_read_transform) are applied, see
+    /// [`read_raw`](Cache::read_raw) for the container as stored.
+    ///
     /// # Errors
     ///
     /// When trying to retrieve data from an index or an archive that does not
@@ -202,24 +905,80 @@ impl Cache {
     /// Any other errors such as sector validation failures or failed parsers
     /// should be considered a bug.
     pub fn read(&self, index_id: u8, archive_id: u32) -> crate::Result<Buffer<Encoded>> {
-        let index = self
-            .indices
-            .get(&index_id)
-            .ok_or(RuneFsError::Read(ReadError::IndexNotFound(index_id)))?;
+        let archive = self.archive_ref(index_id, archive_id)?;
+        let buffer = self.data.read(archive)?;
 
-        let archive = index
-            .archive_refs
-            .get(&archive_id)
-            .ok_or(RuneFsError::Read(ReadError::ArchiveNotFound {
-                idx: index_id,
-                arc: archive_id,
-            }))?;
+        self.read_transforms.apply(index_id, archive_id, buffer)
+    }
 
-        let buffer = self.data.read(archive)?;
+    /// Retrieves the container of an archive exactly as stored in
+    /// `main_file_cache.dat2`, read transforms are never applied.
+    ///
+    /// These are the bytes the reference table [`crc`](ArchiveEntry::crc) is
+    /// computed over, excluding the 2-byte version
+    /// [trailer](codec::split_trailer). Serve these untouched to clients, any
+    /// re-encode changes the crc.
+    ///
+    /// # Errors
+    ///
+    /// See [`read`](Cache::read).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// use rscache::codec;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let container = cache.read_raw(2, 10)?;
+    /// let (body, _) = codec::split_trailer(&container);
+    ///
+    /// assert_eq!(crc32fast::hash(body), cache.archive_metadata(2, 10)?.crc);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_raw(&self, index_id: u8, archive_id: u32) -> crate::Result<Vec<u8>> {
+        let archive = self.archive_ref(index_id, archive_id)?;
 
-        assert_eq!(buffer.len(), archive.length);
+        Ok(self.data.read(archive)?.finalize())
+    }
 
-        Ok(buffer)
+    /// Retrieves an archive without copying it when possible.
+    ///
+    /// Archives that fit in a single sector are borrowed straight from the
+    /// memory mapped data file, longer archives need their sectors reassembled
+    /// and are read into an owned buffer like [`read`](Cache::read). Reads are
+    /// always owned with [read transforms](CacheBuilder::with_read_transform),
+    /// the `safe-io` feature or caches opened with [`open_rw`](Cache::open_rw).
+    ///
+    /// # Errors
+    ///
+    /// See [`read`](Cache::read).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// use std::borrow::Cow;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let buffer = cache.read_ref(255, 10)?;
+    ///
+    /// # #[cfg(not(feature = "safe-io"))]
+    /// assert!(matches!(buffer, Cow::Borrowed(_)));
+    /// assert_eq!(buffer, cache.read(255, 10)?.as_slice());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_ref(&self, index_id: u8, archive_id: u32) -> crate::Result<Cow<'_, [u8]>> {
+        if !self.read_transforms.0.is_empty() {
+            return Ok(Cow::Owned(self.read(index_id, archive_id)?.finalize()));
+        }
+
+        let archive = self.archive_ref(index_id, archive_id)?;
+        self.data.read_ref(archive)
     }
 
     pub(crate) fn read_archive(&self, archive: &ArchiveRef) -> crate::Result<Buffer<Encoded>> {
@@ -238,6 +997,826 @@ impl Cache {
         archive_id: u32,
         writer: &mut W,
     ) -> crate::Result<()> {
+        if !self.read_transforms.0.is_empty() {
+            return Ok(writer.write_all(&self.read(index_id, archive_id)?)?);
+        }
+
+        let archive = self.archive_ref(index_id, archive_id)?;
+        self.data.read_into_writer(archive, writer)
+    }
+
+    /// Builds the update server (js5) response to a request for the archive,
+    /// see [`protocol::write_js5_response`].
+    ///
+    /// Containers are sent as stored, see [`read_raw`](Cache::read_raw), so they
+    /// match the crcs of their reference tables. Archives are sent without
+    /// their version trailer. Reference tables in index 255 have none and are
+    /// sent as is, except for archive 255 which
+    /// is the OSRS [`checksum`](Cache::checksum) table. Servers answering it
+    /// often should encode the checksum once and use
+    /// [`protocol::js5_response`] instead.
+    ///
+    /// # Errors
+    ///
+    /// See [`read`](Cache::read), archive ids above 65535 don't fit a response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let response = cache.build_js5_response(255, 10, false)?;
+    ///
+    /// // the header, then the container with the prefetch bit on its compression.
+    /// assert_eq!(response[..3], [255, 0, 10]);
+    /// assert_eq!(response[3], cache.read_raw(255, 10)?[0] | 0x80);
+    /// assert_eq!(response.len(), 3 + cache.read_raw(255, 10)?.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build_js5_response(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+        priority: bool,
+    ) -> crate::Result<Vec<u8>> {
+        let wire_id = u16::try_from(archive_id).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("archive id {} doesn't fit a js5 response", archive_id),
+            )
+        })?;
+
+        let container = if index_id == REFERENCE_TABLE_ID {
+            if archive_id == REFERENCE_TABLE_ID as u32 {
+                self.checksum()?.encode()?.finalize()
+            } else {
+                self.read_raw(index_id, archive_id)?
+            }
+        } else {
+            let mut container = self.read_raw(index_id, archive_id)?;
+            let body_len = codec::split_trailer(&container).0.len();
+            container.truncate(body_len);
+            container
+        };
+
+        Ok(protocol::js5_response(index_id, wire_id, priority, &container))
+    }
+
+    /// Writes the index as a standalone [bundle](crate::bundle), holding its
+    /// reference table and the raw container of every stored archive.
+    ///
+    /// Containers are exported as stored on disk, see [`read_raw`](Cache::read_raw),
+    /// so the bundle matches the crcs of its reference table byte for byte.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the index or its reference table doesn't exist, or when writing
+    /// to `W` fails.
+    pub fn export_index<W: Write>(&self, index_id: u8, writer: W) -> crate::Result<()> {
+        let reference_table = self.read_raw(REFERENCE_TABLE_ID, index_id as u32)?;
+
+        let mut archives = Vec::new();
+        for entry in &self.reference_table(index_id)?.archives {
+            if self.archive_ref(index_id, entry.id).is_ok() {
+                archives.push(bundle::BundledArchive {
+                    id: entry.id,
+                    container: self.read_raw(index_id, entry.id)?,
+                });
+            }
+        }
+
+        bundle::IndexBundle {
+            index_id,
+            reference_table,
+            archives,
+        }
+        .write_to(writer)
+    }
+
+    /// Whether an `.idx` file exists for the index, empty indices included.
+    #[inline]
+    pub fn contains_index(&self, index_id: u8) -> bool {
+        self.indices.get(&index_id).is_some()
+    }
+
+    /// Number of index ids in use, one past the highest index with an `.idx`
+    /// file or an idx255 entry. Gaps below it count as well.
+    ///
+    /// The reference table index isn't counted, neither is an idx255 entry for
+    /// index 255 itself which some tools write.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// let cache = Cache::new("./data/osrs_cache")?;
+    ///
+    /// assert_eq!(cache.index_count(), 21);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn index_count(&self) -> usize {
+        let reference_index = self.indices.get(&REFERENCE_TABLE_ID);
+        let highest_file = self
+            .indices
+            .ids()
+            .filter(|&index_id| index_id != REFERENCE_TABLE_ID)
+            .last();
+        let highest_entry = reference_index.and_then(|index| {
+            index
+                .archive_refs
+                .iter()
+                .map(|archive| archive.id)
+                .filter(|&index_id| index_id < REFERENCE_TABLE_ID as u32)
+                .last()
+        });
+
+        highest_file
+            .map(u32::from)
+            .max(highest_entry)
+            .map_or(0, |index_id| index_id as usize + 1)
+    }
+
+    /// Problems found while opening the cache that were worked around, empty
+    /// for intact caches.
+    ///
+    /// Reference tables stored in zeroed sectors, as left behind by tools that
+    /// delete an index without updating idx255, are reported here and their
+    /// index is treated as [`Empty`](IndexState::Empty). Any other corruption
+    /// of a reference table still fails [`Cache::new`].
+    #[inline]
+    pub fn open_warnings(&self) -> &[OpenWarning] {
+        self.indices.warnings()
+    }
+
+    /// Number of archives stored in the index, `Some(0)` for empty indices and
+    /// `None` for missing ones.
+    #[inline]
+    pub fn archive_count(&self, index_id: u8) -> Option<usize> {
+        match self.index_state(index_id) {
+            IndexState::Missing => None,
+            IndexState::Empty => Some(0),
+            IndexState::Populated { archives } => Some(archives),
+        }
+    }
+
+    /// Tells a missing index apart from an empty one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::{Cache, IndexState};
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// let cache = Cache::new("./data/osrs_cache")?;
+    ///
+    /// assert!(matches!(cache.index_state(2), IndexState::Populated { .. }));
+    /// assert_eq!(cache.index_state(100), IndexState::Missing);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn index_state(&self, index_id: u8) -> IndexState {
+        match self.indices.get(&index_id) {
+            None => IndexState::Missing,
+            Some(index) => {
+                let archives = index.archive_refs.len();
+                let usable = index.has_reference_table || index_id == REFERENCE_TABLE_ID;
+
+                if archives == 0 || !usable {
+                    IndexState::Empty
+                } else {
+                    IndexState::Populated { archives }
+                }
+            }
+        }
+    }
+
+    /// Retrieves the huffman table.
+    ///
+    /// Required when decompressing chat messages, see
+    /// [`Huffman`](crate::util::Huffman).
+    pub fn huffman_table(&self) -> crate::Result<Buffer<Decoded>> {
+        Ok(Buffer::from(self.named_group(10, group::HUFFMAN)?))
+    }
+
+    /// Reads and decodes the group with the given name.
+    ///
+    /// Well known names are listed in the [`group`] module.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NameHashMismatch`](error::NameHashMismatch) when the index has no
+    /// group with that name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// use rscache::group;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let title = cache.named_group(10, group::TITLE_IMAGE)?;
+    /// assert_eq!(&title[..2], &[0xFF, 0xD8]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn named_group<T: AsRef<str>>(&self, index_id: u8, name: T) -> crate::Result<Vec<u8>> {
+        let archive = self.archive_by_name(index_id, name)?;
+        let buffer = self.read_archive(archive)?;
+
+        Ok(codec::decode(&buffer)?.finalize())
+    }
+
+    /// Id of the archive with the given name, the [djd2 hash](util::djd2::hash)
+    /// of the name is looked up in the index's reference table.
+    ///
+    /// Every name hash of an index is listed by [`archives`](Cache::archives),
+    /// e.g. to brute force them with a [`NameHashTable`](util::NameHashTable).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NameHashMismatch`](error::NameHashMismatch) when the index has no
+    /// archive with that name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// use rscache::group;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let track = cache.archive_id_by_name(6, group::TITLE_MUSIC)?;
+    ///
+    /// assert_eq!(
+    ///     cache.read_decoded(6, track)?.as_slice(),
+    ///     cache.named_group(6, group::TITLE_MUSIC)?
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn archive_id_by_name<T: AsRef<str>>(&self, index_id: u8, name: T) -> crate::Result<u32> {
+        Ok(self.archive_by_name(index_id, name)?.id)
+    }
+
+    /// Reads and decodes a well known archive of the config index.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_decoded`](Cache::read_decoded).
+    pub fn read_config(&self, archive: ConfigArchive) -> crate::Result<Buffer<Decoded>> {
+        self.read_decoded(CONFIG_INDEX, archive.archive_id())
+    }
+
+    /// Flags of the given index's reference table, telling which archive metadata it carries.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the index doesn't exist or its reference table can't be read.
+    pub fn index_flags(&self, index_id: u8) -> crate::Result<ReferenceTableFlags> {
+        Ok(self.metadata_of(index_id)?.flags())
+    }
+
+    /// Reference table of the given index with its stored length and archive id range.
+    ///
+    /// The table is borrowed from the cache, reference tables are parsed once
+    /// when the cache is opened.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IndexNotFound` when the index doesn't exist and fails if its
+    /// reference table can't be read or parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let metadata = cache.metadata_of(2)?;
+    ///
+    /// assert_eq!(metadata.protocol(), 6);
+    /// assert!(metadata.archive_ids.unwrap().contains(&10));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn metadata_of(&self, index_id: u8) -> crate::Result<IndexMetadata<'_>> {
+        let table = Cow::Borrowed(self.reference_table(index_id)?);
+        let stored_len = self
+            .indices
+            .get(&index_id)
+            .map(|index| index.archive_refs.iter().map(|archive| archive.length as u64).sum())
+            .unwrap_or_default();
+        let archive_ids = match (table.archives.first(), table.archives.last()) {
+            (Some(first), Some(last)) => Some(first.id..=last.id),
+            _ => None,
+        };
+
+        Ok(IndexMetadata {
+            index_id,
+            table,
+            stored_len,
+            archive_ids,
+        })
+    }
+
+    /// Reference table entry of a single archive.
+    ///
+    /// The [`sizes`](ArchiveEntry::sizes) are only present when the index has
+    /// the [`SIZES`](ReferenceTableFlags::SIZES) flag.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArchiveNotFound` when the reference table has no entry for the archive.
+    pub fn archive_metadata(&self, index_id: u8, archive_id: u32) -> crate::Result<ArchiveEntry> {
+        self.metadata_of(index_id)?
+            .table
+            .archive(archive_id)
+            .cloned()
+            .ok_or_else(|| {
+                RuneFsError::Read(ReadError::ArchiveNotFound {
+                    idx: index_id,
+                    arc: archive_id,
+                })
+                .into()
+            })
+    }
+
+    /// Ids of the archives whose reference table version is above `version`, in ascending order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IndexNotFound` when the index doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let version = cache.archive_metadata(2, 10)?.version;
+    ///
+    /// assert!(!cache.archives_changed_since(2, version)?.contains(&10));
+    /// assert!(cache.archives_changed_since(2, version - 1)?.contains(&10));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn archives_changed_since(&self, index_id: u8, version: u32) -> crate::Result<Vec<u32>> {
+        let archives = self
+            .index_metadata(index_id)?
+            .archives
+            .iter()
+            .filter(|archive| archive.version > version)
+            .map(|archive| archive.id)
+            .collect();
+
+        Ok(archives)
+    }
+
+    /// Same as [`archives_changed_since`](Cache::archives_changed_since) for every
+    /// index, as `(index_id, archive_id)` pairs in ascending order.
+    pub fn all_archives_changed_since(&self, version: u32) -> Vec<(u8, u32)> {
+        self.indices
+            .ids()
+            .filter(|&index_id| index_id != REFERENCE_TABLE_ID)
+            .flat_map(|index_id| {
+                let archives = self.archives_changed_since(index_id, version);
+                archives
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |archive_id| (index_id, archive_id))
+            })
+            .collect()
+    }
+
+    /// Revision of the cache, the highest revision among the reference tables
+    /// of its indices.
+    ///
+    /// Every update bumps the revision of the tables it touches, so this
+    /// tells caches of different updates apart. It isn't the client build
+    /// number sent in the update server handshake.
+    ///
+    /// Returns `None` when no table stores a revision, which protocol 5 tables
+    /// of old caches don't. Tables that can't be read are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let revision = cache.revision().unwrap();
+    ///
+    /// assert!(cache.metadata_of(2)?.version() <= revision);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn revision(&self) -> Option<u32> {
+        self.indices
+            .ids()
+            .filter(|&index_id| index_id != REFERENCE_TABLE_ID)
+            .filter_map(|index_id| self.metadata_of(index_id).ok()?.revision())
+            .max()
+    }
+
+    /// Reads and decodes an archive.
+    ///
+    /// Archives with a decryption in the cache's
+    /// [registry](CacheBuilder::decryption) are deciphered first.
+    ///
+    /// When the cache was opened with [`check_sizes`](CacheBuilder::check_sizes)
+    /// and the index carries archive sizes, the decoded length is checked
+    /// against the reference table.
+    ///
+    /// # Errors
+    ///
+    /// See [`read`](Cache::read), additionally returns
+    /// [`ValidateError::InvalidSize`](error::ValidateError::InvalidSize) when a
+    /// checked read finds a mismatch.
+    pub fn read_decoded(&self, index_id: u8, archive_id: u32) -> crate::Result<Buffer<Decoded>> {
+        let decryption = self.registered_decryption(index_id, archive_id)?;
+
+        self.read_decoded_with(index_id, archive_id, decryption)
+    }
+
+    /// [`read_decoded`](Cache::read_decoded) with an explicit decryption,
+    /// overriding the registry. `None` reads the archive as plain.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_decoded`](Cache::read_decoded).
+    pub fn read_decoded_with(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+        decryption: Option<&Decryption>,
+    ) -> crate::Result<Buffer<Decoded>> {
+        let buffer = self.read(index_id, archive_id)?;
+        let buffer = match decryption {
+            Some(decryption) => decryption.decode(&buffer)?,
+            None => codec::decode(&buffer)?,
+        };
+
+        self.check_decoded_size(index_id, archive_id, buffer.len())?;
+
+        Ok(buffer)
+    }
+
+    /// Streams the decoded archive into `W`, returning its decoded length.
+    ///
+    /// Plain archives are decompressed sector by sector as they're read, without
+    /// holding the container or the decoded archive in memory. Enciphered archives
+    /// and caches with [read transforms](CacheBuilder::with_read_transform) read the
+    /// container first, then stream its decompression.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_decoded`](Cache::read_decoded), also fails when writing to `W`
+    /// fails. Whatever was written before the error stays written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let mut decoded = Vec::new();
+    /// let len = cache.read_decoded_into_writer(2, 10, &mut decoded)?;
+    ///
+    /// assert_eq!(len, decoded.len());
+    /// assert_eq!(decoded, cache.read_decoded(2, 10)?.finalize());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_decoded_into_writer<W: Write>(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+        writer: &mut W,
+    ) -> crate::Result<usize> {
+        if let Some(decryption) = self.registered_decryption(index_id, archive_id)? {
+            let buffer = decryption.decode(&self.read(index_id, archive_id)?)?;
+            self.check_decoded_size(index_id, archive_id, buffer.len())?;
+            writer.write_all(&buffer)?;
+
+            return Ok(buffer.len());
+        }
+
+        let len = if self.read_transforms.0.is_empty() {
+            let mut container = codec::ContainerWriter::new(&mut *writer);
+            self.data
+                .read_into_writer(self.archive_ref(index_id, archive_id)?, &mut container)?;
+            container.finish()?
+        } else {
+            codec::decode_into(&self.read(index_id, archive_id)?, writer)?
+        };
+        self.check_decoded_size(index_id, archive_id, len)?;
+
+        Ok(len)
+    }
+
+    /// Fails when size checks are on and the decoded length doesn't match the
+    /// reference table.
+    fn check_decoded_size(&self, index_id: u8, archive_id: u32, len: usize) -> crate::Result<()> {
+        if self.check_sizes {
+            let sizes = self.archive_metadata(index_id, archive_id)?.sizes;
+            if let Some(sizes) = sizes {
+                if sizes.decompressed as usize != len {
+                    return Err(ValidateError::InvalidSize {
+                        idx: index_id,
+                        arc: archive_id,
+                        expected: sizes.decompressed as usize,
+                        actual: len,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// [`read_decoded`](Cache::read_decoded), shared with earlier reads of the
+    /// archive when the cache keeps [decoded archives](CacheBuilder::decoded_cache_size).
+    ///
+    /// Without it every call reads and decodes the archive again, like `read_decoded`.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_decoded`](Cache::read_decoded), failed reads aren't kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// use rscache::Cache;
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// let cache = Cache::builder("./data/osrs_cache")
+    ///     .decoded_cache_size(16 * 1024 * 1024)
+    ///     .open()?;
+    ///
+    /// let items = cache.read_decoded_shared(2, 10)?;
+    /// assert!(Arc::ptr_eq(&items, &cache.read_decoded_shared(2, 10)?));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_decoded_shared(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+    ) -> crate::Result<Arc<Vec<u8>>> {
+        let Some(decoded) = &self.decoded else {
+            return Ok(Arc::new(
+                self.read_decoded(index_id, archive_id)?.finalize(),
+            ));
+        };
+
+        let key = (index_id, archive_id);
+        if let Some(buffer) = decoded
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+        {
+            return Ok(buffer);
+        }
+
+        // decode without holding the lock, concurrent misses of the same archive both decode it.
+        let buffer = Arc::new(self.read_decoded(index_id, archive_id)?.finalize());
+        decoded
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, Arc::clone(&buffer));
+
+        Ok(buffer)
+    }
+
+    /// Total length of the decoded archives kept for
+    /// [`read_decoded_shared`](Cache::read_decoded_shared).
+    pub fn decoded_cache_len(&self) -> usize {
+        self.decoded.as_ref().map_or(0, |decoded| {
+            decoded.lock().unwrap_or_else(PoisonError::into_inner).len()
+        })
+    }
+
+    /// The decryption registry the cache was opened with.
+    #[inline]
+    pub fn decryption(&self) -> &DecryptionRegistry {
+        &self.decryption
+    }
+
+    fn registered_decryption(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+    ) -> crate::Result<Option<&Decryption>> {
+        if self.decryption.is_empty() {
+            return Ok(None);
+        }
+
+        let name_hash = self
+            .index_metadata(index_id)?
+            .archive(archive_id)
+            .and_then(|archive| archive.name_hash);
+
+        Ok(self.decryption.get(index_id, archive_id, name_hash))
+    }
+
+    /// Reads and decodes an archive, returning a single file of it.
+    ///
+    /// File ids can be sparse, the reference table tells which file is where in the group.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_decoded`](Cache::read_decoded), additionally returns
+    /// [`ReadError::FileNotFound`](error::ReadError::FileNotFound) when the
+    /// archive has no such file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// use rscache::definition::osrs::{Definition, ItemDefinition};
+    ///
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let buffer = cache.read_file(2, 10, 1042)?;
+    /// let item = ItemDefinition::new(1042, &buffer)?;
+    ///
+    /// assert_eq!(item.name, "Blue partyhat");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_file(&self, index_id: u8, archive_id: u32, file_id: u32) -> crate::Result<Vec<u8>> {
+        let file_ids = self.archive_metadata(index_id, archive_id)?.file_ids;
+        let position = file_ids
+            .iter()
+            .position(|&id| id == file_id)
+            .ok_or(error::ReadError::FileNotFound {
+                idx: index_id,
+                arc: archive_id,
+                file: file_id,
+            })?;
+
+        let buffer = self.read_decoded(index_id, archive_id)?;
+        let mut files = util::split_group(&buffer, file_ids.len())?;
+
+        Ok(files.swap_remove(position))
+    }
+
+    /// Reads and decodes an archive, returning every file of it keyed by its id.
+    ///
+    /// Chunked groups are reassembled, an archive with a single file maps its
+    /// id to the whole decoded buffer.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_decoded`](Cache::read_decoded), additionally fails when the
+    /// group doesn't hold as many files as the reference table lists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let items = cache.read_files(2, 10)?;
+    ///
+    /// assert_eq!(items[&1042], cache.read_file(2, 10, 1042)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_files(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+    ) -> crate::Result<BTreeMap<u32, Vec<u8>>> {
+        let file_ids = self.archive_metadata(index_id, archive_id)?.file_ids;
+        let buffer = self.read_decoded(index_id, archive_id)?;
+        let files = util::split_group(&buffer, file_ids.len())?;
+
+        Ok(file_ids.into_iter().zip(files).collect())
+    }
+
+    /// Id of the file with the given name in an archive.
+    ///
+    /// Only reference tables with the [`NAMED`](ReferenceTableFlags::NAMED)
+    /// flag carry file names, as [djd2 hashes](util::djd2::hash) next to the
+    /// file ids. Sprite groups and some binary groups use them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ArchiveNotFound` when the reference table has no entry for the
+    /// archive and [`ReadError::FileNameNotFound`](error::ReadError::FileNameNotFound)
+    /// when none of its files has the name, which includes tables without names.
+    pub fn file_id_by_name<T: AsRef<str>>(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+        name: T,
+    ) -> crate::Result<u32> {
+        let hash = util::djd2::hash(name);
+        let archive = self.archive_metadata(index_id, archive_id)?;
+
+        archive
+            .file_name_hashes
+            .iter()
+            .flatten()
+            .position(|&file_hash| file_hash == hash)
+            .map(|position| archive.file_ids[position])
+            .ok_or_else(|| {
+                error::ReadError::FileNameNotFound {
+                    idx: index_id,
+                    arc: archive_id,
+                    hash,
+                }
+                .into()
+            })
+    }
+
+    /// [`read_file`](Cache::read_file) with the file looked up by name, see
+    /// [`file_id_by_name`](Cache::file_id_by_name).
+    ///
+    /// # Errors
+    ///
+    /// See [`file_id_by_name`](Cache::file_id_by_name) and [`read_file`](Cache::read_file).
+    pub fn read_file_by_name<T: AsRef<str>>(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+        name: T,
+    ) -> crate::Result<Vec<u8>> {
+        let file_id = self.file_id_by_name(index_id, archive_id, name)?;
+
+        self.read_file(index_id, archive_id, file_id)
+    }
+
+    /// The decoded archive as a [hex dump](util::hexdump::HexDump), showing at
+    /// most `limit` bytes.
+    ///
+    /// # Errors
+    ///
+    /// See [`read_decoded`](Cache::read_decoded).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// println!("{}", cache.hexdump(2, 10, 256)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn hexdump(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+        limit: usize,
+    ) -> crate::Result<util::hexdump::HexDump<'static>> {
+        let buffer = self.read_decoded(index_id, archive_id)?;
+
+        Ok(util::hexdump::HexDump::new(buffer.finalize()).max_len(limit))
+    }
+
+    /// Parsed reference table of the given index, these are parsed once when the cache is opened.
+    pub(crate) fn index_metadata(&self, index_id: u8) -> crate::Result<&ReferenceTable> {
+        let index = self
+            .indices
+            .get(&index_id)
+            .ok_or(RuneFsError::Read(ReadError::IndexNotFound(index_id)))?;
+
+        Ok(&index.metadata)
+    }
+
+    /// Parsed reference table of the given index, failing for indices without one.
+    pub(crate) fn reference_table(&self, index_id: u8) -> crate::Result<&ReferenceTable> {
+        let index = self
+            .indices
+            .get(&index_id)
+            .ok_or(RuneFsError::Read(ReadError::IndexNotFound(index_id)))?;
+        if !index.has_reference_table {
+            return Err(RuneFsError::Read(ReadError::ArchiveNotFound {
+                idx: REFERENCE_TABLE_ID,
+                arc: index_id as u32,
+            })
+            .into());
+        }
+
+        Ok(&index.metadata)
+    }
+
+    /// Location of an archive in `main_file_cache.dat2`.
+    ///
+    /// Archives without any data are treated as missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IndexNotFound` or `ArchiveNotFound` when the archive isn't stored.
+    pub fn archive_ref(&self, index_id: u8, archive_id: u32) -> crate::Result<&ArchiveRef> {
         let index = self
             .indices
             .get(&index_id)
@@ -245,27 +1824,95 @@ impl Cache {
 
         let archive = index
             .archive_refs
-            .get(&archive_id)
+            .get(archive_id)
             .ok_or(RuneFsError::Read(ReadError::ArchiveNotFound {
                 idx: index_id,
                 arc: archive_id,
             }))?;
-        Ok(self.data.read_into_writer(archive, writer)?)
+
+        Ok(archive)
     }
 
-    /// Retrieves the huffman table.
+    /// Every archive stored in the index, in ascending id order.
     ///
-    /// Required when decompressing chat messages, see
-    /// [`Huffman`](crate::util::Huffman).
-    pub fn huffman_table(&self) -> crate::Result<Buffer<Decoded>> {
-        let index_id = 10;
+    /// Archives without any data are skipped, like they are by
+    /// [`archive_ref`](Cache::archive_ref). Name hashes come from the index's
+    /// reference table.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IndexNotFound` when there is no `.idx` file for the index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let regions = cache
+    ///     .archives(5)?
+    ///     .filter(|archive| archive.name_hash.is_some())
+    ///     .count();
+    /// assert!(regions > 0);
+    ///
+    /// let first = cache.archives(2)?.next().unwrap();
+    /// assert_eq!(cache.archive_ref(2, first.id)?.length, first.length);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn archives(&self, index_id: u8) -> crate::Result<impl Iterator<Item = ArchiveInfo> + '_> {
+        let index = self
+            .indices
+            .get(&index_id)
+            .ok_or(RuneFsError::Read(ReadError::IndexNotFound(index_id)))?;
 
-        let archive = self.archive_by_name(index_id, "huffman")?;
-        let buffer = self.read_archive(archive)?;
+        Ok(index.archive_refs.iter().map(move |archive| ArchiveInfo {
+            id: archive.id,
+            name_hash: index
+                .metadata
+                .archive(archive.id)
+                .and_then(|entry| entry.name_hash),
+            sector: archive.sector as u32,
+            length: archive.length,
+        }))
+    }
 
-        assert_eq!(buffer.len(), archive.length);
+    /// Where the archive is stored in `main_file_cache.dat2`, including its full sector chain.
+    ///
+    /// The chain is validated like a [`read`](Cache::read), but no data is copied.
+    ///
+    /// # Errors
+    ///
+    /// See [`read`](Cache::read).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use rscache::Cache;
+    /// # fn main() -> Result<(), rscache::Error> {
+    /// # let cache = Cache::new("./data/osrs_cache")?;
+    /// let placement = cache.archive_placement(2, 10)?;
+    ///
+    /// assert_eq!(placement.sectors[0], placement.sector);
+    /// assert_eq!(placement.sectors.len(), placement.length.div_ceil(placement.data_len()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn archive_placement(
+        &self,
+        index_id: u8,
+        archive_id: u32,
+    ) -> crate::Result<ArchivePlacement> {
+        let archive = self.archive_ref(index_id, archive_id)?;
+        let sectors = self.data.sectors(archive)?;
 
-        Ok(buffer.decode()?)
+        Ok(ArchivePlacement {
+            index_id,
+            archive_id,
+            sector: archive.sector as u32,
+            length: archive.length,
+            sectors: sectors.into_iter().map(|sector| sector as u32).collect(),
+        })
     }
 
     pub(crate) fn archive_by_name<T: AsRef<str>>(
@@ -281,23 +1928,26 @@ impl Cache {
 
         let archive = index
             .metadata
+            .archives
             .iter()
-            .find(|archive| archive.name_hash == hash)
+            .find(|archive| archive.name_hash == Some(hash))
             .ok_or_else(|| crate::error::NameHashMismatch {
                 hash,
                 name: name.as_ref().into(),
                 idx: index_id,
             })?;
 
-        let archive_ref = index
-            .archive_refs
-            .get(&archive.id)
-            .ok_or(RuneFsError::Read(ReadError::ArchiveNotFound {
-                idx: index_id,
-                arc: archive.id,
-            }))?;
+        self.archive_ref(index_id, archive.id)
+    }
+}
 
-        Ok(archive_ref)
+/// The data file of a cache, only memory mapped when it isn't written to.
+fn open_data(path: &Path, writable: bool) -> crate::Result<Dat2> {
+    let path = path.join(MAIN_DATA);
+    if writable {
+        Dat2::unmapped(path)
+    } else {
+        Dat2::new(path)
     }
 }
 
@@ -307,3 +1957,49 @@ fn is_normal<T: Send + Sync + Sized + Unpin>() {}
 fn normal_types() {
     is_normal::<Cache>();
 }
+
+#[test]
+fn borrowed_reference_tables() -> crate::Result<()> {
+    let cache = Cache::new("./data/osrs_cache")?;
+
+    let metadata = cache.metadata_of(2)?;
+    assert!(matches!(metadata.table, Cow::Borrowed(_)));
+    assert!(std::ptr::eq(&*metadata.table, cache.index_metadata(2)?));
+    assert_eq!(
+        cache.archive_metadata(2, 10)?,
+        *metadata.table.archive(10).unwrap()
+    );
+    assert!(cache.reference_table(REFERENCE_TABLE_ID).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn reference_tables_parsed_once() -> crate::Result<()> {
+    let opened = reference_table::parses();
+    let cache = Cache::new("./data/osrs_cache")?;
+    let checksum = cache.checksum()?;
+    cache.metadata_of(2)?;
+    let parsed = reference_table::parses();
+    assert!(parsed > opened);
+
+    assert_eq!(cache.checksum()?, checksum);
+    cache.metadata_of(2)?;
+    cache.archive_metadata(2, 10)?;
+    assert_eq!(reference_table::parses(), parsed);
+
+    Ok(())
+}
+
+#[test]
+fn writable_caches_are_not_mapped() -> crate::Result<()> {
+    let mut cache = Cache::open_rw("./data/osrs_cache")?;
+    assert!(!cache.data.is_mapped());
+    cache.reload()?;
+    assert!(!cache.data.is_mapped());
+
+    let mapped = cfg!(all(feature = "mmap", not(feature = "safe-io")));
+    assert_eq!(Cache::new("./data/osrs_cache")?.data.is_mapped(), mapped);
+
+    Ok(())
+}
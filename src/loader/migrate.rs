@@ -0,0 +1,273 @@
+//! Maps definitions across two revisions of the cache.
+//!
+//! Jagex renumbers and removes definitions between revisions, content keyed by
+//! the old ids silently points at something else afterwards. [`match_definitions`]
+//! pairs the definitions of two loaders and reports what it couldn't pair.
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::loader::{migrate, osrs::ItemLoader};
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! let old = ItemLoader::new(&cache)?;
+//! let new = ItemLoader::new(&cache)?;
+//!
+//! let map = migrate::match_definitions(&old, &new);
+//!
+//! assert_eq!(map.new_id(1042), Some(1042));
+//! assert!(map.unmatched_old.is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::definition::osrs::{ItemDefinition, NpcDefinition, ObjectDefinition};
+
+/// A definition that can be paired with its counterpart in another revision.
+pub trait Matchable {
+    fn name(&self) -> &str;
+
+    /// Every model the definition uses, in a stable order.
+    fn model_ids(&self) -> Vec<u16>;
+
+    /// Whether every field except the id is equal.
+    fn fields_eq(&self, other: &Self) -> bool;
+}
+
+/// How a pair of definitions was matched, from most to least certain.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum MatchKind {
+    /// Every field except the id is equal.
+    Exact,
+    /// Same name and models, other fields changed.
+    NameAndModels,
+    /// Same models under another name, usually a rename.
+    Models,
+    /// Same name with other models, usually a remodel.
+    Name,
+}
+
+/// The new id of a definition and how it was found.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Migration {
+    pub new_id: u32,
+    pub kind: MatchKind,
+}
+
+/// Pairing of the definitions of two revisions, built by [`match_definitions`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct MigrationMap {
+    /// Matched definitions keyed by their old id.
+    pub pairs: BTreeMap<u32, Migration>,
+    /// Old ids without a counterpart, in ascending order.
+    pub unmatched_old: Vec<u32>,
+    /// New ids without a counterpart, in ascending order.
+    pub unmatched_new: Vec<u32>,
+}
+
+impl MigrationMap {
+    /// The new id of the definition with the given old id.
+    #[inline]
+    pub fn new_id(&self, old_id: u32) -> Option<u32> {
+        self.pairs.get(&old_id).map(|migration| migration.new_id)
+    }
+
+    /// Pairs whose id changed, as `(old_id, new_id)` in ascending old id order.
+    pub fn renumbered(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.pairs
+            .iter()
+            .filter(|(&old_id, migration)| old_id != migration.new_id)
+            .map(|(&old_id, migration)| (old_id, migration.new_id))
+    }
+}
+
+/// Pairs the definitions of two loaders.
+///
+/// Definitions are paired by exact field equality first, then by name and
+/// models, then by models or name alone. Every pass prefers a candidate with
+/// the same id. The first two passes settle ties on the lowest new id; the
+/// last two only pair a definition when it has a single candidate left, and
+/// unnamed (`"null"`) definitions are never paired by name.
+///
+/// Works for any loader iterating `(&u32, &D)`, like the item, npc and object loaders.
+pub fn match_definitions<'a, D, I>(old: I, new: I) -> MigrationMap
+where
+    D: Matchable + 'a,
+    I: IntoIterator<Item = (&'a u32, &'a D)>,
+{
+    let old: BTreeMap<u32, &D> = old.into_iter().map(|(&id, def)| (id, def)).collect();
+    let new: BTreeMap<u32, &D> = new.into_iter().map(|(&id, def)| (id, def)).collect();
+
+    let mut matcher = Matcher {
+        old: old.keys().copied().collect(),
+        new: new.keys().copied().collect(),
+        pairs: BTreeMap::new(),
+    };
+
+    matcher.pass(&old, &new, MatchKind::Exact, false, |def| {
+        Some((def.name(), def.model_ids()))
+    });
+    matcher.pass(&old, &new, MatchKind::NameAndModels, false, |def| {
+        Some((def.name(), def.model_ids()))
+    });
+    matcher.pass(&old, &new, MatchKind::Models, true, |def| {
+        let models = def.model_ids();
+        (!models.is_empty()).then_some(("", models))
+    });
+    matcher.pass(&old, &new, MatchKind::Name, true, |def| {
+        let name = def.name();
+        (!name.is_empty() && name != "null").then_some((name, Vec::new()))
+    });
+
+    MigrationMap {
+        pairs: matcher.pairs,
+        unmatched_old: matcher.old.into_iter().collect(),
+        unmatched_new: matcher.new.into_iter().collect(),
+    }
+}
+
+/// Ids that are still unmatched on either side, and the pairs found so far.
+struct Matcher {
+    old: BTreeSet<u32>,
+    new: BTreeSet<u32>,
+    pairs: BTreeMap<u32, Migration>,
+}
+
+impl Matcher {
+    fn pass<'a, D, K>(
+        &mut self,
+        old: &BTreeMap<u32, &'a D>,
+        new: &BTreeMap<u32, &'a D>,
+        kind: MatchKind,
+        unique: bool,
+        key: K,
+    ) where
+        D: Matchable,
+        K: Fn(&'a D) -> Option<(&'a str, Vec<u16>)>,
+    {
+        let mut candidates: HashMap<(&str, Vec<u16>), Vec<u32>> = HashMap::new();
+        for &id in &self.new {
+            if let Some(key) = key(new[&id]) {
+                candidates.entry(key).or_default().push(id);
+            }
+        }
+
+        let old_ids: Vec<u32> = self.old.iter().copied().collect();
+        for old_id in old_ids {
+            let old_def = old[&old_id];
+            let old_key = match key(old_def) {
+                Some(key) => key,
+                None => continue,
+            };
+            let matches = |id: &u32| {
+                self.new.contains(id) && (kind != MatchKind::Exact || old_def.fields_eq(new[id]))
+            };
+
+            let same_id =
+                self.new.contains(&old_id) && key(new[&old_id]).as_ref() == Some(&old_key);
+            let new_id = if same_id && matches(&old_id) {
+                old_id
+            } else {
+                let bucket = match candidates.get(&old_key) {
+                    Some(bucket) => bucket,
+                    None => continue,
+                };
+                let mut remaining = bucket.iter().copied().filter(matches);
+
+                match (remaining.next(), remaining.next()) {
+                    (Some(new_id), None) => new_id,
+                    (Some(new_id), Some(_)) if !unique => new_id,
+                    _ => continue,
+                }
+            };
+
+            self.old.remove(&old_id);
+            self.new.remove(&new_id);
+            self.pairs.insert(old_id, Migration { new_id, kind });
+        }
+    }
+}
+
+impl Matchable for ItemDefinition {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn model_ids(&self) -> Vec<u16> {
+        let character = &self.character_model_data;
+
+        std::iter::once(Some(self.inventory_model_data.inventory_model))
+            .chain([
+                character.male_model10,
+                character.male_model1,
+                character.male_model12,
+                character.female_model10,
+                character.female_model1,
+                character.female_model12,
+            ])
+            .flatten()
+            .collect()
+    }
+
+    fn fields_eq(&self, other: &Self) -> bool {
+        let other = Self {
+            id: self.id,
+            ..other.clone()
+        };
+
+        *self == other
+    }
+}
+
+impl Matchable for NpcDefinition {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    fn model_ids(&self) -> Vec<u16> {
+        self.model_data.models.clone()
+    }
+
+    fn fields_eq(&self, other: &Self) -> bool {
+        let other = Self {
+            id: self.id,
+            ..other.clone()
+        };
+
+        *self == other
+    }
+}
+
+impl Matchable for ObjectDefinition {
+    #[inline]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    fn model_ids(&self) -> Vec<u16> {
+        self.model_data.models.clone()
+    }
+
+    fn fields_eq(&self, other: &Self) -> bool {
+        let other = Self {
+            id: self.id,
+            ..other.clone()
+        };
+
+        *self == other
+    }
+}
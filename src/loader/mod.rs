@@ -1,12 +1,15 @@
 //! Loaders for definitions.
 //!
+//! Iterating a loader always yields its definitions in ascending id order, so
+//! output built from it is stable between runs.
+//!
 //! # Custom loaders
 //!
 //! If you need a certain loader and this crate doesn't provide it you can use the below
 //! example to help you make your own loader if you desperately need it.
 //!
 //! ```
-//! use std::{collections::HashMap, io::{ self, BufReader, }};
+//! use std::{collections::BTreeMap, io::{ self, BufReader, }};
 //! use rscache::{
 //!     Cache, extension::ReadExt,
 //!     definition::osrs::{ Definition, FetchDefinition },
@@ -25,7 +28,7 @@
 //! }
 //!
 //! // Newtype defining the loader.
-//! struct CustomLoader(HashMap<u16, CustomDefinition>);
+//! struct CustomLoader(BTreeMap<u32, CustomDefinition>);
 //! 
 //! impl CustomLoader {
 //!     fn new(cache: &Cache) -> Result<Self, rscache::Error> {
@@ -41,8 +44,8 @@
 //!         Ok(Self(map))
 //!     }
 //!
-//!     // Simple HashMap lookup.
-//!     fn load(&self, id: u16) -> Option<&CustomDefinition> {
+//!     // Simple map lookup.
+//!     fn load(&self, id: u32) -> Option<&CustomDefinition> {
 //!         self.0.get(&id)
 //!     }
 //! }
@@ -50,20 +53,28 @@
 //! // Your definition with all the required fields. (in this example it's just a ItemDefinition)
 //! #[derive(Default)]
 //! struct CustomDefinition {
-//!     pub id: u16,
+//!     pub id: u32,
 //!     pub name: String,
 //! }
 //!
 //! impl Definition for CustomDefinition {
-//!     fn new(id: u16, buffer: &[u8]) -> Result<Self, rscache::Error> {
+//!     fn new(id: u32, buffer: &[u8]) -> Result<Self, rscache::Error> {
 //!         let mut reader = BufReader::new(buffer);
 //!         let def = decode_buffer(id, &mut reader)?;
 //!
 //!         Ok(def)
 //!     }
+//!
+//!     fn id(&self) -> u32 {
+//!         self.id
+//!     }
+//!
+//!     fn name(&self) -> Option<&str> {
+//!         Some(&self.name)
+//!     }
 //! }
 //! 
-//! fn decode_buffer(id: u16, reader: &mut BufReader<&[u8]>) -> io::Result<CustomDefinition> {
+//! fn decode_buffer(id: u32, reader: &mut BufReader<&[u8]>) -> io::Result<CustomDefinition> {
 //!     // Parse the buffer into a definition.
 //!     let mut def = CustomDefinition {
 //!         id,
@@ -90,9 +101,27 @@
 //! }
 //! ```
 
+pub mod migrate;
 /// OSRS loaders.
 pub mod osrs;
 /// RS3 loaders.
 #[cfg(feature = "rs3")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rs3")))]
 pub mod rs3;
+
+use crate::Cache;
+
+/// A loader whose definitions can be read one at a time, without building the loader.
+///
+/// Implemented by the config loaders, see [`Timeline::definition_history`](crate::timeline::Timeline::definition_history).
+pub trait DefinitionLoader {
+    type Definition;
+
+    /// Index and archive holding the definition.
+    fn archive_of(id: u32) -> (u8, u32);
+
+    /// The undecoded payload of the definition, like the loader's inherent `raw`.
+    fn raw(cache: &Cache, id: u32) -> crate::Result<Vec<u8>>;
+
+    fn parse(id: u32, buffer: &[u8]) -> crate::Result<Self::Definition>;
+}
@@ -1,39 +1,312 @@
-use std::collections::{
-    hash_map::{self, Entry},
-    HashMap,
-};
+use std::collections::{btree_map, hash_map::Entry, BTreeMap, HashMap};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    decryption::Decryption,
+    definition::config_archives::{ConfigArchive, CONFIG_INDEX},
     definition::osrs::{
-        Definition, FetchDefinition, ItemDefinition, LocationDefinition, MapDefinition,
-        NpcDefinition, ObjectDefinition,
+        AreaDefinition, ComponentDefinition, DbRowDefinition, DbTableDefinition, Definition,
+        EnumDefinition, FetchDefinition, HealthBarDefinition, HitSplatDefinition,
+        IdentityKitDefinition, InventoryDefinition, ItemDefinition, Location, LocationDefinition,
+        LocationSpawn, MapDefinition, ModelDefinition, NpcDefinition, ObjectDefinition,
+        OverlayDefinition, ParamDefinition, Position, SequenceDefinition, SpotAnimDefinition,
+        SpriteDefinition, StructDefinition, UnderlayDefinition, VarbitDefinition,
+        WorldMapDefinition,
     },
+    group,
+    util::{self, instance::Region},
     Cache,
 };
 
 /// Loads all item definitions from the current cache.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
-pub struct ItemLoader(HashMap<u16, ItemDefinition>);
+pub struct ItemLoader(
+    BTreeMap<u32, ItemDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
 
-impl_osrs_loader!(ItemLoader, ItemDefinition, index_id: 2, archive_id: 10);
+impl_osrs_loader!(
+    ItemLoader,
+    ItemDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Item.archive_id()
+);
 
 /// Loads all npc definitions from the current cache.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
-pub struct NpcLoader(HashMap<u16, NpcDefinition>);
+pub struct NpcLoader(
+    BTreeMap<u32, NpcDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
 
-impl_osrs_loader!(NpcLoader, NpcDefinition, index_id: 2, archive_id: 9);
+impl_osrs_loader!(
+    NpcLoader,
+    NpcDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Npc.archive_id()
+);
 
 /// Loads all object definitions from the current cache.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ObjectLoader(
+    BTreeMap<u32, ObjectDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    ObjectLoader,
+    ObjectDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Object.archive_id()
+);
+
+/// Loads all enum definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct EnumLoader(
+    BTreeMap<u32, EnumDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    EnumLoader,
+    EnumDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Enum.archive_id()
+);
+
+/// Loads all param definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ParamLoader(
+    BTreeMap<u32, ParamDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    ParamLoader,
+    ParamDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Param.archive_id()
+);
+
+/// Loads all struct definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct StructLoader(
+    BTreeMap<u32, StructDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    StructLoader,
+    StructDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Struct.archive_id()
+);
+
+/// Loads all varbit definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct VarbitLoader(
+    BTreeMap<u32, VarbitDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    VarbitLoader,
+    VarbitDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::VarBit.archive_id()
+);
+
+/// Loads all underlay definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct UnderlayLoader(
+    BTreeMap<u32, UnderlayDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    UnderlayLoader,
+    UnderlayDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Underlay.archive_id()
+);
+
+/// Loads all overlay definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct OverlayLoader(
+    BTreeMap<u32, OverlayDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    OverlayLoader,
+    OverlayDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Overlay.archive_id()
+);
+
+/// Loads all area definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct AreaLoader(
+    BTreeMap<u32, AreaDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    AreaLoader,
+    AreaDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Area.archive_id()
+);
+
+/// Loads all sequence definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct SequenceLoader(
+    BTreeMap<u32, SequenceDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    SequenceLoader,
+    SequenceDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Sequence.archive_id()
+);
+
+/// Loads all spot animation definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct SpotAnimLoader(
+    BTreeMap<u32, SpotAnimDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    SpotAnimLoader,
+    SpotAnimDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::SpotAnimation.archive_id()
+);
+
+/// Loads all inventory definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct InventoryLoader(
+    BTreeMap<u32, InventoryDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    InventoryLoader,
+    InventoryDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::Inventory.archive_id()
+);
+
+/// Loads all identity kit definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
-pub struct ObjectLoader(HashMap<u16, ObjectDefinition>);
+pub struct IdentityKitLoader(
+    BTreeMap<u32, IdentityKitDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
 
-impl_osrs_loader!(ObjectLoader, ObjectDefinition, index_id: 2, archive_id: 6);
+impl_osrs_loader!(
+    IdentityKitLoader,
+    IdentityKitDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::IdentityKit.archive_id()
+);
+
+/// Loads all hit splat definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct HitSplatLoader(
+    BTreeMap<u32, HitSplatDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    HitSplatLoader,
+    HitSplatDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::HitSplat.archive_id()
+);
+
+/// Loads all health bar definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct HealthBarLoader(
+    BTreeMap<u32, HealthBarDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    HealthBarLoader,
+    HealthBarDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::HealthBar.archive_id()
+);
+
+/// Loads all database table definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct DbTableLoader(
+    BTreeMap<u32, DbTableDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    DbTableLoader,
+    DbTableDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::DbTable.archive_id()
+);
+
+/// Loads all database row definitions from the current cache.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct DbRowLoader(
+    BTreeMap<u32, DbRowDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_osrs_loader!(
+    DbRowLoader,
+    DbRowDefinition,
+    index_id: CONFIG_INDEX,
+    archive_id: ConfigArchive::DbRow.archive_id()
+);
 
 /// Loads maps definitions lazily from the current cache.
 #[derive(Debug)]
@@ -55,15 +328,59 @@ impl<'cache> MapLoader<'cache> {
         }
     }
 
+    /// Loads the map of a region, deciphered with keys registered in the
+    /// cache's [`DecryptionRegistry`](crate::decryption::DecryptionRegistry) if any.
     pub fn load(&mut self, id: u16) -> crate::Result<&MapDefinition> {
+        self.load_with(id, None)
+    }
+
+    /// Loads the map of the region holding the world tile at `x` and `y`.
+    pub fn load_at(&mut self, x: u16, y: u16) -> crate::Result<&MapDefinition> {
+        self.load(Position::new(x, y, 0).region_id())
+    }
+
+    /// Loads the map of a region enciphered with XTEA, these keys take
+    /// precedence over keys registered on the cache.
+    ///
+    /// Maps are cached by region, a map that was loaded before is returned
+    /// without using the keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReadError::InvalidKeys`](crate::error::ReadError::InvalidKeys)
+    /// when the map doesn't decode with the keys.
+    pub fn load_with_keys(&mut self, id: u16, keys: &[u32; 4]) -> crate::Result<&MapDefinition> {
+        self.load_with(id, Some(&Decryption::Xtea(*keys)))
+    }
+
+    /// Reads the undecoded map payload of the region, without caching it.
+    pub fn raw(&self, id: u16) -> crate::Result<Vec<u8>> {
+        let (x, y) = (id >> 8, id & 0xFF);
+
+        let archive_id = self.cache.archive_by_name(5, format!("m{}_{}", x, y))?.id;
+
+        Ok(self.cache.read_decoded(5, archive_id)?.finalize())
+    }
+
+    fn load_with(
+        &mut self,
+        id: u16,
+        decryption: Option<&Decryption>,
+    ) -> crate::Result<&MapDefinition> {
         if let Entry::Vacant(entry) = self.maps.entry(id) {
             let x = id >> 8;
             let y = id & 0xFF;
 
-            let map_archive = self.cache.archive_by_name(5, format!("m{}_{}", x, y))?;
-            let buffer = self.cache.read_archive(map_archive)?.decode()?;
+            let archive_id = self.cache.archive_by_name(5, format!("m{}_{}", x, y))?.id;
+            let buffer = match decryption {
+                Some(decryption) => {
+                    self.cache
+                        .read_decoded_with(5, archive_id, Some(decryption))?
+                }
+                None => self.cache.read_decoded(5, archive_id)?,
+            };
 
-            entry.insert(MapDefinition::new(id, &buffer)?);
+            entry.insert(MapDefinition::new(id.into(), &buffer)?);
         }
 
         Ok(&self.maps[&id])
@@ -93,23 +410,571 @@ impl<'cache> LocationLoader<'cache> {
     /// Loads the location data for a particular region.
     ///
     /// Also takes a `keys: [u32; 4]` because the location archive is encrypted
-    /// with XTEA. The buffer is automatically decoded with the given keys.
+    /// with XTEA. The buffer is automatically decoded with the given keys, these
+    /// take precedence over keys registered on the cache.
     pub fn load(&mut self, id: u16, keys: &[u32; 4]) -> crate::Result<&LocationDefinition> {
+        self.load_with(id, Some(&Decryption::Xtea(*keys)))
+    }
+
+    /// Loads the location data for a particular region, deciphered with the keys
+    /// in the cache's [`DecryptionRegistry`](crate::decryption::DecryptionRegistry).
+    ///
+    /// Regions without registered keys are read as plain.
+    pub fn load_registered(&mut self, id: u16) -> crate::Result<&LocationDefinition> {
+        self.load_with(id, None)
+    }
+
+    /// Locations of a region at their absolute tiles, see [`load`](Self::load) for the keys.
+    ///
+    /// The region's map is read along to place locations on bridges one plane
+    /// lower, like the client renders them, see [`LocationDefinition::spawns_on`].
+    pub fn spawns(&mut self, id: u16, keys: &[u32; 4]) -> crate::Result<Vec<LocationSpawn>> {
+        let (x, y) = (id >> 8, id & 0xFF);
+        let map_id = self.cache.archive_by_name(5, format!("m{}_{}", x, y))?.id;
+        let map = MapDefinition::new(id.into(), &self.cache.read_decoded(5, map_id)?)?;
+
+        Ok(self.load(id, keys)?.spawns_on(&map).collect())
+    }
+
+    /// Reads the deciphered but undecoded location payload of the region, without caching it.
+    pub fn raw(&self, id: u16, keys: &[u32; 4]) -> crate::Result<Vec<u8>> {
+        let (x, y) = (id >> 8, id & 0xFF);
+
+        let archive_id = self.cache.archive_by_name(5, format!("l{}_{}", x, y))?.id;
+        let buffer = self
+            .cache
+            .read_decoded_with(5, archive_id, Some(&Decryption::Xtea(*keys)))?;
+
+        Ok(buffer.finalize())
+    }
+
+    fn load_with(
+        &mut self,
+        id: u16,
+        decryption: Option<&Decryption>,
+    ) -> crate::Result<&LocationDefinition> {
         if let Entry::Vacant(entry) = self.locations.entry(id) {
             let x = id >> 8;
             let y = id & 0xFF;
 
-            let loc_archive = self.cache.archive_by_name(5, format!("l{}_{}", x, y))?;
-            let buffer = self
-                .cache
-                .read_archive(loc_archive)?
-                .with_xtea_keys(*keys)
-                .decode()?;
+            let archive_id = self.cache.archive_by_name(5, format!("l{}_{}", x, y))?.id;
+            let buffer = match decryption {
+                Some(decryption) => {
+                    self.cache
+                        .read_decoded_with(5, archive_id, Some(decryption))?
+                }
+                None => self.cache.read_decoded(5, archive_id)?,
+            };
 
-            entry.insert(LocationDefinition::new(id, &buffer)?);
+            entry.insert(LocationDefinition::new(id.into(), &buffer)?);
         }
 
         Ok(&self.locations[&id])
     }
 }
-    
\ No newline at end of file
+
+/// Loads interfaces lazily from the current cache.
+///
+/// Every archive in the interface index is one interface and every file in it is one
+/// of its components, keyed by its file id.
+#[derive(Debug)]
+pub struct InterfaceLoader<'cache> {
+    cache: &'cache Cache,
+    file_ids: BTreeMap<u16, Vec<u32>>,
+    interfaces: HashMap<u16, Vec<ComponentDefinition>>,
+}
+
+impl<'cache> InterfaceLoader<'cache> {
+    /// Make a new `InterfaceLoader`.
+    ///
+    /// The components are loaded lazily and cached internally on load.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache has no interface index.
+    pub fn new(cache: &'cache Cache) -> crate::Result<Self> {
+        let file_ids = cache
+            .index_metadata(3)?
+            .archives
+            .iter()
+            .map(|archive| (archive.id as u16, archive.file_ids.clone()))
+            .collect();
+
+        Ok(Self {
+            cache,
+            file_ids,
+            interfaces: HashMap::new(),
+        })
+    }
+
+    /// Loads all components of the given interface, ordered by component id.
+    ///
+    /// Returns `None` if the interface doesn't exist.
+    pub fn load(&mut self, id: u16) -> crate::Result<Option<&[ComponentDefinition]>> {
+        let file_ids = match self.file_ids.get(&id) {
+            Some(file_ids) => file_ids,
+            None => return Ok(None),
+        };
+
+        if let Entry::Vacant(entry) = self.interfaces.entry(id) {
+            let buffer = self.cache.read_decoded_shared(3, id as u32)?;
+
+            let files = util::split_group(&buffer, file_ids.len())?;
+            let mut components = Vec::with_capacity(files.len());
+            for (&file_id, data) in file_ids.iter().zip(&files) {
+                let component_id = (id as u32) << 16 | file_id;
+                components.push(ComponentDefinition::new(component_id, data)?);
+            }
+
+            entry.insert(components);
+        }
+
+        Ok(self.interfaces.get(&id).map(Vec::as_slice))
+    }
+
+    /// Loads a single component by its packed `interface_id << 16 | component_id`.
+    pub fn component(&mut self, id: u32) -> crate::Result<Option<&ComponentDefinition>> {
+        let components = self.load((id >> 16) as u16)?;
+
+        Ok(components.and_then(|components| {
+            components
+                .binary_search_by_key(&id, |component| component.id)
+                .ok()
+                .map(|index| &components[index])
+        }))
+    }
+
+    /// Reads the undecoded payload of a single component by its packed
+    /// `interface_id << 16 | component_id`, without caching it.
+    ///
+    /// Returns `None` if the component doesn't exist.
+    pub fn raw(&self, id: u32) -> crate::Result<Option<Vec<u8>>> {
+        let interface_id = id >> 16;
+        let exists = self
+            .file_ids
+            .get(&(interface_id as u16))
+            .is_some_and(|file_ids| file_ids.contains(&(id & 0xFFFF)));
+        if !exists {
+            return Ok(None);
+        }
+
+        self.cache.read_file(3, interface_id, id & 0xFFFF).map(Some)
+    }
+
+    /// Ids of every interface in the cache, in ascending order.
+    pub fn ids(&self) -> impl Iterator<Item = u16> + '_ {
+        self.file_ids.keys().copied()
+    }
+}
+
+/// Index of the models.
+const MODEL_INDEX: u8 = 7;
+
+/// Loads models lazily from the current cache.
+///
+/// Models are numerous and large, they're only decoded on load.
+#[derive(Debug)]
+pub struct ModelLoader<'cache> {
+    cache: &'cache Cache,
+    models: HashMap<u32, ModelDefinition>,
+}
+
+impl<'cache> ModelLoader<'cache> {
+    /// Make a new `ModelLoader`.
+    ///
+    /// This takes a `Cache` by references with a `'cache` lifetime.
+    /// All the models are loaded lazily where the `&'cache Cache` is used
+    /// to cache them internally on load.
+    pub fn new(cache: &'cache Cache) -> Self {
+        Self {
+            cache,
+            models: HashMap::new(),
+        }
+    }
+
+    /// Loads the model with this id.
+    pub fn load(&mut self, id: u32) -> crate::Result<&ModelDefinition> {
+        if let Entry::Vacant(entry) = self.models.entry(id) {
+            let buffer = self.cache.read_decoded(MODEL_INDEX, id)?;
+
+            entry.insert(ModelDefinition::new(id, &buffer)?);
+        }
+
+        Ok(&self.models[&id])
+    }
+
+    /// Reads the undecoded model, without caching it.
+    pub fn raw(&self, id: u32) -> crate::Result<Vec<u8>> {
+        Ok(self.cache.read_decoded(MODEL_INDEX, id)?.finalize())
+    }
+}
+
+/// Index of the sprite groups.
+const SPRITE_INDEX: u8 = 8;
+
+/// Loads sprites lazily from the current cache.
+///
+/// Every archive in the sprite index is one group of frames, e.g. the map icon
+/// sheet, most of them hold a single frame.
+#[derive(Debug)]
+pub struct SpriteLoader<'cache> {
+    cache: &'cache Cache,
+    sprites: HashMap<u32, Vec<SpriteDefinition>>,
+}
+
+impl<'cache> SpriteLoader<'cache> {
+    /// Make a new `SpriteLoader`.
+    ///
+    /// This takes a `Cache` by references with a `'cache` lifetime.
+    /// All the sprites are loaded lazily where the `&'cache Cache` is used
+    /// to cache them internally on load.
+    pub fn new(cache: &'cache Cache) -> Self {
+        Self {
+            cache,
+            sprites: HashMap::new(),
+        }
+    }
+
+    /// Loads every frame of a sprite group, in frame order.
+    pub fn load(&mut self, id: u32) -> crate::Result<&[SpriteDefinition]> {
+        if let Entry::Vacant(entry) = self.sprites.entry(id) {
+            let buffer = self.cache.read_decoded(SPRITE_INDEX, id)?;
+
+            entry.insert(SpriteDefinition::decode(id, &buffer)?);
+        }
+
+        Ok(&self.sprites[&id])
+    }
+
+    /// Loads a single frame of a sprite group, `None` if the group doesn't have it.
+    pub fn frame(&mut self, id: u32, frame: usize) -> crate::Result<Option<&SpriteDefinition>> {
+        Ok(self.load(id)?.get(frame))
+    }
+
+    /// Reads the undecoded sprite group, without caching it.
+    pub fn raw(&self, id: u32) -> crate::Result<Vec<u8>> {
+        Ok(self.cache.read_decoded(SPRITE_INDEX, id)?.finalize())
+    }
+}
+
+/// Index of the world maps.
+const WORLD_MAP_INDEX: u8 = 19;
+
+/// Loads every world map from the current cache, with its squares and icons.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct WorldMapLoader(BTreeMap<u32, WorldMapDefinition>);
+
+impl WorldMapLoader {
+    /// Parses the details and composite map groups of the world map index.
+    ///
+    /// # Errors
+    ///
+    /// Fails when either group is missing or doesn't parse.
+    pub fn new(cache: &Cache) -> crate::Result<Self> {
+        let details_id = cache
+            .archive_by_name(WORLD_MAP_INDEX, group::WORLD_MAP_DETAILS)?
+            .id;
+        let composite_id = cache
+            .archive_by_name(WORLD_MAP_INDEX, group::WORLD_MAP_COMPOSITE)?
+            .id;
+        let composites = cache.read_files(WORLD_MAP_INDEX, composite_id)?;
+
+        let mut world_maps = BTreeMap::new();
+        for (id, buffer) in cache.read_files(WORLD_MAP_INDEX, details_id)? {
+            let mut world_map = WorldMapDefinition::new(id, &buffer)?;
+            if let Some(composite) = composites.get(&id) {
+                world_map.read_composite(composite)?;
+            }
+            world_maps.insert(id, world_map);
+        }
+
+        Ok(Self(world_maps))
+    }
+
+    #[inline]
+    pub fn load(&self, id: u32) -> Option<&WorldMapDefinition> {
+        self.0.get(&id)
+    }
+
+    /// The map with the given internal name, e.g. `"main"` for the surface.
+    pub fn load_by_name(&self, internal_name: &str) -> Option<&WorldMapDefinition> {
+        self.0
+            .values()
+            .find(|world_map| world_map.internal_name == internal_name)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> btree_map::Iter<'_, u32, WorldMapDefinition> {
+        self.0.iter()
+    }
+}
+
+/// Regions a lazily built [`WorldObjects`] keeps loaded unless configured otherwise.
+pub const DEFAULT_WORLD_REGIONS: usize = 64;
+
+/// A location resolved against its object definition.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PlacedObject {
+    pub location: Location,
+    /// `None` when the object loader doesn't know the object.
+    pub definition: Option<ObjectDefinition>,
+    pub footprint: Footprint,
+}
+
+/// Tiles covered by a [`PlacedObject`], sizes swapped for odd orientations.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Footprint {
+    /// South west tile of the object.
+    pub position: Position,
+    pub size_x: u8,
+    pub size_y: u8,
+}
+
+impl Footprint {
+    /// Whether the tile lies within the footprint, on the same plane.
+    pub const fn contains(&self, position: Position) -> bool {
+        let (x, y) = (self.position.x as u32, self.position.y as u32);
+
+        position.plane == self.position.plane
+            && position.x as u32 >= x
+            && (position.x as u32) < x + self.size_x as u32
+            && position.y as u32 >= y
+            && (position.y as u32) < y + self.size_y as u32
+    }
+}
+
+#[derive(Debug, Default)]
+struct RegionObjects {
+    tiles: HashMap<u32, Vec<PlacedObject>>,
+    /// Tick of the last lookup, `None` for regions that can't be loaded again.
+    last_used: Option<u64>,
+}
+
+/// Objects of the world by position, across regions.
+///
+/// Locations are indexed by their south west tile, the tile the cache stores
+/// them at, use the [footprint](PlacedObject::footprint) for the tiles they cover.
+///
+/// Built lazily from a cache, regions are loaded on first lookup with the keys
+/// registered in the cache's [`DecryptionRegistry`](crate::decryption::DecryptionRegistry)
+/// and the least recently used one is dropped once more than the
+/// [capacity](WorldObjects::with_capacity) are loaded. Regions that were
+/// [inserted](WorldObjects::insert) are kept until dropped by hand.
+///
+/// # Example
+///
+/// ```
+/// # use rscache::Cache;
+/// use rscache::{
+///     decryption::DecryptionRegistry,
+///     definition::osrs::Position,
+///     loader::osrs::{ObjectLoader, WorldObjects},
+/// };
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// let mut registry = DecryptionRegistry::new();
+/// registry.insert_region(12850, [3030157619, 2364842415, 3297319647, 1973582566]);
+/// let cache = Cache::builder("./data/osrs_cache").decryption(registry).open()?;
+/// let objects = ObjectLoader::new(&cache)?;
+///
+/// let mut world = WorldObjects::new(&cache, &objects);
+/// for object in world.at(Position::new(3200, 3200, 0))? {
+///     println!("{:?}", object.definition.as_ref().map(|object| &object.name));
+/// }
+/// assert_eq!(world.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct WorldObjects<'a> {
+    cache: Option<&'a Cache>,
+    objects: &'a ObjectLoader,
+    regions: HashMap<u16, RegionObjects>,
+    capacity: usize,
+    tick: u64,
+}
+
+impl<'a> WorldObjects<'a> {
+    /// Objects loaded lazily from the cache, resolved with `objects`.
+    pub fn new(cache: &'a Cache, objects: &'a ObjectLoader) -> Self {
+        Self {
+            cache: Some(cache),
+            objects,
+            regions: HashMap::new(),
+            capacity: DEFAULT_WORLD_REGIONS,
+            tick: 0,
+        }
+    }
+
+    /// Objects of already loaded regions only, resolved with `objects`.
+    pub fn from_regions<'r, I>(objects: &'a ObjectLoader, regions: I) -> Self
+    where
+        I: IntoIterator<Item = &'r Region>,
+    {
+        let mut world = Self {
+            cache: None,
+            objects,
+            regions: HashMap::new(),
+            capacity: DEFAULT_WORLD_REGIONS,
+            tick: 0,
+        };
+        for region in regions {
+            world.insert(&region.locations);
+        }
+
+        world
+    }
+
+    /// Regions kept loaded from the cache, at least 1.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Indexes the locations of a region, replacing the region if it was loaded.
+    ///
+    /// Inserted regions aren't dropped to make room for others.
+    pub fn insert(&mut self, locations: &LocationDefinition) {
+        let mut region = self.index(locations);
+        region.last_used = None;
+
+        self.regions.insert(locations.id, region);
+    }
+
+    /// Drops a region, returns whether it was loaded.
+    pub fn remove(&mut self, region_id: u16) -> bool {
+        self.regions.remove(&region_id).is_some()
+    }
+
+    /// Objects whose south west tile is the given one, loading its region if needed.
+    ///
+    /// Regions without locations in the cache are empty, so are regions that
+    /// aren't loaded when the objects weren't built from a cache.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the region's locations can't be read or parsed, e.g. without
+    /// the right keys.
+    pub fn at(&mut self, position: Position) -> crate::Result<&[PlacedObject]> {
+        let region_id = position.region_id();
+        self.tick += 1;
+
+        if !self.regions.contains_key(&region_id) {
+            if let Some(cache) = self.cache {
+                let region = self.load(cache, region_id)?;
+                self.evict();
+                self.regions.insert(region_id, region);
+            }
+        }
+
+        let tick = self.tick;
+        Ok(self.regions.get_mut(&region_id).map_or(&[], |region| {
+            if let Some(last_used) = &mut region.last_used {
+                *last_used = tick;
+            }
+            region
+                .tiles
+                .get(&position.packed())
+                .map_or(&[], Vec::as_slice)
+        }))
+    }
+
+    /// Objects at the given tile of an already loaded region, see [`at`](Self::at).
+    pub fn get(&self, position: Position) -> &[PlacedObject] {
+        self.regions
+            .get(&position.region_id())
+            .and_then(|region| region.tiles.get(&position.packed()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    #[inline]
+    pub fn contains_region(&self, region_id: u16) -> bool {
+        self.regions.contains_key(&region_id)
+    }
+
+    /// Number of loaded regions.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    fn load(&self, cache: &Cache, region_id: u16) -> crate::Result<RegionObjects> {
+        let (x, y) = (region_id >> 8, region_id & 0xFF);
+
+        let archive_id = match cache.archive_by_name(5, format!("l{}_{}", x, y)) {
+            Ok(archive) => archive.id,
+            Err(crate::Error::NameHash(_)) => {
+                return Ok(RegionObjects {
+                    tiles: HashMap::new(),
+                    last_used: Some(self.tick),
+                })
+            }
+            Err(err) => return Err(err),
+        };
+        let buffer = cache.read_decoded(5, archive_id)?;
+        let locations = LocationDefinition::new(region_id.into(), &buffer)?;
+
+        let mut region = self.index(&locations);
+        region.last_used = Some(self.tick);
+
+        Ok(region)
+    }
+
+    /// Drops least recently used regions until there's room for one more.
+    fn evict(&mut self) {
+        let mut loaded: Vec<_> = self
+            .regions
+            .iter()
+            .filter_map(|(&id, region)| region.last_used.map(|last_used| (last_used, id)))
+            .collect();
+        loaded.sort_unstable();
+        let excess = (loaded.len() + 1).saturating_sub(self.capacity);
+        for (_, id) in loaded.into_iter().take(excess) {
+            self.regions.remove(&id);
+        }
+    }
+
+    fn index(&self, locations: &LocationDefinition) -> RegionObjects {
+        let mut tiles: HashMap<u32, Vec<PlacedObject>> = HashMap::new();
+
+        for location in &locations.data {
+            let definition = self.objects.load(location.id).cloned();
+            let (size_x, size_y) = definition.as_ref().map_or((1, 1), |object| {
+                (object.model_data.size_x, object.model_data.size_y)
+            });
+            let position = locations.position_of(location);
+            let (size_x, size_y) = if location.orientation & 1 == 1 {
+                (size_y, size_x)
+            } else {
+                (size_x, size_y)
+            };
+
+            tiles
+                .entry(position.packed())
+                .or_default()
+                .push(PlacedObject {
+                    location: location.clone(),
+                    definition,
+                    footprint: Footprint {
+                        position,
+                        size_x,
+                        size_y,
+                    },
+                });
+        }
+
+        RegionObjects {
+            tiles,
+            last_used: None,
+        }
+    }
+}
@@ -1,16 +1,69 @@
-use std::collections::{hash_map, HashMap};
+use std::{
+    collections::{btree_map, BTreeMap},
+    ops::ControlFlow,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    definition::config_archives::ConfigIndex,
     definition::rs3::{FetchDefinition, ItemDefinition},
+    definition::Language,
     Cache,
 };
 
 /// Loads all item definitions from the current cache.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
-pub struct ItemLoader(HashMap<u32, ItemDefinition>);
+pub struct ItemLoader(
+    BTreeMap<u32, ItemDefinition>,
+    #[cfg_attr(feature = "serde", serde(skip))] Option<u32>,
+);
+
+impl_rs3_loader!(ItemLoader, ItemDefinition, index_id: ConfigIndex::Item.index_id());
+
+impl ItemLoader {
+    /// Same as [`new`](ItemLoader::new), with the names of every item
+    /// [selecting](crate::definition::LocalizedString::select) `language`.
+    ///
+    /// The cache carries a single language, add the others through
+    /// [`add_translations`](ItemLoader::add_translations).
+    pub fn with_language(cache: &Cache, language: Language) -> crate::Result<Self> {
+        let mut loader = Self::new(cache)?;
+        loader.set_language(language);
+
+        Ok(loader)
+    }
+
+    /// Selects the variant `name()` returns for every item, items without
+    /// one fall back to English.
+    pub fn set_language(&mut self, language: Language) {
+        for item in self.0.values_mut() {
+            item.name.select(language);
+        }
+    }
 
-impl_rs3_loader!(ItemLoader, ItemDefinition, index_id: 19);
+    /// Adds the names of a cache in another language as the `language`
+    /// variant of the loaded items.
+    ///
+    /// Names of the translated cache parse as its only, English, variant and
+    /// are copied over from there. Items missing from either cache are left
+    /// untouched, as are the ones without a name in the translated cache.
+    ///
+    /// # Errors
+    ///
+    /// See [`new`](ItemLoader::new).
+    pub fn add_translations(&mut self, cache: &Cache, language: Language) -> crate::Result<()> {
+        Self::for_each(cache, |id, translated| {
+            if let Some(item) = self.0.get_mut(&id) {
+                let name = translated.name.english();
+                if !name.is_empty() {
+                    item.name.insert(language, name);
+                }
+            }
+            ControlFlow::Continue(())
+        })
+    }
+}
@@ -0,0 +1,198 @@
+//! Wire formats of the update server (js5) protocol.
+//!
+//! Clients request archives by index and archive id, the server answers with
+//! the archive's container split into blocks of [`BLOCK_LEN`] bytes. The first
+//! block starts with a 3-byte header, every block after it with
+//! [`BLOCK_MARKER`].
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::protocol;
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! let response = cache.build_js5_response(2, 10, true)?;
+//!
+//! // index, archive and the compression byte of the container.
+//! assert_eq!(response[..4], [2, 0, 10, 1]);
+//! assert_eq!(response[protocol::BLOCK_LEN], protocol::BLOCK_MARKER);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{self, Read, Write};
+
+use crate::codec::MAX_DECOMPRESSED_LEN;
+
+/// Length of a response block on the wire, the marker included.
+pub const BLOCK_LEN: usize = 512;
+
+/// Leads every block of a response after the first.
+pub const BLOCK_MARKER: u8 = 0xFF;
+
+/// Set on the compression byte of responses to prefetch requests, which
+/// aren't a priority to the client.
+pub const PREFETCH: u8 = 0x80;
+
+/// Builds the response to a request for a container, see [`write_js5_response`].
+///
+/// # Examples
+///
+/// ```
+/// use rscache::protocol;
+///
+/// let response = protocol::js5_response(2, 10, true, &[0, 0, 0, 0, 1, 42]);
+///
+/// assert_eq!(response, [2, 0, 10, 0, 0, 0, 0, 1, 42]);
+/// ```
+pub fn js5_response(index_id: u8, archive_id: u16, priority: bool, container: &[u8]) -> Vec<u8> {
+    let len = 3 + container.len();
+    let mut response = Vec::with_capacity(len + len / BLOCK_LEN);
+    write_js5_response(&mut response, index_id, archive_id, priority, container)
+        .expect("writing to a Vec can't fail");
+
+    response
+}
+
+/// Writes the response to a request for a container into `W`.
+///
+/// The container is sent as given, without the version trailer of archives
+/// outside of the reference table index, see
+/// [`Cache::build_js5_response`](crate::Cache::build_js5_response). Responses
+/// to non-`priority` requests have the [`PREFETCH`] bit set on the
+/// compression byte.
+///
+/// # Errors
+///
+/// Fails when writing to `W` fails.
+pub fn write_js5_response<W: Write>(
+    writer: &mut W,
+    index_id: u8,
+    archive_id: u16,
+    priority: bool,
+    container: &[u8],
+) -> io::Result<()> {
+    let [archive_hi, archive_lo] = archive_id.to_be_bytes();
+    let mut header = vec![index_id, archive_hi, archive_lo];
+    if let Some((&compression, _)) = container.split_first() {
+        header.push(if priority {
+            compression
+        } else {
+            compression | PREFETCH
+        });
+    }
+    let container = container.get(1..).unwrap_or_default();
+
+    let (first, rest) = container.split_at(container.len().min(BLOCK_LEN - header.len()));
+    writer.write_all(&header)?;
+    writer.write_all(first)?;
+    for block in rest.chunks(BLOCK_LEN - 1) {
+        writer.write_all(&[BLOCK_MARKER])?;
+        writer.write_all(block)?;
+    }
+
+    Ok(())
+}
+
+/// A response read with [`read_js5_response`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Js5Response {
+    pub index_id: u8,
+    pub archive_id: u16,
+    /// Whether the [`PREFETCH`] bit was clear.
+    pub priority: bool,
+    /// The container, with the [`PREFETCH`] bit cleared.
+    pub container: Vec<u8>,
+}
+
+/// Reads a response written by [`write_js5_response`], joining its blocks.
+///
+/// The length of the container is taken from its header.
+///
+/// # Errors
+///
+/// Fails when reading from `R` fails, a block doesn't start with
+/// [`BLOCK_MARKER`] or the container declares a length above
+/// [`MAX_DECOMPRESSED_LEN`].
+///
+/// # Examples
+///
+/// ```
+/// use rscache::protocol;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let container = [2, 0, 0, 2, 0, 0, 0, 0, 4].repeat(100);
+/// let response = protocol::js5_response(2, 10, false, &container[..9 + 512]);
+///
+/// let read = protocol::read_js5_response(&mut response.as_slice())?;
+/// assert_eq!((read.index_id, read.archive_id, read.priority), (2, 10, false));
+/// assert_eq!(read.container, &container[..9 + 512]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_js5_response<R: Read>(reader: &mut R) -> io::Result<Js5Response> {
+    let mut blocks = Blocks { reader, offset: 0 };
+    let mut header = [0; 8];
+    blocks.read_exact(&mut header)?;
+    let [index_id, archive_hi, archive_lo, compression, len @ ..] = header;
+
+    let compression = compression & !PREFETCH;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_DECOMPRESSED_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("response declares a container of {} bytes", len),
+        ));
+    }
+    // the decompressed length follows the header of compressed containers.
+    let header_len = if compression == 0 { 5 } else { 9 };
+    let mut container = vec![0; header_len + len];
+    container[0] = compression;
+    container[1..5].copy_from_slice(&header[4..]);
+    blocks.read_exact(&mut container[5..])?;
+
+    Ok(Js5Response {
+        index_id,
+        archive_id: u16::from_be_bytes([archive_hi, archive_lo]),
+        priority: header[3] & PREFETCH == 0,
+        container,
+    })
+}
+
+/// Reads the bytes of a response, skipping the markers between its blocks.
+struct Blocks<'a, R: Read> {
+    reader: &'a mut R,
+    /// Bytes of the response read so far, markers included.
+    offset: usize,
+}
+
+impl<R: Read> Blocks<'_, R> {
+    fn read_exact(&mut self, buffer: &mut [u8]) -> io::Result<()> {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            if self.offset > 0 && self.offset.is_multiple_of(BLOCK_LEN) {
+                let mut marker = [0];
+                self.reader.read_exact(&mut marker)?;
+                if marker[0] != BLOCK_MARKER {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "response block starts with {} instead of a marker",
+                            marker[0]
+                        ),
+                    ));
+                }
+                self.offset += 1;
+            }
+
+            let len = (BLOCK_LEN - self.offset % BLOCK_LEN).min(buffer.len() - filled);
+            self.reader.read_exact(&mut buffer[filled..filled + len])?;
+            filled += len;
+            self.offset += len;
+        }
+
+        Ok(())
+    }
+}
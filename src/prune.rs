@@ -0,0 +1,322 @@
+//! Strips a cache down to a whitelist of indices and archives.
+//!
+//! [`prune`] writes a new cache holding only what a [`PruneSpec`] keeps, e.g.
+//! to ship a small test cache. Archive containers are copied byte for byte,
+//! reference tables are rewritten to list only the retained archives, so every
+//! crc of the pruned cache still matches its data. The pruned cache is opened
+//! and verified before [`prune`] returns.
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::{prune::{self, PruneSpec}, IndexState};
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! # let dir = tempfile::tempdir()?;
+//! // the huffman table and the item definitions.
+//! let spec = PruneSpec::new().index(10).archive(2, 10);
+//! let report = prune::prune(&cache, dir.path(), &spec)?;
+//! assert!(report.size_after < report.size_before);
+//!
+//! let pruned = Cache::new(dir.path())?;
+//! assert_eq!(pruned.index_state(2), IndexState::Populated { archives: 1 });
+//! assert_eq!(pruned.index_state(7), IndexState::Missing);
+//! assert_eq!(pruned.read_raw(2, 10)?, cache.read_raw(2, 10)?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::Path,
+};
+
+use runefs::{
+    error::{Error as RuneFsError, ReadError},
+    IDX_PREFIX, MAIN_DATA, REFERENCE_TABLE_ID, SECTOR_SIZE,
+};
+
+use crate::{
+    bundle, codec::CompressionPolicy, error::ValidateError, reference_table::ArchiveEntry, util,
+    Cache,
+};
+
+/// What [`prune`] keeps, built up index by index.
+///
+/// Indices listed with [`index`](PruneSpec::index) are kept whole, the ones
+/// listed with [`archive`](PruneSpec::archive) or
+/// [`archive_named`](PruneSpec::archive_named) only keep the given archives.
+/// Everything else is dropped.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct PruneSpec {
+    indices: BTreeMap<u8, Selection>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum Selection {
+    All,
+    Archives {
+        ids: BTreeSet<u32>,
+        names: BTreeSet<String>,
+    },
+}
+
+impl PruneSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps every archive of the index.
+    pub fn index(mut self, index_id: u8) -> Self {
+        self.indices.insert(index_id, Selection::All);
+        self
+    }
+
+    /// Keeps a single archive of the index, unless the whole index is kept already.
+    pub fn archive(mut self, index_id: u8, archive_id: u32) -> Self {
+        if let Selection::Archives { ids, .. } = self.selection(index_id) {
+            ids.insert(archive_id);
+        }
+        self
+    }
+
+    /// Keeps the archive with the given name, see [`archive`](PruneSpec::archive).
+    pub fn archive_named<T: Into<String>>(mut self, index_id: u8, name: T) -> Self {
+        if let Selection::Archives { names, .. } = self.selection(index_id) {
+            names.insert(name.into());
+        }
+        self
+    }
+
+    /// Ids of the indices that are kept, in ascending order.
+    pub fn index_ids(&self) -> impl Iterator<Item = u8> + '_ {
+        self.indices.keys().copied()
+    }
+
+    fn selection(&mut self, index_id: u8) -> &mut Selection {
+        self.indices
+            .entry(index_id)
+            .or_insert_with(|| Selection::Archives {
+                ids: BTreeSet::new(),
+                names: BTreeSet::new(),
+            })
+    }
+}
+
+/// Sizes of a cache before and after [`prune`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct PruneReport {
+    /// Total length of the source cache's data and idx files.
+    pub size_before: u64,
+    /// Total length of the pruned cache's data and idx files.
+    pub size_after: u64,
+    /// Every kept index, in ascending order.
+    pub indices: Vec<PrunedIndex>,
+}
+
+/// Archives of a single index before and after [`prune`].
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct PrunedIndex {
+    pub index_id: u8,
+    pub archives_before: usize,
+    pub archives_after: usize,
+    /// Total length of the stored archive containers, sector headers excluded.
+    pub stored_before: u64,
+    pub stored_after: u64,
+}
+
+/// Writes the parts of `src` that `keep` lists into a new cache at `dest_dir`.
+///
+/// Indices that are kept whole keep their reference table as is, the tables of
+/// partially kept indices are re-encoded with the compression of
+/// [`CompressionPolicy::osrs`]. Archives listed in a table but not stored in
+/// the source stay listed.
+///
+/// # Errors
+///
+/// Fails when `dest_dir` already holds a cache, when a kept index, archive or
+/// archive name doesn't exist in `src`, when the files can't be written, or with
+/// [`ValidateError::InvalidCrc`] when an archive of the pruned cache doesn't match
+/// its reference table.
+pub fn prune<P: AsRef<Path>>(
+    src: &Cache,
+    dest_dir: P,
+    keep: &PruneSpec,
+) -> crate::Result<PruneReport> {
+    let dest_dir = dest_dir.as_ref();
+    if dest_dir.join(MAIN_DATA).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already holds a cache", dest_dir.display()),
+        )
+        .into());
+    }
+    fs::create_dir_all(dest_dir)?;
+
+    // the first sector is never used.
+    let mut dat2 = vec![0; SECTOR_SIZE];
+    let mut sector = 1;
+    let mut reference_idx = Vec::new();
+    let mut kept = Vec::with_capacity(keep.indices.len());
+    let mut indices = Vec::with_capacity(keep.indices.len());
+
+    for (&index_id, selection) in &keep.indices {
+        let metadata = src.metadata_of(index_id)?;
+        let archives = select(index_id, &metadata.table.archives, selection)?;
+
+        let mut idx = Vec::new();
+        let mut stored_after = 0;
+        let mut archives_after = 0;
+        for archive in &archives {
+            if src.archive_ref(index_id, archive.id).is_err() {
+                continue;
+            }
+
+            let container = src.read_raw(index_id, archive.id)?;
+            let entry = bundle::sectors(&mut dat2, &mut sector, index_id, archive.id, &container);
+            bundle::put_entry(&mut idx, archive.id, entry);
+            stored_after += container.len() as u64;
+            archives_after += 1;
+        }
+
+        let table = match selection {
+            Selection::All => src.read_raw(REFERENCE_TABLE_ID, index_id as u32)?,
+            Selection::Archives { .. } => {
+                let mut table = metadata.table.clone().into_owned();
+                table.archives.clone_from(&archives);
+
+                CompressionPolicy::osrs().encode(
+                    REFERENCE_TABLE_ID,
+                    index_id as u32,
+                    &table.encode(),
+                )?
+            }
+        };
+        let entry = bundle::sectors(
+            &mut dat2,
+            &mut sector,
+            REFERENCE_TABLE_ID,
+            index_id as u32,
+            &table,
+        );
+        bundle::put_entry(&mut reference_idx, index_id as u32, entry);
+        fs::write(dest_dir.join(format!("{}{}", IDX_PREFIX, index_id)), idx)?;
+
+        indices.push(PrunedIndex {
+            index_id,
+            archives_before: src.archive_count(index_id).unwrap_or_default(),
+            archives_after,
+            stored_before: metadata.stored_len,
+            stored_after,
+        });
+        kept.push((index_id, archives));
+    }
+
+    fs::write(dest_dir.join(MAIN_DATA), dat2)?;
+    fs::write(
+        dest_dir.join(format!("{}{}", IDX_PREFIX, REFERENCE_TABLE_ID)),
+        reference_idx,
+    )?;
+
+    verify(dest_dir, &kept)?;
+
+    Ok(PruneReport {
+        size_before: cache_size(&src.path)?,
+        size_after: cache_size(dest_dir)?,
+        indices,
+    })
+}
+
+/// The entries of the table that the selection keeps, in ascending id order.
+fn select(
+    index_id: u8,
+    archives: &[ArchiveEntry],
+    selection: &Selection,
+) -> crate::Result<Vec<ArchiveEntry>> {
+    let (ids, names) = match selection {
+        Selection::All => return Ok(archives.to_vec()),
+        Selection::Archives { ids, names } => (ids, names),
+    };
+
+    let mut hashes = BTreeSet::new();
+    for name in names {
+        let hash = util::djd2::hash(name);
+        if !archives
+            .iter()
+            .any(|archive| archive.name_hash == Some(hash))
+        {
+            return Err(crate::error::NameHashMismatch {
+                hash,
+                name: name.clone(),
+                idx: index_id,
+            }
+            .into());
+        }
+        hashes.insert(hash);
+    }
+    for &archive_id in ids {
+        if archives.iter().all(|archive| archive.id != archive_id) {
+            return Err(RuneFsError::Read(ReadError::ArchiveNotFound {
+                idx: index_id,
+                arc: archive_id,
+            })
+            .into());
+        }
+    }
+
+    Ok(archives
+        .iter()
+        .filter(|archive| {
+            ids.contains(&archive.id)
+                || archive.name_hash.is_some_and(|hash| hashes.contains(&hash))
+        })
+        .cloned()
+        .collect())
+}
+
+/// Opens the pruned cache and checks every stored archive against its reference table.
+fn verify(dest_dir: &Path, kept: &[(u8, Vec<ArchiveEntry>)]) -> crate::Result<()> {
+    let pruned = Cache::new(dest_dir)?;
+
+    for (index_id, archives) in kept {
+        let table = pruned.reference_table(*index_id)?;
+        for archive in archives {
+            if pruned.archive_ref(*index_id, archive.id).is_err() {
+                continue;
+            }
+
+            let container = pruned.read_raw(*index_id, archive.id)?;
+            let (body, _) = crate::codec::split_trailer(&container);
+            let crc = crc32fast::hash(body);
+            let expected = table.archive(archive.id).map(|archive| archive.crc);
+            if expected != Some(crc) {
+                return Err(ValidateError::InvalidCrc {
+                    idx: *index_id as usize,
+                    internal: expected.unwrap_or_default(),
+                    external: crc,
+                }
+                .into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Total length of the data and idx files in the cache directory.
+fn cache_size(path: &Path) -> crate::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name == MAIN_DATA || name.starts_with(IDX_PREFIX) {
+            size += entry.metadata()?.len();
+        }
+    }
+
+    Ok(size)
+}
@@ -0,0 +1,446 @@
+//! Typed reference tables.
+//!
+//! Every index has a reference table stored in index 255 that describes its
+//! archives. Which per-archive metadata is present depends on the table's
+//! [flags](ReferenceTableFlags).
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::reference_table::{ReferenceTable, ReferenceTableFlags};
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! let buffer = cache.read(255, 10)?.decode()?;
+//! let table = ReferenceTable::parse(&buffer)?;
+//!
+//! assert!(table.flags.contains(ReferenceTableFlags::NAMED));
+//! assert_eq!(cache.index_flags(10)?, table.flags);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{borrow::Cow, ops::RangeInclusive};
+
+use nom::{
+    combinator::cond,
+    multi::count,
+    number::complete::{be_i32, be_u16, be_u32, be_u8},
+    IResult,
+};
+use runefs::parse::be_u32_smart;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+thread_local! {
+    static PARSES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Reference tables parsed on this thread so far.
+#[cfg(test)]
+pub(crate) fn parses() -> usize {
+    PARSES.with(std::cell::Cell::get)
+}
+
+/// Flags byte of a reference table, describing which metadata it carries.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ReferenceTableFlags(u8);
+
+impl ReferenceTableFlags {
+    /// Archives and their files have name hashes.
+    pub const NAMED: Self = Self(1);
+    /// Archives have a whirlpool digest.
+    pub const WHIRLPOOL: Self = Self(1 << 1);
+    /// Archives have their compressed and decompressed sizes.
+    pub const SIZES: Self = Self(1 << 2);
+    /// Archives have a checksum of their decompressed data.
+    pub const HASHES: Self = Self(1 << 3);
+
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Keeps only the known flags, unknown bits are dropped.
+    #[inline]
+    pub const fn from_bits_truncate(bits: u8) -> Self {
+        Self(bits & 0xF)
+    }
+
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl std::ops::BitOr for ReferenceTableFlags {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+/// Compressed and decompressed size of an archive, present with [`ReferenceTableFlags::SIZES`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ArchiveSizes {
+    pub compressed: u32,
+    pub decompressed: u32,
+}
+
+/// Metadata of a single archive in a [`ReferenceTable`].
+///
+/// Optional fields are `None` when the table's flags don't include them.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ArchiveEntry {
+    pub id: u32,
+    pub name_hash: Option<i32>,
+    pub crc: u32,
+    pub hash: Option<i32>,
+    pub whirlpool: Option<Vec<u8>>,
+    pub sizes: Option<ArchiveSizes>,
+    /// Full version, containers only keep the low 16 bits in their trailer.
+    pub version: u32,
+    pub file_ids: Vec<u32>,
+    pub file_name_hashes: Option<Vec<i32>>,
+}
+
+impl ArchiveEntry {
+    /// The version as stored in the 2-byte trailer of the archive's container.
+    #[inline]
+    pub const fn trailer_version(&self) -> u16 {
+        self.version as u16
+    }
+
+    /// Compares a version read from a container trailer or sent by a client,
+    /// only the low 16 bits are significant.
+    #[inline]
+    pub const fn matches_version(&self, version: u32) -> bool {
+        self.version & 0xFFFF == version & 0xFFFF
+    }
+
+    /// Number of files in the archive's group.
+    #[inline]
+    pub fn file_count(&self) -> usize {
+        self.file_ids.len()
+    }
+}
+
+/// Decoded reference table of an index.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ReferenceTable {
+    pub protocol: u8,
+    /// Only present from protocol 6 onwards.
+    pub revision: Option<u32>,
+    pub flags: ReferenceTableFlags,
+    pub archives: Vec<ArchiveEntry>,
+}
+
+impl ReferenceTable {
+    /// Parses a decoded reference table, honoring every flag.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error when the buffer is shorter than its header and flags require,
+    /// or when archive or file ids aren't strictly ascending.
+    pub fn parse(buffer: &[u8]) -> crate::Result<Self> {
+        #[cfg(test)]
+        PARSES.with(|parses| parses.set(parses.get() + 1));
+
+        let (buffer, protocol) = be_u8(buffer)?;
+        let (buffer, revision) = cond(protocol >= 6, be_u32)(buffer)?;
+        let (buffer, flags) = be_u8(buffer)?;
+        let flags = ReferenceTableFlags::from_bits_truncate(flags);
+        let wide = protocol >= 7;
+
+        let (buffer, archive_count) = id(buffer, wide)?;
+        let archive_count = archive_count as usize;
+        let (buffer, ids) = deltas(buffer, wide, archive_count)?;
+        let (buffer, name_hashes) = cond(
+            flags.contains(ReferenceTableFlags::NAMED),
+            count(be_i32, archive_count),
+        )(buffer)?;
+        let (buffer, crcs) = count(be_u32, archive_count)(buffer)?;
+        let (buffer, hashes) = cond(
+            flags.contains(ReferenceTableFlags::HASHES),
+            count(be_i32, archive_count),
+        )(buffer)?;
+        let (buffer, whirlpools) = cond(
+            flags.contains(ReferenceTableFlags::WHIRLPOOL),
+            count(whirlpool, archive_count),
+        )(buffer)?;
+        let (buffer, sizes) = cond(
+            flags.contains(ReferenceTableFlags::SIZES),
+            count(archive_sizes, archive_count),
+        )(buffer)?;
+        let (buffer, versions) = count(be_u32, archive_count)(buffer)?;
+        let (mut buffer, file_counts) = count(|buf| id(buf, wide), archive_count)(buffer)?;
+
+        let mut file_ids = Vec::with_capacity(archive_count);
+        for &file_count in &file_counts {
+            let (buf, ids) = deltas(buffer, wide, file_count as usize)?;
+            buffer = buf;
+            file_ids.push(ids);
+        }
+
+        let mut file_name_hashes = Vec::with_capacity(archive_count);
+        if flags.contains(ReferenceTableFlags::NAMED) {
+            for &file_count in &file_counts {
+                let (buf, hashes) = count(be_i32, file_count as usize)(buffer)?;
+                buffer = buf;
+                file_name_hashes.push(hashes);
+            }
+        }
+
+        let mut file_name_hashes = file_name_hashes.into_iter();
+        let archives = file_ids
+            .into_iter()
+            .enumerate()
+            .map(|(index, file_ids)| ArchiveEntry {
+                id: ids[index],
+                name_hash: name_hashes.as_ref().map(|hashes| hashes[index]),
+                crc: crcs[index],
+                hash: hashes.as_ref().map(|hashes| hashes[index]),
+                whirlpool: whirlpools.as_ref().map(|digests| digests[index].to_vec()),
+                sizes: sizes.as_ref().map(|sizes| sizes[index]),
+                version: versions[index],
+                file_ids,
+                file_name_hashes: file_name_hashes.next(),
+            })
+            .collect();
+
+        Ok(Self {
+            protocol,
+            revision,
+            flags,
+            archives,
+        })
+    }
+
+    /// Version of the whole table, protocol 5 tables don't store one and report 0.
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.revision.unwrap_or(0)
+    }
+
+    /// Finds the entry of the given archive.
+    #[inline]
+    pub fn archive(&self, archive_id: u32) -> Option<&ArchiveEntry> {
+        self.archives
+            .binary_search_by_key(&archive_id, |archive| archive.id)
+            .ok()
+            .map(|index| &self.archives[index])
+    }
+
+    /// Encodes the table the way [`parse`](ReferenceTable::parse) reads it, the
+    /// inverse of parsing for every table it accepts.
+    ///
+    /// Archive and file ids are expected in ascending order. Optional metadata
+    /// is written for the flags of the table, archives missing it get zeroes.
+    pub fn encode(&self) -> Vec<u8> {
+        let wide = self.protocol >= 7;
+        let flags = self.flags;
+        let mut buffer = vec![self.protocol];
+        if self.protocol >= 6 {
+            buffer.extend(self.version().to_be_bytes());
+        }
+        buffer.push(flags.bits());
+
+        put_id(&mut buffer, wide, self.archives.len() as u32);
+        put_deltas(
+            &mut buffer,
+            wide,
+            self.archives.iter().map(|archive| archive.id),
+        );
+        if flags.contains(ReferenceTableFlags::NAMED) {
+            for archive in &self.archives {
+                buffer.extend(archive.name_hash.unwrap_or_default().to_be_bytes());
+            }
+        }
+        for archive in &self.archives {
+            buffer.extend(archive.crc.to_be_bytes());
+        }
+        if flags.contains(ReferenceTableFlags::HASHES) {
+            for archive in &self.archives {
+                buffer.extend(archive.hash.unwrap_or_default().to_be_bytes());
+            }
+        }
+        if flags.contains(ReferenceTableFlags::WHIRLPOOL) {
+            for archive in &self.archives {
+                let mut digest = archive.whirlpool.clone().unwrap_or_default();
+                digest.resize(64, 0);
+                buffer.extend(digest);
+            }
+        }
+        if flags.contains(ReferenceTableFlags::SIZES) {
+            for archive in &self.archives {
+                let sizes = archive.sizes.unwrap_or_default();
+                buffer.extend(sizes.compressed.to_be_bytes());
+                buffer.extend(sizes.decompressed.to_be_bytes());
+            }
+        }
+        for archive in &self.archives {
+            buffer.extend(archive.version.to_be_bytes());
+        }
+        for archive in &self.archives {
+            put_id(&mut buffer, wide, archive.file_ids.len() as u32);
+        }
+        for archive in &self.archives {
+            put_deltas(&mut buffer, wide, archive.file_ids.iter().copied());
+        }
+        if flags.contains(ReferenceTableFlags::NAMED) {
+            for archive in &self.archives {
+                let hashes = archive.file_name_hashes.as_deref().unwrap_or_default();
+                for index in 0..archive.file_ids.len() {
+                    buffer.extend(hashes.get(index).copied().unwrap_or_default().to_be_bytes());
+                }
+            }
+        }
+
+        buffer
+    }
+}
+
+/// Reference table of a single index together with stats derived from the
+/// index, see [`Cache::metadata_of`](crate::Cache::metadata_of).
+///
+/// The table is borrowed from the cache, which parses every reference table
+/// when it is opened.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct IndexMetadata<'a> {
+    pub index_id: u8,
+    pub table: Cow<'a, ReferenceTable>,
+    /// Total length of the archives stored in `main_file_cache.dat2`, sector headers excluded.
+    pub stored_len: u64,
+    /// Lowest and highest archive id listed in the table, `None` when it lists none.
+    pub archive_ids: Option<RangeInclusive<u32>>,
+}
+
+impl IndexMetadata<'_> {
+    #[inline]
+    pub fn protocol(&self) -> u8 {
+        self.table.protocol
+    }
+
+    /// Only present from protocol 6 onwards.
+    #[inline]
+    pub fn revision(&self) -> Option<u32> {
+        self.table.revision
+    }
+
+    /// See [`ReferenceTable::version`].
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.table.version()
+    }
+
+    #[inline]
+    pub fn flags(&self) -> ReferenceTableFlags {
+        self.table.flags
+    }
+
+    /// Number of groups (archives) listed in the table.
+    #[inline]
+    pub fn group_count(&self) -> usize {
+        self.table.archives.len()
+    }
+
+    /// Entries of the listed archives with their crc, version and file ids, by
+    /// ascending archive id.
+    #[inline]
+    pub fn archives(&self) -> &[ArchiveEntry] {
+        &self.table.archives
+    }
+
+    /// Number of files in all groups of the index.
+    #[inline]
+    pub fn file_count(&self) -> usize {
+        self.archives().iter().map(ArchiveEntry::file_count).sum()
+    }
+}
+
+fn id(buffer: &[u8], wide: bool) -> IResult<&[u8], u32, ()> {
+    if buffer.is_empty() {
+        Err(nom::Err::Error(()))
+    } else if wide {
+        be_u32_smart(buffer)
+    } else {
+        let (buffer, id) = be_u16(buffer)?;
+        Ok((buffer, u32::from(id)))
+    }
+}
+
+fn put_id(buffer: &mut Vec<u8>, wide: bool, id: u32) {
+    if wide && id >= 0x8000 {
+        buffer.extend((id | 0x8000_0000).to_be_bytes());
+    } else {
+        buffer.extend((id as u16).to_be_bytes());
+    }
+}
+
+fn put_deltas<I: IntoIterator<Item = u32>>(buffer: &mut Vec<u8>, wide: bool, ids: I) {
+    let mut previous = 0;
+    for id in ids {
+        put_id(buffer, wide, id.wrapping_sub(previous));
+        previous = id;
+    }
+}
+
+/// Ids are stored as deltas from the previous id, they have to be strictly ascending.
+fn deltas(buffer: &[u8], wide: bool, len: usize) -> IResult<&[u8], Vec<u32>, ()> {
+    let (buffer, deltas) = count(|buf| id(buf, wide), len)(buffer)?;
+
+    let mut ids: Vec<u32> = Vec::with_capacity(deltas.len());
+    for delta in deltas {
+        let id = match ids.last() {
+            None => delta,
+            Some(&previous) => previous
+                .checked_add(delta)
+                .filter(|&id| id > previous)
+                .ok_or(nom::Err::Error(()))?,
+        };
+        ids.push(id);
+    }
+
+    Ok((buffer, ids))
+}
+
+fn whirlpool(buffer: &[u8]) -> IResult<&[u8], &[u8], ()> {
+    nom::bytes::complete::take(64_usize)(buffer)
+}
+
+fn archive_sizes(buffer: &[u8]) -> IResult<&[u8], ArchiveSizes, ()> {
+    let (buffer, compressed) = be_u32(buffer)?;
+    let (buffer, decompressed) = be_u32(buffer)?;
+
+    Ok((
+        buffer,
+        ArchiveSizes {
+            compressed,
+            decompressed,
+        },
+    ))
+}
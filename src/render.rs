@@ -0,0 +1,147 @@
+//! Software rendering of cache content into images.
+//!
+//! Renders are meant for documentation and debugging, not for matching the
+//! client pixel for pixel. They're drawn on the CPU into an RGBA [`Image`]
+//! that can be saved as a PNG.
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::{
+//!     decryption::DecryptionRegistry,
+//!     loader::osrs::{
+//!         LocationLoader, MapLoader, ModelLoader, ObjectLoader, OverlayLoader, UnderlayLoader,
+//!     },
+//!     render::{self, SceneLoaders, SceneOptions},
+//! };
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! let mut registry = DecryptionRegistry::new();
+//! registry.insert_region(12850, [3030157619, 2364842415, 3297319647, 1973582566]);
+//! let cache = Cache::builder("./data/osrs_cache").decryption(registry).open()?;
+//!
+//! let mut loaders = SceneLoaders {
+//!     maps: &mut MapLoader::new(&cache),
+//!     locations: &mut LocationLoader::new(&cache),
+//!     models: &mut ModelLoader::new(&cache),
+//!     objects: &ObjectLoader::new(&cache)?,
+//!     underlays: &UnderlayLoader::new(&cache)?,
+//!     overlays: &OverlayLoader::new(&cache)?,
+//! };
+//! let options = SceneOptions {
+//!     width: 128,
+//!     height: 128,
+//!     ..SceneOptions::default()
+//! };
+//!
+//! let image = render::scene_region(12850, &mut loaders, &options)?;
+//! let mut png = Vec::new();
+//! image.write_png(&mut png)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{self, Write};
+
+use flate2::write::ZlibEncoder;
+
+mod scene;
+
+#[doc(inline)]
+pub use scene::{scene_region, SceneLoaders, SceneOptions};
+
+/// An RGBA image with 8 bits per channel, rows run from top to bottom.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// An image with every pixel set to `fill`.
+    pub fn new(width: u32, height: u32, fill: [u8; 4]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: fill.repeat(width as usize * height as usize),
+        }
+    }
+
+    #[inline]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The RGBA channels of every pixel, row by row.
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The pixel at `x` and `y`, `None` outside of the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+
+        self.pixels[offset..offset + 4].try_into().ok()
+    }
+
+    /// Blends `color` over the pixel at `x` and `y`, its alpha channel is the opacity.
+    fn blend(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+        let alpha = u32::from(color[3]);
+
+        for (channel, value) in self.pixels[offset..offset + 3].iter_mut().zip(color) {
+            *channel =
+                ((u32::from(*channel) * (255 - alpha) + u32::from(value) * alpha) / 255) as u8;
+        }
+    }
+
+    /// Encodes the image as an 8-bit RGBA PNG.
+    ///
+    /// # Errors
+    ///
+    /// Fails when writing to `W` fails.
+    pub fn write_png<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(b"\x89PNG\r\n\x1A\n")?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend(self.width.to_be_bytes());
+        header.extend(self.height.to_be_bytes());
+        // 8 bits per channel, RGBA, deflate, adaptive filtering and no interlacing.
+        header.extend([8, 6, 0, 0, 0]);
+        write_chunk(&mut writer, b"IHDR", &header)?;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        if self.width > 0 {
+            for row in self.pixels.chunks_exact(self.width as usize * 4) {
+                // every row without a filter.
+                encoder.write_all(&[0])?;
+                encoder.write_all(row)?;
+            }
+        }
+        write_chunk(&mut writer, b"IDAT", &encoder.finish()?)?;
+
+        write_chunk(&mut writer, b"IEND", &[])
+    }
+}
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&hasher.finalize().to_be_bytes())
+}
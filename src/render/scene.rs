@@ -0,0 +1,599 @@
+use std::f64::consts::FRAC_1_SQRT_2;
+
+use super::Image;
+use crate::{
+    definition::osrs::{LocationSpawn, MapDefinition, ObjectDefinition, ObjectModelData},
+    loader::osrs::{
+        LocationLoader, MapLoader, ModelLoader, ObjectLoader, OverlayLoader, UnderlayLoader,
+    },
+};
+
+/// Tiles along each side of a region.
+const REGION_SIZE: usize = 64;
+/// Planes of a region.
+const PLANES: usize = 4;
+/// Length of a tile side in model units.
+const TILE_SIZE: f64 = 128.0;
+/// Object model sizes and offsets are in 128ths.
+const MODEL_SCALE: f64 = 128.0;
+/// Height of a plane above the one below where the map doesn't store one.
+const PLANE_HEIGHT: i32 = 240;
+/// Overlay color the client treats as not drawn.
+const HIDDEN_OVERLAY: u32 = 0xFF_00_FF;
+/// Direction towards the light, (-1, -1, 2) normalized: from the south west and above.
+const LIGHT: [f64; 3] = [
+    -0.408_248_290_463_863,
+    -0.408_248_290_463_863,
+    0.816_496_580_927_726,
+];
+/// Brightness of faces facing away from the light.
+const AMBIENT: f64 = 0.55;
+
+/// How [`scene_region`] looks at the region.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SceneOptions {
+    /// Width of the image in pixels, the region is scaled to fit.
+    pub width: u32,
+    /// Height of the image in pixels.
+    pub height: u32,
+    /// Highest plane drawn, the planes below it are drawn as well.
+    pub plane: u8,
+    /// Rotation of the camera around the vertical axis in degrees, 0 looks
+    /// north and 90 looks east.
+    pub yaw: f64,
+    /// Angle of the camera below the horizon in degrees, 90 looks straight down.
+    pub pitch: f64,
+    /// RGBA color of the pixels nothing is drawn on.
+    pub background: [u8; 4],
+}
+
+impl Default for SceneOptions {
+    fn default() -> Self {
+        Self {
+            width: 512,
+            height: 512,
+            plane: 0,
+            yaw: 45.0,
+            pitch: 45.0,
+            background: [0, 0, 0, 255],
+        }
+    }
+}
+
+/// The loaders [`scene_region`] reads a region through.
+#[derive(Debug)]
+pub struct SceneLoaders<'a, 'cache> {
+    pub maps: &'a mut MapLoader<'cache>,
+    pub locations: &'a mut LocationLoader<'cache>,
+    pub models: &'a mut ModelLoader<'cache>,
+    pub objects: &'a ObjectLoader,
+    pub underlays: &'a UnderlayLoader,
+    pub overlays: &'a OverlayLoader,
+}
+
+/// Renders a region with its terrain and object models, seen from above at
+/// an angle.
+///
+/// The terrain is built from the tile heights of the map, generating the
+/// ground plane where the map doesn't store a height like the client does,
+/// and colored with the underlay or overlay of every tile. Objects are placed
+/// by their location type and orientation, mirrored, scaled and offset as
+/// their definition says and recolored. Faces are shaded by a fixed light and
+/// painted back to front.
+///
+/// The render is an approximation of the client's: underlay colors aren't
+/// blended between tiles, overlays cover whole tiles, textures are drawn with
+/// the color of their face and wall decorations aren't pushed off their wall.
+/// Location keys are the ones registered in the cache's
+/// [`DecryptionRegistry`](crate::decryption::DecryptionRegistry).
+///
+/// # Errors
+///
+/// Fails when the map, the locations or a placed model of the region can't be
+/// read. Locations of objects the object loader doesn't know are skipped.
+pub fn scene_region(
+    region_id: u16,
+    loaders: &mut SceneLoaders<'_, '_>,
+    options: &SceneOptions,
+) -> crate::Result<Image> {
+    let map = loaders.maps.load(region_id)?;
+    let heights = Heights::new(map);
+    let mut faces = terrain(
+        map,
+        &heights,
+        (loaders.underlays, loaders.overlays),
+        options.plane,
+    );
+
+    let locations = loaders.locations.load_registered(region_id)?;
+    for spawn in locations.spawns_on(map) {
+        if spawn.position.plane > options.plane {
+            continue;
+        }
+        if let Some(object) = loaders.objects.load(spawn.id) {
+            place(map, &spawn, object, &heights, loaders.models, &mut faces)?;
+        }
+    }
+
+    Ok(draw(&faces, options))
+}
+
+/// A shaded triangle in region space: `x` points east, `y` north and `z` up,
+/// in model units.
+#[derive(Clone, Copy, Debug)]
+struct Face {
+    points: [[f64; 3]; 3],
+    color: [u8; 4],
+}
+
+impl Face {
+    fn shaded(points: [[f64; 3]; 3], [red, green, blue]: [u8; 3], alpha: u8) -> Self {
+        let [a, b, c] = points;
+        let normal = cross(sub(b, a), sub(c, a));
+        let length = dot(normal, normal).sqrt();
+        // faces are lit from both sides, their winding isn't known after mirroring.
+        let lambert = if length > 0.0 {
+            (dot(normal, LIGHT) / length).abs()
+        } else {
+            0.0
+        };
+        let intensity = AMBIENT + (1.0 - AMBIENT) * lambert;
+        let shade = |channel: u8| (f64::from(channel) * intensity).round().min(255.0) as u8;
+
+        Self {
+            points,
+            color: [shade(red), shade(green), shade(blue), alpha],
+        }
+    }
+}
+
+/// Heights of the south west corners of every tile, in model units.
+struct Heights(Vec<i32>);
+
+impl Heights {
+    fn new(map: &MapDefinition) -> Self {
+        let (base_x, base_y) = map.region_base_coords();
+        let mut heights = vec![0; PLANES * REGION_SIZE * REGION_SIZE];
+
+        for plane in 0..PLANES {
+            for x in 0..REGION_SIZE {
+                for y in 0..REGION_SIZE {
+                    let below = match plane {
+                        0 => 0,
+                        _ => heights[Self::index(plane - 1, x, y)],
+                    };
+                    heights[Self::index(plane, x, y)] = match map.map_data(x, y, plane).height {
+                        0 if plane == 0 => {
+                            generated_height(
+                                i32::from(base_x) + x as i32,
+                                i32::from(base_y) + y as i32,
+                            ) * 8
+                        }
+                        0 => below + PLANE_HEIGHT,
+                        1 => below,
+                        stored => below + i32::from(stored) * 8,
+                    };
+                }
+            }
+        }
+
+        Self(heights)
+    }
+
+    #[inline]
+    const fn index(plane: usize, x: usize, y: usize) -> usize {
+        (plane * REGION_SIZE + x) * REGION_SIZE + y
+    }
+
+    /// Height of the south west corner of a tile, corners past the edge of the
+    /// region take the height of the last tile.
+    fn corner(&self, plane: usize, x: usize, y: usize) -> f64 {
+        let last = REGION_SIZE - 1;
+
+        f64::from(self.0[Self::index(plane, x.min(last), y.min(last))])
+    }
+}
+
+/// Two faces for every tile with a floor on the drawn planes.
+fn terrain(
+    map: &MapDefinition,
+    heights: &Heights,
+    (underlays, overlays): (&UnderlayLoader, &OverlayLoader),
+    top_plane: u8,
+) -> Vec<Face> {
+    let mut faces = Vec::new();
+
+    for plane in 0..=usize::from(top_plane).min(PLANES - 1) {
+        for x in 0..REGION_SIZE {
+            for y in 0..REGION_SIZE {
+                let tile = map.map_data(x, y, plane);
+                let overlay = (tile.overlay_id as u8)
+                    .checked_sub(1)
+                    .and_then(|id| overlays.load(id.into()));
+                let color = match overlay {
+                    Some(overlay) if overlay.texture.is_some() => {
+                        overlay.secondary_color.unwrap_or(overlay.color)
+                    }
+                    Some(overlay) => overlay.color,
+                    None => match tile
+                        .underlay_id
+                        .checked_sub(1)
+                        .and_then(|id| underlays.load(id.into()))
+                    {
+                        Some(underlay) => underlay.color,
+                        None => continue,
+                    },
+                };
+                if color == HIDDEN_OVERLAY {
+                    continue;
+                }
+
+                let corner = |dx: usize, dy: usize| {
+                    [
+                        (x + dx) as f64 * TILE_SIZE,
+                        (y + dy) as f64 * TILE_SIZE,
+                        heights.corner(plane, x + dx, y + dy),
+                    ]
+                };
+                let [_, red, green, blue] = color.to_be_bytes();
+                let (south_west, north_east) = (corner(0, 0), corner(1, 1));
+                faces.push(Face::shaded(
+                    [south_west, corner(1, 0), north_east],
+                    [red, green, blue],
+                    255,
+                ));
+                faces.push(Face::shaded(
+                    [south_west, north_east, corner(0, 1)],
+                    [red, green, blue],
+                    255,
+                ));
+            }
+        }
+    }
+
+    faces
+}
+
+/// Models of an object for a location type, like the client picks them.
+fn model_ids(data: &ObjectModelData, loc_type: u8) -> &[u16] {
+    // diagonal centerpieces are rotated centerpieces.
+    let loc_type = if loc_type == 11 { 10 } else { loc_type };
+
+    if data.types.is_empty() {
+        // objects without types only have centerpiece models, all of them merged.
+        return if loc_type == 10 { &data.models } else { &[] };
+    }
+
+    data.types
+        .iter()
+        .position(|&ty| ty == loc_type)
+        .and_then(|position| data.models.get(position..=position))
+        .unwrap_or_default()
+}
+
+/// Adds the faces of the object's models at its location.
+fn place(
+    map: &MapDefinition,
+    spawn: &LocationSpawn,
+    object: &ObjectDefinition,
+    heights: &Heights,
+    models: &mut ModelLoader<'_>,
+    faces: &mut Vec<Face>,
+) -> crate::Result<()> {
+    let data = &object.model_data;
+    let (base_x, base_y) = map.region_base_coords();
+    let (x, y) = (
+        usize::from(spawn.position.x.wrapping_sub(base_x)),
+        usize::from(spawn.position.y.wrapping_sub(base_y)),
+    );
+    let plane = usize::from(spawn.position.plane);
+    if x >= REGION_SIZE || y >= REGION_SIZE || plane >= PLANES {
+        return Ok(());
+    }
+
+    let (size_x, size_y) = if spawn.orientation & 1 == 1 {
+        (usize::from(data.size_y), usize::from(data.size_x))
+    } else {
+        (usize::from(data.size_x), usize::from(data.size_y))
+    };
+    let center = [
+        (x as f64 + size_x as f64 / 2.0) * TILE_SIZE,
+        (y as f64 + size_y as f64 / 2.0) * TILE_SIZE,
+    ];
+    let ground = (heights.corner(plane, x, y)
+        + heights.corner(plane, x + size_x, y)
+        + heights.corner(plane, x, y + size_y)
+        + heights.corner(plane, x + size_x, y + size_y))
+        / 4.0;
+
+    let mirrored = object.rotated ^ (spawn.orientation > 3);
+    let scale = [data.model_size_x, data.model_size_z, data.model_size_y]
+        .map(|size| f64::from(size) / MODEL_SCALE);
+    // offsets are signed.
+    let offset =
+        [data.offset_x, data.offset_z, data.offset_y].map(|offset| f64::from(offset as i16));
+
+    for &id in model_ids(data, spawn.loc_type) {
+        let model = models.load(id.into())?;
+        // models have `y` pointing down and `z` pointing north.
+        let points: Vec<[f64; 3]> = model
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let (mut east, mut north) = (f64::from(vertex.x), f64::from(vertex.z));
+                if mirrored {
+                    north = -north;
+                }
+                for _ in 0..spawn.orientation & 3 {
+                    (east, north) = (north, -east);
+                }
+                if spawn.loc_type == 11 {
+                    (east, north) = (
+                        (east + north) * FRAC_1_SQRT_2,
+                        (north - east) * FRAC_1_SQRT_2,
+                    );
+                }
+
+                [
+                    center[0] + east * scale[0] + offset[0],
+                    center[1] + north * scale[2] + offset[2],
+                    ground - (f64::from(vertex.y) * scale[1] + offset[1]),
+                ]
+            })
+            .collect();
+
+        for (face, vertices) in model.faces.iter().enumerate() {
+            // render type 2 hides the face.
+            if model.face_render_types.get(face) == Some(&2) {
+                continue;
+            }
+            let alpha = 255 - model.face_alphas.get(face).copied().unwrap_or(0);
+            let [Some(&a), Some(&b), Some(&c)] =
+                vertices.map(|vertex| points.get(usize::from(vertex)))
+            else {
+                continue;
+            };
+            if alpha == 0 {
+                continue;
+            }
+
+            let color = model.face_colors.get(face).copied().unwrap_or_default();
+            let color = data
+                .recolor_find
+                .iter()
+                .position(|&find| find == color)
+                .and_then(|position| data.recolor_replace.get(position).copied())
+                .unwrap_or(color);
+            faces.push(Face::shaded([a, b, c], hsl16_to_rgb(color), alpha));
+        }
+    }
+
+    Ok(())
+}
+
+/// An orthographic camera, looking at the scene from `yaw` and `pitch`.
+struct Camera {
+    right: [f64; 3],
+    up: [f64; 3],
+    forward: [f64; 3],
+}
+
+impl Camera {
+    fn new(yaw: f64, pitch: f64) -> Self {
+        let (yaw_sin, yaw_cos) = yaw.to_radians().sin_cos();
+        let (pitch_sin, pitch_cos) = pitch.to_radians().sin_cos();
+
+        Self {
+            right: [yaw_cos, -yaw_sin, 0.0],
+            up: [yaw_sin * pitch_sin, yaw_cos * pitch_sin, pitch_cos],
+            forward: [yaw_sin * pitch_cos, yaw_cos * pitch_cos, -pitch_sin],
+        }
+    }
+
+    /// Screen `x` to the right, screen `y` up and the depth away from the camera.
+    fn project(&self, point: [f64; 3]) -> [f64; 3] {
+        [
+            dot(point, self.right),
+            dot(point, self.up),
+            dot(point, self.forward),
+        ]
+    }
+}
+
+/// Paints the faces back to front, scaled to fit the image.
+fn draw(faces: &[Face], options: &SceneOptions) -> Image {
+    let mut image = Image::new(options.width, options.height, options.background);
+    if faces.is_empty() || options.width == 0 || options.height == 0 {
+        return image;
+    }
+
+    let camera = Camera::new(options.yaw, options.pitch);
+    let mut projected: Vec<([[f64; 2]; 3], f64, [u8; 4])> = faces
+        .iter()
+        .map(|face| {
+            let points = face.points.map(|point| camera.project(point));
+            let depth = points.iter().map(|point| point[2]).sum::<f64>() / 3.0;
+
+            (points.map(|[x, y, _]| [x, y]), depth, face.color)
+        })
+        .collect();
+    // farthest first, faces at the same depth keep their order.
+    projected.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let (mut min, mut max) = ([f64::MAX; 2], [f64::MIN; 2]);
+    for point in projected.iter().flat_map(|(points, ..)| points) {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(point[axis]);
+            max[axis] = max[axis].max(point[axis]);
+        }
+    }
+    let (width, height) = (f64::from(options.width - 1), f64::from(options.height - 1));
+    let span = [(max[0] - min[0]).max(1.0), (max[1] - min[1]).max(1.0)];
+    let scale = (width / span[0]).min(height / span[1]);
+    let margin = [
+        (width - span[0] * scale) / 2.0,
+        (height - span[1] * scale) / 2.0,
+    ];
+
+    for (points, _, color) in projected {
+        let points = points.map(|[x, y]| {
+            [
+                (x - min[0]) * scale + margin[0],
+                (max[1] - y) * scale + margin[1],
+            ]
+        });
+        fill(&mut image, points, color);
+    }
+
+    image
+}
+
+/// Blends the triangle onto every pixel whose center it covers.
+fn fill(image: &mut Image, [a, b, c]: [[f64; 2]; 3], color: [u8; 4]) {
+    let area = edge(a, b, c);
+    if area == 0.0 {
+        return;
+    }
+
+    let bounds = |axis: usize, len: u32| {
+        let low = a[axis].min(b[axis]).min(c[axis]).floor();
+        let high = a[axis].max(b[axis]).max(c[axis]).ceil();
+        (high >= 0.0 && low < f64::from(len))
+            .then(|| (low.max(0.0) as u32, (high as u32).min(len - 1)))
+    };
+    let (Some((min_x, max_x)), Some((min_y, max_y))) =
+        (bounds(0, image.width()), bounds(1, image.height()))
+    else {
+        return;
+    };
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let point = [f64::from(x) + 0.5, f64::from(y) + 0.5];
+            let weights = [edge(b, c, point), edge(c, a, point), edge(a, b, point)];
+            let inside = if area > 0.0 {
+                weights.iter().all(|&weight| weight >= 0.0)
+            } else {
+                weights.iter().all(|&weight| weight <= 0.0)
+            };
+            if inside {
+                image.blend(x, y, color);
+            }
+        }
+    }
+}
+
+#[inline]
+fn edge(a: [f64; 2], b: [f64; 2], point: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (point[1] - a[1]) - (b[1] - a[1]) * (point[0] - a[0])
+}
+
+#[inline]
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+#[inline]
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[inline]
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// RGB of a model face color, 6 bits of hue, 3 of saturation and 7 of lightness.
+fn hsl16_to_rgb(hsl: u16) -> [u8; 3] {
+    let hue = f64::from(hsl >> 10 & 0x3F) / 64.0 + 0.0078125;
+    let saturation = f64::from(hsl >> 7 & 0x7) / 8.0 + 0.0625;
+    let lightness = f64::from(hsl & 0x7F) / 128.0;
+
+    let high = if lightness < 0.5 {
+        lightness * (1.0 + saturation)
+    } else {
+        lightness + saturation - lightness * saturation
+    };
+    let low = 2.0 * lightness - high;
+    let channel = |hue: f64| {
+        let hue = hue.rem_euclid(1.0);
+        let value = if 6.0 * hue < 1.0 {
+            low + (high - low) * 6.0 * hue
+        } else if 2.0 * hue < 1.0 {
+            high
+        } else if 3.0 * hue < 2.0 {
+            low + (high - low) * (2.0 / 3.0 - hue) * 6.0
+        } else {
+            low
+        };
+
+        (value * 256.0).clamp(0.0, 255.0) as u8
+    };
+
+    [
+        channel(hue + 1.0 / 3.0),
+        channel(hue),
+        channel(hue - 1.0 / 3.0),
+    ]
+}
+
+/// Height in 8ths of the ground tiles the map leaves to the client, smoothed
+/// noise over the world coordinates of the tile.
+fn generated_height(x: i32, y: i32) -> i32 {
+    let (x, y) = (x + 932_731, y + 556_238);
+    let height = interpolated_noise(x + 45_365, y + 91_923, 4) - 128
+        + ((interpolated_noise(x + 10_294, y + 37_821, 2) - 128) >> 1)
+        + ((interpolated_noise(x, y, 1) - 128) >> 2);
+
+    ((f64::from(height) * 0.3) as i32 + 35).clamp(10, 60)
+}
+
+fn interpolated_noise(x: i32, y: i32, scale: i32) -> i32 {
+    let (whole_x, fraction_x) = (x / scale, x & (scale - 1));
+    let (whole_y, fraction_y) = (y / scale, y & (scale - 1));
+    let south = interpolate(
+        smooth_noise(whole_x, whole_y),
+        smooth_noise(whole_x + 1, whole_y),
+        fraction_x,
+        scale,
+    );
+    let north = interpolate(
+        smooth_noise(whole_x, whole_y + 1),
+        smooth_noise(whole_x + 1, whole_y + 1),
+        fraction_x,
+        scale,
+    );
+
+    interpolate(south, north, fraction_y, scale)
+}
+
+/// Cosine interpolation with the client's fixed point cosine table.
+fn interpolate(a: i32, b: i32, fraction: i32, scale: i32) -> i32 {
+    let cosine = (65_536.0 * (f64::from(fraction * 1024 / scale) * 0.003_067_961_5).cos()) as i32;
+    let weight = (65_536 - cosine) >> 1;
+
+    ((a * (65_536 - weight)) >> 16) + ((b * weight) >> 16)
+}
+
+fn smooth_noise(x: i32, y: i32) -> i32 {
+    let corners =
+        noise(x - 1, y - 1) + noise(x + 1, y - 1) + noise(x - 1, y + 1) + noise(x + 1, y + 1);
+    let sides = noise(x - 1, y) + noise(x + 1, y) + noise(x, y - 1) + noise(x, y + 1);
+
+    noise(x, y) / 4 + sides / 8 + corners / 16
+}
+
+fn noise(x: i32, y: i32) -> i32 {
+    let n = x.wrapping_add(y.wrapping_mul(57));
+    let n = (n << 13) ^ n;
+    let n = n
+        .wrapping_mul(n.wrapping_mul(n).wrapping_mul(15_731).wrapping_add(789_221))
+        .wrapping_add(1_376_312_589)
+        & 0x7FFF_FFFF;
+
+    (n >> 19) & 0xFF
+}
@@ -0,0 +1,210 @@
+//! Reads across dated snapshots of a cache.
+//!
+//! A [`Timeline`] holds an ordered list of caches, oldest first, and answers
+//! questions like "what did item 4151 look like in every revision" without
+//! opening more snapshots than needed. Snapshots given by path are opened on
+//! first use and stay open afterwards.
+//!
+//! # Example
+//!
+//! ```
+//! use rscache::{loader::osrs::ItemLoader, timeline::Timeline};
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! let timeline = Timeline::new(["./data/osrs_cache", "./data/osrs_cache"]);
+//! let history = timeline.definition_history::<ItemLoader>(4151)?;
+//!
+//! // the same cache twice, the whip never changed.
+//! assert_eq!(history.len(), 1);
+//! assert_eq!(history[0].1.as_ref().unwrap().name, "Abyssal whip");
+//! assert_eq!(timeline.first_changed(2, 10)?, None);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::PathBuf;
+
+use once_cell::sync::OnceCell;
+use runefs::{
+    codec::{Buffer, Encoded},
+    error::{Error as RuneFsError, ReadError as RuneFsReadError},
+};
+
+use crate::{error::ReadError, loader::DefinitionLoader, Cache, Error};
+
+/// A cache snapshot, opened on first use when given by path.
+#[derive(Debug)]
+struct Snapshot {
+    path: Option<PathBuf>,
+    cache: OnceCell<Cache>,
+}
+
+/// Ordered cache snapshots, see the [module docs](self).
+///
+/// Revisions are the positions of the snapshots, starting at 0 for the oldest.
+#[derive(Debug, Default)]
+pub struct Timeline {
+    snapshots: Vec<Snapshot>,
+}
+
+impl Timeline {
+    /// A timeline over the caches at the given paths, oldest first.
+    ///
+    /// Nothing is opened until a revision is read.
+    pub fn new<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        let snapshots = paths
+            .into_iter()
+            .map(|path| Snapshot {
+                path: Some(path.into()),
+                cache: OnceCell::new(),
+            })
+            .collect();
+
+        Self { snapshots }
+    }
+
+    /// A timeline over already opened caches, oldest first.
+    pub fn from_caches<I: IntoIterator<Item = Cache>>(caches: I) -> Self {
+        let snapshots = caches
+            .into_iter()
+            .map(|cache| Snapshot {
+                path: None,
+                cache: OnceCell::with_value(cache),
+            })
+            .collect();
+
+        Self { snapshots }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// The cache of the revision, opening it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the snapshot can't be opened, see [`Cache::new`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when the revision is out of range.
+    pub fn cache(&self, revision: usize) -> crate::Result<&Cache> {
+        let snapshot = &self.snapshots[revision];
+
+        snapshot.cache.get_or_try_init(|| match &snapshot.path {
+            Some(path) => Cache::new(path),
+            None => unreachable!("caches without a path are opened up front"),
+        })
+    }
+
+    /// Reads an archive of the revision, see [`Cache::read`].
+    pub fn read_at(
+        &self,
+        revision: usize,
+        index_id: u8,
+        archive_id: u32,
+    ) -> crate::Result<Buffer<Encoded>> {
+        self.cache(revision)?.read(index_id, archive_id)
+    }
+
+    /// Every revision where the definition changed, with the definition as of
+    /// that revision, `None` while it doesn't exist.
+    ///
+    /// The first revision is always listed. Revisions where the reference
+    /// table crc of the definition's archive is unchanged are skipped without
+    /// reading the archive, otherwise the definition is only parsed when its
+    /// bytes differ from the previous revision.
+    ///
+    /// # Errors
+    ///
+    /// Fails when a snapshot can't be opened or a definition can't be read or parsed.
+    pub fn definition_history<L: DefinitionLoader>(
+        &self,
+        id: u32,
+    ) -> crate::Result<Vec<(usize, Option<L::Definition>)>> {
+        let (index_id, archive_id) = L::archive_of(id);
+        let mut history = Vec::new();
+        let mut previous_crc = None;
+        let mut previous_raw = None;
+
+        for revision in 0..self.len() {
+            let cache = self.cache(revision)?;
+            let crc = archive_crc(cache, index_id, archive_id)?;
+            if revision > 0 && crc.is_some() && crc == previous_crc {
+                continue;
+            }
+            previous_crc = crc;
+
+            let raw = match crc {
+                Some(_) => raw_or_missing(L::raw(cache, id))?,
+                None => None,
+            };
+            if revision > 0 && raw == previous_raw {
+                continue;
+            }
+
+            let definition = raw.as_deref().map(|raw| L::parse(id, raw)).transpose()?;
+            history.push((revision, definition));
+            previous_raw = raw;
+        }
+
+        Ok(history)
+    }
+
+    /// The first revision where the archive's reference table crc differs from
+    /// the revision before it, `None` when it never changes.
+    ///
+    /// Archives missing from a revision count as changed.
+    ///
+    /// # Errors
+    ///
+    /// Fails when a snapshot can't be opened or the index has no reference table.
+    pub fn first_changed(&self, index_id: u8, archive_id: u32) -> crate::Result<Option<usize>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+
+        let mut previous = archive_crc(self.cache(0)?, index_id, archive_id)?;
+        for revision in 1..self.len() {
+            let crc = archive_crc(self.cache(revision)?, index_id, archive_id)?;
+            if crc != previous {
+                return Ok(Some(revision));
+            }
+            previous = crc;
+        }
+
+        Ok(None)
+    }
+}
+
+/// Crc of the archive in the index's reference table, `None` when it isn't listed.
+fn archive_crc(cache: &Cache, index_id: u8, archive_id: u32) -> crate::Result<Option<u32>> {
+    Ok(cache
+        .metadata_of(index_id)?
+        .table
+        .archive(archive_id)
+        .map(|archive| archive.crc))
+}
+
+/// Turns the errors of a definition that doesn't exist into `None`.
+fn raw_or_missing(raw: crate::Result<Vec<u8>>) -> crate::Result<Option<Vec<u8>>> {
+    match raw {
+        Ok(raw) => Ok(Some(raw)),
+        Err(Error::Read(ReadError::FileNotFound { .. }))
+        | Err(Error::RuneFs(RuneFsError::Read(RuneFsReadError::ArchiveNotFound { .. }))) => {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
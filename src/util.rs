@@ -2,41 +2,150 @@
 
 #[allow(unused_assignments)]
 mod huffman;
+pub mod collision;
+pub mod hexdump;
+pub mod instance;
 #[allow(clippy::many_single_char_names, clippy::too_many_lines)]
 mod isaac_rand;
+mod name_hash;
 
 pub use huffman::Huffman;
 pub use isaac_rand::IsaacRand;
+pub use name_hash::{NameHashTable, NameTransform};
 
 use std::{
-    collections::HashMap,
+    collections::BTreeMap,
     io::{self, BufReader},
 };
 
-use crate::extension::ReadExt;
+use crate::{definition::Value, extension::ReadExt};
 
 macro_rules! impl_osrs_loader {
     ($ldr:ident, $def:ty, index_id: $idx_id:expr $(, archive_id: $arc_id:expr)?) => {
         impl $ldr {
             #[allow(unreachable_code)]
             pub fn new(cache: &Cache) -> crate::Result<Self> {
+                let source_crc = Self::source_crc_of(cache)?;
                 $(
-                    let map = <$def>::fetch_from_archive(cache, $idx_id, $arc_id)?;
+                    let map = crate::definition::osrs::fetch_archive::<$def>(cache, $idx_id, $arc_id)?;
 
-                    return Ok(Self(map));
+                    return Ok(Self(map, Some(source_crc)));
                 )?
 
                 let map = <$def>::fetch_from_index(cache, $idx_id)?;
 
-                Ok(Self(map))
+                Ok(Self(map, Some(source_crc)))
             }
 
-            pub fn load(&self, id: u16) -> Option<&$def> {
+            /// Crc the definitions are parsed from, the archive's reference table crc.
+            #[allow(unreachable_code)]
+            fn source_crc_of(cache: &Cache) -> crate::Result<u32> {
+                $(
+                    return Ok(cache.archive_metadata($idx_id, $arc_id)?.crc);
+                )?
+
+                Ok(crc32fast::hash(&cache.read_raw(runefs::REFERENCE_TABLE_ID, $idx_id as u32)?))
+            }
+
+            pub fn load(&self, id: u32) -> Option<&$def> {
                 self.0.get(&id)
             }
+
+            /// Reference table crc of the data the loader was built from, `None`
+            /// for loaders that were collected or deserialized.
+            #[inline]
+            pub const fn source_crc(&self) -> Option<u32> {
+                self.1
+            }
+
+            /// Whether the cache's definitions changed since the loader was
+            /// built, e.g. after the cache was swapped on disk and
+            /// [reloaded](crate::Cache::reload).
+            ///
+            /// Loaders without a [`source_crc`](Self::source_crc) are always stale.
+            ///
+            /// # Errors
+            ///
+            /// Fails when the reference table can't be read.
+            pub fn is_stale(&self, cache: &Cache) -> crate::Result<bool> {
+                Ok(self.1 != Some(Self::source_crc_of(cache)?))
+            }
+
+            /// Rebuilds the loader from the cache when it [is stale](Self::is_stale),
+            /// returning whether it did.
+            ///
+            /// # Errors
+            ///
+            /// See [`new`](Self::new), the loader is left as is on failure.
+            pub fn refresh(&mut self, cache: &Cache) -> crate::Result<bool> {
+                if !self.is_stale(cache)? {
+                    return Ok(false);
+                }
+                *self = Self::new(cache)?;
+
+                Ok(true)
+            }
+
+            /// Parses every definition and hands it to `f` without building the loader,
+            /// stopping as soon as `f` breaks.
+            #[allow(unreachable_code)]
+            pub fn for_each<F>(cache: &Cache, f: F) -> crate::Result<()>
+            where
+                F: FnMut(u32, $def) -> std::ops::ControlFlow<()>,
+            {
+                $(
+                    return <$def>::for_each_in_archive(cache, $idx_id, $arc_id, f);
+                )?
+
+                <$def>::for_each_in_index(cache, $idx_id, f)
+            }
+
+            /// Reads the undecoded definition payload, the bytes `load` parsed the
+            /// definition from. Pairs with [`annotate`](crate::definition::annotate)
+            /// to inspect fields a newer revision added.
+            #[allow(unreachable_code)]
+            pub fn raw(cache: &Cache, id: u32) -> crate::Result<Vec<u8>> {
+                $(
+                    return cache.read_file($idx_id, $arc_id, id);
+                )?
+
+                Ok(cache.read_decoded($idx_id, id)?.finalize())
+            }
+
+            /// The [raw](Self::raw) definition payload as a [hex dump](crate::util::hexdump::HexDump).
+            pub fn raw_hexdump(
+                cache: &Cache,
+                id: u32,
+            ) -> crate::Result<crate::util::hexdump::HexDump<'static>> {
+                Ok(crate::util::hexdump::HexDump::new(Self::raw(cache, id)?))
+            }
         }
 
-        impl_iter_for_loader!($ldr, u16, $def);
+        impl crate::loader::DefinitionLoader for $ldr {
+            type Definition = $def;
+
+            #[allow(unreachable_code, unused_variables)]
+            #[inline]
+            fn archive_of(id: u32) -> (u8, u32) {
+                $(
+                    return ($idx_id, $arc_id);
+                )?
+
+                ($idx_id, id)
+            }
+
+            #[inline]
+            fn raw(cache: &Cache, id: u32) -> crate::Result<Vec<u8>> {
+                Self::raw(cache, id)
+            }
+
+            #[inline]
+            fn parse(id: u32, buffer: &[u8]) -> crate::Result<Self::Definition> {
+                <$def>::new(id, buffer)
+            }
+        }
+
+        impl_iter_for_loader!($ldr, u32, $def);
     };
 }
 
@@ -45,14 +154,99 @@ macro_rules! impl_rs3_loader {
     ($ldr:ident, $def:ty, index_id: $idx_id:expr) => {
         impl $ldr {
             pub fn new(cache: &Cache) -> crate::Result<Self> {
+                let source_crc = Self::source_crc_of(cache)?;
                 let map = <$def>::fetch_from_index(cache, $idx_id)?;
 
-                Ok(Self(map))
+                Ok(Self(map, Some(source_crc)))
+            }
+
+            /// Crc the definitions are parsed from, the crc of the index's reference table.
+            fn source_crc_of(cache: &Cache) -> crate::Result<u32> {
+                Ok(crc32fast::hash(&cache.read_raw(runefs::REFERENCE_TABLE_ID, $idx_id as u32)?))
             }
 
             pub fn load(&self, id: u32) -> Option<&$def> {
                 self.0.get(&id)
             }
+
+            /// Reference table crc of the data the loader was built from, `None`
+            /// for loaders that were collected or deserialized.
+            #[inline]
+            pub const fn source_crc(&self) -> Option<u32> {
+                self.1
+            }
+
+            /// Whether the cache's definitions changed since the loader was
+            /// built, e.g. after the cache was swapped on disk and
+            /// [reloaded](crate::Cache::reload).
+            ///
+            /// Loaders without a [`source_crc`](Self::source_crc) are always stale.
+            ///
+            /// # Errors
+            ///
+            /// Fails when the reference table can't be read.
+            pub fn is_stale(&self, cache: &Cache) -> crate::Result<bool> {
+                Ok(self.1 != Some(Self::source_crc_of(cache)?))
+            }
+
+            /// Rebuilds the loader from the cache when it [is stale](Self::is_stale),
+            /// returning whether it did.
+            ///
+            /// # Errors
+            ///
+            /// See [`new`](Self::new), the loader is left as is on failure.
+            pub fn refresh(&mut self, cache: &Cache) -> crate::Result<bool> {
+                if !self.is_stale(cache)? {
+                    return Ok(false);
+                }
+                *self = Self::new(cache)?;
+
+                Ok(true)
+            }
+
+            /// Parses every definition and hands it to `f` without building the loader,
+            /// stopping as soon as `f` breaks.
+            pub fn for_each<F>(cache: &Cache, f: F) -> crate::Result<()>
+            where
+                F: FnMut(u32, $def) -> std::ops::ControlFlow<()>,
+            {
+                <$def>::for_each_in_index(cache, $idx_id, f)
+            }
+
+            /// Reads the undecoded definition payload, the bytes `load` parsed the
+            /// definition from.
+            pub fn raw(cache: &Cache, id: u32) -> crate::Result<Vec<u8>> {
+                let block_size = crate::definition::rs3::ID_BLOCK_SIZE;
+
+                cache.read_file($idx_id, id / block_size, id % block_size)
+            }
+
+            /// The [raw](Self::raw) definition payload as a [hex dump](crate::util::hexdump::HexDump).
+            pub fn raw_hexdump(
+                cache: &Cache,
+                id: u32,
+            ) -> crate::Result<crate::util::hexdump::HexDump<'static>> {
+                Ok(crate::util::hexdump::HexDump::new(Self::raw(cache, id)?))
+            }
+        }
+
+        impl crate::loader::DefinitionLoader for $ldr {
+            type Definition = $def;
+
+            #[inline]
+            fn archive_of(id: u32) -> (u8, u32) {
+                ($idx_id, id / crate::definition::rs3::ID_BLOCK_SIZE)
+            }
+
+            #[inline]
+            fn raw(cache: &Cache, id: u32) -> crate::Result<Vec<u8>> {
+                Self::raw(cache, id)
+            }
+
+            #[inline]
+            fn parse(id: u32, buffer: &[u8]) -> crate::Result<Self::Definition> {
+                <$def as crate::definition::rs3::Definition>::new(id, buffer)
+            }
         }
 
         impl_iter_for_loader!($ldr, u32, $def);
@@ -63,19 +257,26 @@ macro_rules! impl_iter_for_loader {
     ($ldr:ident, $id:ty, $def:ty) => {
         impl $ldr {
             #[inline]
-            pub fn iter(&self) -> hash_map::Iter<'_, $id, $def> {
+            pub fn iter(&self) -> btree_map::Iter<'_, $id, $def> {
                 self.0.iter()
             }
 
             #[inline]
-            pub fn iter_mut(&mut self) -> hash_map::IterMut<'_, $id, $def> {
+            pub fn iter_mut(&mut self) -> btree_map::IterMut<'_, $id, $def> {
                 self.0.iter_mut()
             }
         }
 
+        impl FromIterator<($id, $def)> for $ldr {
+            #[inline]
+            fn from_iter<T: IntoIterator<Item = ($id, $def)>>(definitions: T) -> Self {
+                Self(definitions.into_iter().collect(), None)
+            }
+        }
+
         impl IntoIterator for $ldr {
             type Item = ($id, $def);
-            type IntoIter = hash_map::IntoIter<$id, $def>;
+            type IntoIter = btree_map::IntoIter<$id, $def>;
 
             #[inline]
             fn into_iter(self) -> Self::IntoIter {
@@ -85,7 +286,7 @@ macro_rules! impl_iter_for_loader {
 
         impl<'a> IntoIterator for &'a $ldr {
             type Item = (&'a $id, &'a $def);
-            type IntoIter = hash_map::Iter<'a, $id, $def>;
+            type IntoIter = btree_map::Iter<'a, $id, $def>;
             #[inline]
             fn into_iter(self) -> Self::IntoIter {
                 self.0.iter()
@@ -94,7 +295,7 @@ macro_rules! impl_iter_for_loader {
 
         impl<'a> IntoIterator for &'a mut $ldr {
             type Item = (&'a $id, &'a mut $def);
-            type IntoIter = hash_map::IterMut<'a, $id, $def>;
+            type IntoIter = btree_map::IterMut<'a, $id, $def>;
 
             #[inline]
             fn into_iter(self) -> Self::IntoIter {
@@ -104,15 +305,28 @@ macro_rules! impl_iter_for_loader {
     };
 }
 
+/// Hash of an archive or file name, as stored in named reference tables.
+///
+/// The hash is the same as [`djd2::hash`], names are hashed by character, so
+/// the client's names are case sensitive.
+///
+/// # Examples
+///
+/// ```
+/// use rscache::util;
+///
+/// assert_eq!(util::hash_archive_name("huffman"), 1258058669);
+/// ```
+#[inline]
+pub fn hash_archive_name(name: &str) -> i32 {
+    djd2::hash(name)
+}
+
 /// djd2 module for string hashing
 pub mod djd2 {
 
     /// Hashes the string
     ///
-    /// # Errors
-    ///
-    /// Can panic if `nth(n)` returns `None` if n >= strings iter length.
-    ///
     /// # Examples
     ///
     /// ```
@@ -120,18 +334,71 @@ pub mod djd2 {
     /// assert_eq!(hash, 1258058669);
     /// ```
     pub fn hash<T: AsRef<str>>(string: T) -> i32 {
-        let string = string.as_ref();
-        let mut hash = 0;
-
-        for index in 0..string.len() {
-            hash =
-                string.chars().nth(index).unwrap_or_else(|| {
-                    panic!("index {} not valid in str len {}", index, string.len())
-                }) as i32
-                    + ((hash << 5) - hash);
+        string.as_ref().chars().fold(0_i32, |hash, char| {
+            (char as i32).wrapping_add((hash << 5).wrapping_sub(hash))
+        })
+    }
+}
+
+/// Splits a decoded archive group into its files.
+///
+/// A group holding a single file is the file itself, otherwise the size of
+/// every file is stored as deltas in one or more chunks at the end of the group.
+///
+/// # Errors
+///
+/// Returns an `InvalidData` I/O error when the sizes don't fit the group.
+///
+/// # Examples
+///
+/// ```
+/// # use rscache::Cache;
+/// use rscache::{codec, util};
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// # let cache = Cache::new("./data/osrs_cache")?;
+/// let buffer = codec::decode(&cache.read(2, 10)?)?;
+/// let file_count = cache.archive_metadata(2, 10)?.file_ids.len();
+///
+/// let items = util::split_group(&buffer, file_count)?;
+/// assert_eq!(items.len(), file_count);
+/// # Ok(())
+/// # }
+/// ```
+pub fn split_group(buffer: &[u8], file_count: usize) -> crate::Result<Vec<Vec<u8>>> {
+    let malformed = || io::Error::new(io::ErrorKind::InvalidData, "malformed archive group");
+
+    match file_count {
+        0 => return Ok(Vec::new()),
+        1 => return Ok(vec![buffer.to_vec()]),
+        _ => {}
+    }
+
+    let (&chunks, buffer) = buffer.split_last().ok_or_else(malformed)?;
+    let sizes_len = (chunks as usize)
+        .checked_mul(file_count)
+        .and_then(|len| len.checked_mul(4))
+        .filter(|&len| chunks > 0 && len <= buffer.len())
+        .ok_or_else(malformed)?;
+    let (mut data, sizes) = buffer.split_at(buffer.len() - sizes_len);
+
+    let mut files = vec![Vec::new(); file_count];
+    for chunk in sizes.chunks_exact(file_count * 4) {
+        let mut size = 0_i32;
+        for (file, delta) in files.iter_mut().zip(chunk.chunks_exact(4)) {
+            size = size.wrapping_add(i32::from_be_bytes([delta[0], delta[1], delta[2], delta[3]]));
+            let len = usize::try_from(size)
+                .ok()
+                .filter(|&len| len <= data.len())
+                .ok_or_else(malformed)?;
+
+            let (block, rest) = data.split_at(len);
+            file.extend_from_slice(block);
+            data = rest;
         }
-        hash
     }
+
+    Ok(files)
 }
 
 /// Useful for decoding parameters when reading from definition buffers.
@@ -139,17 +406,17 @@ pub mod djd2 {
 /// # Errors
 ///
 /// Can return `std::io::Error` if reading from the `BufReader<&[u8]>` fails.
-pub fn read_parameters(reader: &mut BufReader<&[u8]>) -> io::Result<HashMap<u32, String>> {
+pub fn read_parameters(reader: &mut BufReader<&[u8]>) -> io::Result<BTreeMap<u32, Value>> {
     let len = reader.read_u8()?;
-    let mut map = HashMap::new();
+    let mut map = BTreeMap::new();
 
     for _ in 0..len {
         let is_string = reader.read_u8()? == 1;
         let key = reader.read_u24()?;
         let value = if is_string {
-            reader.read_string()?
+            Value::String(reader.read_string()?)
         } else {
-            reader.read_i32()?.to_string()
+            Value::Int(reader.read_i32()?)
         };
 
         map.insert(key, value);
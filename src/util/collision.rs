@@ -0,0 +1,263 @@
+//! Walkability of a region, for pathfinding.
+//!
+//! A [`CollisionMap`] marks the tiles of a region the client doesn't let
+//! players walk on, built the way the client clips a region when loading it:
+//! - tiles flagged as blocked in the map.
+//! - the footprint of solid objects, like trees and tables.
+//! - ground decoration only reachable from its sides.
+//! - the edges blocked by solid walls, see [`Location::wall_edges`].
+//!
+//! Tiles under a bridge take the clipping of the bridge, one plane up.
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::{
+//!     loader::osrs::{LocationLoader, MapLoader, ObjectLoader},
+//!     util::collision::CollisionMap,
+//! };
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! let keys = [3030157619, 2364842415, 3297319647, 1973582566];
+//! let map = MapLoader::new(&cache).load(12850)?.clone();
+//! let locations = LocationLoader::new(&cache).load(12850, &keys)?.clone();
+//! let objects = ObjectLoader::new(&cache)?;
+//!
+//! let collision = CollisionMap::new(&map, &locations, &objects);
+//! println!("{} blocked tiles", collision.blocked_tiles().count());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    definition::osrs::{
+        Location, LocationCategory, LocationDefinition, LocationKind, MapDefinition,
+        ObjectDefinition, Position, WallEdges,
+    },
+    loader::osrs::ObjectLoader,
+};
+
+use super::instance::Region;
+
+/// Width and height of a region in tiles.
+const REGION_SIZE: usize = 64;
+/// Planes of a region.
+const PLANES: usize = 4;
+
+/// Tile setting of blocked tiles.
+const BLOCKED_TILE: u8 = 0x1;
+
+/// Blocked tiles and walls of a single region.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CollisionMap {
+    region_x: u16,
+    region_y: u16,
+    /// Indexed `[plane][x][y]`.
+    blocked: Vec<bool>,
+    walls: Vec<WallEdges>,
+}
+
+impl CollisionMap {
+    /// Clips the region of `map` with its `locations`, objects are looked up in `objects`.
+    ///
+    /// Locations of unknown objects don't block anything.
+    pub fn new(
+        map: &MapDefinition,
+        locations: &LocationDefinition,
+        objects: &ObjectLoader,
+    ) -> Self {
+        let mut collision = Self {
+            region_x: map.region_x,
+            region_y: map.region_y,
+            blocked: vec![false; PLANES * REGION_SIZE * REGION_SIZE],
+            walls: vec![WallEdges::empty(); PLANES * REGION_SIZE * REGION_SIZE],
+        };
+
+        for plane in 0..PLANES {
+            for x in 0..REGION_SIZE {
+                for y in 0..REGION_SIZE {
+                    if map.map_data(x, y, plane).settings & BLOCKED_TILE == BLOCKED_TILE {
+                        if let Some(plane) = collision_plane(map, x, y, plane) {
+                            collision.block(x as i32, y as i32, plane);
+                        }
+                    }
+                }
+            }
+        }
+
+        for location in &locations.data {
+            if let Some(object) = objects.load(location.id) {
+                collision.add_location(map, locations, location, object);
+            }
+        }
+
+        collision
+    }
+
+    /// Clips a region, see [`new`](Self::new).
+    #[inline]
+    pub fn from_region(region: &Region, objects: &ObjectLoader) -> Self {
+        Self::new(&region.map, &region.locations, objects)
+    }
+
+    #[inline]
+    pub const fn region_id(&self) -> u16 {
+        self.region_x << 8 | self.region_y
+    }
+
+    #[inline]
+    pub const fn region_base_coords(&self) -> (u16, u16) {
+        (self.region_x << 6, self.region_y << 6)
+    }
+
+    /// Whether the tile at world coordinates `x` and `y` can't be walked on.
+    ///
+    /// Tiles outside of the region are never blocked by it.
+    pub fn is_blocked(&self, x: u16, y: u16, plane: u8) -> bool {
+        self.local_index(x, y, plane)
+            .is_some_and(|index| self.blocked[index])
+    }
+
+    /// Edges of the tile at world coordinates `x` and `y` that can't be
+    /// walked through, empty outside of the region.
+    pub fn walls(&self, x: u16, y: u16, plane: u8) -> WallEdges {
+        self.local_index(x, y, plane)
+            .map_or(WallEdges::empty(), |index| self.walls[index])
+    }
+
+    /// Every blocked tile of the region, by plane, then x and y.
+    pub fn blocked_tiles(&self) -> impl Iterator<Item = Position> + '_ {
+        let (base_x, base_y) = self.region_base_coords();
+
+        self.blocked
+            .iter()
+            .enumerate()
+            .filter(|(_, &blocked)| blocked)
+            .map(move |(index, _)| {
+                let plane = index / (REGION_SIZE * REGION_SIZE);
+                let x = index / REGION_SIZE % REGION_SIZE;
+                let y = index % REGION_SIZE;
+
+                Position::new(base_x + x as u16, base_y + y as u16, plane as u8)
+            })
+    }
+
+    const fn index(x: usize, y: usize, plane: usize) -> usize {
+        (plane * REGION_SIZE + x) * REGION_SIZE + y
+    }
+
+    fn local_index(&self, x: u16, y: u16, plane: u8) -> Option<usize> {
+        let (base_x, base_y) = self.region_base_coords();
+        let x = x.wrapping_sub(base_x) as usize;
+        let y = y.wrapping_sub(base_y) as usize;
+
+        (x < REGION_SIZE && y < REGION_SIZE && (plane as usize) < PLANES)
+            .then(|| Self::index(x, y, plane as usize))
+    }
+
+    fn add_location(
+        &mut self,
+        map: &MapDefinition,
+        locations: &LocationDefinition,
+        location: &Location,
+        object: &ObjectDefinition,
+    ) {
+        if !object.solid {
+            return;
+        }
+
+        let position = locations.position_of(location);
+        let (base_x, base_y) = locations.region_base_coords();
+        let x = position.x.wrapping_sub(base_x) as usize;
+        let y = position.y.wrapping_sub(base_y) as usize;
+        if x >= REGION_SIZE || y >= REGION_SIZE || position.plane as usize >= PLANES {
+            return;
+        }
+        let plane = match collision_plane(map, x, y, position.plane as usize) {
+            Some(plane) => plane,
+            None => return,
+        };
+
+        let (size_x, size_y) = if location.orientation & 1 == 1 {
+            (object.model_data.size_y, object.model_data.size_x)
+        } else {
+            (object.model_data.size_x, object.model_data.size_y)
+        };
+
+        match location.kind().map(LocationKind::category) {
+            Some(LocationCategory::Wall) => {
+                if let Some(edges) = location.wall_edges() {
+                    self.add_walls(x as i32, y as i32, plane, edges);
+                }
+            }
+            // diagonal walls block their whole tile, like other centerpieces.
+            Some(LocationCategory::Centerpiece) => {
+                for dx in 0..i32::from(size_x) {
+                    for dy in 0..i32::from(size_y) {
+                        self.block(x as i32 + dx, y as i32 + dy, plane);
+                    }
+                }
+            }
+            // only ground decoration reachable from its sides blocks, like the client does.
+            Some(LocationCategory::GroundDecoration) if object.interact_type == 1 => {
+                self.block(x as i32, y as i32, plane);
+            }
+            _ => {}
+        }
+    }
+
+    /// Blocks the edges of a tile, and the matching edges of its neighbours.
+    fn add_walls(&mut self, x: i32, y: i32, plane: usize, edges: WallEdges) {
+        const SIDES: [(WallEdges, i32, i32, WallEdges); 8] = [
+            (WallEdges::NORTH, 0, 1, WallEdges::SOUTH),
+            (WallEdges::EAST, 1, 0, WallEdges::WEST),
+            (WallEdges::SOUTH, 0, -1, WallEdges::NORTH),
+            (WallEdges::WEST, -1, 0, WallEdges::EAST),
+            (WallEdges::NORTH_WEST, -1, 1, WallEdges::SOUTH_EAST),
+            (WallEdges::NORTH_EAST, 1, 1, WallEdges::SOUTH_WEST),
+            (WallEdges::SOUTH_EAST, 1, -1, WallEdges::NORTH_WEST),
+            (WallEdges::SOUTH_WEST, -1, -1, WallEdges::NORTH_EAST),
+        ];
+
+        self.add_edges(x, y, plane, edges);
+        for (edge, dx, dy, opposite) in SIDES {
+            if edges.contains(edge) {
+                self.add_edges(x + dx, y + dy, plane, opposite);
+            }
+        }
+    }
+
+    fn add_edges(&mut self, x: i32, y: i32, plane: usize, edges: WallEdges) {
+        if let Some(index) = Self::checked_index(x, y, plane) {
+            self.walls[index] = self.walls[index] | edges;
+        }
+    }
+
+    /// Blocks a tile, tiles outside of the region are ignored.
+    fn block(&mut self, x: i32, y: i32, plane: usize) {
+        if let Some(index) = Self::checked_index(x, y, plane) {
+            self.blocked[index] = true;
+        }
+    }
+
+    fn checked_index(x: i32, y: i32, plane: usize) -> Option<usize> {
+        let size = REGION_SIZE as i32;
+
+        ((0..size).contains(&x) && (0..size).contains(&y))
+            .then(|| Self::index(x as usize, y as usize, plane))
+    }
+}
+
+/// The plane the clipping of a tile ends up on, one lower below bridges and
+/// `None` for the ground plane below a bridge.
+fn collision_plane(map: &MapDefinition, x: usize, y: usize, plane: usize) -> Option<usize> {
+    let (base_x, base_y) = map.region_base_coords();
+
+    if map.is_bridge(base_x + x as u16, base_y + y as u16) {
+        plane.checked_sub(1)
+    } else {
+        Some(plane)
+    }
+}
@@ -0,0 +1,93 @@
+//! `hexdump -C` style output for eyeballing raw bytes.
+//!
+//! # Example
+//!
+//! ```
+//! use rscache::util::hexdump::HexDump;
+//!
+//! let dump = HexDump::new(b"rs-cache\x00\x01\x02").to_string();
+//!
+//! assert_eq!(
+//!     dump,
+//!     "00000000  72 73 2d 63 61 63 68 65  00 01 02                 |rs-cache...|\n"
+//! );
+//! ```
+
+use std::{borrow::Cow, fmt};
+
+/// Bytes per line unless [configured](HexDump::bytes_per_line) otherwise.
+pub const DEFAULT_BYTES_PER_LINE: usize = 16;
+
+/// Formats bytes as lines of offset, hex and ASCII columns, see the [module docs](self).
+///
+/// Lines get an extra space after every 8 bytes, bytes outside the printable
+/// ASCII range show up as `.` in the ASCII column. Output is written straight
+/// to the formatter.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HexDump<'a> {
+    bytes: Cow<'a, [u8]>,
+    bytes_per_line: usize,
+    max_len: Option<usize>,
+}
+
+impl<'a> HexDump<'a> {
+    pub fn new<T: Into<Cow<'a, [u8]>>>(bytes: T) -> Self {
+        Self {
+            bytes: bytes.into(),
+            bytes_per_line: DEFAULT_BYTES_PER_LINE,
+            max_len: None,
+        }
+    }
+
+    /// Bytes shown on every line, at least 1.
+    pub fn bytes_per_line(mut self, bytes_per_line: usize) -> Self {
+        self.bytes_per_line = bytes_per_line.max(1);
+        self
+    }
+
+    /// Shows only the first `max_len` bytes, followed by a line counting the omitted ones.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shown = self
+            .max_len
+            .map_or(self.bytes.len(), |max_len| max_len.min(self.bytes.len()));
+
+        for (line, chunk) in self.bytes[..shown].chunks(self.bytes_per_line).enumerate() {
+            write!(f, "{:08x} ", line * self.bytes_per_line)?;
+            for column in 0..self.bytes_per_line {
+                if column % 8 == 0 {
+                    f.write_str(" ")?;
+                }
+                match chunk.get(column) {
+                    Some(byte) => write!(f, "{:02x} ", byte)?,
+                    None => f.write_str("   ")?,
+                }
+            }
+
+            f.write_str(" |")?;
+            for &byte in chunk {
+                let printable = byte.is_ascii_graphic() || byte == b' ';
+                fmt::Write::write_char(f, if printable { byte as char } else { '.' })?;
+            }
+            f.write_str("|\n")?;
+        }
+
+        let omitted = self.bytes.len() - shown;
+        if omitted > 0 {
+            writeln!(f, "... {} more bytes", omitted)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
-/// Decompresses chat messages.
-/// 
+use std::io;
+
+/// Compresses and decompresses chat messages.
+///
 /// # Examples
 ///
 /// ```
@@ -9,12 +11,12 @@
 /// # fn main() -> Result<(), rscache::Error> {
 /// # let cache = Cache::new("./data/osrs_cache")?;
 /// let huffman_tbl = cache.huffman_table()?;
-/// let huffman = Huffman::new(&huffman_tbl);
+/// let huffman = Huffman::new(&huffman_tbl)?;
 ///
 /// let compressed_msg = &[174, 128, 35, 32, 208, 96];
 /// let decompressed_len = 8; // client will include this in the chat packet.
 ///
-/// let decompressed_msg = huffman.decompress(compressed_msg, decompressed_len);
+/// let decompressed_msg = huffman.decompress(compressed_msg, decompressed_len)?;
 ///
 /// if let Ok(msg) = String::from_utf8(decompressed_msg) {
 ///     assert_eq!(msg, "rs-cache");
@@ -22,10 +24,31 @@
 /// # Ok(())
 /// # }
 /// ```
+///
+/// Outgoing messages are compressed the same way:
+///
+/// ```
+/// # use rscache::Cache;
+/// use rscache::util::Huffman;
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// # let cache = Cache::new("./data/osrs_cache")?;
+/// let huffman = Huffman::new(cache.huffman_table()?)?;
+///
+/// let compressed_msg = huffman.compress("rs-cache");
+/// assert_eq!(compressed_msg, [174, 128, 35, 32, 208, 96]);
+/// assert_eq!(huffman.decompress(&compressed_msg, 8)?, b"rs-cache");
+/// # Ok(())
+/// # }
+/// ```
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
 pub struct Huffman {
     keys: Vec<i32>,
+    /// Code of every symbol, left aligned.
+    masks: Vec<i32>,
+    /// Code length of every symbol, 0 for symbols without a code.
+    sizes: Vec<u8>,
 }
 
 impl Huffman {
@@ -33,8 +56,14 @@ impl Huffman {
     ///
     /// The sizes can be found in the cache.
     /// Call the [`huffman_table()`](../struct.Cache.html#method.huffman_table) function to get the huffman table which
-    /// contains the sizes needed to initialize this struct.
-    pub fn new(sizes: &[u8]) -> Self {
+    /// contains the sizes needed to initialize this struct, the returned buffer can be passed as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` I/O error when a code is longer than 32 bits or
+    /// the sizes don't describe a valid prefix code.
+    pub fn new<T: AsRef<[u8]>>(sizes: T) -> crate::Result<Self> {
+        let sizes = sizes.as_ref();
         let i_2 = sizes.len();
         let mut masks: Vec<i32> = vec![0; i_2];
         let mut ints_3: Vec<i32> = vec![0; 33];
@@ -43,14 +72,17 @@ impl Huffman {
 
         for i_5 in 0..i_2 {
             let b_6 = sizes[i_5];
+            if b_6 > 32 {
+                return Err(malformed().into());
+            }
             if b_6 != 0 {
-                let i_7 = 1 << (32 - b_6);
+                let i_7 = 1_i32.wrapping_shl(32 - b_6 as u32);
                 let i_8 = ints_3[b_6 as usize];
                 masks[i_5] = i_8;
-                let mut i_9 = 0;
-                let mut i_10 = 0;
-                let mut i_11 = 0;
-                let mut i_12_1 = 0;
+                let i_9;
+                let mut i_10;
+                let mut i_11;
+                let mut i_12_1;
                 if i_8 & i_7 != 0 {
                     i_9 = ints_3[(b_6 - 1) as usize];
                 } else {
@@ -63,7 +95,7 @@ impl Huffman {
                             break;
                         }
 
-                        i_12_1 = 1 << (32 - i_10);
+                        i_12_1 = 1_i32.wrapping_shl(32 - i_10 as u32);
                         if i_11 & i_12_1 != 0 {
                             ints_3[i_10 as usize] = ints_3[(i_10 - 1) as usize];
                             break;
@@ -84,40 +116,81 @@ impl Huffman {
                     i_10 += 1;
                 }
 
-                i_10 = 0;
+                let mut key = 0_usize;
 
                 i_11 = 0;
                 while i_11 < b_6 as i32 {
-                    i_12_1 = ((i32::min_value()) as u32 >> i_11) as i32;
+                    i_12_1 = (i32::MIN as u32 >> i_11) as i32;
                     if i_8 & i_12_1 != 0 {
-                        if keys[i_10 as usize] == 0 {
-                            keys[i_10 as usize] = i_4;
+                        if keys[key] == 0 {
+                            keys[key] = i_4;
                         }
 
-                        i_10 = keys[i_10 as usize];
+                        // a negative key is a leaf, a code can't continue past another one.
+                        key = usize::try_from(keys[key]).map_err(|_| malformed())?;
                     } else {
-                        i_10 += 1;
+                        key += 1;
                     }
 
-                    if i_10 as usize >= keys.len() {
-                        let mut ints_13_vec = vec![0; keys.len() * 2];
-
-                        ints_13_vec[..keys.len()].clone_from_slice(&keys[..]);
-
-                        keys = ints_13_vec;
+                    if key >= keys.len() {
+                        let len = (keys.len() * 2).max(key + 1);
+                        keys.resize(len, 0);
                     }
 
                     i_11 += 1;
                 }
 
-                keys[i_10 as usize] = (!i_5) as i32;
-                if i_10 >= i_4 {
-                    i_4 = i_10 + 1;
+                keys[key] = (!i_5) as i32;
+                if key as i32 >= i_4 {
+                    i_4 = key as i32 + 1;
+                }
+            }
+        }
+
+        Ok(Self {
+            keys,
+            masks,
+            sizes: sizes.to_vec(),
+        })
+    }
+
+    /// Compresses a chat message, packing the codes of its characters most
+    /// significant bit first like the client does.
+    ///
+    /// Characters are encoded as their Latin-1 byte. Characters above `U+00FF`
+    /// and those without a code in the table are dropped, so the
+    /// `decompressed_len` to send along is the number of characters for which
+    /// [`can_encode`](Huffman::can_encode) holds, not `text.len()`.
+    pub fn compress(&self, text: &str) -> Vec<u8> {
+        let mut compressed = Vec::with_capacity(text.len());
+        let mut bit = 0;
+
+        for symbol in text.chars().filter_map(|character| self.symbol(character)) {
+            let mask = self.masks[symbol] as u32;
+            for offset in 0..self.sizes[symbol] {
+                if bit % 8 == 0 {
+                    compressed.push(0);
                 }
+                if mask & (1 << (31 - offset)) != 0 {
+                    compressed[bit / 8] |= 0x80 >> (bit % 8);
+                }
+                bit += 1;
             }
         }
 
-        Self { keys }
+        compressed
+    }
+
+    /// Whether [`compress`](Huffman::compress) keeps the character.
+    #[inline]
+    pub fn can_encode(&self, character: char) -> bool {
+        self.symbol(character).is_some()
+    }
+
+    fn symbol(&self, character: char) -> Option<usize> {
+        let symbol = u8::try_from(character).ok()? as usize;
+
+        (self.sizes.get(symbol).copied().unwrap_or_default() != 0).then_some(symbol)
     }
 
     /// Decompresses the given buffer.
@@ -125,109 +198,45 @@ impl Huffman {
     /// The buffer is normally an encoded chat message which will be decoded into
     /// the original message. This helps limit chat packet sizes.
     ///
-    /// # Panics
+    /// A `decompressed_len` of 0 yields an empty message.
     ///
-    /// Panics if the decompressed length == 0
-    pub fn decompress(&self, compressed: &[u8], decompressed_len: usize) -> Vec<u8> {
-        let mut decompressed = vec![0; decompressed_len];
-
-        let i_2 = 0;
-        let mut i_4 = 0;
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` I/O error when the buffer runs out before
+    /// `decompressed_len` symbols are decoded or holds a code not in the table.
+    pub fn decompress(&self, compressed: &[u8], decompressed_len: usize) -> crate::Result<Vec<u8>> {
+        // every bit decodes at most one symbol.
+        let mut decompressed =
+            Vec::with_capacity(decompressed_len.min(compressed.len().saturating_mul(8)));
         if decompressed_len == 0 {
-            panic!("Huffman decompressed message length can't be 0.");
+            return Ok(decompressed);
         }
-        let mut i_7 = 0;
-        let mut i_8 = i_2;
-
-        loop {
-            if i_4 >= decompressed_len {
-                break;
-            }
-
-            let b_9 = compressed[i_8 as usize];
-            if b_9 > 127 {
-                i_7 = self.keys[i_7 as usize];
-            } else {
-                i_7 += 1;
-            }
-
-            let mut i_10 = 0;
-            i_10_keys(&mut i_10, &self.keys, &mut i_7, &mut i_4, &mut decompressed);
-
-            if b_9 & 0x40 != 0 {
-                i_7 = self.keys[i_7 as usize];
-            } else {
-                i_7 += 1;
-            }
-
-            i_10_keys(&mut i_10, &self.keys, &mut i_7, &mut i_4, &mut decompressed);
-
-            if b_9 & 0x20 != 0 {
-                i_7 = self.keys[i_7 as usize];
-            } else {
-                i_7 += 1;
-            }
-
-            i_10_keys(&mut i_10, &self.keys, &mut i_7, &mut i_4, &mut decompressed);
-
-            if b_9 & 0x10 != 0 {
-                i_7 = self.keys[i_7 as usize];
-            } else {
-                i_7 += 1;
-            }
 
-            i_10_keys(&mut i_10, &self.keys, &mut i_7, &mut i_4, &mut decompressed);
-
-            if b_9 & 0x8 != 0 {
-                i_7 = self.keys[i_7 as usize];
-            } else {
-                i_7 += 1;
-            }
-
-            i_10_keys(&mut i_10, &self.keys, &mut i_7, &mut i_4, &mut decompressed);
-
-            if b_9 & 0x4 != 0 {
-                i_7 = self.keys[i_7 as usize];
-            } else {
-                i_7 += 1;
-            }
-
-            i_10_keys(&mut i_10, &self.keys, &mut i_7, &mut i_4, &mut decompressed);
-
-            if b_9 & 0x2 != 0 {
-                i_7 = self.keys[i_7 as usize];
-            } else {
-                i_7 += 1;
-            }
-
-            i_10_keys(&mut i_10, &self.keys, &mut i_7, &mut i_4, &mut decompressed);
-
-            if b_9 & 0x1 != 0 {
-                i_7 = self.keys[i_7 as usize];
-            } else {
-                i_7 += 1;
+        let mut key = 0_usize;
+        for &byte in compressed {
+            for bit in (0..8).rev() {
+                key = if byte & (1 << bit) != 0 {
+                    let next = *self.keys.get(key).ok_or_else(malformed)?;
+                    usize::try_from(next).map_err(|_| malformed())?
+                } else {
+                    key + 1
+                };
+
+                let value = *self.keys.get(key).ok_or_else(malformed)?;
+                if value < 0 {
+                    decompressed.push((!value) as u8);
+                    if decompressed.len() == decompressed_len {
+                        return Ok(decompressed);
+                    }
+                    key = 0;
+                }
             }
-
-            i_10_keys(&mut i_10, &self.keys, &mut i_7, &mut i_4, &mut decompressed);
-
-            i_8 += 1;
         }
 
-        decompressed
+        Err(malformed().into())
     }
 }
 
-fn i_10_keys(
-    i_10: &mut i32,
-    keys: &[i32],
-    i_7: &mut i32,
-    i_4: &mut usize,
-    decompressed: &mut [u8],
-) {
-    *i_10 = keys[*i_7 as usize];
-    if *i_10 < 0 {
-        decompressed[*i_4] = (!*i_10) as u8;
-        *i_4 += 1;
-        *i_7 = 0;
-    }
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed huffman code")
 }
@@ -0,0 +1,284 @@
+//! Reassembly of instanced regions from their template chunks.
+//!
+//! Instances (player owned houses, raids, ...) aren't stored in the cache.
+//! The server sends a grid of [`ZoneTemplate`]s instead, every one pointing the
+//! client to an 8×8 chunk of a static region and the rotation it's placed at.
+//! [`Region::extract_chunk`] pulls such a chunk out of a region the way the
+//! client does.
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::{
+//!     loader::osrs::{LocationLoader, MapLoader, ObjectLoader},
+//!     util::instance::{Region, ZoneTemplate},
+//! };
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! // the chunk at 3200, 3200 rotated by 90 degrees.
+//! let template = ZoneTemplate::new(0, 400, 400, 1);
+//! assert_eq!(template.region_id(), 12850);
+//!
+//! let keys = [3030157619, 2364842415, 3297319647, 1973582566];
+//! let map = MapLoader::new(&cache).load(template.region_id())?.clone();
+//! let locations = LocationLoader::new(&cache).load(template.region_id(), &keys)?.clone();
+//! let objects = ObjectLoader::new(&cache)?;
+//!
+//! let chunk = Region::new(map, locations).extract_zone(template, &objects);
+//! assert_eq!(chunk.tiles.len(), 8);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    definition::osrs::{LocationDefinition, MapData, MapDefinition},
+    loader::osrs::ObjectLoader,
+};
+
+/// Width and height of an instance in chunks.
+pub const INSTANCE_SIZE: usize = 13;
+/// Width and height of a chunk in tiles.
+pub const CHUNK_SIZE: usize = 8;
+/// Marks a zone of the template grid without a template, `-1` in the client.
+pub const EMPTY_ZONE: u32 = u32::MAX;
+
+/// A chunk of a static region placed in an instance, packed like the client does.
+///
+/// Bits 24-25 hold the plane, 14-23 the chunk x, 3-13 the chunk y and 1-2 the
+/// rotation in clockwise quarter turns. Chunk coordinates are absolute, i.e.
+/// tile coordinates divided by 8.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ZoneTemplate {
+    pub packed: u32,
+}
+
+impl ZoneTemplate {
+    pub const fn new(plane: u8, chunk_x: u16, chunk_y: u16, rotation: u8) -> Self {
+        Self {
+            packed: (plane as u32 & 0x3) << 24
+                | (chunk_x as u32 & 0x3FF) << 14
+                | (chunk_y as u32 & 0x7FF) << 3
+                | (rotation as u32 & 0x3) << 1,
+        }
+    }
+
+    #[inline]
+    pub const fn plane(self) -> u8 {
+        (self.packed >> 24 & 0x3) as u8
+    }
+
+    #[inline]
+    pub const fn chunk_x(self) -> u16 {
+        (self.packed >> 14 & 0x3FF) as u16
+    }
+
+    #[inline]
+    pub const fn chunk_y(self) -> u16 {
+        (self.packed >> 3 & 0x7FF) as u16
+    }
+
+    #[inline]
+    pub const fn rotation(self) -> u8 {
+        (self.packed >> 1 & 0x3) as u8
+    }
+
+    /// Id of the static region holding the chunk, as used by the map and location loaders.
+    #[inline]
+    pub const fn region_id(self) -> u16 {
+        (self.chunk_x() >> 3) << 8 | self.chunk_y() >> 3
+    }
+
+    /// Position of the chunk within its region, both below 8.
+    #[inline]
+    pub const fn local_chunk(self) -> (u8, u8) {
+        ((self.chunk_x() & 0x7) as u8, (self.chunk_y() & 0x7) as u8)
+    }
+}
+
+/// Template grid of an instance, indexed `[plane][zone_x][zone_y]`.
+pub type TemplateGrid = [[[Option<ZoneTemplate>; INSTANCE_SIZE]; INSTANCE_SIZE]; 4];
+
+/// Decodes the packed grid sent by the server, [`EMPTY_ZONE`]s become `None`.
+pub fn decode_template_grid(packed: &[[[u32; INSTANCE_SIZE]; INSTANCE_SIZE]; 4]) -> TemplateGrid {
+    packed.map(|plane| {
+        plane.map(|column| {
+            column.map(|packed| (packed != EMPTY_ZONE).then_some(ZoneTemplate { packed }))
+        })
+    })
+}
+
+/// Every static region the grid places chunks of, in ascending id order.
+pub fn required_regions(grid: &TemplateGrid) -> BTreeSet<u16> {
+    grid.iter()
+        .flatten()
+        .flatten()
+        .flatten()
+        .map(|template| template.region_id())
+        .collect()
+}
+
+/// The map and locations of a static region.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Region {
+    pub map: MapDefinition,
+    pub locations: LocationDefinition,
+}
+
+/// An 8×8 block of tiles of a single plane, rotated into place.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ChunkData {
+    /// Tiles indexed `[x][y]` relative to the chunk's south west corner after
+    /// rotation, overlay rotations are turned along.
+    pub tiles: Vec<Vec<MapData>>,
+    /// Locations whose south west tile lies in the source chunk.
+    pub locations: Vec<ChunkLocation>,
+}
+
+/// A location of a [`ChunkData`], rotated into place.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ChunkLocation {
+    pub id: u32,
+    pub loc_type: u8,
+    pub orientation: u8,
+    /// South west tile relative to the chunk's south west corner. Large
+    /// locations near the edge of the source chunk can end up outside of it,
+    /// so these can be negative or above 7.
+    pub x: i16,
+    pub y: i16,
+}
+
+impl Region {
+    pub fn new(map: MapDefinition, locations: LocationDefinition) -> Self {
+        Self { map, locations }
+    }
+
+    /// Pulls the chunk at `chunk_x`, `chunk_y` of the region out of `plane`,
+    /// rotated by `rotation` clockwise quarter turns.
+    ///
+    /// Location positions account for their size and orientation, sizes are
+    /// looked up in `objects`, unknown objects are treated as 1×1.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the plane is above 3 or the chunk lies outside the region.
+    pub fn extract_chunk(
+        &self,
+        plane: u8,
+        chunk_x: u8,
+        chunk_y: u8,
+        rotation: u8,
+        objects: &ObjectLoader,
+    ) -> ChunkData {
+        assert!(plane < 4, "plane {} out of range", plane);
+        assert!(
+            (chunk_x as usize) < CHUNK_SIZE && (chunk_y as usize) < CHUNK_SIZE,
+            "chunk {}, {} out of range",
+            chunk_x,
+            chunk_y
+        );
+        let base_x = chunk_x as usize * CHUNK_SIZE;
+        let base_y = chunk_y as usize * CHUNK_SIZE;
+        let rotation = rotation & 0x3;
+
+        let mut tiles = vec![vec![MapData::default(); CHUNK_SIZE]; CHUNK_SIZE];
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                let mut tile = self
+                    .map
+                    .map_data(base_x + x, base_y + y, plane as usize)
+                    .clone();
+                if (2..=49).contains(&tile.attr_opcode) {
+                    tile.overlay_rotation = (tile.overlay_rotation + rotation) & 0x3;
+                    tile.attr_opcode = 2 + tile.overlay_path * 4 + tile.overlay_rotation;
+                }
+
+                let (rotated_x, rotated_y) = rotate(x as i16, y as i16, (1, 1), 0, rotation);
+                tiles[rotated_x as usize][rotated_y as usize] = tile;
+            }
+        }
+
+        let locations = self
+            .locations
+            .data
+            .iter()
+            .filter_map(|location| {
+                let (x, y, z) = location.pos;
+                // location positions are stored relative to the region id, not its base tile.
+                let x = x.wrapping_sub(self.locations.region_x) as usize;
+                let y = y.wrapping_sub(self.locations.region_y) as usize;
+                let in_chunk = (base_x..base_x + CHUNK_SIZE).contains(&x)
+                    && (base_y..base_y + CHUNK_SIZE).contains(&y);
+                if z != u16::from(plane) || !in_chunk {
+                    return None;
+                }
+
+                let size = objects.load(location.id).map_or((1, 1), |object| {
+                    (object.model_data.size_x, object.model_data.size_y)
+                });
+                let (x, y) = rotate(
+                    (x - base_x) as i16,
+                    (y - base_y) as i16,
+                    size,
+                    location.orientation,
+                    rotation,
+                );
+
+                Some(ChunkLocation {
+                    id: location.id,
+                    loc_type: location.loc_type,
+                    orientation: (location.orientation + rotation) & 0x3,
+                    x,
+                    y,
+                })
+            })
+            .collect();
+
+        ChunkData { tiles, locations }
+    }
+
+    /// [`extract_chunk`](Self::extract_chunk) with the plane, chunk and rotation of the template.
+    ///
+    /// The template is expected to point into this region.
+    pub fn extract_zone(&self, template: ZoneTemplate, objects: &ObjectLoader) -> ChunkData {
+        let (chunk_x, chunk_y) = template.local_chunk();
+
+        self.extract_chunk(
+            template.plane(),
+            chunk_x,
+            chunk_y,
+            template.rotation(),
+            objects,
+        )
+    }
+}
+
+/// Rotates the south west tile of something `size` large within a chunk,
+/// following the client's `rotateX` and `rotateY`.
+///
+/// Sizes are swapped for odd orientations, as the footprint is turned as well.
+pub const fn rotate(x: i16, y: i16, size: (u8, u8), orientation: u8, rotation: u8) -> (i16, i16) {
+    let (size_x, size_y) = if orientation & 1 == 1 {
+        (size.1 as i16, size.0 as i16)
+    } else {
+        (size.0 as i16, size.1 as i16)
+    };
+    let max = CHUNK_SIZE as i16 - 1;
+
+    match rotation & 0x3 {
+        0 => (x, y),
+        1 => (y, max - x - (size_x - 1)),
+        2 => (max - x - (size_x - 1), max - y - (size_y - 1)),
+        _ => (max - y - (size_y - 1), x),
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::{btree_map, BTreeMap};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::djd2;
+use crate::reference_table::ReferenceTable;
+
+/// Words hashed between two progress reports.
+const CHUNK_LEN: usize = 4096;
+
+/// Known names of archive [name hashes](djd2::hash).
+///
+/// # Examples
+///
+/// ```
+/// # use rscache::Cache;
+/// use rscache::util::{djd2, NameHashTable, NameTransform};
+///
+/// # fn main() -> Result<(), rscache::Error> {
+/// # let cache = Cache::new("./data/osrs_cache")?;
+/// let table = cache.metadata_of(5)?.table;
+/// let unresolved = NameHashTable::new().unresolved(&table);
+///
+/// let mut names = NameHashTable::new();
+/// let words = ["m", "l"].into_iter().map(String::from);
+/// let transforms = [
+///     NameTransform::Digits(60),
+///     NameTransform::Suffix("_".into()),
+///     NameTransform::Digits(60),
+/// ];
+/// names.bruteforce(&unresolved, words, &transforms, |_| {});
+///
+/// assert_eq!(names.get(djd2::hash("m50_50")), Some("m50_50"));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Names are distinct per hash, a name hashing like a name inserted before is
+/// kept aside as a [collision](NameHashTable::collisions).
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct NameHashTable {
+    names: BTreeMap<i32, String>,
+    collisions: BTreeMap<i32, Vec<String>>,
+}
+
+/// Turns a word into more candidate names in [`NameHashTable::bruteforce`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum NameTransform {
+    /// Prepends the string.
+    Prefix(String),
+    /// Appends the string, e.g. `".dat"`.
+    Suffix(String),
+    /// Appends every number from 0 up to and including the given one.
+    Digits(u32),
+}
+
+impl NameTransform {
+    fn apply(&self, candidates: &mut Vec<String>) {
+        let applied: Vec<String> = match self {
+            Self::Prefix(prefix) => candidates
+                .iter()
+                .map(|candidate| format!("{}{}", prefix, candidate))
+                .collect(),
+            Self::Suffix(suffix) => candidates
+                .iter()
+                .map(|candidate| format!("{}{}", candidate, suffix))
+                .collect(),
+            Self::Digits(max) => candidates
+                .iter()
+                .flat_map(|candidate| (0..=*max).map(move |n| format!("{}{}", candidate, n)))
+                .collect(),
+        };
+        candidates.extend(applied);
+    }
+}
+
+impl NameHashTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the name under its hash, returning the hash.
+    ///
+    /// The first name inserted for a hash stays, other names with the same
+    /// hash are only recorded as collisions.
+    pub fn insert<T: Into<String>>(&mut self, name: T) -> i32 {
+        let name = name.into();
+        let hash = djd2::hash(&name);
+        match self.names.entry(hash) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(name);
+            }
+            btree_map::Entry::Occupied(entry) if *entry.get() != name => {
+                let collisions = self.collisions.entry(hash).or_default();
+                if !collisions.contains(&name) {
+                    collisions.push(name);
+                }
+            }
+            btree_map::Entry::Occupied(_) => {}
+        }
+
+        hash
+    }
+
+    /// The name of the hash, same as [`resolve`](Self::resolve).
+    #[inline]
+    pub fn get(&self, hash: i32) -> Option<&str> {
+        self.resolve(hash)
+    }
+
+    /// The first name inserted with the hash.
+    #[inline]
+    pub fn resolve(&self, hash: i32) -> Option<&str> {
+        self.names.get(&hash).map(String::as_str)
+    }
+
+    /// Names hashing like the name [`resolve`](Self::resolve) returns, in
+    /// insertion order, with their hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rscache::util::NameHashTable;
+    ///
+    /// let mut names = NameHashTable::new();
+    /// let hash = names.insert("Aa");
+    /// names.insert("BB");
+    ///
+    /// assert_eq!(names.resolve(hash), Some("Aa"));
+    /// assert_eq!(names.collisions().collect::<Vec<_>>(), [(hash, "BB")]);
+    /// ```
+    pub fn collisions(&self) -> impl Iterator<Item = (i32, &str)> {
+        self.collisions
+            .iter()
+            .flat_map(|(&hash, names)| names.iter().map(move |name| (hash, name.as_str())))
+    }
+
+    /// Number of hashes with a name, collisions aside.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Known names of the table's named archives, by archive id.
+    pub fn names_in<'a>(&'a self, table: &ReferenceTable) -> BTreeMap<u32, &'a str> {
+        table
+            .archives
+            .iter()
+            .filter_map(|archive| Some((archive.id, self.get(archive.name_hash?)?)))
+            .collect()
+    }
+
+    /// Name hashes of the table's archives without a known name, without duplicates.
+    ///
+    /// Unnamed archives in a named table have a hash of 0, the hash of the
+    /// empty string, these are skipped.
+    pub fn unresolved(&self, table: &ReferenceTable) -> Vec<i32> {
+        let mut hashes: Vec<i32> = table
+            .archives
+            .iter()
+            .filter_map(|archive| archive.name_hash)
+            .filter(|&hash| hash != 0 && !self.names.contains_key(&hash))
+            .collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        hashes
+    }
+
+    /// Hashes every word of the wordlist and records the ones matching an
+    /// unresolved hash, returning the number of newly resolved hashes.
+    ///
+    /// Besides the word itself every transform is applied, in order, to all
+    /// candidates so far. `[Prefix("l"), Digits(9)]` tries `word`, `lword`,
+    /// `word0`..`word9` and `lword0`..`lword9`. Words are hashed in parallel
+    /// with the `rayon` feature.
+    ///
+    /// `progress` is called with the total number of candidates tried after
+    /// every few thousand words.
+    pub fn bruteforce<I, F>(
+        &mut self,
+        unresolved: &[i32],
+        wordlist: I,
+        transforms: &[NameTransform],
+        mut progress: F,
+    ) -> usize
+    where
+        I: IntoIterator<Item = String>,
+        F: FnMut(u64),
+    {
+        let mut unresolved = unresolved.to_vec();
+        unresolved.sort_unstable();
+        unresolved.dedup();
+
+        let mut wordlist = wordlist.into_iter();
+        let mut tried = 0;
+        let mut resolved = 0;
+        loop {
+            let chunk: Vec<String> = wordlist.by_ref().take(CHUNK_LEN).collect();
+            if chunk.is_empty() {
+                break;
+            }
+
+            let search = |word: &String| {
+                let mut candidates = vec![word.clone()];
+                for transform in transforms {
+                    transform.apply(&mut candidates);
+                }
+                let len = candidates.len() as u64;
+                let matches = candidates
+                    .into_iter()
+                    .filter(|candidate| unresolved.binary_search(&djd2::hash(candidate)).is_ok())
+                    .collect::<Vec<_>>();
+
+                (len, matches)
+            };
+            #[cfg(feature = "rayon")]
+            let results: Vec<(u64, Vec<String>)> = chunk.par_iter().map(search).collect();
+            #[cfg(not(feature = "rayon"))]
+            let results: Vec<(u64, Vec<String>)> = chunk.iter().map(search).collect();
+
+            for (len, matches) in results {
+                tried += len;
+                for name in matches {
+                    if self.get(djd2::hash(&name)).is_none() {
+                        resolved += 1;
+                    }
+                    self.insert(name);
+                }
+            }
+            progress(tried);
+        }
+
+        resolved
+    }
+}
+
+impl Extend<String> for NameHashTable {
+    fn extend<T: IntoIterator<Item = String>>(&mut self, names: T) {
+        for name in names {
+            self.insert(name);
+        }
+    }
+}
+
+impl FromIterator<String> for NameHashTable {
+    fn from_iter<T: IntoIterator<Item = String>>(names: T) -> Self {
+        let mut table = Self::new();
+        table.extend(names);
+        table
+    }
+}
@@ -0,0 +1,224 @@
+//! Integrity checks of a whole cache, like `fsck`.
+//!
+//! [`Cache::verify`] follows the sector chain of every archive in every index
+//! and collects what is wrong with each one into a [`VerifyReport`], instead of
+//! failing on the first broken archive the way a read does. With
+//! [`VerifyOptions::crcs`] the containers are also checked against the crcs of
+//! their reference tables.
+//!
+//! # Example
+//!
+//! ```
+//! # use rscache::Cache;
+//! use rscache::verify::VerifyOptions;
+//!
+//! # fn main() -> Result<(), rscache::Error> {
+//! # let cache = Cache::new("./data/osrs_cache")?;
+//! let report = cache.verify_with(VerifyOptions::new().crcs(true))?;
+//!
+//! for problem in &report.problems {
+//!     println!("{}", problem);
+//! }
+//! assert!(report.is_ok());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use runefs::REFERENCE_TABLE_ID;
+
+use crate::{codec, dat2::ChainBreak, Cache};
+
+/// Which checks [`Cache::verify_with`] runs on top of the sector chains.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub struct VerifyOptions {
+    crcs: bool,
+}
+
+impl VerifyOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares the crc of every container with its reference table entry.
+    pub fn crcs(mut self, crcs: bool) -> Self {
+        self.crcs = crcs;
+        self
+    }
+}
+
+/// Everything [`Cache::verify`] found, the cache is intact when there are no
+/// problems.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct VerifyReport {
+    /// Number of stored archives checked, reference tables included.
+    pub archives: usize,
+    /// Broken archives by ascending index and archive id.
+    pub problems: Vec<ArchiveProblem>,
+}
+
+impl VerifyReport {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Ids of the indices with at least one problem, in ascending order.
+    pub fn broken_indices(&self) -> Vec<u8> {
+        let mut indices: Vec<u8> = self
+            .problems
+            .iter()
+            .map(|problem| problem.index_id)
+            .collect();
+        indices.dedup();
+
+        indices
+    }
+}
+
+/// What is wrong with a single archive, see [`VerifyReport`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ArchiveProblem {
+    pub index_id: u8,
+    pub archive_id: u32,
+    pub problem: Problem,
+}
+
+impl fmt::Display for ArchiveProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "archive {} in index {}: {}",
+            self.archive_id, self.index_id, self.problem
+        )
+    }
+}
+
+/// Kind of [`ArchiveProblem`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[non_exhaustive]
+pub enum Problem {
+    /// The sector holding `chunk` of the archive can't be parsed or its header
+    /// belongs to another archive, chunk or index.
+    BadSector { sector: usize, chunk: usize },
+    /// The sector chain ends, or runs past the end of `main_file_cache.dat2`,
+    /// before the length the idx file lists.
+    LengthMismatch { expected: usize, actual: usize },
+    /// The container doesn't match the crc of its reference table entry.
+    CrcMismatch { expected: u32, actual: u32 },
+    /// Listed in the reference table but not stored in the idx file.
+    Missing,
+    /// The reference table of index `archive_id`, stored in index 255, can't
+    /// be read. None of the index's archives are checked against it.
+    ReferenceTable(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadSector { sector, chunk } => {
+                write!(f, "bad sector {} holding chunk {}", sector, chunk)
+            }
+            Self::LengthMismatch { expected, actual } => {
+                write!(f, "sectors hold {} of {} bytes", actual, expected)
+            }
+            Self::CrcMismatch { expected, actual } => {
+                write!(f, "crc {} doesn't match {}", actual, expected)
+            }
+            Self::Missing => write!(f, "listed in the reference table but not stored"),
+            Self::ReferenceTable(error) => write!(f, "reference table can't be read: {}", error),
+        }
+    }
+}
+
+pub(crate) fn verify(cache: &Cache, options: VerifyOptions) -> crate::Result<VerifyReport> {
+    let mut report = VerifyReport::default();
+    let mut container = Vec::new();
+
+    for index_id in cache.indices.ids() {
+        let index = cache.indices.get(&index_id).expect("listed index exists");
+        // index 255 has no reference table of its own.
+        let table = if index_id == REFERENCE_TABLE_ID {
+            None
+        } else {
+            match cache.metadata_of(index_id) {
+                Ok(table) => Some(table),
+                // empty indices without a table are fine.
+                Err(error) if index.archive_refs.len() > 0 || is_listed(cache, index_id) => {
+                    report.problems.push(ArchiveProblem {
+                        index_id: REFERENCE_TABLE_ID,
+                        archive_id: index_id as u32,
+                        problem: Problem::ReferenceTable(error.to_string()),
+                    });
+                    None
+                }
+                Err(_) => None,
+            }
+        };
+        let mut problems = Vec::new();
+        let mut problem = |archive_id, problem| {
+            problems.push(ArchiveProblem {
+                index_id,
+                archive_id,
+                problem,
+            })
+        };
+
+        for archive in index.archive_refs.iter() {
+            report.archives += 1;
+            container.clear();
+            let chain = cache.data.follow(archive, |_, data| {
+                if options.crcs {
+                    container.extend_from_slice(data);
+                }
+                Ok(())
+            })?;
+
+            match chain {
+                Err(ChainBreak::Short { read, .. }) => problem(
+                    archive.id,
+                    Problem::LengthMismatch {
+                        expected: archive.length,
+                        actual: read,
+                    },
+                ),
+                Err(ChainBreak::Header { sector, chunk, .. }) => {
+                    problem(archive.id, Problem::BadSector { sector, chunk })
+                }
+                Ok(()) if options.crcs => {
+                    let entry = table
+                        .as_ref()
+                        .and_then(|table| table.table.archive(archive.id));
+                    if let Some(entry) = entry {
+                        let (body, _) = codec::split_trailer(&container);
+                        let actual = crc32fast::hash(body);
+                        if actual != entry.crc {
+                            let expected = entry.crc;
+                            problem(archive.id, Problem::CrcMismatch { expected, actual });
+                        }
+                    }
+                }
+                Ok(()) => {}
+            }
+        }
+
+        for entry in table.iter().flat_map(|table| table.archives()) {
+            if index.archive_refs.get(entry.id).is_none() {
+                problem(entry.id, Problem::Missing);
+            }
+        }
+        report.problems.extend(problems);
+    }
+    report.problems.sort_unstable();
+
+    Ok(report)
+}
+
+/// Whether index 255 stores a reference table for the index.
+fn is_listed(cache: &Cache, index_id: u8) -> bool {
+    cache
+        .indices
+        .get(&REFERENCE_TABLE_ID)
+        .is_some_and(|index| index.archive_refs.get(index_id as u32).is_some())
+}
@@ -0,0 +1,80 @@
+//! Runs every throughput benchmark workload once, see `benches/throughput`.
+
+mod test_util;
+
+#[path = "../benches/throughput/workloads.rs"]
+mod workloads;
+
+use test_util::fixture;
+use workloads::{READ_ARCHIVES, READ_INDEX};
+
+#[test]
+fn open() -> Result<(), rscache::Error> {
+    let fixture = workloads::fixture();
+    let cache = workloads::open(fixture.path())?;
+
+    assert_eq!(cache.archive_count(READ_INDEX), Some(READ_ARCHIVES as usize + 2));
+
+    Ok(())
+}
+
+#[test]
+fn reads() -> Result<(), rscache::Error> {
+    let cache = workloads::fixture().cache();
+    let sequential = workloads::archive_ids(&cache, READ_INDEX)?;
+    let random = workloads::shuffled_archive_ids(&cache, READ_INDEX)?;
+
+    assert_eq!(sequential.len(), READ_ARCHIVES as usize + 2);
+    assert_ne!(sequential, random);
+    assert_eq!(random, workloads::shuffled_archive_ids(&cache, READ_INDEX)?);
+
+    let len = workloads::read_archives(&cache, READ_INDEX, &sequential)?;
+    assert!(len > fixture::RAW_ARCHIVE_LEN + 512 * READ_ARCHIVES as usize);
+    assert_eq!(workloads::read_archives(&cache, READ_INDEX, &random)?, len);
+
+    let raw_len = workloads::read_raw_archives(&cache, READ_INDEX, &sequential)?;
+    assert!(raw_len > 512 * READ_ARCHIVES as usize);
+
+    let small = workloads::single_sector_ids(&cache, READ_INDEX, &sequential)?;
+    assert!(!small.is_empty() && small.len() < sequential.len());
+    let (small_len, borrowed) = workloads::read_ref_archives(&cache, READ_INDEX, &small)?;
+    assert_eq!(small_len, workloads::read_raw_archives(&cache, READ_INDEX, &small)?);
+    if cfg!(not(feature = "safe-io")) {
+        assert_eq!(borrowed, small.len());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn checksum() -> Result<(), rscache::Error> {
+    // the persisted checksum fingerprints the files, keep them on disk.
+    let fixture = workloads::fixture();
+    let cache = fixture.cache();
+
+    assert_eq!(workloads::checksum(&cache)?.iter().count(), 11);
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("checksum.bin");
+    let created = workloads::persisted_checksum(&cache, &path)?;
+    assert_eq!(workloads::persisted_checksum(&cache, &path)?, created);
+
+    Ok(())
+}
+
+#[test]
+fn loaders() -> Result<(), rscache::Error> {
+    let cache = workloads::fixture().cache();
+    let (items, npcs, objects) = workloads::configs(&cache)?;
+
+    assert!(items.load(2).is_some());
+    assert!(npcs.load(1).is_some());
+    assert!(objects.load(1).is_some());
+    assert_eq!(
+        workloads::configs_serial(&cache)?,
+        items.iter().count() + npcs.iter().count() + objects.iter().count()
+    );
+    assert_eq!(workloads::region(&cache)?, 2);
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+mod test_util;
+
+use rscache::{
+    bundle::{self, IndexBundle, BUNDLE_VERSION},
+    error::BundleError,
+    Cache, Error,
+};
+use test_util::fixture::{self, FixtureBuilder};
+
+fn export(cache: &Cache, index_id: u8) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    cache.export_index(index_id, &mut buffer).unwrap();
+    buffer
+}
+
+#[test]
+fn round_trip() -> Result<(), Error> {
+    let source = fixture::fixture();
+    let cache = source.cache();
+    let buffer = export(&cache, 2);
+
+    let target = FixtureBuilder::new().index(0, 5).index(3, 6).build();
+    bundle::import_index(target.path(), buffer.as_slice())?;
+    let imported = target.cache();
+
+    assert_eq!(imported.archive_count(2), cache.archive_count(2));
+    for archive_id in [6, 9, 10] {
+        assert_eq!(
+            imported.read_decoded(2, archive_id)?.as_slice(),
+            cache.read_decoded(2, archive_id)?.as_slice()
+        );
+    }
+    assert_eq!(
+        imported.checksum()?.iter().nth(2),
+        cache.checksum()?.iter().nth(2)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn stable_exports() -> Result<(), Error> {
+    let cache = fixture::fixture().cache();
+
+    for index_id in [0, 2, 5, 7] {
+        assert_eq!(export(&cache, index_id), export(&cache, index_id));
+    }
+    assert_eq!(export(&fixture::fixture().cache(), 2), export(&cache, 2));
+
+    Ok(())
+}
+
+#[test]
+fn overwrite() -> Result<(), Error> {
+    let source = fixture::fixture();
+    let buffer = export(&source.cache(), 5);
+
+    let target = fixture::fixture();
+    let mut cache = target.cache();
+    let checksum = cache.checksum()?;
+    bundle::import_index(target.path(), buffer.as_slice())?;
+    cache.reload()?;
+
+    assert_eq!(cache.checksum()?, checksum);
+    assert_eq!(cache.read_decoded(5, 0)?.len(), fixture::map().len());
+    assert_eq!(cache.read_decoded(10, 0)?.len(), fixture::huffman_sizes().len());
+
+    Ok(())
+}
+
+#[test]
+fn bundle_layout() {
+    let cache = fixture::fixture().cache();
+    let buffer = export(&cache, 2);
+    let bundle = IndexBundle::read_from(buffer.as_slice()).unwrap();
+
+    assert_eq!(&buffer[..4], b"RSIB");
+    assert_eq!(buffer[4], BUNDLE_VERSION);
+    assert_eq!(bundle.index_id, 2);
+    assert_eq!(
+        bundle.archives.iter().map(|archive| archive.id).collect::<Vec<_>>(),
+        [6, 9, 10]
+    );
+    assert_eq!(bundle.parse_reference_table().unwrap().archives.len(), 3);
+
+    let mut written = Vec::new();
+    bundle.write_to(&mut written).unwrap();
+    assert_eq!(written, buffer);
+}
+
+#[test]
+fn invalid_crc() {
+    let cache = fixture::fixture().cache();
+    let mut bundle = IndexBundle::read_from(export(&cache, 2).as_slice()).unwrap();
+    bundle.archives[1].container[6] ^= 0xFF;
+
+    let mut buffer = Vec::new();
+    bundle.write_to(&mut buffer).unwrap();
+
+    assert!(matches!(
+        IndexBundle::read_from(buffer.as_slice()),
+        Err(Error::Bundle(BundleError::InvalidCrc { arc: 9, .. }))
+    ));
+}
+
+#[test]
+fn unknown_archive() {
+    let cache = fixture::fixture().cache();
+    let mut bundle = IndexBundle::read_from(export(&cache, 2).as_slice()).unwrap();
+    bundle.archives[2].id = 11;
+
+    let mut buffer = Vec::new();
+    bundle.write_to(&mut buffer).unwrap();
+
+    assert!(matches!(
+        IndexBundle::read_from(buffer.as_slice()),
+        Err(Error::Bundle(BundleError::UnknownArchive(11)))
+    ));
+}
+
+#[test]
+fn invalid_header() {
+    let cache = fixture::fixture().cache();
+    let buffer = export(&cache, 2);
+
+    let mut magic = buffer.clone();
+    magic[0] = b'X';
+    assert!(matches!(
+        IndexBundle::read_from(magic.as_slice()),
+        Err(Error::Bundle(BundleError::InvalidMagic))
+    ));
+
+    let mut version = buffer.clone();
+    version[4] = BUNDLE_VERSION + 1;
+    assert!(matches!(
+        IndexBundle::read_from(version.as_slice()),
+        Err(Error::Bundle(BundleError::UnsupportedVersion(_)))
+    ));
+
+    assert!(IndexBundle::read_from(&buffer[..buffer.len() - 1]).is_err());
+}
+
+#[test]
+fn missing_index() {
+    let cache = fixture::fixture().cache();
+
+    assert!(cache.export_index(11, Vec::new()).is_err());
+}
+
+#[test]
+fn osrs_maps() {
+    let cache = test_util::osrs_cache();
+    let bundle = IndexBundle::read_from(export(&cache, 5).as_slice()).unwrap();
+
+    assert_eq!(Some(bundle.archives.len()), cache.archive_count(5));
+}
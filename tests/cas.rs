@@ -0,0 +1,142 @@
+#![cfg(feature = "cas")]
+
+mod test_util;
+
+use std::{fs, io, path::Path};
+
+use rscache::{
+    cas::{self, ContentHash, Manifest},
+    Cache,
+};
+use test_util::fixture::{self, ArchiveSpec};
+
+fn store_objects(store: &Path) -> usize {
+    walk(&store.join("objects"))
+}
+
+fn walk(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            if entry.file_type().unwrap().is_dir() {
+                walk(&entry.path())
+            } else {
+                1
+            }
+        })
+        .sum()
+}
+
+#[test]
+fn round_trip() -> Result<(), rscache::Error> {
+    let cache = test_util::osrs_cache();
+    let (store, out) = (tempfile::tempdir()?, tempfile::tempdir()?);
+
+    let manifest = cas::export(&cache, store.path())?;
+    assert_eq!(manifest.indices, (0..=20).collect::<Vec<_>>());
+    cas::restore(&manifest, store.path(), out.path())?;
+
+    let restored = Cache::new(out.path())?;
+    assert_eq!(restored.checksum()?, cache.checksum()?);
+    for entry in &manifest.entries {
+        assert_eq!(
+            restored.read_raw(entry.index_id, entry.archive_id)?,
+            cache.read_raw(entry.index_id, entry.archive_id)?
+        );
+    }
+
+    // restoring twice into the same directory is refused.
+    let result = cas::restore(&manifest, store.path(), out.path());
+    assert!(
+        matches!(result, Err(rscache::Error::Io(error)) if error.kind() == io::ErrorKind::AlreadyExists)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn revisions_share_objects() -> Result<(), rscache::Error> {
+    let mut items = fixture::items();
+    items[1] = items[0].clone();
+    let (old, new) = (
+        fixture::fixture(),
+        fixture::defaults()
+            .archive(2, ArchiveSpec::new(10, items))
+            .build(),
+    );
+    let store = tempfile::tempdir()?;
+
+    let old_manifest = cas::export(&old.cache(), store.path())?;
+    let unique = store_objects(store.path());
+    let new_manifest = cas::export(&new.cache(), store.path())?;
+
+    // the item archive and the reference table of index 2 changed.
+    assert_eq!(store_objects(store.path()), unique + 2);
+    let changed: Vec<_> = old_manifest
+        .entries
+        .iter()
+        .zip(&new_manifest.entries)
+        .filter(|(old, new)| old.hash != new.hash)
+        .map(|(old, _)| (old.index_id, old.archive_id))
+        .collect();
+    assert_eq!(changed, vec![(2, 10), (255, 2)]);
+
+    let out = tempfile::tempdir()?;
+    cas::restore(&old_manifest, store.path(), out.path())?;
+    assert_eq!(Cache::new(out.path())?.checksum()?, old.cache().checksum()?);
+
+    Ok(())
+}
+
+#[test]
+fn manifest_text() -> Result<(), rscache::Error> {
+    let fixture = fixture::fixture();
+    let store = tempfile::tempdir()?;
+    let manifest = cas::export(&fixture.cache(), store.path())?;
+
+    let mut text = Vec::new();
+    manifest.write_to(&mut text)?;
+    assert_eq!(Manifest::read_from(text.as_slice())?, manifest);
+
+    let hash = ContentHash::of(b"rs-cache");
+    assert_eq!(hash.to_string().parse::<ContentHash>()?, hash);
+    assert!("not a hash".parse::<ContentHash>().is_err());
+
+    let malformed = [
+        "",
+        "rs-cache manifest 2\n0\n",
+        "rs-cache manifest 1\n0\n0 1 2\n",
+        "rs-cache manifest 1\nzero\n",
+    ];
+    for text in malformed {
+        let result = Manifest::read_from(text.as_bytes());
+        assert!(
+            matches!(result, Err(rscache::Error::Io(ref error)) if error.kind() == io::ErrorKind::InvalidData),
+            "{:?}",
+            text
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn corrupted_object() -> Result<(), rscache::Error> {
+    let fixture = fixture::fixture();
+    let (store, out) = (tempfile::tempdir()?, tempfile::tempdir()?);
+    let manifest = cas::export(&fixture.cache(), store.path())?;
+
+    let entry = manifest.entries[0];
+    let path = store.path().join(entry.hash.object_path());
+    let mut object = fs::read(&path)?;
+    object[0] ^= 1;
+    fs::write(&path, object)?;
+
+    let result = cas::restore(&manifest, store.path(), out.path());
+    assert!(
+        matches!(result, Err(rscache::Error::Io(error)) if error.kind() == io::ErrorKind::InvalidData)
+    );
+
+    Ok(())
+}
@@ -79,12 +79,545 @@ fn invalid_len() {
     );
 }
 
+#[cfg(feature = "whirlpool")]
+#[test]
+fn whirlpool_digests() {
+    use whirlpool::{Digest, Whirlpool};
+
+    let cache = test_util::osrs_cache();
+    let checksum = cache.checksum_with_len(22).unwrap();
+    let entries: Vec<_> = checksum.iter().collect();
+
+    let table = cache.read(255, 2).unwrap();
+    assert_eq!(entries[2].hash(), Whirlpool::digest(&table).as_slice());
+    // padded like indices without a reference table.
+    assert_eq!(entries[21].hash(), [0; 64]);
+}
+
+mod detailed {
+    use super::*;
+    use rscache::{
+        checksum::UNCHECKED_INDICES,
+        error::{ChecksumMismatch, CrcMismatch},
+    };
+
+    const CRCS: [u32; 21] = [
+        1593884597, 1029608590, 16840364, 4209099954, 3716821437, 165713182, 686540367, 4262755489,
+        2208636505, 3047082366, 586413816, 2890424900, 3411535427, 3178880569, 153718440,
+        3849392898, 3628627685, 2813112885, 1461700456, 2751169400, 2927815226,
+    ];
+
+    #[test]
+    fn matching() {
+        let checksum = test_util::osrs_cache().checksum().unwrap();
+
+        assert_eq!(checksum.validate_detailed(&CRCS), Ok(()));
+    }
+
+    #[test]
+    fn every_mismatch() {
+        let checksum = test_util::osrs_cache().checksum().unwrap();
+        let mut crcs = CRCS;
+        crcs[3] = 4209098954;
+        crcs[20] = 0;
+
+        let mismatch = checksum.validate_detailed(&crcs).unwrap_err();
+        assert!(!mismatch.is_length_mismatch());
+        assert_eq!(
+            mismatch.mismatches,
+            [
+                CrcMismatch {
+                    index_id: 3,
+                    expected: 4209099954,
+                    actual: 4209098954
+                },
+                CrcMismatch {
+                    index_id: 20,
+                    expected: 2927815226,
+                    actual: 0
+                },
+            ]
+        );
+        assert_eq!(
+            mismatch.to_string(),
+            "checksum mismatch, index 3 expected crc 4209099954 but was 4209098954, \
+             index 20 expected crc 2927815226 but was 0"
+        );
+
+        // the error converts for `?`.
+        let error: rscache::Error = mismatch.into();
+        assert!(matches!(error, rscache::Error::Checksum(_)));
+    }
+
+    #[test]
+    fn wrong_length() {
+        let checksum = test_util::osrs_cache().checksum().unwrap();
+
+        let mut crcs = CRCS.to_vec();
+        crcs[1] = 1;
+        crcs.pop();
+        assert_eq!(
+            checksum.validate_detailed(&crcs),
+            Err(ChecksumMismatch {
+                expected_len: 21,
+                actual_len: 20,
+                mismatches: vec![CrcMismatch {
+                    index_id: 1,
+                    expected: 1029608590,
+                    actual: 1
+                }],
+                skipped: Vec::new(),
+            })
+        );
+
+        crcs = CRCS.to_vec();
+        crcs.push(42);
+        let mismatch = checksum.validate_detailed(&crcs).unwrap_err();
+        assert_eq!((mismatch.expected_len, mismatch.actual_len), (21, 22));
+        assert!(mismatch.mismatches.is_empty());
+        assert_eq!(
+            mismatch.to_string(),
+            "checksum mismatch, expected 21 crcs but got 22"
+        );
+    }
+
+    #[test]
+    fn unchecked_indices() {
+        let checksum = test_util::osrs_cache().checksum().unwrap();
+        assert_eq!(UNCHECKED_INDICES, [16, 47]);
+
+        // clients that dropped index 16 send an empty crc.
+        let mut crcs = CRCS;
+        crcs[16] = 0;
+        assert_eq!(checksum.validate_detailed(&crcs), Ok(()));
+
+        crcs[16] = 1;
+        crcs[2] = 0;
+        let mismatch = checksum.validate_detailed(&crcs).unwrap_err();
+        let ids: Vec<_> = mismatch.mismatches.iter().map(|mismatch| mismatch.index_id).collect();
+        assert_eq!(ids, [2, 16]);
+
+        // index 47 is empty on the server side.
+        let checksum = checksum.resized(48);
+        let mut crcs = CRCS.to_vec();
+        crcs.resize(48, 0);
+        crcs[47] = 123;
+        assert_eq!(checksum.validate_detailed(&crcs), Ok(()));
+        crcs[46] = 123;
+        let mismatch = checksum.validate_detailed(&crcs).unwrap_err();
+        assert_eq!(mismatch.skipped, [47]);
+        assert_eq!(mismatch.mismatches.len(), 1);
+    }
+}
+
+mod reference {
+    use super::test_util::fixture;
+    use rscache::checksum::{IndexVerdict, ReferenceEntry};
+
+    fn fixture_reference() -> Vec<ReferenceEntry> {
+        let cache = fixture::fixture().cache();
+
+        cache
+            .checksum()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(index_id, entry)| ReferenceEntry {
+                index_id: index_id as u8,
+                crc: entry.crc(),
+                version: entry.version(),
+            })
+            .collect()
+    }
+
+    fn to_csv(reference: &[ReferenceEntry]) -> String {
+        let mut csv = String::from("index_id,crc,version\n");
+        for entry in reference {
+            csv.push_str(&format!("{},{},{}\n", entry.index_id, entry.crc, entry.version));
+        }
+        csv
+    }
+
+    #[test]
+    fn matching() {
+        let fixture = fixture::fixture();
+        let reference = fixture_reference();
+        let path = fixture.path().join("reference.csv");
+        std::fs::write(&path, to_csv(&reference)).unwrap();
+
+        let report = fixture.cache().verify_against_reference(&path).unwrap();
+
+        assert!(report.is_match());
+        assert_eq!(report.indices.len(), 11);
+        assert_eq!(report.mismatches().count(), 0);
+    }
+
+    #[test]
+    fn off_by_one_crc() {
+        let cache = fixture::fixture().cache();
+        let mut reference = fixture_reference();
+        let expected = ReferenceEntry {
+            crc: reference[2].crc.wrapping_add(1),
+            ..reference[2]
+        };
+        reference[2] = expected;
+
+        let report = cache.checksum().unwrap().matches_reference(&reference);
+        let mismatches: Vec<_> = report.mismatches().collect();
+
+        assert!(!report.is_match());
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].index_id, 2);
+        assert!(matches!(
+            mismatches[0].verdict,
+            IndexVerdict::Mismatch { expected: e, actual } if e == expected && actual.crc + 1 == e.crc
+        ));
+    }
+
+    #[test]
+    fn missing_and_unlisted() {
+        let cache = fixture::fixture().cache();
+        let mut reference = fixture_reference();
+        reference.remove(0);
+        reference.push(ReferenceEntry {
+            index_id: 20,
+            crc: 1,
+            version: 1,
+        });
+
+        let report = cache.checksum().unwrap().matches_reference(&reference);
+
+        assert!(!report.is_match());
+        assert_eq!(report.indices.first().unwrap().verdict, IndexVerdict::Unlisted);
+        assert_eq!(report.indices.last().unwrap().index_id, 20);
+        assert_eq!(report.indices.last().unwrap().verdict, IndexVerdict::Missing);
+        assert_eq!(report.mismatches().count(), 2);
+    }
+
+    #[test]
+    fn malformed_csv() {
+        assert_eq!(
+            ReferenceEntry::read_csv("# build 1\n\n0,1,2\n".as_bytes()).unwrap(),
+            [ReferenceEntry {
+                index_id: 0,
+                crc: 1,
+                version: 2
+            }]
+        );
+        assert!(ReferenceEntry::read_csv("0,1,2\n1,x,2\n".as_bytes()).is_err());
+        assert!(ReferenceEntry::read_csv("0,1,2\n0,1\n".as_bytes()).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json() {
+        let fixture = fixture::fixture();
+        let reference = fixture_reference();
+        let json: Vec<String> = reference
+            .iter()
+            .map(|entry| {
+                format!(
+                    r#"{{"index_id":{},"crc":{},"version":{}}}"#,
+                    entry.index_id, entry.crc, entry.version
+                )
+            })
+            .collect();
+        let path = fixture.path().join("reference.json");
+        std::fs::write(&path, format!("[{}]", json.join(","))).unwrap();
+
+        assert!(fixture.cache().verify_against_reference(&path).unwrap().is_match());
+        assert_eq!(
+            ReferenceEntry::read_json(std::fs::File::open(&path).unwrap()).unwrap(),
+            reference
+        );
+    }
+}
+
+mod update_from {
+    use super::test_util::fixture::{self, ArchiveSpec, FixtureBuilder};
+    use rscache::{bundle, Cache};
+
+    /// Replaces index 2 of the default fixture with a different one, like an editor writing it would.
+    fn rewrite_index_2(cache: &mut Cache, path: &std::path::Path) {
+        let source = FixtureBuilder::new()
+            .index(2, 6)
+            .archive(2, ArchiveSpec::new(10, fixture::items()).named("edited"))
+            .build()
+            .cache();
+        let mut buffer = Vec::new();
+        source.export_index(2, &mut buffer).unwrap();
+
+        bundle::import_index(path, buffer.as_slice()).unwrap();
+        cache.reload().unwrap();
+    }
+
+    #[test]
+    fn matches_full_recompute() {
+        let fixture = fixture::fixture();
+        let mut cache = fixture.cache();
+        let mut checksum = cache.checksum().unwrap();
+        let before = checksum.clone();
+
+        rewrite_index_2(&mut cache, fixture.path());
+        assert_ne!(checksum, cache.checksum().unwrap());
+
+        checksum.update_from(&cache, 2).unwrap();
+        assert_eq!(checksum, cache.checksum().unwrap());
+
+        let changed: Vec<usize> = before
+            .iter()
+            .zip(&checksum)
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(index_id, _)| index_id)
+            .collect();
+        assert_eq!(changed, [2]);
+    }
+
+    #[test]
+    fn unknown_index() {
+        let cache = fixture::fixture().cache();
+        let mut checksum = cache.checksum().unwrap();
+
+        assert!(checksum.update_from(&cache, 200).is_err());
+        assert_eq!(checksum, cache.checksum().unwrap());
+    }
+
+    #[test]
+    fn single_entry() {
+        let fixture = fixture::fixture();
+        let mut cache = fixture.cache();
+        let mut checksum = cache.checksum().unwrap();
+
+        rewrite_index_2(&mut cache, fixture.path());
+        let entry = cache.checksum_entry(2).unwrap();
+        assert_ne!(checksum.iter().nth(2), Some(&entry));
+
+        checksum.update_entry(2, entry).unwrap();
+        assert_eq!(checksum, cache.checksum().unwrap());
+        assert!(cache.checksum_entry(200).is_err());
+        assert!(checksum.update_entry(200, Default::default()).is_err());
+    }
+
+    #[test]
+    fn single_entries_match_full_checksum() {
+        let cache = super::test_util::osrs_cache();
+        let checksum = cache.checksum().unwrap();
+
+        // empty and unlisted indices included.
+        for (index_id, entry) in checksum.iter().enumerate() {
+            assert_eq!(&cache.checksum_entry(index_id as u8).unwrap(), entry);
+        }
+    }
+}
+
+mod persisted {
+    use super::test_util::fixture::{self, ArchiveSpec, Fixture};
+    use rscache::checksum::Checksum;
+    use std::fs;
+
+    /// Overwrites the cache files of `fixture` with those of `source`, like an update would.
+    fn overwrite(fixture: &Fixture, source: &Fixture) {
+        for file in fs::read_dir(source.path()).unwrap() {
+            let file = file.unwrap();
+            fs::copy(file.path(), fixture.path().join(file.file_name())).unwrap();
+        }
+    }
+
+    /// The default fixture with the contents of archive 1 in index 0 reversed,
+    /// which keeps every file the same length.
+    fn modified() -> Fixture {
+        let mut data = fixture::pattern(fixture::RAW_ARCHIVE_LEN);
+        data.reverse();
+
+        fixture::defaults()
+            .archive(
+                0,
+                ArchiveSpec::new(1, vec![data]).compression(runefs::codec::Compression::None),
+            )
+            .build()
+    }
+
+    #[test]
+    fn hit() {
+        let fixture = fixture::fixture();
+        let cache = fixture.cache();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checksum.bin");
+
+        assert_eq!(Checksum::load_persisted(&cache, &path).unwrap(), None);
+        let checksum = Checksum::load_or_create(&cache, &path).unwrap();
+        assert_eq!(checksum, cache.checksum().unwrap());
+
+        let persisted = fs::read(&path).unwrap();
+        assert_eq!(Checksum::load_or_create(&cache, &path).unwrap(), checksum);
+        assert_eq!(fs::read(&path).unwrap(), persisted);
+        assert_eq!(Checksum::load_persisted(&fixture.cache(), &path).unwrap(), Some(checksum));
+    }
+
+    #[test]
+    fn miss_after_modifying_an_archive() {
+        let fixture = fixture::fixture();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checksum.bin");
+        let before = Checksum::load_or_create(&fixture.cache(), &path).unwrap();
+
+        let source = modified();
+        for file in fs::read_dir(source.path()).unwrap() {
+            let file = file.unwrap();
+            let len = |path: std::path::PathBuf| fs::metadata(path).unwrap().len();
+            assert_eq!(len(file.path()), len(fixture.path().join(file.file_name())));
+        }
+        overwrite(&fixture, &source);
+        let cache = fixture.cache();
+
+        assert_eq!(Checksum::load_persisted(&cache, &path).unwrap(), None);
+        let after = Checksum::load_or_create(&cache, &path).unwrap();
+        assert_ne!(after, before);
+        assert_eq!(after, cache.checksum().unwrap());
+        assert_eq!(Checksum::load_persisted(&cache, &path).unwrap(), Some(after));
+    }
+
+    #[test]
+    fn corruption_recreates() {
+        let fixture = fixture::fixture();
+        let cache = fixture.cache();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checksum.bin");
+        let checksum = Checksum::load_or_create(&cache, &path).unwrap();
+        let persisted = fs::read(&path).unwrap();
+
+        let mut flipped = persisted.clone();
+        let middle = flipped.len() / 2;
+        flipped[middle] ^= 1;
+        for corrupted in [flipped, persisted[..persisted.len() - 1].to_vec(), Vec::new()] {
+            fs::write(&path, corrupted).unwrap();
+
+            assert!(Checksum::load_persisted(&cache, &path).is_err());
+            assert_eq!(Checksum::load_or_create(&cache, &path).unwrap(), checksum);
+            assert_eq!(fs::read(&path).unwrap(), persisted);
+        }
+    }
+}
+
+mod length {
+    use super::test_util::fixture;
+    use rscache::checksum::Checksum;
+
+    fn fixture_checksum() -> Checksum {
+        fixture::fixture().cache().checksum().unwrap()
+    }
+
+    #[test]
+    fn truncated() {
+        let checksum = fixture_checksum();
+        assert_eq!(checksum.iter().count(), 11);
+
+        let truncated = checksum.clone().truncated(8);
+        assert_eq!(truncated.iter().count(), 8);
+        assert!(truncated.iter().eq(checksum.iter().take(8)));
+        assert_eq!(truncated.encode_for(rscache::checksum::Protocol::Osrs).unwrap().len(), 5 + 8 * 8);
+
+        assert_eq!(checksum.clone().truncated(11), checksum);
+        assert_eq!(checksum.clone().truncated(21), checksum);
+    }
+
+    #[test]
+    fn padded() {
+        let fixture = fixture::fixture();
+        let cache = fixture.cache();
+        let checksum = cache.checksum().unwrap();
+
+        for count in [0, 8, 11, 21] {
+            let resized = cache.checksum_with_len(count).unwrap();
+            assert_eq!(resized.iter().count(), count);
+            assert_eq!(resized, checksum.clone().resized(count));
+            assert!(resized.iter().take(11).eq(checksum.iter().take(count)));
+            assert!(resized.iter().skip(11).all(|entry| entry.crc() == 0 && entry.version() == 0));
+        }
+        assert_eq!(cache.checksum_with_len(21).unwrap().index_count(), 22);
+    }
+}
+
+mod protocol {
+    use super::test_util::fixture;
+    use rscache::checksum::Protocol;
+
+    #[test]
+    fn osrs_matches_encode() {
+        let checksum = fixture::fixture().cache().checksum().unwrap();
+
+        let buffer = checksum.encode_for(Protocol::Osrs).unwrap();
+        assert_eq!(buffer, checksum.encode().unwrap().finalize());
+    }
+
+    #[test]
+    fn versioned_format() {
+        use rscache::checksum::ChecksumFormat;
+
+        let checksum = fixture::fixture().cache().checksum().unwrap();
+        let legacy = checksum.encode().unwrap().finalize();
+        let same = checksum.encode_versioned(ChecksumFormat::Legacy).unwrap();
+        assert_eq!(same.finalize(), legacy);
+
+        let versioned = checksum
+            .encode_versioned(ChecksumFormat::Versioned)
+            .unwrap()
+            .finalize();
+        let count = checksum.iter().count();
+        assert_eq!(versioned[0], 0);
+        assert_eq!(&versioned[1..5], &(2 + count as u32 * 8).to_be_bytes());
+        assert_eq!(&versioned[5..7], &[ChecksumFormat::VERSIONED, count as u8]);
+        assert_eq!(&versioned[7..], &legacy[5..]);
+
+        let oversized = checksum.resized(256);
+        let versioned = oversized.encode_versioned(ChecksumFormat::Versioned);
+        assert!(versioned.is_err());
+        assert!(oversized.encode_versioned(ChecksumFormat::Legacy).is_ok());
+    }
+
+    #[test]
+    fn encode_borrows_checksum() {
+        let checksum = fixture::fixture().cache().checksum().unwrap();
+        let crcs: Vec<u32> = checksum.iter().map(|entry| entry.crc()).collect();
+
+        let buffer = checksum.encode().unwrap().finalize();
+        assert_eq!(buffer, checksum.encode().unwrap().finalize());
+        assert!(checksum.validate(&crcs).is_ok());
+
+        // an uncompressed container holding the entries.
+        let mut entries = Vec::new();
+        for entry in &checksum {
+            entry.encode_into(&mut entries);
+        }
+        assert_eq!(&buffer[5..], entries.as_slice());
+    }
+
+    #[cfg(feature = "rs3")]
+    #[test]
+    fn rs3_matches_rsa_checksum() {
+        use super::rsa::{EXPONENT, MODULUS};
+        use rscache::checksum::RsaKeys;
+
+        let cache = fixture::fixture().cache();
+        let protocol = Protocol::Rs3(RsaKeys::new(EXPONENT, MODULUS));
+
+        let buffer = cache.checksum().unwrap().encode_for(protocol).unwrap();
+        let legacy = cache
+            .checksum_with(RsaKeys::new(EXPONENT, MODULUS))
+            .unwrap()
+            .encode()
+            .unwrap();
+        assert_eq!(buffer, legacy.finalize());
+    }
+}
+
 #[cfg(all(test, feature = "rs3"))]
 mod rsa {
     use rscache::checksum::{RsaChecksum, RsaKeys};
     use super::test_util;
-    pub const EXPONENT: &'static [u8] = b"5206580307236375668350588432916871591810765290737810323990754121164270399789630501436083337726278206128394461017374810549461689174118305784406140446740993";
-    pub const MODULUS: &'static [u8] = b"6950273013450460376345707589939362735767433035117300645755821424559380572176824658371246045200577956729474374073582306250298535718024104420271215590565201";
+    pub const EXPONENT: &[u8] = b"5206580307236375668350588432916871591810765290737810323990754121164270399789630501436083337726278206128394461017374810549461689174118305784406140446740993";
+    pub const MODULUS: &[u8] = b"6950273013450460376345707589939362735767433035117300645755821424559380572176824658371246045200577956729474374073582306250298535718024104420271215590565201";
 
     #[test]
     fn with_keys() {
@@ -96,4 +629,44 @@ mod rsa {
         assert_eq!(&hash, "118e0146af6cf288630357eec6298c34a2430065");
         assert_eq!(buffer.len(), 4681);
     }
+
+    #[test]
+    fn key_formats() {
+        use num_bigint::BigInt;
+
+        let exponent = BigInt::parse_bytes(EXPONENT, 10).unwrap();
+        let modulus = BigInt::parse_bytes(MODULUS, 10).unwrap();
+        let hex = (exponent.to_str_radix(16), modulus.to_str_radix(16).to_uppercase());
+        let bytes = (exponent.to_bytes_be().1, modulus.to_bytes_be().1);
+        let hash = [0, 1, 2, 3, 4, 5, 6, 7];
+
+        let encrypted = RsaKeys::new(EXPONENT, MODULUS).encrypt(&hash).unwrap();
+        let from_hex = RsaKeys::from_hex(hex.0.as_bytes(), hex.1.as_bytes());
+        assert_eq!(from_hex.encrypt(&hash).unwrap(), encrypted);
+        let from_bytes = RsaKeys::from_be_bytes(&bytes.0, &bytes.1);
+        assert_eq!(from_bytes.encrypt(&hash).unwrap(), encrypted);
+    }
+
+    #[test]
+    fn invalid_keys() {
+        use rscache::error::{ChecksumError, Error};
+
+        let cache = test_util::fixture::fixture().cache();
+        let invalid = [
+            (RsaKeys::new(b"65537x", MODULUS), "exponent"),
+            (RsaKeys::new(EXPONENT, b""), "modulus"),
+            (RsaKeys::new(EXPONENT, b"0"), "modulus"),
+            (RsaKeys::new(EXPONENT, b"-3233"), "modulus"),
+            (RsaKeys::from_hex(b"1000g1", b"ca1"), "exponent"),
+            (RsaKeys::from_be_bytes(EXPONENT, &[0, 0]), "modulus"),
+        ];
+
+        for (keys, key) in invalid {
+            assert_eq!(keys.encrypt(&[1]), Err(ChecksumError::InvalidRsaKey(key)));
+            assert!(matches!(
+                RsaChecksum::with_keys(&cache, keys),
+                Err(Error::ChecksumEncode(ChecksumError::InvalidRsaKey(name))) if name == key
+            ));
+        }
+    }
 }
\ No newline at end of file
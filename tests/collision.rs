@@ -0,0 +1,178 @@
+mod test_util;
+
+use rscache::{
+    definition::osrs::{
+        Location, LocationDefinition, MapData, MapDefinition, ObjectDefinition, Position,
+        WallEdges,
+    },
+    loader::osrs::ObjectLoader,
+    util::collision::CollisionMap,
+};
+
+const REGION_X: u16 = 50;
+const REGION_Y: u16 = 50;
+const BASE: u16 = 3200;
+
+fn object(id: u32, size_x: u8, size_y: u8, interact_type: u8) -> (u32, ObjectDefinition) {
+    let mut object = ObjectDefinition {
+        id,
+        solid: interact_type != 0,
+        interact_type,
+        ..ObjectDefinition::default()
+    };
+    object.model_data.size_x = size_x;
+    object.model_data.size_y = size_y;
+
+    (id, object)
+}
+
+fn objects() -> ObjectLoader {
+    [
+        object(1, 1, 1, 2),
+        object(2, 2, 3, 2),
+        object(3, 1, 1, 0),
+        object(4, 1, 1, 1),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn location(id: u32, loc_type: u8, orientation: u8, x: u16, y: u16, z: u16) -> Location {
+    Location {
+        id,
+        loc_type,
+        orientation,
+        // positions are stored relative to the region id.
+        pos: (REGION_X + x, REGION_Y + y, z),
+    }
+}
+
+fn collision(map: MapDefinition, locations: Vec<Location>) -> CollisionMap {
+    let locations = LocationDefinition {
+        id: REGION_X << 8 | REGION_Y,
+        region_x: REGION_X,
+        region_y: REGION_Y,
+        data: locations,
+    };
+
+    CollisionMap::new(&map, &locations, &objects())
+}
+
+fn map() -> MapDefinition {
+    MapDefinition {
+        region_x: REGION_X,
+        region_y: REGION_Y,
+        data: vec![vec![vec![MapData::default(); 64]; 64]; 4],
+    }
+}
+
+#[test]
+fn blocked_tile_flags() {
+    let mut map = map();
+    map.data[0][3][4].settings = 1;
+    map.data[2][5][6].settings = 1 | 4;
+    let collision = collision(map, Vec::new());
+
+    assert_eq!(collision.region_id(), 12850);
+    assert_eq!(
+        collision.blocked_tiles().collect::<Vec<_>>(),
+        vec![
+            Position::new(BASE + 3, BASE + 4, 0),
+            Position::new(BASE + 5, BASE + 6, 2)
+        ]
+    );
+    assert!(collision.is_blocked(BASE + 3, BASE + 4, 0));
+    assert!(!collision.is_blocked(BASE + 3, BASE + 4, 1));
+    assert!(!collision.is_blocked(BASE + 64 + 3, BASE + 4, 0));
+}
+
+#[test]
+fn bridges_move_clipping_down() {
+    let mut map = map();
+    map.data[1][10][10].settings = 2;
+    map.data[0][10][10].settings = 1;
+    map.data[1][10][11].settings = 2;
+    map.data[2][10][11].settings = 1;
+    let collision = collision(map, vec![location(1, 10, 0, 11, 10, 1)]);
+
+    // plane 0 below the bridge drops its clipping, plane 2 above it lands on plane 1.
+    assert_eq!(
+        collision.blocked_tiles().collect::<Vec<_>>(),
+        vec![
+            Position::new(BASE + 10, BASE + 11, 1),
+            Position::new(BASE + 11, BASE + 10, 1)
+        ]
+    );
+}
+
+#[test]
+fn rotated_object_footprint() {
+    let collision = collision(map(), vec![location(2, 10, 1, 62, 20, 0)]);
+
+    // 2×3 turned into 3×2, the column past the region's edge is dropped.
+    assert_eq!(
+        collision.blocked_tiles().collect::<Vec<_>>(),
+        vec![
+            Position::new(BASE + 62, BASE + 20, 0),
+            Position::new(BASE + 62, BASE + 21, 0),
+            Position::new(BASE + 63, BASE + 20, 0),
+            Position::new(BASE + 63, BASE + 21, 0),
+        ]
+    );
+}
+
+#[test]
+fn walls_block_both_sides() {
+    let collision = collision(
+        map(),
+        vec![location(1, 0, 0, 8, 8, 0), location(1, 3, 1, 20, 20, 0)],
+    );
+
+    assert_eq!(collision.blocked_tiles().count(), 0);
+    assert_eq!(collision.walls(BASE + 8, BASE + 8, 0), WallEdges::WEST);
+    assert_eq!(collision.walls(BASE + 7, BASE + 8, 0), WallEdges::EAST);
+    assert_eq!(collision.walls(BASE + 20, BASE + 20, 0), WallEdges::NORTH_EAST);
+    assert_eq!(collision.walls(BASE + 21, BASE + 21, 0), WallEdges::SOUTH_WEST);
+}
+
+#[test]
+fn non_solid_objects_and_floor_decoration() {
+    let collision = collision(
+        map(),
+        vec![
+            location(3, 10, 0, 1, 1, 0),
+            location(1, 22, 0, 2, 2, 0),
+            location(4, 22, 0, 3, 3, 0),
+            location(1, 4, 0, 4, 4, 0),
+            location(99, 10, 0, 5, 5, 0),
+        ],
+    );
+
+    assert_eq!(
+        collision.blocked_tiles().collect::<Vec<_>>(),
+        vec![Position::new(BASE + 3, BASE + 3, 0)]
+    );
+    assert!(collision.walls(BASE + 4, BASE + 4, 0).is_empty());
+}
+
+#[test]
+fn lumbridge_kitchen() -> Result<(), rscache::Error> {
+    use rscache::loader::osrs::{LocationLoader, MapLoader};
+
+    let cache = test_util::osrs_cache();
+    let keys = [3030157619, 2364842415, 3297319647, 1973582566];
+    let map = MapLoader::new(&cache).load(12850)?.clone();
+    let locations = LocationLoader::new(&cache).load(12850, &keys)?.clone();
+    let objects = ObjectLoader::new(&cache)?;
+
+    let collision = CollisionMap::new(&map, &locations, &objects);
+
+    // the kitchen's east wall, with the range against it.
+    assert_eq!(collision.walls(3213, 3215, 0), WallEdges::WEST);
+    assert!(collision.walls(3212, 3215, 0).contains(WallEdges::EAST));
+    assert!(collision.is_blocked(3212, 3215, 0));
+    assert!(!collision.is_blocked(3214, 3215, 0));
+    assert_eq!(collision.blocked_tiles().count(), 1808);
+
+    Ok(())
+}
@@ -0,0 +1,308 @@
+mod test_util;
+
+use std::fs;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "rs3")]
+use rscache::definition::rs3;
+use rscache::{
+    codec,
+    definition::osrs::{
+        AreaDefinition, ComponentDefinition, DbRowDefinition, DbTableDefinition, Definition,
+        EnumDefinition, HealthBarDefinition, HitSplatDefinition, IdentityKitDefinition,
+        InventoryDefinition, ItemDefinition, LocationDefinition, MapDefinition, ModelDefinition,
+        NpcDefinition, ObjectDefinition, OverlayDefinition, ParamDefinition, SequenceDefinition,
+        SpotAnimDefinition, SpriteDefinition, StructDefinition, UnderlayDefinition,
+        VarbitDefinition, WorldMapDefinition,
+    },
+    loader::osrs::{
+        InterfaceLoader, ItemLoader, LocationLoader, MapLoader, NpcLoader, ObjectLoader,
+    },
+    reference_table::ReferenceTable,
+    error::ReadError,
+    util::{self, Huffman},
+    Cache, Error,
+};
+use runefs::MAIN_DATA;
+use test_util::fixture::{self, Fixture, LOCATION_KEYS, REGION_ID};
+
+const ROUNDS: u64 = 32;
+
+/// Runs every public entry point over the cache, results are ignored, only panics fail.
+fn exercise(cache: &Cache) {
+    for index_id in 0..=10 {
+        let _ = cache.read(index_id, 0);
+        let _ = cache.read_decoded(index_id, 1);
+    }
+    let _ = cache.read_decoded(2, 10);
+    let _ = cache.read(7, fixture::EXPANDED_ARCHIVE_ID);
+    let _ = cache.archive_placement(0, 1);
+    let _ = cache.huffman_table();
+    let _ = cache.archive_metadata(2, 10);
+
+    let _ = ItemLoader::new(cache);
+    let _ = NpcLoader::new(cache);
+    let _ = ObjectLoader::new(cache);
+    let _ = MapLoader::new(cache).load(REGION_ID);
+    let _ = LocationLoader::new(cache).load(REGION_ID, &LOCATION_KEYS);
+    if let Ok(mut interfaces) = InterfaceLoader::new(cache) {
+        let _ = interfaces.load(0);
+    }
+}
+
+fn mutate(rng: &mut StdRng, buffer: &mut [u8], flips: usize) {
+    if buffer.is_empty() {
+        return;
+    }
+    for _ in 0..flips {
+        let index = rng.gen_range(0..buffer.len());
+        buffer[index] = rng.gen();
+    }
+}
+
+fn random_bytes(rng: &mut StdRng, max_len: usize) -> Vec<u8> {
+    let len = rng.gen_range(0..max_len);
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+fn mutated_fixture(rng: &mut StdRng, file: &str) -> Fixture {
+    let fixture = fixture::fixture();
+    let path = fixture.path().join(file);
+    let mut buffer = fs::read(&path).unwrap();
+    let flips = rng.gen_range(1..16);
+    mutate(rng, &mut buffer, flips);
+    fs::write(path, buffer).unwrap();
+
+    fixture
+}
+
+mod cache {
+    use super::*;
+
+    #[test]
+    fn idx_entry_past_data_file() -> Result<(), rscache::Error> {
+        let fixture = fixture::fixture();
+        let path = fixture.path().join("main_file_cache.idx2");
+        let mut idx = fs::read(&path)?;
+        // archive 10 claims 64 KiB starting at a sector far past the end of the data file.
+        idx[60..66].copy_from_slice(&[0x01, 0x00, 0x00, 0x0F, 0xFF, 0xFF]);
+        fs::write(path, idx)?;
+
+        let cache = Cache::new(fixture.path())?;
+        assert!(matches!(
+            cache.read(2, 10),
+            Err(Error::Read(ReadError::LengthMismatch { actual: 0, .. }))
+        ));
+        assert!(ItemLoader::new(&cache).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_data_file() {
+        let fixture = fixture::fixture();
+        let path = fixture.path().join(MAIN_DATA);
+        let buffer = fs::read(&path).unwrap();
+        fs::write(&path, &buffer[..buffer.len() / 2]).unwrap();
+
+        if let Ok(cache) = Cache::new(fixture.path()) {
+            exercise(&cache);
+        }
+    }
+
+    #[test]
+    fn mutated_data_file() {
+        let mut rng = StdRng::seed_from_u64(0x5EED);
+        for _ in 0..ROUNDS {
+            let fixture = mutated_fixture(&mut rng, MAIN_DATA);
+            if let Ok(cache) = Cache::new(fixture.path()) {
+                exercise(&cache);
+            }
+        }
+    }
+
+    #[test]
+    fn mutated_idx_files() {
+        let mut rng = StdRng::seed_from_u64(0x1DC5);
+        for round in 0..ROUNDS {
+            let file = if round % 2 == 0 {
+                "main_file_cache.idx255"
+            } else {
+                "main_file_cache.idx2"
+            };
+            let fixture = mutated_fixture(&mut rng, file);
+            if let Ok(cache) = Cache::new(fixture.path()) {
+                exercise(&cache);
+            }
+        }
+    }
+}
+
+mod containers {
+    use super::*;
+
+    #[test]
+    fn oversized_decompressed_len() {
+        let mut container = vec![1, 0, 0, 0, 4];
+        container.extend(u32::MAX.to_be_bytes());
+        container.extend(b"BZh9");
+
+        assert!(codec::decode(&container).is_err());
+    }
+
+    #[test]
+    fn compressed_len_past_end() {
+        assert!(codec::decode(&[0, 0, 0, 1, 0, 1, 2]).is_err());
+        assert!(codec::decode(&[2, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 1]).is_err());
+        assert!(codec::decode(&[1, 0, 0, 0, 1, 0, 0, 0, 1, 0]).is_err());
+    }
+
+    #[test]
+    fn truncated_containers() {
+        let cache = fixture::fixture().cache();
+        for (index_id, archive_id) in [(0, 2), (2, 10), (5, 0), (255, 2)] {
+            let container = cache.read(index_id, archive_id).unwrap();
+            for len in 0..container.len().min(64) {
+                let _ = codec::decode(&container[..len]);
+            }
+        }
+    }
+
+    #[test]
+    fn mutated_containers() {
+        let cache = fixture::fixture().cache();
+        let mut rng = StdRng::seed_from_u64(0xC0DE);
+        for (index_id, archive_id) in [(0, 2), (2, 10), (5, 0), (5, 1), (255, 2)] {
+            let container = cache.read(index_id, archive_id).unwrap();
+            for _ in 0..ROUNDS {
+                let mut buffer = container.to_vec();
+                mutate(&mut rng, &mut buffer, 4);
+                let _ = codec::decode(&buffer);
+                let _ = codec::decode_with_keys(&buffer, &LOCATION_KEYS);
+            }
+        }
+    }
+
+    #[test]
+    fn random_containers() {
+        let mut rng = StdRng::seed_from_u64(0xBAD);
+        for _ in 0..ROUNDS * 4 {
+            let _ = codec::decode(&random_bytes(&mut rng, 64));
+        }
+    }
+}
+
+mod parsers {
+    use super::*;
+
+    fn definitions(buffer: &[u8]) {
+        let _ = ItemDefinition::new(0, buffer);
+        let _ = NpcDefinition::new(0, buffer);
+        let _ = ObjectDefinition::new(0, buffer);
+        let _ = MapDefinition::new(REGION_ID as u32, buffer);
+        let _ = LocationDefinition::new(REGION_ID as u32, buffer);
+        let _ = ComponentDefinition::new(0, buffer);
+        let _ = EnumDefinition::new(0, buffer);
+        let _ = VarbitDefinition::new(0, buffer);
+        let _ = StructDefinition::new(0, buffer);
+        let _ = ParamDefinition::new(0, buffer);
+        let _ = UnderlayDefinition::new(0, buffer);
+        let _ = OverlayDefinition::new(0, buffer);
+        let _ = AreaDefinition::new(0, buffer);
+        let _ = SequenceDefinition::new(0, buffer);
+        let _ = SpotAnimDefinition::new(0, buffer);
+        let _ = InventoryDefinition::new(0, buffer);
+        let _ = IdentityKitDefinition::new(0, buffer);
+        let _ = HitSplatDefinition::new(0, buffer);
+        let _ = HealthBarDefinition::new(0, buffer);
+        let _ = DbTableDefinition::new(0, buffer);
+        let _ = DbRowDefinition::new(0, buffer);
+        let _ = WorldMapDefinition::new(0, buffer);
+        let _ = SpriteDefinition::decode(0, buffer);
+        let _ = ModelDefinition::new(0, buffer);
+        #[cfg(feature = "rs3")]
+        let _ = <rs3::ItemDefinition as rs3::Definition>::new(0, buffer);
+    }
+
+    #[test]
+    fn reference_tables() {
+        let cache = fixture::fixture().cache();
+        let mut rng = StdRng::seed_from_u64(0x7AB1E);
+        for index_id in [0, 2, 5, 7, 10] {
+            let table = cache.read_decoded(255, index_id).unwrap().to_vec();
+            for len in 0..table.len() {
+                let _ = ReferenceTable::parse(&table[..len]);
+            }
+            for _ in 0..ROUNDS {
+                let mut buffer = table.clone();
+                mutate(&mut rng, &mut buffer, 4);
+                let _ = ReferenceTable::parse(&buffer);
+            }
+        }
+    }
+
+    #[test]
+    fn mutated_definitions() {
+        let mut rng = StdRng::seed_from_u64(0xDEF);
+        let mut buffers = fixture::items();
+        buffers.extend(fixture::npcs());
+        buffers.extend(fixture::objects());
+        buffers.push(fixture::map());
+        buffers.push(fixture::locations());
+
+        for buffer in buffers {
+            for len in 0..buffer.len() {
+                definitions(&buffer[..len]);
+            }
+            for _ in 0..ROUNDS {
+                let mut buffer = buffer.clone();
+                mutate(&mut rng, &mut buffer, 2);
+                definitions(&buffer);
+            }
+        }
+    }
+
+    #[test]
+    fn random_definitions() {
+        let mut rng = StdRng::seed_from_u64(0xF00D);
+        for _ in 0..ROUNDS * 4 {
+            definitions(&random_bytes(&mut rng, 128));
+        }
+    }
+
+    #[test]
+    fn groups() {
+        let mut rng = StdRng::seed_from_u64(0x6209);
+        assert!(util::split_group(&[], 2).is_err());
+        assert!(util::split_group(&[0], 2).is_err());
+        assert!(util::split_group(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0, 1], 2).is_err());
+
+        for _ in 0..ROUNDS * 4 {
+            let buffer = random_bytes(&mut rng, 64);
+            let _ = util::split_group(&buffer, rng.gen_range(0..8));
+        }
+    }
+
+    #[test]
+    fn huffman() {
+        let mut rng = StdRng::seed_from_u64(0x4FF);
+        assert!(Huffman::new([33]).is_err());
+
+        let huffman = Huffman::new(fixture::huffman_sizes()).unwrap();
+        assert_eq!(huffman.decompress(b"rs", 0).unwrap(), b"");
+        assert!(huffman.decompress(b"rs", 3).is_err());
+
+        for _ in 0..ROUNDS {
+            let sizes: Vec<u8> = (0..256).map(|_| rng.gen_range(0..=32)).collect();
+            if let Ok(huffman) = Huffman::new(&sizes) {
+                let _ = huffman.decompress(&random_bytes(&mut rng, 16), rng.gen_range(0..32));
+            }
+
+            let mut sizes = fixture::huffman_sizes();
+            mutate(&mut rng, &mut sizes, 4);
+            if let Ok(huffman) = Huffman::new(&sizes) {
+                let _ = huffman.decompress(&random_bytes(&mut rng, 16), rng.gen_range(0..32));
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+mod test_util;
+
+use rscache::diff::{ArchiveChange, IndexDiff};
+use test_util::fixture::{self, ArchiveSpec, FixtureBuilder};
+
+fn base() -> FixtureBuilder {
+    FixtureBuilder::new()
+        .index(0, 5)
+        .archive(0, ArchiveSpec::new(1, vec![fixture::pattern(100)]))
+        .index(2, 6)
+        .archive(2, ArchiveSpec::new(6, fixture::objects()))
+        .archive(2, ArchiveSpec::new(9, fixture::npcs()))
+        .archive(2, ArchiveSpec::new(10, fixture::items()))
+        .index(3, 6)
+        .archive(3, ArchiveSpec::new(0, vec![fixture::pattern(10)]))
+}
+
+#[test]
+fn same_cache() -> Result<(), rscache::Error> {
+    let cache = fixture::fixture().cache();
+
+    assert!(cache.diff(&fixture::fixture().cache())?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn added_removed_and_modified() -> Result<(), rscache::Error> {
+    let mut objects = ArchiveSpec::new(6, fixture::objects());
+    objects.revision = 2;
+    let new = FixtureBuilder::new()
+        .index(0, 5)
+        .archive(0, ArchiveSpec::new(1, vec![fixture::pattern(100)]))
+        .index(2, 6)
+        .revision(2, 2)
+        .archive(2, objects)
+        .archive(2, ArchiveSpec::new(10, fixture::npcs()))
+        .archive(2, ArchiveSpec::new(11, fixture::items()))
+        .index(4, 7)
+        .archive(4, ArchiveSpec::new(5, vec![fixture::pattern(10)]))
+        .build()
+        .cache();
+    let old = base().build().cache();
+
+    let diff = old.diff(&new)?;
+    let ids: Vec<u8> = diff.indices.iter().map(|index| index.index_id).collect();
+    assert_eq!(ids, [2, 3, 4]);
+    assert_eq!(diff.index(0), None);
+
+    let configs = diff.index(2).unwrap();
+    assert_eq!(
+        (configs.old_revision, configs.new_revision),
+        (Some(1), Some(2))
+    );
+    assert_eq!(configs.added, [11]);
+    assert_eq!(configs.removed, [9]);
+    let crc =
+        |cache: &rscache::Cache, archive_id| cache.archive_metadata(2, archive_id).unwrap().crc;
+    assert_eq!(
+        configs.modified,
+        [
+            // same data, bumped version.
+            ArchiveChange {
+                archive_id: 6,
+                old_crc: crc(&old, 6),
+                new_crc: crc(&old, 6),
+                old_version: 1,
+                new_version: 2,
+            },
+            ArchiveChange {
+                archive_id: 10,
+                old_crc: crc(&old, 10),
+                new_crc: crc(&new, 10),
+                old_version: 1,
+                new_version: 1,
+            },
+        ]
+    );
+
+    assert_eq!(
+        diff.index(3),
+        Some(&IndexDiff {
+            index_id: 3,
+            old_revision: Some(1),
+            new_revision: None,
+            removed: vec![0],
+            ..IndexDiff::default()
+        })
+    );
+    assert_eq!(diff.index(4).unwrap().added, [5]);
+
+    let reverse = new.diff(&old)?;
+    assert_eq!(reverse.index(2).unwrap().added, [9]);
+    assert_eq!(reverse.index(4).unwrap().removed, [5]);
+
+    Ok(())
+}
+
+#[test]
+fn revision_only() -> Result<(), rscache::Error> {
+    let old = base().build().cache();
+    let new = base().revision(3, 2).build().cache();
+
+    let diff = new.diff(&old)?;
+    let index = diff.index(3).unwrap();
+    assert!(!index.has_archive_changes());
+    assert_eq!((index.old_revision, index.new_revision), (Some(2), Some(1)));
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json() -> Result<(), rscache::Error> {
+    let old = base().build().cache();
+    let new = base().revision(2, 2).build().cache();
+    let diff = old.diff(&new)?;
+
+    let json = serde_json::to_string(&diff).unwrap();
+    assert!(json.contains(r#""old_revision":1,"new_revision":2"#));
+    assert_eq!(
+        serde_json::from_str::<rscache::diff::CacheDiff>(&json).unwrap(),
+        diff
+    );
+
+    Ok(())
+}
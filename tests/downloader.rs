@@ -0,0 +1,221 @@
+#![cfg(feature = "net")]
+
+mod test_util;
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+};
+
+use rscache::{
+    codec::{self, Compression},
+    downloader::{Downloader, Progress, HANDSHAKE, PRIORITY_REQUEST},
+    error::DownloadError,
+    Cache,
+};
+use test_util::fixture::{self, ArchiveSpec, FixtureBuilder};
+
+const REVISION: u32 = 221;
+
+/// Serves a cache over an in-memory js5 connection.
+#[derive(Debug)]
+struct MockServer {
+    cache: Cache,
+    received: Vec<u8>,
+    responses: VecDeque<u8>,
+    handshaken: bool,
+    /// Flips a byte of every response to this request.
+    tamper: Option<(u8, u16)>,
+}
+
+impl MockServer {
+    fn new(cache: Cache) -> Self {
+        Self {
+            cache,
+            received: Vec::new(),
+            responses: VecDeque::new(),
+            handshaken: false,
+            tamper: None,
+        }
+    }
+}
+
+impl Read for MockServer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.responses.read(buf)
+    }
+}
+
+impl Write for MockServer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.received.extend(buf);
+
+        if !self.handshaken && self.received.len() >= 5 {
+            let revision = u32::from_be_bytes(self.received[1..5].try_into().unwrap());
+            assert_eq!(self.received[0], HANDSHAKE);
+            self.responses
+                .push_back(if revision == REVISION { 0 } else { 6 });
+            self.received.drain(..5);
+            self.handshaken = true;
+        }
+        while self.handshaken && self.received.len() >= 4 {
+            let request: Vec<u8> = self.received.drain(..4).collect();
+            if request[0] != PRIORITY_REQUEST {
+                continue;
+            }
+
+            let archive_id = u16::from_be_bytes([request[2], request[3]]);
+            let mut response = self
+                .cache
+                .build_js5_response(request[1], archive_id as u32, true)
+                .unwrap();
+            if self.tamper == Some((request[1], archive_id)) {
+                *response.last_mut().unwrap() ^= 1;
+            }
+            self.responses.extend(response);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The default fixture without its expanded archive, which js5 can't serve, and
+/// without a reference table for index 3.
+fn served() -> fixture::Fixture {
+    served_builder().build()
+}
+
+fn served_builder() -> FixtureBuilder {
+    FixtureBuilder::new()
+        .index(0, 5)
+        .archive(
+            0,
+            ArchiveSpec::new(1, vec![fixture::pattern(fixture::RAW_ARCHIVE_LEN)])
+                .compression(Compression::None),
+        )
+        .archive(
+            0,
+            ArchiveSpec::new(2, vec![fixture::pattern(100)]).compression(Compression::Bzip2),
+        )
+        .index(1, 5)
+        .index(2, 6)
+        .archive(2, ArchiveSpec::new(6, fixture::objects()))
+        .archive(2, ArchiveSpec::new(9, fixture::npcs()))
+        .archive(2, ArchiveSpec::new(10, fixture::items()))
+        .index(4, 7)
+        .archive(
+            4,
+            ArchiveSpec::new(1, vec![fixture::locations()]).keys(fixture::LOCATION_KEYS),
+        )
+}
+
+fn downloader(cache: Cache) -> Downloader<MockServer> {
+    Downloader::handshake(MockServer::new(cache), REVISION).unwrap()
+}
+
+#[test]
+fn downloads_every_archive() -> Result<(), rscache::Error> {
+    let source = served().cache();
+    let out = tempfile::tempdir()?;
+
+    let mut progress = Vec::new();
+    let report = downloader(served().cache()).download(out.path(), |p| progress.push(p))?;
+    assert_eq!((report.downloaded, report.skipped), (4 + 6, 0));
+
+    let cache = Cache::new(out.path())?;
+    assert_eq!(cache.checksum()?, source.checksum()?);
+    for (index_id, archive_id) in [(0, 1), (0, 2), (2, 6), (2, 9), (2, 10), (4, 1)] {
+        let container = cache.read_raw(index_id, archive_id)?;
+        let (body, version) = codec::split_trailer(&container);
+        assert_eq!(body, source.read_raw(index_id, archive_id)?);
+        assert_eq!(version, Some(1));
+    }
+
+    let last = progress.iter().rfind(|p| p.index_id == 2).unwrap();
+    assert_eq!((last.done, last.total), (3, 3));
+    assert!(progress.iter().all(|p| p.downloaded));
+
+    Ok(())
+}
+
+#[test]
+fn resumes_up_to_date_archives() -> Result<(), rscache::Error> {
+    let out = tempfile::tempdir()?;
+    downloader(served().cache()).download(out.path(), |_| {})?;
+    let dat2_len = std::fs::metadata(out.path().join("main_file_cache.dat2"))?.len();
+
+    let mut progress = Vec::new();
+    let report = downloader(served().cache()).download(out.path(), |p| progress.push(p))?;
+
+    assert_eq!((report.downloaded, report.skipped), (0, 4 + 6));
+    assert!(progress.iter().all(|p: &Progress| !p.downloaded));
+    let resumed_len = std::fs::metadata(out.path().join("main_file_cache.dat2"))?.len();
+    assert_eq!(resumed_len, dat2_len);
+
+    Ok(())
+}
+
+#[test]
+fn progress_bytes_from_sizes() -> Result<(), rscache::Error> {
+    let source = served_builder().sizes(2).build().cache();
+    let out = tempfile::tempdir()?;
+    let compressed: Vec<u64> = [6, 9, 10]
+        .iter()
+        .map(|&archive_id| source.read_raw(2, archive_id).map(|c| c.len() as u64))
+        .collect::<Result<_, _>>()?;
+    let total = compressed.iter().sum::<u64>();
+
+    let mut progress = Vec::new();
+    downloader(served_builder().sizes(2).build().cache())
+        .download(out.path(), |p| progress.push(p))?;
+
+    let bytes: Vec<_> = progress
+        .iter()
+        .filter(|p| p.index_id == 2)
+        .map(|p| (p.done_bytes, p.total_bytes))
+        .collect();
+    let mut done = 0;
+    for (&(done_bytes, total_bytes), size) in bytes.iter().zip(&compressed) {
+        done += size;
+        assert_eq!((done_bytes, total_bytes), (Some(done), Some(total)));
+    }
+    assert_eq!(bytes.len(), 3);
+    // index 0 has no sizes in its reference table.
+    assert!(progress
+        .iter()
+        .filter(|p| p.index_id == 0)
+        .all(|p| (p.done_bytes, p.total_bytes) == (None, None)));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_crc() {
+    let out = tempfile::tempdir().unwrap();
+    let mut server = MockServer::new(served().cache());
+    server.tamper = Some((2, 9));
+
+    let error = Downloader::handshake(server, REVISION)
+        .unwrap()
+        .download(out.path(), |_| {})
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        rscache::Error::Download(DownloadError::InvalidCrc { idx: 2, arc: 9, .. })
+    ));
+}
+
+#[test]
+fn rejected_revision() {
+    let error = Downloader::handshake(MockServer::new(served().cache()), REVISION + 1).unwrap_err();
+
+    assert!(matches!(
+        error,
+        rscache::Error::Download(DownloadError::Rejected(6))
+    ));
+}
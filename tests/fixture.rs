@@ -0,0 +1,1990 @@
+mod test_util;
+
+use rscache::{checksum::Checksum, util::Huffman};
+use test_util::fixture::{self, ArchiveSpec, FixtureBuilder};
+
+mod basic {
+    use super::*;
+    use rscache::Cache;
+
+    #[test]
+    fn new() {
+        let fixture = fixture::fixture();
+
+        assert!(Cache::new(fixture.path()).is_ok());
+    }
+
+    #[test]
+    fn huffman_table() {
+        let cache = fixture::fixture().cache();
+        let buffer = cache.huffman_table().unwrap();
+
+        assert_eq!(buffer.as_slice(), fixture::huffman_sizes().as_slice());
+        assert_eq!(buffer.len(), 256);
+
+        let huffman = Huffman::new(&buffer).unwrap();
+        assert_eq!(huffman.decompress(b"rs-cache", 8).unwrap(), b"rs-cache");
+    }
+}
+
+mod read {
+    use super::*;
+
+    #[test]
+    fn multi_sector() {
+        let cache = fixture::fixture().cache();
+        let buffer = cache.read(0, 1).unwrap().decode().unwrap();
+
+        assert_eq!(buffer.as_slice(), fixture::pattern(fixture::RAW_ARCHIVE_LEN));
+    }
+
+    #[test]
+    fn bzip2() {
+        let cache = fixture::fixture().cache();
+        let buffer = cache.read(0, 2).unwrap().decode().unwrap();
+
+        assert_eq!(buffer.as_slice(), fixture::pattern(100));
+    }
+
+    #[test]
+    fn expanded_header() {
+        let cache = fixture::fixture().cache();
+        let buffer = cache
+            .read(7, fixture::EXPANDED_ARCHIVE_ID)
+            .unwrap()
+            .decode()
+            .unwrap();
+
+        assert_eq!(buffer.as_slice(), fixture::pattern(fixture::EXPANDED_ARCHIVE_LEN));
+    }
+
+    #[test]
+    fn into_writer() {
+        let cache = fixture::fixture().cache();
+        let mut writer = Vec::new();
+        cache.read_into_writer(0, 1, &mut writer).unwrap();
+
+        assert_eq!(writer, cache.read(0, 1).unwrap().as_slice());
+    }
+
+    /// Accepts a single byte per `write` call.
+    struct ByteWriter(Vec<u8>);
+
+    impl std::io::Write for ByteWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.extend(buf.first());
+            Ok(buf.len().min(1))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writers_match_read() {
+        let fixture = fixture::defaults()
+            .archive(0, ArchiveSpec::new(3, vec![fixture::pattern(20_000)]))
+            .build();
+        let cache = fixture.cache();
+
+        for (index_id, archive_id) in [(0, 1), (0, 3), (2, 10), (7, fixture::EXPANDED_ARCHIVE_ID)] {
+            let buffer = cache.read(index_id, archive_id).unwrap();
+
+            let mut cursor = std::io::Cursor::new(vec![0xFF; 3]);
+            cursor.set_position(3);
+            cache.read_into_writer(index_id, archive_id, &mut cursor).unwrap();
+            assert_eq!(&cursor.get_ref()[3..], buffer.as_slice());
+
+            let mut writer = ByteWriter(Vec::new());
+            cache.read_into_writer(index_id, archive_id, &mut writer).unwrap();
+            assert_eq!(writer.0, buffer.as_slice());
+        }
+    }
+
+    #[test]
+    fn borrowed_single_sector() {
+        use std::borrow::Cow;
+
+        let cache = fixture::fixture().cache();
+        let mut single_sectors = 0;
+        for (index_id, archive_id) in [(0, 1), (0, 2), (2, 10), (7, fixture::EXPANDED_ARCHIVE_ID)] {
+            let buffer = cache.read_ref(index_id, archive_id).unwrap();
+            let single_sector = cache.archive_placement(index_id, archive_id).unwrap().sectors.len() == 1;
+            single_sectors += single_sector as usize;
+
+            assert_eq!(buffer, cache.read(index_id, archive_id).unwrap().as_slice());
+            assert_eq!(
+                matches!(buffer, Cow::Borrowed(_)),
+                single_sector && cfg!(not(feature = "safe-io")),
+                "{} {}",
+                index_id,
+                archive_id
+            );
+        }
+        assert!((1..4).contains(&single_sectors));
+
+        let transformed = rscache::Cache::builder(fixture::fixture().path())
+            .with_read_transform(|_, _, data| Ok(data))
+            .open()
+            .unwrap();
+        assert!(matches!(transformed.read_ref(0, 2).unwrap(), Cow::Owned(_)));
+        assert!(cache.read_ref(2, 25_000).is_err());
+    }
+
+    #[test]
+    fn fails() {
+        let cache = fixture::fixture().cache();
+
+        assert!(cache.read(2, 25_000).is_err());
+        assert!(cache.read(11, 0).is_err());
+    }
+
+    #[test]
+    fn custom_layout() {
+        let fixture = FixtureBuilder::new()
+            .index(0, 7)
+            .archive(0, ArchiveSpec::new(3, vec![vec![1, 2, 3], vec![4, 5]]))
+            .build();
+        let cache = fixture.cache();
+
+        assert!(cache.read(0, 3).unwrap().decode().is_ok());
+    }
+}
+
+mod checksum {
+    use super::*;
+    use rscache::{error::ValidateError, Cache};
+
+    fn crcs(cache: &Cache) -> Vec<u32> {
+        (0..=10)
+            .map(|index_id| crc32fast::hash(&cache.read(255, index_id).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn validate() {
+        let cache = fixture::fixture().cache();
+        let checksum = Checksum::new(&cache).unwrap();
+        let crcs = crcs(&cache);
+
+        assert_eq!(crcs.len(), 11);
+        assert!(checksum.validate(&crcs).is_ok());
+    }
+
+    #[test]
+    fn invalid_crc() {
+        let cache = fixture::fixture().cache();
+        let checksum = Checksum::new(&cache).unwrap();
+        let mut crcs = crcs(&cache);
+        let internal = crcs[2];
+        crcs[2] = !internal;
+
+        assert_eq!(
+            checksum.validate(&crcs),
+            Err(ValidateError::InvalidCrc {
+                idx: 2,
+                external: !internal,
+                internal,
+            })
+        );
+    }
+
+    #[test]
+    fn encode() {
+        let cache = fixture::fixture().cache();
+        let buffer = Checksum::new(&cache).unwrap().encode().unwrap();
+
+        assert_eq!(buffer.len(), 5 + 11 * 8);
+    }
+}
+
+mod index_state {
+    use super::*;
+    use rscache::{
+        error::ValidateError, reference_table::ReferenceTableFlags, Error, IndexState, OpenWarning,
+    };
+    use runefs::error::{Error as RuneFsError, ReadError};
+    use std::fs;
+
+    /// Index 1 sits between two populated indices but has an empty idx file and no reference table.
+    fn empty_index_fixture() -> fixture::Fixture {
+        FixtureBuilder::new()
+            .index(0, 6)
+            .archive(0, ArchiveSpec::new(0, vec![fixture::pattern(10)]))
+            .index(2, 6)
+            .archive(2, ArchiveSpec::new(3, vec![fixture::pattern(20)]))
+            .build()
+    }
+
+    fn is_archive_not_found(result: Result<(), Error>) -> bool {
+        matches!(
+            result,
+            Err(Error::RuneFs(RuneFsError::Read(ReadError::ArchiveNotFound { .. })))
+        )
+    }
+
+    fn is_index_not_found(result: Result<(), Error>) -> bool {
+        matches!(
+            result,
+            Err(Error::RuneFs(RuneFsError::Read(ReadError::IndexNotFound(_))))
+        )
+    }
+
+    #[test]
+    fn states() {
+        let fixture = empty_index_fixture();
+        let cache = fixture.cache();
+
+        assert_eq!(cache.index_state(0), IndexState::Populated { archives: 1 });
+        assert_eq!(cache.index_state(1), IndexState::Empty);
+        assert_eq!(cache.index_state(3), IndexState::Missing);
+    }
+
+    #[test]
+    fn empty() {
+        let fixture = empty_index_fixture();
+        let cache = fixture.cache();
+
+        assert!(cache.contains_index(1));
+        assert_eq!(cache.archive_count(1), Some(0));
+        assert!(is_archive_not_found(cache.read(1, 0).map(drop)));
+        assert!(is_archive_not_found(cache.read_decoded(1, 0).map(drop)));
+        assert!(is_archive_not_found(
+            cache.read_into_writer(1, 0, &mut Vec::new())
+        ));
+        assert!(is_archive_not_found(cache.archive_metadata(1, 0).map(drop)));
+        assert!(is_archive_not_found(cache.index_flags(1).map(drop)));
+    }
+
+    #[test]
+    fn missing() {
+        let fixture = empty_index_fixture();
+        let cache = fixture.cache();
+
+        assert!(!cache.contains_index(3));
+        assert_eq!(cache.archive_count(3), None);
+        assert!(is_index_not_found(cache.read(3, 0).map(drop)));
+        assert!(is_index_not_found(cache.read_decoded(3, 0).map(drop)));
+        assert!(is_index_not_found(
+            cache.read_into_writer(3, 0, &mut Vec::new())
+        ));
+        assert!(is_index_not_found(cache.index_flags(3).map(drop)));
+    }
+
+    #[test]
+    fn populated() {
+        let fixture = empty_index_fixture();
+        let cache = fixture.cache();
+
+        assert!(cache.contains_index(2));
+        assert_eq!(cache.archive_count(2), Some(1));
+        assert_eq!(cache.index_flags(2).unwrap(), ReferenceTableFlags::empty());
+        assert_eq!(cache.read_decoded(2, 3).unwrap().as_slice(), fixture::pattern(20));
+        assert!(is_archive_not_found(cache.read(2, 0).map(drop)));
+    }
+
+    #[test]
+    fn checksum() {
+        let fixture = empty_index_fixture();
+        let cache = fixture.cache();
+        let checksum = Checksum::new(&cache).unwrap();
+
+        let table_crc = |index_id| crc32fast::hash(&cache.read(255, index_id).unwrap());
+        let crcs = [table_crc(0), 0, table_crc(2)];
+        assert!(checksum.validate(&crcs).is_ok());
+        assert_eq!(
+            checksum.validate(&[table_crc(0), table_crc(2)]),
+            Err(ValidateError::InvalidLength {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(checksum.encode().unwrap().len(), 5 + 3 * 8);
+    }
+
+    #[test]
+    fn default_fixture() {
+        let cache = fixture::fixture().cache();
+
+        assert_eq!(cache.index_state(1), IndexState::Empty);
+        assert_eq!(cache.archive_count(2), Some(3));
+        assert_eq!(cache.index_state(11), IndexState::Missing);
+        assert_eq!(cache.index_state(255), IndexState::Populated { archives: 11 });
+        assert_eq!(cache.index_count(), 11);
+        assert!(cache.open_warnings().is_empty());
+    }
+
+    fn table_entry(fixture: &fixture::Fixture, index_id: usize) -> [u8; 6] {
+        let idx = fs::read(fixture.path().join("main_file_cache.idx255")).unwrap();
+
+        idx[index_id * 6..index_id * 6 + 6].try_into().unwrap()
+    }
+
+    /// Overwrites the idx255 entry of the index, growing the file as needed.
+    fn set_table_entry(fixture: &fixture::Fixture, index_id: usize, entry: [u8; 6]) {
+        let path = fixture.path().join("main_file_cache.idx255");
+        let mut idx = fs::read(&path).unwrap();
+        idx.resize(idx.len().max(index_id * 6 + 6), 0);
+        idx[index_id * 6..index_id * 6 + 6].copy_from_slice(&entry);
+        fs::write(path, idx).unwrap();
+    }
+
+    fn crcs(checksum: &Checksum) -> Vec<u32> {
+        checksum.iter().map(|entry| entry.crc()).collect()
+    }
+
+    #[test]
+    fn zero_length_reference_table() {
+        let fixture = fixture::fixture();
+        let expected = crcs(&fixture.cache().checksum().unwrap());
+        set_table_entry(&fixture, 2, [0; 6]);
+        let cache = fixture.cache();
+
+        // the archives are still there, but without a table nothing describes them.
+        assert!(cache.contains_index(2));
+        assert_eq!(cache.index_state(2), IndexState::Empty);
+        assert_eq!(cache.archive_count(2), Some(0));
+        assert_eq!(cache.index_count(), 11);
+        assert!(cache.open_warnings().is_empty());
+
+        let checksum = cache.checksum().unwrap();
+        let mut crcs = crcs(&checksum);
+        assert_eq!(crcs[2], 0);
+        crcs[2] = expected[2];
+        assert_eq!(crcs, expected);
+        assert_eq!(checksum.encode().unwrap().len(), 5 + 11 * 8);
+    }
+
+    #[test]
+    fn reference_index_self_entry() {
+        let fixture = fixture::fixture();
+        let expected = fixture.cache().checksum().unwrap();
+        let entry = table_entry(&fixture, 0);
+        set_table_entry(&fixture, 255, entry);
+        let cache = fixture.cache();
+
+        assert_eq!(cache.index_count(), 11);
+        assert!(!cache.contains_index(11));
+        assert_eq!(cache.index_state(254), IndexState::Missing);
+        assert_eq!(cache.checksum().unwrap(), expected);
+        assert_eq!(cache.checksum().unwrap().encode().unwrap().len(), 5 + 11 * 8);
+    }
+
+    #[test]
+    fn zeroed_reference_table_sector() {
+        let fixture = fixture::fixture();
+        let expected = crcs(&fixture.cache().checksum().unwrap());
+
+        // wipe the first sector of index 5's reference table.
+        let entry = table_entry(&fixture, 5);
+        let sector = u32::from_be_bytes([0, entry[3], entry[4], entry[5]]) as usize;
+        let path = fixture.path().join(runefs::MAIN_DATA);
+        let mut dat2 = fs::read(&path).unwrap();
+        dat2[sector * 520..(sector + 1) * 520].fill(0);
+        fs::write(path, dat2).unwrap();
+        // index 6's table points to the empty sector at the start of the data file.
+        let mut entry = table_entry(&fixture, 6);
+        entry[3..].fill(0);
+        set_table_entry(&fixture, 6, entry);
+
+        let cache = fixture.cache();
+        assert_eq!(
+            cache.open_warnings(),
+            [
+                OpenWarning::ZeroedReferenceTable { index_id: 5, sector },
+                OpenWarning::ZeroedReferenceTable { index_id: 6, sector: 0 },
+            ]
+        );
+        assert!(cache.contains_index(5));
+        assert_eq!(cache.index_state(5), IndexState::Empty);
+        assert_eq!(cache.index_state(6), IndexState::Empty);
+        assert_eq!(cache.index_count(), 11);
+
+        let mut crcs = crcs(&cache.checksum().unwrap());
+        assert_eq!(crcs[5..7], [0, 0]);
+        crcs[5] = expected[5];
+        crcs[6] = expected[6];
+        assert_eq!(crcs, expected);
+    }
+
+    #[test]
+    fn gaps_keep_their_position() {
+        let fixture = fixture::fixture();
+        let expected = crcs(&fixture.cache().checksum().unwrap());
+        fs::remove_file(fixture.path().join("main_file_cache.idx4")).unwrap();
+        // index 10 is only listed in idx255.
+        fs::remove_file(fixture.path().join("main_file_cache.idx10")).unwrap();
+        let cache = fixture.cache();
+
+        assert!(!cache.contains_index(4));
+        assert!(!cache.contains_index(10));
+        assert_eq!(cache.index_count(), 11);
+        assert_eq!(crcs(&cache.checksum().unwrap()), expected);
+
+        let mut checksum = cache.checksum().unwrap();
+        checksum.update_from(&cache, 4).unwrap();
+        assert_eq!(checksum, cache.checksum().unwrap());
+    }
+}
+
+mod read_transform {
+    use super::*;
+    use rscache::{loader::osrs::ItemLoader, Cache};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    const KEY: u8 = 0x5A;
+
+    fn xor_fixture() -> fixture::Fixture {
+        FixtureBuilder::new()
+            .index(2, 6)
+            .archive(2, ArchiveSpec::new(10, fixture::items()).xor(KEY))
+            .build()
+    }
+
+    fn xor(key: u8) -> impl Fn(u8, u32, Vec<u8>) -> Result<Vec<u8>, rscache::Error> {
+        move |_, _, mut data| {
+            data.iter_mut().for_each(|byte| *byte ^= key);
+            Ok(data)
+        }
+    }
+
+    #[test]
+    fn loader() {
+        let fixture = xor_fixture();
+
+        assert!(ItemLoader::new(&fixture.cache()).is_err());
+
+        let cache = Cache::builder(fixture.path())
+            .with_read_transform(xor(KEY))
+            .open()
+            .unwrap();
+        let item_loader = ItemLoader::new(&cache).unwrap();
+        assert_eq!(item_loader.load(0).unwrap().name, "Bronze fixture");
+
+        let mut writer = Vec::new();
+        cache.read_into_writer(2, 10, &mut writer).unwrap();
+        assert_eq!(writer, cache.read(2, 10).unwrap().as_slice());
+    }
+
+    #[test]
+    fn composes_in_order() {
+        let fixture = xor_fixture();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let first = Arc::clone(&calls);
+        let second = Arc::clone(&calls);
+        let cache = Cache::builder(fixture.path())
+            .with_read_transform(move |_, _, data| {
+                assert_eq!(first.fetch_add(1, Ordering::SeqCst) % 2, 0);
+                xor(0x0F)(2, 10, data)
+            })
+            .with_read_transform(move |index_id, archive_id, data| {
+                assert_eq!((index_id, archive_id), (2, 10));
+                assert_eq!(second.fetch_add(1, Ordering::SeqCst) % 2, 1);
+                xor(KEY ^ 0x0F)(index_id, archive_id, data)
+            })
+            .open()
+            .unwrap();
+
+        assert!(cache.read_decoded(2, 10).is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.index_flags(2).unwrap().bits(), 0);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn errors_propagate() {
+        let fixture = xor_fixture();
+        let cache = Cache::builder(fixture.path())
+            .with_read_transform(|_, _, _| {
+                Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "obfuscated").into())
+            })
+            .open()
+            .unwrap();
+
+        assert!(matches!(cache.read(2, 10), Err(rscache::Error::Io(_))));
+    }
+}
+
+mod loader {
+    use super::*;
+    use rscache::loader::osrs::{
+        InterfaceLoader, ItemLoader, LocationLoader, MapLoader, NpcLoader, ObjectLoader,
+    };
+
+    /// A minimal if3 text-less layer component.
+    fn component(name: &str) -> Vec<u8> {
+        let mut buffer = vec![0xFF, 0];
+        // content type, x, y, width and height.
+        buffer.extend([0, 0, 0, 0, 0, 0, 0, 10, 0, 10]);
+        // position and size modes, no parent and not hidden.
+        buffer.extend([0, 0, 0, 0, 0xFF, 0xFF, 0]);
+        // scroll width, scroll height and no click through.
+        buffer.extend([0, 0, 0, 0, 0]);
+        // click mask.
+        buffer.extend([0, 0, 0]);
+        buffer.extend(name.as_bytes());
+        buffer.push(0);
+        // no actions, drag settings and an empty target verb.
+        buffer.extend([0, 0, 0, 0, 0]);
+        // no hooks and no transmit triggers.
+        buffer.extend([0; 18 + 3]);
+
+        buffer
+    }
+
+    #[test]
+    fn gapped_file_ids() {
+        let fixture = FixtureBuilder::new()
+            .index(2, 6)
+            .archive(2, ArchiveSpec::new(9, fixture::npcs()).file_ids(vec![1, 4]))
+            .archive(2, ArchiveSpec::new(10, fixture::items()).file_ids(vec![0, 2, 5]))
+            .build();
+        let cache = fixture.cache();
+
+        let items = ItemLoader::new(&cache).unwrap();
+        let ids: Vec<u32> = items.iter().map(|(&id, _)| id).collect();
+        assert_eq!(ids, [0, 2, 5]);
+        assert_eq!(items.load(0).unwrap().name, "Bronze fixture");
+        // enumerating the files would put the coins at 1.
+        assert!(items.load(1).is_none());
+        assert!(items.load(2).unwrap().stackable);
+        assert!(items.load(5).unwrap().members_only);
+
+        let npcs = NpcLoader::new(&cache).unwrap();
+        assert!(npcs.load(0).is_none());
+        assert_eq!(npcs.load(1).unwrap().name, "Fixture guide");
+        assert_eq!(npcs.load(4).unwrap().name, "Fixture scenery");
+    }
+
+    #[test]
+    fn gapped_component_ids() {
+        let fixture = FixtureBuilder::new()
+            .index(3, 6)
+            .archive(
+                3,
+                ArchiveSpec::new(4, vec![component("first"), component("second")])
+                    .file_ids(vec![0, 3]),
+            )
+            .build();
+        let cache = fixture.cache();
+        let mut interfaces = InterfaceLoader::new(&cache).unwrap();
+
+        let ids: Vec<u32> = interfaces
+            .load(4)
+            .unwrap()
+            .unwrap()
+            .iter()
+            .map(|component| component.id)
+            .collect();
+        assert_eq!(ids, [4 << 16, 4 << 16 | 3]);
+        assert_eq!(interfaces.component(4 << 16 | 3).unwrap().unwrap().name, "second");
+        assert!(interfaces.component(4 << 16 | 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn registered_decryption() {
+        use rscache::{
+            decryption::{Decryption, DecryptionRegistry},
+            definition::osrs::{FetchDefinition, ItemDefinition},
+            Cache,
+        };
+        use std::ops::ControlFlow;
+
+        let keys = fixture::LOCATION_KEYS;
+        let fixture = FixtureBuilder::new()
+            .index(2, 6)
+            .archive(2, ArchiveSpec::new(10, fixture::items()).keys(keys))
+            .index(3, 6)
+            .archive(3, ArchiveSpec::new(4, vec![component("first")]).keys(keys))
+            .index(9, 6)
+            .archive(9, ArchiveSpec::new(0, vec![fixture::items().remove(0)]).keys(keys))
+            .build();
+        let mut registry = DecryptionRegistry::new();
+        registry.insert(2, 10, Decryption::Xtea(keys));
+        registry.insert(3, 4, Decryption::Xtea(keys));
+        registry.insert_index(9, Decryption::Xtea(keys));
+        let cache = Cache::builder(fixture.path()).decryption(registry).open().unwrap();
+
+        let items = ItemLoader::new(&cache).unwrap();
+        assert_eq!(items.load(0).unwrap().name, "Bronze fixture");
+        let items = ItemDefinition::fetch_from_archive::<ItemDefinition>(&cache, 2, 10).unwrap();
+        assert!(items[&2].members_only);
+
+        let mut names = Vec::new();
+        ItemDefinition::for_each_in_index(&cache, 9, |_, item: ItemDefinition| {
+            names.push(item.name);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(names, ["Bronze fixture"]);
+
+        let mut interfaces = InterfaceLoader::new(&cache).unwrap();
+        assert_eq!(interfaces.component(4 << 16).unwrap().unwrap().name, "first");
+
+        // without the registry the archives don't decode.
+        let unregistered = fixture.cache();
+        assert!(ItemLoader::new(&unregistered).is_err());
+        assert!(InterfaceLoader::new(&unregistered).unwrap().load(4).is_err());
+    }
+
+    #[test]
+    fn items() {
+        let cache = fixture::fixture().cache();
+        let item_loader = ItemLoader::new(&cache).unwrap();
+
+        let item = item_loader.load(0).unwrap();
+        assert_eq!(item.name, "Bronze fixture");
+        assert_eq!(item.cost, 250);
+        assert!(item_loader.load(1).unwrap().stackable);
+        assert!(item_loader.load(2).unwrap().members_only);
+        assert!(item_loader.load(3).is_none());
+    }
+
+    #[test]
+    fn wide_item_ids() {
+        use rscache::definition::osrs::{Definition, FetchDefinition, ItemDefinition};
+
+        let items = fixture::items();
+        let fixture = FixtureBuilder::new()
+            .index(2, 7)
+            .archive(
+                2,
+                ArchiveSpec::new(10, vec![items[1].clone(), items[0].clone()])
+                    .file_ids(vec![1, 70_000]),
+            )
+            .build();
+        let cache = fixture.cache();
+
+        let item = ItemDefinition::new(70_000, &items[0]).unwrap();
+        assert_eq!(item.id, 70_000);
+
+        let items: std::collections::BTreeMap<u32, ItemDefinition> =
+            ItemDefinition::fetch_from_archive(&cache, 2, 10).unwrap();
+        assert_eq!(items[&70_000].name, "Bronze fixture");
+        assert_eq!(items[&1].name, "Fixture coins");
+
+        let item_loader = ItemLoader::new(&cache).unwrap();
+        assert_eq!(item_loader.load(70_000).unwrap().id, 70_000);
+        assert!(item_loader.load(70_000 & 0xFFFF).is_none());
+
+        let mut ids: Vec<u32> = item_loader.iter().map(|(&id, _)| id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 70_000]);
+    }
+
+    #[test]
+    fn for_each_item() {
+        use std::ops::ControlFlow;
+
+        let cache = fixture::fixture().cache();
+
+        let mut names = Vec::new();
+        ItemLoader::for_each(&cache, |_, item| {
+            names.push(item.name);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        names.sort();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names[0], "Bronze fixture");
+
+        let mut visited = 0;
+        ItemLoader::for_each(&cache, |_, _| {
+            visited += 1;
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        assert_eq!(visited, 1);
+    }
+
+    #[test]
+    fn for_each_stops_reading() {
+        use rscache::definition::osrs::{FetchDefinition, ItemDefinition};
+        use std::ops::ControlFlow;
+
+        // archive 1 holds a truncated definition, only reading it fails.
+        let fixture = FixtureBuilder::new()
+            .index(0, 6)
+            .archive(0, ArchiveSpec::new(0, vec![fixture::items()[0].clone()]))
+            .archive(0, ArchiveSpec::new(1, vec![vec![12, 0]]))
+            .build();
+        let cache = fixture.cache();
+
+        let mut ids = Vec::new();
+        ItemDefinition::for_each_in_index(&cache, 0, |id, _: ItemDefinition| {
+            ids.push(id);
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        assert_eq!(ids, vec![0]);
+
+        let result = ItemDefinition::for_each_in_index(&cache, 0, |_, _: ItemDefinition| {
+            ControlFlow::Continue(())
+        });
+        assert!(result.is_err());
+        assert!(ItemDefinition::fetch_from_index::<ItemDefinition>(&cache, 0).is_err());
+    }
+
+    #[test]
+    fn deterministic_iteration() {
+        let cache = fixture::fixture().cache();
+        let first = ItemLoader::new(&cache).unwrap();
+        let second = ItemLoader::new(&cache).unwrap();
+
+        let ids: Vec<u32> = first.iter().map(|(&id, _)| id).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+        assert_eq!(format!("{:?}", first), format!("{:?}", second));
+
+        let ids: Vec<u32> = NpcLoader::new(&cache)
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert!(ids.windows(2).all(|ids| ids[0] < ids[1]));
+    }
+
+    #[test]
+    fn npcs() {
+        let cache = fixture::fixture().cache();
+        let npc_loader = NpcLoader::new(&cache).unwrap();
+
+        let npc = npc_loader.load(0).unwrap();
+        assert_eq!(npc.name, "Fixture guide");
+        assert_eq!(npc.size, 2);
+        assert!(npc.interactable);
+        assert!(!npc_loader.load(1).unwrap().interactable);
+    }
+
+    #[test]
+    fn objects() {
+        let cache = fixture::fixture().cache();
+        let obj_loader = ObjectLoader::new(&cache).unwrap();
+
+        let door = obj_loader.load(0).unwrap();
+        assert_eq!(door.name, "Fixture door");
+        assert_eq!(door.wall_or_door, Some(1));
+
+        let fountain = obj_loader.load(1).unwrap();
+        assert_eq!(fountain.animation_id, Some(1234));
+        assert!(fountain.hollow);
+        assert!(fountain.solid);
+    }
+
+    #[test]
+    fn maps() {
+        let cache = fixture::fixture().cache();
+        let mut map_loader = MapLoader::new(&cache);
+        let map_def = map_loader.load(fixture::REGION_ID).unwrap();
+
+        assert_eq!(map_def.region_base_coords(), (3200, 3200));
+        assert_eq!(map_def.map_data(0, 0, 0).height, 20);
+        assert_eq!(map_def.map_data(1, 0, 0).height, 0);
+    }
+
+    #[test]
+    fn locations() {
+        let cache = fixture::fixture().cache();
+        let mut location_loader = LocationLoader::new(&cache);
+        let location_def = location_loader
+            .load(fixture::REGION_ID, &fixture::LOCATION_KEYS)
+            .unwrap();
+
+        assert_eq!(location_def.data.len(), 2);
+        assert_eq!(location_def.data[0].id, 1);
+        assert_eq!(location_def.data[0].loc_type, 10);
+        assert_eq!(location_def.data[1].id, 2);
+        assert_eq!(location_def.data[1].loc_type, 22);
+        assert_eq!(location_def.data[1].orientation, 1);
+    }
+
+    #[test]
+    fn locations_wrong_keys() {
+        let cache = fixture::fixture().cache();
+        let mut location_loader = LocationLoader::new(&cache);
+
+        assert!(location_loader.load(fixture::REGION_ID, &[0; 4]).is_err());
+    }
+
+    #[test]
+    fn no_interfaces() {
+        let cache = fixture::fixture().cache();
+        let mut interface_loader = InterfaceLoader::new(&cache).unwrap();
+
+        assert!(interface_loader.load(0).unwrap().is_none());
+    }
+}
+
+mod stale_loaders {
+    use super::*;
+    use rscache::{
+        definition::osrs::Definition,
+        loader::osrs::{ItemLoader, NpcLoader},
+    };
+    use std::fs;
+
+    /// Swaps the files of `fixture` for the ones of `update`, like a cache
+    /// update replacing files on disk.
+    fn swap(fixture: &fixture::Fixture, update: &fixture::Fixture) {
+        for entry in fs::read_dir(update.path()).unwrap() {
+            let path = entry.unwrap().path();
+            fs::rename(&path, fixture.path().join(path.file_name().unwrap())).unwrap();
+        }
+    }
+
+    fn renamed_item() -> fixture::Fixture {
+        let mut items = fixture::items();
+        items[1] = items[0].clone();
+
+        fixture::defaults()
+            .archive(2, ArchiveSpec::new(10, items))
+            .build()
+    }
+
+    #[test]
+    fn refresh_after_reload() -> Result<(), rscache::Error> {
+        let fixture = fixture::fixture();
+        let mut cache = fixture.cache();
+        let mut items = ItemLoader::new(&cache)?;
+        let mut npcs = NpcLoader::new(&cache)?;
+        assert!(items.source_crc().is_some());
+        assert!(!items.is_stale(&cache)?);
+        assert!(!items.refresh(&cache)?);
+
+        swap(&fixture, &renamed_item());
+        // still reading the old files until reloaded.
+        assert!(!items.is_stale(&cache)?);
+        cache.reload()?;
+
+        assert!(items.is_stale(&cache)?);
+        assert_eq!(items.load(1).unwrap().name(), Some("Fixture coins"));
+        assert!(items.refresh(&cache)?);
+        assert!(!items.is_stale(&cache)?);
+        assert_eq!(items.load(1).unwrap().name(), Some("Bronze fixture"));
+        assert_eq!(items, ItemLoader::new(&cache)?);
+
+        // npcs live in another archive of the same index.
+        assert!(!npcs.is_stale(&cache)?);
+        assert!(!npcs.refresh(&cache)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn collected_loaders_are_stale() -> Result<(), rscache::Error> {
+        let cache = fixture::fixture().cache();
+        let mut items: ItemLoader = ItemLoader::new(&cache)?.into_iter().collect();
+        assert_eq!(items.source_crc(), None);
+        assert!(items.is_stale(&cache)?);
+
+        assert!(items.refresh(&cache)?);
+        assert_eq!(items.source_crc(), ItemLoader::new(&cache)?.source_crc());
+
+        Ok(())
+    }
+}
+
+mod named_group {
+    use super::*;
+    use rscache::{group, util, Error};
+
+    fn title_fixture() -> fixture::Fixture {
+        FixtureBuilder::new()
+            .index(10, 6)
+            .archive(
+                10,
+                ArchiveSpec::new(0, vec![fixture::huffman_sizes()]).named(group::HUFFMAN),
+            )
+            .archive(
+                10,
+                ArchiveSpec::new(1, vec![fixture::pattern(700)]).named(group::TITLE_IMAGE),
+            )
+            .build()
+    }
+
+    #[test]
+    fn decodes_named_groups() {
+        let cache = title_fixture().cache();
+
+        let huffman = cache.named_group(10, group::HUFFMAN).unwrap();
+        assert_eq!(huffman, fixture::huffman_sizes());
+        assert_eq!(huffman, cache.huffman_table().unwrap().as_slice());
+
+        let title = cache.named_group(10, group::TITLE_IMAGE).unwrap();
+        assert_eq!(title, fixture::pattern(700));
+    }
+
+    #[test]
+    fn any_index() {
+        let cache = fixture::fixture().cache();
+
+        assert_eq!(cache.named_group(5, "m50_50").unwrap(), fixture::map());
+    }
+
+    #[test]
+    fn archive_ids() {
+        let cache = title_fixture().cache();
+
+        assert_eq!(cache.archive_id_by_name(10, group::HUFFMAN).unwrap(), 0);
+        assert_eq!(cache.archive_id_by_name(10, group::TITLE_IMAGE).unwrap(), 1);
+        assert!(matches!(
+            cache.archive_id_by_name(10, group::TITLE_MUSIC),
+            Err(Error::NameHash(_))
+        ));
+
+        let name_hashes: Vec<_> = cache
+            .archives(10)
+            .unwrap()
+            .filter_map(|archive| archive.name_hash)
+            .collect();
+        assert_eq!(
+            name_hashes,
+            [util::djd2::hash(group::HUFFMAN), util::djd2::hash(group::TITLE_IMAGE)]
+        );
+    }
+
+    #[test]
+    fn unknown_name() {
+        let cache = title_fixture().cache();
+
+        assert!(matches!(
+            cache.named_group(10, group::TITLE_MUSIC),
+            Err(Error::NameHash(_))
+        ));
+    }
+}
+
+mod file_names {
+    use super::*;
+    use rscache::{error::ReadError, util::djd2, Error};
+
+    /// Sprites 0, 3 and 7 of a sprite group in index 8, the first two named.
+    fn sprite_fixture() -> fixture::Fixture {
+        let sprites = vec![fixture::pattern(10), fixture::pattern(20), fixture::pattern(30)];
+
+        FixtureBuilder::new()
+            .index(8, 6)
+            .archive(
+                8,
+                ArchiveSpec::new(2, sprites)
+                    .file_ids(vec![0, 3, 7])
+                    .file_names(vec!["compass", "mapback", ""]),
+            )
+            .build()
+    }
+
+    #[test]
+    fn resolves_planted_names() -> Result<(), Error> {
+        let cache = sprite_fixture().cache();
+
+        assert_eq!(cache.file_id_by_name(8, 2, "compass")?, 0);
+        assert_eq!(cache.file_id_by_name(8, 2, "mapback")?, 3);
+        assert_eq!(cache.read_file_by_name(8, 2, "mapback")?, fixture::pattern(20));
+        assert_eq!(cache.read_file_by_name(8, 2, "mapback")?, cache.read_file(8, 2, 3)?);
+
+        let hashes = cache.archive_metadata(8, 2)?.file_name_hashes;
+        assert_eq!(hashes, Some(vec![djd2::hash("compass"), djd2::hash("mapback"), 0]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_names() {
+        let cache = sprite_fixture().cache();
+
+        assert!(matches!(
+            cache.file_id_by_name(8, 2, "scrollbar"),
+            Err(Error::Read(ReadError::FileNameNotFound { idx: 8, arc: 2, hash }))
+                if hash == djd2::hash("scrollbar")
+        ));
+        assert!(cache.file_id_by_name(8, 1, "compass").is_err());
+
+        // tables without the named flag have no file names at all.
+        let cache = fixture::fixture().cache();
+        assert!(matches!(
+            cache.file_id_by_name(2, 10, "0"),
+            Err(Error::Read(ReadError::FileNameNotFound { .. }))
+        ));
+    }
+}
+
+mod read_files {
+    use super::*;
+    use rscache::Error;
+
+    /// Three files with sparse ids, striped across three chunks.
+    fn chunked_fixture() -> fixture::Fixture {
+        let files = vec![fixture::pattern(10), fixture::pattern(1), fixture::pattern(301)];
+
+        FixtureBuilder::new()
+            .index(2, 6)
+            .archive(
+                2,
+                ArchiveSpec::new(4, files).file_ids(vec![1, 5, 8]).chunks(3),
+            )
+            .build()
+    }
+
+    #[test]
+    fn every_file() -> Result<(), Error> {
+        let cache = fixture::fixture().cache();
+
+        let items = cache.read_files(2, 10)?;
+        assert_eq!(items.keys().copied().collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(items.into_values().collect::<Vec<_>>(), fixture::items());
+
+        // a lone file is the whole archive.
+        let files = cache.read_files(0, 2)?;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[&0], fixture::pattern(100));
+        assert_eq!(files[&0], cache.read_decoded(0, 2)?.finalize());
+
+        Ok(())
+    }
+
+    #[test]
+    fn chunked() -> Result<(), Error> {
+        let cache = chunked_fixture().cache();
+        assert_eq!(cache.read_decoded(2, 4)?.finalize().last(), Some(&3));
+
+        let files = cache.read_files(2, 4)?;
+        assert_eq!(files.keys().copied().collect::<Vec<_>>(), [1, 5, 8]);
+        assert_eq!(files[&1], fixture::pattern(10));
+        assert_eq!(files[&5], fixture::pattern(1));
+        assert_eq!(files[&8], fixture::pattern(301));
+        for (file_id, file) in files {
+            assert_eq!(cache.read_file(2, 4, file_id)?, file);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing_archive() {
+        let cache = fixture::fixture().cache();
+
+        assert!(cache.read_files(2, 7).is_err());
+        assert!(cache.read_files(42, 0).is_err());
+    }
+}
+
+mod changed_since {
+    use super::*;
+
+    /// The default fixture with archive 9 of index 2 bumped to version 5, every other archive is at 1.
+    fn bumped_fixture() -> fixture::Fixture {
+        let mut npcs = ArchiveSpec::new(9, fixture::npcs());
+        npcs.revision = 5;
+
+        FixtureBuilder::new()
+            .index(0, 5)
+            .archive(0, ArchiveSpec::new(1, vec![fixture::pattern(10)]))
+            .index(2, 6)
+            .archive(2, ArchiveSpec::new(6, fixture::objects()))
+            .archive(2, npcs)
+            .archive(2, ArchiveSpec::new(10, fixture::items()))
+            .build()
+    }
+
+    #[test]
+    fn single_index() {
+        let cache = bumped_fixture().cache();
+
+        assert_eq!(cache.archives_changed_since(2, 1).unwrap(), [9]);
+        assert_eq!(cache.archives_changed_since(2, 0).unwrap(), [6, 9, 10]);
+        assert!(cache.archives_changed_since(2, 5).unwrap().is_empty());
+        assert!(cache.archives_changed_since(1, 0).unwrap().is_empty());
+        assert!(cache.archives_changed_since(100, 0).is_err());
+    }
+
+    #[test]
+    fn every_index() {
+        let cache = bumped_fixture().cache();
+
+        assert_eq!(cache.all_archives_changed_since(1), [(2, 9)]);
+        assert_eq!(
+            cache.all_archives_changed_since(0),
+            [(0, 1), (2, 6), (2, 9), (2, 10)]
+        );
+    }
+}
+
+mod revision {
+    use super::*;
+
+    #[test]
+    fn highest_table_revision() {
+        let cache = FixtureBuilder::new()
+            .index(0, 5)
+            .index(2, 6)
+            .revision(2, 7)
+            .index(3, 7)
+            .revision(3, 3)
+            .build()
+            .cache();
+
+        assert_eq!(cache.revision(), Some(7));
+        assert_eq!(fixture::fixture().cache().revision(), Some(1));
+    }
+
+    #[test]
+    fn unversioned_tables() {
+        let cache = FixtureBuilder::new().index(0, 5).index(1, 5).build().cache();
+
+        assert_eq!(cache.revision(), None);
+    }
+}
+
+mod metadata {
+    use super::*;
+    use rscache::reference_table::ReferenceTableFlags;
+    use std::borrow::Cow;
+
+    fn stored_len(cache: &rscache::Cache, index_id: u8, archive_ids: &[u32]) -> u64 {
+        archive_ids
+            .iter()
+            .map(|&archive_id| cache.read(index_id, archive_id).unwrap().len() as u64)
+            .sum()
+    }
+
+    #[test]
+    fn configs() {
+        let cache = fixture::fixture().cache();
+        let metadata = cache.metadata_of(2).unwrap();
+
+        assert_eq!(metadata.index_id, 2);
+        assert_eq!(metadata.protocol(), 6);
+        assert_eq!(metadata.revision(), Some(1));
+        assert_eq!(metadata.flags(), ReferenceTableFlags::empty());
+        assert_eq!(metadata.group_count(), 3);
+        assert_eq!(metadata.archive_ids, Some(6..=10));
+        assert_eq!(metadata.stored_len, stored_len(&cache, 2, &[6, 9, 10]));
+    }
+
+    #[test]
+    fn archive_entries() {
+        let cache = fixture::fixture().cache();
+        let metadata = cache.metadata_of(2).unwrap();
+
+        let archives = metadata.archives();
+        let ids: Vec<u32> = archives.iter().map(|archive| archive.id).collect();
+        assert_eq!(ids, [6, 9, 10]);
+        for archive in archives {
+            let container = cache.read(2, archive.id).unwrap();
+            assert_eq!(archive.crc, crc32fast::hash(&container));
+            assert_eq!(archive.version, 1);
+        }
+
+        let items = &archives[2];
+        assert_eq!(items.file_count(), fixture::items().len());
+        assert_eq!(items.file_name_hashes, None);
+        let files = fixture::objects().len() + fixture::npcs().len() + fixture::items().len();
+        assert_eq!(metadata.file_count(), files);
+    }
+
+    #[test]
+    fn expanded() {
+        let cache = fixture::fixture().cache();
+        let metadata = cache.metadata_of(7).unwrap();
+
+        assert_eq!(metadata.protocol(), 7);
+        assert_eq!(metadata.group_count(), 1);
+        assert_eq!(
+            metadata.archive_ids,
+            Some(fixture::EXPANDED_ARCHIVE_ID..=fixture::EXPANDED_ARCHIVE_ID)
+        );
+        assert_eq!(
+            metadata.stored_len,
+            stored_len(&cache, 7, &[fixture::EXPANDED_ARCHIVE_ID])
+        );
+    }
+
+    #[test]
+    fn empty_and_missing() {
+        let cache = fixture::fixture().cache();
+        let metadata = cache.metadata_of(1).unwrap();
+
+        assert_eq!(metadata.group_count(), 0);
+        assert_eq!(metadata.archive_ids, None);
+        assert_eq!(metadata.stored_len, 0);
+        assert!(cache.metadata_of(100).is_err());
+    }
+
+    #[test]
+    fn memoized() {
+        let cache = fixture::fixture().cache();
+        let first = cache.metadata_of(5).unwrap();
+        let second = cache.metadata_of(5).unwrap();
+
+        match (&first.table, &second.table) {
+            (Cow::Borrowed(first), Cow::Borrowed(second)) => assert!(std::ptr::eq(*first, *second)),
+            _ => panic!("memoized reference tables are borrowed"),
+        }
+        assert!(first.flags().contains(ReferenceTableFlags::NAMED));
+        assert_eq!(
+            cache.archive_metadata(5, 1).unwrap(),
+            first.table.archives[1]
+        );
+    }
+
+    #[test]
+    fn borrowed_and_untransformed() {
+        let fixture = fixture::fixture();
+        let cache = rscache::Cache::builder(fixture.path())
+            .with_read_transform(|_, _, mut data| {
+                data.iter_mut().for_each(|byte| *byte ^= 0xFF);
+                Ok(data)
+            })
+            .open()
+            .unwrap();
+
+        let metadata = cache.metadata_of(5).unwrap();
+        assert!(matches!(metadata.table, Cow::Borrowed(_)));
+        assert_eq!(metadata, fixture.cache().metadata_of(5).unwrap());
+    }
+}
+
+mod compression_policy {
+    use super::*;
+    use rscache::{
+        codec::{self, Compression, CompressionPolicy},
+        Cache,
+    };
+
+    #[test]
+    fn osrs_defaults() {
+        let policy = CompressionPolicy::default();
+
+        assert_eq!(policy, CompressionPolicy::osrs());
+        for index_id in [0, 2, 5, 7, 10, 255] {
+            assert_eq!(policy.compression_for(index_id, 0), Compression::Gzip);
+        }
+        assert_eq!(policy.compression_for(18, 0), Compression::Bzip2);
+        assert_eq!(policy.compression_for(20, 0), Compression::None);
+    }
+
+    #[test]
+    fn overrides() {
+        let policy = CompressionPolicy::new(Compression::None)
+            .with_index(2, Compression::Gzip)
+            .with_archive(2, 10, Compression::Bzip2)
+            .with_archive(5, 1, Compression::Gzip);
+
+        assert_eq!(policy.compression_for(0, 10), Compression::None);
+        assert_eq!(policy.compression_for(2, 9), Compression::Gzip);
+        assert_eq!(policy.compression_for(2, 10), Compression::Bzip2);
+        assert_eq!(policy.compression_for(5, 0), Compression::None);
+        assert_eq!(policy.compression_for(5, 1), Compression::Gzip);
+
+        let container = policy.encode(2, 10, &fixture::pattern(100)).unwrap();
+        assert_eq!(&container[..1], &[1]);
+    }
+
+    #[test]
+    fn round_trips_through_checked_reads() {
+        let payload = fixture::pattern(2_000);
+        let fixture = FixtureBuilder::new()
+            .index(2, 6)
+            .sizes(2)
+            .archive(2, ArchiveSpec::new(10, vec![payload.clone()]))
+            .build();
+
+        for compression in [Compression::None, Compression::Bzip2, Compression::Gzip] {
+            let policy = CompressionPolicy::osrs().with_archive(2, 10, compression);
+            let encoded = payload.clone();
+            let cache = Cache::builder(fixture.path())
+                .check_sizes(true)
+                .with_read_transform(move |index_id, archive_id, _| {
+                    policy.encode(index_id, archive_id, &encoded)
+                })
+                .open()
+                .unwrap();
+
+            let container = cache.read(2, 10).unwrap();
+            assert_eq!(container[0], compression as u8);
+            assert_eq!(codec::split_trailer(&container).1, None);
+            assert_eq!(cache.read_decoded(2, 10).unwrap().as_slice(), payload);
+        }
+    }
+}
+
+mod encode {
+    use super::*;
+    use rscache::codec::{self, Compression};
+
+    const COMPRESSIONS: [Compression; 3] =
+        [Compression::None, Compression::Bzip2, Compression::Gzip];
+
+    #[test]
+    fn round_trips_every_compression() {
+        let payload = fixture::pattern(3_000);
+
+        for compression in COMPRESSIONS {
+            let container = codec::encode(&payload, compression).unwrap();
+
+            assert_eq!(container[0], compression as u8);
+            assert_eq!(codec::split_trailer(&container).1, None);
+            assert_eq!(codec::decode(&container).unwrap().as_slice(), payload);
+        }
+    }
+
+    #[test]
+    fn version_trailer() {
+        let payload = fixture::pattern(3_000);
+
+        for compression in COMPRESSIONS {
+            let body = codec::encode(&payload, compression).unwrap();
+            let container = codec::encode_with_version(&payload, compression, 0x1234).unwrap();
+
+            assert_eq!(codec::split_trailer(&container), (body.as_slice(), Some(0x1234)));
+            assert_eq!(codec::decode(&container).unwrap().as_slice(), payload);
+        }
+    }
+
+    #[test]
+    fn gzip_containers_decode() {
+        let payload = fixture::pattern(3_000);
+        let container = codec::encode(&payload, Compression::Gzip).unwrap();
+
+        // gzip header, compressed and decompressed lengths, then the gzip magic.
+        assert_eq!(container[0], 2);
+        assert_eq!(&container[5..9], &(payload.len() as u32).to_be_bytes());
+        assert_eq!(&container[9..11], &[0x1F, 0x8B]);
+        assert!(container.len() < payload.len());
+        assert_eq!(codec::decode(&container).unwrap().as_slice(), payload);
+    }
+
+    #[test]
+    fn decode_full_keeps_compression_and_version() {
+        let payload = fixture::pattern(3_000);
+
+        for compression in COMPRESSIONS {
+            let body = codec::encode(&payload, compression).unwrap();
+            // header, with the decompressed length unless uncompressed, then the body.
+            let header_len = if compression == Compression::None {
+                5
+            } else {
+                9
+            };
+            let compressed_len = u32::from_be_bytes(body[1..5].try_into().unwrap()) as usize;
+            assert_eq!(body.len(), header_len + compressed_len);
+
+            let plain = codec::decode_full(&body).unwrap();
+            assert_eq!(plain.compression, compression);
+            assert_eq!(plain.data, payload);
+            assert_eq!(plain.version, None);
+
+            let versioned = codec::encode_with_version(&payload, compression, 513).unwrap();
+            assert_eq!(codec::decode_full(&versioned).unwrap().version, Some(513));
+
+            // only exactly 2 bytes past the body are a trailer.
+            for extra in [1, 3] {
+                let mut container = body.clone();
+                container.extend(vec![1; extra]);
+                assert_eq!(codec::decode_full(&container).unwrap().version, None);
+            }
+        }
+    }
+
+    #[test]
+    fn decode_into_streams_every_compression() {
+        let payload = fixture::pattern(3_000);
+
+        for compression in COMPRESSIONS {
+            let container = codec::encode_with_version(&payload, compression, 7).unwrap();
+            let mut decoded = Vec::new();
+
+            let len = codec::decode_into(&container, &mut decoded).unwrap();
+            assert_eq!(len, payload.len());
+            assert_eq!(decoded, payload);
+
+            let mut encoded = Vec::new();
+            let len = codec::encode_into(&payload, compression, &mut encoded).unwrap();
+            assert_eq!(len, encoded.len());
+            assert_eq!(encoded, codec::encode(&payload, compression).unwrap());
+        }
+    }
+
+    #[test]
+    fn decode_into_rejects_malformed_containers() {
+        let payload = fixture::pattern(3_000);
+
+        for compression in COMPRESSIONS {
+            let container = codec::encode(&payload, compression).unwrap();
+
+            for len in [3, 7, container.len() / 2, container.len() - 1] {
+                let result = codec::decode_into(&container[..len], &mut Vec::new());
+                assert!(result.is_err(), "{:?} {}", compression, len);
+            }
+
+            // declaring fewer decompressed bytes than the stream holds.
+            if compression != Compression::None {
+                let mut short = container.clone();
+                short[8] -= 1;
+                assert!(codec::decode_into(&short, &mut Vec::new()).is_err());
+            }
+        }
+
+        let mut huge = codec::encode(&payload, Compression::Gzip).unwrap();
+        huge[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(codec::decode_into(&huge, &mut Vec::new()).is_err());
+    }
+
+    #[cfg(feature = "rs3")]
+    mod lzma {
+        use super::*;
+
+        const PAYLOAD: &[u8] = b"the quick brown fox jumps over the lazy dog, the lazy dog sleeps";
+
+        /// [`PAYLOAD`] as stored by RS3: type 3, compressed and decompressed
+        /// lengths, then an LZMA stream with properties and dictionary size
+        /// but without the uncompressed size of the `.lzma` header.
+        #[rustfmt::skip]
+        const CONTAINER: [u8; 79] = [
+            3, 0, 0, 0, 70, 0, 0, 0, 64,
+            93, 0, 0, 128, 0,
+            0, 58, 26, 8, 206, 118, 199, 229, 233, 214, 7, 52, 195, 209, 14, 191, 206, 85, 225,
+            170, 189, 224, 228, 143, 152, 1, 221, 141, 229, 7, 84, 158, 99, 70, 135, 154, 225,
+            212, 124, 88, 37, 247, 117, 77, 67, 1, 86, 85, 205, 121, 55, 238, 141, 66, 240, 108,
+            251, 241, 251, 4, 5, 62, 24, 135, 155,
+        ];
+
+        #[test]
+        fn decodes_rs3_containers() {
+            assert_eq!(codec::decode(&CONTAINER).unwrap().as_slice(), PAYLOAD);
+
+            let mut versioned = CONTAINER.to_vec();
+            versioned.extend([0, 9]);
+            assert_eq!(codec::split_trailer(&versioned), (&CONTAINER[..], Some(9)));
+            assert_eq!(codec::decode(&versioned).unwrap().as_slice(), PAYLOAD);
+
+            let mut decoded = Vec::new();
+            let len = codec::decode_into(&versioned, &mut decoded).unwrap();
+            assert_eq!(len, PAYLOAD.len());
+            assert_eq!(decoded, PAYLOAD);
+        }
+
+        #[test]
+        fn round_trips() {
+            let payload = fixture::pattern(3_000);
+            let container = codec::encode_with_version(&payload, Compression::Lzma, 3).unwrap();
+
+            assert_eq!(container[0], 3);
+            assert_eq!(codec::decode(&container).unwrap().as_slice(), payload);
+        }
+
+        #[test]
+        fn truncated_streams_fail() {
+            assert!(codec::decode(&CONTAINER[..40]).is_err());
+
+            let mut short = CONTAINER;
+            short[4] = 20;
+            assert!(codec::decode(&short).is_err());
+            assert!(codec::decode_into(&CONTAINER[..40], &mut Vec::new()).is_err());
+        }
+    }
+}
+
+mod config_archives {
+    use super::*;
+    use rscache::definition::config_archives::{ConfigArchive, CONFIG_INDEX};
+
+    #[test]
+    fn every_archive_reads() {
+        let builder = ConfigArchive::ALL.into_iter().fold(
+            FixtureBuilder::new().index(CONFIG_INDEX, 6),
+            |builder, archive| {
+                let files = vec![archive.name().as_bytes().to_vec(); 2];
+                builder.archive(CONFIG_INDEX, ArchiveSpec::new(archive.archive_id(), files))
+            },
+        );
+        let cache = builder.build().cache();
+
+        for archive in ConfigArchive::ALL {
+            let buffer = cache.read_config(archive).unwrap();
+            assert!(!buffer.is_empty(), "{:?}", archive);
+            assert!(buffer.starts_with(archive.name().as_bytes()), "{:?}", archive);
+        }
+    }
+
+    #[test]
+    fn ids_and_names() {
+        let ids: Vec<u32> = ConfigArchive::ALL.iter().map(|archive| archive.archive_id()).collect();
+        let mut sorted = ids.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(ids, sorted);
+
+        for archive in ConfigArchive::ALL {
+            assert_eq!(ConfigArchive::from_archive_id(archive.archive_id()), Some(archive));
+        }
+        assert_eq!(ConfigArchive::from_archive_id(7), None);
+        assert_eq!(ConfigArchive::SpotAnimation.name(), "spot_animation");
+    }
+
+    #[test]
+    fn loaders_use_the_layout() {
+        let cache = fixture::fixture().cache();
+
+        assert_eq!(
+            cache.read_config(ConfigArchive::Item).unwrap().as_slice(),
+            cache.read_decoded(CONFIG_INDEX, 10).unwrap().as_slice()
+        );
+        assert!(cache.read_config(ConfigArchive::VarBit).is_err());
+    }
+
+    #[cfg(feature = "rs3")]
+    #[test]
+    fn rs3_indices() {
+        use rscache::definition::config_archives::ConfigIndex;
+
+        for index in ConfigIndex::ALL {
+            assert_eq!(ConfigIndex::from_index_id(index.index_id()), Some(index));
+        }
+        assert_eq!(ConfigIndex::Item.index_id(), 19);
+        assert_eq!(ConfigIndex::Item.name(), ConfigArchive::Item.name());
+    }
+}
+
+mod preservation {
+    use super::*;
+    use rscache::{bundle, codec, reference_table::ReferenceTable, Cache};
+
+    /// Every archive listed in a reference table, as `(index_id, archive_id, crc)`.
+    fn listed(cache: &Cache) -> Vec<(u8, u32, u32)> {
+        let mut archives = Vec::new();
+        for index_id in 0..=10 {
+            let table = ReferenceTable::parse(&cache.read_decoded(255, index_id).unwrap()).unwrap();
+            for archive in table.archives {
+                archives.push((index_id as u8, archive.id, archive.crc));
+            }
+        }
+        archives
+    }
+
+    #[test]
+    fn crcs_cover_raw_reads() {
+        let cache = fixture::fixture().cache();
+        let archives = listed(&cache);
+        assert_eq!(archives.len(), 9);
+
+        for (index_id, archive_id, crc) in archives {
+            let container = cache.read_raw(index_id, archive_id).unwrap();
+            let (body, _) = codec::split_trailer(&container);
+
+            assert_eq!(crc32fast::hash(body), crc, "{} {}", index_id, archive_id);
+            assert_eq!(cache.read(index_id, archive_id).unwrap().as_slice(), container);
+        }
+    }
+
+    #[test]
+    fn raw_reads_skip_transforms() {
+        let fixture = fixture::fixture();
+        let cache = Cache::builder(fixture.path())
+            .with_read_transform(|_, _, data| Ok(data.into_iter().rev().collect()))
+            .open()
+            .unwrap();
+        let untransformed = fixture.cache();
+
+        let raw = cache.read_raw(2, 10).unwrap();
+        assert!(codec::is_identical_container(&raw, &untransformed.read(2, 10).unwrap()));
+        assert!(!codec::is_identical_container(&raw, &cache.read(2, 10).unwrap()));
+    }
+
+    #[test]
+    fn reencoding_changes_containers() {
+        let cache = fixture::fixture().cache();
+        let raw = cache.read_raw(2, 10).unwrap();
+        let decoded = codec::decode(&raw).unwrap();
+
+        let reencoded = codec::encode(&decoded, rscache::codec::Compression::Bzip2).unwrap();
+        assert_eq!(codec::decode(&reencoded).unwrap().as_slice(), decoded.as_slice());
+        assert!(!codec::is_identical_container(&raw, &reencoded));
+    }
+
+    #[test]
+    fn bundles_carry_raw_containers() {
+        let source = fixture::fixture();
+        let cache = source.cache();
+        let mut buffer = Vec::new();
+        cache.export_index(5, &mut buffer).unwrap();
+
+        let target = FixtureBuilder::new().index(0, 5).build();
+        bundle::import_index(target.path(), buffer.as_slice()).unwrap();
+        let imported = target.cache();
+
+        for archive_id in [0, 1] {
+            assert!(codec::is_identical_container(
+                &imported.read_raw(5, archive_id).unwrap(),
+                &cache.read_raw(5, archive_id).unwrap()
+            ));
+        }
+        assert!(codec::is_identical_container(
+            &imported.read_raw(255, 5).unwrap(),
+            &cache.read_raw(255, 5).unwrap()
+        ));
+    }
+}
+
+mod decryption {
+    use super::*;
+    use rscache::{
+        codec,
+        decryption::{Decryption, DecryptionRegistry},
+        error::ReadError,
+        loader::osrs::{LocationLoader, MapLoader},
+        Cache,
+    };
+
+    fn open(fixture: &fixture::Fixture, registry: DecryptionRegistry) -> Cache {
+        Cache::builder(fixture.path()).decryption(registry).open().unwrap()
+    }
+
+    #[test]
+    fn registered_region() {
+        let fixture = fixture::fixture();
+        let mut registry = DecryptionRegistry::new();
+        assert_eq!(registry.insert_region(fixture::REGION_ID, fixture::LOCATION_KEYS), None);
+        let cache = open(&fixture, registry);
+
+        let mut location_loader = LocationLoader::new(&cache);
+        let location_def = location_loader.load_registered(fixture::REGION_ID).unwrap();
+        assert_eq!(location_def.data.len(), 2);
+
+        let unregistered = fixture.cache();
+        assert!(LocationLoader::new(&unregistered).load_registered(fixture::REGION_ID).is_err());
+    }
+
+    #[test]
+    fn explicit_keys_win() {
+        let fixture = fixture::fixture();
+        let mut registry = DecryptionRegistry::new();
+        registry.insert_region(fixture::REGION_ID, [0; 4]);
+        let cache = open(&fixture, registry);
+
+        assert!(LocationLoader::new(&cache).load_registered(fixture::REGION_ID).is_err());
+
+        let mut location_loader = LocationLoader::new(&cache);
+        let location_def = location_loader
+            .load(fixture::REGION_ID, &fixture::LOCATION_KEYS)
+            .unwrap();
+        assert_eq!(location_def.data.len(), 2);
+        let keys = Decryption::Xtea(fixture::LOCATION_KEYS);
+        assert!(cache.read_decoded_with(5, 1, Some(&keys)).is_ok());
+    }
+
+    #[test]
+    fn read_decoded_precedence() {
+        let fixture = fixture::fixture();
+        let keys = Decryption::Xtea(fixture::LOCATION_KEYS);
+        let wrong = Decryption::Xtea([0; 4]);
+
+        let mut registry = DecryptionRegistry::new();
+        registry.insert_index(5, wrong);
+        registry.insert_named(5, "l50_50", keys);
+        assert_eq!(registry.get(5, 1, Some(rscache::util::djd2::hash("l50_50"))), Some(&keys));
+        assert_eq!(registry.get(5, 1, None), Some(&wrong));
+        assert_eq!(registry.get(4, 1, None), None);
+
+        let cache = open(&fixture, registry.clone());
+        assert!(cache.read_decoded(5, 1).is_ok());
+        // the index-wide keys apply to the unencrypted map as well.
+        assert!(cache.read_decoded(5, 0).is_err());
+        assert!(cache.read_decoded_with(5, 0, None).is_ok());
+
+        registry.insert(5, 1, wrong);
+        let cache = open(&fixture, registry);
+        assert!(cache.read_decoded(5, 1).is_err());
+        assert_eq!(cache.decryption().len(), 3);
+    }
+
+    fn invalid_keys<T>(result: Result<T, rscache::Error>) -> bool {
+        matches!(result, Err(rscache::Error::Read(ReadError::InvalidKeys)))
+    }
+
+    #[test]
+    fn wrong_keys() {
+        let cache = fixture::fixture().cache();
+        let container = cache.read(5, 1).unwrap();
+
+        assert!(codec::decode_with_keys(&container, &fixture::LOCATION_KEYS).is_ok());
+        assert!(invalid_keys(codec::decode_with_keys(&container, &[0; 4])));
+        assert!(invalid_keys(LocationLoader::new(&cache).load(fixture::REGION_ID, &[1; 4])));
+        // malformed headers aren't blamed on the keys.
+        assert!(!invalid_keys(codec::decode_with_keys(&container[..4], &[0; 4])));
+    }
+
+    #[test]
+    fn encrypted_map() {
+        let fixture = fixture::defaults()
+            .archive(
+                5,
+                ArchiveSpec::new(0, vec![fixture::map()])
+                    .named("m50_50")
+                    .keys(fixture::LOCATION_KEYS),
+            )
+            .build();
+        let cache = fixture.cache();
+
+        let mut map_loader = MapLoader::new(&cache);
+        assert!(map_loader.load(fixture::REGION_ID).is_err());
+        assert!(invalid_keys(map_loader.load_with_keys(fixture::REGION_ID, &[0; 4])));
+        let map = map_loader
+            .load_with_keys(fixture::REGION_ID, &fixture::LOCATION_KEYS)
+            .unwrap();
+        assert_eq!(map.map_data(0, 0, 0).height, 20);
+
+        // loaded maps are cached, the keys don't matter anymore.
+        assert!(map_loader.load(fixture::REGION_ID).is_ok());
+    }
+}
+
+mod placement {
+    use super::*;
+    use runefs::{MAIN_DATA, SECTOR_SIZE};
+
+    /// Every stored archive of the default fixture, including the reference tables.
+    const ARCHIVES: [(u8, u32); 11] = [
+        (0, 1),
+        (0, 2),
+        (2, 6),
+        (2, 9),
+        (2, 10),
+        (5, 0),
+        (5, 1),
+        (7, fixture::EXPANDED_ARCHIVE_ID),
+        (10, 0),
+        (255, 0),
+        (255, 2),
+    ];
+
+    #[test]
+    fn sectors_reproduce_reads() {
+        let fixture = fixture::fixture();
+        let cache = fixture.cache();
+        let dat2 = std::fs::read(fixture.path().join(MAIN_DATA)).unwrap();
+
+        for (index_id, archive_id) in ARCHIVES {
+            let placement = cache.archive_placement(index_id, archive_id).unwrap();
+            assert_eq!(placement.sectors.len(), placement.length.div_ceil(placement.data_len()));
+            assert_eq!(placement.sectors[0], placement.sector);
+
+            let mut data = Vec::new();
+            for &sector in &placement.sectors {
+                let start = sector as usize * SECTOR_SIZE + placement.header_len();
+                let len = placement.data_len().min(placement.length - data.len());
+                data.extend_from_slice(&dat2[start..start + len]);
+            }
+            assert_eq!(data, cache.read(index_id, archive_id).unwrap().as_slice());
+        }
+
+        let raw = cache.archive_placement(0, 1).unwrap();
+        assert!(raw.sectors.len() > 1);
+        let expanded = cache.archive_placement(7, fixture::EXPANDED_ARCHIVE_ID).unwrap();
+        assert_eq!(expanded.data_len(), 510);
+    }
+
+    #[test]
+    fn missing_archive() {
+        let cache = fixture::fixture().cache();
+
+        assert!(cache.archive_placement(2, 11).is_err());
+        assert!(cache.archive_placement(100, 0).is_err());
+    }
+}
+
+mod world_objects {
+    use super::*;
+    use rscache::{
+        decryption::DecryptionRegistry,
+        definition::osrs::{LocationDefinition, Position},
+        loader::osrs::{LocationLoader, MapLoader, ObjectLoader, WorldObjects},
+        util::instance::Region,
+        Cache,
+    };
+
+    const BASE: u16 = 50 << 6;
+
+    /// The default objects and a 2×3 table as object 2.
+    fn objects() -> Vec<Vec<u8>> {
+        let mut objects = fixture::objects();
+        let mut table = vec![2];
+        table.extend(b"Fixture table\0");
+        table.extend([14, 2, 15, 3, 0]);
+        objects.push(table);
+        objects
+    }
+
+    /// The door as ground decoration below the fountain at local (1, 2), the
+    /// table at local (10, 10) rotated once.
+    fn locations() -> Vec<u8> {
+        let mut buffer = vec![1, (1 << 6 | 2) + 1, 22 << 2, 0];
+        buffer.extend([1, (1 << 6 | 2) + 1, 10 << 2, 0]);
+        buffer.push(1);
+        buffer.extend((0x8000_u16 | ((10 << 6 | 10) + 1)).to_be_bytes());
+        buffer.extend([10 << 2 | 1, 0, 0]);
+        buffer
+    }
+
+    /// Plain locations in region 50, 50 and a copy in region 50, 51.
+    fn world() -> fixture::Fixture {
+        fixture::defaults()
+            .archive(2, ArchiveSpec::new(6, objects()))
+            .archive(5, ArchiveSpec::new(1, vec![locations()]).named("l50_50"))
+            .archive(5, ArchiveSpec::new(2, vec![locations()]).named("l50_51"))
+            .build()
+    }
+
+    fn names(objects: &[rscache::loader::osrs::PlacedObject]) -> Vec<&str> {
+        objects
+            .iter()
+            .map(|object| object.definition.as_ref().unwrap().name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn registered_keys() {
+        let fixture = fixture::fixture();
+        let mut registry = DecryptionRegistry::new();
+        registry.insert_region(fixture::REGION_ID, fixture::LOCATION_KEYS);
+        let cache = Cache::builder(fixture.path()).decryption(registry).open().unwrap();
+        let objects = ObjectLoader::new(&cache).unwrap();
+        let mut world = WorldObjects::new(&cache, &objects);
+
+        let fountain = world.at(Position::new(BASE + 1, BASE + 2, 0)).unwrap();
+        assert_eq!(names(fountain), ["Fixture fountain"]);
+        assert_eq!(fountain[0].location.loc_type, 10);
+        assert_eq!(fountain[0].footprint.position, Position::new(BASE + 1, BASE + 2, 0));
+
+        // object 2 isn't defined in the default fixture.
+        let unknown = world.at(Position::new(BASE + 3, BASE + 4, 0)).unwrap();
+        assert_eq!(unknown.len(), 1);
+        assert!(unknown[0].definition.is_none());
+
+        assert!(world.at(Position::new(BASE + 1, BASE + 2, 1)).unwrap().is_empty());
+        assert!(world.at(Position::new(BASE, BASE, 0)).unwrap().is_empty());
+        assert_eq!(world.len(), 1);
+
+        // regions without locations are empty, but loaded.
+        assert!(world.at(Position::new(0, 0, 0)).unwrap().is_empty());
+        assert!(world.contains_region(0));
+
+        let unregistered = fixture.cache();
+        let mut world = WorldObjects::new(&unregistered, &objects);
+        assert!(world.at(Position::new(BASE + 1, BASE + 2, 0)).is_err());
+    }
+
+    #[test]
+    fn stacked_objects() {
+        let fixture = world();
+        let cache = fixture.cache();
+        let objects = ObjectLoader::new(&cache).unwrap();
+        let mut world = WorldObjects::new(&cache, &objects);
+
+        let tile = world.at(Position::new(BASE + 1, BASE + 2, 0)).unwrap();
+        assert_eq!(names(tile), ["Fixture door", "Fixture fountain"]);
+        assert_eq!(tile[0].location.loc_type, 22);
+
+        let table = &world.at(Position::new(BASE + 10, BASE + 10, 0)).unwrap()[0];
+        assert_eq!((table.footprint.size_x, table.footprint.size_y), (3, 2));
+        assert!(table.footprint.contains(Position::new(BASE + 12, BASE + 11, 0)));
+        assert!(!table.footprint.contains(Position::new(BASE + 11, BASE + 12, 0)));
+        assert!(!table.footprint.contains(Position::new(BASE + 10, BASE + 10, 1)));
+        // only the south west tile is indexed.
+        assert!(world.get(Position::new(BASE + 11, BASE + 10, 0)).is_empty());
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let fixture = world();
+        let cache = fixture.cache();
+        let objects = ObjectLoader::new(&cache).unwrap();
+        let mut world = WorldObjects::new(&cache, &objects).with_capacity(2);
+        let south = Position::new(BASE + 1, BASE + 2, 0);
+        let north = Position::new(BASE + 1, BASE + 64 + 2, 0);
+
+        assert_eq!(world.at(south).unwrap().len(), 2);
+        assert_eq!(world.at(north).unwrap().len(), 2);
+        assert_eq!(world.at(south).unwrap().len(), 2);
+        assert_eq!(world.len(), 2);
+
+        world.at(Position::new(0, 0, 0)).unwrap();
+        assert_eq!(world.len(), 2);
+        assert!(world.contains_region(fixture::REGION_ID));
+        assert!(!world.contains_region(fixture::REGION_ID + 1));
+        assert!(world.get(north).is_empty());
+
+        // evicted regions are read again on lookup.
+        assert_eq!(world.at(north).unwrap().len(), 2);
+        assert!(!world.contains_region(fixture::REGION_ID));
+    }
+
+    #[test]
+    fn from_regions() {
+        let fixture = world();
+        let cache = fixture.cache();
+        let objects = ObjectLoader::new(&cache).unwrap();
+        let map = MapLoader::new(&cache).load(fixture::REGION_ID).unwrap().clone();
+        let locations = LocationLoader::new(&cache)
+            .load_registered(fixture::REGION_ID)
+            .unwrap()
+            .clone();
+        let region = Region::new(map, locations);
+
+        let mut world = WorldObjects::from_regions(&objects, [&region]).with_capacity(1);
+        assert_eq!(world.get(Position::new(BASE + 1, BASE + 2, 0)).len(), 2);
+
+        // without a cache other regions stay empty and nothing is evicted.
+        assert!(world.at(Position::new(BASE, BASE + 64, 0)).unwrap().is_empty());
+        assert!(world.contains_region(fixture::REGION_ID));
+        assert_eq!(world.len(), 1);
+
+        world.insert(&LocationDefinition::default());
+        assert_eq!(world.len(), 2);
+        assert!(world.remove(0));
+        assert!(!world.remove(0));
+    }
+}
+
+mod archives {
+    use super::*;
+    use rscache::{util::djd2, Error};
+    use runefs::error::ReadError;
+
+    #[test]
+    fn ascending_with_locations() {
+        let cache = fixture::fixture().cache();
+
+        let archives: Vec<_> = cache.archives(2).unwrap().collect();
+        let ids: Vec<_> = archives.iter().map(|archive| archive.id).collect();
+        assert_eq!(ids, [6, 9, 10]);
+        for archive in &archives {
+            let archive_ref = cache.archive_ref(2, archive.id).unwrap();
+            assert_eq!(archive.sector as usize, archive_ref.sector);
+            assert_eq!(archive.length, archive_ref.length);
+            assert_eq!(archive.length, cache.read_raw(2, archive.id).unwrap().len());
+            assert_eq!(archive.name_hash, None);
+        }
+
+        let names: Vec<_> = cache.archives(5).unwrap().map(|archive| archive.name_hash).collect();
+        assert_eq!(names, [Some(djd2::hash("m50_50")), Some(djd2::hash("l50_50"))]);
+    }
+
+    #[test]
+    fn empty_and_missing() {
+        let cache = fixture::fixture().cache();
+
+        assert_eq!(cache.archives(1).unwrap().count(), 0);
+        assert!(matches!(
+            cache.archives(42).map(|archives| archives.count()),
+            Err(Error::RuneFs(runefs::Error::Read(ReadError::IndexNotFound(42))))
+        ));
+        // the reference table index lists the table of every index.
+        let tables: Vec<_> = cache.archives(255).unwrap().map(|archive| archive.id).collect();
+        assert_eq!(tables, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    }
+}
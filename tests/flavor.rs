@@ -0,0 +1,148 @@
+mod test_util;
+
+use std::fs;
+
+use rscache::{
+    error::FlavorError,
+    flavor::{Evidence, OSRS_MAX_INDICES},
+    Cache, CacheFlavor,
+};
+use test_util::fixture::{self, Fixture};
+
+/// The default fixture with more indices than any OSRS cache has.
+fn rs3_layout() -> Fixture {
+    (11..=OSRS_MAX_INDICES as u8)
+        .fold(fixture::defaults(), |builder, index_id| {
+            builder.index(index_id, 6)
+        })
+        .build()
+}
+
+fn flavor_error(result: Result<Cache, rscache::Error>) -> Option<FlavorError> {
+    match result {
+        Err(rscache::Error::Flavor(error)) => Some(error),
+        _ => None,
+    }
+}
+
+#[test]
+fn osrs() -> Result<(), rscache::Error> {
+    assert_eq!(
+        rscache::detect("./data/osrs_cache")?,
+        CacheFlavor::Osrs {
+            revision_estimate: Some(2097)
+        }
+    );
+
+    let fixture = fixture::fixture();
+    let evidence = Evidence::collect(fixture.path())?;
+    assert_eq!(evidence.index_ids, (0..=10).collect::<Vec<_>>());
+    assert_eq!(evidence.reference_tables, 11);
+    assert_eq!(
+        evidence.protocols.iter().copied().collect::<Vec<_>>(),
+        vec![5, 6, 7]
+    );
+    assert!(evidence.unreadable_tables.is_empty());
+    assert_eq!(
+        evidence.flavor(),
+        CacheFlavor::Osrs {
+            revision_estimate: Some(1)
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rs3() -> Result<(), rscache::Error> {
+    let fixture = rs3_layout();
+    assert_eq!(
+        rscache::detect(fixture.path())?,
+        CacheFlavor::Rs3 {
+            index_count: OSRS_MAX_INDICES + 1,
+            jcache: false
+        }
+    );
+
+    let dir = tempfile::tempdir()?;
+    for index_id in [2, 12, 13] {
+        fs::write(dir.path().join(format!("js5-{}.jcache", index_id)), [])?;
+    }
+    assert_eq!(
+        rscache::detect(dir.path())?,
+        CacheFlavor::Rs3 {
+            index_count: 3,
+            jcache: true
+        }
+    );
+    assert_eq!(
+        flavor_error(Cache::builder(dir.path()).check_flavor(true).open()),
+        Some(FlavorError::Jcache)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn legacy_and_unknown() -> Result<(), rscache::Error> {
+    let dir = tempfile::tempdir()?;
+    assert_eq!(
+        rscache::detect(dir.path())?,
+        CacheFlavor::Unknown {
+            evidence: Evidence::default()
+        }
+    );
+
+    for name in [
+        "main_file_cache.dat",
+        "main_file_cache.idx0",
+        "main_file_cache.idx1",
+    ] {
+        fs::write(dir.path().join(name), [])?;
+    }
+    assert_eq!(
+        rscache::detect(dir.path())?,
+        CacheFlavor::Legacy { index_count: 2 }
+    );
+    assert_eq!(
+        flavor_error(Cache::builder(dir.path()).check_flavor(true).open()),
+        Some(FlavorError::Legacy)
+    );
+
+    assert!(rscache::detect(dir.path().join("missing")).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn broken_reference_table() -> Result<(), rscache::Error> {
+    let fixture = fixture::fixture();
+    // points the table of index 1 past the end of the data file.
+    let path = fixture.path().join("main_file_cache.idx255");
+    let mut idx = fs::read(&path)?;
+    idx[9..12].copy_from_slice(&[0xFF; 3]);
+    fs::write(&path, idx)?;
+
+    let evidence = Evidence::collect(fixture.path())?;
+    assert_eq!(evidence.unreadable_tables, vec![1]);
+    assert!(matches!(evidence.flavor(), CacheFlavor::Osrs { .. }));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "rs3"))]
+#[test]
+fn check_flavor() {
+    let fixture = rs3_layout();
+    assert_eq!(
+        flavor_error(Cache::builder(fixture.path()).check_flavor(true).open()),
+        Some(FlavorError::Rs3)
+    );
+    // the check is opt-in.
+    assert!(Cache::new(fixture.path()).is_ok());
+
+    assert!(Cache::builder("./data/osrs_cache")
+        .check_flavor(true)
+        .open()
+        .is_ok());
+}
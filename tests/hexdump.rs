@@ -0,0 +1,82 @@
+mod test_util;
+
+use rscache::{loader::osrs::ItemLoader, util::hexdump::HexDump};
+use test_util::fixture;
+
+/// Printable bytes `<` to `[`, followed by a few that aren't and a space.
+fn bytes() -> Vec<u8> {
+    let mut bytes: Vec<u8> = (0x3C..0x5C).collect();
+    bytes.extend([0x00, 0x7F, 0xFF, b' ']);
+    bytes
+}
+
+#[test]
+fn lines() {
+    assert_eq!(
+        HexDump::new(bytes()).to_string(),
+        "\
+00000000  3c 3d 3e 3f 40 41 42 43  44 45 46 47 48 49 4a 4b  |<=>?@ABCDEFGHIJK|
+00000010  4c 4d 4e 4f 50 51 52 53  54 55 56 57 58 59 5a 5b  |LMNOPQRSTUVWXYZ[|
+00000020  00 7f ff 20                                       |... |
+"
+    );
+    assert_eq!(HexDump::new(&[][..]).to_string(), "");
+}
+
+#[test]
+fn bytes_per_line() {
+    assert_eq!(
+        HexDump::new(&bytes()[..10]).bytes_per_line(4).to_string(),
+        "\
+00000000  3c 3d 3e 3f  |<=>?|
+00000004  40 41 42 43  |@ABC|
+00000008  44 45        |DE|
+"
+    );
+    assert_eq!(
+        HexDump::new(&bytes()[..2]).bytes_per_line(0).to_string(),
+        "00000000  3c  |<|\n00000001  3d  |=|\n"
+    );
+}
+
+#[test]
+fn truncated() {
+    let bytes = bytes();
+    assert_eq!(
+        HexDump::new(&bytes[..]).max_len(20).to_string(),
+        "\
+00000000  3c 3d 3e 3f 40 41 42 43  44 45 46 47 48 49 4a 4b  |<=>?@ABCDEFGHIJK|
+00000010  4c 4d 4e 4f                                       |LMNO|
+... 16 more bytes
+"
+    );
+
+    // limits past the end show everything.
+    assert_eq!(
+        HexDump::new(&bytes[..]).max_len(100).to_string(),
+        HexDump::new(&bytes[..]).to_string()
+    );
+    assert_eq!(HexDump::new(&bytes[..]).max_len(0).to_string(), "... 36 more bytes\n");
+}
+
+#[test]
+fn conveniences() -> Result<(), rscache::Error> {
+    let cache = fixture::fixture().cache();
+
+    let dump = cache.hexdump(0, 2, 32)?;
+    assert_eq!(dump.bytes(), fixture::pattern(100));
+    assert_eq!(dump.to_string().lines().count(), 3);
+    assert!(dump.to_string().ends_with("... 68 more bytes\n"));
+
+    let dump = ItemLoader::raw_hexdump(&cache, 0)?;
+    assert_eq!(dump.bytes(), ItemLoader::raw(&cache, 0)?);
+    assert_eq!(
+        dump.to_string(),
+        "\
+00000000  02 42 72 6f 6e 7a 65 20  66 69 78 74 75 72 65 00  |.Bronze fixture.|
+00000010  0c 00 00 00 fa 00                                 |......|
+"
+    );
+
+    Ok(())
+}
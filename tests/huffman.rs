@@ -0,0 +1,53 @@
+mod test_util;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rscache::util::Huffman;
+
+fn osrs_huffman() -> Huffman {
+    Huffman::new(test_util::osrs_cache().huffman_table().unwrap()).unwrap()
+}
+
+#[test]
+fn compresses_like_the_client() -> Result<(), rscache::Error> {
+    let huffman = osrs_huffman();
+
+    assert_eq!(huffman.compress("rs-cache"), [174, 128, 35, 32, 208, 96]);
+    assert_eq!(huffman.compress(""), b"");
+    assert_eq!(huffman.decompress(&huffman.compress(""), 0)?, b"");
+
+    Ok(())
+}
+
+#[test]
+fn round_trip() -> Result<(), rscache::Error> {
+    let huffman = osrs_huffman();
+    let mut rng = StdRng::seed_from_u64(0x4FF);
+
+    for _ in 0..1000 {
+        let len = rng.gen_range(1..=80);
+        let text: String = (0..len).map(|_| rng.gen_range(' '..='~')).collect();
+
+        let compressed = huffman.compress(&text);
+        assert_eq!(huffman.decompress(&compressed, text.len())?, text.as_bytes());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn drops_unencodable_characters() -> Result<(), rscache::Error> {
+    let mut sizes = vec![8; 256];
+    sizes[b'x' as usize] = 0;
+    let huffman = Huffman::new(sizes)?;
+
+    assert!(!huffman.can_encode('x'));
+    assert!(!huffman.can_encode('€'));
+    assert!(huffman.can_encode('é'));
+    assert_eq!(huffman.compress("ax€é"), huffman.compress("aé"));
+
+    let text = "fix €5 café";
+    let kept = text.chars().filter(|&character| huffman.can_encode(character)).count();
+    assert_eq!(huffman.decompress(&huffman.compress(text), kept)?, b"fi 5 caf\xE9");
+
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeSet;
+
+use rscache::{
+    definition::osrs::{Location, LocationDefinition, MapData, MapDefinition, ObjectDefinition},
+    loader::osrs::ObjectLoader,
+    util::instance::{self, Region, ZoneTemplate, CHUNK_SIZE, EMPTY_ZONE, INSTANCE_SIZE},
+};
+
+const REGION_X: u16 = 50;
+const REGION_Y: u16 = 51;
+
+fn object(id: u32, size_x: u8, size_y: u8) -> (u32, ObjectDefinition) {
+    let mut object = ObjectDefinition {
+        id,
+        ..ObjectDefinition::default()
+    };
+    object.model_data.size_x = size_x;
+    object.model_data.size_y = size_y;
+
+    (id, object)
+}
+
+/// Every tile of plane 1 has a distinct height and an overlay in rotation 1.
+fn region(locations: Vec<Location>) -> Region {
+    let mut map = MapDefinition {
+        region_x: REGION_X,
+        region_y: REGION_Y,
+        data: vec![vec![vec![MapData::default(); 64]; 64]; 4],
+    };
+    for x in 0..64 {
+        for y in 0..64 {
+            map.data[1][x][y] = MapData {
+                height: (x * 4 + y) as u8,
+                attr_opcode: 2 + 3 * 4 + 1,
+                overlay_id: 7,
+                overlay_path: 3,
+                overlay_rotation: 1,
+                ..MapData::default()
+            };
+        }
+    }
+
+    let locations = LocationDefinition {
+        id: REGION_X << 8 | REGION_Y,
+        region_x: REGION_X,
+        region_y: REGION_Y,
+        data: locations,
+    };
+
+    Region::new(map, locations)
+}
+
+fn location(id: u32, orientation: u8, x: u16, y: u16, z: u16) -> Location {
+    Location {
+        id,
+        loc_type: 10,
+        orientation,
+        // positions are stored relative to the region id.
+        pos: (REGION_X + x, REGION_Y + y, z),
+    }
+}
+
+/// Tiles covered by something at `x`, `y` with the given size and orientation.
+fn footprint(x: i16, y: i16, size: (u8, u8), orientation: u8) -> BTreeSet<(i16, i16)> {
+    let (size_x, size_y) = if orientation & 1 == 1 { (size.1, size.0) } else { size };
+
+    (x..x + size_x as i16)
+        .flat_map(|x| (y..y + size_y as i16).map(move |y| (x, y)))
+        .collect()
+}
+
+#[test]
+fn zone_template() {
+    let template = ZoneTemplate::new(2, 400, 409, 3);
+
+    assert_eq!(template.packed, 2 << 24 | 400 << 14 | 409 << 3 | 3 << 1);
+    assert_eq!(template.plane(), 2);
+    assert_eq!(template.chunk_x(), 400);
+    assert_eq!(template.chunk_y(), 409);
+    assert_eq!(template.rotation(), 3);
+    assert_eq!(template.region_id(), 50 << 8 | 51);
+    assert_eq!(template.local_chunk(), (0, 1));
+}
+
+#[test]
+fn template_grid() {
+    let mut packed = [[[EMPTY_ZONE; INSTANCE_SIZE]; INSTANCE_SIZE]; 4];
+    packed[0][6][6] = ZoneTemplate::new(0, 400, 400, 1).packed;
+    packed[1][0][12] = ZoneTemplate::new(1, 401, 409, 0).packed;
+    packed[3][12][0] = ZoneTemplate::new(0, 240, 392, 2).packed;
+
+    let grid = instance::decode_template_grid(&packed);
+
+    assert_eq!(grid[0][6][6], Some(ZoneTemplate::new(0, 400, 400, 1)));
+    assert_eq!(grid[1][0][12].map(ZoneTemplate::rotation), Some(0));
+    assert_eq!(grid.iter().flatten().flatten().flatten().count(), 3);
+    assert_eq!(
+        instance::required_regions(&grid).into_iter().collect::<Vec<_>>(),
+        vec![30 << 8 | 49, 50 << 8 | 50, 50 << 8 | 51]
+    );
+}
+
+#[test]
+fn tiles_rotate() {
+    let region = region(Vec::new());
+    let objects = ObjectLoader::default();
+
+    // (rotation, source tile) -> destination tile.
+    let expected = |rotation, x: usize, y: usize| match rotation {
+        0 => (x, y),
+        1 => (y, 7 - x),
+        2 => (7 - x, 7 - y),
+        _ => (7 - y, x),
+    };
+
+    for rotation in 0..4 {
+        let chunk = region.extract_chunk(1, 2, 3, rotation, &objects);
+
+        assert_eq!(chunk.tiles.len(), CHUNK_SIZE);
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                let (rotated_x, rotated_y) = expected(rotation, x, y);
+                let tile = &chunk.tiles[rotated_x][rotated_y];
+                let source = region.map.map_data(16 + x, 24 + y, 1);
+
+                assert_eq!(tile.height, source.height, "rotation {} tile {}, {}", rotation, x, y);
+                assert_eq!(tile.overlay_rotation, (1 + rotation) & 3);
+                assert_eq!(tile.attr_opcode, 2 + 3 * 4 + ((1 + rotation) & 3));
+                assert_eq!(tile.overlay_path, 3);
+            }
+        }
+    }
+}
+
+#[test]
+fn full_turn_is_identity() {
+    for x in 0..CHUNK_SIZE as i16 {
+        for y in 0..CHUNK_SIZE as i16 {
+            for orientation in 0..4 {
+                for size in [(1, 1), (2, 1), (1, 3), (2, 2), (3, 2)] {
+                    let (mut rotated_x, mut rotated_y, mut rotated_orientation) = (x, y, orientation);
+                    for _ in 0..4 {
+                        (rotated_x, rotated_y) =
+                            instance::rotate(rotated_x, rotated_y, size, rotated_orientation, 1);
+                        rotated_orientation = (rotated_orientation + 1) & 3;
+                    }
+                    assert_eq!((rotated_x, rotated_y), (x, y));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn location_footprints_rotate() {
+    let sizes = [(1, 1), (2, 1), (1, 2), (2, 2), (3, 1), (3, 2)];
+    let objects: ObjectLoader = sizes
+        .iter()
+        .enumerate()
+        .map(|(id, &(size_x, size_y))| object(id as u32, size_x, size_y))
+        .collect();
+
+    for (id, &size) in sizes.iter().enumerate() {
+        for orientation in 0..4 {
+            for x in 0..CHUNK_SIZE as u16 {
+                for y in 0..CHUNK_SIZE as u16 {
+                    // chunk 1, 2 of plane 0.
+                    let region = region(vec![location(id as u32, orientation, 8 + x, 16 + y, 0)]);
+
+                    for rotation in 0..4 {
+                        let chunk = region.extract_chunk(0, 1, 2, rotation, &objects);
+                        let [rotated] = chunk.locations[..] else {
+                            panic!("expected a single location, got {:?}", chunk.locations);
+                        };
+
+                        assert_eq!(rotated.id, id as u32);
+                        assert_eq!(rotated.loc_type, 10);
+                        assert_eq!(rotated.orientation, (orientation + rotation) & 3);
+
+                        // the rotated footprint covers exactly the rotated tiles.
+                        let expected: BTreeSet<_> = footprint(x as i16, y as i16, size, orientation)
+                            .into_iter()
+                            .map(|(x, y)| instance::rotate(x, y, (1, 1), 0, rotation))
+                            .collect();
+                        assert_eq!(
+                            footprint(rotated.x, rotated.y, size, rotated.orientation),
+                            expected,
+                            "size {:?} orientation {} at {}, {} rotation {}",
+                            size,
+                            orientation,
+                            x,
+                            y,
+                            rotation
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn location_positions() {
+    // a 2x1 object at the chunk's south west tile, facing north.
+    let objects: ObjectLoader = [object(1, 2, 1)].into_iter().collect();
+    let region = region(vec![location(1, 0, 0, 0, 0)]);
+
+    let positions: Vec<_> = (0..4)
+        .map(|rotation| {
+            let location = region.extract_chunk(0, 0, 0, rotation, &objects).locations[0];
+            (location.x, location.y, location.orientation)
+        })
+        .collect();
+
+    assert_eq!(positions, vec![(0, 0, 0), (0, 6, 1), (6, 7, 2), (7, 0, 3)]);
+}
+
+#[test]
+fn locations_outside_the_chunk() {
+    let objects = ObjectLoader::default();
+    let region = region(vec![
+        location(1, 0, 7, 7, 0),
+        location(2, 0, 8, 7, 0),
+        location(3, 0, 7, 8, 0),
+        location(4, 0, 7, 7, 1),
+        location(5, 2, 0, 0, 0),
+    ]);
+
+    let ids: Vec<_> = region
+        .extract_chunk(0, 0, 0, 0, &objects)
+        .locations
+        .iter()
+        .map(|location| location.id)
+        .collect();
+    assert_eq!(ids, vec![1, 5]);
+
+    let ids: Vec<_> = region
+        .extract_zone(ZoneTemplate::new(1, 400, 408, 2), &objects)
+        .locations
+        .iter()
+        .map(|location| (location.id, location.x, location.y))
+        .collect();
+    assert_eq!(ids, vec![(4, 0, 0)]);
+}
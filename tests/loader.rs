@@ -4,53 +4,1036 @@ mod test_util;
 mod osrs {
     use super::test_util;
 
+    mod any {
+        use super::test_util;
+        use rscache::{
+            definition::{
+                osrs::{self, Definition, ItemDefinition},
+                DefinitionKind,
+            },
+            loader::osrs::{ItemLoader, NpcLoader, ObjectLoader},
+        };
+
+        #[test]
+        fn every_kind() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let items = ItemLoader::new(&cache)?;
+            let npcs = NpcLoader::new(&cache)?;
+            let objects = ObjectLoader::new(&cache)?;
+
+            for kind in DefinitionKind::ALL {
+                let archive_id = kind.config_archive().archive_id();
+                for id in [0, 1, 1042, 1513] {
+                    let definition = osrs::parse_any(kind, id, &cache.read_file(2, archive_id, id)?)?;
+                    let (name, json) = match kind {
+                        DefinitionKind::Item => (items.load(id).unwrap().name(), json(items.load(id))),
+                        DefinitionKind::Npc => (npcs.load(id).unwrap().name(), json(npcs.load(id))),
+                        DefinitionKind::Object => (objects.load(id).unwrap().name(), json(objects.load(id))),
+                    };
+
+                    assert_eq!(definition.id(), id);
+                    assert_eq!(definition.name(), name);
+                    assert_eq!(definition_json(&*definition), json);
+                }
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        fn names() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let items = ItemLoader::new(&cache)?;
+
+            assert_eq!(items.load(1042).unwrap().name(), Some("Blue partyhat"));
+            let unnamed = |name: &str| ItemDefinition {
+                name: name.to_owned(),
+                ..ItemDefinition::default()
+            };
+            assert_eq!(unnamed("null").name(), None);
+            assert_eq!(unnamed("").name(), None);
+
+            Ok(())
+        }
+
+        /// Every named definition of the kind as JSON lines, like the dump_definitions example.
+        #[cfg(feature = "serde")]
+        fn dump(kind: DefinitionKind) -> Result<String, rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let archive_id = kind.config_archive().archive_id();
+            let file_ids = cache.archive_metadata(2, archive_id)?.file_ids;
+            let buffer = cache.read_decoded(2, archive_id)?;
+            let files = rscache::util::split_group(&buffer, file_ids.len())?;
+
+            let mut dump = String::new();
+            for (&id, data) in file_ids.iter().zip(&files) {
+                let definition = osrs::parse_any(kind, id, data)?;
+                if definition.name().is_some() {
+                    dump += &definition.to_json().unwrap().to_string();
+                    dump.push('\n');
+                }
+            }
+
+            Ok(dump)
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn stable_json_dumps() -> Result<(), rscache::Error> {
+            for kind in DefinitionKind::ALL {
+                let first = dump(kind)?;
+
+                assert!(first.lines().count() > 1000);
+                assert_eq!(dump(kind)?, first);
+            }
+
+            Ok(())
+        }
+
+        #[cfg(feature = "serde")]
+        fn json<T: serde::Serialize>(definition: Option<&T>) -> Option<serde_json::Value> {
+            Some(serde_json::to_value(definition.unwrap()).unwrap())
+        }
+
+        #[cfg(not(feature = "serde"))]
+        fn json<T>(_: Option<&T>) -> Option<()> {
+            None
+        }
+
+        #[cfg(feature = "serde")]
+        fn definition_json(definition: &dyn Definition) -> Option<serde_json::Value> {
+            Some(definition.to_json().unwrap())
+        }
+
+        #[cfg(not(feature = "serde"))]
+        fn definition_json(_: &dyn Definition) -> Option<()> {
+            None
+        }
+    }
+
     mod items {
         use super::test_util;
-        use rscache::loader::osrs::ItemLoader;
+        use rscache::{
+            definition::{
+                osrs::{Definition, FetchDefinition, ItemDefinition},
+                Value,
+            },
+            loader::osrs::ItemLoader,
+        };
+
+        fn item_loader() -> ItemLoader {
+            ItemLoader::new(&test_util::osrs_cache()).unwrap()
+        }
+
+        #[test]
+        fn loader_matches_serial_fetch() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            // the loader parses in parallel with the rayon feature.
+            let serial = ItemDefinition::fetch_from_archive::<ItemDefinition>(&cache, 2, 10)?;
+            let item_loader = ItemLoader::new(&cache)?;
+
+            assert_eq!(item_loader.iter().count(), serial.len());
+            assert!(item_loader.iter().eq(serial.iter()));
+
+            Ok(())
+        }
+
+        #[test]
+        fn blue_partyhat() {
+            let item_loader = item_loader();
+            let item = item_loader.load(1042).unwrap();
+
+            assert_eq!(item.name, "Blue partyhat");
+            assert!(!item.stackable);
+            assert!(!item.members_only);
+        }
+
+        #[test]
+        fn magic_logs() {
+            let item_loader = item_loader();
+            let item = item_loader.load(1513).unwrap();
+
+            assert_eq!(item.name, "Magic logs");
+            assert!(!item.stackable);
+            assert!(item.members_only);
+        }
+
+        #[test]
+        fn ground_actions() {
+            let item_loader = item_loader();
+            let item = item_loader.load(1511).unwrap();
+
+            assert_eq!(item.name, "Logs");
+            assert_eq!(
+                item.ground_actions(),
+                [None, None, Some("Take"), Some("Light"), None]
+            );
+            assert_eq!(item.interface_options, ["", "", "", "", "Drop"]);
+            assert!(item.has_ground_action("Take"));
+            assert!(item.has_ground_action("Light"));
+            assert!(!item.has_ground_action("Drop"));
+            assert_eq!(item.inventory_model_data.ambient, 15);
+        }
+
+        #[test]
+        fn sorted_iteration() {
+            let item_loader = item_loader();
+            let ids: Vec<u32> = item_loader.iter().map(|(&id, _)| id).collect();
+
+            assert_eq!(ids.len(), 23_458);
+            assert!(ids.windows(2).all(|ids| ids[0] < ids[1]));
+        }
+
+        #[test]
+        fn noted() {
+            let item_loader = item_loader();
+            let item = item_loader.load(1512).unwrap();
+
+            assert!(item.stackable);
+            assert!(!item.members_only);
+        }
+
+        #[test]
+        fn placeholders() {
+            let item_loader = item_loader();
+            let whip = item_loader.load(4151).unwrap();
+            assert_eq!(whip.placeholder_id, Some(14032));
+            assert_eq!(whip.placeholder_template, None);
+
+            let placeholder = item_loader.load(14032).unwrap();
+            assert_eq!(placeholder.placeholder_id, Some(4151));
+            assert_eq!(placeholder.placeholder_template, Some(14401));
+        }
+
+        #[test]
+        fn params() {
+            let item_loader = item_loader();
+            let leather = item_loader.load(1741).unwrap();
+            assert_eq!(leather.params[&370], Value::Int(5));
+            assert_eq!(leather.params[&601].as_str(), Some("Tan Leather"));
+            assert_eq!(leather.params.len(), 17);
+
+            let glory = item_loader.load(1706).unwrap();
+            assert_eq!(glory.params[&451], Value::from("Edgeville"));
+            assert!(item_loader.load(4151).unwrap().params.is_empty());
+        }
+
+        #[test]
+        fn wear_positions_and_weight() -> Result<(), rscache::Error> {
+            // wear_pos1 3, wear_pos2 5, wear_pos3 11, weight -4500, terminator.
+            let buffer = [13, 3, 14, 5, 27, 11, 75, 0xEE, 0x6C, 0];
+            let item = ItemDefinition::new(88, &buffer)?;
+
+            assert_eq!(
+                (item.wear_pos1, item.wear_pos2, item.wear_pos3),
+                (Some(3), Some(5), Some(11))
+            );
+            assert_eq!(item.weight, -4500);
+
+            Ok(())
+        }
+
+        #[test]
+        fn stack_variants() {
+            let item_loader = item_loader();
+            let coins = item_loader.load(995).unwrap();
+
+            assert_eq!(
+                coins.stack_ids,
+                Some([996, 997, 998, 999, 1000, 1001, 1002, 1003, 1004, 0])
+            );
+            assert_eq!(
+                coins.stack_count,
+                Some([2, 3, 4, 5, 25, 100, 250, 1000, 10000, 0])
+            );
+            assert_eq!(item_loader.load(1511).unwrap().stack_ids, None);
+        }
+
+        #[test]
+        fn non_existent() {
+            let item_loader = item_loader();
+            assert!(item_loader.load(65_535).is_none());
+        }
+
+        #[test]
+        fn raw_matches_load() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let item_loader = ItemLoader::new(&cache)?;
+
+            for id in [0, 1042, 1512, 4151, 11802] {
+                let raw = ItemLoader::raw(&cache, id)?;
+                assert_eq!(&ItemDefinition::new(id, &raw)?, item_loader.load(id).unwrap());
+            }
+
+            Ok(())
+        }
+    }
+
+    mod enums {
+        use super::test_util;
+        use rscache::{
+            definition::{
+                osrs::{Definition, EnumDefinition},
+                Value,
+            },
+            loader::osrs::EnumLoader,
+        };
+
+        fn enum_loader() -> EnumLoader {
+            EnumLoader::new(&test_util::osrs_cache()).unwrap()
+        }
+
+        #[test]
+        fn skill_names() {
+            let enum_loader = enum_loader();
+            let skills = enum_loader.load(108).unwrap();
+
+            assert_eq!((skills.keytype, skills.valtype), ('i', 's'));
+            assert_eq!(skills.get(1), Some(&Value::from("Attack")));
+            assert_eq!(skills.string(3), "Ranged");
+            assert_eq!(skills.string(-1), "this skill");
+            assert_eq!(skills.int(1), 0);
+        }
+
+        #[test]
+        fn int_values() {
+            let enum_loader = enum_loader();
+            let enum_def = enum_loader.load(1301).unwrap();
+
+            assert_eq!((enum_def.keytype, enum_def.valtype), ('i', 'i'));
+            assert_eq!(enum_def.values.len(), 1232);
+            assert_eq!(enum_def.int(0), 982013790);
+            assert_eq!(enum_loader.iter().count(), 2328);
+        }
+
+        #[test]
+        fn large_enum() -> Result<(), rscache::Error> {
+            // keytype 'o', valtype 'i', default -1 and 5000 int values.
+            let mut buffer = vec![1, b'o', 2, b'i', 4, 0xFF, 0xFF, 0xFF, 0xFF, 6];
+            buffer.extend(5000_u16.to_be_bytes());
+            for key in 0..5000_i32 {
+                buffer.extend(key.to_be_bytes());
+                buffer.extend((key * 2).to_be_bytes());
+            }
+            buffer.push(0);
+            let enum_def = EnumDefinition::new(7, &buffer)?;
+
+            assert_eq!(enum_def.values.len(), 5000);
+            assert_eq!(enum_def.int(4999), 9998);
+            assert_eq!(enum_def.int(5000), -1);
+            assert_eq!(enum_def.string(0), "null");
+
+            buffer[9] = 7;
+            assert!(EnumDefinition::new(7, &buffer).is_err());
+
+            Ok(())
+        }
+
+        #[test]
+        fn raw_matches_load() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let enum_loader = EnumLoader::new(&cache)?;
+
+            for id in [0, 108, 1301] {
+                let raw = EnumLoader::raw(&cache, id)?;
+                assert_eq!(&EnumDefinition::new(id, &raw)?, enum_loader.load(id).unwrap());
+            }
+
+            Ok(())
+        }
+    }
+
+    mod params {
+        use super::test_util;
+        use rscache::{
+            definition::{
+                osrs::{Definition, ParamDefinition},
+                Value,
+            },
+            loader::osrs::{ItemLoader, ParamLoader},
+        };
+
+        #[test]
+        fn declared_types() {
+            let cache = test_util::osrs_cache();
+            let param_loader = ParamLoader::new(&cache).unwrap();
+            let item_loader = ItemLoader::new(&cache).unwrap();
+            let leather = item_loader.load(1741).unwrap();
+
+            for (id, value) in &leather.params {
+                let param = param_loader.load(*id).unwrap();
+                assert_eq!(param.is_string(), value.as_str().is_some(), "{}", id);
+            }
+
+            let spell_name = param_loader.load(601).unwrap();
+            assert_eq!(spell_name.stack_type, 's');
+            assert_eq!(spell_name.default_value(), Value::from("Spell"));
+            assert!(!spell_name.auto_disable);
+
+            let rune_count = param_loader.load(370).unwrap();
+            assert_eq!(rune_count.stack_type, 'i');
+            assert_eq!(rune_count.default_value(), Value::Int(0));
+            assert_eq!(param_loader.iter().count(), 746);
+        }
+
+        #[test]
+        fn opcodes() -> Result<(), rscache::Error> {
+            // type 'i', default -1, members only (4), terminator.
+            let param = ParamDefinition::new(9, &[1, b'i', 2, 0xFF, 0xFF, 0xFF, 0xFF, 4, 0])?;
+            assert_eq!(param.default_value(), Value::Int(-1));
+            assert_eq!(param.default_string, None);
+            assert!(!param.auto_disable);
+
+            // type 's', default "Attack".
+            let param = ParamDefinition::new(9, &[1, b's', 5, 65, 116, 116, 97, 99, 107, 0, 0])?;
+            assert_eq!(param.default_string.as_deref(), Some("Attack"));
+            assert!(param.auto_disable);
+            assert!(ParamDefinition::new(9, &[3, 0]).is_err());
+
+            Ok(())
+        }
+    }
+
+    mod structs {
+        use super::test_util;
+        use rscache::{
+            definition::{
+                osrs::{Definition, StructDefinition},
+                Value,
+            },
+            loader::osrs::StructLoader,
+        };
+
+        fn struct_loader() -> StructLoader {
+            StructLoader::new(&test_util::osrs_cache()).unwrap()
+        }
+
+        #[test]
+        fn mixed_params() {
+            let struct_loader = struct_loader();
+            let quest = struct_loader.load(300).unwrap();
+
+            assert_eq!(quest.params[&579], Value::from("Cook's Assistant"));
+            assert_eq!(quest.params[&614], Value::Int(0));
+            assert_eq!(quest.params.len(), 6);
+            assert_eq!(struct_loader.load(1).unwrap().params[&462].as_int(), Some(52));
+            assert_eq!(struct_loader.iter().count(), 601);
+        }
+
+        #[test]
+        fn empty() -> Result<(), rscache::Error> {
+            let struct_loader = struct_loader();
+            assert!(struct_loader.load(551).unwrap().params.is_empty());
+
+            let empty = StructDefinition::new(3, &[0])?;
+            assert_eq!(empty, StructDefinition { id: 3, ..StructDefinition::default() });
+            assert!(StructDefinition::new(3, &[]).is_err());
+
+            Ok(())
+        }
+
+        #[test]
+        fn params() -> Result<(), rscache::Error> {
+            // two params: int 7 = -2, string 70000 = "Lumbridge".
+            let mut buffer = vec![249, 2, 0, 0, 0, 7, 0xFF, 0xFF, 0xFF, 0xFE, 1, 0x01, 0x11, 0x70];
+            buffer.extend(b"Lumbridge\0");
+            buffer.push(0);
+            let struct_def = StructDefinition::new(3, &buffer)?;
+
+            assert_eq!(struct_def.params[&7], Value::Int(-2));
+            assert_eq!(struct_def.params[&70000].as_str(), Some("Lumbridge"));
+
+            Ok(())
+        }
+    }
+
+    mod varbits {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{Definition, VarbitDefinition},
+            loader::osrs::VarbitLoader,
+        };
+
+        #[test]
+        fn varbits() {
+            let varbit_loader = VarbitLoader::new(&test_util::osrs_cache()).unwrap();
+            let varbit = varbit_loader.load(2).unwrap();
+
+            assert_eq!((varbit.varp_id, varbit.lsb, varbit.msb), (318, 2, 2));
+            assert_eq!(varbit.extract(0b100), 1);
+            assert_eq!(varbit.extract(0b011), 0);
+            assert_eq!(varbit_loader.iter().count(), 8363);
+        }
+
+        #[test]
+        fn extract() -> Result<(), rscache::Error> {
+            // varp 300, bits 8..=15.
+            let varbit = VarbitDefinition::new(5, &[1, 0x01, 0x2C, 8, 15, 0])?;
+            assert_eq!(varbit.varp_id, 300);
+            assert_eq!(varbit.extract(0x0012_3456), 0x34);
+            assert_eq!(varbit.extract(-1), 0xFF);
+
+            let full = VarbitDefinition {
+                lsb: 0,
+                msb: 31,
+                ..VarbitDefinition::default()
+            };
+            assert_eq!(full.extract(-1), -1);
+            assert_eq!(full.extract(i32::MIN), i32::MIN);
+            assert_eq!(full.extract(123_456), 123_456);
+
+            let sign_bit = VarbitDefinition {
+                lsb: 31,
+                msb: 31,
+                ..VarbitDefinition::default()
+            };
+            assert_eq!(sign_bit.extract(i32::MIN), 1);
+            assert_eq!(sign_bit.extract(i32::MAX), 0);
+
+            Ok(())
+        }
+    }
+
+    mod floors {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{Definition, Hsl, OverlayDefinition},
+            loader::osrs::{OverlayLoader, UnderlayLoader},
+        };
+
+        #[test]
+        fn underlays() {
+            let cache = test_util::osrs_cache();
+            let underlay_loader = UnderlayLoader::new(&cache).unwrap();
+            assert_eq!(underlay_loader.iter().count(), 94);
+
+            let underlay = underlay_loader.load(1).unwrap();
+            assert_eq!(underlay.color, 0xB7_D4_9A);
+            assert_eq!(underlay.hue_multiplier(), 58);
+            assert_eq!(
+                underlay.hsl(),
+                Hsl {
+                    hue: 14,
+                    saturation: 101,
+                    lightness: 183
+                }
+            );
+        }
+
+        #[test]
+        fn overlays() {
+            let cache = test_util::osrs_cache();
+            let overlay_loader = OverlayLoader::new(&cache).unwrap();
+            assert_eq!(overlay_loader.iter().count(), 152);
+
+            let textured = overlay_loader.load(4).unwrap();
+            assert_eq!(textured.texture, Some(3));
+            assert!(textured.hide_underlay);
+
+            let blended = overlay_loader.load(50).unwrap();
+            assert_eq!(blended.color, 0xFF_00_FF);
+            assert!(!blended.hide_underlay);
+            assert_eq!(blended.secondary_color, Some(0x82_79_44));
+            assert_eq!(blended.hsl().hue, -42);
+            assert_eq!(
+                blended.secondary_hsl(),
+                Some(Hsl {
+                    hue: 36,
+                    saturation: 80,
+                    lightness: 99
+                })
+            );
+        }
+
+        #[test]
+        fn overlay_opcodes() -> Result<(), rscache::Error> {
+            // color, texture 7, shows the underlay, secondary color, terminator.
+            let buffer = [1, 0x12, 0x34, 0x56, 2, 7, 5, 7, 0, 0, 0xFF, 0];
+            let overlay = OverlayDefinition::new(3, &buffer)?;
+
+            assert_eq!(overlay.color, 0x12_34_56);
+            assert_eq!(overlay.texture, Some(7));
+            assert!(!overlay.hide_underlay);
+            assert_eq!(overlay.secondary_color, Some(0xFF));
+            assert!(OverlayDefinition::new(3, &[3, 0]).is_err());
+
+            Ok(())
+        }
+    }
+
+    mod areas {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{AreaDefinition, Definition},
+            loader::osrs::AreaLoader,
+        };
+
+        #[test]
+        fn labels_and_icons() {
+            let cache = test_util::osrs_cache();
+            let area_loader = AreaLoader::new(&cache).unwrap();
+            assert_eq!(area_loader.iter().count(), 621);
+
+            let lumbridge = area_loader.load(87).unwrap();
+            assert_eq!(lumbridge.name(), Some("Lumbridge"));
+            assert_eq!(lumbridge.text_color, 0xFF_FF_FF);
+            assert_eq!(lumbridge.text_size, 1);
+            assert_eq!(lumbridge.sprite_id, None);
+
+            let map_link = area_loader.load(13).unwrap();
+            assert_eq!(map_link.name(), None);
+            assert_eq!(map_link.sprite_id, Some(1534));
+            assert!(map_link.has_action("open"));
+            assert_eq!(map_link.menu_target.as_deref(), Some("Map"));
+            assert_eq!(map_link.category, Some(1062));
+        }
+
+        #[test]
+        fn opcodes() -> Result<(), rscache::Error> {
+            #[rustfmt::skip]
+            let buffer = [
+                // no sprite, hover sprite 0x8000 as a 4 byte smart.
+                1, 0x7F, 0xFF,
+                2, 0x80, 0x00, 0x80, 0x00,
+                // a triangle, a color and a byte per corner.
+                15, 3, 0, 1, 0, 2, 0xFF, 0xFF, 0, 0, 0, 4, 0, 5,
+                0, 0, 0, 0, 1, 0, 0, 0, 9, 1, 2, 3,
+                // flags, alignments and terminator.
+                7, 2, 29, 0, 30, 2, 0,
+            ];
+            let area = AreaDefinition::new(5, &buffer)?;
+
+            assert_eq!(area.sprite_id, None);
+            assert_eq!(area.hover_sprite_id, Some(0x8000));
+            assert_eq!(area.polygon, [(1, 2), (-1, 0), (4, 5)]);
+            assert_eq!(area.flags, 2);
+            assert_eq!(area.horizontal_alignment, Some(0));
+            assert_eq!(area.vertical_alignment, Some(2));
+
+            Ok(())
+        }
+    }
+
+    mod sequences {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{Definition, FrameId, SequenceDefinition},
+            loader::osrs::SequenceLoader,
+        };
+
+        #[test]
+        fn frames() {
+            let cache = test_util::osrs_cache();
+            let sequence_loader = SequenceLoader::new(&cache).unwrap();
+            assert_eq!(sequence_loader.iter().count(), 8317);
+
+            let idle = sequence_loader.load(808).unwrap();
+            assert_eq!(idle.frame_ids.len(), 12);
+            assert_eq!(idle.frame_lengths.len(), 12);
+            assert_eq!(idle.frame_ids[0], FrameId { group: 207, file: 236 });
+            assert_eq!(idle.duration(), 252);
+
+            let talking = sequence_loader.load(7).unwrap();
+            assert_eq!(talking.chat_frame_ids.len(), 9);
+            assert_eq!(talking.chat_frame_ids[0], FrameId { group: 1715, file: 50 });
+        }
+
+        #[test]
+        fn overrides_and_sounds() {
+            let cache = test_util::osrs_cache();
+            let sequence_loader = SequenceLoader::new(&cache).unwrap();
+            let sequence = sequence_loader.load(335).unwrap();
+
+            assert_eq!(sequence.loop_offset, Some(74));
+            assert_eq!(sequence.left_hand_item, Some(0));
+            assert_eq!(sequence.right_hand_item, Some(13309));
+            assert_eq!((sequence.max_loops, sequence.forced_priority), (1, 0));
+            assert_eq!(sequence.priority, Some(1));
+            assert_eq!(sequence.replay_mode, 2);
+            assert_eq!(sequence.frame_sounds[8], 824_337);
+        }
+
+        #[test]
+        fn frame_tables() -> Result<(), rscache::Error> {
+            #[rustfmt::skip]
+            let buffer = [
+                // 2 frames: lengths, files, groups.
+                1, 0, 2, 0, 4, 0, 6, 0, 7, 0, 8, 0x01, 0x02, 0x01, 0x02,
+                // 1 chat frame: file, group.
+                12, 1, 0, 3, 0, 9,
+                0,
+            ];
+            let sequence = SequenceDefinition::new(1, &buffer)?;
+
+            assert_eq!(sequence.frame_lengths, [4, 6]);
+            assert_eq!(
+                sequence.frame_ids,
+                [FrameId { group: 0x102, file: 7 }, FrameId { group: 0x102, file: 8 }]
+            );
+            assert_eq!(sequence.frame_ids[1].packed(), 0x0102_0008);
+            assert_eq!(sequence.chat_frame_ids, [FrameId { group: 9, file: 3 }]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn animaya_without_frames() -> Result<(), rscache::Error> {
+            #[rustfmt::skip]
+            let buffer = [
+                14, 0, 0, 0x30, 0x39,
+                15, 0, 1, 0, 3, 0x01, 0x02, 0x03,
+                16, 0, 1, 0, 20,
+                17, 2, 4, 5,
+                0,
+            ];
+            let sequence = SequenceDefinition::new(1, &buffer)?;
+
+            assert!(sequence.frame_ids.is_empty());
+            assert_eq!(sequence.duration(), 0);
+            assert_eq!(sequence.animaya_id, Some(12345));
+            assert_eq!(sequence.animaya_sounds.get(&3), Some(&0x01_02_03));
+            assert_eq!(sequence.animaya_range, Some((1, 20)));
+            assert_eq!(sequence.animaya_masks, [4, 5]);
+
+            Ok(())
+        }
+    }
+
+    mod spot_anims {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{Definition, SpotAnimDefinition},
+            loader::osrs::SpotAnimLoader,
+        };
+
+        #[test]
+        fn load_spot_anims() {
+            let cache = test_util::osrs_cache();
+            let spot_anim_loader = SpotAnimLoader::new(&cache).unwrap();
+            assert_eq!(spot_anim_loader.iter().count(), 1679);
+
+            let spot_anim = spot_anim_loader.load(0).unwrap();
+            assert_eq!(spot_anim.model_id, 3015);
+            assert_eq!(spot_anim.animation_id, Some(85));
+            assert_eq!((spot_anim.resize_x, spot_anim.resize_y), (128, 128));
+            assert!(spot_anim.recolors.is_empty());
+        }
+
+        #[test]
+        fn recolors() {
+            let cache = test_util::osrs_cache();
+            let spot_anim_loader = SpotAnimLoader::new(&cache).unwrap();
+            let spot_anim = spot_anim_loader.load(709).unwrap();
+
+            assert_eq!(spot_anim.model_id, 15836);
+            assert_eq!(spot_anim.rotation, 180);
+            assert_eq!(spot_anim.recolors, [(20, 414)]);
+        }
+
+        #[test]
+        fn all_opcodes() -> Result<(), rscache::Error> {
+            #[rustfmt::skip]
+            let buffer = [
+                1, 0x01, 0x00,
+                2, 0xFF, 0xFF,
+                4, 0, 64,
+                5, 0, 192,
+                6, 0, 90,
+                7, 30,
+                8, 40,
+                40, 2, 0, 1, 0, 2, 0, 3, 0, 4,
+                41, 1, 0, 5, 0, 6,
+                0,
+            ];
+            let spot_anim = SpotAnimDefinition::new(1, &buffer)?;
+
+            assert_eq!(spot_anim.model_id, 256);
+            assert_eq!(spot_anim.animation_id, None);
+            assert_eq!((spot_anim.resize_x, spot_anim.resize_y), (64, 192));
+            assert_eq!(spot_anim.rotation, 90);
+            assert_eq!((spot_anim.ambient, spot_anim.contrast), (30, 40));
+            assert_eq!(spot_anim.recolors, [(1, 2), (3, 4)]);
+            assert_eq!(spot_anim.retextures, [(5, 6)]);
+
+            Ok(())
+        }
+    }
+
+    mod inventories {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{Definition, InventoryDefinition},
+            loader::osrs::InventoryLoader,
+        };
+
+        #[test]
+        fn load_inventories() {
+            let cache = test_util::osrs_cache();
+            let inventory_loader = InventoryLoader::new(&cache).unwrap();
+            assert_eq!(inventory_loader.iter().count(), 628);
+
+            assert_eq!(inventory_loader.load(93).unwrap().capacity, 28);
+            assert_eq!(inventory_loader.load(94).unwrap().capacity, 14);
+            assert_eq!(inventory_loader.load(95).unwrap().capacity, 816);
+        }
+
+        #[test]
+        fn capacity_defaults_to_zero() -> Result<(), rscache::Error> {
+            let inventory = InventoryDefinition::new(7, &[0])?;
+
+            assert_eq!(inventory.id, 7);
+            assert_eq!(inventory.capacity, 0);
+
+            Ok(())
+        }
+    }
+
+    mod identity_kits {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{Definition, IdentityKitDefinition},
+            loader::osrs::IdentityKitLoader,
+        };
+
+        #[test]
+        fn load_identity_kits() {
+            let cache = test_util::osrs_cache();
+            let identity_kit_loader = IdentityKitLoader::new(&cache).unwrap();
+            assert_eq!(identity_kit_loader.iter().count(), 151);
+
+            let head = identity_kit_loader.load(0).unwrap();
+            assert_eq!(head.body_part, Some(0));
+            assert_eq!(head.models, [230]);
+            assert_eq!(head.head_models[..2], [Some(63), None]);
+            assert!(!head.non_selectable);
+
+            assert!(identity_kit_loader.load(35).unwrap().non_selectable);
+            assert_eq!(identity_kit_loader.load(34).unwrap().recolors, [(43072, 4550)]);
+        }
+
+        #[test]
+        fn all_opcodes() -> Result<(), rscache::Error> {
+            #[rustfmt::skip]
+            let buffer = [
+                1, 9,
+                2, 2, 0, 10, 0, 11,
+                3,
+                40, 1, 0, 1, 0, 2,
+                41, 1, 0, 3, 0, 4,
+                60, 0, 12,
+                69, 0, 13,
+                0,
+            ];
+            let identity_kit = IdentityKitDefinition::new(1, &buffer)?;
+
+            assert_eq!(identity_kit.body_part, Some(9));
+            assert_eq!(identity_kit.models, [10, 11]);
+            assert!(identity_kit.non_selectable);
+            assert_eq!(identity_kit.recolors, [(1, 2)]);
+            assert_eq!(identity_kit.retextures, [(3, 4)]);
+            assert_eq!(identity_kit.head_models[0], Some(12));
+            assert_eq!(identity_kit.head_models[9], Some(13));
+
+            Ok(())
+        }
+    }
+
+    mod combat_feedback {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{Definition, HitSplatDefinition},
+            loader::osrs::{HealthBarLoader, HitSplatLoader},
+        };
+
+        #[test]
+        fn load_hit_splats() {
+            let cache = test_util::osrs_cache();
+            let hit_splat_loader = HitSplatLoader::new(&cache).unwrap();
+            assert_eq!(hit_splat_loader.iter().count(), 14);
+
+            let damage = hit_splat_loader.load(0).unwrap();
+            assert_eq!(damage.middle_sprite_id, Some(1358));
+            assert_eq!(damage.text_color, 0xFF_FF_FF);
+            assert_eq!(damage.display_cycles, 50);
+            assert_eq!(damage.text(25), "25");
+        }
+
+        #[test]
+        fn load_health_bars() {
+            let cache = test_util::osrs_cache();
+            let health_bar_loader = HealthBarLoader::new(&cache).unwrap();
+            assert_eq!(health_bar_loader.iter().count(), 16);
+
+            let health_bar = health_bar_loader.load(1).unwrap();
+            assert_eq!(health_bar.width, 100);
+            assert_eq!(health_bar.display_cycles, 300);
+            assert_eq!(health_bar.fade_start_cycle, Some(280));
+        }
 
-        fn item_loader() -> ItemLoader {
-            ItemLoader::new(&test_util::osrs_cache()).unwrap()
+        #[test]
+        fn hit_splat_morphs() -> Result<(), rscache::Error> {
+            #[rustfmt::skip]
+            let buffer = [
+                // varbit 5, no varp, default 9, 2 ids: hidden and 3.
+                18, 0, 5, 0xFF, 0xFF, 0, 9, 1, 0xFF, 0xFF, 0, 3,
+                0,
+            ];
+            let hit_splat = HitSplatDefinition::new(1, &buffer)?;
+
+            assert_eq!(hit_splat.morph_varbit, Some(5));
+            assert_eq!(hit_splat.morph_varp, None);
+            assert_eq!(hit_splat.morph(0), None);
+            assert_eq!(hit_splat.morph(1), Some(3));
+            assert_eq!(hit_splat.morph(4), Some(9));
+
+            Ok(())
         }
+    }
+
+    mod database {
+        use rscache::definition::osrs::{
+            DbRowDefinition, DbTableDefinition, DbValue, Definition, Position,
+        };
+
+        #[rustfmt::skip]
+        const TABLE: [u8; 20] = [
+            1, 3,
+            // column 0 with defaults: int and string, 1 tuple.
+            0x80, 2, 0, 36, 1, 0, 0, 0, 5, b'Y', b'e', b's', 0,
+            // column 2 without defaults: coordinate.
+            2, 1, 22,
+            255,
+            0,
+        ];
+
+        #[rustfmt::skip]
+        const ROW: [u8; 27] = [
+            // table 300 as a var int.
+            4, 0xAC, 0x02,
+            3, 3,
+            // column 0: int, 2 tuples.
+            0, 1, 0, 2, 0, 0, 0, 1, 0xFF, 0xFF, 0xFF, 0xFF,
+            // column 2: coordinate, 1 tuple.
+            2, 1, 22, 1, 0x13, 0x20, 0x0C, 0x80,
+            255,
+            0,
+        ];
 
         #[test]
-        fn blue_partyhat() {
-            let item_loader = item_loader();
-            let item = item_loader.load(1042).unwrap();
+        fn table_columns() -> Result<(), rscache::Error> {
+            let table = DbTableDefinition::new(300, &TABLE)?;
 
-            assert_eq!(item.name, "Blue partyhat");
-            assert!(!item.stackable);
-            assert!(!item.members_only);
+            assert_eq!(table.columns.len(), 2);
+            assert_eq!(table.columns[&0].types, [0, 36]);
+            assert_eq!(
+                table.columns[&0].values,
+                [DbValue::Int(5), DbValue::String("Yes".to_owned())]
+            );
+            assert_eq!(table.columns[&2].types, [22]);
+            assert!(table.columns[&2].values.is_empty());
+
+            Ok(())
         }
 
         #[test]
-        fn magic_logs() {
-            let item_loader = item_loader();
-            let item = item_loader.load(1513).unwrap();
+        fn sparse_row_columns() -> Result<(), rscache::Error> {
+            let table = DbTableDefinition::new(300, &TABLE)?;
+            let row = DbRowDefinition::new(1, &ROW)?;
 
-            assert_eq!(item.name, "Magic logs");
-            assert!(!item.stackable);
-            assert!(item.members_only);
+            assert_eq!(row.table_id, 300);
+            assert!(!row.columns.contains_key(&1));
+
+            let ints: Vec<_> = row.columns[&0].tuples().collect();
+            assert_eq!(ints, [[DbValue::Int(1)], [DbValue::Int(-1)]]);
+            assert_eq!(
+                row.columns[&2].values[0].as_coordinate(),
+                Some(Position::new(3200, 3200, 1))
+            );
+
+            let mut overridden = row.clone();
+            overridden.columns.remove(&0);
+            let column = overridden.column_or_default(&table, 0).unwrap();
+            assert_eq!(column.values[1].as_str(), Some("Yes"));
+            assert!(row.column_or_default(&table, 1).is_none());
+
+            Ok(())
         }
+    }
+
+    mod world_maps {
+        use std::collections::BTreeSet;
+
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{Position, TileRect, WorldMapSectionKind},
+            loader::osrs::WorldMapLoader,
+        };
 
         #[test]
-        fn noted() {
-            let item_loader = item_loader();
-            let item = item_loader.load(1512).unwrap();
+        fn load_surface() {
+            let cache = test_util::osrs_cache();
+            let world_map_loader = WorldMapLoader::new(&cache).unwrap();
+            assert_eq!(world_map_loader.iter().count(), 33);
 
-            assert!(item.stackable);
-            assert!(!item.members_only);
+            let surface = world_map_loader.load_by_name("main").unwrap();
+            assert_eq!(surface.id, 0);
+            assert_eq!(surface.name, "RuneScape Surface");
+            assert_eq!(surface.origin, Some(Position::new(3232, 3232, 0)));
+            assert!(surface.main);
+            assert_eq!(surface.default_zoom, 75);
+            assert_eq!(surface.sections.len(), 3);
+            assert_eq!(
+                surface.bounds(),
+                Some(TileRect { min_x: 1152, min_y: 2496, max_x: 3903, max_y: 4095 })
+            );
+
+            assert_eq!(surface.squares.len(), 826);
+            assert_eq!(surface.squares[0].geography_group, Some(0));
+            assert_eq!(surface.icons.len(), 289);
+            assert_eq!(surface.icons[0].area_id, Some(87));
+            assert_eq!(surface.icons[0].position, Position::new(3239, 3234, 0));
+
+            let lumbridge = Position::new(3222, 3218, 0);
+            let section = surface.section_at(lumbridge).unwrap();
+            assert_eq!(section.display_position(lumbridge), Some(lumbridge));
+            assert!(section.source.region_ids().any(|region_id| region_id == lumbridge.region_id()));
         }
 
         #[test]
-        fn non_existent() {
-            let item_loader = item_loader();
-            assert!(item_loader.load(65_535).is_none());
+        fn moved_sections() {
+            let cache = test_util::osrs_cache();
+            let world_map_loader = WorldMapLoader::new(&cache).unwrap();
+            let cavern = world_map_loader.load_by_name("ancient_cavern").unwrap();
+
+            let section = cavern.sections[1];
+            assert_eq!(section.kind, WorldMapSectionKind::Chunks);
+            assert_eq!(section.plane, 1);
+            assert_eq!(
+                section.display_position(Position::new(1730, 5315, 1)),
+                Some(Position::new(1794, 5379, 1))
+            );
+            assert_eq!(section.display_position(Position::new(1730, 5315, 0)), None);
+
+            assert_eq!(cavern.squares.len(), 192);
+            assert!(cavern
+                .squares
+                .iter()
+                .any(|square| square.source != square.display && square.geography_group == Some(827)));
+
+            let kinds: BTreeSet<_> = world_map_loader
+                .iter()
+                .flat_map(|(_, world_map)| world_map.sections.iter().map(|section| section.kind))
+                .collect();
+            assert_eq!(kinds.len(), 4);
         }
     }
 
     mod npcs {
         use super::test_util;
-        use rscache::loader::osrs::NpcLoader;
+        use rscache::{
+            definition::osrs::{Definition, NpcDefinition},
+            loader::osrs::NpcLoader,
+        };
 
         fn npc_loader() -> NpcLoader {
             NpcLoader::new(&test_util::osrs_cache()).unwrap()
@@ -68,7 +1051,7 @@ mod osrs {
         #[test]
         fn last_valid_npc() {
             let npc_loader = npc_loader();
-            let npc = npc_loader.load(8691).unwrap();
+            let npc = npc_loader.load(8696).unwrap();
 
             assert_eq!(npc.name, "Mosol Rei");
             assert!(npc.interactable);
@@ -79,11 +1062,33 @@ mod osrs {
             let npc_loader = npc_loader();
             assert!(npc_loader.load(65_535).is_none());
         }
+
+        #[test]
+        fn raw_matches_load() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let npc_loader = NpcLoader::new(&cache)?;
+
+            for id in [0, 3226, 8696] {
+                let raw = NpcLoader::raw(&cache, id)?;
+                assert_eq!(&NpcDefinition::new(id, &raw)?, npc_loader.load(id).unwrap());
+            }
+
+            Ok(())
+        }
+
+        #[test]
+        fn raw_non_existent() {
+            let cache = test_util::osrs_cache();
+            assert!(NpcLoader::raw(&cache, 65_535).is_err());
+        }
     }
 
     mod objects {
         use super::test_util;
-        use rscache::loader::osrs::ObjectLoader;
+        use rscache::{
+            definition::osrs::{Definition, ObjectDefinition},
+            loader::osrs::ObjectLoader,
+        };
 
         fn obj_loader() -> ObjectLoader {
             ObjectLoader::new(&test_util::osrs_cache()).unwrap()
@@ -95,7 +1100,7 @@ mod osrs {
             let obj = obj_loader.load(25034).unwrap();
 
             assert_eq!(obj.name, "Law rift");
-            assert_eq!(obj.animation_id, 2178);
+            assert_eq!(obj.animation_id, Some(2178));
             assert!(obj.solid);
             assert!(!obj.obstruct_ground);
         }
@@ -131,12 +1136,66 @@ mod osrs {
             assert_eq!(obj.supports_items, Some(0));
             assert!(obj.solid);
             assert!(!obj.obstruct_ground);
+            assert!(obj.has_action("Open"));
+            assert_eq!(obj.interact_type, 2);
+            assert!(obj.blocks_projectile);
+            assert_eq!(obj.animation_id, None);
+        }
+
+        #[test]
+        fn morphs() {
+            let obj_loader = obj_loader();
+            let obj = obj_loader.load(137).unwrap();
+
+            assert_eq!(obj.morph_varbit, Some(1794));
+            assert_eq!(obj.morph_varp, None);
+            // opcode 77 has no default, the extra last entry is hidden.
+            assert_eq!(obj.morphisms, [Some(11449), Some(11450), None]);
+            assert_eq!(obj.morph(1), Some(11450));
+            assert_eq!(obj.morph(2), None);
+            assert!(obj_loader.load(1725).unwrap().morphisms.is_empty());
+        }
+
+        #[test]
+        fn clipping_flags() -> Result<(), rscache::Error> {
+            // not solid (17), occludes (23), name "Arch", terminator.
+            let obj = ObjectDefinition::new(1, &[17, 23, 2, 65, 114, 99, 104, 0, 0])?;
+            assert!(!obj.solid);
+            assert!(!obj.blocks_projectile);
+            assert_eq!(obj.interact_type, 0);
+            assert_eq!(obj.supports_items, Some(0));
+            assert!(obj.occludes);
+
+            // interact type 1 (27), hollow (74), projectiles pass (18).
+            let obj = ObjectDefinition::new(2, &[27, 74, 18, 0])?;
+            assert!(obj.solid);
+            assert!(obj.hollow);
+            assert!(!obj.blocks_projectile);
+            assert_eq!(obj.interact_type, 1);
+
+            Ok(())
+        }
+
+        #[test]
+        fn raw_matches_load() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let obj_loader = ObjectLoader::new(&cache)?;
+
+            for id in [590, 1725, 2030, 25034] {
+                let raw = ObjectLoader::raw(&cache, id)?;
+                assert_eq!(&ObjectDefinition::new(id, &raw)?, obj_loader.load(id).unwrap());
+            }
+
+            Ok(())
         }
     }
 
     mod locations {
         use super::test_util;
-        use rscache::loader::osrs::LocationLoader;
+        use rscache::{
+            definition::osrs::{Definition, LocationDefinition, LocationKind, Position},
+            loader::osrs::LocationLoader,
+        };
 
         #[test]
         fn lumbridge() {
@@ -152,11 +1211,48 @@ mod osrs {
             assert_eq!(location_def.region_base_coords(), (3200, 3200));
             assert_eq!(location_def.data.len(), 4730);
         }
+
+        #[test]
+        fn raw_matches_load() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let keys: [u32; 4] = [3030157619, 2364842415, 3297319647, 1973582566];
+
+            let mut location_loader = LocationLoader::new(&cache);
+            let raw = location_loader.raw(12850, &keys)?;
+            let location_def = LocationDefinition::new(12850, &raw)?;
+
+            assert_eq!(&location_def, location_loader.load(12850, &keys)?);
+
+            Ok(())
+        }
+
+        #[test]
+        fn spawns_at_world_coordinates() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let keys: [u32; 4] = [3030157619, 2364842415, 3297319647, 1973582566];
+
+            let mut location_loader = LocationLoader::new(&cache);
+            let spawns = location_loader.spawns(12850, &keys)?;
+            // the cooking range of the castle's kitchen.
+            let range = spawns.iter().find(|spawn| spawn.id == 114).unwrap();
+
+            assert_eq!(spawns.len(), 4730);
+            assert_eq!(range.position, Position::new(3212, 3215, 0));
+            assert_eq!(range.kind(), Some(LocationKind::Centerpiece));
+            assert_eq!(range.orientation, 2);
+            assert!(!range.bridged);
+
+            Ok(())
+        }
     }
 
     mod maps {
         use super::test_util;
-        use rscache::loader::osrs::MapLoader;
+        use rscache::{
+            definition::osrs::{Definition, MapDefinition},
+            error::ReadError,
+            loader::osrs::MapLoader,
+        };
 
         #[test]
         fn lumbridge() {
@@ -169,6 +1265,388 @@ mod osrs {
             assert_eq!(map_def.region_y, 50);
             assert_eq!(map_def.region_base_coords(), (3200, 3200));
         }
+
+        #[test]
+        fn raw_matches_load() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+
+            let mut map_loader = MapLoader::new(&cache);
+            let raw = map_loader.raw(12850)?;
+            let map_def = MapDefinition::new(12850, &raw)?;
+
+            assert_eq!(&map_def, map_loader.load(12850)?);
+
+            Ok(())
+        }
+
+        #[test]
+        fn load_at_world_coordinates() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+
+            let mut map_loader = MapLoader::new(&cache);
+            let map_def = map_loader.load_at(3222, 3218)?.clone();
+
+            assert_eq!(map_def.id(), 12850);
+            assert_eq!(map_def.tile_at(3222, 3218, 0)?, map_def.map_data(22, 18, 0));
+
+            Ok(())
+        }
+
+        #[test]
+        fn tile_outside_region() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+
+            let mut map_loader = MapLoader::new(&cache);
+            let map_def = map_loader.load(12850)?;
+
+            assert!(matches!(
+                map_def.tile_at(3264, 3200, 0),
+                Err(rscache::Error::Read(ReadError::TileOutOfBounds { region: 12850, .. }))
+            ));
+            assert!(map_def.tile_at(3199, 3200, 0).is_err());
+            assert!(map_def.tile_at(3200, 3200, 4).is_err());
+
+            Ok(())
+        }
+    }
+
+    mod interfaces {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{ComponentDefinition, HookArg, HookTrigger},
+            loader::osrs::InterfaceLoader,
+        };
+
+        #[test]
+        fn on_click_with_component_arg() {
+            let cache = test_util::osrs_cache();
+            let mut interface_loader = InterfaceLoader::new(&cache).unwrap();
+
+            let component = interface_loader.component(121 << 16 | 103).unwrap().unwrap();
+            let hook = component.hook(HookTrigger::Click).unwrap();
+
+            assert!(component.if3);
+            assert_eq!(component.parent_id.map(|id| id >> 16), Some(121));
+            assert_eq!(hook.script_id, 989);
+            assert_eq!(hook.args, vec![HookArg::ComponentId]);
+        }
+
+        #[test]
+        fn on_click_with_int_arg() {
+            let cache = test_util::osrs_cache();
+            let mut interface_loader = InterfaceLoader::new(&cache).unwrap();
+
+            let component = interface_loader.component(156 << 16 | 13).unwrap().unwrap();
+            let hook = component.hook(HookTrigger::Click).unwrap();
+
+            assert_eq!(hook.script_id, 1905);
+            assert_eq!(hook.args, vec![HookArg::Int(156 << 16 | 15)]);
+        }
+
+        #[test]
+        fn decode_all() {
+            let cache = test_util::osrs_cache();
+            let mut interface_loader = InterfaceLoader::new(&cache).unwrap();
+            let ids: Vec<u16> = interface_loader.ids().collect();
+            assert!(ids.windows(2).all(|ids| ids[0] < ids[1]));
+
+            let mut components = 0;
+            for id in ids {
+                components += interface_loader.load(id).unwrap().unwrap().len();
+            }
+
+            assert_eq!(components, 22410);
+        }
+
+        #[test]
+        fn non_existent() {
+            let cache = test_util::osrs_cache();
+            let mut interface_loader = InterfaceLoader::new(&cache).unwrap();
+
+            assert!(interface_loader.load(65_535).unwrap().is_none());
+        }
+
+        #[test]
+        fn raw_matches_component() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let mut interface_loader = InterfaceLoader::new(&cache)?;
+
+            for id in [121 << 16 | 103, 156 << 16 | 13] {
+                let raw = interface_loader.raw(id)?.unwrap();
+                let component = ComponentDefinition::new(id, &raw)?;
+                assert_eq!(Some(&component), interface_loader.component(id)?);
+            }
+            assert!(interface_loader.raw(121 << 16 | 0xFFFF)?.is_none());
+
+            Ok(())
+        }
+    }
+
+    mod models {
+        use super::test_util;
+        use rscache::{
+            definition::osrs::{Definition, ModelDefinition, Vertex},
+            loader::osrs::ModelLoader,
+        };
+
+        #[test]
+        fn textured_square() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let mut model_loader = ModelLoader::new(&cache);
+            let model = model_loader.load(153)?;
+
+            assert_eq!(
+                model.vertices,
+                [
+                    Vertex { x: -64, y: 0, z: 64 },
+                    Vertex { x: -64, y: 0, z: -64 },
+                    Vertex { x: 64, y: 0, z: -64 },
+                    Vertex { x: 64, y: 0, z: 64 },
+                ]
+            );
+            assert_eq!(model.faces, [[0, 1, 2], [0, 2, 3]]);
+            assert_eq!(model.face_colors, [127, 127]);
+            assert_eq!(model.face_textures, [Some(3), Some(3)]);
+            assert_eq!(model.texture_coordinates, [Some(0), Some(0)]);
+            assert_eq!(model.texture_triangles, [[0, 1, 3]]);
+            assert_eq!(
+                model.bounds(),
+                Some((Vertex { x: -64, y: 0, z: -64 }, Vertex { x: 64, y: 0, z: 64 }))
+            );
+
+            Ok(())
+        }
+
+        #[test]
+        fn face_compression_types() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let mut model_loader = ModelLoader::new(&cache);
+            let model = model_loader.load(79)?;
+
+            // compressed as 1, 2, 1, 4, 2, 3.
+            assert_eq!(
+                model.faces,
+                [[0, 1, 2], [0, 2, 3], [2, 4, 5], [4, 2, 1], [4, 1, 6], [6, 1, 0]]
+            );
+            assert_eq!(model.vertices[6], Vertex { x: 0, y: 14, z: -44 });
+            assert_eq!(model.vertex_skins, [11; 7]);
+            assert_eq!(model.face_priority(5), 6);
+
+            Ok(())
+        }
+
+        #[test]
+        fn versioned() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let mut model_loader = ModelLoader::new(&cache);
+
+            let model = model_loader.load(28260)?;
+            assert_eq!(model.faces, [[0, 1, 2], [0, 2, 3], [0, 3, 1], [1, 3, 2]]);
+            assert_eq!(model.face_textures, [Some(26); 4]);
+            assert_eq!(model.texture_coordinates, [Some(0), Some(0), Some(1), Some(1)]);
+            assert_eq!(model.texture_triangles, [[0, 1, 3], [3, 2, 0]]);
+
+            let model = model_loader.load(15752)?;
+            assert_eq!(model.face_alphas, [254, 254]);
+            assert_eq!(model.face_colors, [103, 103]);
+
+            // textured without texture triangles, so without coordinates.
+            let model = model_loader.load(2059)?;
+            assert_eq!(model.texture_coordinates, [None, None]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn animaya_formats() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let mut model_loader = ModelLoader::new(&cache);
+
+            // old format, with vertex skins at 13..20.
+            let raw = model_loader.raw(79)?;
+            let (body, footer) = raw.split_at(raw.len() - 18);
+            let mut buffer = [&body[..20], &[1, 3, 200], &body[20..]].concat();
+            buffer.extend_from_slice(&footer[..10]);
+            buffer.push(1);
+            buffer.extend_from_slice(&footer[10..]);
+            buffer.extend_from_slice(&[0, 10, 0xFF, 0xFE]);
+            assert_eq!(&ModelDefinition::new(79, &buffer)?, model_loader.load(79)?);
+
+            let raw = model_loader.raw(28260)?;
+            let (body, footer) = raw.split_at(raw.len() - 23);
+            let mut buffer = [body, &footer[..11], &[0], &footer[11..21]].concat();
+            buffer.extend_from_slice(&[0, 0, 0xFF, 0xFD]);
+            assert_eq!(&ModelDefinition::new(28260, &buffer)?, model_loader.load(28260)?);
+
+            Ok(())
+        }
+
+        #[test]
+        fn raw_matches_load() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let mut model_loader = ModelLoader::new(&cache);
+
+            let raw = model_loader.raw(0)?;
+            let model = ModelDefinition::new(0, &raw)?;
+
+            assert_eq!(&model, model_loader.load(0)?);
+            assert_eq!(model.vertices.len(), 176);
+            assert_eq!(model.faces.len(), 327);
+
+            Ok(())
+        }
+
+        #[test]
+        fn vertex_out_of_range() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let model_loader = ModelLoader::new(&cache);
+
+            // the first face index follows 4 vertex flags, 2 compression and 2 render types.
+            let mut raw = model_loader.raw(153)?;
+            assert_eq!(raw[8], 64);
+            raw[8] = 64 + 4;
+
+            assert!(ModelDefinition::new(153, &raw).is_err());
+
+            Ok(())
+        }
+    }
+
+    mod sprites {
+        use super::test_util;
+        use rscache::{definition::osrs::SpriteDefinition, loader::osrs::SpriteLoader};
+
+        #[test]
+        fn sprite_sheet() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let mut sprite_loader = SpriteLoader::new(&cache);
+
+            let frames = sprite_loader.load(423)?;
+            assert_eq!(frames.len(), 20);
+            assert!(frames.iter().enumerate().all(|(index, frame)| frame.frame == index));
+            assert!(frames
+                .iter()
+                .all(|frame| (frame.max_width, frame.max_height) == (13, 13)));
+
+            let first = &frames[0];
+            assert_eq!((first.width, first.height), (13, 11));
+            assert_eq!((first.offset_x, first.offset_y), (0, 1));
+            assert_eq!(first.pixels.len(), 13 * 11);
+
+            assert_eq!(sprite_loader.frame(423, 19)?.map(|frame| frame.frame), Some(19));
+            assert!(sprite_loader.frame(423, 20)?.is_none());
+
+            Ok(())
+        }
+
+        #[test]
+        fn column_major_frame() -> Result<(), rscache::Error> {
+            let cache = test_util::osrs_cache();
+            let mut sprite_loader = SpriteLoader::new(&cache);
+
+            let raw = sprite_loader.raw(4)?;
+            let sprite = &sprite_loader.load(4)?[0];
+            assert_eq!(raw[0] & 1, 1);
+            assert_eq!((sprite.width, sprite.height), (4, 334));
+
+            for x in 0..sprite.width {
+                for y in 0..sprite.height {
+                    let index = raw[1 + usize::from(x) * 334 + usize::from(y)];
+                    let alpha = sprite.pixel(x, y).unwrap() >> 24;
+                    assert_eq!(index == 0, alpha == 0, "({}, {})", x, y);
+                }
+            }
+            assert_eq!(sprite.pixel(4, 0), None);
+
+            Ok(())
+        }
+
+        #[test]
+        fn transposed_with_alpha() -> Result<(), rscache::Error> {
+            #[rustfmt::skip]
+            let buffer = [
+                // vertical with alpha, indices then alphas column by column.
+                0b11,
+                0, 2, 1, 1, 0, 2,
+                0x00, 0xFF, 0x40, 0x80, 0x00, 0x10,
+                // palette: black, red.
+                0x00, 0x00, 0x00, 0xFF, 0x00, 0x00,
+                // max 4x5, 3 palette entries, offset (1, 2), 2x3, 1 frame.
+                0, 4, 0, 5, 2, 0, 1, 0, 2, 0, 2, 0, 3, 0, 1,
+            ];
+
+            let frames = SpriteDefinition::decode(7, &buffer)?;
+            assert_eq!(frames.len(), 1);
+
+            let sprite = &frames[0];
+            assert_eq!((sprite.id, sprite.width, sprite.height), (7, 2, 3));
+            assert_eq!((sprite.offset_x, sprite.offset_y), (1, 2));
+            assert_eq!((sprite.max_width, sprite.max_height), (4, 5));
+            assert_eq!(
+                sprite.pixels,
+                [0, 0x8000_0001, 0xFFFF_0000, 0, 0x4000_0001, 0x10FF_0000]
+            );
+            assert_eq!(&sprite.to_rgba()[4..8], [0, 0, 1, 0x80]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn palette_index_zero_is_transparent() -> Result<(), rscache::Error> {
+            #[rustfmt::skip]
+            let buffer = [
+                // row-major without alpha.
+                0, 0, 1,
+                0xFF, 0xFF, 0xFF,
+                0, 2, 0, 1, 1, 0, 0, 0, 0, 0, 2, 0, 1, 0, 1,
+            ];
+
+            let sprite = &SpriteDefinition::decode(0, &buffer)?[0];
+            assert_eq!(sprite.pixels, [0, 0xFFFF_FFFF]);
+
+            Ok(())
+        }
+
+        #[test]
+        fn truncated() {
+            assert!(SpriteDefinition::decode(0, &[0, 1]).is_err());
+            // one 2x3 frame declared but no pixel data.
+            let footer = [0, 2, 0, 3, 0, 0, 0, 0, 0, 0, 2, 0, 3, 0, 1];
+            assert!(SpriteDefinition::decode(0, &footer).is_err());
+        }
+    }
+}
+
+mod localized {
+    use rscache::definition::{Language, LocalizedString};
+
+    #[test]
+    fn variants() {
+        let mut name = LocalizedString::from("Coins");
+        assert_eq!(name.selected(), Language::English);
+        assert_eq!(name.insert(Language::Portuguese, "Moedas"), None);
+        assert_eq!(name.insert(Language::Dutch, "Munten"), None);
+
+        assert_eq!(name.to_string(), "Coins");
+        for (language, text) in [(Language::Portuguese, "Moedas"), (Language::Dutch, "Munten")] {
+            name.select(language);
+            assert_eq!(name, text);
+            assert_eq!(name.get(language), Some(text));
+        }
+        assert_eq!(name.english(), "Coins");
+        assert_eq!(name.variants().count(), 3);
+
+        name.select(Language::Spanish);
+        assert_eq!(name.as_str(), "Coins");
+    }
+
+    #[test]
+    fn language_ids() {
+        for language in Language::ALL {
+            assert_eq!(Language::from_id(language.id()), Some(language));
+        }
+        assert_eq!(Language::German.id(), 1);
+        assert_eq!(Language::from_id(7), None);
     }
 }
 
@@ -178,8 +1656,11 @@ mod rs3 {
 
     mod items {
         use super::test_util;
-        use rscache::loader::rs3::ItemLoader;
-        
+        use rscache::{
+            definition::rs3::{Definition, ItemDefinition},
+            loader::rs3::ItemLoader,
+        };
+
         fn item_loader() -> ItemLoader {
             ItemLoader::new(&test_util::rs3_cache()).unwrap()
         }
@@ -245,5 +1726,84 @@ mod rs3 {
             let item = item_loader.load(65_535);
             assert!(item.is_none());
         }
+        #[test]
+        fn raw_matches_load() -> Result<(), rscache::Error> {
+            let cache = test_util::rs3_cache();
+            let item_loader = ItemLoader::new(&cache)?;
+            for id in [1042, 31285, 45652] {
+                let raw = ItemLoader::raw(&cache, id)?;
+                assert_eq!(&ItemDefinition::new(id, &raw)?, item_loader.load(id).unwrap());
+            }
+            Ok(())
+        }
+    }
+
+    mod languages {
+        use super::test_util::fixture::{ArchiveSpec, Fixture, FixtureBuilder};
+        use rscache::{
+            definition::{rs3::Definition, Language},
+            loader::rs3::ItemLoader,
+        };
+
+        fn item(name: &str) -> Vec<u8> {
+            let mut buffer = vec![2];
+            buffer.extend(name.as_bytes());
+            buffer.extend([0, 0]);
+            buffer
+        }
+
+        /// Items 0 and 1 in archive 0 of the rs3 item index, item 1 only when named.
+        fn items(first: &str, second: Option<&str>) -> Fixture {
+            let (files, file_ids) = match second {
+                Some(second) => (vec![item(first), item(second)], vec![0, 1]),
+                None => (vec![item(first)], vec![0]),
+            };
+
+            FixtureBuilder::new()
+                .index(19, 6)
+                .archive(19, ArchiveSpec::new(0, files).file_ids(file_ids))
+                .build()
+        }
+
+        #[test]
+        fn translations() -> Result<(), rscache::Error> {
+            let english = items("Bronze sword", Some("Coins"));
+            let german = items("Bronzeschwert", Some(""));
+            let mut loader = ItemLoader::new(&english.cache())?;
+            loader.add_translations(&german.cache(), Language::German)?;
+
+            let sword = loader.load(0).unwrap();
+            assert_eq!(sword.name, "Bronze sword");
+            assert_eq!(
+                sword.name.variants().collect::<Vec<_>>(),
+                vec![(Language::English, "Bronze sword"), (Language::German, "Bronzeschwert")]
+            );
+            // unnamed translations aren't added.
+            assert_eq!(loader.load(1).unwrap().name.get(Language::German), None);
+
+            loader.set_language(Language::German);
+            assert_eq!(loader.load(0).unwrap().name(), Some("Bronzeschwert"));
+            assert_eq!(loader.load(1).unwrap().name(), Some("Coins"));
+
+            loader.set_language(Language::English);
+            assert_eq!(loader.load(0).unwrap().name(), Some("Bronze sword"));
+
+            Ok(())
+        }
+
+        #[test]
+        fn with_language() -> Result<(), rscache::Error> {
+            let english = items("Bronze sword", Some("Coins"));
+            let french = items("Épée en bronze", None);
+            let mut loader = ItemLoader::with_language(&english.cache(), Language::French)?;
+            assert_eq!(loader.load(0).unwrap().name(), Some("Bronze sword"));
+
+            loader.add_translations(&french.cache(), Language::French)?;
+            assert_eq!(loader.load(0).unwrap().name(), Some("Épée en bronze"));
+            assert_eq!(loader.load(0).unwrap().name.english(), "Bronze sword");
+            assert_eq!(loader.load(1).unwrap().name(), Some("Coins"));
+
+            Ok(())
+        }
     }
 }
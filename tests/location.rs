@@ -0,0 +1,158 @@
+use rscache::definition::osrs::{
+    Location, LocationDefinition, MapData, MapDefinition, Position, WallEdges,
+};
+
+fn location(loc_type: u8, orientation: u8) -> Location {
+    Location {
+        loc_type,
+        orientation,
+        ..Location::default()
+    }
+}
+
+#[test]
+fn wall_edges_truth_table() {
+    use WallEdges as E;
+
+    #[rustfmt::skip]
+    let table = [
+        // (type, rotation, edges)
+        (0, 0, E::WEST),
+        (0, 1, E::NORTH),
+        (0, 2, E::EAST),
+        (0, 3, E::SOUTH),
+        (1, 0, E::NORTH_WEST),
+        (1, 1, E::NORTH_EAST),
+        (1, 2, E::SOUTH_EAST),
+        (1, 3, E::SOUTH_WEST),
+        (2, 0, E::WEST | E::NORTH),
+        (2, 1, E::NORTH | E::EAST),
+        (2, 2, E::EAST | E::SOUTH),
+        (2, 3, E::SOUTH | E::WEST),
+        (3, 0, E::NORTH_WEST),
+        (3, 1, E::NORTH_EAST),
+        (3, 2, E::SOUTH_EAST),
+        (3, 3, E::SOUTH_WEST),
+        (9, 0, E::SOUTH_WEST_TO_NORTH_EAST),
+        (9, 1, E::NORTH_WEST_TO_SOUTH_EAST),
+        (9, 2, E::SOUTH_WEST_TO_NORTH_EAST),
+        (9, 3, E::NORTH_WEST_TO_SOUTH_EAST),
+    ];
+
+    for (loc_type, rotation, edges) in table {
+        assert_eq!(
+            location(loc_type, rotation).wall_edges(),
+            Some(edges),
+            "type {} rotation {}",
+            loc_type,
+            rotation
+        );
+    }
+}
+
+#[test]
+fn not_a_wall() {
+    for loc_type in (4..=8).chain(10..=22) {
+        for rotation in 0..4 {
+            assert_eq!(location(loc_type, rotation).wall_edges(), None);
+        }
+    }
+}
+
+#[test]
+fn corner_walls_block_two_edges() {
+    let edges = location(2, 1).wall_edges().unwrap();
+
+    assert!(edges.contains(WallEdges::NORTH));
+    assert!(edges.contains(WallEdges::EAST));
+    assert!(!edges.contains(WallEdges::SOUTH));
+    assert!(!edges.contains(WallEdges::WEST));
+    assert_eq!(edges.bits().count_ones(), 2);
+}
+
+#[test]
+fn spawns_on_bridges_move_down() {
+    let mut map = MapDefinition {
+        region_x: 50,
+        region_y: 50,
+        data: vec![vec![vec![MapData::default(); 64]; 64]; 4],
+    };
+    map.data[1][3][4].settings = 2;
+    let at = |x: u16, y: u16, z: u16| Location {
+        id: 7,
+        loc_type: 10,
+        // positions are stored relative to the region id.
+        pos: (50 + x, 50 + y, z),
+        ..Location::default()
+    };
+    let locations = LocationDefinition {
+        id: 12850,
+        region_x: 50,
+        region_y: 50,
+        data: vec![at(3, 4, 1), at(3, 4, 0), at(5, 4, 1)],
+    };
+
+    let stored: Vec<_> = locations.spawns().map(|spawn| spawn.position).collect();
+    let placed: Vec<_> = locations
+        .spawns_on(&map)
+        .map(|spawn| (spawn.position, spawn.bridged))
+        .collect();
+
+    assert_eq!(
+        stored,
+        vec![
+            Position::new(3203, 3204, 1),
+            Position::new(3203, 3204, 0),
+            Position::new(3205, 3204, 1)
+        ]
+    );
+    assert_eq!(
+        placed,
+        vec![
+            (Position::new(3203, 3204, 0), true),
+            (Position::new(3203, 3204, 0), true),
+            (Position::new(3205, 3204, 1), false)
+        ]
+    );
+}
+
+mod kind {
+    use rscache::definition::osrs::{LocationCategory, LocationKind};
+
+    #[test]
+    fn round_trips_raw_types() {
+        for loc_type in 0..=22 {
+            let kind = LocationKind::from_type(loc_type).unwrap();
+            assert_eq!(kind.loc_type(), loc_type);
+            assert_eq!(super::location(loc_type, 0).kind(), Some(kind));
+        }
+
+        assert_eq!(LocationKind::from_type(23), None);
+        assert_eq!(super::location(255, 0).kind(), None);
+    }
+
+    #[test]
+    fn client_categories() {
+        let category = |loc_type| LocationKind::from_type(loc_type).unwrap().category();
+
+        for loc_type in 0..=3 {
+            assert_eq!(category(loc_type), LocationCategory::Wall);
+        }
+        for loc_type in 4..=8 {
+            assert_eq!(category(loc_type), LocationCategory::WallDecoration);
+        }
+        for loc_type in 9..=21 {
+            assert_eq!(category(loc_type), LocationCategory::Centerpiece);
+        }
+        assert_eq!(category(22), LocationCategory::GroundDecoration);
+    }
+
+    #[test]
+    fn named_kinds() {
+        assert_eq!(LocationKind::from_type(0), Some(LocationKind::WallStraight));
+        assert_eq!(LocationKind::from_type(9), Some(LocationKind::WallDiagonal));
+        assert_eq!(LocationKind::from_type(10), Some(LocationKind::Centerpiece));
+        assert_eq!(LocationKind::from_type(11), Some(LocationKind::CenterpieceDiagonal));
+        assert_eq!(LocationKind::from_type(22), Some(LocationKind::GroundDecor));
+    }
+}
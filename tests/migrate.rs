@@ -0,0 +1,109 @@
+use rscache::{
+    definition::osrs::{ItemDefinition, NpcDefinition},
+    loader::{
+        migrate::{self, MatchKind, Migration},
+        osrs::{ItemLoader, NpcLoader},
+    },
+};
+
+fn item(id: u32, name: &str, model: u16, cost: i32) -> (u32, ItemDefinition) {
+    let mut item = ItemDefinition {
+        id,
+        name: name.to_owned(),
+        cost,
+        ..ItemDefinition::default()
+    };
+    item.inventory_model_data.inventory_model = model;
+
+    (id, item)
+}
+
+/// Coins unchanged, the sword renamed, the shield renumbered, the helm
+/// repriced, the cape removed and the ring added.
+fn loaders() -> (ItemLoader, ItemLoader) {
+    let old = [
+        item(995, "Coins", 2484, 1),
+        item(1277, "Bronze sword", 2605, 26),
+        item(1171, "Wooden shield", 2543, 20),
+        item(1139, "Bronze med helm", 2813, 24),
+        item(1007, "Red cape", 2470, 2),
+    ];
+    let new = [
+        item(995, "Coins", 2484, 1),
+        item(1277, "Bronze shortsword", 2605, 26),
+        item(1540, "Wooden shield", 2543, 20),
+        item(1139, "Bronze med helm", 2813, 30),
+        item(1635, "Gold ring", 2677, 350),
+    ];
+
+    (old.into_iter().collect(), new.into_iter().collect())
+}
+
+#[test]
+fn renamed_and_renumbered() {
+    let (old, new) = loaders();
+    let map = migrate::match_definitions(&old, &new);
+
+    let migration = |new_id, kind| Some(Migration { new_id, kind });
+    assert_eq!(map.pairs.get(&995).copied(), migration(995, MatchKind::Exact));
+    assert_eq!(map.pairs.get(&1277).copied(), migration(1277, MatchKind::Models));
+    assert_eq!(map.pairs.get(&1171).copied(), migration(1540, MatchKind::Exact));
+    assert_eq!(map.pairs.get(&1139).copied(), migration(1139, MatchKind::NameAndModels));
+    assert_eq!(map.renumbered().collect::<Vec<_>>(), vec![(1171, 1540)]);
+
+    assert_eq!(map.unmatched_old, vec![1007]);
+    assert_eq!(map.unmatched_new, vec![1635]);
+    assert_eq!(map.new_id(1007), None);
+}
+
+#[test]
+fn prefers_same_id() {
+    let old: ItemLoader = [item(1, "null", 0, 0), item(2, "null", 0, 0)].into_iter().collect();
+    let new: ItemLoader = [item(0, "null", 0, 0), item(2, "null", 0, 0)].into_iter().collect();
+    let map = migrate::match_definitions(&old, &new);
+
+    assert_eq!(map.new_id(1), Some(0));
+    assert_eq!(map.new_id(2), Some(2));
+}
+
+#[test]
+fn ambiguous_heuristics_stay_unmatched() {
+    let old: ItemLoader = [item(1, "Rune bar", 1, 0)].into_iter().collect();
+    let new: ItemLoader = [item(2, "Rune bar", 2, 0), item(3, "Rune bar", 3, 0)]
+        .into_iter()
+        .collect();
+    let map = migrate::match_definitions(&old, &new);
+
+    assert!(map.pairs.is_empty());
+    assert_eq!(map.unmatched_old, vec![1]);
+    assert_eq!(map.unmatched_new, vec![2, 3]);
+}
+
+#[test]
+fn npcs() {
+    let npc = |id, name: &str, models: Vec<u16>| {
+        let mut npc = NpcDefinition {
+            id,
+            name: name.to_owned(),
+            ..NpcDefinition::default()
+        };
+        npc.model_data.models = models;
+        (id, npc)
+    };
+    let old: NpcLoader = [npc(3226, "Woodsman tutor", vec![1, 2])].into_iter().collect();
+    let new: NpcLoader = [npc(3227, "Woodsman tutor", vec![1, 3])].into_iter().collect();
+    let map = migrate::match_definitions(&old, &new);
+
+    assert_eq!(map.pairs[&3226], Migration { new_id: 3227, kind: MatchKind::Name });
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json() {
+    let (old, new) = loaders();
+    let map = migrate::match_definitions(&old, &new);
+
+    let json = serde_json::to_string(&map).unwrap();
+    assert!(json.contains(r#""1171":{"new_id":1540,"kind":"Exact"}"#));
+    assert_eq!(serde_json::from_str::<migrate::MigrationMap>(&json).unwrap(), map);
+}
@@ -0,0 +1,105 @@
+mod test_util;
+
+use rscache::util::{self, djd2, NameHashTable, NameTransform};
+use test_util::fixture::{self, ArchiveSpec, FixtureBuilder};
+
+fn words(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+}
+
+#[test]
+fn recovers_transformed_names() {
+    let fixture = FixtureBuilder::new()
+        .index(8, 6)
+        .archive(8, ArchiveSpec::new(0, vec![fixture::pattern(10)]).named("logo"))
+        .archive(8, ArchiveSpec::new(3, vec![fixture::pattern(10)]).named("titlebutton_7.dat"))
+        .archive(8, ArchiveSpec::new(4, vec![fixture::pattern(10)]))
+        .build();
+    let cache = fixture.cache();
+    let table = cache.metadata_of(8).unwrap().table;
+
+    let mut names = NameHashTable::new();
+    let unresolved = names.unresolved(&table);
+    assert_eq!(unresolved.len(), 2);
+
+    let transforms = [
+        NameTransform::Suffix("_".into()),
+        NameTransform::Digits(9),
+        NameTransform::Suffix(".dat".into()),
+    ];
+    let resolved = names.bruteforce(
+        &unresolved,
+        words(&["music", "titlebutton", "logo"]),
+        &transforms,
+        |_| {},
+    );
+
+    assert_eq!(resolved, 2);
+    assert!(names.unresolved(&table).is_empty());
+    assert_eq!(
+        names.names_in(&table).into_iter().collect::<Vec<_>>(),
+        [(0, "logo"), (3, "titlebutton_7.dat")]
+    );
+}
+
+#[test]
+fn transforms_compose_in_order() {
+    let mut names = NameHashTable::new();
+    let planted = [djd2::hash("lword7"), djd2::hash("word3"), djd2::hash("7wordl")];
+
+    let transforms = [NameTransform::Prefix("l".into()), NameTransform::Digits(9)];
+    let resolved = names.bruteforce(&planted, words(&["word"]), &transforms, |_| {});
+
+    assert_eq!(resolved, 2);
+    assert_eq!(names.get(planted[0]), Some("lword7"));
+    assert_eq!(names.get(planted[1]), Some("word3"));
+    assert_eq!(names.get(planted[2]), None);
+}
+
+#[test]
+fn progress() {
+    let mut names = NameHashTable::new();
+    let wordlist = (0..10_000).map(|word| format!("word{}", word));
+    let mut reports = Vec::new();
+
+    let resolved = names.bruteforce(
+        &[djd2::hash("word9999.dat")],
+        wordlist,
+        &[NameTransform::Suffix(".dat".into())],
+        |tried| reports.push(tried),
+    );
+
+    assert_eq!(resolved, 1);
+    assert!(reports.len() > 1);
+    assert!(reports.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(reports.last(), Some(&20_000));
+}
+
+#[test]
+fn known_names_are_not_resolved_again() {
+    let mut names: NameHashTable = words(&["huffman"]).into_iter().collect();
+    let hashes = [djd2::hash("huffman"), djd2::hash("huffman")];
+
+    assert_eq!(names.bruteforce(&hashes, words(&["huffman"]), &[], |_| {}), 0);
+    assert_eq!(names.len(), 1);
+    assert_eq!(names.insert("title.jpg"), djd2::hash("title.jpg"));
+}
+
+#[test]
+fn collisions_keep_the_first_name() {
+    let mut names = NameHashTable::new();
+    let hash = names.insert("Aa");
+    assert_eq!(names.insert("BB"), hash);
+    names.insert("BB");
+    names.insert("Aa");
+    names.insert("huffman");
+
+    assert_eq!(names.len(), 2);
+    assert_eq!(names.resolve(hash), Some("Aa"));
+    assert_eq!(names.collisions().collect::<Vec<_>>(), [(hash, "BB")]);
+    assert_eq!(
+        names.resolve(util::hash_archive_name("huffman")),
+        Some("huffman")
+    );
+    assert_eq!(util::hash_archive_name("title.jpg"), djd2::hash("title.jpg"));
+}
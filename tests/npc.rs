@@ -0,0 +1,137 @@
+use rscache::definition::{
+    opcodes::DefinitionKind,
+    osrs::{Definition, NpcDefinition, NpcStats},
+};
+
+fn string(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend(value.as_bytes());
+    buffer.push(0);
+}
+
+/// Fields both layouts share: name, size, a walk animation with rotations and a combat level.
+fn common(buffer: &mut Vec<u8>) {
+    buffer.push(2);
+    string(buffer, "Guard");
+    buffer.extend([12, 1]);
+    buffer.push(17);
+    for animation in [819_u16, 820, 821, 822] {
+        buffer.extend(animation.to_be_bytes());
+    }
+    buffer.push(95);
+    buffer.extend(21_u16.to_be_bytes());
+}
+
+/// Pre-2023 layout: morphs without a default (106) and the pet flag (111).
+fn old_layout() -> Vec<u8> {
+    let mut buffer = Vec::new();
+    common(&mut buffer);
+    buffer.push(106);
+    buffer.extend([0xFF, 0xFF, 0x01, 0x2C, 1, 0x00, 0x10, 0x00, 0x11]);
+    buffer.push(111);
+    buffer.push(0);
+    buffer
+}
+
+/// 2023+ layout of the same npc: morphs with a default (118), the follower
+/// flag (122) and the new category, stats and height opcodes.
+fn new_layout() -> Vec<u8> {
+    let mut buffer = Vec::new();
+    buffer.extend([18, 0x00, 0x2A]);
+    common(&mut buffer);
+    for (opcode, level) in (74..=79).zip([20_u16, 20, 18, 22, 1, 1]) {
+        buffer.push(opcode);
+        buffer.extend(level.to_be_bytes());
+    }
+    buffer.push(118);
+    buffer.extend([0xFF, 0xFF, 0x01, 0x2C, 0xFF, 0xFF, 1, 0x00, 0x10, 0x00, 0x11]);
+    buffer.push(122);
+    buffer.extend([124, 0x00, 0xC8]);
+    buffer.extend([115, 0x03, 0x34, 0x03, 0x35, 0x03, 0x36, 0x03, 0x37]);
+    buffer.push(0);
+    buffer
+}
+
+#[test]
+fn layouts_are_equivalent() {
+    let old = NpcDefinition::new(3, &old_layout()).unwrap();
+    let new = NpcDefinition::new(3, &new_layout()).unwrap();
+
+    assert_eq!(old.name, "Guard");
+    assert_eq!(old.morph_varbit, None);
+    assert_eq!(old.morph_varp, Some(300));
+    assert_eq!(old.morphisms, [Some(16), Some(17), None]);
+    assert!(old.pet);
+
+    assert_eq!(new.category, Some(42));
+    assert_eq!(new.height, Some(200));
+    assert_eq!(new.stats.hitpoints, Some(22));
+    assert_eq!(new.stats.magic, Some(1));
+    assert_eq!(new.animation_data.running, Some(820));
+    assert_eq!(new.animation_data.running_rotate_right, Some(823));
+
+    let mut stripped = new.clone();
+    stripped.category = None;
+    stripped.height = None;
+    stripped.stats = NpcStats::default();
+    stripped.animation_data.running = None;
+    stripped.animation_data.running_rotate_180 = None;
+    stripped.animation_data.running_rotate_left = None;
+    stripped.animation_data.running_rotate_right = None;
+    assert_eq!(stripped, old);
+}
+
+#[test]
+fn morphs() {
+    // varbit 5, no varp, default morph 18, [16, hidden].
+    let mut buffer = vec![118, 0x00, 0x05, 0xFF, 0xFF, 0x00, 0x12, 1, 0x00, 0x10, 0xFF, 0xFF];
+    buffer.push(0);
+    let npc = NpcDefinition::new(3, &buffer).unwrap();
+
+    assert_eq!(npc.morph_varbit, Some(5));
+    assert_eq!(npc.morph_varp, None);
+    assert_eq!(npc.morphisms, [Some(16), None, Some(18)]);
+    assert_eq!(npc.morph(0), Some(16));
+    assert_eq!(npc.morph(1), None);
+    assert_eq!(npc.morph(2), Some(18));
+    assert_eq!(npc.morph(1000), Some(18));
+
+    let old = NpcDefinition::new(3, &old_layout()).unwrap();
+    assert_eq!(old.morph(1), Some(17));
+    assert_eq!(old.morph(2), None);
+    assert_eq!(NpcDefinition::default().morph(0), None);
+}
+
+#[test]
+fn lenient_stops_at_unknown_opcodes() {
+    let mut buffer = old_layout();
+    buffer.pop();
+    let offset = buffer.len();
+    buffer.extend([200, 1, 2, 3, 0]);
+
+    assert!(NpcDefinition::new(3, &buffer).is_err());
+
+    let (npc, unknown) = NpcDefinition::new_lenient(3, &buffer).unwrap();
+    let unknown = unknown.unwrap();
+    assert_eq!(unknown.kind, DefinitionKind::Npc);
+    assert_eq!(unknown.opcode, 200);
+    assert_eq!(unknown.offset, offset);
+    assert!(unknown.to_string().contains("opcode 200"));
+    assert_eq!(npc, NpcDefinition::new(3, &old_layout()).unwrap());
+}
+
+#[test]
+fn lenient_matches_strict_for_known_layouts() {
+    for buffer in [old_layout(), new_layout()] {
+        let (npc, unknown) = NpcDefinition::new_lenient(3, &buffer).unwrap();
+
+        assert_eq!(unknown, None);
+        assert_eq!(npc, NpcDefinition::new(3, &buffer).unwrap());
+    }
+}
+
+#[test]
+fn lenient_still_fails_on_truncation() {
+    let buffer = new_layout();
+
+    assert!(NpcDefinition::new_lenient(3, &buffer[..buffer.len() / 2]).is_err());
+}
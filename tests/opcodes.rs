@@ -0,0 +1,124 @@
+mod test_util;
+
+use rscache::definition::{
+    annotate,
+    opcodes::FieldType,
+    osrs::{Definition, ItemDefinition, NpcDefinition, ObjectDefinition},
+    DefinitionKind,
+};
+use runefs::{ArchiveFileGroup, IndexMetadata, REFERENCE_TABLE_ID};
+
+fn definition_buffers(archive_id: u32) -> Vec<(u32, Vec<u8>)> {
+    let cache = test_util::osrs_cache();
+    let buffer = cache.read(REFERENCE_TABLE_ID, 2).unwrap().decode().unwrap();
+    let metadata = IndexMetadata::from_buffer(buffer).unwrap();
+    let entry_count = metadata
+        .iter()
+        .find(|archive| archive.id == archive_id)
+        .unwrap()
+        .entry_count;
+
+    let buffer = cache.read(2, archive_id).unwrap().decode().unwrap();
+    ArchiveFileGroup::from_buffer(&buffer, entry_count)
+        .into_iter()
+        .map(|file| (file.id, file.data))
+        .collect()
+}
+
+fn assert_fully_annotated(kind: DefinitionKind, archive_id: u32) {
+    for (id, buffer) in definition_buffers(archive_id) {
+        let fields = annotate(kind, &buffer);
+        let end = fields
+            .last()
+            .map_or(0, |field| field.offset + 1 + field.payload.len());
+
+        for field in &fields {
+            assert!(field.entry.is_some(), "{:?} {} unknown field {}", kind, id, field);
+            assert!(!field.truncated, "{:?} {} truncated field {}", kind, id, field);
+        }
+        assert_eq!(buffer.get(end), Some(&0), "{:?} {} not terminated", kind, id);
+    }
+}
+
+#[test]
+fn items_fully_annotated() {
+    assert_fully_annotated(DefinitionKind::Item, 10);
+}
+
+#[test]
+fn npcs_fully_annotated() {
+    assert_fully_annotated(DefinitionKind::Npc, 9);
+}
+
+#[test]
+fn objects_fully_annotated() {
+    assert_fully_annotated(DefinitionKind::Object, 6);
+}
+
+#[test]
+fn unknown_opcode() {
+    // name "a", then opcode 3 which items don't use.
+    let buffer = &[2, 97, 0, 3, 1, 2, 0];
+    let fields = annotate(DefinitionKind::Item, buffer);
+
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].name(), "name");
+    assert_eq!(fields[1].name(), "unknown");
+    assert_eq!(fields[1].offset, 3);
+    assert_eq!(fields[1].payload, &[1, 2, 0]);
+}
+
+#[test]
+fn truncated_payload() {
+    // cost (i32) with only two bytes left.
+    let buffer = &[12, 0, 1];
+    let fields = annotate(DefinitionKind::Item, buffer);
+
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].name(), "cost");
+    assert!(fields[0].truncated);
+    assert_eq!(fields[0].payload, &[0, 1]);
+}
+
+/// A well-formed payload of `ty`, lists are empty.
+fn sample_payload(ty: FieldType) -> Vec<u8> {
+    match ty {
+        FieldType::Flag => vec![],
+        FieldType::U8 | FieldType::I8 => vec![1],
+        FieldType::U16 | FieldType::I16 => vec![0, 1],
+        FieldType::I32 => vec![0, 0, 0, 1],
+        FieldType::String => vec![b'a', 0],
+        FieldType::U16Pair => vec![0, 1, 0, 2],
+        FieldType::U16U8 => vec![0, 1, 2],
+        FieldType::U16Quad => vec![0, 1, 0, 2, 0, 3, 0, 4],
+        FieldType::U16List | FieldType::U16U8List | FieldType::U16PairList | FieldType::Params => {
+            vec![0]
+        }
+        // varbit, varp, length 0 and a single morph id.
+        FieldType::Morphs => vec![0, 1, 0, 2, 0, 0, 3],
+        FieldType::MorphsWithDefault => vec![0, 1, 0, 2, 0, 4, 0, 0, 3],
+        FieldType::AmbientSounds => vec![0, 1, 0, 2, 3, 0],
+    }
+}
+
+#[test]
+fn parsers_agree_with_tables() {
+    for kind in DefinitionKind::ALL {
+        for entry in kind.opcodes() {
+            for opcode in entry.first..=entry.last {
+                let mut buffer = vec![opcode];
+                buffer.extend(sample_payload(entry.ty));
+                buffer.push(0);
+
+                let parsed = match kind {
+                    DefinitionKind::Item => ItemDefinition::new(0, &buffer).map(drop),
+                    DefinitionKind::Npc => NpcDefinition::new(0, &buffer).map(drop),
+                    DefinitionKind::Object => ObjectDefinition::new(0, &buffer).map(drop),
+                };
+                if let Err(error) = parsed {
+                    panic!("{:?} opcode {} ({}): {}", kind, opcode, entry.field, error);
+                }
+            }
+        }
+    }
+}
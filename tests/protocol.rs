@@ -0,0 +1,156 @@
+mod test_util;
+
+use rscache::protocol::{self, BLOCK_LEN, BLOCK_MARKER, PREFETCH};
+
+/// Checks the block markers of a response and strips them off.
+fn unchunk(response: &[u8]) -> Vec<u8> {
+    let (first, rest) = response.split_at(response.len().min(BLOCK_LEN));
+    let mut data = first.to_vec();
+    for block in rest.chunks(BLOCK_LEN) {
+        assert_eq!(block[0], BLOCK_MARKER);
+        data.extend(&block[1..]);
+    }
+
+    data
+}
+
+#[test]
+fn block_boundaries() {
+    // lengths of the container and the response, with a 3 byte header.
+    let lengths = [(1, 4), (509, 512), (510, 514), (1020, 1024), (1021, 1026)];
+
+    for (container_len, response_len) in lengths {
+        let container: Vec<u8> = (0..container_len).map(|byte| byte as u8).collect();
+        let response = protocol::js5_response(7, 300, true, &container);
+
+        assert_eq!(response.len(), response_len, "{}", container_len);
+        assert_eq!(response[..3], [7, 1, 44]);
+        assert_eq!(unchunk(&response)[3..], container);
+    }
+}
+
+#[test]
+fn prefetch_bit() {
+    let container = [2, 0, 0, 0, 1, 0, 0, 0, 1, 42];
+
+    let priority = protocol::js5_response(2, 10, true, &container);
+    let prefetch = protocol::js5_response(2, 10, false, &container);
+
+    assert_eq!(priority[3], 2);
+    assert_eq!(prefetch[3], 2 | PREFETCH);
+    assert_eq!(priority[4..], prefetch[4..]);
+}
+
+#[test]
+fn writer_matches_vec() {
+    let container = vec![1; 2000];
+    let mut response = Vec::new();
+    protocol::write_js5_response(&mut response, 2, 10, false, &container).unwrap();
+
+    assert_eq!(response, protocol::js5_response(2, 10, false, &container));
+}
+
+#[test]
+fn read_round_trip() {
+    for (len, priority) in [(0, true), (505, false), (506, true), (3000, false)] {
+        let mut container = vec![2, 0, 0, 0, 0, 0, 0, 0, 1];
+        container[1..5].copy_from_slice(&(len as u32).to_be_bytes());
+        container.extend((0..len).map(|byte| byte as u8));
+        let mut response = protocol::js5_response(7, 300, priority, &container);
+        // trailing bytes belong to the next response.
+        response.extend([7, 1, 44]);
+
+        let mut reader = response.as_slice();
+        let read = protocol::read_js5_response(&mut reader).unwrap();
+        assert_eq!((read.index_id, read.archive_id), (7, 300));
+        assert_eq!(read.priority, priority);
+        assert_eq!(read.container, container, "{}", len);
+        assert_eq!(reader, [7, 1, 44]);
+    }
+}
+
+#[test]
+fn read_missing_marker() {
+    let container = [&[0, 0, 0, 3, 227][..], &[0; 995]].concat();
+    let mut response = protocol::js5_response(2, 10, true, &container);
+    response[BLOCK_LEN] = 0;
+
+    assert!(protocol::read_js5_response(&mut response.as_slice()).is_err());
+}
+
+mod osrs {
+    use super::*;
+    use rscache::codec;
+
+    #[test]
+    fn multi_block_archive() -> Result<(), rscache::Error> {
+        let cache = test_util::osrs_cache();
+        let container = cache.read(2, 10)?;
+        let (body, version) = codec::split_trailer(&container);
+        assert!(version.is_some());
+
+        let response = cache.build_js5_response(2, 10, true)?;
+        let blocks = (3 + body.len() - BLOCK_LEN).div_ceil(BLOCK_LEN - 1);
+        assert_eq!(response.len(), 3 + body.len() + blocks);
+        assert_eq!(unchunk(&response), [&[2, 0, 10], body].concat());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reference_tables() -> Result<(), rscache::Error> {
+        let cache = test_util::osrs_cache();
+
+        // reference tables have no trailer to strip.
+        let table = cache.read(255, 2)?;
+        let response = cache.build_js5_response(255, 2, true)?;
+        assert_eq!(
+            unchunk(&response),
+            [&[255, 0, 2], table.as_slice()].concat()
+        );
+
+        let checksum = cache.checksum()?.encode()?;
+        let response = cache.build_js5_response(255, 255, true)?;
+        assert_eq!(
+            unchunk(&response),
+            [&[255, 0, 255], checksum.as_slice()].concat()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_archives() {
+        let cache = test_util::osrs_cache();
+
+        assert!(cache.build_js5_response(2, 25_000, true).is_err());
+        assert!(cache.build_js5_response(2, 70_000, true).is_err());
+    }
+}
+
+#[test]
+fn responses_skip_read_transforms() -> Result<(), rscache::Error> {
+    use rscache::Cache;
+    use test_util::fixture::{self, ArchiveSpec, FixtureBuilder};
+
+    let fixture = FixtureBuilder::new()
+        .index(2, 6)
+        .archive(2, ArchiveSpec::new(10, fixture::items()).xor(0x5A))
+        .build();
+    let cache = Cache::builder(fixture.path())
+        .with_read_transform(|_, _, mut data| {
+            data.iter_mut().for_each(|byte| *byte ^= 0x5A);
+            Ok(data)
+        })
+        .open()?;
+
+    let response = cache.build_js5_response(2, 10, true)?;
+    let container = &unchunk(&response)[3..];
+    assert_eq!(container, cache.read_raw(2, 10)?);
+    assert_eq!(
+        crc32fast::hash(container),
+        cache.archive_metadata(2, 10)?.crc
+    );
+
+    Ok(())
+}
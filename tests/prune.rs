@@ -0,0 +1,130 @@
+mod test_util;
+
+use rscache::{
+    loader::osrs::{ItemLoader, MapLoader},
+    prune::{self, PruneSpec},
+    Cache, Error, IndexState,
+};
+use runefs::{error::ReadError, REFERENCE_TABLE_ID};
+use test_util::fixture;
+
+fn populated(cache: &Cache) -> Vec<u8> {
+    (0..REFERENCE_TABLE_ID)
+        .filter(|&index_id| matches!(cache.index_state(index_id), IndexState::Populated { .. }))
+        .collect()
+}
+
+#[test]
+fn single_index() -> Result<(), Error> {
+    let fixture = fixture::fixture();
+    let cache = fixture.cache();
+    let dir = tempfile::tempdir()?;
+
+    let report = prune::prune(&cache, dir.path(), &PruneSpec::new().index(2))?;
+    let pruned = Cache::new(dir.path())?;
+    assert_eq!(populated(&pruned), [2]);
+    assert!(pruned.open_warnings().is_empty());
+    assert_eq!(pruned.index_count(), 3);
+    assert_eq!(pruned.index_state(5), IndexState::Missing);
+
+    for archive_id in [6, 9, 10] {
+        assert_eq!(pruned.read_raw(2, archive_id)?, cache.read_raw(2, archive_id)?);
+    }
+    assert_eq!(
+        pruned.read_raw(REFERENCE_TABLE_ID, 2)?,
+        cache.read_raw(REFERENCE_TABLE_ID, 2)?
+    );
+    assert_eq!(ItemLoader::new(&pruned)?, ItemLoader::new(&cache)?);
+
+    // the kept index checks out against the source.
+    let (checksum, source) = (pruned.checksum()?, cache.checksum()?);
+    assert_eq!(checksum.iter().count(), 3);
+    assert_eq!(checksum.iter().nth(2), source.iter().nth(2));
+
+    assert!(report.size_after < report.size_before);
+    assert_eq!(report.indices.len(), 1);
+    let index = report.indices[0];
+    assert_eq!((index.index_id, index.archives_before, index.archives_after), (2, 3, 3));
+    assert_eq!(index.stored_after, index.stored_before);
+
+    Ok(())
+}
+
+#[test]
+fn selected_archives() -> Result<(), Error> {
+    let fixture = fixture::fixture();
+    let cache = fixture.cache();
+    let dir = tempfile::tempdir()?;
+
+    let spec = PruneSpec::new()
+        .archive(2, 10)
+        .archive_named(5, "m50_50")
+        .index(10)
+        .archive(10, 0);
+    assert_eq!(spec.index_ids().collect::<Vec<_>>(), [2, 5, 10]);
+
+    let report = prune::prune(&cache, dir.path(), &spec)?;
+    let pruned = Cache::new(dir.path())?;
+    assert_eq!(populated(&pruned), [2, 5, 10]);
+    assert_eq!(pruned.archive_count(2), Some(1));
+    assert_eq!(pruned.archive_count(5), Some(1));
+
+    let table = pruned.metadata_of(2)?;
+    assert_eq!(table.group_count(), 1);
+    assert_eq!(table.table.archives[0], cache.archive_metadata(2, 10)?);
+    assert_eq!(table.version(), cache.metadata_of(2)?.version());
+    assert!(pruned.read(2, 9).is_err());
+
+    assert_eq!(ItemLoader::new(&pruned)?, ItemLoader::new(&cache)?);
+    assert_eq!(
+        MapLoader::new(&pruned).load(fixture::REGION_ID)?,
+        MapLoader::new(&cache).load(fixture::REGION_ID)?
+    );
+    assert_eq!(
+        pruned.huffman_table()?.as_slice(),
+        cache.huffman_table()?.as_slice()
+    );
+
+    let archives: Vec<_> = report
+        .indices
+        .iter()
+        .map(|index| (index.archives_before, index.archives_after))
+        .collect();
+    assert_eq!(archives, [(3, 1), (2, 1), (1, 1)]);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_entries() -> Result<(), Error> {
+    let fixture = fixture::fixture();
+    let cache = fixture.cache();
+
+    let prune = |spec: PruneSpec| prune::prune(&cache, tempfile::tempdir()?.path(), &spec);
+    assert!(matches!(
+        prune(PruneSpec::new().index(42)),
+        Err(Error::RuneFs(runefs::Error::Read(ReadError::IndexNotFound(42))))
+    ));
+    assert!(matches!(
+        prune(PruneSpec::new().archive(2, 11)),
+        Err(Error::RuneFs(runefs::Error::Read(ReadError::ArchiveNotFound { idx: 2, arc: 11 })))
+    ));
+    assert!(matches!(
+        prune(PruneSpec::new().archive_named(5, "m1_1")),
+        Err(Error::NameHash(_))
+    ));
+
+    // pruning never overwrites a cache.
+    let dir = tempfile::tempdir()?;
+    prune::prune(&cache, dir.path(), &PruneSpec::new().index(2))?;
+    assert!(matches!(
+        prune::prune(&cache, dir.path(), &PruneSpec::new().index(2)),
+        Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::AlreadyExists
+    ));
+    assert!(matches!(
+        prune::prune(&cache, fixture.path(), &PruneSpec::new()),
+        Err(Error::Io(_))
+    ));
+
+    Ok(())
+}
@@ -4,6 +4,95 @@ mod test_util;
 mod osrs {
     use super::test_util;
 
+    #[test]
+    fn decoded_cache_shares_archives() -> Result<(), rscache::Error> {
+        use rscache::Cache;
+        use std::sync::Arc;
+
+        let cache = Cache::builder("./data/osrs_cache")
+            .decoded_cache_size(1024 * 1024)
+            .open()?;
+
+        let items = cache.read_decoded_shared(2, 10)?;
+        assert_eq!(items.as_slice(), cache.read_decoded(2, 10)?.as_slice());
+        assert!(Arc::ptr_eq(&items, &cache.read_decoded_shared(2, 10)?));
+        assert_eq!(cache.decoded_cache_len(), items.len());
+
+        // filling the rest of the budget with models drops the item archive.
+        let mut archive_id = 0;
+        while cache.decoded_cache_len() >= items.len() && archive_id < 1_000 {
+            cache.read_decoded_shared(7, archive_id)?;
+            archive_id += 1;
+        }
+        assert!(cache.decoded_cache_len() <= 1024 * 1024);
+        assert!(!Arc::ptr_eq(&items, &cache.read_decoded_shared(2, 10)?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn decoded_cache_disabled_by_default() -> Result<(), rscache::Error> {
+        let cache = test_util::osrs_cache();
+
+        let items = cache.read_decoded_shared(2, 10)?;
+        assert!(!std::sync::Arc::ptr_eq(&items, &cache.read_decoded_shared(2, 10)?));
+        assert_eq!(cache.decoded_cache_len(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decoded_into_writer_matches_read_decoded() -> Result<(), rscache::Error> {
+        let cache = test_util::osrs_cache();
+        let mut archives = 0;
+
+        for index_id in 0..=20 {
+            let Ok(metadata) = cache.metadata_of(index_id) else {
+                continue;
+            };
+            for archive in metadata.table.archives.iter().take(50) {
+                // skips archives enciphered with unknown keys.
+                let Ok(expected) = cache.read_decoded(index_id, archive.id) else {
+                    continue;
+                };
+                let mut decoded = Vec::new();
+                let len = cache.read_decoded_into_writer(index_id, archive.id, &mut decoded)?;
+
+                assert_eq!(len, decoded.len());
+                assert_eq!(decoded, expected.as_slice(), "{} {}", index_id, archive.id);
+                archives += 1;
+            }
+        }
+
+        assert!(archives > 500, "{}", archives);
+
+        Ok(())
+    }
+
+    #[test]
+    fn crcs_cover_every_group() {
+        let cache = test_util::osrs_cache();
+        let mut groups = 0;
+
+        for index_id in 0..=u8::MAX {
+            let Ok(metadata) = cache.metadata_of(index_id) else {
+                continue;
+            };
+            for archive in &metadata.table.archives {
+                let Ok(container) = cache.read_raw(index_id, archive.id) else {
+                    continue;
+                };
+                let (body, version) = rscache::codec::split_trailer(&container);
+
+                assert_eq!(crc32fast::hash(body), archive.crc, "{} {}", index_id, archive.id);
+                assert_eq!(version, Some(archive.trailer_version()));
+                groups += 1;
+            }
+        }
+
+        assert!(groups > 50_000, "{}", groups);
+    }
+
     #[test]
     fn metadata() {
         let cache = test_util::osrs_cache();
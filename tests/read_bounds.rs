@@ -0,0 +1,73 @@
+//! Runs in its own test binary, the global allocator tracks the peak heap
+//! usage of the whole process.
+
+mod test_util;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use rscache::{error::ReadError, Cache, Error};
+use test_util::fixture;
+
+struct PeakAlloc {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = self.current.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+        self.peak.fetch_max(current, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.current.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+/// Peak heap growth while running `f`.
+fn peak_growth<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let baseline = ALLOCATOR.current.load(Ordering::SeqCst);
+    ALLOCATOR.peak.store(baseline, Ordering::SeqCst);
+    let value = f();
+
+    (value, ALLOCATOR.peak.load(Ordering::SeqCst) - baseline)
+}
+
+#[test]
+fn inflated_idx_length() -> Result<(), Error> {
+    let fixture = fixture::fixture();
+    let path = fixture.path().join("main_file_cache.idx2");
+    let mut idx = fs::read(&path)?;
+    // archive 10 keeps its sectors but claims the largest length an idx entry can hold.
+    idx[60..63].copy_from_slice(&[0xFF, 0xFF, 0xFF]);
+    fs::write(path, idx)?;
+    let cache = Cache::new(fixture.path())?;
+
+    let (result, growth) = peak_growth(|| cache.read(2, 10));
+
+    match result {
+        Err(Error::Read(ReadError::LengthMismatch {
+            idx: 2,
+            arc: 10,
+            expected: 0xFF_FFFF,
+            actual,
+        })) => assert!(actual > 0 && actual < 0xFF_FFFF),
+        other => panic!("expected a length mismatch, got {:?}", other.map(|buffer| buffer.len())),
+    }
+    // the fixture's data file is a few KB, reserving the idx length would be 16 MB.
+    assert!(growth < 64 * 1024, "read allocated {} bytes", growth);
+
+    Ok(())
+}
@@ -0,0 +1,349 @@
+mod test_util;
+
+use rscache::reference_table::{ArchiveSizes, ReferenceTable, ReferenceTableFlags};
+use runefs::{IndexMetadata, REFERENCE_TABLE_ID};
+use test_util::fixture;
+
+const ALL_FLAGS: [ReferenceTableFlags; 4] = [
+    ReferenceTableFlags::NAMED,
+    ReferenceTableFlags::WHIRLPOOL,
+    ReferenceTableFlags::SIZES,
+    ReferenceTableFlags::HASHES,
+];
+
+/// Two archives (ids 3 and 70) holding files [0, 2] and [5].
+fn encode(protocol: u8, flags: ReferenceTableFlags) -> Vec<u8> {
+    // every id is below 32768, so a protocol 7 smart is encoded as 2 bytes too.
+    let id = |buffer: &mut Vec<u8>, value: u16| buffer.extend(value.to_be_bytes());
+
+    let mut buffer = vec![protocol];
+    if protocol >= 6 {
+        buffer.extend(42_u32.to_be_bytes());
+    }
+    buffer.push(flags.bits());
+    id(&mut buffer, 2);
+    id(&mut buffer, 3);
+    id(&mut buffer, 67);
+    if flags.contains(ReferenceTableFlags::NAMED) {
+        buffer.extend(11_i32.to_be_bytes());
+        buffer.extend(12_i32.to_be_bytes());
+    }
+    buffer.extend(0xDEAD_BEEF_u32.to_be_bytes());
+    buffer.extend(0xCAFE_BABE_u32.to_be_bytes());
+    if flags.contains(ReferenceTableFlags::HASHES) {
+        buffer.extend((-1_i32).to_be_bytes());
+        buffer.extend((-2_i32).to_be_bytes());
+    }
+    if flags.contains(ReferenceTableFlags::WHIRLPOOL) {
+        buffer.extend([1; 64]);
+        buffer.extend([2; 64]);
+    }
+    if flags.contains(ReferenceTableFlags::SIZES) {
+        for size in [100_u32, 200, 300, 400] {
+            buffer.extend(size.to_be_bytes());
+        }
+    }
+    buffer.extend(7_u32.to_be_bytes());
+    buffer.extend(8_u32.to_be_bytes());
+    id(&mut buffer, 2);
+    id(&mut buffer, 1);
+    id(&mut buffer, 0);
+    id(&mut buffer, 2);
+    id(&mut buffer, 5);
+    if flags.contains(ReferenceTableFlags::NAMED) {
+        for hash in [21_i32, 22, 23] {
+            buffer.extend(hash.to_be_bytes());
+        }
+    }
+
+    buffer
+}
+
+#[test]
+fn every_flag_combination() {
+    for protocol in [5, 6, 7] {
+        for bits in 0..16 {
+            let flags = ReferenceTableFlags::from_bits_truncate(bits);
+            let table = ReferenceTable::parse(&encode(protocol, flags)).unwrap();
+
+            assert_eq!(table.protocol, protocol);
+            assert_eq!(table.revision, (protocol >= 6).then_some(42));
+            assert_eq!(table.flags, flags);
+            assert_eq!(table.archives.len(), 2);
+
+            let first = &table.archives[0];
+            let second = &table.archives[1];
+            assert_eq!((first.id, second.id), (3, 70));
+            assert_eq!((first.crc, second.crc), (0xDEAD_BEEF, 0xCAFE_BABE));
+            assert_eq!((first.version, second.version), (7, 8));
+            assert_eq!(first.file_ids, vec![0, 2]);
+            assert_eq!(second.file_ids, vec![5]);
+
+            let named = flags.contains(ReferenceTableFlags::NAMED);
+            assert_eq!(first.name_hash, named.then_some(11));
+            assert_eq!(second.name_hash, named.then_some(12));
+            assert_eq!(first.file_name_hashes, named.then(|| vec![21, 22]));
+            assert_eq!(second.file_name_hashes, named.then(|| vec![23]));
+
+            let hashes = flags.contains(ReferenceTableFlags::HASHES);
+            assert_eq!(first.hash, hashes.then_some(-1));
+            assert_eq!(second.hash, hashes.then_some(-2));
+
+            let whirlpool = flags.contains(ReferenceTableFlags::WHIRLPOOL);
+            assert_eq!(first.whirlpool, whirlpool.then(|| vec![1; 64]));
+            assert_eq!(second.whirlpool, whirlpool.then(|| vec![2; 64]));
+
+            let sizes = flags.contains(ReferenceTableFlags::SIZES);
+            assert_eq!(
+                first.sizes,
+                sizes.then_some(ArchiveSizes {
+                    compressed: 100,
+                    decompressed: 200
+                })
+            );
+            assert_eq!(
+                second.sizes,
+                sizes.then_some(ArchiveSizes {
+                    compressed: 300,
+                    decompressed: 400
+                })
+            );
+        }
+    }
+}
+
+#[test]
+fn encode_round_trip() {
+    for protocol in [5, 6, 7] {
+        for bits in 0..16 {
+            let buffer = encode(protocol, ReferenceTableFlags::from_bits_truncate(bits));
+
+            assert_eq!(ReferenceTable::parse(&buffer).unwrap().encode(), buffer);
+        }
+    }
+
+    // wide ids past i16::MAX take 4 bytes.
+    let mut table = ReferenceTable::parse(&encode(7, ReferenceTableFlags::empty())).unwrap();
+    table.archives[1].id = 70_000;
+    table.archives[1].file_ids = vec![0, 40_000];
+    assert_eq!(ReferenceTable::parse(&table.encode()).unwrap(), table);
+}
+
+#[test]
+fn truncated() {
+    for flags in ALL_FLAGS {
+        let buffer = encode(6, flags);
+
+        assert!(ReferenceTable::parse(&buffer[..buffer.len() - 1]).is_err());
+    }
+    assert!(ReferenceTable::parse(&[]).is_err());
+}
+
+#[test]
+fn non_ascending_ids() {
+    let mut buffer = encode(6, ReferenceTableFlags::empty());
+    let len = buffer.len();
+    // archive 3 holds files [0, 2], a delta of 0 would make them [0, 0].
+    buffer[len - 4..len - 2].copy_from_slice(&0_u16.to_be_bytes());
+    assert!(ReferenceTable::parse(&buffer).is_err());
+
+    let mut buffer = encode(6, ReferenceTableFlags::empty());
+    // archive ids [3, 70] become [3, 3].
+    buffer[10..12].copy_from_slice(&0_u16.to_be_bytes());
+    assert!(ReferenceTable::parse(&buffer).is_err());
+}
+
+#[test]
+fn flags() {
+    let flags = ReferenceTableFlags::NAMED | ReferenceTableFlags::SIZES;
+
+    assert_eq!(flags.bits(), 0b101);
+    assert!(flags.contains(ReferenceTableFlags::NAMED));
+    assert!(!flags.contains(ReferenceTableFlags::WHIRLPOOL));
+    assert!(ReferenceTableFlags::empty().is_empty());
+    assert_eq!(ReferenceTableFlags::from_bits_truncate(0xFF).bits(), 0xF);
+}
+
+#[test]
+fn index_flags() {
+    let cache = fixture::fixture().cache();
+
+    assert_eq!(cache.index_flags(0).unwrap(), ReferenceTableFlags::empty());
+    assert_eq!(cache.index_flags(5).unwrap(), ReferenceTableFlags::NAMED);
+    assert_eq!(cache.index_flags(7).unwrap(), ReferenceTableFlags::empty());
+    assert!(cache.index_flags(11).is_err());
+}
+
+#[test]
+fn osrs_tables() {
+    let cache = test_util::osrs_cache();
+
+    for index_id in 0..=20 {
+        let buffer = cache
+            .read(REFERENCE_TABLE_ID, index_id)
+            .unwrap()
+            .decode()
+            .unwrap();
+        let table = ReferenceTable::parse(&buffer).unwrap();
+        assert_eq!(table.encode(), buffer.as_slice());
+        let metadata = IndexMetadata::from_buffer(buffer).unwrap();
+
+        assert_eq!(cache.index_flags(index_id as u8).unwrap(), table.flags);
+        assert_eq!(table.archives.len(), metadata.iter().count());
+        for (archive, expected) in table.archives.iter().zip(&metadata) {
+            assert_eq!(archive.id, expected.id);
+            assert_eq!(archive.crc, expected.crc);
+            assert_eq!(archive.version, expected.version);
+            assert_eq!(archive.file_ids, expected.valid_ids);
+            assert_eq!(table.archive(archive.id), Some(archive));
+        }
+    }
+}
+
+mod sizes {
+    use super::*;
+    use rscache::{error::ValidateError, Cache, Error};
+    use test_util::fixture::{ArchiveSpec, FixtureBuilder};
+
+    fn sized_fixture(declared_size: Option<u32>) -> fixture::Fixture {
+        let mut archive = ArchiveSpec::new(4, vec![fixture::pattern(600)]);
+        if let Some(size) = declared_size {
+            archive = archive.declared_size(size);
+        }
+
+        FixtureBuilder::new()
+            .index(0, 6)
+            .archive(0, ArchiveSpec::new(1, vec![fixture::pattern(10)]))
+            .index(1, 6)
+            .archive(1, archive)
+            .sizes(1)
+            .build()
+    }
+
+    #[test]
+    fn present() {
+        let fixture = sized_fixture(None);
+        let cache = fixture.cache();
+        let container_len = cache.read(1, 4).unwrap().len() as u32;
+
+        assert!(cache.index_flags(1).unwrap().contains(ReferenceTableFlags::SIZES));
+        assert_eq!(
+            cache.archive_metadata(1, 4).unwrap().sizes,
+            Some(ArchiveSizes {
+                compressed: container_len,
+                decompressed: 600,
+            })
+        );
+    }
+
+    #[test]
+    fn absent() {
+        let fixture = sized_fixture(None);
+        let cache = Cache::builder(fixture.path()).check_sizes(true).open().unwrap();
+
+        assert!(!cache.index_flags(0).unwrap().contains(ReferenceTableFlags::SIZES));
+        assert_eq!(cache.archive_metadata(0, 1).unwrap().sizes, None);
+        assert_eq!(cache.read_decoded(0, 1).unwrap().len(), 10);
+        assert!(cache.archive_metadata(0, 2).is_err());
+    }
+
+    #[test]
+    fn checked_read() {
+        let fixture = sized_fixture(None);
+        let cache = Cache::builder(fixture.path()).check_sizes(true).open().unwrap();
+
+        assert_eq!(
+            cache.read_decoded(1, 4).unwrap().as_slice(),
+            fixture::pattern(600)
+        );
+    }
+
+    #[test]
+    fn mismatch() {
+        let fixture = sized_fixture(Some(601));
+
+        let cache = fixture.cache();
+        assert_eq!(cache.read_decoded(1, 4).unwrap().len(), 600);
+
+        let cache = Cache::builder(fixture.path()).check_sizes(true).open().unwrap();
+        assert!(matches!(
+            cache.read_decoded(1, 4),
+            Err(Error::Validate(ValidateError::InvalidSize {
+                idx: 1,
+                arc: 4,
+                expected: 601,
+                actual: 600,
+            }))
+        ));
+    }
+}
+
+mod versions {
+    use super::*;
+    use rscache::codec;
+    use test_util::fixture::{ArchiveSpec, FixtureBuilder};
+
+    const VERSION: u32 = 0x0003_0007;
+
+    fn versioned(id: u32) -> ArchiveSpec {
+        let mut archive = ArchiveSpec::new(id, vec![fixture::pattern(40)]);
+        archive.revision = VERSION;
+        archive
+    }
+
+    #[test]
+    fn table_versions() {
+        let cache = FixtureBuilder::new()
+            .index(0, 5)
+            .archive(0, versioned(1))
+            .index(1, 6)
+            .archive(1, versioned(1))
+            .build()
+            .cache();
+        let checksum = cache.checksum().unwrap();
+
+        for (index_id, protocol, version) in [(0, 5, 0), (1, 6, 1)] {
+            let buffer = cache.read_decoded(REFERENCE_TABLE_ID, index_id).unwrap();
+            let table = ReferenceTable::parse(&buffer).unwrap();
+
+            assert_eq!(table.protocol, protocol);
+            assert_eq!(table.version(), version);
+            assert_eq!(checksum.iter().nth(index_id as usize).unwrap().version(), version);
+
+            let entry = table.archive(1).unwrap();
+            assert_eq!(entry.version, VERSION);
+            assert_eq!(entry.trailer_version(), 7);
+        }
+    }
+
+    #[test]
+    fn trailer_comparison() {
+        let mut buffer = encode(6, ReferenceTableFlags::empty());
+        // bump the version of archive 3 from 7 to 0x0001_0007.
+        let versions = buffer.len() - 18;
+        buffer[versions..versions + 4].copy_from_slice(&0x0001_0007_u32.to_be_bytes());
+        let entry = ReferenceTable::parse(&buffer).unwrap().archives[0].clone();
+
+        assert_eq!(entry.version, 0x0001_0007);
+        assert!(entry.matches_version(7));
+        assert!(entry.matches_version(0x0001_0007));
+        assert!(entry.matches_version(0x0005_0007));
+        assert!(!entry.matches_version(8));
+    }
+
+    #[test]
+    fn split_trailer() {
+        let container = versioned(1).container();
+        assert_eq!(codec::split_trailer(&container), (container.as_slice(), None));
+
+        let mut trailed = container.clone();
+        trailed.extend(7_u16.to_be_bytes());
+        assert_eq!(
+            codec::split_trailer(&trailed),
+            (container.as_slice(), Some(7))
+        );
+
+        assert_eq!(codec::split_trailer(&[]), (&[][..], None));
+        assert_eq!(codec::split_trailer(&[0, 0]), (&[0_u8, 0][..], None));
+    }
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "image")]
+
+mod test_util;
+
+use std::{fs, io::Read, path::Path};
+
+use flate2::read::ZlibDecoder;
+use rscache::{
+    decryption::DecryptionRegistry,
+    loader::osrs::{
+        LocationLoader, MapLoader, ModelLoader, ObjectLoader, OverlayLoader, UnderlayLoader,
+    },
+    render::{self, Image, SceneLoaders, SceneOptions},
+    Cache,
+};
+
+const REGION_ID: u16 = 12850;
+const KEYS: [u32; 4] = [3030157619, 2364842415, 3297319647, 1973582566];
+const SNAPSHOT: &str = "tests/snapshots/scene_12850.png";
+
+/// Channels of a pixel may differ by this much from the snapshot.
+const CHANNEL_TOLERANCE: u8 = 8;
+
+/// Width, height and RGBA pixels of a PNG written by [`Image::write_png`],
+/// which only writes unfiltered rows.
+fn read_png(path: impl AsRef<Path>) -> (u32, u32, Vec<u8>) {
+    let png = fs::read(path).unwrap();
+    let mut chunks = &png[8..];
+    let (mut width, mut height, mut data) = (0, 0, Vec::new());
+
+    while !chunks.is_empty() {
+        let len = u32::from_be_bytes(chunks[..4].try_into().unwrap()) as usize;
+        let (kind, body) = (&chunks[4..8], &chunks[8..8 + len]);
+        match kind {
+            b"IHDR" => {
+                width = u32::from_be_bytes(body[..4].try_into().unwrap());
+                height = u32::from_be_bytes(body[4..8].try_into().unwrap());
+                assert_eq!(body[8..], [8, 6, 0, 0, 0]);
+            }
+            b"IDAT" => data.extend_from_slice(body),
+            _ => {}
+        }
+        chunks = &chunks[12 + len..];
+    }
+
+    let mut rows = Vec::new();
+    ZlibDecoder::new(data.as_slice())
+        .read_to_end(&mut rows)
+        .unwrap();
+    let pixels = rows
+        .chunks_exact(width as usize * 4 + 1)
+        .flat_map(|row| {
+            assert_eq!(row[0], 0, "filtered row");
+            row[1..].to_vec()
+        })
+        .collect();
+
+    (width, height, pixels)
+}
+
+fn render(options: &SceneOptions) -> Image {
+    let mut registry = DecryptionRegistry::new();
+    registry.insert_region(REGION_ID, KEYS);
+    let cache = Cache::builder("./data/osrs_cache")
+        .decryption(registry)
+        .open()
+        .unwrap();
+
+    let mut loaders = SceneLoaders {
+        maps: &mut MapLoader::new(&cache),
+        locations: &mut LocationLoader::new(&cache),
+        models: &mut ModelLoader::new(&cache),
+        objects: &ObjectLoader::new(&cache).unwrap(),
+        underlays: &UnderlayLoader::new(&cache).unwrap(),
+        overlays: &OverlayLoader::new(&cache).unwrap(),
+    };
+
+    render::scene_region(REGION_ID, &mut loaders, options).unwrap()
+}
+
+#[test]
+fn scene_snapshot() {
+    let image = render(&SceneOptions {
+        width: 96,
+        height: 96,
+        ..SceneOptions::default()
+    });
+
+    // `SNAPSHOTS=overwrite` writes the render as the new snapshot.
+    if std::env::var_os("SNAPSHOTS").is_some_and(|value| value == "overwrite") {
+        image
+            .write_png(fs::File::create(SNAPSHOT).unwrap())
+            .unwrap();
+    }
+
+    let (width, height, pixels) = read_png(SNAPSHOT);
+    assert_eq!((width, height), (image.width(), image.height()));
+    let differing = image
+        .pixels()
+        .chunks_exact(4)
+        .zip(pixels.chunks_exact(4))
+        .filter(|(rendered, snapshot)| {
+            rendered
+                .iter()
+                .zip(*snapshot)
+                .any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    // a few pixels along edges may flip with floating point differences.
+    assert!(
+        differing <= (width * height / 100) as usize,
+        "{} pixels differ from {}",
+        differing,
+        SNAPSHOT
+    );
+}
+
+#[test]
+fn png_round_trip() {
+    let mut image = Image::new(3, 2, [10, 20, 30, 255]);
+    assert_eq!(image.pixel(2, 1), Some([10, 20, 30, 255]));
+    assert_eq!(image.pixel(3, 0), None);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("image.png");
+    image.write_png(fs::File::create(&path).unwrap()).unwrap();
+    assert_eq!(read_png(&path), (3, 2, image.pixels().to_vec()));
+
+    image = Image::new(0, 0, [0; 4]);
+    image.write_png(fs::File::create(&path).unwrap()).unwrap();
+    assert_eq!(read_png(&path), (0, 0, Vec::new()));
+}
+
+#[test]
+fn empty_options() {
+    let image = render(&SceneOptions {
+        width: 0,
+        height: 0,
+        ..SceneOptions::default()
+    });
+
+    assert_eq!(image.pixels(), &[] as &[u8]);
+}
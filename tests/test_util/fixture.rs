@@ -0,0 +1,579 @@
+//! Generates a tiny but structurally complete cache in a temporary directory.
+//!
+//! The default fixture built by [`fixture`] contains:
+//! - index 0 with a raw multi-sector archive (protocol 5).
+//! - index 2 with synthetic item, npc and object config archives (protocol 6).
+//! - index 5 with a named map archive and an XTEA encrypted location archive (protocol 6).
+//! - index 7 with an archive id above `u16::MAX`, forcing expanded sector headers (protocol 7).
+//! - index 10 with a named huffman archive (protocol 6).
+//! - indices 1, 3, 4, 6, 8 and 9 left empty.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use rscache::{util::djd2, Cache};
+use runefs::{
+    codec::{Buffer, Compression, Decoded},
+    MAIN_DATA, REFERENCE_TABLE_ID,
+};
+use tempfile::TempDir;
+
+const SECTOR_SIZE: usize = 520;
+
+pub const LOCATION_KEYS: [u32; 4] = [0x1234_5678, 0x9ABC_DEF0, 0x0FED_CBA9, 0x8765_4321];
+pub const REGION_ID: u16 = 50 << 8 | 50;
+pub const RAW_ARCHIVE_LEN: usize = 3_000;
+pub const EXPANDED_ARCHIVE_ID: u32 = 70_000;
+pub const EXPANDED_ARCHIVE_LEN: usize = 1_200;
+
+/// A generated cache, removed from disk when dropped.
+pub struct Fixture {
+    dir: TempDir,
+}
+
+impl Fixture {
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn cache(&self) -> Cache {
+        Cache::new(self.path()).unwrap()
+    }
+}
+
+/// A single archive (group) as it will be stored in the fixture.
+#[derive(Clone, Debug)]
+pub struct ArchiveSpec {
+    pub id: u32,
+    pub name: Option<String>,
+    pub revision: u32,
+    pub compression: Compression,
+    pub keys: Option<[u32; 4]>,
+    pub file_ids: Vec<u32>,
+    pub files: Vec<Vec<u8>>,
+    /// Names of the files, hashed into the reference table.
+    pub file_names: Option<Vec<String>>,
+    /// Overrides the decompressed size written to reference tables with sizes.
+    pub declared_size: Option<u32>,
+    /// Obfuscates the stored container by xor-ing every byte with this key.
+    pub xor: Option<u8>,
+    /// Number of stripes every file of a multi-file group is split into.
+    pub chunks: u8,
+}
+
+impl ArchiveSpec {
+    pub fn new(id: u32, files: Vec<Vec<u8>>) -> Self {
+        Self {
+            id,
+            name: None,
+            revision: 1,
+            compression: Compression::Gzip,
+            keys: None,
+            file_ids: (0..files.len() as u32).collect(),
+            files,
+            file_names: None,
+            declared_size: None,
+            xor: None,
+            chunks: 1,
+        }
+    }
+
+    /// Assigns (possibly sparse) ids to the files, in ascending order.
+    pub fn file_ids(mut self, file_ids: Vec<u32>) -> Self {
+        assert_eq!(file_ids.len(), self.files.len());
+        self.file_ids = file_ids;
+        self
+    }
+
+    /// Names every file, in file id order.
+    pub fn file_names<T: Into<String>>(mut self, names: Vec<T>) -> Self {
+        assert_eq!(names.len(), self.files.len(), "every file needs a name");
+        self.file_names = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn named<T: Into<String>>(mut self, name: T) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn declared_size(mut self, size: u32) -> Self {
+        self.declared_size = Some(size);
+        self
+    }
+
+    pub fn xor(mut self, key: u8) -> Self {
+        self.xor = Some(key);
+        self
+    }
+
+    /// Stripes every file across `chunks` chunks of the group.
+    pub fn chunks(mut self, chunks: u8) -> Self {
+        assert!(chunks > 0);
+        self.chunks = chunks;
+        self
+    }
+
+    pub fn keys(mut self, keys: [u32; 4]) -> Self {
+        self.keys = Some(keys);
+        self
+    }
+
+    /// The archive exactly as stored in `main_file_cache.dat2`.
+    pub fn container(&self) -> Vec<u8> {
+        let buffer: Buffer<Decoded> = Buffer::from(self.group());
+        let mut container = buffer
+            .with_compression(self.compression)
+            .encode()
+            .unwrap()
+            .finalize();
+
+        if let Some(keys) = &self.keys {
+            encipher(&mut container[5..], keys);
+        }
+        if let Some(key) = self.xor {
+            container.iter_mut().for_each(|byte| *byte ^= key);
+        }
+
+        container
+    }
+
+    /// Packs the files into a file group of `chunks` stripes, a lone file is stored as is.
+    fn group(&self) -> Vec<u8> {
+        if self.files.len() == 1 {
+            return self.files[0].clone();
+        }
+
+        let chunks = self.chunks as usize;
+        let stripe = |file: &[u8], chunk: usize| {
+            let len = file.len().div_ceil(chunks);
+            let start = (chunk * len).min(file.len());
+            start..(start + len).min(file.len())
+        };
+
+        let mut group = Vec::new();
+        for chunk in 0..chunks {
+            for file in &self.files {
+                group.extend_from_slice(&file[stripe(file, chunk)]);
+            }
+        }
+        for chunk in 0..chunks {
+            let mut previous = 0;
+            for file in &self.files {
+                let len = stripe(file, chunk).len() as i32;
+                group.extend((len - previous).to_be_bytes());
+                previous = len;
+            }
+        }
+        group.push(self.chunks);
+
+        group
+    }
+}
+
+#[derive(Clone, Debug)]
+struct IndexSpec {
+    protocol: u8,
+    revision: u32,
+    sizes: bool,
+    archives: BTreeMap<u32, ArchiveSpec>,
+}
+
+/// Lays out indices and archives on disk.
+#[derive(Clone, Debug, Default)]
+pub struct FixtureBuilder {
+    indices: BTreeMap<u8, IndexSpec>,
+}
+
+impl FixtureBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an empty index with a reference table using the given protocol.
+    pub fn index(mut self, id: u8, protocol: u8) -> Self {
+        self.indices.insert(
+            id,
+            IndexSpec {
+                protocol,
+                revision: 1,
+                sizes: false,
+                archives: BTreeMap::new(),
+            },
+        );
+        self
+    }
+
+    /// Writes compressed and decompressed archive sizes into the index's reference table.
+    pub fn sizes(mut self, index_id: u8) -> Self {
+        self.indices
+            .get_mut(&index_id)
+            .expect("index must be added before its sizes")
+            .sizes = true;
+        self
+    }
+
+    /// Sets the revision of the index's reference table, only stored from protocol 6 onwards.
+    pub fn revision(mut self, index_id: u8, revision: u32) -> Self {
+        self.indices
+            .get_mut(&index_id)
+            .expect("index must be added before its revision")
+            .revision = revision;
+        self
+    }
+
+    pub fn archive(mut self, index_id: u8, archive: ArchiveSpec) -> Self {
+        self.indices
+            .get_mut(&index_id)
+            .expect("index must be added before its archives")
+            .archives
+            .insert(archive.id, archive);
+        self
+    }
+
+    pub fn build(self) -> Fixture {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dat2 = vec![0; SECTOR_SIZE];
+        let mut idx255 = Vec::new();
+
+        // every index id up to the highest one gets an idx file, like the real cache.
+        let last = self.indices.keys().last().copied().unwrap_or_default();
+        for index_id in 0..=last {
+            let mut idx = Vec::new();
+            if let Some(index) = self.indices.get(&index_id) {
+                let mut containers = Vec::with_capacity(index.archives.len());
+                for archive in index.archives.values() {
+                    let container = archive.container();
+                    let entry = write_sectors(&mut dat2, index_id, archive.id, &container);
+                    put_entry(&mut idx, archive.id, entry);
+                    containers.push(container);
+                }
+
+                let table = encode_reference_table(index, &containers);
+                let entry = write_sectors(&mut dat2, REFERENCE_TABLE_ID, index_id as u32, &table);
+                put_entry(&mut idx255, index_id as u32, entry);
+            } else {
+                put_entry(&mut idx255, index_id as u32, [0; 6]);
+            }
+
+            fs::write(
+                dir.path().join(format!("main_file_cache.idx{}", index_id)),
+                idx,
+            )
+            .unwrap();
+        }
+
+        fs::write(dir.path().join("main_file_cache.idx255"), idx255).unwrap();
+        fs::write(dir.path().join(MAIN_DATA), dat2).unwrap();
+
+        Fixture { dir }
+    }
+}
+
+/// Builds the default fixture described in the module documentation.
+pub fn fixture() -> Fixture {
+    defaults().build()
+}
+
+/// The builder behind [`fixture`], for fixtures that extend the default layout.
+pub fn defaults() -> FixtureBuilder {
+    FixtureBuilder::new()
+        .index(0, 5)
+        .archive(
+            0,
+            ArchiveSpec::new(1, vec![pattern(RAW_ARCHIVE_LEN)]).compression(Compression::None),
+        )
+        .archive(
+            0,
+            ArchiveSpec::new(2, vec![pattern(100)]).compression(Compression::Bzip2),
+        )
+        .index(1, 5)
+        .index(2, 6)
+        .archive(2, ArchiveSpec::new(6, objects()))
+        .archive(2, ArchiveSpec::new(9, npcs()))
+        .archive(2, ArchiveSpec::new(10, items()))
+        .index(3, 6)
+        .index(4, 6)
+        .index(5, 6)
+        .archive(5, ArchiveSpec::new(0, vec![map()]).named("m50_50"))
+        .archive(
+            5,
+            ArchiveSpec::new(1, vec![locations()])
+                .named("l50_50")
+                .keys(LOCATION_KEYS),
+        )
+        .index(6, 6)
+        .index(7, 7)
+        .archive(
+            7,
+            ArchiveSpec::new(EXPANDED_ARCHIVE_ID, vec![pattern(EXPANDED_ARCHIVE_LEN)])
+                .compression(Compression::None),
+        )
+        .index(8, 6)
+        .index(9, 6)
+        .index(10, 6)
+        .archive(
+            10,
+            ArchiveSpec::new(0, vec![huffman_sizes()])
+                .named("huffman")
+                .compression(Compression::None),
+        )
+}
+
+/// Deterministic filler data, every byte depends on its position.
+pub fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+/// Item definitions 0..=2.
+pub fn items() -> Vec<Vec<u8>> {
+    vec![
+        definition(|buffer| {
+            string(buffer, 2, "Bronze fixture");
+            buffer.push(12);
+            buffer.extend(250_i32.to_be_bytes());
+        }),
+        definition(|buffer| {
+            string(buffer, 2, "Fixture coins");
+            buffer.push(11);
+        }),
+        definition(|buffer| {
+            string(buffer, 2, "Members fixture");
+            buffer.push(16);
+        }),
+    ]
+}
+
+/// Npc definitions 0..=1.
+pub fn npcs() -> Vec<Vec<u8>> {
+    vec![
+        definition(|buffer| {
+            string(buffer, 2, "Fixture guide");
+            buffer.extend([12, 2]);
+        }),
+        definition(|buffer| {
+            string(buffer, 2, "Fixture scenery");
+            buffer.push(107);
+        }),
+    ]
+}
+
+/// Object definitions 0..=1.
+pub fn objects() -> Vec<Vec<u8>> {
+    vec![
+        definition(|buffer| {
+            string(buffer, 2, "Fixture door");
+            buffer.extend([19, 1]);
+        }),
+        definition(|buffer| {
+            string(buffer, 2, "Fixture fountain");
+            buffer.push(24);
+            buffer.extend(1_234_u16.to_be_bytes());
+            buffer.push(74);
+        }),
+    ]
+}
+
+/// A flat region with a single raised tile at (0, 0) on the lowest plane.
+pub fn map() -> Vec<u8> {
+    let mut buffer = vec![1, 20];
+    buffer.resize(1 + 4 * 64 * 64, 0);
+    buffer
+}
+
+/// Two locations: object 1 at local (1, 2) and object 2 at local (3, 4), rotated once.
+pub fn locations() -> Vec<u8> {
+    let mut buffer = Vec::new();
+    // object id 1 with a single location of type 10.
+    buffer.push(2);
+    buffer.extend(smart((1 << 6 | 2) + 1));
+    buffer.extend([10 << 2, 0]);
+    // object id 2 with a single location of type 22.
+    buffer.push(1);
+    buffer.extend(smart((3 << 6 | 4) + 1));
+    buffer.extend([22 << 2 | 1, 0, 0]);
+    buffer
+}
+
+/// A valid huffman size table where every symbol is encoded with 8 bits.
+pub fn huffman_sizes() -> Vec<u8> {
+    vec![8; 256]
+}
+
+fn definition<F: FnOnce(&mut Vec<u8>)>(f: F) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    f(&mut buffer);
+    buffer.push(0);
+    buffer
+}
+
+fn string(buffer: &mut Vec<u8>, opcode: u8, value: &str) {
+    buffer.push(opcode);
+    buffer.extend(value.as_bytes());
+    buffer.push(0);
+}
+
+fn smart(value: u16) -> Vec<u8> {
+    if value < 128 {
+        vec![value as u8]
+    } else {
+        (value | 0x8000).to_be_bytes().to_vec()
+    }
+}
+
+fn smart_u32(buffer: &mut Vec<u8>, value: u32) {
+    if value < 0x8000 {
+        buffer.extend((value as u16).to_be_bytes());
+    } else {
+        buffer.extend((value | 0x8000_0000).to_be_bytes());
+    }
+}
+
+/// XTEA enciphers every whole block, the inverse of `runefs::xtea::decipher`.
+fn encipher(data: &mut [u8], keys: &[u32; 4]) {
+    const RATIO: u32 = 0x9E37_79B9;
+
+    for block in data.chunks_exact_mut(8) {
+        let mut v0 = u32::from_be_bytes([block[0], block[1], block[2], block[3]]);
+        let mut v1 = u32::from_be_bytes([block[4], block[5], block[6], block[7]]);
+        let mut sum = 0_u32;
+        for _ in 0..32 {
+            v0 = v0.wrapping_add(
+                (((v1 << 4) ^ (v1 >> 5)).wrapping_add(v1))
+                    ^ sum.wrapping_add(keys[(sum & 3) as usize]),
+            );
+            sum = sum.wrapping_add(RATIO);
+            v1 = v1.wrapping_add(
+                (((v0 << 4) ^ (v0 >> 5)).wrapping_add(v0))
+                    ^ sum.wrapping_add(keys[((sum >> 11) & 3) as usize]),
+            );
+        }
+        block[..4].copy_from_slice(&v0.to_be_bytes());
+        block[4..].copy_from_slice(&v1.to_be_bytes());
+    }
+}
+
+/// Writes the data as a chain of sectors, returning the idx entry pointing to it.
+fn write_sectors(dat2: &mut Vec<u8>, index_id: u8, archive_id: u32, data: &[u8]) -> [u8; 6] {
+    let expanded = archive_id > u16::MAX as u32;
+    let data_len = if expanded { 510 } else { 512 };
+    let first = dat2.len() / SECTOR_SIZE;
+
+    let chunks: Vec<&[u8]> = data.chunks(data_len).collect();
+    for (chunk, block) in chunks.iter().enumerate() {
+        let sector = first + chunk;
+        let next = if chunk + 1 == chunks.len() {
+            0
+        } else {
+            sector + 1
+        };
+
+        let start = dat2.len();
+        if expanded {
+            dat2.extend(archive_id.to_be_bytes());
+        } else {
+            dat2.extend((archive_id as u16).to_be_bytes());
+        }
+        dat2.extend((chunk as u16).to_be_bytes());
+        dat2.extend(&(next as u32).to_be_bytes()[1..]);
+        dat2.push(index_id);
+        dat2.extend(*block);
+        dat2.resize(start + SECTOR_SIZE, 0);
+    }
+
+    let mut entry = [0; 6];
+    entry[..3].copy_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+    entry[3..].copy_from_slice(&(first as u32).to_be_bytes()[1..]);
+    entry
+}
+
+fn put_entry(idx: &mut Vec<u8>, archive_id: u32, entry: [u8; 6]) {
+    let offset = archive_id as usize * 6;
+    if idx.len() < offset + 6 {
+        idx.resize(offset + 6, 0);
+    }
+    idx[offset..offset + 6].copy_from_slice(&entry);
+}
+
+/// Encodes the reference table of an index, containers are gzip compressed like the real cache.
+fn encode_reference_table(index: &IndexSpec, containers: &[Vec<u8>]) -> Vec<u8> {
+    let named = index
+        .archives
+        .values()
+        .any(|archive| archive.name.is_some() || archive.file_names.is_some());
+    let flags = u8::from(named) | if index.sizes { 4 } else { 0 };
+    let wide = index.protocol >= 7;
+    let mut buffer = vec![index.protocol];
+    if index.protocol >= 6 {
+        buffer.extend(index.revision.to_be_bytes());
+    }
+    buffer.push(flags);
+
+    let count = |buffer: &mut Vec<u8>, value: u32| {
+        if wide {
+            smart_u32(buffer, value);
+        } else {
+            buffer.extend((value as u16).to_be_bytes());
+        }
+    };
+
+    count(&mut buffer, index.archives.len() as u32);
+    let mut previous = 0;
+    for &id in index.archives.keys() {
+        count(&mut buffer, id - previous);
+        previous = id;
+    }
+    if named {
+        for archive in index.archives.values() {
+            let hash = archive.name.as_ref().map_or(0, djd2::hash);
+            buffer.extend(hash.to_be_bytes());
+        }
+    }
+    for container in containers {
+        buffer.extend(crc32fast::hash(container).to_be_bytes());
+    }
+    if index.sizes {
+        for (archive, container) in index.archives.values().zip(containers) {
+            let decompressed = archive
+                .declared_size
+                .unwrap_or(archive.group().len() as u32);
+            buffer.extend((container.len() as u32).to_be_bytes());
+            buffer.extend(decompressed.to_be_bytes());
+        }
+    }
+    for archive in index.archives.values() {
+        buffer.extend(archive.revision.to_be_bytes());
+    }
+    for archive in index.archives.values() {
+        count(&mut buffer, archive.files.len() as u32);
+    }
+    for archive in index.archives.values() {
+        let mut previous = 0;
+        for &file_id in &archive.file_ids {
+            count(&mut buffer, file_id - previous);
+            previous = file_id;
+        }
+    }
+    if named {
+        for archive in index.archives.values() {
+            for position in 0..archive.files.len() {
+                let hash = archive
+                    .file_names
+                    .as_ref()
+                    .map_or(0, |names| djd2::hash(&names[position]));
+                buffer.extend(hash.to_be_bytes());
+            }
+        }
+    }
+
+    let table: Buffer<Decoded> = Buffer::from(buffer);
+    table
+        .with_compression(Compression::Gzip)
+        .encode()
+        .unwrap()
+        .finalize()
+}
@@ -1,11 +1,16 @@
+#[allow(dead_code)]
+pub mod fixture;
+
 use rscache::Cache;
 use sha1_smol::Sha1;
 
+#[allow(dead_code)]
 pub fn osrs_cache() -> Cache {
     Cache::new("./data/osrs_cache").unwrap()
 }
 
 #[cfg(all(test, feature = "rs3"))]
+#[allow(dead_code)]
 pub fn rs3_cache() -> Cache {
     Cache::new("./data/rs3_cache").unwrap()
 }
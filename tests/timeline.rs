@@ -0,0 +1,102 @@
+mod test_util;
+
+use rscache::{
+    loader::osrs::{ItemLoader, NpcLoader},
+    timeline::Timeline,
+};
+use test_util::fixture::{self, ArchiveSpec, Fixture};
+
+/// The default fixture with item 1 renamed to the name of item 0.
+fn changed_item() -> Fixture {
+    let mut items = fixture::items();
+    items[1] = items[0].clone();
+
+    fixture::defaults()
+        .archive(2, ArchiveSpec::new(10, items))
+        .build()
+}
+
+/// The default fixture without item 2.
+fn removed_item() -> Fixture {
+    let mut items = fixture::items();
+    items.pop();
+
+    fixture::defaults()
+        .archive(2, ArchiveSpec::new(10, items))
+        .build()
+}
+
+#[test]
+fn history_shows_the_change() -> Result<(), rscache::Error> {
+    let snapshots = [fixture::fixture(), fixture::fixture(), changed_item(), changed_item()];
+    let timeline = Timeline::new(snapshots.iter().map(|fixture| fixture.path().to_path_buf()));
+    assert_eq!(timeline.len(), 4);
+
+    let history = timeline.definition_history::<ItemLoader>(1)?;
+    let names: Vec<_> = history
+        .iter()
+        .map(|(revision, item)| (*revision, item.as_ref().map(|item| item.name.as_str())))
+        .collect();
+    assert_eq!(names, vec![(0, Some("Fixture coins")), (2, Some("Bronze fixture"))]);
+    assert_eq!(history[1].1.as_ref().unwrap().id, 1);
+
+    // the archive changed, the other items didn't.
+    assert_eq!(timeline.definition_history::<ItemLoader>(0)?.len(), 1);
+    assert_eq!(timeline.definition_history::<ItemLoader>(2)?.len(), 1);
+    assert_eq!(timeline.first_changed(2, 10)?, Some(2));
+
+    // npcs live in another archive.
+    assert_eq!(timeline.definition_history::<NpcLoader>(1)?.len(), 1);
+    assert_eq!(timeline.first_changed(2, 9)?, None);
+
+    Ok(())
+}
+
+#[test]
+fn missing_definitions() -> Result<(), rscache::Error> {
+    let snapshots = [removed_item(), fixture::fixture(), removed_item()];
+    let timeline = Timeline::from_caches(snapshots.iter().map(Fixture::cache));
+
+    let history = timeline.definition_history::<ItemLoader>(2)?;
+    let names: Vec<_> = history
+        .iter()
+        .map(|(revision, item)| (*revision, item.as_ref().map(|item| item.name.as_str())))
+        .collect();
+    assert_eq!(names, vec![(0, None), (1, Some("Members fixture")), (2, None)]);
+
+    let never = timeline.definition_history::<ItemLoader>(100)?;
+    assert_eq!(never.len(), 1);
+    assert!(never[0].1.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn read_at() -> Result<(), rscache::Error> {
+    let (old, new) = (fixture::fixture(), changed_item());
+    let timeline = Timeline::new([old.path(), new.path()]);
+
+    let read = |revision| timeline.read_at(revision, 2, 10).map(|buffer| buffer.finalize());
+    assert_eq!(read(0)?, old.cache().read(2, 10)?.finalize());
+    assert_eq!(read(1)?, new.cache().read(2, 10)?.finalize());
+    assert_ne!(read(0)?, read(1)?);
+    assert_eq!(
+        timeline.read_at(1, 0, 1)?.finalize(),
+        old.cache().read(0, 1)?.finalize()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn opens_lazily() -> Result<(), rscache::Error> {
+    let fixture = fixture::fixture();
+    let timeline = Timeline::new([fixture.path().to_path_buf(), fixture.path().join("missing")]);
+
+    assert!(timeline.read_at(0, 0, 1).is_ok());
+    assert!(timeline.cache(1).is_err());
+    assert!(timeline.first_changed(0, 1).is_err());
+    assert_eq!(Timeline::default().first_changed(0, 1)?, None);
+
+    Ok(())
+}
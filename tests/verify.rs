@@ -0,0 +1,143 @@
+mod test_util;
+
+use std::{fs, path::Path};
+
+use rscache::{
+    verify::{ArchiveProblem, Problem, VerifyOptions},
+    Cache,
+};
+use runefs::{IDX_PREFIX, MAIN_DATA, SECTOR_SIZE};
+use test_util::fixture;
+
+fn patch(path: &Path, offset: usize, bytes: &[u8]) {
+    let mut data = fs::read(path).unwrap();
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    fs::write(path, data).unwrap();
+}
+
+fn flip(path: &Path, offset: usize) {
+    let mut data = fs::read(path).unwrap();
+    data[offset] ^= 0xFF;
+    fs::write(path, data).unwrap();
+}
+
+fn problem(index_id: u8, archive_id: u32, problem: Problem) -> ArchiveProblem {
+    ArchiveProblem {
+        index_id,
+        archive_id,
+        problem,
+    }
+}
+
+#[test]
+fn intact_cache() -> Result<(), rscache::Error> {
+    let cache = fixture::fixture().cache();
+    let stored: usize = (0..=10)
+        .chain([255])
+        .filter_map(|index_id| cache.archive_count(index_id))
+        .sum();
+
+    let report = cache.verify_with(VerifyOptions::new().crcs(true))?;
+    assert!(report.is_ok(), "{:?}", report.problems);
+    assert_eq!(report.archives, stored);
+
+    Ok(())
+}
+
+#[test]
+fn reports_every_problem() -> Result<(), rscache::Error> {
+    let fixture = fixture::fixture();
+    let path = fixture.path();
+    let cache = fixture.cache();
+    let raw = cache.archive_placement(0, 1)?;
+    let objects = cache.archive_placement(2, 6)?;
+    let items = cache.archive_placement(2, 10)?;
+    drop(cache);
+    let idx = |index_id: u8| path.join(format!("{}{}", IDX_PREFIX, index_id));
+
+    // the second sector of archive 0/1 claims to hold chunk 5.
+    patch(
+        &path.join(MAIN_DATA),
+        raw.sectors[1] as usize * SECTOR_SIZE + 2,
+        &[0, 5],
+    );
+    // a data byte of archive 2/10, its sectors are intact.
+    flip(
+        &path.join(MAIN_DATA),
+        items.sector as usize * SECTOR_SIZE + 20,
+    );
+    // the idx entry of archive 2/6 lists more bytes than its chain holds.
+    let len = (objects.length as u32 + 600).to_be_bytes();
+    patch(&idx(2), 6 * 6, &len[1..]);
+    // the idx entry of archive 2/9 is gone.
+    patch(&idx(2), 9 * 6, &[0; 6]);
+
+    let cache = Cache::new(path)?;
+    let report = cache.verify()?;
+    let short = Problem::LengthMismatch {
+        expected: objects.length + 600,
+        actual: objects.sectors.len() * objects.data_len(),
+    };
+
+    assert!(cache.read(2, 6).is_err());
+    assert_eq!(
+        report.problems,
+        [
+            problem(
+                0,
+                1,
+                Problem::BadSector {
+                    sector: raw.sectors[1] as usize,
+                    chunk: 1
+                }
+            ),
+            problem(2, 6, short),
+            problem(2, 9, Problem::Missing),
+        ]
+    );
+    assert_eq!(report.broken_indices(), [0, 2]);
+
+    let report = cache.verify_with(VerifyOptions::new().crcs(true))?;
+    assert!(report.problems.contains(&problem(
+        2,
+        10,
+        Problem::CrcMismatch {
+            expected: cache.archive_metadata(2, 10)?.crc,
+            actual: crc32fast::hash(&cache.read_raw(2, 10)?),
+        }
+    )));
+    assert_eq!(report.problems.len(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn unreadable_reference_table() -> Result<(), rscache::Error> {
+    let fixture = fixture::fixture();
+    let path = fixture.path();
+    let table = fixture.cache().archive_placement(255, 2)?;
+
+    // the table of index 2 is left in zeroed sectors, as some tools do.
+    patch(
+        &path.join(MAIN_DATA),
+        table.sector as usize * SECTOR_SIZE,
+        &[0; SECTOR_SIZE],
+    );
+
+    let cache = Cache::new(path)?;
+    let report = cache.verify_with(VerifyOptions::new().crcs(true))?;
+    let unreadable: Vec<&ArchiveProblem> = report
+        .problems
+        .iter()
+        .filter(|problem| matches!(problem.problem, Problem::ReferenceTable(_)))
+        .collect();
+
+    assert_eq!(unreadable.len(), 1);
+    assert_eq!((unreadable[0].index_id, unreadable[0].archive_id), (255, 2));
+    assert!(unreadable[0]
+        .to_string()
+        .starts_with("archive 2 in index 255: reference table can't be read"));
+    assert_eq!(report.broken_indices(), [255]);
+
+    Ok(())
+}
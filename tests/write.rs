@@ -0,0 +1,200 @@
+mod test_util;
+
+use std::{fs, io};
+
+use rscache::{
+    codec::{self, Compression, CompressionPolicy},
+    prune::{self, PruneSpec},
+    Cache, Error,
+};
+use runefs::{error::ReadError, MAIN_DATA};
+use test_util::fixture::{self, ArchiveSpec, FixtureBuilder};
+
+fn io_error(result: Result<(), Error>, kind: io::ErrorKind) -> bool {
+    matches!(result, Err(Error::Io(error)) if error.kind() == kind)
+}
+
+/// Checks every crc of the index against its reference table.
+fn verify(cache: &Cache, index_id: u8) -> Result<(), Error> {
+    let dir = tempfile::tempdir()?;
+    prune::prune(cache, dir.path(), &PruneSpec::new().index(index_id))?;
+
+    Ok(())
+}
+
+#[test]
+fn replaces_in_place() -> Result<(), Error> {
+    let fixture = fixture::fixture();
+    let mut cache = Cache::open_rw(fixture.path())?;
+    let sector = cache.archive_ref(2, 10)?.sector;
+    let revision = cache.metadata_of(2)?.table.revision;
+    let len = fs::metadata(fixture.path().join(MAIN_DATA))?.len();
+
+    cache.write(2, 10, b"renamed items")?;
+    assert_eq!(cache.read_decoded(2, 10)?.as_slice(), b"renamed items");
+    assert_eq!(cache.archive_ref(2, 10)?.sector, sector);
+    assert_eq!(fs::metadata(fixture.path().join(MAIN_DATA))?.len(), len);
+
+    let reopened = Cache::new(fixture.path())?;
+    assert_eq!(reopened.read_decoded(2, 10)?.as_slice(), b"renamed items");
+    assert_eq!(reopened.archive_metadata(2, 10)?.version, 2);
+    assert_eq!(reopened.archive_metadata(2, 10)?.file_ids, [0, 1, 2]);
+    assert_eq!(
+        reopened.metadata_of(2)?.table.revision,
+        revision.map(|revision| revision + 1)
+    );
+    // the other archives are untouched.
+    assert_eq!(
+        reopened.read_raw(2, 9)?,
+        fixture::fixture().cache().read_raw(2, 9)?
+    );
+    verify(&reopened, 2)?;
+
+    Ok(())
+}
+
+#[test]
+fn appends_when_it_grows() -> Result<(), Error> {
+    let fixture = fixture::fixture();
+    let mut cache = Cache::open_rw(fixture.path())?;
+    let data = fixture::pattern(5000);
+    let len = fs::metadata(fixture.path().join(MAIN_DATA))?.len();
+
+    cache.write(0, 2, &data)?;
+    assert!(fs::metadata(fixture.path().join(MAIN_DATA))?.len() > len);
+
+    let reopened = Cache::new(fixture.path())?;
+    assert_eq!(reopened.read_decoded(0, 2)?.as_slice(), data.as_slice());
+    assert_eq!(
+        reopened.read_decoded(0, 1)?.as_slice(),
+        fixture::fixture().cache().read_decoded(0, 1)?.as_slice()
+    );
+    verify(&reopened, 0)?;
+
+    Ok(())
+}
+
+#[test]
+fn adds_archives() -> Result<(), Error> {
+    let fixture = fixture::defaults()
+        .sizes(2)
+        .archive(7, ArchiveSpec::new(70_000, vec![fixture::pattern(100)]))
+        .build();
+    let mut cache = Cache::open_rw(fixture.path())?;
+
+    cache.write(2, 20, b"new configs")?;
+    cache.write(7, 70_001, &fixture::pattern(2000))?;
+
+    let reopened = Cache::new(fixture.path())?;
+    assert_eq!(reopened.read_file(2, 20, 0)?, b"new configs");
+    let archive = reopened.archive_metadata(2, 20)?;
+    assert_eq!(archive.version, 1);
+    assert_eq!(archive.sizes.map(|sizes| sizes.decompressed), Some(11));
+    assert_eq!(
+        reopened.read_decoded(7, 70_001)?.as_slice(),
+        fixture::pattern(2000).as_slice()
+    );
+    assert_eq!(
+        reopened.read_decoded(7, 70_000)?.as_slice(),
+        fixture::pattern(100).as_slice()
+    );
+    verify(&reopened, 2)?;
+    verify(&reopened, 7)?;
+
+    Ok(())
+}
+
+#[test]
+fn compression_policy() -> Result<(), Error> {
+    let fixture = fixture::defaults().sizes(2).build();
+    let mut cache = Cache::open_rw(fixture.path())?;
+    let policy = CompressionPolicy::osrs()
+        .with_archive(2, 10, Compression::None)
+        .with_index(255, Compression::Bzip2);
+
+    cache.write_with_policy(2, 10, b"renamed items", &policy)?;
+    cache.write_with_policy(2, 20, b"new configs", &policy)?;
+
+    let reopened = Cache::builder(fixture.path()).check_sizes(true).open()?;
+    assert_eq!(reopened.read_decoded(2, 10)?.as_slice(), b"renamed items");
+    assert_eq!(reopened.read_decoded(2, 20)?.as_slice(), b"new configs");
+    assert_eq!(reopened.read(2, 10)?[0], Compression::None as u8);
+    assert_eq!(reopened.read(2, 20)?[0], Compression::Gzip as u8);
+    assert_eq!(reopened.read(255, 2)?[0], Compression::Bzip2 as u8);
+    verify(&reopened, 2)?;
+
+    // plain writes keep the compressions chosen before.
+    cache.write(2, 10, b"items")?;
+    let reopened = Cache::builder(fixture.path()).check_sizes(true).open()?;
+    assert_eq!(reopened.read_decoded(2, 10)?.as_slice(), b"items");
+    assert_eq!(reopened.read(2, 10)?[0], Compression::None as u8);
+    assert_eq!(reopened.read(255, 2)?[0], Compression::Bzip2 as u8);
+
+    Ok(())
+}
+
+#[test]
+fn write_transforms() -> Result<(), Error> {
+    const KEY: u8 = 0x5A;
+    let xor = |_, _, mut data: Vec<u8>| -> Result<Vec<u8>, Error> {
+        data.iter_mut().for_each(|byte| *byte ^= KEY);
+        Ok(data)
+    };
+    let fixture = FixtureBuilder::new()
+        .index(2, 6)
+        .archive(2, ArchiveSpec::new(10, fixture::items()).xor(KEY))
+        .build();
+    let mut cache = Cache::builder(fixture.path())
+        .writable(true)
+        .with_read_transform(xor)
+        .with_write_transform(|_, _, data| {
+            assert_eq!(codec::split_trailer(&data).1, Some(2));
+            Ok(data)
+        })
+        .with_write_transform(xor)
+        .open()?;
+
+    cache.write(2, 10, b"renamed items")?;
+
+    let reopened = Cache::builder(fixture.path())
+        .with_read_transform(xor)
+        .open()?;
+    assert_eq!(reopened.read_decoded(2, 10)?.as_slice(), b"renamed items");
+    assert_eq!(reopened.read(2, 10)?[0], Compression::Gzip as u8);
+    assert!(Cache::new(fixture.path())?.read_decoded(2, 10).is_err());
+
+    let stored = reopened.read_raw(2, 10)?;
+    assert_eq!(
+        crc32fast::hash(codec::split_trailer(&stored).0),
+        reopened.archive_metadata(2, 10)?.crc
+    );
+
+    Ok(())
+}
+
+#[test]
+fn read_only_and_unsupported() {
+    let fixture = fixture::fixture();
+
+    let mut cache = Cache::new(fixture.path()).unwrap();
+    assert!(io_error(
+        cache.write(2, 10, b""),
+        io::ErrorKind::PermissionDenied
+    ));
+
+    let mut cache = Cache::open_rw(fixture.path()).unwrap();
+    assert!(io_error(
+        cache.write(255, 2, b""),
+        io::ErrorKind::Unsupported
+    ));
+    assert!(matches!(
+        cache.write(42, 0, b""),
+        Err(Error::RuneFs(runefs::Error::Read(
+            ReadError::IndexNotFound(42)
+        )))
+    ));
+    assert_eq!(
+        cache.read_raw(2, 10).unwrap(),
+        fixture::fixture().cache().read_raw(2, 10).unwrap()
+    );
+}